@@ -4,28 +4,34 @@ use std::fs;
 use std::path::Path;
 
 fn main() -> Result<(), Box<dyn Error>> {
-    // Define the Arkade Script files to compile
+    // Define the Arkade Script files to compile, and the .hack files of
+    // those that have one
     let files = vec![
-        ("single_sig.ark", "single_sig.json", "single_sig.hack"),
-        ("htlc.ark", "htlc.json", "htlc.hack"),
+        ("single_sig.ark", "single_sig.json", Some("single_sig.hack")),
+        ("htlc.ark", "htlc.json", Some("htlc.hack")),
         (
             "arkade_kitties.ark",
             "arkade_kitties.json",
-            "arkade_kitties.hack",
+            Some("arkade_kitties.hack"),
         ),
         (
             "non_interactive_swap.ark",
             "non_interactive_swap.json",
-            "non_interactive_swap.hack",
+            Some("non_interactive_swap.hack"),
         ),
+        (
+            "threshold_multisig_htlc.ark",
+            "threshold_multisig_htlc.json",
+            Some("threshold_multisig_htlc.hack"),
+        ),
+        ("fuji_safe.ark", "fuji_safe.json", None),
+        ("nft_mint.ark", "nft_mint.json", None),
+        ("payment_auth.ark", "payment_auth.json", None),
     ];
 
     // Compile each file
     for (input_file, output_file, hack_file) in files {
-        println!(
-            "Compiling {} to {} and {}...",
-            input_file, output_file, hack_file
-        );
+        println!("Compiling {} to {}...", input_file, output_file);
 
         // Read the input file
         let input_path = Path::new("examples").join(input_file);
@@ -40,6 +46,11 @@ fn main() -> Result<(), Box<dyn Error>> {
         fs::write(&output_path, &json)?;
 
         // Generate .hack file with opcodes for Bitcoin Script editors
+        let Some(hack_file) = hack_file else {
+            println!("Successfully compiled {} to {}", input_file, output_file);
+            println!("\n-----------------------------------\n");
+            continue;
+        };
         let mut hack_content = String::new();
         hack_content.push_str(&format!("# {}\n", output.name));
         hack_content.push_str("#\n");
@@ -56,7 +67,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             for opcode in &func.asm {
                 hack_content.push_str(&format!("{}\n", opcode));
             }
-            hack_content.push_str("\n");
+            hack_content.push('\n');
         }
 
         let hack_path = Path::new("examples").join(hack_file);
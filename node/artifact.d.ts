@@ -0,0 +1,208 @@
+// Shapes of the values exchanged with the addon; they match the WASM
+// package's declarations.
+
+/** A constructor parameter or function input */
+export interface Parameter {
+  name: string;
+  /** Arkade Script type, e.g. `"pubkey"`, `"bytes32"` or `"int"` */
+  type: string;
+}
+
+/** A value the spender supplies in the witness */
+export interface WitnessElement {
+  /** Matches a `<name>` placeholder in `asm` */
+  name: string;
+  type: string;
+  encoding: "xonly-32" | "schnorr-64" | "schnorr-sighash" | "raw" | "raw-20" | "raw-32" | "scriptnum" | "le64" | "le32" | "array" | "unknown";
+  /** Sighash flag the signature must be made with; the script appends it */
+  sighash?: number;
+}
+
+/** One item of the witness stack, as `witnessTemplate` lists them */
+export interface WitnessItem {
+  /** Position in the witness, 0 at the bottom of the stack */
+  index: number;
+  /** The `witnessSchema` element the item carries */
+  name: string;
+  role: "signature" | "serverSignature" | "preimage" | "index" | "value";
+  type: string;
+  encoding: WitnessElement["encoding"];
+  sighash?: number;
+}
+
+/** What an `asm` token is; sizes are stack bytes, given when the type fixes them */
+export type OperationData =
+  | { kind: "opcode" }
+  | { kind: "push"; hex: string; size: number }
+  | { kind: "constructorParam"; name: string; type: string; size?: number }
+  | { kind: "functionInput"; name: string; type: string; size?: number }
+  | { kind: "runtime"; name: string; size: number }
+  | { kind: "local"; name: string };
+
+/** One `asm` token and what it stands for */
+export interface Operation {
+  op: string;
+  data: OperationData;
+}
+
+export interface RequireStatement {
+  type: string;
+  message?: string;
+}
+
+/** Estimated witness of a script-path spend, in bytes */
+export interface WitnessSize {
+  stack: number;
+  script: number;
+  controlBlock: number;
+  total: number;
+}
+
+/** What spending a leaf costs before the contract is instantiated */
+export interface SizeEstimate {
+  /** Leaf script bytes, with constructor arguments at their usual size */
+  script: number;
+  /** Whole witness bytes at their largest */
+  witness: number;
+  /** Weight units the spending input adds: 4 per non-witness byte, 1 per witness byte */
+  weight: number;
+}
+
+/** One tapscript leaf: a function's cooperative or exit variant */
+export interface AbiFunction {
+  name: string;
+  functionInputs: Parameter[];
+  witnessSchema: WitnessElement[];
+  /** The witness stack to build, bottom first */
+  witnessTemplate: WitnessItem[];
+  /** `true` for the cooperative path, `false` for the unilateral exit */
+  serverVariant: boolean;
+  require: RequireStatement[];
+  /** Script assembly; `<name>` tokens are placeholders */
+  asm: string[];
+  /** Each `asm` token, described */
+  operations: Operation[];
+  witnessSize: WitnessSize;
+  sizeEstimate: SizeEstimate;
+  /** First 4 bytes, hex, of SHA-256 of `name(type,...):server` or `:exit` */
+  selector: string;
+  /** Position of the leaf in the script tree */
+  leafIndex: number;
+  /** The leaf's place in the taproot output, set by linking with an address */
+  proof?: LeafProof;
+  /** Serialized leaf script, hex, without the witness; set once linked */
+  scriptHex?: string;
+  /** Events a spend emits, each field given by an input's name */
+  emits?: { event: string; args: string[] }[];
+}
+
+/** What a control block needs besides the internal key */
+export interface LeafProof {
+  leafHash: string;
+  /** Sibling hashes, hex, from the leaf up to the root */
+  merklePath: string[];
+  /** Whether the output key's y coordinate is odd */
+  parity: boolean;
+  /** Leaf version and parity, internal key, then `merklePath`, hex */
+  controlBlock: string;
+}
+
+/** The script tree of a linked contract's output and the keys it joins */
+export interface TaprootTree {
+  /** x-only, hex */
+  internalKey: string;
+  /** x-only, hex: the internal key tweaked by `merkleRoot` */
+  outputKey: string;
+  parity: boolean;
+  merkleRoot: string;
+  root: TapNode;
+}
+
+/** A leaf, by its place in `functions`, or a branch of two subtrees */
+export type TapNode =
+  | { leafIndex: number; hash: string }
+  | { hash: string; left: TapNode; right: TapNode };
+
+/** A leaf of a spend path, by its place in `functions` and the tree */
+export interface PathLeaf {
+  leafIndex: number;
+  selector: string;
+}
+
+/** Every way a contract's output can be spent */
+export interface SpendGraph {
+  keyPath: {
+    /** x-only, hex, or a `<name>` placeholder until linked */
+    internalKey: string;
+    /** `false` for the NUMS point and keys derived from it */
+    spendable: boolean;
+  };
+  /** Each public function's cooperative and exit leaves */
+  paths: { function: string; server: PathLeaf; exit: PathLeaf }[];
+  /** Renewal timelock in blocks, from the `renew` option */
+  renew?: number;
+  /** `tr()` descriptor of the whole output, once linked */
+  descriptor?: string;
+}
+
+/** An event declared for indexers; it does not affect the script */
+export interface AbiEvent {
+  name: string;
+  inputs: Parameter[];
+}
+
+export interface OptimizationReport {
+  function: string;
+  serverVariant: boolean;
+  sizeBefore: number;
+  sizeAfter: number;
+}
+
+export interface LeafMetrics {
+  function: string;
+  serverVariant: boolean;
+  sigops: number;
+  introspectionOps: number;
+  arithmetic64Ops: number;
+}
+
+/** A compiled contract, as written by `arkadec` */
+export interface ArkadeArtifact {
+  contractName: string;
+  constructorInputs: Parameter[];
+  functions: AbiFunction[];
+  exitDelay: number;
+  source?: string;
+  /** SHA-256 of `source`, hex, when the source is normalized */
+  sourceHash?: string;
+  /** SHA-256, hex, of the name, constructor inputs and every variant's `asm` */
+  artifactHash: string;
+  compiler?: { name: string; version: string };
+  updatedAt?: string;
+  warnings?: string[];
+  optimizations?: OptimizationReport[];
+  metrics?: LeafMetrics[];
+  generics?: Record<string, number>;
+  address?: string;
+  descriptor?: string;
+  internalKey?: string;
+  edition?: "2024" | "2025";
+  events?: AbiEvent[];
+  spendGraph?: SpendGraph;
+  /** Set by linking with an address */
+  taproot?: TaprootTree;
+}
+
+/** A problem reported by `validate` */
+export interface Diagnostic {
+  severity: "error" | "warning";
+  /** `"parse"`, `"type"`, `"compile"` or an analysis code such as `"unsat"` */
+  code: string;
+  /** Stable code such as `"E0102"`, explained by `arkadec --explain` */
+  id: string;
+  message: string;
+  line?: number;
+  column?: number;
+  endLine?: number;
+  endColumn?: number;
+}
@@ -0,0 +1,74 @@
+//! Node.js bindings for the Arkade Script compiler
+//!
+//! N-API bindings so server-side JavaScript can call the compiler natively,
+//! without the WASM module's startup and copy overhead.
+
+use std::collections::HashMap;
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use arkade_compiler::{diagnostics, linker, ContractJson};
+
+/// Compile Arkade Script source code to an artifact
+///
+/// # Arguments
+/// * `source` - The Arkade Script source code
+///
+/// # Returns
+/// The artifact as an `ArkadeArtifact` object, or throws with the parse or
+/// compile error
+#[napi(ts_return_type = "ArkadeArtifact")]
+pub fn compile(source: String) -> Result<serde_json::Value> {
+    let artifact = arkade_compiler::compile(&source).map_err(|e| invalid(e.to_string()))?;
+    to_json(&artifact)
+}
+
+/// Check Arkade Script source code without generating output
+///
+/// # Arguments
+/// * `source` - The Arkade Script source code
+///
+/// # Returns
+/// Every diagnostic found. The source is valid if none is an error.
+#[napi(ts_return_type = "Diagnostic[]")]
+pub fn validate(source: String) -> Result<serde_json::Value> {
+    to_json(&diagnostics::check(&source))
+}
+
+/// Link constructor arguments into an artifact
+///
+/// # Arguments
+/// * `artifact` - An artifact returned by `compile`
+/// * `args` - Hex or decimal value of every constructor input, and of
+///   `SERVER_KEY` to link the server key as well
+///
+/// # Returns
+/// The artifact with concrete values in its scripts
+#[napi(
+    ts_args_type = "artifact: ArkadeArtifact, args: Record<string, string>",
+    ts_return_type = "ArkadeArtifact"
+)]
+pub fn link(
+    artifact: serde_json::Value,
+    args: HashMap<String, String>,
+) -> Result<serde_json::Value> {
+    let artifact: ContractJson = serde_json::from_value(artifact)
+        .map_err(|e| invalid(format!("Invalid artifact: {}", e)))?;
+    let linked = linker::link(&artifact, &args).map_err(invalid)?;
+    to_json(&linked)
+}
+
+/// Get the compiler version
+#[napi]
+pub fn version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<serde_json::Value> {
+    serde_json::to_value(value).map_err(|e| Error::from_reason(e.to_string()))
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(Status::InvalidArg, message)
+}
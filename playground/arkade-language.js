@@ -6,7 +6,7 @@ const arkadeMonarch = {
 
     keywords: [
         'contract', 'function', 'options', 'require', 'if', 'else',
-        'for', 'in', 'let', 'internal', 'new'
+        'for', 'in', 'let', 'internal', 'macro', 'new', 'between', 'and'
     ],
 
     typeKeywords: [
@@ -17,7 +17,8 @@ const arkadeMonarch = {
     builtinFunctions: [
         'checkSig', 'checkMultisig', 'checkSigFromStack', 'checkSigFromStackVerify',
         'sha256', 'sha256Initialize', 'sha256Update', 'sha256Finalize',
-        'neg64', 'le64ToScriptNum', 'le32ToLe64', 'ecMulScalarVerify', 'tweakVerify'
+        'neg64', 'le64ToScriptNum', 'le32ToLe64', 'ecMulScalarVerify', 'tweakVerify',
+        'size'
     ],
 
     operators: [
@@ -34,13 +35,13 @@ const arkadeMonarch = {
             [/\s+/, 'white'],
 
             // Keywords
-            [/\b(contract|function|options|require|if|else|for|in|let|internal|new)\b/, 'keyword'],
+            [/\b(contract|function|options|require|if|else|for|in|let|internal|macro|new|between|and)\b/, 'keyword'],
 
             // Types
             [/\b(pubkey|signature|bytes32|bytes20|bytes|asset|int|bool)\b/, 'type'],
 
             // Built-in functions
-            [/\b(checkSig|checkMultisig|checkSigFromStack|checkSigFromStackVerify|sha256|sha256Initialize|sha256Update|sha256Finalize|neg64|le64ToScriptNum|le32ToLe64|ecMulScalarVerify|tweakVerify)\b/, 'predefined'],
+            [/\b(checkSig|checkMultisig|checkSigFromStack|checkSigFromStackVerify|sha256|sha256Initialize|sha256Update|sha256Finalize|neg64|le64ToScriptNum|le32ToLe64|ecMulScalarVerify|tweakVerify|size)\b/, 'predefined'],
 
             // Transaction/this keywords
             [/\b(tx|this)\b/, 'variable.predefined'],
@@ -135,6 +136,7 @@ const arkadeCompletions = [
     { label: 'for', kind: 'Keyword', insertText: 'for (${1:i}, ${2:item}) in ${3:array} {\n\t$0\n}', insertTextRules: 4 },
     { label: 'let', kind: 'Keyword', insertText: 'let ${1:name} = ${2:value};', insertTextRules: 4 },
     { label: 'internal', kind: 'Keyword', insertText: 'internal' },
+    { label: 'macro', kind: 'Keyword', insertText: 'macro ${1:name}(${2:params}) {\n\t$0\n}', insertTextRules: 4 },
 
     // Types
     { label: 'pubkey', kind: 'TypeParameter', insertText: 'pubkey' },
@@ -151,6 +153,7 @@ const arkadeCompletions = [
     { label: 'checkMultisig', kind: 'Function', insertText: 'checkMultisig([${1:sigs}], [${2:pubkeys}])', insertTextRules: 4, detail: 'Verify multiple signatures' },
     { label: 'checkSigFromStack', kind: 'Function', insertText: 'checkSigFromStack(${1:sig}, ${2:pubkey}, ${3:msg})', insertTextRules: 4, detail: 'Verify signature from stack' },
     { label: 'sha256', kind: 'Function', insertText: 'sha256(${1:data})', insertTextRules: 4, detail: 'SHA256 hash' },
+    { label: 'size', kind: 'Function', insertText: 'size(${1:data}) between ${2:min} and ${3:max}', insertTextRules: 4, detail: 'Bound the size of data in bytes' },
 
     // Transaction introspection
     { label: 'tx.time', kind: 'Property', insertText: 'tx.time', detail: 'Transaction locktime' },
@@ -34,6 +34,7 @@ let openTabs = [];
 let fileContents = {}; // Cache of file contents for each open file
 let expandedFolders = new Set(); // Track which folders are expanded
 let lastCompiledSource = null; // Source that produced the current output
+let validateTimer = null; // Pending debounced validation
 
 // ── localStorage persistence ──────────────────────────────────────
 const STORAGE_KEY = 'arkade-playground';
@@ -603,6 +604,7 @@ async function initCompiler() {
 
         // Show button as ready to compile
         markDirty();
+        runValidate();
     } catch (err) {
         console.error('Failed to initialize WASM:', err);
         showError('Failed to load compiler. Make sure the WASM module is built.');
@@ -1055,11 +1057,12 @@ function initMonaco() {
             doCompile();
         });
 
-        // Mark dirty on change — no auto-compile
+        // Mark dirty on change — no auto-compile, but validate as the user types
         editor.onDidChangeModelContent(() => {
             if (editor.getValue() !== lastCompiledSource) {
                 markDirty();
             }
+            scheduleValidate();
         });
 
         // Load shared contract from URL hash if present
@@ -1078,6 +1081,39 @@ function initMonaco() {
     });
 }
 
+// Validate shortly after the user stops typing
+function scheduleValidate() {
+    clearTimeout(validateTimer);
+    validateTimer = setTimeout(runValidate, 300);
+}
+
+// Show errors and warnings from validate() as editor markers
+function runValidate() {
+    if (!wasmReady || !editor) return;
+
+    let diagnostics = [];
+    try {
+        diagnostics = validate(editor.getValue());
+    } catch (err) {
+        console.error('Validation failed:', err);
+    }
+
+    const model = editor.getModel();
+    const markers = diagnostics.map(d => {
+        // Diagnostics without a location mark the first line
+        const line = d.line || 1;
+        return {
+            severity: d.severity === 'error' ? monaco.MarkerSeverity.Error : monaco.MarkerSeverity.Warning,
+            message: `${d.message} [${d.code}]`,
+            startLineNumber: line,
+            startColumn: d.column || 1,
+            endLineNumber: d.endLine || line,
+            endColumn: d.endColumn || model.getLineMaxColumn(line),
+        };
+    });
+    monaco.editor.setModelMarkers(model, 'arkade', markers);
+}
+
 // Mark the editor as having uncompiled changes
 function markDirty() {
     const btn = document.getElementById('compile-btn');
@@ -1106,14 +1142,14 @@ function doCompile() {
     clearErrors();
 
     try {
-        const result = compile(source);
+        const result = JSON.stringify(compile(source), null, 2);
         lastCompiledSource = source;
         displayJson(result);
         displayAsm(result);
         showSuccess(result);
         markCompiled();
     } catch (err) {
-        showError(err.toString());
+        showError(err.toString(), err.line);
     }
 }
 
@@ -1207,8 +1243,8 @@ function showSuccess(jsonStr) {
     statusEl.className = 'compile-status success';
 }
 
-// Show error
-function showError(message) {
+// Show error, highlighting `line` (or a line named in the message)
+function showError(message, line) {
     const statusEl = document.getElementById('compile-status');
     statusEl.innerHTML = `<i class="fas fa-times-circle"></i> Error`;
     statusEl.className = 'compile-status error';
@@ -1225,8 +1261,8 @@ function showError(message) {
 
     // Highlight line if possible
     const lineMatch = message.match(/line (\d+)/i);
-    if (lineMatch && editor) {
-        const lineNumber = parseInt(lineMatch[1], 10);
+    const lineNumber = line || (lineMatch && parseInt(lineMatch[1], 10));
+    if (lineNumber && editor) {
         editor.revealLineInCenter(lineNumber);
         editor.setSelection({
             startLineNumber: lineNumber,
@@ -0,0 +1,502 @@
+//! Path satisfiability.
+//!
+//! Collects the numeric, hash and time constraints along every path through
+//! a function and feeds them to a small solver that tracks, per term, an
+//! integer interval plus excluded values, and, per pair of terms, which
+//! orderings are still possible. A path whose constraints cannot all hold is
+//! reported as `unsat`; a `require` already implied by earlier constraints on
+//! the same path is reported as `redundant`.
+
+use alloc::collections::BTreeMap;
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use super::{paths, Finding, Step};
+use crate::models::{Expression, Function, Requirement, Span};
+
+/// Check every path through `function`.
+pub(super) fn check_function(function: &Function) -> Vec<Finding> {
+    let all_paths = paths(&function.statements);
+    let mut conflicts: Vec<(Origin, Origin)> = Vec::new();
+    let mut redundant: Vec<(Origin, Origin)> = Vec::new();
+    let mut satisfiable_paths = 0;
+
+    for path in &all_paths {
+        let mut solver = Solver::default();
+        let mut satisfiable = true;
+        for step in path {
+            let outcome = match *step {
+                Step::Require(req, span) => solver.require(req, Origin::require(span)),
+                Step::Assume {
+                    condition,
+                    negated,
+                    span,
+                } => solver.assume(condition, negated, Origin::condition(span)),
+                Step::Bind(name) => {
+                    solver.forget(name);
+                    Outcome::Added
+                }
+            };
+            match outcome {
+                Outcome::Added => {}
+                Outcome::Implied(new, by) => {
+                    if new.kind == "require" && !redundant.contains(&(new, by)) {
+                        redundant.push((new, by));
+                    }
+                }
+                Outcome::Conflict(new, with) => {
+                    // Two branch conditions that exclude each other just mean
+                    // this combination of branches is never taken together.
+                    let reportable = new.kind == "require" || with.kind == "require";
+                    if reportable && !conflicts.contains(&(new, with)) {
+                        conflicts.push((new, with));
+                    }
+                    satisfiable = false;
+                    break;
+                }
+            }
+        }
+        if satisfiable {
+            satisfiable_paths += 1;
+        }
+    }
+
+    let mut findings: Vec<Finding> = conflicts
+        .into_iter()
+        .map(|(new, with)| {
+            let consequence = if satisfiable_paths == 0 {
+                "the function can never be satisfied"
+            } else if new.kind == "condition" {
+                "this branch is never taken"
+            } else {
+                "this path can never be satisfied"
+            };
+            let message = if new == with {
+                format!(
+                    "fn {}: {} can never hold; {}",
+                    function.name, new, consequence
+                )
+            } else {
+                format!(
+                    "fn {}: {} contradicts {}; {}",
+                    function.name, new, with, consequence
+                )
+            };
+            Finding {
+                code: "unsat",
+                message,
+                spans: vec![new.span, with.span],
+            }
+        })
+        .collect();
+    findings.extend(redundant.into_iter().map(|(new, by)| Finding {
+        code: "redundant",
+        message: format!("fn {}: {} is implied by {}", function.name, new, by),
+        spans: vec![new.span, by.span],
+    }));
+    findings
+}
+
+/// Where a constraint came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Origin {
+    kind: &'static str,
+    span: Span,
+}
+
+impl Origin {
+    fn require(span: Span) -> Self {
+        Origin {
+            kind: "require",
+            span,
+        }
+    }
+
+    fn condition(span: Span) -> Self {
+        Origin {
+            kind: "condition",
+            span,
+        }
+    }
+}
+
+impl core::fmt::Display for Origin {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} at {}", self.kind, self.span)
+    }
+}
+
+/// Result of adding one constraint.
+enum Outcome {
+    Added,
+    /// Already implied by an earlier constraint
+    Implied(Origin, Origin),
+    /// Cannot hold together with an earlier constraint
+    Conflict(Origin, Origin),
+}
+
+/// The orderings between two operands a comparison admits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Order {
+    lt: bool,
+    eq: bool,
+    gt: bool,
+}
+
+impl Order {
+    /// No constraint at all
+    const ANY: Order = Order {
+        lt: true,
+        eq: true,
+        gt: true,
+    };
+
+    fn from_op(op: &str) -> Option<Order> {
+        let (lt, eq, gt) = match op {
+            "<" => (true, false, false),
+            "<=" => (true, true, false),
+            "==" => (false, true, false),
+            "!=" => (true, false, true),
+            ">" => (false, false, true),
+            ">=" => (false, true, true),
+            _ => return None,
+        };
+        Some(Order { lt, eq, gt })
+    }
+
+    /// The same relation with its operands swapped
+    fn flip(self) -> Order {
+        Order {
+            lt: self.gt,
+            eq: self.eq,
+            gt: self.lt,
+        }
+    }
+
+    fn negate(self) -> Order {
+        Order {
+            lt: !self.lt,
+            eq: !self.eq,
+            gt: !self.gt,
+        }
+    }
+
+    fn meet(self, other: Order) -> Order {
+        Order {
+            lt: self.lt && other.lt,
+            eq: self.eq && other.eq,
+            gt: self.gt && other.gt,
+        }
+    }
+
+    fn is_empty(self) -> bool {
+        !(self.lt || self.eq || self.gt)
+    }
+
+    fn implies(self, other: Order) -> bool {
+        (!self.lt || other.lt) && (!self.eq || other.eq) && (!self.gt || other.gt)
+    }
+
+    fn holds(self, ordering: core::cmp::Ordering) -> bool {
+        match ordering {
+            core::cmp::Ordering::Less => self.lt,
+            core::cmp::Ordering::Equal => self.eq,
+            core::cmp::Ordering::Greater => self.gt,
+        }
+    }
+}
+
+/// An operand reduced to something the solver understands.
+enum Operand {
+    Int(i128),
+    Str(String),
+    Term(String),
+}
+
+fn operand(expr: &Expression) -> Operand {
+    match expr {
+        Expression::Literal(lit) => match lit.parse::<i128>() {
+            Ok(n) => Operand::Int(n),
+            Err(_) => Operand::Str(lit.clone()),
+        },
+        // `tx.time` keeps its surrounding whitespace from the grammar
+        Expression::Property(path) => Operand::Term(format!(
+            "{:?}",
+            Expression::Property(path.trim().to_string())
+        )),
+        other => Operand::Term(format!("{:?}", other)),
+    }
+}
+
+/// Possible values of an integer term.
+#[derive(Debug, Clone, Default)]
+struct Interval {
+    lo: Option<(i128, Origin)>,
+    hi: Option<(i128, Origin)>,
+    excluded: Vec<(i128, Origin)>,
+}
+
+/// Possible values of a term compared against non-numeric literals.
+#[derive(Debug, Clone, Default)]
+struct Literals {
+    equal: Option<(String, Origin)>,
+    excluded: Vec<(String, Origin)>,
+}
+
+#[derive(Debug, Default)]
+struct Solver {
+    intervals: BTreeMap<String, Interval>,
+    literals: BTreeMap<String, Literals>,
+    /// Keyed by the two term keys in sorted order
+    relations: BTreeMap<(String, String), Vec<(Order, Origin)>>,
+    /// Requirements only checked for exact repetition (signatures)
+    seen: BTreeMap<String, Origin>,
+}
+
+impl Solver {
+    fn require(&mut self, req: &Requirement, origin: Origin) -> Outcome {
+        match req {
+            Requirement::Comparison { left, op, right } => match Order::from_op(op) {
+                Some(order) => self.compare(operand(left), order, operand(right), origin),
+                None => self.exact(format!("{:?}", req), origin),
+            },
+            Requirement::After {
+                locktime,
+                timelock_var,
+            } => {
+                let time = operand(&Expression::Property("tx.time".to_string()));
+                let bound = match timelock_var {
+                    Some(var) => operand(&Expression::Variable(var.clone())),
+                    None => Operand::Int(*locktime as i128),
+                };
+                let at_least = Order::from_op(">=").unwrap();
+                self.compare(time, at_least, bound, origin)
+            }
+            Requirement::HashEqual {
+                preimage,
+                hash,
+                function,
+            } => {
+                let digest = format!(
+                    "{:?}({:?})",
+                    function,
+                    Expression::Variable(preimage.clone())
+                );
+                let hash = operand(&Expression::Variable(hash.clone()));
+                let equal = Order::from_op("==").unwrap();
+                self.compare(Operand::Term(digest), equal, hash, origin)
+            }
+            _ => self.exact(format!("{:?}", req), origin),
+        }
+    }
+
+    fn assume(&mut self, condition: &Expression, negated: bool, origin: Origin) -> Outcome {
+        let (left, order, right) = match condition {
+            Expression::BinaryOp { left, op, right } => match Order::from_op(op) {
+                Some(order) => (operand(left), order, operand(right)),
+                None => return Outcome::Added,
+            },
+            other => (
+                operand(other),
+                Order::from_op("==").unwrap(),
+                Operand::Str("true".to_string()),
+            ),
+        };
+        let order = if negated { order.negate() } else { order };
+        self.compare(left, order, right, origin)
+    }
+
+    /// Drop everything known about a variable that was rebound.
+    fn forget(&mut self, name: &str) {
+        let needle = format!("{:?}", Expression::Variable(name.to_string()));
+        self.intervals.retain(|k, _| !k.contains(&needle));
+        self.literals.retain(|k, _| !k.contains(&needle));
+        self.relations
+            .retain(|(a, b), _| !a.contains(&needle) && !b.contains(&needle));
+    }
+
+    fn exact(&mut self, key: String, origin: Origin) -> Outcome {
+        match self.seen.get(&key) {
+            Some(by) => Outcome::Implied(origin, *by),
+            None => {
+                self.seen.insert(key, origin);
+                Outcome::Added
+            }
+        }
+    }
+
+    fn compare(&mut self, left: Operand, order: Order, right: Operand, origin: Origin) -> Outcome {
+        match (left, right) {
+            (Operand::Int(a), Operand::Int(b)) => {
+                if order.holds(a.cmp(&b)) {
+                    Outcome::Added
+                } else {
+                    Outcome::Conflict(origin, origin)
+                }
+            }
+            (Operand::Str(a), Operand::Str(b)) => {
+                let equal = a == b;
+                if (equal && order.eq) || (!equal && (order.lt || order.gt)) {
+                    Outcome::Added
+                } else {
+                    Outcome::Conflict(origin, origin)
+                }
+            }
+            (Operand::Term(t), Operand::Int(k)) => self.bound(t, order, k, origin),
+            (Operand::Int(k), Operand::Term(t)) => self.bound(t, order.flip(), k, origin),
+            (Operand::Term(t), Operand::Str(s)) | (Operand::Str(s), Operand::Term(t)) => {
+                self.literal(t, order, s, origin)
+            }
+            (Operand::Term(a), Operand::Term(b)) => self.relate(a, order, b, origin),
+            // Mixed numeric/non-numeric literals are left to the type checker
+            _ => Outcome::Added,
+        }
+    }
+
+    fn bound(&mut self, term: String, order: Order, k: i128, origin: Origin) -> Outcome {
+        let iv = self.intervals.entry(term).or_default();
+        if order.is_empty() {
+            return Outcome::Conflict(origin, origin);
+        }
+
+        // Implied by what is already known?
+        let lo = iv.lo.map(|(v, _)| v);
+        let hi = iv.hi.map(|(v, _)| v);
+        let implied_by = match (order.lt, order.eq, order.gt) {
+            (true, false, false) => iv.hi.filter(|(v, _)| *v < k),
+            (true, true, false) => iv.hi.filter(|(v, _)| *v <= k),
+            (false, true, false) => iv.lo.filter(|(v, _)| lo == hi && *v == k),
+            (false, false, true) => iv.lo.filter(|(v, _)| *v > k),
+            (false, true, true) => iv.lo.filter(|(v, _)| *v >= k),
+            (true, false, true) => iv
+                .lo
+                .filter(|(v, _)| *v > k)
+                .or(iv.hi.filter(|(v, _)| *v < k))
+                .or(iv.excluded.iter().copied().find(|(v, _)| *v == k)),
+            _ => return Outcome::Added,
+        };
+        if let Some((_, by)) = implied_by {
+            return Outcome::Implied(origin, by);
+        }
+
+        // Tighten
+        if order.lt && !order.gt {
+            let new_hi = if order.eq { k } else { k - 1 };
+            if hi.is_none_or(|h| new_hi < h) {
+                iv.hi = Some((new_hi, origin));
+            }
+        }
+        if order.gt && !order.lt {
+            let new_lo = if order.eq { k } else { k + 1 };
+            if lo.is_none_or(|l| new_lo > l) {
+                iv.lo = Some((new_lo, origin));
+            }
+        }
+        if order.eq && !order.lt && !order.gt {
+            if lo.is_none_or(|l| k > l) {
+                iv.lo = Some((k, origin));
+            }
+            if hi.is_none_or(|h| k < h) {
+                iv.hi = Some((k, origin));
+            }
+        }
+        if order.lt && order.gt && !order.eq {
+            iv.excluded.push((k, origin));
+        }
+
+        // Consistent?
+        if let (Some((l, lo_origin)), Some((h, hi_origin))) = (iv.lo, iv.hi) {
+            if l > h {
+                let with = if lo_origin == origin {
+                    hi_origin
+                } else {
+                    lo_origin
+                };
+                return Outcome::Conflict(origin, with);
+            }
+            if l == h {
+                if let Some((_, ex_origin)) = iv.excluded.iter().find(|(v, _)| *v == l) {
+                    let with = if *ex_origin == origin {
+                        lo_origin
+                    } else {
+                        *ex_origin
+                    };
+                    return Outcome::Conflict(origin, with);
+                }
+            }
+        }
+        Outcome::Added
+    }
+
+    fn literal(&mut self, term: String, order: Order, value: String, origin: Origin) -> Outcome {
+        let lits = self.literals.entry(term).or_default();
+        if order == Order::from_op("==").unwrap() {
+            if let Some((current, by)) = &lits.equal {
+                return if *current == value {
+                    Outcome::Implied(origin, *by)
+                } else {
+                    Outcome::Conflict(origin, *by)
+                };
+            }
+            if let Some((_, by)) = lits.excluded.iter().find(|(v, _)| *v == value) {
+                return Outcome::Conflict(origin, *by);
+            }
+            lits.equal = Some((value, origin));
+        } else if order == Order::from_op("!=").unwrap() {
+            if let Some((current, by)) = &lits.equal {
+                return if *current == value {
+                    Outcome::Conflict(origin, *by)
+                } else {
+                    Outcome::Implied(origin, *by)
+                };
+            }
+            if let Some((_, by)) = lits.excluded.iter().find(|(v, _)| *v == value) {
+                return Outcome::Implied(origin, *by);
+            }
+            lits.excluded.push((value, origin));
+        }
+        Outcome::Added
+    }
+
+    fn relate(&mut self, a: String, order: Order, b: String, origin: Origin) -> Outcome {
+        if a == b {
+            return if order.eq {
+                Outcome::Added
+            } else {
+                Outcome::Conflict(origin, origin)
+            };
+        }
+        let (key, order) = if a < b {
+            ((a, b), order)
+        } else {
+            ((b, a), order.flip())
+        };
+        let known = self.relations.entry(key).or_default();
+
+        let all = known.iter().fold(Order::ANY, |acc, (o, _)| acc.meet(*o));
+        if !known.is_empty() && all.implies(order) {
+            let by = known
+                .iter()
+                .find(|(o, _)| o.implies(order))
+                .or(known.last())
+                .map(|(_, by)| *by)
+                .unwrap();
+            return Outcome::Implied(origin, by);
+        }
+        if all.meet(order).is_empty() {
+            let with = known
+                .iter()
+                .find(|(o, _)| o.meet(order).is_empty())
+                .or(known.last())
+                .map(|(_, by)| *by)
+                .unwrap_or(origin);
+            return Outcome::Conflict(origin, with);
+        }
+        known.push((order, origin));
+        Outcome::Added
+    }
+}
@@ -0,0 +1,191 @@
+//! Dead code.
+//!
+//! A `require` comparing two literals that always holds, such as
+//! `require(1 == 1)`, checks nothing, and an `if` whose condition is a
+//! constant always takes the same branch. Both are reported as `dead`;
+//! with the optimizer on, [`remove_dead_code`] drops them before code
+//! generation, keeping only the branch that is taken.
+
+use alloc::{format, vec, vec::Vec};
+
+use super::Finding;
+use crate::models::{Contract, Expression, Function, Requirement, Statement};
+
+/// Report the dead requirements and branches of `function`.
+pub(super) fn check_function(function: &Function) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    check_statements(function, &function.statements, &mut findings);
+    findings
+}
+
+fn check_statements(function: &Function, statements: &[Statement], findings: &mut Vec<Finding>) {
+    for statement in statements {
+        match statement {
+            Statement::Require(requirement, span) => {
+                if holds(requirement) == Some(true) {
+                    findings.push(Finding {
+                        code: "dead",
+                        message: format!("fn {}: require at {} always holds", function.name, span),
+                        spans: vec![*span],
+                    });
+                }
+            }
+            Statement::IfElse {
+                condition,
+                then_body,
+                else_body,
+                span,
+            } => {
+                if let Some(value) = constant(condition) {
+                    let consequence = match (value, else_body) {
+                        (false, _) => "; its branch is never taken",
+                        (true, Some(_)) => "; its else branch is never taken",
+                        (true, None) => "",
+                    };
+                    findings.push(Finding {
+                        code: "dead",
+                        message: format!(
+                            "fn {}: condition at {} is always {}{}",
+                            function.name, span, value, consequence
+                        ),
+                        spans: vec![*span],
+                    });
+                }
+                check_statements(function, then_body, findings);
+                if let Some(else_body) = else_body {
+                    check_statements(function, else_body, findings);
+                }
+            }
+            Statement::ForIn { body, .. } => check_statements(function, body, findings),
+            Statement::LetBinding { .. } | Statement::VarAssign { .. } => {}
+        }
+    }
+}
+
+/// Drop the requirements that always hold and the branches never taken,
+/// from every function of `contract`.
+pub fn remove_dead_code(contract: &mut Contract) {
+    for function in &mut contract.functions {
+        remove_from(&mut function.statements);
+    }
+}
+
+fn remove_from(statements: &mut Vec<Statement>) {
+    let mut kept = Vec::with_capacity(statements.len());
+    for statement in statements.drain(..) {
+        match statement {
+            Statement::Require(ref requirement, _) if holds(requirement) == Some(true) => {}
+            Statement::IfElse {
+                condition,
+                mut then_body,
+                mut else_body,
+                span,
+            } => {
+                remove_from(&mut then_body);
+                if let Some(else_body) = &mut else_body {
+                    remove_from(else_body);
+                }
+                // Names are function-wide, so a branch's bindings can move
+                // out of it
+                match constant(&condition) {
+                    Some(true) => kept.extend(then_body),
+                    Some(false) => kept.extend(else_body.unwrap_or_default()),
+                    None => kept.push(Statement::IfElse {
+                        condition,
+                        then_body,
+                        else_body,
+                        span,
+                    }),
+                }
+            }
+            Statement::ForIn {
+                index_var,
+                value_var,
+                iterable,
+                mut body,
+            } => {
+                remove_from(&mut body);
+                kept.push(Statement::ForIn {
+                    index_var,
+                    value_var,
+                    iterable,
+                    body,
+                });
+            }
+            other => kept.push(other),
+        }
+    }
+    *statements = kept;
+}
+
+/// Whether a requirement on literals alone holds, or `None` when it
+/// depends on anything else.
+fn holds(requirement: &Requirement) -> Option<bool> {
+    match requirement {
+        Requirement::Comparison { left, op, right } => compare(left, op, right),
+        _ => None,
+    }
+}
+
+/// The value of a condition built from literals alone.
+fn constant(condition: &Expression) -> Option<bool> {
+    match condition {
+        Expression::BinaryOp { left, op, right } => compare(left, op, right),
+        Expression::Not { value } => constant(value).map(|holds| !holds),
+        other => boolean(other).or_else(|| number(other).map(|n| n != 0)),
+    }
+}
+
+fn compare(left: &Expression, op: &str, right: &Expression) -> Option<bool> {
+    match op {
+        "&&" => return Some(constant(left)? && constant(right)?),
+        "||" => return Some(constant(left)? || constant(right)?),
+        _ => {}
+    }
+    if let (Some(a), Some(b)) = (number(left), number(right)) {
+        return match op {
+            "==" => Some(a == b),
+            "!=" => Some(a != b),
+            "<" => Some(a < b),
+            "<=" => Some(a <= b),
+            ">" => Some(a > b),
+            ">=" => Some(a >= b),
+            _ => None,
+        };
+    }
+    let (a, b) = (boolean(left)?, boolean(right)?);
+    match op {
+        "==" => Some(a == b),
+        "!=" => Some(a != b),
+        _ => None,
+    }
+}
+
+/// The value of integer arithmetic on literals, where it does not overflow.
+fn number(expr: &Expression) -> Option<i64> {
+    match expr {
+        Expression::Literal(lit) => lit.parse().ok(),
+        Expression::BinaryOp { left, op, right } => {
+            let (a, b) = (number(left)?, number(right)?);
+            match op.as_str() {
+                "+" => a.checked_add(b),
+                "-" => a.checked_sub(b),
+                "*" => a.checked_mul(b),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// `true` or `false`, which parse as names in conditions.
+fn boolean(expr: &Expression) -> Option<bool> {
+    match expr {
+        Expression::Literal(text) | Expression::Variable(text) => match text.as_str() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,125 @@
+//! Deprecated syntax and builtins.
+//!
+//! [`DEPRECATIONS`] lists what is on its way out of the language, with what
+//! to write instead and the edition that removes it. Contracts of earlier
+//! editions get a `deprecated` finding for every use, so they keep
+//! compiling while they migrate; [`check_removed`] fails the compilation of
+//! contracts of the removing edition or later.
+
+use super::Finding;
+use crate::models::{Contract, Edition, Expression, Function, Requirement, Span, Statement};
+use alloc::{format, string::String, vec, vec::Vec};
+
+/// A deprecated construct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deprecation {
+    /// The construct, as messages name it
+    pub construct: &'static str,
+    /// What to write instead
+    pub replacement: &'static str,
+    /// The first edition without the construct
+    pub removed_in: Edition,
+}
+
+/// `checkMultisig([keys], [sigs])`, from before signatures came from the
+/// witness. It compiles to a placeholder rather than a signature check.
+pub const SIGNATURE_ARRAY_MULTISIG: Deprecation = Deprecation {
+    construct: "checkMultisig(keys, sigs)",
+    replacement: "checkMultisig(keys, m), which takes the signatures from the witness",
+    removed_in: Edition::Edition2025,
+};
+
+/// `checkMultisig(keys)` without a threshold, implicitly n-of-n.
+pub const IMPLICIT_MULTISIG: Deprecation = Deprecation {
+    construct: "checkMultisig without a threshold",
+    replacement: "the threshold, as in checkMultisig([a, b], 2)",
+    removed_in: Edition::Edition2025,
+};
+
+/// Every deprecation.
+pub const DEPRECATIONS: &[Deprecation] = &[SIGNATURE_ARRAY_MULTISIG, IMPLICIT_MULTISIG];
+
+pub(super) fn check_contract(contract: &Contract) -> Vec<Finding> {
+    let edition = contract.edition();
+    uses(contract)
+        .filter(|(deprecation, ..)| edition < deprecation.removed_in)
+        .map(|(deprecation, function, span)| Finding {
+            code: "deprecated",
+            message: format!(
+                "fn {}: {} at {} is deprecated and will be removed in edition {}; write {}",
+                function.name,
+                deprecation.construct,
+                span,
+                deprecation.removed_in,
+                deprecation.replacement
+            ),
+            spans: vec![span],
+        })
+        .collect()
+}
+
+/// Fail on the first construct the contract's edition has removed.
+pub fn check_removed(contract: &Contract) -> Result<(), String> {
+    let edition = contract.edition();
+    match uses(contract).find(|(deprecation, ..)| edition >= deprecation.removed_in) {
+        Some((deprecation, function, span)) => Err(format!(
+            "fn {}: {} at {} was removed in edition {}; write {}",
+            function.name,
+            deprecation.construct,
+            span,
+            deprecation.removed_in,
+            deprecation.replacement
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The deprecated constructs the contract uses, in source order.
+fn uses(contract: &Contract) -> impl Iterator<Item = (&'static Deprecation, &Function, Span)> {
+    contract.functions.iter().flat_map(|function| {
+        let mut found = Vec::new();
+        statements(&function.statements, &mut found);
+        found
+            .into_iter()
+            .map(move |(deprecation, span)| (deprecation, function, span))
+    })
+}
+
+fn statements(statements: &[Statement], found: &mut Vec<(&'static Deprecation, Span)>) {
+    for stmt in statements {
+        match stmt {
+            Statement::Require(req, span) => match req {
+                Requirement::CheckMultisig { threshold: 0, .. } => {
+                    found.push((&IMPLICIT_MULTISIG, *span));
+                }
+                Requirement::Comparison { left, right, .. }
+                    if is_signature_array_multisig(left) || is_signature_array_multisig(right) =>
+                {
+                    found.push((&SIGNATURE_ARRAY_MULTISIG, *span));
+                }
+                _ => {}
+            },
+            Statement::IfElse {
+                condition,
+                then_body,
+                else_body,
+                span,
+            } => {
+                if is_signature_array_multisig(condition) {
+                    found.push((&SIGNATURE_ARRAY_MULTISIG, *span));
+                }
+                self::statements(then_body, found);
+                if let Some(else_body) = else_body {
+                    self::statements(else_body, found);
+                }
+            }
+            Statement::ForIn { body, .. } => self::statements(body, found),
+            Statement::LetBinding { .. } | Statement::VarAssign { .. } => {}
+        }
+    }
+}
+
+/// The legacy form is kept as raw call text
+fn is_signature_array_multisig(expr: &Expression) -> bool {
+    matches!(expr, Expression::Property(call) if call.trim_start().starts_with("checkMultisig"))
+}
@@ -0,0 +1,339 @@
+//! Witness malleability.
+//!
+//! A witness is malleable when a third party who sees a valid spend can
+//! rewrite it into a different valid witness without any secret. Following
+//! Miniscript's non-malleability rules, this looks for:
+//!
+//! - hash comparisons that are not verified, so any wrong preimage takes the
+//!   other branch (conditions are never size-checked, so every unverified
+//!   comparison is reported)
+//! - hash locks that accept a preimage of any size, unless the contract sets
+//!   `strictHashlocks = true` to pin preimages to 32 bytes
+//! - signature parameters that no signature check binds, or that only pick
+//!   an `if` branch and can be swapped for an empty signature to take the
+//!   `else` branch instead
+//! - booleans taken directly from the witness, either as a bare `require`
+//!   that any non-zero value satisfies, or as a branch selector that can be
+//!   flipped because one branch checks no signature beyond the other's
+//!
+//! Tapscript only accepts an empty signature as a failed check, so a
+//! signature checked in a condition without an `else` branch (as in
+//! threshold counting) is not malleable.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use super::Finding;
+use crate::models::{array_type, Expression, Function, HashFunction, Requirement, Span, Statement};
+
+pub(super) fn check_function(function: &Function, strict_hashlocks: bool) -> Vec<Finding> {
+    let mut scan = Scan::default();
+    scan.statements(&function.statements);
+
+    let witness = |ty: &str| -> Vec<&str> {
+        function
+            .parameters
+            .iter()
+            .filter(|p| array_type(&p.param_type).map_or(p.param_type.as_str(), |(t, _)| t) == ty)
+            .map(|p| p.name.as_str())
+            .collect()
+    };
+    let is_witness = |name: &str| function.parameters.iter().any(|p| p.name == name);
+
+    let mut findings = Vec::new();
+    let mut report = |message: String, spans: Vec<Span>| {
+        findings.push(Finding {
+            code: "malleable",
+            message: format!("fn {}: {}", function.name, message),
+            spans,
+        });
+    };
+
+    for (preimage, span) in &scan.hash_conditions {
+        if is_witness(preimage) {
+            report(
+                format!(
+                    "hash comparison on `{}` at {} is not verified; a third party can replace a non-matching `{}` with any other value",
+                    preimage, span, preimage
+                ),
+                vec![*span],
+            );
+        }
+    }
+
+    if !strict_hashlocks {
+        for (preimage, span) in &scan.hash_locks {
+            if is_witness(scan.resolve(preimage).as_str()) {
+                report(
+                    format!(
+                        "hash lock on `{}` at {} accepts a preimage of any size; set `strictHashlocks = true` to require 32 bytes",
+                        preimage, span
+                    ),
+                    vec![*span],
+                );
+            }
+        }
+    }
+
+    if !scan.multisig {
+        for sig in ["signature", "schnorrsig", "datasig"]
+            .into_iter()
+            .flat_map(witness)
+        {
+            if !scan.bound_sigs.contains(sig) {
+                report(
+                    format!(
+                        "signature `{}` is not bound by any signature check; a third party can replace it with any value",
+                        sig
+                    ),
+                    Vec::new(),
+                );
+            }
+        }
+    }
+
+    let bools = witness("bool");
+    for (name, span) in &scan.bare_bools {
+        if bools.contains(&name.as_str()) {
+            report(
+                format!(
+                    "boolean `{}` at {} is taken directly from the witness and any non-zero value satisfies it; compare it against `true`",
+                    name, span
+                ),
+                vec![*span],
+            );
+        }
+    }
+
+    for selector in &scan.selectors {
+        if let (Some((sig, key)), Some(else_keys)) = (&selector.checked_sig, &selector.else_keys) {
+            let mut available = selector.then_keys.clone();
+            available.insert(key.clone());
+            if else_keys.is_subset(&available) {
+                report(
+                    format!(
+                        "signature `{}` only selects the branch at {}; a third party can replace it with an empty signature to take the else branch",
+                        sig, selector.span
+                    ),
+                    vec![selector.span],
+                );
+            }
+        }
+
+        let else_keys = selector.else_keys.clone().unwrap_or_default();
+        let flippable =
+            selector.then_keys.is_subset(&else_keys) || else_keys.is_subset(&selector.then_keys);
+        if !flippable {
+            continue;
+        }
+        for name in bools.iter().filter(|b| selector.names.contains(**b)) {
+            report(
+                format!(
+                    "boolean `{}` selects the branch at {} straight from the witness; a third party can flip it because one branch checks no signature beyond the other's",
+                    name, selector.span
+                ),
+                vec![selector.span],
+            );
+        }
+    }
+
+    findings
+}
+
+/// An `if` statement and what it takes to satisfy each branch.
+struct Selector {
+    /// Identifiers mentioned by the condition
+    names: BTreeSet<String>,
+    /// Signature and key when the condition is a single signature check
+    checked_sig: Option<(String, String)>,
+    /// Keys whose signatures the `then` branch checks
+    then_keys: BTreeSet<String>,
+    /// Keys whose signatures the `else` branch checks, if there is one
+    else_keys: Option<BTreeSet<String>>,
+    span: Span,
+}
+
+/// Facts gathered from one walk over a function body.
+#[derive(Default)]
+struct Scan {
+    /// Signatures passed to any signature check
+    bound_sigs: BTreeSet<String>,
+    /// Loop variables and the array parameter they iterate
+    aliases: BTreeMap<String, String>,
+    /// Whether a threshold multisig consumes signatures positionally
+    multisig: bool,
+    /// Preimages of hash comparisons used as `if` conditions
+    hash_conditions: Vec<(String, Span)>,
+    /// Preimages of hash locks
+    hash_locks: Vec<(String, Span)>,
+    /// Identifiers required to be truthy on their own
+    bare_bools: Vec<(String, Span)>,
+    selectors: Vec<Selector>,
+}
+
+impl Scan {
+    fn statements(&mut self, statements: &[Statement]) {
+        for stmt in statements {
+            match stmt {
+                Statement::Require(req, span) => self.requirement(req, *span),
+                Statement::IfElse {
+                    condition,
+                    then_body,
+                    else_body,
+                    span,
+                } => {
+                    self.condition(condition, *span);
+                    self.statements(then_body);
+                    if let Some(else_stmts) = else_body {
+                        self.statements(else_stmts);
+                    }
+                    let checked_sig = match condition {
+                        Expression::CheckSigExpr { signature, pubkey }
+                        | Expression::CheckSigFromStackExpr {
+                            signature, pubkey, ..
+                        } => Some((self.resolve(signature), pubkey.clone())),
+                        _ => None,
+                    };
+                    self.selectors.push(Selector {
+                        names: identifiers(condition),
+                        checked_sig,
+                        then_keys: signed_keys(then_body),
+                        else_keys: else_body.as_deref().map(signed_keys),
+                        span: *span,
+                    });
+                }
+                Statement::ForIn {
+                    value_var,
+                    iterable,
+                    body,
+                    ..
+                } => {
+                    if let Expression::Variable(array) = iterable {
+                        self.aliases.insert(value_var.clone(), array.clone());
+                    }
+                    self.statements(body);
+                }
+                Statement::LetBinding { value, .. } | Statement::VarAssign { value, .. } => {
+                    if let Expression::CheckSigCount { .. } = value {
+                        self.multisig = true;
+                    }
+                }
+            }
+        }
+    }
+
+    fn requirement(&mut self, req: &Requirement, span: Span) {
+        match req {
+            Requirement::CheckSig { signature, .. }
+            | Requirement::CheckSigFromStack { signature, .. } => self.bind(signature),
+            Requirement::CheckMultisig { .. } => self.multisig = true,
+            Requirement::Comparison { left, op, right } if op == "&&" || op == "||" => {
+                self.condition(left, span);
+                self.condition(right, span);
+            }
+            Requirement::Comparison { left, op, right } => match left {
+                Expression::CheckSigExpr { signature, .. }
+                | Expression::CheckSigFromStackExpr { signature, .. }
+                | Expression::CheckSigFromStackVerify { signature, .. } => self.bind(signature),
+                // Legacy `checkMultisig([keys], [sigs])` is kept as raw call text
+                Expression::Property(call) if call.trim_start().starts_with("checkMultisig") => {
+                    self.multisig = true;
+                }
+                Expression::Variable(name)
+                    if op == "==" && matches!(right, Expression::Literal(l) if l == "true") =>
+                {
+                    self.bare_bools.push((name.clone(), span));
+                }
+                _ => {}
+            },
+            Requirement::HashEqual { preimage, .. } => {
+                self.hash_locks.push((preimage.clone(), span));
+            }
+            Requirement::After { .. }
+            | Requirement::Older { .. }
+            | Requirement::SizeBetween { .. } => {}
+        }
+    }
+
+    fn condition(&mut self, condition: &Expression, span: Span) {
+        match condition {
+            Expression::CheckSigExpr { signature, .. }
+            | Expression::CheckSigFromStackExpr { signature, .. }
+            | Expression::CheckSigFromStackVerify { signature, .. } => self.bind(signature),
+            Expression::BinaryOp { left, right, .. } => {
+                self.condition(left, span);
+                self.condition(right, span);
+            }
+            Expression::Property(call) => {
+                if let Some(preimage) = hashed(call) {
+                    self.hash_conditions.push((preimage.to_string(), span));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn bind(&mut self, signature: &str) {
+        let name = self.resolve(signature);
+        self.bound_sigs.insert(name);
+    }
+
+    /// Map a loop variable or `arr[i]` back to the parameter it reads.
+    fn resolve(&self, name: &str) -> String {
+        let base = name.split('[').next().unwrap_or(name).trim();
+        self.aliases
+            .get(base)
+            .cloned()
+            .unwrap_or_else(|| base.to_string())
+    }
+}
+
+/// The argument of a `sha256(x)` call, or of another hash function, the
+/// parser kept as raw text.
+fn hashed(call: &str) -> Option<&str> {
+    HashFunction::split_call(call).map(|(_, argument)| argument)
+}
+
+/// Keys whose signatures a branch checks, nested branches included.
+fn signed_keys(statements: &[Statement]) -> BTreeSet<String> {
+    let mut keys = BTreeSet::new();
+    for stmt in statements {
+        match stmt {
+            Statement::Require(req, _) => match req {
+                Requirement::CheckSig { pubkey, .. }
+                | Requirement::CheckSigFromStack { pubkey, .. } => {
+                    keys.insert(pubkey.clone());
+                }
+                Requirement::CheckMultisig { pubkeys, .. } => keys.extend(pubkeys.iter().cloned()),
+                _ => {}
+            },
+            Statement::IfElse {
+                then_body,
+                else_body,
+                ..
+            } => {
+                keys.extend(signed_keys(then_body));
+                if let Some(else_stmts) = else_body {
+                    keys.extend(signed_keys(else_stmts));
+                }
+            }
+            Statement::ForIn { body, .. } => keys.extend(signed_keys(body)),
+            _ => {}
+        }
+    }
+    keys
+}
+
+/// Every identifier-like token mentioned by an expression.
+fn identifiers(expr: &Expression) -> BTreeSet<String> {
+    format!("{:?}", expr)
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect()
+}
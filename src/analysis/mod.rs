@@ -0,0 +1,136 @@
+//! Static analyses over the contract AST.
+//!
+//! Each analysis walks the functions of a parsed [`Contract`] and reports
+//! [`Finding`]s. Findings never stop compilation; the compiler surfaces them
+//! as `warning[<code>]: ...` lines on `ContractJson::warnings`, next to the
+//! type checker's warnings. The one exception is [`check_removed`], which
+//! rejects deprecated constructs the contract's edition no longer has.
+//! [`remove_dead_code`] rewrites the contract instead, for the optimizer.
+
+use crate::models::{Contract, Span, Statement};
+use alloc::{string::String, vec, vec::Vec};
+
+mod constraints;
+mod dead_code;
+mod deprecations;
+mod malleability;
+mod timelocks;
+
+pub use dead_code::remove_dead_code;
+pub use deprecations::{
+    check_removed, Deprecation, DEPRECATIONS, IMPLICIT_MULTISIG, SIGNATURE_ARRAY_MULTISIG,
+};
+
+/// A single diagnostic produced by an analysis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// Short machine-readable category, e.g. `unsat` or `redundant`
+    pub code: &'static str,
+    /// Human-readable description, including the locations involved
+    pub message: String,
+    /// Source locations of the constructs involved
+    pub spans: Vec<Span>,
+}
+
+impl core::fmt::Display for Finding {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "warning[{}]: {}", self.code, self.message)
+    }
+}
+
+/// Run every analysis over the contract.
+pub fn check_contract(contract: &Contract) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for function in &contract.functions {
+        findings.extend(constraints::check_function(function));
+        findings.extend(dead_code::check_function(function));
+        if !function.is_internal {
+            findings.extend(malleability::check_function(
+                function,
+                contract.strict_hashlocks,
+            ));
+        }
+    }
+    findings.extend(timelocks::check_contract(contract));
+    findings.extend(deprecations::check_contract(contract));
+    findings
+}
+
+/// Upper bound on the number of paths enumerated per function. Deeply nested
+/// branching beyond this is only partially analyzed.
+const MAX_PATHS: usize = 64;
+
+/// One step along an execution path through a function body.
+#[derive(Debug, Clone, Copy)]
+enum Step<'a> {
+    /// A `require` that must hold
+    Require(&'a crate::models::Requirement, Span),
+    /// An `if` condition assumed true (`negated == false`) or false
+    Assume {
+        condition: &'a crate::models::Expression,
+        negated: bool,
+        span: Span,
+    },
+    /// A variable (re)bound to a new value
+    Bind(&'a str),
+}
+
+/// Enumerate the straight-line paths through `statements`.
+///
+/// Loop bodies are unrolled by the code generator with substituted names, so
+/// they are not followed here.
+fn paths(statements: &[Statement]) -> Vec<Vec<Step<'_>>> {
+    let mut result = vec![Vec::new()];
+    for stmt in statements {
+        match stmt {
+            Statement::Require(req, span) => {
+                for path in &mut result {
+                    path.push(Step::Require(req, *span));
+                }
+            }
+            Statement::LetBinding { name, .. } | Statement::VarAssign { name, .. } => {
+                for path in &mut result {
+                    path.push(Step::Bind(name));
+                }
+            }
+            Statement::IfElse {
+                condition,
+                then_body,
+                else_body,
+                span,
+            } => {
+                let branches = [
+                    (false, paths(then_body)),
+                    (
+                        true,
+                        else_body
+                            .as_deref()
+                            .map(paths)
+                            .unwrap_or_else(|| vec![Vec::new()]),
+                    ),
+                ];
+                let mut next = Vec::new();
+                for path in &result {
+                    for (negated, tails) in &branches {
+                        for tail in tails.iter().cloned() {
+                            if next.len() == MAX_PATHS {
+                                break;
+                            }
+                            let mut p = path.clone();
+                            p.push(Step::Assume {
+                                condition,
+                                negated: *negated,
+                                span: *span,
+                            });
+                            p.extend(tail);
+                            next.push(p);
+                        }
+                    }
+                }
+                result = next;
+            }
+            Statement::ForIn { .. } => {}
+        }
+    }
+    result
+}
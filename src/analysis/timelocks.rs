@@ -0,0 +1,348 @@
+//! Timelock consistency.
+//!
+//! Checks that the absolute (CLTV) and relative (CSV) timelocks a leaf ends
+//! up with can hold together, and that the `exit` option fits the timelocks
+//! written in function bodies:
+//!
+//! - absolute timelocks on one path that mix block heights and timestamps
+//! - absolute timelocks contradicted by an upper bound on `tx.locktime`
+//! - an `exit` delay that does not fit BIP68's 16-bit relative lock
+//! - an `exit` delay that is not shorter than the `renew` delay
+//! - exit leaves that fall back to N-of-N signatures and so drop an
+//!   in-function absolute timelock, leaving the exit path weaker than the
+//!   cooperative one
+//! - relative timelocks that count time on a path that also counts blocks,
+//!   including the exit delay of the exit leaf: an input's nSequence has a
+//!   single unit
+
+use super::{paths, Finding, Step};
+use crate::compiler::function_uses_introspection;
+use crate::models::{Contract, Duration, Expression, Function, Requirement, Span, Statement};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// nLockTime values below this are block heights, values at or above it are
+/// UNIX timestamps.
+const LOCKTIME_THRESHOLD: u64 = 500_000_000;
+
+/// Largest block count a BIP68 relative timelock can encode.
+const MAX_RELATIVE_BLOCKS: u64 = 0xffff;
+
+/// An absolute timelock required on a path.
+#[derive(Debug, Clone, Copy)]
+struct Absolute {
+    /// The locktime, when it is a literal rather than a parameter
+    value: Option<u64>,
+    span: Span,
+}
+
+impl Absolute {
+    fn describe(&self) -> String {
+        match self.value {
+            Some(v) if v >= LOCKTIME_THRESHOLD => format!("timestamp {}", v),
+            Some(v) => format!("block height {}", v),
+            None => "a parameter".to_string(),
+        }
+    }
+}
+
+pub(super) fn check_contract(contract: &Contract) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    let exit = contract.exit_delay();
+    if exit > MAX_RELATIVE_BLOCKS {
+        findings.push(Finding {
+            code: "timelock",
+            message: format!(
+                "exit delay of {} blocks exceeds the {}-block maximum of a relative timelock; exit leaves can never be spent",
+                exit, MAX_RELATIVE_BLOCKS
+            ),
+            spans: Vec::new(),
+        });
+    }
+    if let Some(renew) = contract.renewal_timelock {
+        if exit >= renew {
+            findings.push(Finding {
+                code: "timelock",
+                message: format!(
+                    "exit delay of {} blocks is not shorter than the renew delay of {} blocks; the exit path unlocks only after the VTXO can be renewed",
+                    exit, renew
+                ),
+                spans: Vec::new(),
+            });
+        }
+    }
+
+    for function in &contract.functions {
+        findings.extend(check_paths(function));
+
+        if !function.is_internal && !function_uses_introspection(function) {
+            let mut timed = Vec::new();
+            collect_relatives(&function.statements, &mut timed);
+            timed.retain(|(age, _)| matches!(age, Duration::Seconds(_)));
+            if let Some((age, span)) = timed.first() {
+                findings.push(Finding {
+                    code: "timelock",
+                    message: format!(
+                        "fn {}: relative timelock of {} at {} counts time but the exit delay counts blocks; an input's nSequence cannot be both, so the exit leaf can never be spent",
+                        function.name,
+                        describe_age(age),
+                        span
+                    ),
+                    spans: timed.iter().map(|(_, span)| *span).collect(),
+                });
+            }
+        }
+
+        // Every contract has exit leaves, after the default delay if the
+        // options set none
+        if function.is_internal {
+            continue;
+        }
+        if function_uses_introspection(function) {
+            let mut dropped = Vec::new();
+            collect_absolutes(&function.statements, &mut dropped);
+            if let Some(first) = dropped.first() {
+                findings.push(Finding {
+                    code: "timelock",
+                    message: format!(
+                        "fn {}: the exit path falls back to N-of-N signatures and does not enforce the absolute timelock at {}; the exit leaf is weaker than the cooperative leaf",
+                        function.name, first.span
+                    ),
+                    spans: dropped.iter().map(|a| a.span).collect(),
+                });
+            }
+        }
+    }
+
+    findings
+}
+
+/// Check the absolute timelocks along every path through `function`.
+fn check_paths(function: &Function) -> Vec<Finding> {
+    let mut findings: Vec<Finding> = Vec::new();
+    let mut push = |finding: Finding| {
+        if !findings.contains(&finding) {
+            findings.push(finding);
+        }
+    };
+
+    for path in paths(&function.statements) {
+        let mut heights: Vec<Absolute> = Vec::new();
+        let mut times: Vec<Absolute> = Vec::new();
+        let mut locktime_max: Option<(u64, Span)> = None;
+        let mut relatives: Vec<(Duration, Span)> = Vec::new();
+
+        for step in path {
+            let Step::Require(req, span) = step else {
+                continue;
+            };
+            if let Requirement::Older {
+                age,
+                timelock_var: None,
+            } = req
+            {
+                relatives.push((*age, span));
+            }
+            if let Some(abs) = absolute_timelock(req, span) {
+                match abs.value {
+                    Some(v) if v >= LOCKTIME_THRESHOLD => times.push(abs),
+                    Some(_) => heights.push(abs),
+                    None => {}
+                }
+            }
+            if let Some(max) = locktime_upper_bound(req) {
+                if locktime_max.is_none_or(|(m, _)| max < m) {
+                    locktime_max = Some((max, span));
+                }
+            }
+        }
+
+        if let (Some(h), Some(t)) = (heights.first(), times.first()) {
+            let (first, second) = if h.span.start < t.span.start {
+                (h, t)
+            } else {
+                (t, h)
+            };
+            push(Finding {
+                code: "timelock",
+                message: format!(
+                    "fn {}: absolute timelock at {} uses {} but absolute timelock at {} uses {}; a transaction's nLockTime cannot be both, so this path can never be satisfied",
+                    function.name,
+                    first.span,
+                    first.describe(),
+                    second.span,
+                    second.describe()
+                ),
+                spans: vec![first.span, second.span],
+            });
+        }
+
+        let blocks = relatives
+            .iter()
+            .find(|(age, _)| matches!(age, Duration::Blocks(_)));
+        let timed = relatives
+            .iter()
+            .find(|(age, _)| matches!(age, Duration::Seconds(_)));
+        if let (Some((b, b_span)), Some((t, t_span))) = (blocks, timed) {
+            let (first, second) = if b_span.start < t_span.start {
+                ((b, b_span), (t, t_span))
+            } else {
+                ((t, t_span), (b, b_span))
+            };
+            push(Finding {
+                code: "timelock",
+                message: format!(
+                    "fn {}: relative timelock of {} at {} and relative timelock of {} at {} use different units; an input's nSequence cannot be both, so this path can never be satisfied",
+                    function.name,
+                    describe_age(first.0),
+                    first.1,
+                    describe_age(second.0),
+                    second.1
+                ),
+                spans: vec![*b_span, *t_span],
+            });
+        }
+
+        if let Some((max, bound_span)) = locktime_max {
+            for abs in heights.iter().chain(times.iter()) {
+                let value = abs.value.unwrap_or_default();
+                if value > max {
+                    push(Finding {
+                        code: "timelock",
+                        message: format!(
+                            "fn {}: absolute timelock at {} needs nLockTime >= {} but require at {} caps tx.locktime at {}; this path can never be satisfied",
+                            function.name, abs.span, value, bound_span, max
+                        ),
+                        spans: vec![abs.span, bound_span],
+                    });
+                }
+            }
+        }
+    }
+
+    findings
+}
+
+/// Collect every absolute timelock in a function body, branches included.
+fn collect_absolutes(statements: &[Statement], out: &mut Vec<Absolute>) {
+    for stmt in statements {
+        match stmt {
+            Statement::Require(req, span) => {
+                out.extend(absolute_timelock(req, *span));
+            }
+            Statement::IfElse {
+                then_body,
+                else_body,
+                ..
+            } => {
+                collect_absolutes(then_body, out);
+                if let Some(else_stmts) = else_body {
+                    collect_absolutes(else_stmts, out);
+                }
+            }
+            Statement::ForIn { body, .. } => collect_absolutes(body, out),
+            _ => {}
+        }
+    }
+}
+
+/// A relative timelock as messages give it: `144 blocks`, `2 days`.
+fn describe_age(age: &Duration) -> String {
+    match age {
+        Duration::Blocks(blocks) => format!("{} blocks", blocks),
+        Duration::Seconds(_) => age.to_string(),
+    }
+}
+
+/// Collect every relative timelock given as a duration in a function body,
+/// branches included.
+fn collect_relatives(statements: &[Statement], out: &mut Vec<(Duration, Span)>) {
+    for stmt in statements {
+        match stmt {
+            Statement::Require(
+                Requirement::Older {
+                    age,
+                    timelock_var: None,
+                },
+                span,
+            ) => out.push((*age, *span)),
+            Statement::IfElse {
+                then_body,
+                else_body,
+                ..
+            } => {
+                collect_relatives(then_body, out);
+                if let Some(else_stmts) = else_body {
+                    collect_relatives(else_stmts, out);
+                }
+            }
+            Statement::ForIn { body, .. } => collect_relatives(body, out),
+            _ => {}
+        }
+    }
+}
+
+/// The absolute timelock a requirement imposes, if any: `tx.time >= x`
+/// compiles to `OP_CHECKLOCKTIMEVERIFY`.
+fn absolute_timelock(req: &Requirement, span: Span) -> Option<Absolute> {
+    match req {
+        Requirement::After {
+            locktime,
+            timelock_var,
+        } => Some(Absolute {
+            value: if timelock_var.is_some() {
+                None
+            } else {
+                Some(*locktime)
+            },
+            span,
+        }),
+        Requirement::Comparison { left, op, right }
+            if is_tx_time(left) && matches!(op.as_str(), ">=" | ">" | "==") =>
+        {
+            let value = match right {
+                Expression::Literal(lit) => lit.parse::<u64>().ok(),
+                _ => None,
+            };
+            let value = if op == ">" {
+                value.map(|v| v + 1)
+            } else {
+                value
+            };
+            Some(Absolute { value, span })
+        }
+        _ => None,
+    }
+}
+
+/// The largest `tx.locktime` a requirement admits, if it bounds it.
+fn locktime_upper_bound(req: &Requirement) -> Option<u64> {
+    let Requirement::Comparison { left, op, right } = req else {
+        return None;
+    };
+    let Expression::TxIntrospection { property } = left else {
+        return None;
+    };
+    if property.trim() != "locktime" {
+        return None;
+    }
+    let Expression::Literal(lit) = right else {
+        return None;
+    };
+    let value = lit.parse::<u64>().ok()?;
+    match op.as_str() {
+        "<" => Some(value.saturating_sub(1)),
+        "<=" | "==" => Some(value),
+        _ => None,
+    }
+}
+
+/// `tx.time` — the parser keeps surrounding whitespace in property paths.
+fn is_tx_time(expr: &Expression) -> bool {
+    matches!(expr, Expression::Property(p) if p.trim() == "tx.time")
+}
@@ -0,0 +1,589 @@
+//! `proptest` generators for the AST, behind the `testing` feature.
+//!
+//! Every generated node is one the parser builds from some source, and
+//! [`crate::printer::print`] writes that source back, so a property test can
+//! render a generated contract, parse it and compare. Spans are left at
+//! their default: compare parsed contracts with their spans cleared.
+
+use proptest::arbitrary::Arbitrary;
+use proptest::collection::{btree_map, btree_set, vec};
+use proptest::option;
+use proptest::prelude::*;
+use proptest::sample::select;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::constants::CONSTANTS;
+use crate::models::{
+    AssetLookupSource, Contract, Duration, Edition, Emit, Event, Expression, Function,
+    GroupIOSource, GroupSumSource, HashFunction, Parameter, Requirement, Span, Statement, Trivia,
+};
+use crate::printer::print_expression;
+
+const BASE_TYPES: &[&str] = &[
+    "pubkey",
+    "signature",
+    "schnorrsig",
+    "datasig",
+    "bytes32",
+    "bytes20",
+    "bytes",
+    "asset",
+    "int",
+    "bool",
+];
+const OPERATORS: &[&str] = &["+", "-", "*", "/", "%", "==", "!=", "<", "<=", ">", ">="];
+const COMPARISONS: &[&str] = &["==", "!=", "<", "<=", ">", ">="];
+const TX_PROPERTIES: &[&str] = &["version", "locktime", "numInputs", "numOutputs", "weight"];
+const INPUT_PROPERTIES: &[&str] = &["value", "scriptPubKey", "sequence", "outpoint", "issuance"];
+const OUTPUT_PROPERTIES: &[&str] = &["value", "scriptPubKey", "nonce"];
+const THIS_PROPERTIES: &[&str] = &["this.activeInputIndex", "this.activeBytecode"];
+const GROUP_PROPERTIES: &[&str] = &[
+    "numInputs",
+    "numOutputs",
+    "sumInputs",
+    "sumOutputs",
+    "delta",
+    "control",
+    "metadataHash",
+    "assetId",
+    "isFresh",
+];
+
+/// An identifier. The capital letter keeps it from being a keyword, or a
+/// keyword followed by an identifier (`letter`).
+fn identifier() -> impl Strategy<Value = String> {
+    "[a-z]{1,3}[A-Z][a-z0-9]{0,3}"
+}
+
+fn number() -> impl Strategy<Value = String> {
+    "0|[1-9][0-9]{0,5}"
+}
+
+fn select_string(options: &'static [&'static str]) -> impl Strategy<Value = String> {
+    select(options).prop_map(str::to_string)
+}
+
+/// A variable or a number, which most builtins take as arguments.
+fn operand() -> impl Strategy<Value = Expression> {
+    prop_oneof![
+        identifier().prop_map(Expression::Variable),
+        number().prop_map(Expression::Literal),
+    ]
+}
+
+fn boxed_operand() -> impl Strategy<Value = Box<Expression>> {
+    operand().prop_map(Box::new)
+}
+
+/// A call to one of the Arkade opcode builtins.
+fn opcode_call() -> impl Strategy<Value = Expression> {
+    let variable = || identifier().prop_map(|name| Box::new(Expression::Variable(name)));
+    prop_oneof![
+        boxed_operand().prop_map(|data| Expression::Sha256Initialize { data }),
+        (variable(), boxed_operand())
+            .prop_map(|(context, chunk)| Expression::Sha256Update { context, chunk }),
+        (variable(), boxed_operand()).prop_map(|(context, last_chunk)| {
+            Expression::Sha256Finalize {
+                context,
+                last_chunk,
+            }
+        }),
+        boxed_operand().prop_map(|value| Expression::Neg64 { value }),
+        boxed_operand().prop_map(|value| Expression::Not { value }),
+        boxed_operand().prop_map(|value| Expression::Le64ToScriptNum { value }),
+        boxed_operand().prop_map(|value| Expression::Le32ToLe64 { value }),
+        (boxed_operand(), boxed_operand(), boxed_operand()).prop_map(
+            |(scalar, point_p, point_q)| Expression::EcMulScalarVerify {
+                scalar,
+                point_p,
+                point_q,
+            }
+        ),
+        (boxed_operand(), boxed_operand(), boxed_operand()).prop_map(
+            |(point_p, tweak, point_q)| Expression::TweakVerify {
+                point_p,
+                tweak,
+                point_q,
+            }
+        ),
+    ]
+}
+
+/// Transaction, input and output introspection.
+fn introspection() -> impl Strategy<Value = Expression> {
+    prop_oneof![
+        select_string(TX_PROPERTIES).prop_map(|property| Expression::TxIntrospection { property }),
+        (boxed_operand(), select_string(INPUT_PROPERTIES))
+            .prop_map(|(index, property)| Expression::InputIntrospection { index, property }),
+        (boxed_operand(), select_string(OUTPUT_PROPERTIES))
+            .prop_map(|(index, property)| Expression::OutputIntrospection { index, property }),
+        identifier().prop_map(|property| Expression::CurrentInput(Some(property))),
+    ]
+}
+
+fn this_property() -> impl Strategy<Value = Expression> {
+    select_string(THIS_PROPERTIES).prop_map(Expression::Property)
+}
+
+/// `new Name(args)`, with arguments that may be contract instances too.
+fn contract_instance() -> impl Strategy<Value = Expression> {
+    let instance = |args: BoxedStrategy<Expression>| {
+        ("[A-Z][a-z]{1,6}", vec(args, 0..3)).prop_map(|(contract_name, args)| {
+            Expression::ContractInstance {
+                contract_name,
+                args,
+            }
+        })
+    };
+    let nested = instance(operand().boxed()).boxed();
+    instance(prop_oneof![3 => operand(), 1 => nested].boxed())
+}
+
+/// `tx.assetGroups` lookups that read as expressions.
+fn asset_group() -> impl Strategy<Value = Expression> {
+    prop_oneof![
+        identifier().prop_map(|asset_id| Expression::GroupFind { asset_id }),
+        Just(Expression::AssetGroupsLength),
+        (
+            boxed_operand(),
+            prop_oneof![Just(GroupSumSource::Inputs), Just(GroupSumSource::Outputs)]
+        )
+            .prop_map(|(index, source)| Expression::GroupSum { index, source }),
+        (
+            boxed_operand(),
+            prop_oneof![Just(GroupIOSource::Inputs), Just(GroupIOSource::Outputs)]
+        )
+            .prop_map(|(index, source)| Expression::GroupNumIO { index, source }),
+    ]
+}
+
+/// `group.property` on a group found with `tx.assetGroups.find`.
+fn group_property() -> impl Strategy<Value = Expression> {
+    (identifier(), select_string(GROUP_PROPERTIES))
+        .prop_map(|(group, property)| Expression::GroupProperty { group, property })
+}
+
+fn asset_lookup() -> impl Strategy<Value = Expression> {
+    (
+        prop_oneof![
+            Just(AssetLookupSource::Input),
+            Just(AssetLookupSource::Output)
+        ],
+        boxed_operand(),
+        identifier(),
+    )
+        .prop_map(|(source, index, asset_id)| Expression::AssetLookup {
+            source,
+            index,
+            asset_id,
+        })
+}
+
+impl Arbitrary for Expression {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    /// A general expression, as in a condition or a `let` binding.
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let leaf = prop_oneof![
+            4 => operand(),
+            1 => (identifier(), identifier())
+                .prop_map(|(signature, pubkey)| Expression::CheckSigExpr { signature, pubkey }),
+            1 => vec(identifier(), 1..4).prop_map(|pubkeys| Expression::CheckSigCount { pubkeys }),
+            1 => opcode_call(),
+            1 => introspection(),
+            1 => this_property(),
+            1 => asset_group(),
+            1 => contract_instance(),
+        ];
+        leaf.prop_recursive(3, 16, 2, |inner| {
+            (inner.clone(), select_string(OPERATORS), inner).prop_map(|(left, op, right)| {
+                Expression::BinaryOp {
+                    left: Box::new(left),
+                    op,
+                    right: Box::new(right),
+                }
+            })
+        })
+        .boxed()
+    }
+}
+
+fn comparison(
+    left: impl Strategy<Value = Expression>,
+    ops: &'static [&'static str],
+    right: impl Strategy<Value = Expression>,
+) -> impl Strategy<Value = Requirement> {
+    (left, select_string(ops), right).prop_map(|(left, op, right)| Requirement::Comparison {
+        left,
+        op,
+        right,
+    })
+}
+
+/// An expression required to be true on its own.
+fn truthy(expression: impl Strategy<Value = Expression>) -> impl Strategy<Value = Requirement> {
+    expression.prop_map(|left| Requirement::Comparison {
+        left,
+        op: "==".to_string(),
+        right: Expression::Literal("true".to_string()),
+    })
+}
+
+/// The right side of an asset lookup comparison, which may do arithmetic.
+fn asset_amount() -> impl Strategy<Value = Expression> {
+    let term = || prop_oneof![asset_lookup(), operand()];
+    prop_oneof![
+        asset_lookup(),
+        operand(),
+        (term(), select_string(&["+", "-", "*", "/", "%"]), term()).prop_map(
+            |(left, op, right)| {
+                Expression::BinaryOp {
+                    left: Box::new(left),
+                    op,
+                    right: Box::new(right),
+                }
+            }
+        ),
+    ]
+}
+
+fn multisig() -> impl Strategy<Value = Requirement> {
+    prop_oneof![
+        // An array literal, where 0 means every key
+        vec(identifier(), 1..5).prop_flat_map(|pubkeys| {
+            let n = pubkeys.len() as u16;
+            (Just(pubkeys), 0..=n)
+                .prop_map(|(pubkeys, threshold)| Requirement::CheckMultisig { pubkeys, threshold })
+        }),
+        // A `pubkey[]` parameter, where 0 means every key
+        (identifier(), 0u16..5).prop_map(|(array, threshold)| Requirement::CheckMultisig {
+            pubkeys: vec![format!("{}[]", array)],
+            threshold,
+        }),
+    ]
+}
+
+fn io_introspection() -> impl Strategy<Value = Expression> {
+    prop_oneof![
+        (boxed_operand(), select_string(INPUT_PROPERTIES))
+            .prop_map(|(index, property)| Expression::InputIntrospection { index, property }),
+        (boxed_operand(), select_string(OUTPUT_PROPERTIES))
+            .prop_map(|(index, property)| Expression::OutputIntrospection { index, property }),
+    ]
+}
+
+/// What an input or output is compared with.
+fn io_value() -> impl Strategy<Value = Expression> {
+    prop_oneof![
+        3 => operand(),
+        1 => io_introspection(),
+        1 => identifier().prop_map(|property| Expression::CurrentInput(Some(property))),
+        1 => this_property(),
+        1 => contract_instance(),
+    ]
+}
+
+/// What a group property is compared with.
+fn group_value() -> impl Strategy<Value = Expression> {
+    prop_oneof![
+        3 => operand(),
+        1 => group_property(),
+        1 => (group_property(), select_string(&["+", "-"]), operand()).prop_map(
+            |(left, op, right)| Expression::BinaryOp {
+                left: Box::new(left),
+                op,
+                right: Box::new(right),
+            }
+        ),
+        1 => asset_lookup(),
+        1 => asset_group(),
+    ]
+}
+
+impl Arbitrary for Requirement {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![
+            (
+                identifier(),
+                identifier(),
+                option::of(select(vec![0x00u8, 0x01, 0x02, 0x03, 0x81, 0x82, 0x83]))
+            )
+                .prop_map(|(signature, pubkey, sighash)| Requirement::CheckSig {
+                    signature,
+                    pubkey,
+                    sighash
+                }),
+            (identifier(), identifier(), identifier()).prop_map(|(signature, pubkey, message)| {
+                Requirement::CheckSigFromStack {
+                    signature,
+                    pubkey,
+                    message,
+                }
+            }),
+            multisig(),
+            identifier().prop_map(|var| Requirement::After {
+                locktime: 0,
+                timelock_var: Some(var),
+            }),
+            prop_oneof![
+                (0u64..=0xffff).prop_map(Duration::Blocks),
+                (1u64..100).prop_map(|hours| Duration::Seconds(hours * 3600)),
+                (1u64..100).prop_map(|days| Duration::Seconds(days * 86400)),
+            ]
+            .prop_map(|age| Requirement::Older {
+                age,
+                timelock_var: None,
+            }),
+            (
+                identifier(),
+                identifier(),
+                select(HashFunction::ALL.to_vec())
+            )
+                .prop_map(|(preimage, hash, function)| Requirement::HashEqual {
+                    preimage,
+                    hash,
+                    function
+                }),
+            (identifier(), 0u64..1000, 0u64..1000).prop_map(|(value, a, b)| {
+                Requirement::SizeBetween {
+                    value,
+                    min: a.min(b),
+                    max: a.max(b),
+                }
+            }),
+            comparison(operand(), OPERATORS, operand()),
+            truthy(identifier().prop_map(Expression::Variable)),
+            truthy(opcode_call()),
+            comparison(asset_lookup(), COMPARISONS, asset_amount()),
+            comparison(
+                select_string(TX_PROPERTIES)
+                    .prop_map(|property| Expression::TxIntrospection { property }),
+                COMPARISONS,
+                operand()
+            ),
+            comparison(io_introspection(), COMPARISONS, io_value()),
+            comparison(
+                prop_oneof![
+                    this_property(),
+                    identifier().prop_map(|property| Expression::CurrentInput(Some(property))),
+                ],
+                COMPARISONS,
+                operand()
+            ),
+            truthy(this_property()),
+            truthy(asset_group()),
+            truthy(
+                (boxed_operand(), select_string(&GROUP_PROPERTIES[4..])).prop_map(
+                    |(index, property)| Expression::GroupProperty {
+                        group: format!("assetGroups[{}]", print_expression(&index)),
+                        property,
+                    }
+                )
+            ),
+            comparison(group_property(), OPERATORS, group_value()),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for Statement {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let leaf = prop_oneof![
+            3 => any::<Requirement>().prop_map(|r| Statement::Require(r, Span::default())),
+            1 => (identifier(), any::<Expression>())
+                .prop_map(|(name, value)| Statement::LetBinding { name, value }),
+            1 => (identifier(), any::<Expression>())
+                .prop_map(|(name, value)| Statement::VarAssign { name, value }),
+        ];
+        leaf.prop_recursive(2, 12, 3, |inner| {
+            prop_oneof![
+                (
+                    any::<Expression>(),
+                    vec(inner.clone(), 0..3),
+                    option::of(vec(inner.clone(), 0..3)),
+                )
+                    .prop_map(|(condition, then_body, else_body)| {
+                        Statement::IfElse {
+                            condition,
+                            then_body,
+                            else_body,
+                            span: Span::default(),
+                        }
+                    }),
+                (identifier(), identifier(), identifier(), vec(inner, 0..3)).prop_map(
+                    |(index_var, value_var, iterable, body)| Statement::ForIn {
+                        index_var,
+                        value_var,
+                        iterable: Expression::Variable(iterable),
+                        body,
+                    }
+                ),
+            ]
+        })
+        .boxed()
+    }
+}
+
+impl Arbitrary for Parameter {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let suffix = prop_oneof![
+            3 => Just(String::new()),
+            1 => Just("[]".to_string()),
+            1 => (1u8..5).prop_map(|n| format!("[{}]", n)),
+        ];
+        (identifier(), select(BASE_TYPES), suffix)
+            .prop_map(|(name, base, suffix)| Parameter {
+                name,
+                param_type: format!("{}{}", base, suffix),
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for Trivia {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        // Text as the parser reads it back: no trailing space
+        let comment = || "([A-Za-z0-9][A-Za-z0-9 ,./]{0,16}[A-Za-z0-9])?";
+        (vec(comment(), 0..3), option::of(comment()))
+            .prop_map(|(leading, trailing)| Trivia { leading, trailing })
+            .boxed()
+    }
+}
+
+fn emit() -> impl Strategy<Value = Emit> {
+    (identifier(), vec(identifier(), 0..3)).prop_map(|(event, args)| Emit { event, args })
+}
+
+fn event() -> impl Strategy<Value = Event> {
+    (identifier(), vec(any::<Parameter>(), 0..3))
+        .prop_map(|(name, parameters)| Event { name, parameters })
+}
+
+impl Arbitrary for Function {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            identifier(),
+            vec(any::<Parameter>(), 0..3),
+            vec(any::<Statement>(), 0..4),
+            any::<bool>(),
+            vec(emit(), 0..2),
+            any::<Trivia>(),
+        )
+            .prop_map(
+                |(name, mut parameters, statements, is_internal, emits, trivia)| {
+                    // Internal functions are expanded like macros, whose
+                    // parameters must have different names
+                    if is_internal {
+                        let mut names = BTreeSet::new();
+                        parameters.retain(|p: &Parameter| names.insert(p.name.clone()));
+                    }
+                    Function {
+                        name,
+                        parameters,
+                        statements,
+                        is_internal,
+                        emits,
+                        trivia,
+                    }
+                },
+            )
+            .boxed()
+    }
+}
+
+/// A timelock option: a number of blocks, or a built-in constant.
+fn timelock() -> impl Strategy<Value = Option<Result<u64, String>>> {
+    option::of(prop_oneof![
+        (0u64..100_000).prop_map(Ok),
+        select(&CONSTANTS[..]).prop_map(|name| Err(name.to_string())),
+    ])
+}
+
+impl Arbitrary for Contract {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let options = (
+            option::of(identifier()),
+            timelock(),
+            timelock(),
+            option::of(identifier()),
+            any::<bool>(),
+            option::of(select(Edition::ALL.to_vec())),
+        );
+        (
+            "[A-Z][a-z]{1,6}",
+            btree_set(identifier(), 0..3),
+            vec("[a-z]{1,6}\\.ark", 0..2),
+            vec(any::<Parameter>(), 0..4),
+            vec(any::<Function>(), 1..4),
+            (
+                options,
+                btree_map("[A-Z][A-Z_]{1,6}", 0u64..100_000, 0..2),
+                vec(event(), 0..2),
+                any::<Trivia>(),
+            ),
+        )
+            .prop_map(
+                |(
+                    name,
+                    generics,
+                    imports,
+                    parameters,
+                    functions,
+                    (options, constants, events, trivia),
+                )| {
+                    let (server_key, renew, exit, internal_key, strict_hashlocks, edition) =
+                        options;
+                    let mut named_options = BTreeMap::new();
+                    let mut timelock =
+                        |option: &str, value: Option<Result<u64, String>>| match value? {
+                            Ok(blocks) => Some(blocks),
+                            Err(constant) => {
+                                named_options.insert(option.to_string(), constant);
+                                None
+                            }
+                        };
+                    let renewal_timelock = timelock("renew", renew);
+                    let exit_timelock = timelock("exit", exit);
+                    Contract {
+                        edition,
+                        compiler: None,
+                        name,
+                        generics: generics.into_iter().collect(),
+                        named_options,
+                        parameters,
+                        renewal_timelock,
+                        exit_timelock,
+                        has_server_key: server_key.is_some(),
+                        server_key,
+                        internal_key,
+                        strict_hashlocks,
+                        constants,
+                        events,
+                        functions,
+                        imports,
+                        trivia,
+                    }
+                },
+            )
+            .boxed()
+    }
+}
@@ -0,0 +1,9 @@
+//! Typed bindings generated from a compiled artifact.
+//!
+//! Frontends load the artifact JSON at runtime; the bindings describe the
+//! same contract statically, so passing a constructor argument or witness
+//! element of the wrong name or type is caught by the host language's
+//! compiler instead of at spend time.
+
+pub mod rust;
+pub mod typescript;
@@ -0,0 +1,278 @@
+//! Rust bindings (`arkadec --emit rust`), meant to be generated from
+//! `build.rs`:
+//!
+//! ```ignore
+//! let json = std::fs::read_to_string("contracts/htlc.json")?;
+//! let artifact: arkade_compiler::ContractJson = serde_json::from_str(&json)?;
+//! let out = std::path::Path::new(&std::env::var("OUT_DIR")?).join("htlc.rs");
+//! std::fs::write(out, arkade_compiler::bindings::rust::generate(&artifact))?;
+//! ```
+//!
+//! and pulled in with `include!(concat!(env!("OUT_DIR"), "/htlc.rs"))`.
+//!
+//! For a contract `C` the generated code defines a struct `C` holding the
+//! constructor arguments (and the server key, if a leaf needs it) and a
+//! struct `CSpend`. Every function becomes a method taking the witness
+//! elements of its cooperative path, plus a `_exit` method for the
+//! unilateral exit path; both return the leaf script with the contract's
+//! arguments linked in, and the encoded witness stack. `<VTXO:...>`
+//! placeholders and `let`-bound values are left in the script as written.
+//! The generated code has no dependencies.
+
+use std::fmt::Write;
+
+use crate::models::ContractJson;
+use crate::typechecker::ArkType;
+
+/// Placeholder of the server key in leaf scripts.
+const SERVER_KEY: &str = "SERVER_KEY";
+
+/// Render the bindings for `artifact`.
+pub fn generate(artifact: &ContractJson) -> String {
+    let name = &artifact.name;
+    let spend = format!("{}Spend", name);
+    let mut out = String::new();
+
+    let version = artifact
+        .compiler
+        .as_ref()
+        .map_or(env!("CARGO_PKG_VERSION"), |c| c.version.as_str());
+    let _ = writeln!(
+        out,
+        "// Rust bindings for the {} contract, generated by arkadec {}.",
+        name, version
+    );
+    out.push_str("// Do not edit: regenerate from the artifact.\n\n");
+
+    let needs_server_key = artifact
+        .functions
+        .iter()
+        .any(|f| f.asm.iter().any(|t| t == &format!("<{}>", SERVER_KEY)));
+    let needs_script_num = artifact
+        .functions
+        .iter()
+        .flat_map(|f| &f.witness_schema)
+        .any(|w| ArkType::parse(&w.elem_type) == ArkType::Int);
+
+    // Contract
+    let _ = writeln!(
+        out,
+        "/// The {} contract with its constructor arguments bound.",
+        name
+    );
+    out.push_str("#[allow(clippy::upper_case_acronyms)]\n");
+    out.push_str("#[derive(Debug, Clone, PartialEq, Eq)]\n");
+    let _ = writeln!(out, "pub struct {} {{", name);
+    for p in &artifact.parameters {
+        let _ = writeln!(
+            out,
+            "    pub {}: {},",
+            ident(&p.name),
+            owned_type(&p.param_type)
+        );
+    }
+    if needs_server_key {
+        out.push_str("    /// Key of the Ark server co-signing cooperative paths\n");
+        out.push_str("    pub server_key: [u8; 33],\n");
+    }
+    out.push_str("}\n\n");
+
+    // Spend
+    let _ = writeln!(
+        out,
+        "/// A leaf of {} and the witness that spends it.",
+        name
+    );
+    out.push_str("#[derive(Debug, Clone, PartialEq, Eq)]\n");
+    let _ = writeln!(out, "pub struct {} {{", spend);
+    out.push_str("    /// Leaf script as ASM, with the contract's arguments linked in\n");
+    out.push_str("    pub script: Vec<String>,\n");
+    out.push_str("    /// Witness stack elements, in witness schema order\n");
+    out.push_str("    pub witness: Vec<Vec<u8>>,\n");
+    out.push_str("}\n\n");
+
+    // Methods
+    out.push_str("#[allow(clippy::too_many_arguments)]\n");
+    let _ = writeln!(out, "impl {} {{", name);
+    for f in &artifact.functions {
+        let (method, path) = if f.server_variant {
+            (snake_case(&f.name), "cooperative path")
+        } else {
+            (
+                format!("{}_exit", snake_case(&f.name)),
+                "unilateral exit path",
+            )
+        };
+        let params: Vec<String> = f
+            .witness_schema
+            .iter()
+            .map(|w| format!("{}: {}", ident(&w.name), param_type(&w.elem_type)))
+            .collect();
+        let witness: Vec<String> = f
+            .witness_schema
+            .iter()
+            .map(|w| encode(&ident(&w.name), &w.elem_type))
+            .collect();
+        let asm: Vec<String> = f.asm.iter().map(|t| format!("{:?}", t)).collect();
+
+        let _ = writeln!(out, "    /// Spend `{}` through the {}.", f.name, path);
+        let _ = writeln!(
+            out,
+            "    pub fn {}(&self{}) -> {} {{",
+            ident(&method),
+            params
+                .iter()
+                .map(|p| format!(", {}", p))
+                .collect::<String>(),
+            spend
+        );
+        let _ = writeln!(out, "        {} {{", spend);
+        let _ = writeln!(out, "            script: self.link(&[{}]),", asm.join(", "));
+        let _ = writeln!(out, "            witness: vec![{}],", witness.join(", "));
+        out.push_str("        }\n");
+        out.push_str("    }\n\n");
+    }
+
+    // Linking
+    out.push_str("    fn link(&self, asm: &[&str]) -> Vec<String> {\n");
+    let mut arms: Vec<String> = artifact
+        .parameters
+        .iter()
+        .map(|p| {
+            let field = format!("self.{}", ident(&p.name));
+            format!("\"<{}>\" => {},", p.name, token(&field, &p.param_type))
+        })
+        .collect();
+    if needs_server_key {
+        arms.push(format!(
+            "\"<{}>\" => Self::hex(&self.server_key),",
+            SERVER_KEY
+        ));
+    }
+    if arms.is_empty() {
+        out.push_str("        asm.iter().map(|token| token.to_string()).collect()\n");
+    } else {
+        out.push_str("        asm.iter()\n");
+        out.push_str("            .map(|token| match *token {\n");
+        for arm in arms {
+            let _ = writeln!(out, "                {}", arm);
+        }
+        out.push_str("                _ => token.to_string(),\n");
+        out.push_str("            })\n");
+        out.push_str("            .collect()\n");
+    }
+    out.push_str("    }\n");
+
+    if artifact
+        .parameters
+        .iter()
+        .any(|p| !matches!(ArkType::parse(&p.param_type), ArkType::Int | ArkType::Bool))
+        || needs_server_key
+    {
+        out.push_str("\n    fn hex(bytes: &[u8]) -> String {\n");
+        out.push_str("        bytes.iter().map(|b| format!(\"{:02x}\", b)).collect()\n");
+        out.push_str("    }\n");
+    }
+    if needs_script_num {
+        out.push_str(SCRIPT_NUM);
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Minimal CScriptNum encoding, emitted when a witness element needs it.
+const SCRIPT_NUM: &str = "
+    fn script_num(n: i64) -> Vec<u8> {
+        let mut abs = n.unsigned_abs();
+        let mut bytes = Vec::new();
+        while abs > 0 {
+            bytes.push((abs & 0xff) as u8);
+            abs >>= 8;
+        }
+        match bytes.last_mut() {
+            Some(last) if *last & 0x80 != 0 => bytes.push(if n < 0 { 0x80 } else { 0 }),
+            Some(last) if n < 0 => *last |= 0x80,
+            _ => {}
+        }
+        bytes
+    }
+";
+
+/// Field type of a constructor argument.
+fn owned_type(ark_type: &str) -> &'static str {
+    match ArkType::parse(ark_type) {
+        ArkType::Pubkey => "[u8; 33]",
+        ArkType::Signature | ArkType::DataSig => "[u8; 64]",
+        ArkType::Bytes20 => "[u8; 20]",
+        ArkType::Bytes32 | ArkType::Asset => "[u8; 32]",
+        ArkType::Int => "i64",
+        ArkType::Bool => "bool",
+        ArkType::Uint64Le => "u64",
+        ArkType::Uint32Le => "u32",
+        _ => "Vec<u8>",
+    }
+}
+
+/// Parameter type of a witness element.
+fn param_type(ark_type: &str) -> &'static str {
+    match owned_type(ark_type) {
+        "Vec<u8>" => "&[u8]",
+        fixed => fixed,
+    }
+}
+
+/// Expression encoding witness element `name` as a stack element.
+fn encode(name: &str, ark_type: &str) -> String {
+    match ArkType::parse(ark_type) {
+        ArkType::Int => format!("Self::script_num({})", name),
+        ArkType::Bool => format!("if {} {{ vec![1] }} else {{ Vec::new() }}", name),
+        ArkType::Uint64Le | ArkType::Uint32Le => format!("{}.to_le_bytes().to_vec()", name),
+        _ => format!("{}.to_vec()", name),
+    }
+}
+
+/// Expression rendering the constructor argument `field` as an ASM token.
+fn token(field: &str, ark_type: &str) -> String {
+    match ArkType::parse(ark_type) {
+        ArkType::Int => format!("{}.to_string()", field),
+        ArkType::Bool => format!("(if {} {{ \"1\" }} else {{ \"0\" }}).to_string()", field),
+        ArkType::Uint64Le | ArkType::Uint32Le => format!("Self::hex(&{}.to_le_bytes())", field),
+        _ => format!("Self::hex(&{})", field),
+    }
+}
+
+/// `camelCase` to `snake_case`, escaping keywords.
+fn ident(name: &str) -> String {
+    let snake = snake_case(name);
+    if KEYWORDS.contains(&snake.as_str()) {
+        format!("r#{}", snake)
+    } else {
+        snake
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            if prev.is_ascii_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_ascii_uppercase() && next_lower)
+            {
+                out.push('_');
+            }
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+    "mut", "pub", "ref", "return", "static", "struct", "trait", "true", "type", "unsafe", "use",
+    "where", "while", "yield",
+];
@@ -0,0 +1,107 @@
+//! TypeScript declarations (`arkadec --emit dts`).
+//!
+//! For a contract `C` the declarations export:
+//!
+//! - `CConstructorInputs`, an interface keyed by constructor parameter
+//! - `CConstructorArgs`, the same parameters as a labeled tuple in artifact
+//!   order
+//! - `CFunctionInputs`, mapping each function name to the labeled tuple of
+//!   the `functionInputs` of its cooperative (server) variant
+//! - `CExitFunctionInputs`, the same for the unilateral exit variants, which
+//!   also take the signatures the server would otherwise provide
+//! - `CFunctionName`, the union of function names
+//!
+//! Byte strings, public keys and signatures are hex strings, as in the
+//! artifact and test suites. The output contains only types, so it is valid
+//! both as a `.d.ts` file and as a `.ts` module.
+
+use std::fmt::Write;
+
+use crate::models::{array_type, ContractJson};
+
+/// Render the declarations for `artifact`.
+pub fn generate(artifact: &ContractJson) -> String {
+    let name = &artifact.name;
+    let mut out = String::new();
+
+    let version = artifact
+        .compiler
+        .as_ref()
+        .map_or(env!("CARGO_PKG_VERSION"), |c| c.version.as_str());
+    let _ = writeln!(
+        out,
+        "// Type declarations for the {} contract, generated by arkadec {}.",
+        name, version
+    );
+    out.push_str("// Do not edit: regenerate with `arkadec --emit dts`.\n\n");
+
+    out.push_str("/** Hex-encoded x-only public key */\n");
+    out.push_str("export type PubKey = string;\n");
+    out.push_str("/** Hex-encoded 64-byte Schnorr signature */\n");
+    out.push_str("export type Signature = string;\n");
+    out.push_str("/** Hex-encoded byte string */\n");
+    out.push_str("export type Bytes = string;\n");
+    out.push_str("/** Script number or 64-bit amount */\n");
+    out.push_str("export type Int = bigint | number;\n\n");
+
+    let _ = writeln!(out, "export interface {}ConstructorInputs {{", name);
+    for p in &artifact.parameters {
+        let _ = writeln!(out, "  {}: {};", p.name, ts_type(&p.param_type));
+    }
+    out.push_str("}\n\n");
+
+    let args = artifact
+        .parameters
+        .iter()
+        .map(|p| (p.name.as_str(), p.param_type.as_str()));
+    let _ = writeln!(
+        out,
+        "export type {}ConstructorArgs = {};\n",
+        name,
+        tuple(args)
+    );
+
+    for (prefix, server_variant) in [("", true), ("Exit", false)] {
+        let _ = writeln!(out, "export interface {}{}FunctionInputs {{", name, prefix);
+        for f in &artifact.functions {
+            if f.server_variant != server_variant {
+                continue;
+            }
+            let inputs = f
+                .function_inputs
+                .iter()
+                .map(|i| (i.name.as_str(), i.param_type.as_str()));
+            let _ = writeln!(out, "  {}: {};", f.name, tuple(inputs));
+        }
+        out.push_str("}\n\n");
+    }
+
+    let _ = writeln!(
+        out,
+        "export type {0}FunctionName = keyof {0}FunctionInputs | keyof {0}ExitFunctionInputs;",
+        name
+    );
+    out
+}
+
+/// The TypeScript type of an Arkade Script type.
+fn ts_type(ark_type: &str) -> String {
+    if let Some((element, _)) = array_type(ark_type) {
+        return format!("{}[]", ts_type(element));
+    }
+    match ark_type {
+        "pubkey" => "PubKey",
+        "signature" | "schnorrsig" | "datasig" => "Signature",
+        "bool" => "boolean",
+        "int" | "uint32le" | "uint64le" => "Int",
+        _ => "Bytes",
+    }
+    .to_string()
+}
+
+fn tuple<'a>(elements: impl Iterator<Item = (&'a str, &'a str)>) -> String {
+    let elements: Vec<String> = elements
+        .map(|(name, ark_type)| format!("{}: {}", name, ts_type(ark_type)))
+        .collect();
+    format!("[{}]", elements.join(", "))
+}
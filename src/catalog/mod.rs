@@ -0,0 +1,703 @@
+//! The language's built-in names, for completion, hover and documentation.
+//!
+//! [`CATALOG`] lists every builtin function, transaction and contract
+//! property, asset group property, option key and constant, with a
+//! signature, a one-line description and the [`Extension`] it needs, if
+//! any. Editors query it for completions and hovers and documentation is
+//! generated from it, so they describe the same language the parser
+//! accepts; `tests/catalog_test.rs` keeps it in step with the grammar.
+
+use alloc::{string::String, vec::Vec};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use crate::parser::{Extension, ScriptTarget};
+
+/// What kind of name an [`Entry`] describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EntryKind {
+    /// A function such as `checkSig`
+    Builtin,
+    /// A `tx.*` or `this.*` property
+    TxProperty,
+    /// A property of an asset group, as in `group.sumInputs`
+    GroupProperty,
+    /// A key of the `options` block
+    Option,
+    /// A built-in constant such as `BLOCKS_PER_DAY`
+    Constant,
+}
+
+/// One built-in name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    pub kind: EntryKind,
+    /// As written in source, with `i`, `o`, `t` and `k` for indices
+    pub name: &'static str,
+    /// Parameters and result type, or the value type of a property
+    pub signature: &'static str,
+    pub doc: &'static str,
+    /// The grammar extension using it requires, or `None` for the core
+    pub extension: Option<Extension>,
+}
+
+impl Entry {
+    /// Whether contracts for `target` may use this name.
+    pub fn available_on(&self, target: ScriptTarget) -> bool {
+        self.extension
+            .is_none_or(|extension| target.supports(extension))
+    }
+
+    /// The targets this name is available on.
+    pub fn targets(&self) -> Vec<ScriptTarget> {
+        ScriptTarget::ALL
+            .into_iter()
+            .filter(|target| self.available_on(*target))
+            .collect()
+    }
+}
+
+/// Serialized with the targets the entry is available on, so consumers
+/// need not know which target supports which extension.
+impl Serialize for Entry {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut entry = serializer.serialize_struct("Entry", 6)?;
+        entry.serialize_field("kind", &self.kind)?;
+        entry.serialize_field("name", self.name)?;
+        entry.serialize_field("signature", self.signature)?;
+        entry.serialize_field("doc", self.doc)?;
+        match self.extension {
+            Some(extension) => entry.serialize_field("extension", &extension)?,
+            None => entry.skip_field("extension")?,
+        }
+        entry.serialize_field("targets", &self.targets())?;
+        entry.end()
+    }
+}
+
+/// The entry for `name`, as written in source.
+pub fn find(name: &str) -> Option<&'static Entry> {
+    CATALOG.iter().find(|entry| entry.name == name)
+}
+
+/// The entries of `kind`.
+pub fn of_kind(kind: EntryKind) -> impl Iterator<Item = &'static Entry> {
+    CATALOG.iter().filter(move |entry| entry.kind == kind)
+}
+
+/// The entries contracts for `target` may use.
+pub fn available_on(target: ScriptTarget) -> impl Iterator<Item = &'static Entry> {
+    CATALOG
+        .iter()
+        .filter(move |entry| entry.available_on(target))
+}
+
+/// The `tx.*` or `this.*` property `path` reads, whatever its indices and
+/// method arguments, as `tx.inputs[i].value` for `tx.inputs[0].value`.
+pub fn property(path: &str) -> Option<&'static Entry> {
+    let path = pattern(path);
+    of_kind(EntryKind::TxProperty).find(|entry| pattern(entry.name) == path)
+}
+
+/// Whether `path` reads a property, an asset group property, or something
+/// properties are read from, such as `tx.inputs[i]` or `tx.assetGroups`.
+pub fn is_property_path(path: &str) -> bool {
+    let path = pattern(path);
+    let under = |name: &str| {
+        let name = pattern(name);
+        name == path || name.starts_with(&path) && name[path.len()..].starts_with(['.', '['])
+    };
+    of_kind(EntryKind::TxProperty).any(|entry| under(entry.name))
+        || path
+            .strip_prefix("tx.assetGroups[].")
+            .is_some_and(|property| {
+                of_kind(EntryKind::GroupProperty).any(|entry| entry.name == property)
+            })
+}
+
+/// The entry of `kind` closest to the misspelt `name`, within two edits.
+pub fn suggest(kind: EntryKind, name: &str) -> Option<&'static Entry> {
+    let name = pattern(name);
+    of_kind(kind)
+        .map(|entry| (distance(&name, &pattern(entry.name)), entry))
+        .filter(|(edits, _)| *edits <= 2)
+        .min_by_key(|(edits, _)| *edits)
+        .map(|(_, entry)| entry)
+}
+
+/// `name` with the contents of brackets and method arguments left out.
+fn pattern(name: &str) -> String {
+    let mut pattern = String::with_capacity(name.len());
+    let mut depth = 0usize;
+    for c in name.chars() {
+        match c {
+            '[' | '(' => {
+                if depth == 0 && c == '[' {
+                    pattern.push(c);
+                }
+                depth += 1;
+            }
+            ']' | ')' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 && c == ']' {
+                    pattern.push(c);
+                }
+            }
+            _ if depth == 0 => pattern.push(c),
+            _ => {}
+        }
+    }
+    pattern
+}
+
+/// Levenshtein distance between `a` and `b`, counting characters.
+pub(crate) fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (above + 1)
+                .min(row[j] + 1)
+                .min(diagonal + usize::from(ca != cb));
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+const fn entry(
+    kind: EntryKind,
+    name: &'static str,
+    signature: &'static str,
+    doc: &'static str,
+    extension: Option<Extension>,
+) -> Entry {
+    Entry {
+        kind,
+        name,
+        signature,
+        doc,
+        extension,
+    }
+}
+
+use EntryKind::{Builtin, Constant, GroupProperty, Option as OptionKey, TxProperty};
+const ASSETS: Option<Extension> = Some(Extension::Assets);
+const INTROSPECTION: Option<Extension> = Some(Extension::Introspection);
+const OPCODES: Option<Extension> = Some(Extension::Opcodes);
+
+/// Every built-in name, grouped by kind.
+pub const CATALOG: &[Entry] = &[
+    // ─── Builtin Functions ─────────────────────────────────────────────────
+    entry(
+        Builtin,
+        "checkSig",
+        "checkSig(signature sig, pubkey key[, sighash flags]) -> bool",
+        "Checks a Schnorr signature of the spending transaction by `key`, optionally pinned to sighash flags such as `SIGHASH_SINGLE | ANYONECANPAY`.",
+        None,
+    ),
+    entry(
+        Builtin,
+        "checkMultisig",
+        "checkMultisig(pubkey[] keys, signature[] sigs | int threshold) -> bool",
+        "Checks a signature by every key, or by `threshold` of a `pubkey[]` parameter.",
+        None,
+    ),
+    entry(
+        Builtin,
+        "checkSigCount",
+        "checkSigCount(pubkey[] keys) -> int",
+        "Counts the keys with a valid signature, for quorum rules.",
+        None,
+    ),
+    entry(
+        Builtin,
+        "after",
+        "after(int timestamp)",
+        "Absolute timelock at a UNIX timestamp, at least 500000000 (OP_CHECKLOCKTIMEVERIFY).",
+        None,
+    ),
+    entry(
+        Builtin,
+        "sha256",
+        "sha256(bytes data) -> bytes32",
+        "SHA-256 of `data`; `sha256(preimage) == hash` is a hash lock.",
+        None,
+    ),
+    entry(
+        Builtin,
+        "hash256",
+        "hash256(bytes data) -> bytes32",
+        "SHA-256 of the SHA-256 of `data`, as a hash lock (OP_HASH256).",
+        None,
+    ),
+    entry(
+        Builtin,
+        "hash160",
+        "hash160(bytes data) -> bytes20",
+        "RIPEMD-160 of the SHA-256 of `data`, as a hash lock (OP_HASH160).",
+        None,
+    ),
+    entry(
+        Builtin,
+        "ripemd160",
+        "ripemd160(bytes data) -> bytes20",
+        "RIPEMD-160 of `data`, as a hash lock (OP_RIPEMD160).",
+        None,
+    ),
+    entry(
+        Builtin,
+        "size",
+        "size(bytes data) between int min and int max",
+        "Requires the size of `data` in bytes to be within the inclusive bounds.",
+        None,
+    ),
+    entry(
+        Builtin,
+        "checkSigFromStack",
+        "checkSigFromStack(signature sig, pubkey key, bytes message) -> bool",
+        "Checks a Schnorr signature of `message` by `key` (OP_CHECKSIGFROMSTACK).",
+        OPCODES,
+    ),
+    entry(
+        Builtin,
+        "checkSigFromStackVerify",
+        "checkSigFromStackVerify(signature sig, pubkey key, bytes message)",
+        "Like `checkSigFromStack`, failing the script if the signature is invalid.",
+        OPCODES,
+    ),
+    entry(
+        Builtin,
+        "sha256Initialize",
+        "sha256Initialize(bytes data) -> bytes32",
+        "Starts a streaming SHA-256 with `data`, returning its context.",
+        OPCODES,
+    ),
+    entry(
+        Builtin,
+        "sha256Update",
+        "sha256Update(bytes32 context, bytes chunk) -> bytes32",
+        "Adds `chunk` to a streaming SHA-256.",
+        OPCODES,
+    ),
+    entry(
+        Builtin,
+        "sha256Finalize",
+        "sha256Finalize(bytes32 context, bytes chunk) -> bytes32",
+        "Adds the last chunk to a streaming SHA-256 and returns the digest.",
+        OPCODES,
+    ),
+    entry(
+        Builtin,
+        "neg64",
+        "neg64(uint64le value) -> uint64le",
+        "Negates a 64-bit value (OP_NEG64).",
+        OPCODES,
+    ),
+    entry(
+        Builtin,
+        "le64ToScriptNum",
+        "le64ToScriptNum(uint64le value) -> int",
+        "Converts a 64-bit little-endian value to a script number.",
+        OPCODES,
+    ),
+    entry(
+        Builtin,
+        "le32ToLe64",
+        "le32ToLe64(uint32le value) -> uint64le",
+        "Widens a 32-bit little-endian value to 64 bits.",
+        OPCODES,
+    ),
+    entry(
+        Builtin,
+        "verifyScalarMul",
+        "verifyScalarMul(bytes32 k, pubkey P, pubkey Q) -> bool",
+        "Checks that `Q == k·P` (OP_ECMULSCALARVERIFY).",
+        OPCODES,
+    ),
+    entry(
+        Builtin,
+        "verifyTweak",
+        "verifyTweak(pubkey P, bytes32 t, pubkey Q) -> bool",
+        "Checks that `Q == P + t·G` (OP_TWEAKVERIFY), as in PTLCs.",
+        OPCODES,
+    ),
+    entry(
+        Builtin,
+        "ecMulScalarVerify",
+        "ecMulScalarVerify(bytes32 k, pubkey P, pubkey Q) -> bool",
+        "The original name of `verifyScalarMul`.",
+        OPCODES,
+    ),
+    entry(
+        Builtin,
+        "tweakVerify",
+        "tweakVerify(pubkey P, bytes32 t, pubkey Q) -> bool",
+        "The original name of `verifyTweak`.",
+        OPCODES,
+    ),
+    // ─── Transaction Properties ────────────────────────────────────────────
+    entry(
+        TxProperty,
+        "tx.time",
+        "int",
+        "Absolute timelock: `tx.time >= t` requires block height or time `t` (OP_CHECKLOCKTIMEVERIFY).",
+        None,
+    ),
+    entry(
+        TxProperty,
+        "tx.age",
+        "int",
+        "Relative timelock: `tx.age >= n` requires `n` blocks, or a duration such as `2 days`, since the input confirmed (OP_CHECKSEQUENCEVERIFY).",
+        None,
+    ),
+    entry(
+        TxProperty,
+        "tx.version",
+        "uint32le",
+        "Version of the spending transaction.",
+        INTROSPECTION,
+    ),
+    entry(
+        TxProperty,
+        "tx.locktime",
+        "uint32le",
+        "Locktime of the spending transaction.",
+        INTROSPECTION,
+    ),
+    entry(
+        TxProperty,
+        "tx.numInputs",
+        "int",
+        "Number of inputs of the spending transaction.",
+        INTROSPECTION,
+    ),
+    entry(
+        TxProperty,
+        "tx.numOutputs",
+        "int",
+        "Number of outputs of the spending transaction.",
+        INTROSPECTION,
+    ),
+    entry(
+        TxProperty,
+        "tx.weight",
+        "int",
+        "Weight of the spending transaction.",
+        INTROSPECTION,
+    ),
+    entry(
+        TxProperty,
+        "tx.inputs[i].value",
+        "uint64le",
+        "Amount in sats of input `i`.",
+        INTROSPECTION,
+    ),
+    entry(
+        TxProperty,
+        "tx.inputs[i].scriptPubKey",
+        "bytes",
+        "Script of the output input `i` spends.",
+        INTROSPECTION,
+    ),
+    entry(
+        TxProperty,
+        "tx.inputs[i].sequence",
+        "uint32le",
+        "Sequence number of input `i`.",
+        INTROSPECTION,
+    ),
+    entry(
+        TxProperty,
+        "tx.inputs[i].outpoint",
+        "bytes32",
+        "Outpoint input `i` spends.",
+        INTROSPECTION,
+    ),
+    entry(
+        TxProperty,
+        "tx.inputs[i].issuance",
+        "bytes",
+        "Asset issuance of input `i`.",
+        INTROSPECTION,
+    ),
+    entry(
+        TxProperty,
+        "tx.outputs[o].value",
+        "uint64le",
+        "Amount in sats of output `o`.",
+        INTROSPECTION,
+    ),
+    entry(
+        TxProperty,
+        "tx.outputs[o].scriptPubKey",
+        "bytes",
+        "Script of output `o`; compare it with `new Contract(...)` for covenants.",
+        INTROSPECTION,
+    ),
+    entry(
+        TxProperty,
+        "tx.outputs[o].nonce",
+        "bytes32",
+        "Nonce of output `o`.",
+        INTROSPECTION,
+    ),
+    entry(
+        TxProperty,
+        "tx.input.current.value",
+        "uint64le",
+        "Amount in sats of the input being spent.",
+        INTROSPECTION,
+    ),
+    entry(
+        TxProperty,
+        "tx.input.current.scriptPubKey",
+        "bytes",
+        "Script of the input being spent.",
+        INTROSPECTION,
+    ),
+    entry(
+        TxProperty,
+        "tx.input.current.sequence",
+        "uint32le",
+        "Sequence number of the input being spent.",
+        INTROSPECTION,
+    ),
+    entry(
+        TxProperty,
+        "tx.input.current.outpoint",
+        "bytes32",
+        "Outpoint of the input being spent.",
+        INTROSPECTION,
+    ),
+    entry(
+        TxProperty,
+        "this.activeInputIndex",
+        "int",
+        "Index of the input being spent.",
+        INTROSPECTION,
+    ),
+    entry(
+        TxProperty,
+        "this.activeBytecode",
+        "bytes",
+        "Script of the leaf being executed.",
+        INTROSPECTION,
+    ),
+    entry(
+        TxProperty,
+        "tx.inputs[i].assets.lookup",
+        "tx.inputs[i].assets.lookup(bytes32 assetId) -> uint64le",
+        "Amount of asset `assetId` in input `i`.",
+        ASSETS,
+    ),
+    entry(
+        TxProperty,
+        "tx.outputs[o].assets.lookup",
+        "tx.outputs[o].assets.lookup(bytes32 assetId) -> uint64le",
+        "Amount of asset `assetId` in output `o`.",
+        ASSETS,
+    ),
+    entry(
+        TxProperty,
+        "tx.inputs[i].assets.length",
+        "int",
+        "Number of assets in input `i`.",
+        ASSETS,
+    ),
+    entry(
+        TxProperty,
+        "tx.outputs[o].assets.length",
+        "int",
+        "Number of assets in output `o`.",
+        ASSETS,
+    ),
+    entry(
+        TxProperty,
+        "tx.inputs[i].assets[t].assetId",
+        "bytes32",
+        "ID of the `t`-th asset of input `i`.",
+        ASSETS,
+    ),
+    entry(
+        TxProperty,
+        "tx.inputs[i].assets[t].amount",
+        "uint64le",
+        "Amount of the `t`-th asset of input `i`.",
+        ASSETS,
+    ),
+    entry(
+        TxProperty,
+        "tx.outputs[o].assets[t].assetId",
+        "bytes32",
+        "ID of the `t`-th asset of output `o`.",
+        ASSETS,
+    ),
+    entry(
+        TxProperty,
+        "tx.outputs[o].assets[t].amount",
+        "uint64le",
+        "Amount of the `t`-th asset of output `o`.",
+        ASSETS,
+    ),
+    entry(
+        TxProperty,
+        "tx.assetGroups.find",
+        "tx.assetGroups.find(bytes32 assetId) -> int",
+        "Index of the asset group of `assetId`.",
+        ASSETS,
+    ),
+    entry(
+        TxProperty,
+        "tx.assetGroups.length",
+        "int",
+        "Number of asset groups in the transaction.",
+        ASSETS,
+    ),
+    entry(
+        TxProperty,
+        "tx.assetGroups[k]",
+        "asset group",
+        "The `k`-th asset group; iterate them with `for (k, group) in tx.assetGroups`.",
+        ASSETS,
+    ),
+    // ─── Asset Group Properties ────────────────────────────────────────────
+    entry(
+        GroupProperty,
+        "numInputs",
+        "int",
+        "Number of inputs carrying the group's asset.",
+        ASSETS,
+    ),
+    entry(
+        GroupProperty,
+        "numOutputs",
+        "int",
+        "Number of outputs carrying the group's asset.",
+        ASSETS,
+    ),
+    entry(
+        GroupProperty,
+        "sumInputs",
+        "uint64le",
+        "Total amount of the group's asset in the inputs.",
+        ASSETS,
+    ),
+    entry(
+        GroupProperty,
+        "sumOutputs",
+        "uint64le",
+        "Total amount of the group's asset in the outputs.",
+        ASSETS,
+    ),
+    entry(
+        GroupProperty,
+        "delta",
+        "uint64le",
+        "`sumOutputs - sumInputs`: the amount minted, or burned if negative.",
+        ASSETS,
+    ),
+    entry(
+        GroupProperty,
+        "control",
+        "bytes32",
+        "ID of the asset controlling issuance of the group's asset.",
+        ASSETS,
+    ),
+    entry(
+        GroupProperty,
+        "metadataHash",
+        "bytes32",
+        "Hash of the group's asset metadata.",
+        ASSETS,
+    ),
+    entry(
+        GroupProperty,
+        "assetId",
+        "bytes32",
+        "ID of the group's asset.",
+        ASSETS,
+    ),
+    entry(
+        GroupProperty,
+        "isFresh",
+        "bool",
+        "Whether the asset is issued by the spending transaction.",
+        ASSETS,
+    ),
+    // ─── Options ───────────────────────────────────────────────────────────
+    entry(
+        OptionKey,
+        "server",
+        "server = name",
+        "Names the Ark operator key, injected at runtime; adds the cooperative path.",
+        None,
+    ),
+    entry(
+        OptionKey,
+        "exit",
+        "exit = blocks",
+        "Unilateral exit timelock in blocks or a duration such as 48h (default 288).",
+        None,
+    ),
+    entry(
+        OptionKey,
+        "renew",
+        "renew = blocks",
+        "Cooperative renewal timelock in blocks or a duration such as 7 days.",
+        None,
+    ),
+    entry(
+        OptionKey,
+        "internalKey",
+        "internalKey = unspendable | nums | key",
+        "Taproot internal key, for contracts spent only through their leaves.",
+        None,
+    ),
+    entry(
+        OptionKey,
+        "strictHashlocks",
+        "strictHashlocks = true | false",
+        "Whether hash locks also require a 32-byte preimage (default false).",
+        None,
+    ),
+    // ─── Constants ─────────────────────────────────────────────────────────
+    entry(
+        Constant,
+        "BLOCKS_PER_HOUR",
+        "int",
+        "Blocks per hour on the target network: 6, or 120 on mutinynet.",
+        None,
+    ),
+    entry(
+        Constant,
+        "BLOCKS_PER_DAY",
+        "int",
+        "24 × BLOCKS_PER_HOUR.",
+        None,
+    ),
+    entry(
+        Constant,
+        "BLOCKS_PER_WEEK",
+        "int",
+        "7 × BLOCKS_PER_DAY.",
+        None,
+    ),
+    entry(
+        Constant,
+        "BLOCKS_PER_MONTH",
+        "int",
+        "30 × BLOCKS_PER_DAY.",
+        None,
+    ),
+    entry(
+        Constant,
+        "DUST_LIMIT",
+        "int",
+        "330 sats, the dust limit of a taproot output.",
+        None,
+    ),
+];
@@ -0,0 +1,116 @@
+//! Lowering of `new` constructors.
+//!
+//! `new Name(args)` compiles to a `<VTXO:Name(<arg>,...)>` template: the
+//! scriptPubKey of contract `Name` instantiated with the arguments, which
+//! the linker or the runtime fills in once they are known. A contract
+//! re-creating itself with its own constructor parameters, the usual
+//! recursive covenant, cannot be templated that way: its scriptPubKey
+//! commits to the leaf that checks it. It is the scriptPubKey of the input
+//! being spent, though, so [`lower_self_references`] reads it from there
+//! instead. `new P2TR(key)` and `new P2TR(key, merkleRoot)` are computed
+//! when the contract is linked ([`p2tr_template`]).
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::models::{Contract, Function, Statement};
+use crate::opcodes::{OP_INSPECTINPUTSCRIPTPUBKEY, OP_PUSHCURRENTINPUTINDEX};
+
+/// Replace the templates in `asm` that re-create `contract` with its own
+/// constructor parameters, in order, by the current input's scriptPubKey.
+///
+/// Nothing is replaced in a function that declares a name of one of the
+/// parameters, since its template would then refer to something else.
+pub(super) fn lower_self_references(
+    asm: &mut Vec<String>,
+    contract: &Contract,
+    function: &Function,
+) {
+    let shadowed = contract.parameters.iter().any(|p| {
+        function.parameters.iter().any(|f| f.name == p.name) || binds(&function.statements, &p.name)
+    });
+    if shadowed {
+        return;
+    }
+    let args: Vec<String> = contract
+        .parameters
+        .iter()
+        .map(|p| format!("<{}>", p.name))
+        .collect();
+    let template = format!("<VTXO:{}({})>", contract.name, args.join(","));
+    let mut lowered = Vec::with_capacity(asm.len());
+    for token in asm.drain(..) {
+        if token == template {
+            lowered.push(OP_PUSHCURRENTINPUTINDEX.to_string());
+            lowered.push(OP_INSPECTINPUTSCRIPTPUBKEY.to_string());
+        } else {
+            lowered.push(token);
+        }
+    }
+    *asm = lowered;
+}
+
+/// Whether `statements` bind or assign `name`.
+fn binds(statements: &[Statement], name: &str) -> bool {
+    statements.iter().any(|statement| match statement {
+        Statement::LetBinding { name: bound, .. } | Statement::VarAssign { name: bound, .. } => {
+            bound == name
+        }
+        Statement::IfElse {
+            then_body,
+            else_body,
+            ..
+        } => binds(then_body, name) || else_body.as_deref().is_some_and(|b| binds(b, name)),
+        Statement::ForIn {
+            index_var,
+            value_var,
+            body,
+            ..
+        } => index_var == name || value_var == name || binds(body, name),
+        Statement::Require(..) => false,
+    })
+}
+
+/// The scriptPubKey a `<VTXO:P2TR(...)>` template stands for, given the
+/// bytes of its arguments: an internal key, 33-byte compressed or x-only,
+/// and optionally the 32-byte merkle root of its script tree. `None` if
+/// `template` is no P2TR template or an argument is unknown.
+#[cfg(feature = "std")]
+pub(crate) fn p2tr_template(
+    template: &str,
+    mut arg: impl FnMut(&str) -> Option<Vec<u8>>,
+) -> Option<Result<Vec<u8>, String>> {
+    let args = template.strip_prefix("VTXO:P2TR(")?.strip_suffix(')')?;
+    let args: Vec<Vec<u8>> = args
+        .split(',')
+        .map(
+            |a| match a.strip_prefix('<').and_then(|a| a.strip_suffix('>')) {
+                Some(name) => arg(name),
+                None => hex::decode(a.strip_prefix("0x")?).ok(),
+            },
+        )
+        .collect::<Option<_>>()?;
+    let key = args.first()?;
+    let key = if key.len() == 33 { &key[1..] } else { key };
+    let key: [u8; 32] = match key.try_into() {
+        Ok(key) => key,
+        Err(_) => return Some(Err("P2TR takes a 32-byte internal key".to_string())),
+    };
+    let merkle_root = match args.get(1) {
+        Some(root) => match <[u8; 32]>::try_from(root.as_slice()) {
+            Ok(root) => Some(root),
+            Err(_) => return Some(Err("P2TR takes a 32-byte merkle root".to_string())),
+        },
+        None => None,
+    };
+    if args.len() > 2 {
+        return Some(Err("P2TR takes a key and at most a merkle root".to_string()));
+    }
+    Some(crate::taproot::p2tr_script_pubkey(
+        &key,
+        merkle_root.as_ref(),
+    ))
+}
@@ -0,0 +1,116 @@
+//! A compiler kept across compilations.
+//!
+//! [`compile_with_options`](super::compile_with_options) starts from
+//! nothing each call. A long-running service compiling the same sources
+//! again and again, such as a language server recompiling on every
+//! keystroke or a backend serving artifacts, keeps a [`Compiler`] instead:
+//! it holds the options once and remembers the outcome of recent sources,
+//! so a source it has seen is answered without parsing it again. The
+//! grammar is compiled into the parser, so there is nothing else to set
+//! up.
+//!
+//! A `Compiler` is `Send + Sync`; share it between threads behind an
+//! `Arc` and call [`Compiler::compile`] from any of them.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use sha2::{Digest, Sha256};
+
+use super::{compile_with_options, CompileOptions};
+use crate::models::{CompileError, ContractJson};
+
+/// Sources a [`Compiler`] remembers by default.
+pub const DEFAULT_CACHE_CAPACITY: usize = 256;
+
+/// Compiles sources with fixed options, remembering recent outcomes.
+#[derive(Debug)]
+pub struct Compiler {
+    options: CompileOptions,
+    capacity: usize,
+    cache: Mutex<Cache>,
+}
+
+/// Outcomes by SHA-256 of the source, with the order they were added in
+/// for eviction.
+#[derive(Debug, Default)]
+struct Cache {
+    outcomes: HashMap<[u8; 32], Result<ContractJson, CompileError>>,
+    order: VecDeque<[u8; 32]>,
+}
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Compiler::new(CompileOptions::default())
+    }
+}
+
+impl Compiler {
+    /// A compiler applying `options` to every source.
+    pub fn new(options: CompileOptions) -> Self {
+        Compiler {
+            options,
+            capacity: DEFAULT_CACHE_CAPACITY,
+            cache: Mutex::new(Cache::default()),
+        }
+    }
+
+    /// Remember at most `capacity` sources, forgetting the oldest first;
+    /// 0 turns the cache off.
+    pub fn cache_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// The options every source is compiled with.
+    pub fn options(&self) -> &CompileOptions {
+        &self.options
+    }
+
+    /// Compile `source`, or return the outcome of its last compilation.
+    /// A remembered artifact gets a fresh `updatedAt` from the clock.
+    pub fn compile(&self, source: &str) -> Result<ContractJson, CompileError> {
+        let key: [u8; 32] = Sha256::digest(source.as_bytes()).into();
+        if let Some(outcome) = self.lock().outcomes.get(&key) {
+            return outcome.clone().map(|mut artifact| {
+                artifact.updated_at = self.options.timestamp();
+                artifact
+            });
+        }
+
+        // Compiled without the lock, so other threads are not held up
+        let outcome = compile_with_options(source, &self.options);
+        if self.capacity > 0 {
+            let mut cache = self.lock();
+            if cache.outcomes.insert(key, outcome.clone()).is_none() {
+                cache.order.push_back(key);
+            }
+            while cache.order.len() > self.capacity {
+                if let Some(oldest) = cache.order.pop_front() {
+                    cache.outcomes.remove(&oldest);
+                }
+            }
+        }
+        outcome
+    }
+
+    /// Number of sources remembered.
+    pub fn cached(&self) -> usize {
+        self.lock().outcomes.len()
+    }
+
+    /// Forget every remembered source.
+    pub fn clear_cache(&self) {
+        let mut cache = self.lock();
+        cache.outcomes.clear();
+        cache.order.clear();
+    }
+
+    /// The cache, even if a thread panicked holding it: it is only ever
+    /// left with complete entries.
+    fn lock(&self) -> std::sync::MutexGuard<'_, Cache> {
+        self.cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
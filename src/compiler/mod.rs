@@ -1,30 +1,171 @@
+use crate::analysis;
+use crate::constants;
+use crate::encoding;
+use crate::ir;
+use crate::ir::limits::ScriptLimits;
+use crate::ir::resources::{self, ResourceBudget};
+use crate::legacy;
 use crate::models::{
-    AbiFunction, AssetLookupSource, CompilerInfo, ContractJson, Expression, Function,
-    FunctionInput, GroupIOSource, GroupSumSource, RequireStatement, Requirement, Statement,
-    WitnessElement, DEFAULT_ARRAY_LENGTH,
+    array_type, multisig_keys, selector, span_at, variant_label, AbiEmit, AbiEvent, AbiFunction,
+    AssetLookupSource, CompileError, CompilerInfo, Contract, ContractJson, Duration, Expression,
+    Function, FunctionInput, GroupIOSource, GroupSumSource, HashFunction, LeafMetrics,
+    OptimizationReport, RequireStatement, Requirement, SpendGraph, Statement, WitnessElement,
+    DEFAULT_ARRAY_LENGTH,
 };
 use crate::opcodes::{
-    OP_0, OP_1, OP_1NEGATE, OP_ADD64, OP_CHECKLOCKTIMEVERIFY, OP_CHECKSEQUENCEVERIFY, OP_CHECKSIG,
-    OP_CHECKSIGADD, OP_CHECKSIGFROMSTACK, OP_CHECKSIGFROMSTACKVERIFY, OP_CHECKSIGVERIFY, OP_DIV64,
-    OP_DROP, OP_DUP, OP_ECMULSCALARVERIFY, OP_ELSE, OP_ENDIF, OP_EQUAL, OP_FALSE,
-    OP_FINDASSETGROUPBYASSETID, OP_GREATERTHAN, OP_GREATERTHAN64, OP_GREATERTHANOREQUAL,
-    OP_GREATERTHANOREQUAL64, OP_IF, OP_INPUTBYTECODE, OP_INPUTOUTPOINT, OP_INPUTSEQUENCE,
-    OP_INPUTVALUE, OP_INSPECTASSETGROUP, OP_INSPECTASSETGROUPASSETID, OP_INSPECTASSETGROUPCTRL,
-    OP_INSPECTASSETGROUPMETADATAHASH, OP_INSPECTASSETGROUPNUM, OP_INSPECTASSETGROUPSUM,
-    OP_INSPECTINASSETAT, OP_INSPECTINASSETCOUNT, OP_INSPECTINASSETLOOKUP, OP_INSPECTINPUTISSUANCE,
-    OP_INSPECTINPUTOUTPOINT, OP_INSPECTINPUTSCRIPTPUBKEY, OP_INSPECTINPUTSEQUENCE,
-    OP_INSPECTINPUTVALUE, OP_INSPECTLOCKTIME, OP_INSPECTNUMASSETGROUPS, OP_INSPECTNUMINPUTS,
-    OP_INSPECTNUMOUTPUTS, OP_INSPECTOUTASSETAT, OP_INSPECTOUTASSETCOUNT, OP_INSPECTOUTASSETLOOKUP,
-    OP_INSPECTOUTPUTNONCE, OP_INSPECTOUTPUTSCRIPTPUBKEY, OP_INSPECTOUTPUTVALUE, OP_INSPECTVERSION,
-    OP_LE32TOLE64, OP_LE64TOSCRIPTNUM, OP_LESSTHAN, OP_LESSTHAN64, OP_LESSTHANOREQUAL,
-    OP_LESSTHANOREQUAL64, OP_MUL64, OP_NEG64, OP_NIP, OP_NOT, OP_NUMEQUAL,
-    OP_PUSHCURRENTINPUTINDEX, OP_SCRIPTNUMTOLE64, OP_SHA256, OP_SHA256FINALIZE,
-    OP_SHA256INITIALIZE, OP_SHA256UPDATE, OP_SUB64, OP_TWEAKVERIFY, OP_TXHASH, OP_TXWEIGHT,
-    OP_VERIFY,
+    OP_0, OP_1, OP_1NEGATE, OP_ADD64, OP_BOOLAND, OP_BOOLOR, OP_CAT, OP_CHECKLOCKTIMEVERIFY,
+    OP_CHECKSEQUENCEVERIFY, OP_CHECKSIG, OP_CHECKSIGADD, OP_CHECKSIGFROMSTACK,
+    OP_CHECKSIGFROMSTACKVERIFY, OP_CHECKSIGVERIFY, OP_DIV64, OP_DROP, OP_DUP, OP_ECMULSCALARVERIFY,
+    OP_ELSE, OP_ENDIF, OP_EQUAL, OP_EQUALVERIFY, OP_FINDASSETGROUPBYASSETID, OP_GREATERTHAN,
+    OP_GREATERTHAN64, OP_GREATERTHANOREQUAL, OP_GREATERTHANOREQUAL64, OP_IF, OP_INSPECTASSETGROUP,
+    OP_INSPECTASSETGROUPASSETID, OP_INSPECTASSETGROUPCTRL, OP_INSPECTASSETGROUPMETADATAHASH,
+    OP_INSPECTASSETGROUPNUM, OP_INSPECTASSETGROUPSUM, OP_INSPECTINASSETAT, OP_INSPECTINASSETCOUNT,
+    OP_INSPECTINASSETLOOKUP, OP_INSPECTINPUTISSUANCE, OP_INSPECTINPUTOUTPOINT,
+    OP_INSPECTINPUTSCRIPTPUBKEY, OP_INSPECTINPUTSEQUENCE, OP_INSPECTINPUTVALUE, OP_INSPECTLOCKTIME,
+    OP_INSPECTNUMASSETGROUPS, OP_INSPECTNUMINPUTS, OP_INSPECTNUMOUTPUTS, OP_INSPECTOUTASSETAT,
+    OP_INSPECTOUTASSETCOUNT, OP_INSPECTOUTASSETLOOKUP, OP_INSPECTOUTPUTNONCE,
+    OP_INSPECTOUTPUTSCRIPTPUBKEY, OP_INSPECTOUTPUTVALUE, OP_INSPECTVERSION, OP_LE32TOLE64,
+    OP_LE64TOSCRIPTNUM, OP_LESSTHAN, OP_LESSTHAN64, OP_LESSTHANOREQUAL, OP_LESSTHANOREQUAL64,
+    OP_MUL64, OP_NEG64, OP_NIP, OP_NOT, OP_NUMEQUAL, OP_PUSHCURRENTINPUTINDEX, OP_ROT,
+    OP_SCRIPTNUMTOLE64, OP_SHA256FINALIZE, OP_SHA256INITIALIZE, OP_SHA256UPDATE, OP_SIZE, OP_SUB64,
+    OP_TWEAKVERIFY, OP_TXHASH, OP_TXWEIGHT, OP_VERIFY, OP_WITHIN,
 };
-use crate::parser;
+use crate::parser::{self, ParseError, ParseLimits, ParseOptions, Rule, ScriptTarget};
+use crate::printer;
+use crate::resolver;
+use crate::taproot::{self, Network};
 use crate::typechecker::{self, ArkType};
-use chrono::Utc;
+use alloc::collections::BTreeMap;
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use sha2::{Digest, Sha256};
+
+mod covenant;
+#[cfg(feature = "std")]
+mod instance;
+mod operations;
+mod script;
+mod size;
+mod stack;
+mod template;
+
+#[cfg(feature = "std")]
+pub(crate) use covenant::p2tr_template;
+#[cfg(feature = "std")]
+pub use instance::{Compiler, DEFAULT_CACHE_CAPACITY};
+pub(crate) use operations::operations;
+#[cfg(feature = "std")]
+pub(crate) use operations::{operation, push};
+pub use script::assemble;
+pub(crate) use script::script_hex;
+
+/// Options controlling code generation.
+#[derive(Debug, Clone, Default)]
+pub struct CompileOptions {
+    /// Drop dead requirements and branches, run the IR optimization
+    /// pipeline over every generated leaf and report the script size of
+    /// each before and after.
+    pub optimize: bool,
+    /// Run only the peephole pass of the IR optimizer over every leaf, and
+    /// report sizes the same way; implied by `optimize`.
+    pub peephole: bool,
+    /// Per-leaf resource limits. Compilation fails if any path through a
+    /// generated leaf exceeds them.
+    pub budget: ResourceBudget,
+    /// Standardness limits on the size and opcodes of every generated leaf.
+    /// Compilation fails if a leaf exceeds them; [`ScriptLimits::NONE`]
+    /// lifts them.
+    pub script_limits: ScriptLimits,
+    /// Source of the artifact's `updatedAt` timestamp.
+    pub clock: Clock,
+    /// Values for the contract's generic parameters, e.g. `N = 5` for
+    /// `contract Escrow<N>(pubkey[N] parties)`.
+    pub generics: BTreeMap<String, usize>,
+    /// Network that built-in constants such as `BLOCKS_PER_DAY` are
+    /// resolved for.
+    pub network: Network,
+    /// Script flavour the contract is written for. Syntax from a grammar
+    /// extension the target lacks is a parse error.
+    pub script_target: ScriptTarget,
+    /// Limits on the size and nesting of the source.
+    pub parse_limits: ParseLimits,
+    /// Accept unknown names in the `options` block, ignoring them, as
+    /// before options were checked strictly.
+    pub lenient_options: bool,
+    /// Embed the source as [`printer::print`] writes it, without comments
+    /// or layout, and its hash as `sourceHash`, so that two ways of writing
+    /// the same contract give the same artifact.
+    pub normalize_source: bool,
+    /// Fail if the contract compiles with warnings, with the warnings as
+    /// the error.
+    pub deny_warnings: bool,
+    /// List at most this many problems in an error, counting the rest.
+    pub max_errors: Option<usize>,
+    /// Accept TapLang sources, translating their multisig form; see
+    /// [`legacy`](crate::legacy).
+    pub legacy_taplang: bool,
+    /// Make the artifact depend on the source and these options alone:
+    /// `updatedAt` comes from `SOURCE_DATE_EPOCH`, or is left out when it
+    /// is unset, whatever `clock` says. Write the artifact with
+    /// [`ContractJson::to_reproducible_json`] to sort its keys as well.
+    pub reproducible: bool,
+}
+
+impl CompileOptions {
+    /// The `updatedAt` of an artifact compiled with these options.
+    pub(crate) fn timestamp(&self) -> Option<String> {
+        match self.reproducible {
+            #[cfg(feature = "std")]
+            true => Clock::SourceDateEpoch.timestamp(),
+            #[cfg(not(feature = "std"))]
+            true => None,
+            false => self.clock.timestamp(),
+        }
+    }
+}
+
+/// Where the `updatedAt` timestamp of an artifact comes from. Defaults to
+/// the system clock, or to no timestamp without `std`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Clock {
+    /// The current time, as RFC 3339
+    #[cfg(feature = "std")]
+    #[default]
+    System,
+    /// A fixed timestamp, written as given, for reproducible builds and
+    /// snapshot tests
+    Fixed(String),
+    /// The time `SOURCE_DATE_EPOCH` gives in seconds since 1970, as RFC
+    /// 3339, or no timestamp when it is unset or not a number
+    #[cfg(feature = "std")]
+    SourceDateEpoch,
+    /// No timestamp: `updatedAt` is left out
+    #[cfg_attr(not(feature = "std"), default)]
+    None,
+}
+
+impl Clock {
+    /// The timestamp to record, if any.
+    pub fn timestamp(&self) -> Option<String> {
+        match self {
+            #[cfg(feature = "std")]
+            Clock::System => Some(chrono::Utc::now().to_rfc3339()),
+            #[cfg(feature = "std")]
+            Clock::SourceDateEpoch => std::env::var("SOURCE_DATE_EPOCH")
+                .ok()
+                .and_then(|seconds| seconds.trim().parse::<i64>().ok())
+                .and_then(|seconds| chrono::DateTime::from_timestamp(seconds, 0))
+                .map(|time| time.to_rfc3339()),
+            Clock::Fixed(timestamp) => Some(timestamp.clone()),
+            Clock::None => None,
+        }
+    }
+}
 
 // ─── Introspection Detection ────────────────────────────────────────────────────
 //
@@ -33,31 +174,28 @@ use chrono::Utc;
 // instead of the normal user sig + timelock pattern.
 
 /// Check if a function uses any introspection opcodes
-fn function_uses_introspection(function: &Function) -> bool {
-    function
-        .statements
-        .iter()
-        .any(|s| statement_uses_introspection(s))
+pub(crate) fn function_uses_introspection(function: &Function) -> bool {
+    function.statements.iter().any(statement_uses_introspection)
 }
 
 /// Check if a statement uses introspection
 fn statement_uses_introspection(stmt: &Statement) -> bool {
     match stmt {
-        Statement::Require(req) => requirement_uses_introspection(req),
+        Statement::Require(req, _) => requirement_uses_introspection(req),
         Statement::IfElse {
             condition,
             then_body,
             else_body,
+            ..
         } => {
             expression_uses_introspection(condition)
-                || then_body.iter().any(|s| statement_uses_introspection(s))
+                || then_body.iter().any(statement_uses_introspection)
                 || else_body
                     .as_ref()
-                    .map_or(false, |b| b.iter().any(|s| statement_uses_introspection(s)))
+                    .is_some_and(|b| b.iter().any(statement_uses_introspection))
         }
         Statement::ForIn { iterable, body, .. } => {
-            expression_uses_introspection(iterable)
-                || body.iter().any(|s| statement_uses_introspection(s))
+            expression_uses_introspection(iterable) || body.iter().any(statement_uses_introspection)
         }
         Statement::LetBinding { value, .. } | Statement::VarAssign { value, .. } => {
             expression_uses_introspection(value)
@@ -108,7 +246,9 @@ fn expression_uses_introspection(expr: &Expression) -> bool {
             context,
             last_chunk,
         } => expression_uses_introspection(context) || expression_uses_introspection(last_chunk),
-        Expression::Neg64 { value } => expression_uses_introspection(value),
+        Expression::Neg64 { value } | Expression::Not { value } => {
+            expression_uses_introspection(value)
+        }
         Expression::Le64ToScriptNum { value } => expression_uses_introspection(value),
         Expression::Le32ToLe64 { value } => expression_uses_introspection(value),
         Expression::EcMulScalarVerify {
@@ -139,6 +279,7 @@ fn expression_uses_introspection(expr: &Expression) -> bool {
         Expression::Literal(_) => false,
         Expression::ArrayLength(_) => false,
         Expression::CheckSigExpr { .. } => false,
+        Expression::CheckSigCount { .. } => false,
         Expression::CheckSigFromStackExpr { .. } => false,
         Expression::CheckSigFromStackVerify { .. } => false,
     }
@@ -192,24 +333,101 @@ fn strip_comments(source: &str) -> String {
 ///
 /// # Returns
 ///
-/// A Result containing a ContractJson or an error message
-pub fn compile(source_code: &str) -> Result<ContractJson, String> {
-    let contract = match parser::parse(source_code) {
-        Ok(contract) => contract,
-        Err(e) => return Err(format!("Parse error: {}", e)),
+/// A Result containing a ContractJson or a [`CompileError`], which
+/// displays as the error message
+pub fn compile(source_code: &str) -> Result<ContractJson, CompileError> {
+    compile_with_options(source_code, &CompileOptions::default())
+}
+
+/// Compiles an Arkade Script contract with explicit [`CompileOptions`].
+pub fn compile_with_options(
+    source_code: &str,
+    options: &CompileOptions,
+) -> Result<ContractJson, CompileError> {
+    let parse_options = ParseOptions {
+        target: options.script_target,
+        limits: options.parse_limits,
+        lenient_options: options.lenient_options,
+    };
+    let contract = parser::parse_with_options(source_code, &parse_options)
+        .map_err(|e| parse_error(source_code, e.as_ref()))?;
+    compile_contract(source_code, contract, options)
+        .map_err(|message| CompileError::compile(source_code, message))
+}
+
+/// The [`CompileError`] for `error`, which parsing `source` failed with.
+pub fn parse_error(source: &str, error: &(dyn core::error::Error + 'static)) -> CompileError {
+    if let Some(error) = error.downcast_ref::<ParseError>() {
+        return CompileError::parse(source, error.message.clone(), error.span);
+    }
+    let Some(error) = error.downcast_ref::<pest::error::Error<Rule>>() else {
+        return CompileError::parse(source, error.to_string(), None);
+    };
+    let (line, column) = match error.line_col {
+        pest::error::LineColLocation::Pos(pos) | pest::error::LineColLocation::Span(pos, _) => pos,
     };
+    CompileError::parse(
+        source,
+        error.variant.message(),
+        span_at(source, line, column),
+    )
+}
+
+fn compile_contract(
+    source_code: &str,
+    mut contract: Contract,
+    options: &CompileOptions,
+) -> Result<ContractJson, String> {
+    if options.legacy_taplang {
+        legacy::translate(&mut contract)?;
+    }
+
+    let (source, source_hash) = if options.normalize_source {
+        let source = printer::print(&contract.without_trivia());
+        let hash = hex::encode(Sha256::digest(source.as_bytes()));
+        (source, Some(hash))
+    } else {
+        (strip_comments(source_code), None)
+    };
+
+    // Fix the generic parameters, if any, giving every array its length,
+    // and put in the values of built-in constants
+    let mut contract = contract.specialize(&options.generics)?;
+    constants::resolve(&mut contract, options.network)?;
+    resolver::check(&contract)?;
+    typechecker::check_server_key(&contract)?;
+    typechecker::check_properties(&contract)?;
+    typechecker::check_events(&contract)?;
+    typechecker::check_types(&contract)?;
+    analysis::check_removed(&contract)?;
 
     // ── Type checking ──────────────────────────────────────────────────────
-    // Run the type checker. Errors are non-fatal and returned as warnings on
-    // ContractJson so callers (CLI, WASM, tests) can surface them as they see fit.
+    // Run the type checker. The fatal errors failed the compilation above;
+    // the rest are returned as warnings on ContractJson so callers (CLI,
+    // WASM, tests) can surface them as they see fit.
     let type_errors = typechecker::check_contract(&contract);
-    let warnings: Vec<String> = type_errors
+    let mut warnings: Vec<String> = type_errors
         .iter()
+        .filter(|e| !e.fatal)
         .map(|e| format!("warning[type]: {}", e.message))
         .collect();
 
-    // The Arkade operator key is always injected externally (via getInfo()).
-    // It is never a constructor parameter — options.server is a boolean flag only.
+    // ── Static analysis ────────────────────────────────────────────────────
+    // Path satisfiability and related lints, reported the same way.
+    warnings.extend(
+        analysis::check_contract(&contract)
+            .iter()
+            .map(|f| f.to_string()),
+    );
+
+    // With the optimizer on, what the analysis found dead is not generated
+    let written = options.optimize.then(|| contract.clone());
+    if options.optimize {
+        analysis::remove_dead_code(&mut contract);
+    }
+
+    // The Arkade operator key is always injected externally (via getInfo()),
+    // as <SERVER_KEY>; options.server only names it.
 
     // Collect asset IDs used in lookups for constructor param decomposition
     let lookup_asset_ids = collect_lookup_asset_ids(&contract);
@@ -217,34 +435,158 @@ pub fn compile(source_code: &str) -> Result<ContractJson, String> {
     // Build constructor inputs with asset ID decomposition
     let parameters = decompose_constructor_params(&contract.parameters, &lookup_asset_ids);
 
+    let internal_key = internal_key(&contract, &source)?;
+
     let mut json = ContractJson {
         name: contract.name.clone(),
         parameters,
         functions: Vec::new(),
-        source: Some(strip_comments(source_code)),
+        exit_delay: contract.exit_delay(),
+        source: Some(source),
+        source_hash,
+        artifact_hash: String::new(),
         compiler: Some(CompilerInfo {
             name: "arkade-compiler".to_string(),
             version: env!("CARGO_PKG_VERSION").to_string(),
         }),
-        updated_at: Some(Utc::now().to_rfc3339()),
+        updated_at: options.timestamp(),
         warnings,
+        optimizations: Vec::new(),
+        metrics: Vec::new(),
+        generics: options.generics.clone(),
+        address: None,
+        descriptor: None,
+        internal_key,
+        edition: contract.edition(),
+        spend_graph: None,
+        taproot: None,
+        events: contract
+            .events
+            .iter()
+            .map(|event| AbiEvent {
+                name: event.name.clone(),
+                inputs: event
+                    .parameters
+                    .iter()
+                    .map(|p| FunctionInput {
+                        name: p.name.clone(),
+                        param_type: p.param_type.clone(),
+                    })
+                    .collect(),
+            })
+            .collect(),
     };
 
-    for function in &contract.functions {
+    // Every public function becomes a cooperative and an exit leaf
+    let leaf_count = 2 * contract.functions.iter().filter(|f| !f.is_internal).count();
+
+    for (index, function) in contract.functions.iter().enumerate() {
         if function.is_internal {
             continue;
         }
 
-        let collaborative = generate_function(function, &contract, true)?;
-        json.functions.push(collaborative);
+        for server_variant in [true, false] {
+            let (mut abi, mut report) =
+                generate_function(function, &contract, server_variant, leaf_count, options)?;
+            // Measure the leaf as written, before its dead code was dropped
+            if let (Some(report), Some(written)) = (&mut report, &written) {
+                let before = &written.functions[index];
+                if before != function {
+                    let (_, full) =
+                        generate_function(before, written, server_variant, leaf_count, options)?;
+                    report.size_before = full.map_or(report.size_before, |r| r.size_before);
+                }
+            }
+            abi.leaf_index = json.functions.len();
+
+            let instrs = ir::lift(&abi.asm);
+            let counts = resources::count(&instrs);
+            let variant = variant_label(server_variant);
+            options
+                .budget
+                .check(&counts)
+                .and_then(|()| {
+                    let limits = &options.script_limits;
+                    limits.check(&instrs, abi.size_estimate.script)
+                })
+                .map_err(|e| format!("fn {} ({} variant): {}", function.name, variant, e))?;
+            json.metrics.push(LeafMetrics {
+                function: function.name.clone(),
+                server_variant,
+                resources: counts,
+            });
 
-        let exit = generate_function(function, &contract, false)?;
-        json.functions.push(exit);
+            json.functions.push(abi);
+            json.optimizations.extend(report);
+        }
     }
 
+    json.artifact_hash = json.compute_artifact_hash();
+
+    // A key path through a constructor key can be signed for
+    let spendable = contract.internal_key.as_deref().is_some_and(|key| {
+        contract
+            .parameters
+            .iter()
+            .any(|p| p.name == key && p.param_type == "pubkey")
+    });
+    json.spend_graph = Some(SpendGraph::new(&json, spendable, contract.renewal_timelock));
+
+    if options.deny_warnings && !json.warnings.is_empty() {
+        return Err(denied_warnings(&json.warnings, options.max_errors));
+    }
     Ok(json)
 }
 
+/// The error for a contract whose `warnings` are denied, listing at most
+/// `max_errors` of them.
+fn denied_warnings(warnings: &[String], max_errors: Option<usize>) -> String {
+    let shown = max_errors.unwrap_or(usize::MAX).min(warnings.len());
+    let mut message = format!("Warnings denied: the contract has {}", warnings.len());
+    for warning in &warnings[..shown] {
+        message.push_str("\n  ");
+        message.push_str(warning);
+    }
+    if shown < warnings.len() {
+        message.push_str(&format!("\n  ... and {} more", warnings.len() - shown));
+    }
+    message
+}
+
+/// The internal key the `internalKey` option asks for, as hex. `nums` is
+/// the BIP 341 NUMS point; `unspendable` is that point tweaked by the
+/// SHA-256 of the contract's `source`, so anyone holding the artifact can
+/// check it is unspendable. A `pubkey` constructor parameter gives a
+/// `<name>` placeholder, filled in when linking.
+fn internal_key(
+    contract: &crate::models::Contract,
+    source: &str,
+) -> Result<Option<String>, String> {
+    let key = match contract.internal_key.as_deref() {
+        None => return Ok(None),
+        Some("nums") => taproot::unspendable_key(None)?,
+        Some("unspendable") => {
+            let contract_hash: [u8; 32] = Sha256::digest(source.as_bytes()).into();
+            taproot::unspendable_key(Some(&contract_hash))?
+        }
+        Some(name)
+            if contract
+                .parameters
+                .iter()
+                .any(|p| p.name == name && p.param_type == "pubkey") =>
+        {
+            return Ok(Some(format!("<{}>", name)));
+        }
+        Some(other) => {
+            return Err(format!(
+                "Option 'internalKey' must be unspendable, nums or a pubkey parameter, got '{}'",
+                other
+            ))
+        }
+    };
+    Ok(Some(hex::encode(key)))
+}
+
 /// Collect all asset ID parameter names used in AssetLookup expressions
 fn collect_lookup_asset_ids(contract: &crate::models::Contract) -> Vec<String> {
     let mut ids = Vec::new();
@@ -260,13 +602,14 @@ fn collect_lookup_asset_ids(contract: &crate::models::Contract) -> Vec<String> {
 
 fn collect_asset_ids_from_statement(stmt: &Statement, ids: &mut Vec<String>) {
     match stmt {
-        Statement::Require(req) => {
+        Statement::Require(req, _) => {
             collect_asset_ids_from_requirement(req, ids);
         }
         Statement::IfElse {
             condition,
             then_body,
             else_body,
+            ..
         } => {
             collect_asset_ids_from_expression(condition, ids);
             for s in then_body {
@@ -290,12 +633,12 @@ fn collect_asset_ids_from_statement(stmt: &Statement, ids: &mut Vec<String>) {
 }
 
 fn collect_asset_ids_from_requirement(req: &Requirement, ids: &mut Vec<String>) {
-    match req {
-        Requirement::Comparison { left, op: _, right } => {
-            collect_asset_ids_from_expression(left, ids);
-            collect_asset_ids_from_expression(right, ids);
+    if let Requirement::Comparison { left, op, right } = req {
+        if let Some((_, id)) = compared_asset_id(left, op, right) {
+            ids.push(id.to_string());
         }
-        _ => {}
+        collect_asset_ids_from_expression(left, ids);
+        collect_asset_ids_from_expression(right, ids);
     }
 }
 
@@ -333,10 +676,9 @@ fn decompose_constructor_params(
                 name: format!("{}_gidx", param.name),
                 param_type: "int".to_string(),
             });
-        } else if param.param_type.ends_with("[]") {
+        } else if let Some((base_type, length)) = array_type(&param.param_type) {
             // Array type: flatten to name_0, name_1, name_2, etc.
-            let base_type = param.param_type.trim_end_matches("[]");
-            for i in 0..DEFAULT_ARRAY_LENGTH {
+            for i in 0..length {
                 result.push(crate::models::Parameter {
                     name: format!("{}_{}", param.name, i),
                     param_type: base_type.to_string(),
@@ -349,6 +691,15 @@ fn decompose_constructor_params(
     result
 }
 
+/// Function parameters as the witness carries them: arrays flattened and
+/// asset ids split into `_txid` and `_gidx`, as for constructor parameters.
+fn witness_params(
+    function: &Function,
+    contract: &crate::models::Contract,
+) -> Vec<crate::models::Parameter> {
+    decompose_constructor_params(&function.parameters, &collect_lookup_asset_ids(contract))
+}
+
 /// Build the `witnessSchema` for a function variant.
 ///
 /// The schema lists every value the *caller* must supply in the witness,
@@ -356,7 +707,8 @@ fn decompose_constructor_params(
 /// Constructor parameters are excluded — they are baked into the tapscript leaf.
 ///
 /// For exit paths that fall back to N-of-N (introspection functions), the
-/// schema lists one `<pkName>Sig` entry per constructor pubkey.
+/// schema lists one `<pkName>Sig` entry per pubkey, and the function's own
+/// pubkey parameters.
 fn generate_witness_schema(
     function: &crate::models::Function,
     contract: &crate::models::Contract,
@@ -373,44 +725,112 @@ fn generate_witness_schema(
                 name: format!("{}Sig", pk),
                 elem_type: "signature".to_string(),
                 encoding: ArkType::Signature.encoding().to_string(),
+                sighash: None,
+            });
+        }
+        // The chain reads the function's own keys from the witness
+        for param in function
+            .parameters
+            .iter()
+            .filter(|p| p.param_type == "pubkey")
+        {
+            schema.push(WitnessElement {
+                name: param.name.clone(),
+                elem_type: param.param_type.clone(),
+                encoding: ArkType::Pubkey.encoding().to_string(),
+                sighash: None,
             });
         }
     } else {
         // Normal path: function parameters form the witness elements.
-        for param in &function.parameters {
-            if param.param_type.ends_with("[]") {
-                let base = param.param_type.trim_end_matches("[]");
-                let ark_type = ArkType::parse(base);
-                for i in 0..DEFAULT_ARRAY_LENGTH {
-                    schema.push(WitnessElement {
-                        name: format!("{}_{}", param.name, i),
-                        elem_type: base.to_string(),
-                        encoding: ark_type.encoding().to_string(),
-                    });
-                }
-            } else {
-                let ark_type = ArkType::parse(&param.param_type);
-                schema.push(WitnessElement {
-                    name: param.name.clone(),
-                    elem_type: param.param_type.clone(),
-                    encoding: ark_type.encoding().to_string(),
-                });
-            }
+        for param in witness_params(function, contract) {
+            let ark_type = ArkType::parse(&param.param_type);
+            schema.push(WitnessElement {
+                name: param.name,
+                encoding: ark_type.encoding().to_string(),
+                elem_type: param.param_type,
+                sighash: None,
+            });
+        }
+    }
+
+    let pinned = pinned_sighashes(&function.statements, &contract.array_lengths(function));
+    for element in &mut schema {
+        if let Some(&flag) = pinned.get(&element.name) {
+            element.sighash = Some(flag);
+            element.encoding = ArkType::DataSig.encoding().to_string();
         }
     }
 
-    // Append the server signature for cooperative paths.
-    if server_variant && contract.has_server_key {
+    // Append the server signature for cooperative paths, unless the
+    // function already takes it.
+    if server_variant
+        && contract.has_server_key
+        && !schema.iter().any(|element| element.name == "serverSig")
+    {
         schema.push(WitnessElement {
             name: "serverSig".to_string(),
             elem_type: "signature".to_string(),
             encoding: ArkType::Signature.encoding().to_string(),
+            sighash: None,
         });
     }
 
     schema
 }
 
+/// Witness signatures whose sighash flag a `checkSig` pins, by element
+/// name. A loop over a signature array pins every element.
+fn pinned_sighashes(
+    statements: &[Statement],
+    arrays: &BTreeMap<String, usize>,
+) -> BTreeMap<String, u8> {
+    let mut pinned = BTreeMap::new();
+    for stmt in statements {
+        match stmt {
+            Statement::Require(
+                Requirement::CheckSig {
+                    signature,
+                    sighash: Some(flag),
+                    ..
+                },
+                _,
+            ) => {
+                pinned.insert(signature.clone(), *flag);
+            }
+            Statement::IfElse {
+                then_body,
+                else_body,
+                ..
+            } => {
+                pinned.extend(pinned_sighashes(then_body, arrays));
+                if let Some(else_body) = else_body {
+                    pinned.extend(pinned_sighashes(else_body, arrays));
+                }
+            }
+            Statement::ForIn {
+                value_var,
+                iterable: Expression::Variable(array),
+                body,
+                ..
+            } => {
+                let length = arrays.get(array).copied().unwrap_or(0);
+                for (name, flag) in pinned_sighashes(body, arrays) {
+                    if &name == value_var {
+                        for k in 0..length {
+                            pinned.insert(format!("{}_{}", array, k), flag);
+                        }
+                    } else {
+                        pinned.insert(name, flag);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    pinned
+}
+
 /// Generate a function ABI with server variant flag.
 ///
 /// **Introspection path** (including `ContractInstance` — `new ContractName(args)` present):
@@ -420,33 +840,25 @@ fn generate_witness_schema(
 /// **No introspection**:
 /// - Cooperative path: normal ASM + server signature
 /// - Exit path: normal ASM + exit timelock
+///
+/// With [`CompileOptions::optimize`] set, the finished leaf is run through the
+/// IR optimizer and its size before and after is returned alongside the ABI.
 fn generate_function(
     function: &Function,
     contract: &crate::models::Contract,
     server_variant: bool,
-) -> Result<AbiFunction, String> {
+    leaf_count: usize,
+    options: &CompileOptions,
+) -> Result<(AbiFunction, Option<OptimizationReport>), String> {
     let uses_introspection = function_uses_introspection(function);
     let all_pubkeys = collect_all_pubkeys(contract, function);
 
-    // Flatten array types in function inputs
-    let mut function_inputs: Vec<FunctionInput> = function
-        .parameters
-        .iter()
-        .flat_map(|param| {
-            if param.param_type.ends_with("[]") {
-                let base_type = param.param_type.trim_end_matches("[]");
-                (0..DEFAULT_ARRAY_LENGTH)
-                    .map(|i| FunctionInput {
-                        name: format!("{}_{}", param.name, i),
-                        param_type: base_type.to_string(),
-                    })
-                    .collect::<Vec<_>>()
-            } else {
-                vec![FunctionInput {
-                    name: param.name.clone(),
-                    param_type: param.param_type.clone(),
-                }]
-            }
+    // Flatten array types and asset ids in function inputs
+    let mut function_inputs: Vec<FunctionInput> = witness_params(function, contract)
+        .into_iter()
+        .map(|param| FunctionInput {
+            name: param.name,
+            param_type: param.param_type,
         })
         .collect();
 
@@ -456,7 +868,7 @@ fn generate_function(
     if !server_variant && uses_introspection {
         let existing_sig_names: Vec<String> = function_inputs
             .iter()
-            .filter(|i| i.param_type == "signature")
+            .filter(|i| ArkType::parse(&i.param_type).is_signature())
             .map(|i| i.name.clone())
             .collect();
 
@@ -496,10 +908,10 @@ fn generate_function(
                 message: None,
             });
         }
-    } else if let Some(exit_timelock) = contract.exit_timelock {
+    } else {
         require.push(RequireStatement {
             req_type: "older".to_string(),
-            message: Some(format!("Exit timelock of {} blocks", exit_timelock)),
+            message: Some(format!("Exit timelock of {} blocks", contract.exit_delay())),
         });
     }
 
@@ -507,52 +919,338 @@ fn generate_function(
     // Exit path with any introspection falls back to N-of-N CHECKSIG
     // (pure Bitcoin Script — no non-Bitcoin-Script opcodes allowed).
     // Cooperative path always uses the full statement ASM.
+    let mut locals: Vec<ir::LocalBinding> = Vec::new();
+    // Position of the last check's result, the value the leaf leaves
+    let mut result = None;
     let mut asm = if !server_variant && uses_introspection {
-        generate_nofn_checksig_asm(&all_pubkeys, function)
+        let asm = generate_nofn_checksig_asm(&all_pubkeys, function);
+        result = asm.len().checked_sub(1);
+        asm
     } else {
-        // Normal path: generate ASM from statements (includes introspection opcodes)
-        generate_asm_from_statements(&function.statements)?
+        // Normal path: generate ASM from statements (includes introspection opcodes),
+        // remembering which instructions compute each top-level let binding
+        let mut asm = Vec::new();
+        let arrays = contract.array_lengths(function);
+        for stmt in &function.statements {
+            let mut lowered = Vec::new();
+            let check = generate_asm_from_statements_recursive(
+                core::slice::from_ref(stmt),
+                &arrays,
+                contract.strict_hashlocks,
+                &mut lowered,
+            )?;
+            // Lowering may lengthen the statement, never after its result
+            let from_end = check.map(|at| lowered.len() - at);
+            covenant::lower_self_references(&mut lowered, contract, function);
+            let start = asm.len();
+            match from_end {
+                Some(from_end) => {
+                    let at = lowered.len() - from_end;
+                    if let Some(at) = push_check(&mut asm, &mut result, lowered, at) {
+                        ir::shift_locals(&mut locals, at);
+                    }
+                }
+                None => asm.extend(lowered),
+            }
+            // The value, without the token that names it
+            if let Statement::LetBinding { name, .. } = stmt {
+                locals.push(ir::LocalBinding {
+                    name: name.clone(),
+                    range: start..asm.len() - 1,
+                });
+            }
+        }
+        asm
     };
 
     // Append server signature or exit timelock
     if server_variant {
         if contract.has_server_key {
-            asm.push("<SERVER_KEY>".to_string());
-            asm.push("<serverSig>".to_string());
-            asm.push(OP_CHECKSIG.to_string());
+            let server = vec![
+                "<serverSig>".to_string(),
+                "<SERVER_KEY>".to_string(),
+                OP_CHECKSIG.to_string(),
+            ];
+            if let Some(at) = push_check(&mut asm, &mut result, server, 2) {
+                ir::shift_locals(&mut locals, at);
+            }
         }
-    } else if let Some(exit_timelock) = contract.exit_timelock {
-        asm.push(format!("{}", exit_timelock));
+    } else {
+        asm.push(format!("{}", contract.exit_delay()));
         asm.push(OP_CHECKSEQUENCEVERIFY.to_string());
-        asm.push(OP_DROP.to_string());
+        // With no check result to leave, the exit delay is the leaf's
+        if result.is_some() {
+            asm.push(OP_DROP.to_string());
+        }
     }
 
-    let witness_schema = generate_witness_schema(
+    let mut witness_schema = generate_witness_schema(
         function,
         contract,
         server_variant,
         uses_introspection,
         &all_pubkeys,
     );
+    bind_chain_signatures(&mut asm, &mut witness_schema);
+
+    let mut ctx = leaf_context(contract, function, &function_inputs, locals);
+    // N-of-N exit signatures are witness elements too
+    ctx.witness
+        .extend(witness_schema.iter().map(|e| e.name.clone()));
+    let optimized = if options.optimize {
+        Some(ir::optimize(&asm, &ctx))
+    } else if options.peephole {
+        Some(ir::optimize_peephole(&asm, &ctx))
+    } else {
+        None
+    };
+    // Both sizes are of the leaf as scheduled
+    let mut size_before = None;
+    if let Some(optimized) = optimized {
+        let unoptimized = schedule_leaf(&mut witness_schema.clone(), &asm)?;
+        size_before = Some(ir::script_size(&ir::lift(&unoptimized), &ctx));
+        asm = optimized.asm;
+    }
+    asm = schedule_leaf(&mut witness_schema, &asm)?;
+    let script_len = ir::script_size(&ir::lift(&asm), &ctx);
+    let report = size_before.map(|size_before| OptimizationReport {
+        function: function.name.clone(),
+        server_variant,
+        size_before,
+        size_after: script_len,
+    });
+    let witness_size = size::estimate(&witness_schema, script_len, leaf_count);
+    let size_estimate = size::summarize(script_len, &witness_size);
 
-    Ok(AbiFunction {
+    let input_types: Vec<&str> = function
+        .parameters
+        .iter()
+        .map(|p| p.param_type.as_str())
+        .collect();
+    let mut abi = AbiFunction {
+        selector: selector(&function.name, &input_types, server_variant),
+        leaf_index: 0,
+        proof: None,
+        script_hex: None,
         name: function.name.clone(),
         function_inputs,
+        witness_template: template::witness_template(
+            function,
+            contract,
+            server_variant,
+            &witness_schema,
+        ),
         witness_schema,
         server_variant,
         require,
+        operations: Vec::new(),
         asm,
-    })
+        witness_size,
+        size_estimate,
+        emits: function
+            .emits
+            .iter()
+            .map(|emit| AbiEmit {
+                event: emit.event.clone(),
+                args: emit.args.clone(),
+            })
+            .collect(),
+    };
+    let parameters =
+        decompose_constructor_params(&contract.parameters, &collect_lookup_asset_ids(contract));
+    abi.operations = operations(&abi.asm, &parameters, &abi.witness_schema);
+    abi.script_hex = script_hex(&abi);
+    Ok((abi, report))
+}
+
+/// Bind the signatures of each CHECKSIG/CHECKSIGADD chain in `asm`, which
+/// it reads by its keys' names as `<keySig>`, to elements of `schema`. A
+/// signature the function takes under that name is read as it is. The
+/// others are the function's signatures nothing else reads, in order, so
+/// `checkMultisig(members, 2)` reads `sigs_0` for `members_0`; a key left
+/// without one gets an element of its own, which the spender leaves empty
+/// if the key does not sign.
+fn bind_chain_signatures(asm: &mut [String], schema: &mut Vec<WitnessElement>) {
+    let reads = |name: &str| asm.iter().any(|token| *token == format!("<{}>", name));
+    let mut free: Vec<String> = schema
+        .iter()
+        .filter(|e| ArkType::parse(&e.elem_type).is_signature() && e.name != "serverSig")
+        .filter(|e| !reads(&e.name))
+        .map(|e| e.name.clone())
+        .collect();
+    free.reverse();
+    let mut bound: BTreeMap<String, String> = BTreeMap::new();
+
+    for i in 1..asm.len() {
+        if asm[i] != OP_CHECKSIG {
+            continue;
+        }
+        // The keys, and the signatures before them, the first key's last
+        let mut keys = vec![i - 1];
+        while asm.get(keys[keys.len() - 1] + 3).map(String::as_str) == Some(OP_CHECKSIGADD) {
+            keys.push(keys[keys.len() - 1] + 2);
+        }
+        let Some(first) = keys[0].checked_sub(keys.len()) else {
+            continue;
+        };
+        let signatures: Vec<usize> = (first..keys[0]).rev().collect();
+        let is_chain = keys.iter().zip(&signatures).all(|(&key, &signature)| {
+            let key = asm[key].strip_prefix('<').and_then(|k| k.strip_suffix('>'));
+            key.is_some_and(|key| asm[signature] == format!("<{}Sig>", key))
+        });
+        if !is_chain {
+            continue;
+        }
+        for at in signatures {
+            let name = asm[at][1..asm[at].len() - 1].to_string();
+            if schema.iter().any(|e| e.name == name) {
+                continue;
+            }
+            let element = match bound.get(&name) {
+                Some(element) => element.clone(),
+                None => {
+                    let element = free.pop().unwrap_or_else(|| {
+                        schema.push(WitnessElement {
+                            name: name.clone(),
+                            elem_type: "signature".to_string(),
+                            encoding: ArkType::Signature.encoding().to_string(),
+                            sighash: None,
+                        });
+                        name.clone()
+                    });
+                    bound.insert(name, element.clone());
+                    element
+                }
+            };
+            asm[at] = format!("<{}>", element);
+        }
+    }
+}
+
+/// Order `schema` as `asm` reads it and schedule the reads on the stack.
+fn schedule_leaf(schema: &mut [WitnessElement], asm: &[String]) -> Result<Vec<String>, String> {
+    order_by_use(schema, asm);
+    let witness: Vec<String> = schema.iter().map(|e| e.name.clone()).collect();
+    stack::schedule(asm, &witness)
+}
+
+/// Order `schema` as the leaf consumes it, by the first placeholder of each
+/// element in `asm`. The witness lies under the script, so the element read
+/// first has to be on top, and of elements read one after the other, as the
+/// operands of an opcode are, the last: they are then stacked as read.
+/// Elements the leaf never reads go last.
+fn order_by_use(schema: &mut [WitnessElement], asm: &[String]) {
+    let read = |token: &String| {
+        schema.iter().any(|element| {
+            token.strip_prefix('<').and_then(|t| t.strip_suffix('>')) == Some(&element.name)
+        })
+    };
+    // Where each read's run of reads starts, and how far it is from the end
+    let mut runs = Vec::with_capacity(asm.len());
+    let mut start = 0;
+    for (i, token) in asm.iter().enumerate() {
+        if !read(token) {
+            runs.push(None);
+            start = i + 1;
+            continue;
+        }
+        let end = start + asm[start..].iter().take_while(|t| read(t)).count();
+        runs.push(Some((start, end - 1 - i)));
+    }
+    let position = |name: &str| {
+        let placeholder = format!("<{}>", name);
+        asm.iter()
+            .position(|token| *token == placeholder)
+            .and_then(|i| runs[i])
+    };
+    schema.sort_by_key(|element| position(&element.name).unwrap_or((usize::MAX, 0)));
+}
+
+/// Collect what the IR optimizer needs to know about a leaf: which
+/// placeholders come from the witness, how large the constructor pushes are,
+/// and which let bindings can be propagated into their uses.
+///
+/// Bindings that shadow a parameter, are declared twice or are reassigned
+/// anywhere in the function are not propagated.
+fn leaf_context(
+    contract: &crate::models::Contract,
+    function: &Function,
+    function_inputs: &[FunctionInput],
+    locals: Vec<ir::LocalBinding>,
+) -> ir::LeafContext {
+    let mut witness: Vec<String> = function_inputs.iter().map(|i| i.name.clone()).collect();
+    witness.push("serverSig".to_string());
+
+    let lookup_asset_ids = collect_lookup_asset_ids(contract);
+    let mut push_sizes: BTreeMap<String, usize> =
+        decompose_constructor_params(&contract.parameters, &lookup_asset_ids)
+            .into_iter()
+            .map(|p| {
+                let size = ir::push_size_for_type(&p.param_type);
+                (p.name, size)
+            })
+            .collect();
+    push_sizes.insert("SERVER_KEY".to_string(), ir::push_size_for_type("pubkey"));
+
+    let mut assigned = Vec::new();
+    collect_assigned_names(&function.statements, &mut assigned);
+    let locals = locals
+        .iter()
+        .filter(|l| {
+            !assigned.contains(&l.name)
+                && locals.iter().filter(|o| o.name == l.name).count() == 1
+                && !contract.parameters.iter().any(|p| p.name == l.name)
+                && !function.parameters.iter().any(|p| p.name == l.name)
+        })
+        .cloned()
+        .collect();
+
+    ir::LeafContext {
+        witness,
+        push_sizes,
+        locals,
+    }
+}
+
+/// Collect every variable name that is reassigned or re-declared inside a
+/// nested block.
+fn collect_assigned_names(statements: &[Statement], names: &mut Vec<String>) {
+    for stmt in statements {
+        match stmt {
+            Statement::VarAssign { name, .. } => names.push(name.clone()),
+            Statement::IfElse {
+                then_body,
+                else_body,
+                ..
+            } => {
+                collect_nested_bindings(then_body, names);
+                if let Some(else_stmts) = else_body {
+                    collect_nested_bindings(else_stmts, names);
+                }
+            }
+            Statement::ForIn { body, .. } => collect_nested_bindings(body, names),
+            Statement::Require(..) | Statement::LetBinding { .. } => {}
+        }
+    }
+}
+
+fn collect_nested_bindings(statements: &[Statement], names: &mut Vec<String>) {
+    for stmt in statements {
+        if let Statement::LetBinding { name, .. } = stmt {
+            names.push(name.clone());
+        }
+    }
+    collect_assigned_names(statements, names);
 }
 
 /// Generate N-of-N CHECKSIG chain assembly (Tapscript style)
 ///
 /// For N pubkeys, generates pure Bitcoin script with no introspection:
 /// ```text
-/// <pk1> <pk1Sig> OP_CHECKSIGVERIFY
-/// <pk2> <pk2Sig> OP_CHECKSIGVERIFY
+/// <pk1Sig> <pk1> OP_CHECKSIGVERIFY
+/// <pk2Sig> <pk2> OP_CHECKSIGVERIFY
 /// ...
-/// <pkN> <pkNSig> OP_CHECKSIG
+/// <pkNSig> <pkN> OP_CHECKSIG
 /// ```
 ///
 /// This is the fallback for exit paths when introspection is used.
@@ -563,8 +1261,8 @@ fn generate_nofn_checksig_asm(pubkeys: &[String], _function: &Function) -> Vec<S
     // Generate ONLY N-of-N CHECKSIG chain - no original requirements
     // This is pure Bitcoin script with no Arkade-specific opcodes
     for (i, pk) in pubkeys.iter().enumerate() {
-        asm.push(format!("<{}>", pk));
         asm.push(format!("<{}Sig>", pk));
+        asm.push(format!("<{}>", pk));
         if i < pubkeys.len() - 1 {
             asm.push(OP_CHECKSIGVERIFY.to_string());
         } else {
@@ -606,7 +1304,7 @@ fn collect_requirements_from_statements(
 ) {
     for stmt in statements {
         match stmt {
-            Statement::Require(req) => {
+            Statement::Require(req, _) => {
                 let req_statement = requirement_to_statement(req);
                 requirements.push(req_statement);
             }
@@ -633,9 +1331,9 @@ fn collect_requirements_from_statements(
 /// Convert a Requirement to a RequireStatement
 fn requirement_to_statement(req: &Requirement) -> RequireStatement {
     match req {
-        Requirement::CheckSig { .. } => RequireStatement {
+        Requirement::CheckSig { sighash, .. } => RequireStatement {
             req_type: "signature".to_string(),
-            message: None,
+            message: sighash.map(|flag| format!("Signed with {}", encoding::sighash_names(flag))),
         },
         Requirement::CheckSigFromStack { .. } => RequireStatement {
             req_type: "signatureFromStack".to_string(),
@@ -645,14 +1343,54 @@ fn requirement_to_statement(req: &Requirement) -> RequireStatement {
             req_type: "multisig".to_string(),
             message: None,
         },
-        Requirement::After { blocks, .. } => RequireStatement {
+        Requirement::After {
+            locktime,
+            timelock_var,
+        } => RequireStatement {
             req_type: "after".to_string(),
-            message: Some(format!("Timelock of {} blocks", blocks)),
+            message: Some(match timelock_var {
+                Some(var) => format!("Timelock until {}", var),
+                None if *locktime >= 500_000_000 => {
+                    format!("Timelock until timestamp {}", locktime)
+                }
+                None => format!("Timelock until block height {}", locktime),
+            }),
+        },
+        Requirement::Older { age, timelock_var } => RequireStatement {
+            req_type: "older".to_string(),
+            message: Some(match (timelock_var, age) {
+                (Some(var), _) => format!("Relative timelock of {} blocks", var),
+                (None, Duration::Blocks(blocks)) => {
+                    format!("Relative timelock of {} blocks", blocks)
+                }
+                (None, Duration::Seconds(_)) => format!("Relative timelock of {}", age),
+            }),
         },
         Requirement::HashEqual { .. } => RequireStatement {
             req_type: "hash".to_string(),
             message: None,
         },
+        Requirement::SizeBetween { value, min, max } => RequireStatement {
+            req_type: "size".to_string(),
+            message: Some(format!(
+                "Size of {} between {} and {} bytes",
+                value, min, max
+            )),
+        },
+        Requirement::Comparison {
+            left: Expression::TweakVerify { .. },
+            ..
+        } => RequireStatement {
+            req_type: "tweak".to_string(),
+            message: None,
+        },
+        Requirement::Comparison {
+            left: Expression::EcMulScalarVerify { .. },
+            ..
+        } => RequireStatement {
+            req_type: "scalarMul".to_string(),
+            message: None,
+        },
         Requirement::Comparison { left, .. } => {
             // Detect asset-related comparisons
             let req_type = if contains_asset_lookup(left) {
@@ -670,42 +1408,85 @@ fn requirement_to_statement(req: &Requirement) -> RequireStatement {
     }
 }
 
-/// Generate assembly instructions from statements
-fn generate_asm_from_statements(statements: &[Statement]) -> Result<Vec<String>, String> {
-    let mut asm = Vec::new();
-    generate_asm_from_statements_recursive(statements, &mut asm)?;
-    Ok(asm)
-}
-
-/// Recursively generate assembly from statements
+/// Recursively generate assembly from statements, unrolling loops over the
+/// arrays in `arrays` (name to length). With `strict_hashlocks`, hash locks
+/// also check the preimage size.
+///
+/// Every check but the last is verified, so the statements leave at most
+/// one result: returns where it is in `asm`, if they leave one, from a
+/// requirement that pushes whether it held or an if/else whose branches
+/// both leave one.
 fn generate_asm_from_statements_recursive(
     statements: &[Statement],
+    arrays: &BTreeMap<String, usize>,
+    strict_hashlocks: bool,
     asm: &mut Vec<String>,
-) -> Result<(), String> {
+) -> Result<Option<usize>, String> {
+    let mut result = None;
     for stmt in statements {
         match stmt {
-            Statement::Require(req) => {
-                generate_requirement_asm(req, asm)?;
+            Statement::Require(req, _) => {
+                let mut check = Vec::new();
+                generate_requirement_asm(req, arrays, strict_hashlocks, &mut check)?;
+                let verified = check.last().is_none_or(|op| op.ends_with("VERIFY"));
+                match req {
+                    Requirement::After { .. } | Requirement::Older { .. } => asm.extend(check),
+                    _ if verified => asm.extend(check),
+                    _ => {
+                        let at = check.len() - 1;
+                        push_check(asm, &mut result, check, at);
+                    }
+                }
             }
             Statement::IfElse {
                 condition,
                 then_body,
                 else_body,
+                ..
             } => {
                 // Generate condition expression
-                generate_expression_asm(condition, asm);
-                asm.push(OP_IF.to_string());
+                let mut branches = Vec::new();
+                generate_expression_asm(condition, &mut branches);
+                branches.push(OP_IF.to_string());
 
                 // Generate then branch
-                generate_asm_from_statements_recursive(then_body, asm)?;
+                let then_check = generate_asm_from_statements_recursive(
+                    then_body,
+                    arrays,
+                    strict_hashlocks,
+                    &mut branches,
+                )?;
 
                 // Generate else branch if present
+                let mut else_check = None;
                 if let Some(else_stmts) = else_body {
-                    asm.push(OP_ELSE.to_string());
-                    generate_asm_from_statements_recursive(else_stmts, asm)?;
+                    branches.push(OP_ELSE.to_string());
+                    else_check = generate_asm_from_statements_recursive(
+                        else_stmts,
+                        arrays,
+                        strict_hashlocks,
+                        &mut branches,
+                    )?;
                 }
 
-                asm.push(OP_ENDIF.to_string());
+                branches.push(OP_ENDIF.to_string());
+                match (then_check, else_check) {
+                    // Either way one result is left, after the OP_ENDIF
+                    (Some(_), Some(_)) => {
+                        let at = branches.len() - 1;
+                        push_check(asm, &mut result, branches, at);
+                    }
+                    // A result only one branch leaves is verified in it
+                    (then_check, else_check) => {
+                        if let Some(at) = else_check {
+                            verify_at(&mut branches, at);
+                        }
+                        if let Some(at) = then_check {
+                            verify_at(&mut branches, at);
+                        }
+                        asm.extend(branches);
+                    }
+                }
             }
             Statement::ForIn {
                 index_var,
@@ -734,11 +1515,21 @@ fn generate_asm_from_statements_recursive(
                         // Substitute loop variables and generate ASM for each iteration
                         let substituted_body =
                             substitute_loop_body(body, index_var, value_var, k, None);
-                        generate_asm_from_statements_recursive(&substituted_body, asm)?;
+                        generate_iteration(
+                            &substituted_body,
+                            arrays,
+                            strict_hashlocks,
+                            asm,
+                            &mut result,
+                        )?;
                     }
                 } else if array_name.is_some() {
                     // Iterating over an array variable - unroll with array substitution
-                    let num_iterations = DEFAULT_ARRAY_LENGTH;
+                    let num_iterations = array_name
+                        .as_ref()
+                        .and_then(|name| arrays.get(name))
+                        .copied()
+                        .unwrap_or(DEFAULT_ARRAY_LENGTH);
 
                     for k in 0..num_iterations {
                         // Substitute loop variables and generate ASM for each iteration
@@ -750,38 +1541,153 @@ fn generate_asm_from_statements_recursive(
                             k,
                             array_name.as_ref(),
                         );
-                        generate_asm_from_statements_recursive(&substituted_body, asm)?;
+                        generate_iteration(
+                            &substituted_body,
+                            arrays,
+                            strict_hashlocks,
+                            asm,
+                            &mut result,
+                        )?;
                     }
                 } else {
                     // For other iterables, process body once (fallback)
-                    generate_asm_from_statements_recursive(body, asm)?;
+                    generate_iteration(body, arrays, strict_hashlocks, asm, &mut result)?;
                 }
             }
-            Statement::LetBinding { name: _, value } => {
-                // Emit the expression value onto the stack
-                // TODO: Implement proper variable binding with stack tracking
-                generate_expression_asm(value, asm);
-            }
-            Statement::VarAssign { name: _, value } => {
-                // Push the new value onto the stack.
-                // Full variable reassignment (dropping the old stack value) requires
-                // stack-position tracking, which is deferred to a later phase.
-                // For the common pattern of `typed_var = expr; require(typed_var == ...)`,
-                // emitting the expression is sufficient because the old value has already
-                // been consumed by the time the re-assignment is reached.
-                generate_expression_asm(value, asm);
+            // The value is pushed where it is computed and named for the
+            // reads after it, which the scheduler finds on the stack
+            Statement::LetBinding { name, value } | Statement::VarAssign { name, value } => {
+                generate_binding_asm(value, asm);
+                asm.push(stack::binding(name));
             }
         }
     }
+    Ok(result)
+}
+
+/// Generate the value bound to a variable. Arithmetic on script numbers is
+/// done in 64 bits but is an `int`, so its result is converted back.
+fn generate_binding_asm(value: &Expression, asm: &mut Vec<String>) {
+    generate_expression_asm(value, asm);
+    if matches!(value, Expression::BinaryOp { op, .. } if is_arithmetic_op(op))
+        && !is_u64_value(value)
+    {
+        asm.push(OP_LE64TOSCRIPTNUM.to_string());
+    }
+}
+
+/// Generate one iteration of an unrolled loop into the statements around
+/// it.
+fn generate_iteration(
+    body: &[Statement],
+    arrays: &BTreeMap<String, usize>,
+    strict_hashlocks: bool,
+    asm: &mut Vec<String>,
+    result: &mut Option<usize>,
+) -> Result<(), String> {
+    let mut iteration = Vec::new();
+    match generate_asm_from_statements_recursive(body, arrays, strict_hashlocks, &mut iteration)? {
+        Some(at) => {
+            push_check(asm, result, iteration, at);
+        }
+        None => asm.extend(iteration),
+    }
     Ok(())
 }
 
+/// Append `check`, which leaves whether it held on the stack at `at`
+/// within it. The result of the check before it, at `result`, is verified
+/// first, so only the last check's result stays on the stack.
+///
+/// Returns where an `OP_VERIFY` was inserted, if one was.
+fn push_check(
+    asm: &mut Vec<String>,
+    result: &mut Option<usize>,
+    check: Vec<String>,
+    at: usize,
+) -> Option<usize> {
+    let inserted = result.take().and_then(|previous| verify_at(asm, previous));
+    *result = Some(asm.len() + at);
+    asm.extend(check);
+    inserted
+}
+
+/// Verify the check result at `at`: the check takes its `VERIFY` form, or
+/// an `OP_VERIFY` follows it.
+///
+/// Returns where an `OP_VERIFY` was inserted, if one was.
+fn verify_at(asm: &mut Vec<String>, at: usize) -> Option<usize> {
+    match ir::verify_form(&asm[at]) {
+        Some(verify) => {
+            asm[at] = verify.to_string();
+            None
+        }
+        None => {
+            asm.insert(at + 1, OP_VERIFY.to_string());
+            Some(at + 1)
+        }
+    }
+}
+
+/// The CHECKSIG/CHECKSIGADD chain over `pubkeys`, leaving the number of
+/// valid signatures. The signatures are read up front, the first key's on
+/// top, and each key in turn is checked against the one under the count.
+fn push_sig_count_asm(pubkeys: &[String], asm: &mut Vec<String>) {
+    for pubkey in pubkeys.iter().rev() {
+        asm.push(format!("<{}Sig>", pubkey));
+    }
+    for (i, pubkey) in pubkeys.iter().enumerate() {
+        asm.push(format!("<{}>", pubkey));
+        if i == 0 {
+            asm.push(OP_CHECKSIG.to_string());
+        } else {
+            asm.push(OP_CHECKSIGADD.to_string());
+        }
+    }
+}
+
+/// Push the operands of a checkSig, the signature under the key. With a
+/// pinned sighash flag the witness holds the 64-byte signature and the
+/// script appends the flag, so no other flag can be used; `SIGHASH_DEFAULT`
+/// is pinned by requiring exactly 64 bytes. The signature is checked or
+/// extended before the key is pushed over it.
+fn push_checksig_operands(
+    signature: &str,
+    pubkey: &str,
+    sighash: Option<u8>,
+    asm: &mut Vec<String>,
+) {
+    asm.push(format!("<{}>", signature));
+    match sighash {
+        None => asm.push(format!("<{}>", pubkey)),
+        Some(flag) => {
+            if flag == 0 {
+                asm.push(OP_SIZE.to_string());
+                asm.push("64".to_string());
+                asm.push(OP_EQUALVERIFY.to_string());
+            } else {
+                asm.push(format!("0x{:02x}", flag));
+                asm.push(OP_CAT.to_string());
+            }
+            asm.push(format!("<{}>", pubkey));
+        }
+    }
+}
+
 /// Generate assembly for a single requirement
-fn generate_requirement_asm(req: &Requirement, asm: &mut Vec<String>) -> Result<(), String> {
+fn generate_requirement_asm(
+    req: &Requirement,
+    arrays: &BTreeMap<String, usize>,
+    strict_hashlocks: bool,
+    asm: &mut Vec<String>,
+) -> Result<(), String> {
     match req {
-        Requirement::CheckSig { signature, pubkey } => {
-            asm.push(format!("<{}>", pubkey));
-            asm.push(format!("<{}>", signature));
+        Requirement::CheckSig {
+            signature,
+            pubkey,
+            sighash,
+        } => {
+            push_checksig_operands(signature, pubkey, *sighash, asm);
             asm.push(OP_CHECKSIG.to_string());
             Ok(())
         }
@@ -790,13 +1696,14 @@ fn generate_requirement_asm(req: &Requirement, asm: &mut Vec<String>) -> Result<
             pubkey,
             message,
         } => {
+            asm.push(format!("<{}>", signature));
             asm.push(format!("<{}>", message));
             asm.push(format!("<{}>", pubkey));
-            asm.push(format!("<{}>", signature));
             asm.push(OP_CHECKSIGFROMSTACK.to_string());
             Ok(())
         }
         Requirement::CheckMultisig { pubkeys, threshold } => {
+            let (pubkeys, threshold) = multisig_keys(pubkeys, *threshold, arrays)?;
             let pubkeys_size = pubkeys.len();
             let pubkeys_size = if pubkeys_size <= 999 {
                 pubkeys_size as u16
@@ -804,29 +1711,21 @@ fn generate_requirement_asm(req: &Requirement, asm: &mut Vec<String>) -> Result<
                 return Err("Number of pubkeys should be less than 999.".to_string());
             };
 
-            if threshold < &1u16 {
+            if threshold < 1 {
                 return Err(format!(
                     "m-of-n multisig cannot succeed with threshold(m) of {}",
                     threshold
                 ));
             }
-            if threshold > &pubkeys_size {
+            if threshold > pubkeys_size {
                 return Err(
                     "m-of-n multisig threshold(m) exceeds acceptable number of signers(n)"
                         .to_string(),
                 );
             }
 
-            for (i, pubkey) in pubkeys.iter().enumerate() {
-                if i == 0 {
-                    asm.push(format!("<{}>", pubkey));
-                    asm.push(OP_CHECKSIG.to_string());
-                    continue;
-                }
-                asm.push(format!("<{}>", pubkey));
-                asm.push(OP_CHECKSIGADD.to_string());
-            }
-            if threshold <= &16u16 {
+            push_sig_count_asm(&pubkeys, asm);
+            if threshold <= 16 {
                 asm.push(format!("OP_{}", threshold));
             } else {
                 asm.push(format!("{}", threshold));
@@ -835,32 +1734,67 @@ fn generate_requirement_asm(req: &Requirement, asm: &mut Vec<String>) -> Result<
             Ok(())
         }
         Requirement::After {
-            blocks,
+            locktime,
             timelock_var,
         } => {
             if let Some(var) = timelock_var {
                 asm.push(format!("<{}>", var));
             } else {
-                asm.push(format!("{}", blocks));
+                asm.push(format!("{}", locktime));
             }
             asm.push(OP_CHECKLOCKTIMEVERIFY.to_string());
             asm.push(OP_DROP.to_string());
             Ok(())
         }
-        Requirement::HashEqual { preimage, hash } => {
+        Requirement::Older { age, timelock_var } => {
+            asm.push(older_lock(age, timelock_var)?);
+            asm.push(OP_CHECKSEQUENCEVERIFY.to_string());
+            asm.push(OP_DROP.to_string());
+            Ok(())
+        }
+        Requirement::HashEqual {
+            preimage,
+            hash,
+            function,
+        } => {
             asm.push(format!("<{}>", preimage));
-            asm.push(OP_SHA256.to_string());
+            if strict_hashlocks {
+                // Pin the preimage to 32 bytes, as Miniscript does
+                asm.push(OP_SIZE.to_string());
+                asm.push("32".to_string());
+                asm.push(OP_EQUALVERIFY.to_string());
+            }
+            asm.push(function.opcode().to_string());
             asm.push(format!("<{}>", hash));
             asm.push(OP_EQUAL.to_string());
             Ok(())
         }
+        Requirement::SizeBetween { value, min, max } => {
+            push_size_between_asm(value, *min, *max, asm);
+            Ok(())
+        }
+        Requirement::Comparison { left, op, right } if is_logical_op(op) => {
+            emit_logical_asm(left, op, right, asm);
+            Ok(())
+        }
         Requirement::Comparison { left, op, right } => {
-            generate_comparison_asm(left, op, right, asm);
+            emit_comparison_asm(left, op, right, asm);
             Ok(())
         }
     }
 }
 
+/// Emit `min <= size(value) <= max`: OP_WITHIN excludes its upper bound, and
+/// OP_NIP drops `value` from under the result.
+fn push_size_between_asm(value: &str, min: u64, max: u64, asm: &mut Vec<String>) {
+    asm.push(format!("<{}>", value));
+    asm.push(OP_SIZE.to_string());
+    asm.push(min.to_string());
+    asm.push((max + 1).to_string());
+    asm.push(OP_WITHIN.to_string());
+    asm.push(OP_NIP.to_string());
+}
+
 /// Generate assembly for expression (for use in if conditions)
 fn generate_expression_asm(expr: &Expression, asm: &mut Vec<String>) {
     match expr {
@@ -870,81 +1804,21 @@ fn generate_expression_asm(expr: &Expression, asm: &mut Vec<String>) {
         Expression::Literal(lit) => {
             asm.push(lit.clone());
         }
-        Expression::Property(prop) => {
-            asm.push(format!("<{}>", prop));
-        }
-        Expression::BinaryOp { left, op, right } => {
-            // Emit left operand
+        Expression::Property(prop) => emit_property_asm(prop, asm),
+        Expression::BinaryOp { left, op, right } if is_logical_op(op) => {
             generate_expression_asm(left, asm);
-
-            // Convert to u64le if needed (witness inputs arrive as CScriptNum)
-            if needs_u64_conversion(left) {
-                asm.push(OP_SCRIPTNUMTOLE64.to_string());
-            }
-
-            // Emit right operand
             generate_expression_asm(right, asm);
-
-            // Convert to u64le if needed
-            if needs_u64_conversion(right) {
-                asm.push(OP_SCRIPTNUMTOLE64.to_string());
-            }
-
-            // Emit opcode with OP_VERIFY for 64-bit ops (same as emit_binary_op_asm)
-            match op.as_str() {
-                "+" => {
-                    asm.push(OP_ADD64.to_string());
-                    asm.push(OP_VERIFY.to_string());
-                }
-                "-" => {
-                    asm.push(OP_SUB64.to_string());
-                    asm.push(OP_VERIFY.to_string());
-                }
-                "*" => {
-                    asm.push(OP_MUL64.to_string());
-                    asm.push(OP_VERIFY.to_string());
-                }
-                "/" => {
-                    asm.push(OP_DIV64.to_string());
-                    asm.push(OP_VERIFY.to_string());
-                }
-                ">=" => {
-                    asm.push(OP_GREATERTHANOREQUAL64.to_string());
-                    asm.push(OP_VERIFY.to_string());
-                }
-                "<=" => {
-                    asm.push(OP_LESSTHANOREQUAL64.to_string());
-                    asm.push(OP_VERIFY.to_string());
-                }
-                ">" => {
-                    asm.push(OP_GREATERTHAN64.to_string());
-                    asm.push(OP_VERIFY.to_string());
-                }
-                "<" => {
-                    asm.push(OP_LESSTHAN64.to_string());
-                    asm.push(OP_VERIFY.to_string());
-                }
-                "==" => asm.push(OP_EQUAL.to_string()),
-                "!=" => {
-                    asm.push(OP_EQUAL.to_string());
-                    asm.push(OP_NOT.to_string());
-                }
-                _ => asm.push(OP_FALSE.to_string()),
-            }
-        }
-        Expression::CurrentInput(property) => {
-            if let Some(prop) = property {
-                match prop.as_str() {
-                    "scriptPubKey" => asm.push(OP_INPUTBYTECODE.to_string()),
-                    "value" => asm.push(OP_INPUTVALUE.to_string()),
-                    "sequence" => asm.push(OP_INPUTSEQUENCE.to_string()),
-                    "outpoint" => asm.push(OP_INPUTOUTPOINT.to_string()),
-                    _ => asm.push(OP_INPUTBYTECODE.to_string()),
-                }
+            if op == "&&" {
+                asm.push(OP_BOOLAND.to_string());
             } else {
-                asm.push(OP_INPUTBYTECODE.to_string());
+                asm.push(OP_BOOLOR.to_string());
             }
         }
+        Expression::BinaryOp { left, op, right } if is_arithmetic_op(op) => {
+            emit_binary_op_asm(left, op, right, asm);
+        }
+        Expression::BinaryOp { .. } => emit_condition_asm(expr, asm),
+        Expression::CurrentInput(property) => emit_current_input_asm(property.as_deref(), asm),
         Expression::ArrayIndex { array, index } => {
             // TODO: Implement array indexing in Commit 6
             generate_expression_asm(array, asm);
@@ -953,9 +1827,10 @@ fn generate_expression_asm(expr: &Expression, asm: &mut Vec<String>) {
         Expression::ArrayLength(_) => {
             // TODO: Implement array length in Commit 6
         }
+        Expression::CheckSigCount { pubkeys } => push_sig_count_asm(pubkeys, asm),
         Expression::CheckSigExpr { signature, pubkey } => {
-            asm.push(format!("<{}>", pubkey));
             asm.push(format!("<{}>", signature));
+            asm.push(format!("<{}>", pubkey));
             asm.push(OP_CHECKSIG.to_string());
         }
         Expression::CheckSigFromStackExpr {
@@ -963,9 +1838,9 @@ fn generate_expression_asm(expr: &Expression, asm: &mut Vec<String>) {
             pubkey,
             message,
         } => {
+            asm.push(format!("<{}>", signature));
             asm.push(format!("<{}>", message));
             asm.push(format!("<{}>", pubkey));
-            asm.push(format!("<{}>", signature));
             asm.push(OP_CHECKSIGFROMSTACK.to_string());
         }
         // Streaming SHA256
@@ -991,6 +1866,10 @@ fn generate_expression_asm(expr: &Expression, asm: &mut Vec<String>) {
             generate_expression_asm(value, asm);
             asm.push(OP_NEG64.to_string());
         }
+        Expression::Not { value } => {
+            generate_expression_asm(value, asm);
+            asm.push(OP_NOT.to_string());
+        }
         Expression::Le64ToScriptNum { value } => {
             generate_expression_asm(value, asm);
             asm.push(OP_LE64TOSCRIPTNUM.to_string());
@@ -1025,9 +1904,9 @@ fn generate_expression_asm(expr: &Expression, asm: &mut Vec<String>) {
             pubkey,
             message,
         } => {
+            asm.push(format!("<{}>", signature));
             asm.push(format!("<{}>", message));
             asm.push(format!("<{}>", pubkey));
-            asm.push(format!("<{}>", signature));
             asm.push(OP_CHECKSIGFROMSTACKVERIFY.to_string());
         }
         Expression::AssetLookup {
@@ -1123,113 +2002,12 @@ fn generate_expression_asm(expr: &Expression, asm: &mut Vec<String>) {
     }
 }
 
-/// Generate assembly for comparison expressions
-fn generate_comparison_asm(left: &Expression, op: &str, right: &Expression, asm: &mut Vec<String>) {
-    match (left, op, right) {
-        (Expression::Variable(var), ">=", Expression::Literal(value)) => {
-            asm.push(format!("<{}>", var));
-            asm.push(OP_GREATERTHANOREQUAL.to_string());
-            asm.push(value.clone());
-        }
-        (Expression::Variable(var), "==", Expression::Variable(var2)) => {
-            asm.push(format!("<{}>", var));
-            asm.push(OP_EQUAL.to_string());
-            asm.push(format!("<{}>", var2));
-        }
-        (Expression::Variable(var), ">=", Expression::Variable(var2)) => {
-            asm.push(format!("<{}>", var));
-            asm.push(OP_GREATERTHANOREQUAL.to_string());
-            asm.push(format!("<{}>", var2));
-        }
-        (Expression::Variable(var), "==", Expression::Property(prop)) => {
-            asm.push(format!("<{}>", var));
-            asm.push(OP_EQUAL.to_string());
-            asm.push(format!("<{}>", prop));
-        }
-        (Expression::Variable(var), ">=", Expression::Property(prop)) => {
-            asm.push(format!("<{}>", var));
-            asm.push(OP_GREATERTHANOREQUAL.to_string());
-            asm.push(format!("<{}>", prop));
-        }
-        (Expression::Literal(lit), "==", Expression::Variable(var)) => {
-            asm.push(lit.clone());
-            asm.push(OP_EQUAL.to_string());
-            asm.push(format!("<{}>", var));
-        }
-        (Expression::Literal(lit), ">=", Expression::Variable(var)) => {
-            asm.push(lit.clone());
-            asm.push(OP_GREATERTHANOREQUAL.to_string());
-            asm.push(format!("<{}>", var));
-        }
-        (Expression::Literal(lit), "==", Expression::Literal(value)) => {
-            asm.push(lit.clone());
-            asm.push(OP_EQUAL.to_string());
-            asm.push(value.clone());
-        }
-        (Expression::Literal(lit), ">=", Expression::Literal(value)) => {
-            asm.push(lit.clone());
-            asm.push(OP_GREATERTHANOREQUAL.to_string());
-            asm.push(value.clone());
-        }
-        (Expression::Literal(lit), "==", Expression::Property(prop)) => {
-            asm.push(lit.clone());
-            asm.push(OP_EQUAL.to_string());
-            asm.push(format!("<{}>", prop));
-        }
-        (Expression::Literal(lit), ">=", Expression::Property(prop)) => {
-            asm.push(lit.clone());
-            asm.push(OP_GREATERTHANOREQUAL.to_string());
-            asm.push(format!("<{}>", prop));
-        }
-        (Expression::Property(prop), "==", Expression::Variable(var)) => {
-            asm.push(format!("<{}>", prop));
-            asm.push(OP_EQUAL.to_string());
-            asm.push(format!("<{}>", var));
-        }
-        (Expression::Property(prop), ">=", Expression::Variable(var)) => {
-            asm.push(format!("<{}>", prop));
-            asm.push(OP_GREATERTHANOREQUAL.to_string());
-            asm.push(format!("<{}>", var));
-        }
-        (Expression::Property(prop), "==", Expression::Literal(value)) => {
-            asm.push(format!("<{}>", prop));
-            asm.push(OP_EQUAL.to_string());
-            asm.push(value.clone());
-        }
-        (Expression::Property(prop), ">=", Expression::Literal(value)) => {
-            asm.push(format!("<{}>", prop));
-            asm.push(OP_GREATERTHANOREQUAL.to_string());
-            asm.push(value.clone());
-        }
-        (Expression::Property(prop), "==", Expression::Property(prop2)) => {
-            asm.push(format!("<{}>", prop));
-            asm.push(OP_EQUAL.to_string());
-            asm.push(format!("<{}>", prop2));
-        }
-        (Expression::Property(prop), ">=", Expression::Property(prop2)) => {
-            asm.push(format!("<{}>", prop));
-            asm.push(OP_GREATERTHANOREQUAL.to_string());
-            asm.push(format!("<{}>", prop2));
-        }
-        (Expression::CurrentInput(property), "==", Expression::Literal(value)) => {
-            if value == "true" {
-                if let Some(prop) = property {
-                    match prop.as_str() {
-                        "scriptPubKey" => asm.push(OP_INPUTBYTECODE.to_string()),
-                        "value" => asm.push(OP_INPUTVALUE.to_string()),
-                        "sequence" => asm.push(OP_INPUTSEQUENCE.to_string()),
-                        "outpoint" => asm.push(OP_INPUTOUTPOINT.to_string()),
-                        _ => asm.push(OP_INPUTBYTECODE.to_string()),
-                    }
-                } else {
-                    asm.push(OP_INPUTBYTECODE.to_string());
-                }
-            }
-        }
-        _ => {
-            // For all other expression types, delegate to emit_comparison_asm
-            emit_comparison_asm(left, op, right, asm);
-        }
+/// The sequence `tx.age >= age` pushes for `OP_CHECKSEQUENCEVERIFY`: the
+/// timelock parameter, or the BIP68 encoding of `age`.
+fn older_lock(age: &Duration, timelock_var: &Option<String>) -> Result<String, String> {
+    match timelock_var {
+        Some(var) => Ok(format!("<{}>", var)),
+        None => Ok(age.sequence()?.to_string()),
     }
 }
 
@@ -1240,9 +2018,12 @@ fn generate_base_asm_instructions(requirements: &[Requirement]) -> Vec<String> {
 
     for req in requirements {
         match req {
-            Requirement::CheckSig { signature, pubkey } => {
-                asm.push(format!("<{}>", pubkey));
-                asm.push(format!("<{}>", signature));
+            Requirement::CheckSig {
+                signature,
+                pubkey,
+                sighash,
+            } => {
+                push_checksig_operands(signature, pubkey, *sighash, &mut asm);
                 asm.push(OP_CHECKSIG.to_string());
             }
             Requirement::CheckSigFromStack {
@@ -1251,14 +2032,14 @@ fn generate_base_asm_instructions(requirements: &[Requirement]) -> Vec<String> {
                 message,
             } => {
                 asm.push(format!("<{}>", message));
-                asm.push(format!("<{}>", pubkey));
                 asm.push(format!("<{}>", signature));
+                asm.push(format!("<{}>", pubkey));
                 asm.push(OP_CHECKSIGFROMSTACK.to_string());
             }
             Requirement::CheckMultisig {
                 // signatures,
-                pubkeys,
-                threshold,
+                pubkeys: _,
+                threshold: _,
             } => {
                 // We cannot update here, this is dead code
 
@@ -1273,23 +2054,40 @@ fn generate_base_asm_instructions(requirements: &[Requirement]) -> Vec<String> {
                 asm.push(OP_CHECKMULTISIG.to_string());*/
             }
             Requirement::After {
-                blocks,
+                locktime,
                 timelock_var,
             } => {
                 if let Some(var) = timelock_var {
                     asm.push(format!("<{}>", var));
                 } else {
-                    asm.push(format!("{}", blocks));
+                    asm.push(format!("{}", locktime));
                 }
                 asm.push(OP_CHECKLOCKTIMEVERIFY.to_string());
                 asm.push(OP_DROP.to_string());
             }
-            Requirement::HashEqual { preimage, hash } => {
+            Requirement::Older { age, timelock_var } => {
+                if let Ok(lock) = older_lock(age, timelock_var) {
+                    asm.push(lock);
+                    asm.push(OP_CHECKSEQUENCEVERIFY.to_string());
+                    asm.push(OP_DROP.to_string());
+                }
+            }
+            Requirement::HashEqual {
+                preimage,
+                hash,
+                function,
+            } => {
                 asm.push(format!("<{}>", preimage));
-                asm.push(OP_SHA256.to_string());
+                asm.push(function.opcode().to_string());
                 asm.push(format!("<{}>", hash));
                 asm.push(OP_EQUAL.to_string());
             }
+            Requirement::SizeBetween { value, min, max } => {
+                push_size_between_asm(value, *min, *max, &mut asm);
+            }
+            Requirement::Comparison { left, op, right } if is_logical_op(op) => {
+                emit_logical_asm(left, op, right, &mut asm);
+            }
             Requirement::Comparison { left, op, right } => {
                 emit_comparison_asm(left, op, right, &mut asm);
             }
@@ -1315,20 +2113,94 @@ fn emit_comparison_asm(left: &Expression, op: &str, right: &Expression, asm: &mu
         }
     }
 
-    // Determine if this comparison involves 64-bit values (asset lookups, group sums)
-    let is_64bit = is_64bit_expression(left) || is_64bit_expression(right);
-
-    // Emit left operand
-    emit_expression_asm(left, asm);
+    emit_compared_asm(left, op, right, asm);
+}
 
-    // Emit right operand
-    emit_expression_asm(right, asm);
+/// Whether `op` joins conditions rather than comparing values.
+fn is_logical_op(op: &str) -> bool {
+    op == "&&" || op == "||"
+}
 
-    // Emit comparison operator (correct Bitcoin Script order: left, right, op)
-    if is_64bit {
-        emit_comparison_op_64(op, asm);
+/// Emit assembly for `left && right` or `left || right`: both conditions are
+/// evaluated and their results combined with OP_BOOLAND or OP_BOOLOR.
+fn emit_logical_asm(left: &Expression, op: &str, right: &Expression, asm: &mut Vec<String>) {
+    emit_condition_asm(left, asm);
+    emit_condition_asm(right, asm);
+    if op == "&&" {
+        asm.push(OP_BOOLAND.to_string());
     } else {
+        asm.push(OP_BOOLOR.to_string());
+    }
+}
+
+/// Emit assembly leaving the result of a condition on the stack.
+fn emit_condition_asm(expr: &Expression, asm: &mut Vec<String>) {
+    let (left, op, right) = match expr {
+        Expression::BinaryOp { left, op, right } if is_logical_op(op) => {
+            emit_logical_asm(left, op, right, asm);
+            return;
+        }
+        Expression::BinaryOp { left, op, right }
+            if matches!(op.as_str(), "==" | "!=" | ">=" | ">" | "<=" | "<") =>
+        {
+            (left, op, right)
+        }
+        _ => {
+            emit_expression_asm(expr, asm);
+            return;
+        }
+    };
+    emit_compared_asm(left, op, right, asm);
+}
+
+/// Emit `left op right`, leaving whether it holds. 64-bit values are
+/// compared with the 64-bit opcodes, which take u64le operands.
+fn emit_compared_asm(left: &Expression, op: &str, right: &Expression, asm: &mut Vec<String>) {
+    if let Some((group, id)) = compared_asset_id(left, op, right) {
+        emit_expression_asm(group, asm);
+        asm.push(format!("<{}_txid>", id));
+        asm.push(format!("<{}_gidx>", id));
+        // Group indexes first: a control of -1 fails here
+        asm.push(OP_ROT.to_string());
+        asm.push(OP_EQUALVERIFY.to_string());
+        asm.push(OP_EQUAL.to_string());
+        return;
+    }
+    let is_64bit = is_64bit_expression(left) || is_64bit_expression(right);
+    emit_operand_asm(left, is_64bit, asm);
+    emit_operand_asm(right, is_64bit, asm);
+    if !is_64bit {
         emit_comparison_op(op, asm);
+        return;
+    }
+    match op {
+        ">=" => asm.push(OP_GREATERTHANOREQUAL64.to_string()),
+        ">" => asm.push(OP_GREATERTHAN64.to_string()),
+        "<=" => asm.push(OP_LESSTHANOREQUAL64.to_string()),
+        "<" => asm.push(OP_LESSTHAN64.to_string()),
+        _ => emit_comparison_op(op, asm),
+    }
+}
+
+/// For `group.control == id` or `group.assetId == id`, either way round,
+/// the group property and the asset id it is compared with. Both are an
+/// asset ID as two values, txid and group index.
+fn compared_asset_id<'a>(
+    left: &'a Expression,
+    op: &str,
+    right: &'a Expression,
+) -> Option<(&'a Expression, &'a str)> {
+    let is_id = |expr: &Expression| {
+        matches!(expr, Expression::GroupProperty { property, .. }
+            if property == "control" || property == "assetId")
+    };
+    match (left, right) {
+        (group, Expression::Variable(id)) | (Expression::Variable(id), group)
+            if op == "==" && is_id(group) =>
+        {
+            Some((group, id))
+        }
+        _ => None,
     }
 }
 
@@ -1342,15 +2214,58 @@ fn is_64bit_expression(expr: &Expression) -> bool {
         // Input/Output "value" property returns u64
         Expression::InputIntrospection { property, .. } => property == "value",
         Expression::OutputIntrospection { property, .. } => property == "value",
-        Expression::BinaryOp { left, right, .. } => {
-            is_64bit_expression(left) || is_64bit_expression(right)
+        // Arithmetic is done in 64 bits
+        Expression::BinaryOp { left, op, right } => {
+            is_arithmetic_op(op) || is_64bit_expression(left) || is_64bit_expression(right)
         }
         _ => false,
     }
 }
 
+/// Whether `expr` is a u64le value by its type: an amount, or arithmetic
+/// on one.
+fn is_u64_value(expr: &Expression) -> bool {
+    match expr {
+        Expression::BinaryOp { left, op, right } if is_arithmetic_op(op) => {
+            is_u64_value(left) || is_u64_value(right)
+        }
+        Expression::BinaryOp { .. } => false,
+        _ => is_64bit_expression(expr),
+    }
+}
+
+/// Emit one side of a comparison. In a 64-bit comparison a script number
+/// operand is converted to u64le, as the other side is.
+fn emit_operand_asm(operand: &Expression, is_64bit: bool, asm: &mut Vec<String>) {
+    if is_64bit {
+        emit_u64_operand_asm(operand, asm);
+    } else {
+        emit_expression_asm(operand, asm);
+    }
+}
+
+/// Emit an operand of a 64-bit opcode as u64le: a literal as an 8-byte
+/// push and a witness value converted from a script number.
+fn emit_u64_operand_asm(operand: &Expression, asm: &mut Vec<String>) {
+    if let Expression::Literal(lit) = operand {
+        if let Ok(n) = lit.parse::<i64>() {
+            asm.push(encoding::le64_push(n));
+            return;
+        }
+    }
+    emit_expression_asm(operand, asm);
+    if needs_u64_conversion(operand) {
+        asm.push(OP_SCRIPTNUMTOLE64.to_string());
+    }
+}
+
+/// Whether `op` is one of the 64-bit arithmetic operators.
+fn is_arithmetic_op(op: &str) -> bool {
+    matches!(op, "+" | "-" | "*" | "/" | "%")
+}
+
 /// Emit assembly for an expression (push its value onto the stack)
-fn emit_expression_asm(expr: &Expression, asm: &mut Vec<String>) {
+pub(crate) fn emit_expression_asm(expr: &Expression, asm: &mut Vec<String>) {
     match expr {
         Expression::Variable(var) => {
             asm.push(format!("<{}>", var));
@@ -1358,9 +2273,7 @@ fn emit_expression_asm(expr: &Expression, asm: &mut Vec<String>) {
         Expression::Literal(lit) => {
             asm.push(lit.clone());
         }
-        Expression::Property(prop) => {
-            asm.push(format!("<{}>", prop));
-        }
+        Expression::Property(prop) => emit_property_asm(prop, asm),
         Expression::CurrentInput(property) => {
             emit_current_input_asm(property.as_deref(), asm);
         }
@@ -1463,9 +2376,10 @@ fn emit_expression_asm(expr: &Expression, asm: &mut Vec<String>) {
         Expression::ArrayLength(_) => {
             // TODO: Implement array length in Commit 6
         }
+        Expression::CheckSigCount { pubkeys } => push_sig_count_asm(pubkeys, asm),
         Expression::CheckSigExpr { signature, pubkey } => {
-            asm.push(format!("<{}>", pubkey));
             asm.push(format!("<{}>", signature));
+            asm.push(format!("<{}>", pubkey));
             asm.push(OP_CHECKSIG.to_string());
         }
         Expression::CheckSigFromStackExpr {
@@ -1473,9 +2387,9 @@ fn emit_expression_asm(expr: &Expression, asm: &mut Vec<String>) {
             pubkey,
             message,
         } => {
+            asm.push(format!("<{}>", signature));
             asm.push(format!("<{}>", message));
             asm.push(format!("<{}>", pubkey));
-            asm.push(format!("<{}>", signature));
             asm.push(OP_CHECKSIGFROMSTACK.to_string());
         }
         // Streaming SHA256
@@ -1501,6 +2415,10 @@ fn emit_expression_asm(expr: &Expression, asm: &mut Vec<String>) {
             emit_expression_asm(value, asm);
             asm.push(OP_NEG64.to_string());
         }
+        Expression::Not { value } => {
+            emit_condition_asm(value, asm);
+            asm.push(OP_NOT.to_string());
+        }
         Expression::Le64ToScriptNum { value } => {
             emit_expression_asm(value, asm);
             asm.push(OP_LE64TOSCRIPTNUM.to_string());
@@ -1535,14 +2453,31 @@ fn emit_expression_asm(expr: &Expression, asm: &mut Vec<String>) {
             pubkey,
             message,
         } => {
+            asm.push(format!("<{}>", signature));
             asm.push(format!("<{}>", message));
             asm.push(format!("<{}>", pubkey));
-            asm.push(format!("<{}>", signature));
             asm.push(OP_CHECKSIGFROMSTACKVERIFY.to_string());
         }
     }
 }
 
+/// Emit assembly for a property kept as text. A hash call such as
+/// `sha256(data)` on a name hashes it; anything else is a placeholder.
+fn emit_property_asm(prop: &str, asm: &mut Vec<String>) {
+    let call = HashFunction::split_call(prop).filter(|(_, argument)| {
+        argument
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    });
+    match call {
+        Some((function, argument)) => {
+            asm.push(format!("<{}>", argument));
+            asm.push(function.opcode().to_string());
+        }
+        None => asm.push(format!("<{}>", prop)),
+    }
+}
+
 /// Emit assembly for tx.input.current property access
 fn emit_current_input_asm(property: Option<&str>, asm: &mut Vec<String>) {
     match property {
@@ -1758,21 +2693,9 @@ fn emit_output_introspection_asm(index: &Expression, property: &str, asm: &mut V
 
 /// Emit assembly for a binary arithmetic operation (64-bit)
 fn emit_binary_op_asm(left: &Expression, op: &str, right: &Expression, asm: &mut Vec<String>) {
-    // Emit left operand
-    emit_expression_asm(left, asm);
-
-    // Convert to u64le if needed (witness inputs arrive as csn)
-    if needs_u64_conversion(left) {
-        asm.push(OP_SCRIPTNUMTOLE64.to_string());
-    }
-
-    // Emit right operand
-    emit_expression_asm(right, asm);
-
-    // Convert to u64le if needed
-    if needs_u64_conversion(right) {
-        asm.push(OP_SCRIPTNUMTOLE64.to_string());
-    }
+    // Witness inputs arrive as csn and literals are pushed as u64le
+    emit_u64_operand_asm(left, asm);
+    emit_u64_operand_asm(right, asm);
 
     // Emit 64-bit arithmetic opcode + overflow verify
     match op {
@@ -1788,9 +2711,16 @@ fn emit_binary_op_asm(left: &Expression, op: &str, right: &Expression, asm: &mut
             asm.push(OP_MUL64.to_string());
             asm.push(OP_VERIFY.to_string());
         }
+        // OP_DIV64 leaves the remainder under the quotient
         "/" => {
             asm.push(OP_DIV64.to_string());
             asm.push(OP_VERIFY.to_string());
+            asm.push(OP_NIP.to_string());
+        }
+        "%" => {
+            asm.push(OP_DIV64.to_string());
+            asm.push(OP_VERIFY.to_string());
+            asm.push(OP_DROP.to_string());
         }
         _ => {
             asm.push(format!("OP_{}", op.to_uppercase()));
@@ -1803,7 +2733,7 @@ fn needs_u64_conversion(expr: &Expression) -> bool {
     match expr {
         // Variables (witness inputs) arrive as CScriptNum
         Expression::Variable(_) => true,
-        // Literals are emitted as-is (caller should provide 8-byte LE)
+        // Numeric literals are pushed as u64le instead
         Expression::Literal(_) => false,
         // Asset lookups already produce u64le
         Expression::AssetLookup { .. } => false,
@@ -1903,41 +2833,6 @@ fn emit_comparison_op(op: &str, asm: &mut Vec<String>) {
     }
 }
 
-/// Emit 64-bit comparison operator (u64le operands)
-fn emit_comparison_op_64(op: &str, asm: &mut Vec<String>) {
-    match op {
-        "==" => {
-            asm.push(OP_EQUAL.to_string());
-            asm.push(OP_VERIFY.to_string());
-        }
-        "!=" => {
-            asm.push(OP_EQUAL.to_string());
-            asm.push(OP_NOT.to_string());
-            asm.push(OP_VERIFY.to_string());
-        }
-        ">=" => {
-            asm.push(OP_GREATERTHANOREQUAL64.to_string());
-            asm.push(OP_VERIFY.to_string());
-        }
-        ">" => {
-            asm.push(OP_GREATERTHAN64.to_string());
-            asm.push(OP_VERIFY.to_string());
-        }
-        "<=" => {
-            asm.push(OP_LESSTHANOREQUAL64.to_string());
-            asm.push(OP_VERIFY.to_string());
-        }
-        "<" => {
-            asm.push(OP_LESSTHAN64.to_string());
-            asm.push(OP_VERIFY.to_string());
-        }
-        _ => {
-            asm.push(format!("OP_{}", op));
-            asm.push(OP_VERIFY.to_string());
-        }
-    }
-}
-
 // ─── Loop Unrolling (Commit 5 & 6) ──────────────────────────────────────────────
 
 /// Substitute loop variables in the body for a specific iteration index k.
@@ -1948,7 +2843,7 @@ fn emit_comparison_op_64(op: &str, asm: &mut Vec<String>) {
 /// - `Variable(index_var)` → `Literal(k)`
 /// - `Variable(value_var)` when array_name is Some → `Variable("array_name_{k}")`
 /// - Array indexing `arr[index_var]` → `Variable("arr_{k}")`
-fn substitute_loop_body(
+pub(crate) fn substitute_loop_body(
     body: &[Statement],
     index_var: &str,
     value_var: &str,
@@ -1968,9 +2863,10 @@ fn substitute_statement(
     array_name: Option<&String>,
 ) -> Statement {
     match stmt {
-        Statement::Require(req) => Statement::Require(substitute_requirement(
-            req, index_var, value_var, k, array_name,
-        )),
+        Statement::Require(req, span) => Statement::Require(
+            substitute_requirement(req, index_var, value_var, k, array_name),
+            *span,
+        ),
         Statement::LetBinding { name, value } => Statement::LetBinding {
             name: name.clone(),
             value: substitute_expression(value, index_var, value_var, k, array_name),
@@ -1983,12 +2879,14 @@ fn substitute_statement(
             condition,
             then_body,
             else_body,
+            span,
         } => Statement::IfElse {
             condition: substitute_expression(condition, index_var, value_var, k, array_name),
             then_body: substitute_loop_body(then_body, index_var, value_var, k, array_name),
             else_body: else_body
                 .as_ref()
                 .map(|b| substitute_loop_body(b, index_var, value_var, k, array_name)),
+            span: *span,
         },
         Statement::ForIn {
             index_var: inner_idx,
@@ -2020,7 +2918,11 @@ fn substitute_requirement(
             op: op.clone(),
             right: substitute_expression(right, index_var, value_var, k, array_name),
         },
-        Requirement::CheckSig { signature, pubkey } => {
+        Requirement::CheckSig {
+            signature,
+            pubkey,
+            sighash,
+        } => {
             // Substitute signature and pubkey if they match loop variables
             let new_sig = if signature == value_var {
                 if let Some(arr) = array_name {
@@ -2031,10 +2933,11 @@ fn substitute_requirement(
             } else {
                 signature.clone()
             };
-            let new_pk = pubkey.clone();
+            let new_pk = substitute_indexed_name(pubkey, index_var, k);
             Requirement::CheckSig {
                 signature: new_sig,
                 pubkey: new_pk,
+                sighash: *sighash,
             }
         }
         Requirement::CheckSigFromStack {
@@ -2052,8 +2955,8 @@ fn substitute_requirement(
             } else {
                 signature.clone()
             };
-            let new_pk = pubkey.clone();
-            let new_msg = message.clone();
+            let new_pk = substitute_indexed_name(pubkey, index_var, k);
+            let new_msg = substitute_message(message, index_var, value_var, k, array_name);
             Requirement::CheckSigFromStack {
                 signature: new_sig,
                 pubkey: new_pk,
@@ -2065,6 +2968,29 @@ fn substitute_requirement(
     }
 }
 
+/// `arr[index_var]` (string form) → `arr_{k}`; other names are unchanged.
+fn substitute_indexed_name(name: &str, index_var: &str, k: usize) -> String {
+    match name.strip_suffix(']').and_then(|n| n.split_once('[')) {
+        Some((array, index)) if index == index_var => format!("{}_{}", array, k),
+        _ => name.to_string(),
+    }
+}
+
+/// The message a signature is checked against in iteration `k`: the
+/// iteration's element for the loop value, or `msgs[index]` by position.
+fn substitute_message(
+    message: &str,
+    index_var: &str,
+    value_var: &str,
+    k: usize,
+    array_name: Option<&String>,
+) -> String {
+    match array_name {
+        Some(arr) if message == value_var => format!("{}_{}", arr, k),
+        _ => substitute_indexed_name(message, index_var, k),
+    }
+}
+
 fn substitute_expression(
     expr: &Expression,
     index_var: &str,
@@ -2157,30 +3083,11 @@ fn substitute_expression(
             } else {
                 signature.clone()
             };
-            // Check if pubkey is an array indexed expression (string form)
-            let new_pk = if pubkey.contains('[') && pubkey.contains(']') {
-                if let Some(bracket_start) = pubkey.find('[') {
-                    if let Some(bracket_end) = pubkey.find(']') {
-                        let arr_name = &pubkey[..bracket_start];
-                        let idx = &pubkey[bracket_start + 1..bracket_end];
-                        if idx == index_var {
-                            format!("{}_{}", arr_name, k)
-                        } else {
-                            pubkey.clone()
-                        }
-                    } else {
-                        pubkey.clone()
-                    }
-                } else {
-                    pubkey.clone()
-                }
-            } else {
-                pubkey.clone()
-            };
+            let new_pk = substitute_indexed_name(pubkey, index_var, k);
             Expression::CheckSigFromStackExpr {
                 signature: new_sig,
                 pubkey: new_pk,
-                message: message.clone(),
+                message: substitute_message(message, index_var, value_var, k, array_name),
             }
         }
         // Handle CheckSigExpr
@@ -2196,7 +3103,7 @@ fn substitute_expression(
             };
             Expression::CheckSigExpr {
                 signature: new_sig,
-                pubkey: pubkey.clone(),
+                pubkey: substitute_indexed_name(pubkey, index_var, k),
             }
         }
         // Handle InputIntrospection - substitute index if it matches loop variable
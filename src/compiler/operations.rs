@@ -0,0 +1,107 @@
+//! The `operations` of a leaf: its `asm`, token by token, described.
+//!
+//! A token is an opcode, a constant push, or a `<name>` placeholder for a
+//! constructor argument, a witness element, a value the Ark runtime
+//! provides or a value computed earlier in the leaf. Tools can switch on the kind instead of telling them apart by
+//! the shape of the token and looking placeholder names up in the artifact.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::encoding::{encode_num, value_size};
+use crate::ir::Instr;
+use crate::models::{Operation, OperationData, Parameter, WitnessElement};
+
+/// Bytes of the x-only server key.
+const SERVER_KEY_SIZE: usize = 32;
+
+/// Bytes of the P2TR scriptPubKey a `<VTXO:...>` template stands for.
+const VTXO_SCRIPT_SIZE: usize = 34;
+
+/// Describe each token of `asm`, a leaf whose constructor inputs are
+/// `parameters` and whose witness elements are `witness`.
+pub(crate) fn operations(
+    asm: &[String],
+    parameters: &[Parameter],
+    witness: &[WitnessElement],
+) -> Vec<Operation> {
+    asm.iter()
+        .map(|token| operation(token, parameters, witness))
+        .collect()
+}
+
+/// Describe one token, as [`operations`] does.
+pub(crate) fn operation(
+    token: &str,
+    parameters: &[Parameter],
+    witness: &[WitnessElement],
+) -> Operation {
+    Operation {
+        op: token.to_string(),
+        data: describe(token, parameters, witness),
+    }
+}
+
+/// A push of `bytes`, shown as `token`.
+#[cfg(feature = "std")]
+pub(crate) fn push(token: String, bytes: &[u8]) -> Operation {
+    Operation {
+        op: token,
+        data: OperationData::Push {
+            hex: hex::encode(bytes),
+            size: bytes.len(),
+        },
+    }
+}
+
+fn describe(token: &str, parameters: &[Parameter], witness: &[WitnessElement]) -> OperationData {
+    match Instr::parse(token) {
+        Instr::Num(n) => {
+            let bytes = encode_num(n);
+            OperationData::Push {
+                hex: hex::encode(&bytes),
+                size: bytes.len(),
+            }
+        }
+        Instr::Raw(token) => match token.strip_prefix("0x") {
+            Some(data) => OperationData::Push {
+                size: data.len() / 2,
+                hex: data.to_string(),
+            },
+            None => OperationData::Opcode,
+        },
+        Instr::Op(_) => OperationData::Opcode,
+        Instr::Placeholder(name) => {
+            if let Some(element) = witness.iter().find(|w| w.name == name) {
+                OperationData::FunctionInput {
+                    size: match element.sighash {
+                        Some(_) => Some(64),
+                        None => value_size(&element.elem_type),
+                    },
+                    param_type: element.elem_type.clone(),
+                    name,
+                }
+            } else if let Some(param) = parameters.iter().find(|p| p.name == name) {
+                OperationData::ConstructorParam {
+                    size: value_size(&param.param_type),
+                    param_type: param.param_type.clone(),
+                    name,
+                }
+            } else if name == "SERVER_KEY" {
+                OperationData::Runtime {
+                    name,
+                    size: SERVER_KEY_SIZE,
+                }
+            } else if name.starts_with("VTXO:") {
+                OperationData::Runtime {
+                    name,
+                    size: VTXO_SCRIPT_SIZE,
+                }
+            } else {
+                OperationData::Local { name }
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Script assembly.
+//!
+//! Turns the `asm` of a leaf into the serialized script: opcodes become
+//! their bytes, numbers the minimal push of a script number, `0x` hex
+//! tokens the smallest data push that fits, and `<name>` placeholders
+//! whatever the caller pushes for them.
+
+use alloc::{format, string::String, vec::Vec};
+
+use crate::encoding::{push_data, push_num};
+use crate::models::AbiFunction;
+use crate::opcodes::opcode_byte;
+
+/// Assemble `asm`, calling `placeholder` with the name of each `<name>`
+/// token and the script so far, to push its value or leave it out.
+///
+/// Fails on opcodes with no encoding yet, such as the asset group opcodes,
+/// on tokens that are none of the above and on the first error
+/// `placeholder` returns.
+pub fn assemble(
+    asm: &[String],
+    mut placeholder: impl FnMut(&str, &mut Vec<u8>) -> Result<(), String>,
+) -> Result<Vec<u8>, String> {
+    let mut script = Vec::new();
+    for token in asm {
+        if token.starts_with("OP_") {
+            let byte =
+                opcode_byte(token).ok_or_else(|| format!("{} has no known encoding", token))?;
+            script.push(byte);
+        } else if let Some(name) = token.strip_prefix('<').and_then(|t| t.strip_suffix('>')) {
+            placeholder(name, &mut script)?;
+        } else if let Ok(n) = token.parse::<i64>() {
+            push_num(&mut script, n);
+        } else if let Some(bytes) = token.strip_prefix("0x").and_then(|h| hex::decode(h).ok()) {
+            push_data(&mut script, &bytes);
+        } else {
+            return Err(format!("cannot assemble '{}'", token));
+        }
+    }
+    Ok(script)
+}
+
+/// The leaf script of `function` as hex, if its only placeholders are
+/// witness elements, which the script leaves out.
+pub(crate) fn script_hex(function: &AbiFunction) -> Option<String> {
+    assemble(&function.asm, |name, _| {
+        match function.witness_schema.iter().any(|w| w.name == name) {
+            true => Ok(()),
+            false => Err(format!("<{}> is not linked", name)),
+        }
+    })
+    .ok()
+    .map(hex::encode)
+}
@@ -0,0 +1,96 @@
+//! Witness size estimation.
+//!
+//! A script-path spend carries the witness stack elements, the leaf script
+//! and the control block. Element sizes follow the `encoding` of each
+//! [`WitnessElement`]; values whose length is only known at spend time
+//! (`raw`, `scriptnum`) are sized at their usual upper bound so estimates err
+//! on the side of overpaying. The taproot tree is assumed balanced, which is
+//! how Ark libraries build it from the artifact's leaves.
+
+use crate::models::{SizeEstimate, WitnessElement, WitnessSize, INPUT_BYTES};
+
+/// Bytes assumed for a `raw` element of unknown length.
+const RAW_ELEMENT_SIZE: usize = 32;
+
+/// A control block's leaf version/parity byte plus the internal key.
+const CONTROL_BLOCK_BASE: usize = 33;
+
+/// Each level of the taproot merkle path adds one 32-byte hash.
+const MERKLE_NODE_SIZE: usize = 32;
+
+/// Estimate the witness of one leaf.
+///
+/// `script_len` is the serialized length of the leaf script and `leaf_count`
+/// the number of leaves in the contract's taproot tree.
+pub(crate) fn estimate(
+    schema: &[WitnessElement],
+    script_len: usize,
+    leaf_count: usize,
+) -> WitnessSize {
+    let stack = schema
+        .iter()
+        .map(|e| with_length_prefix(element_size(e)))
+        .sum();
+    let script = with_length_prefix(script_len);
+    let control_block = with_length_prefix(control_block_size(leaf_count));
+
+    // The witness starts with the number of stack items: the elements, the
+    // script and the control block
+    let items = compact_size_len(schema.len() + 2);
+
+    WitnessSize {
+        stack,
+        script,
+        control_block,
+        total: items + stack + script + control_block,
+    }
+}
+
+/// The script, witness and input weight of spending one leaf, whose
+/// witness is `witness`.
+pub(crate) fn summarize(script_len: usize, witness: &WitnessSize) -> SizeEstimate {
+    SizeEstimate {
+        script: script_len,
+        witness: witness.total,
+        weight: INPUT_BYTES * 4 + witness.total,
+    }
+}
+
+/// Bytes of a single witness element, without its length prefix.
+fn element_size(element: &WitnessElement) -> usize {
+    match element.encoding.as_str() {
+        "xonly-32" => 32,
+        "schnorr-64" => 64,
+        // At most: a non-default sighash flag adds a byte
+        "schnorr-sighash" => 65,
+        "raw-20" => 20,
+        "raw-32" => 32,
+        "le64" => 8,
+        "le32" => 4,
+        // Booleans are minimally encoded as a single byte (or none)
+        "scriptnum" if element.elem_type == "bool" => 1,
+        // CScriptNum operands are at most 4 bytes, 5 for locktimes
+        "scriptnum" => 5,
+        _ => RAW_ELEMENT_SIZE,
+    }
+}
+
+/// Control block length for a leaf in a balanced tree of `leaf_count` leaves.
+fn control_block_size(leaf_count: usize) -> usize {
+    let depth = leaf_count.max(1).next_power_of_two().trailing_zeros() as usize;
+    CONTROL_BLOCK_BASE + MERKLE_NODE_SIZE * depth
+}
+
+fn with_length_prefix(len: usize) -> usize {
+    compact_size_len(len) + len
+}
+
+/// Length of a Bitcoin CompactSize encoding of `n`.
+fn compact_size_len(n: usize) -> usize {
+    match n {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        0x1_0000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
@@ -0,0 +1,526 @@
+//! Stack scheduling of witness elements and bound values.
+//!
+//! The code generator reads a value by its `<name>`, wherever it is and as
+//! often as the source does. A witness element, though, is pushed once,
+//! under the script, and a `let` or assigned value once, where it is
+//! computed and named by a [`binding`] marker. [`schedule`] follows the
+//! stack through a leaf and turns each read into what finds the value
+//! there: the last read of a value moves it to the top, with `OP_SWAP`,
+//! `OP_ROT` or `OP_ROLL`, and any read before it copies it, with `OP_DUP`,
+//! `OP_OVER` or `OP_PICK`. A last read of a value already on top is left as
+//! it is: `<name>` pushes nothing for a witness element, and nothing is
+//! left of it for a bound value.
+//!
+//! A value one branch of an `if` consumes and the other does not is
+//! dropped at the end of the other, which also moves the values both keep
+//! to where the first left them. A value nothing reads is dropped where it
+//! is bound, and the value a name had before an assignment where it is
+//! assigned.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use crate::ir::{self, Instr};
+use crate::opcodes::{
+    OP_DROP, OP_DUP, OP_ELSE, OP_ENDIF, OP_IF, OP_NIP, OP_NOTIF, OP_OVER, OP_PICK, OP_ROLL, OP_ROT,
+    OP_SWAP,
+};
+
+/// Marks the value on top of the stack as bound to `name`.
+pub(super) fn binding(name: &str) -> String {
+    format!("={}", name)
+}
+
+/// What the scheduler knows about one stack element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Slot {
+    /// A witness element or bound value not consumed yet
+    Named(String),
+    /// A value the leaf computed, which it consumes where it stands
+    Temp,
+    /// Any number of values an opcode with an unknown stack effect left
+    Unknown,
+}
+
+/// The main and alt stacks, bottom first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Stacks {
+    main: Vec<Slot>,
+    alt: Vec<Slot>,
+}
+
+/// An open `OP_IF`: the stacks its branches start from, and once the then
+/// branch is closed, the stacks it left and where its `OP_ELSE` is.
+struct Branch {
+    start: Stacks,
+    then: Option<(Stacks, usize)>,
+}
+
+/// Rewrite the reads in `asm` of `witness`, the witness elements top first,
+/// and of bound values, and remove the [`binding`] markers.
+///
+/// Fails on a leaf that consumes a witness element or bound value other
+/// than by reading it, and on a read of one under the values of an opcode
+/// with an unknown stack effect.
+pub(super) fn schedule(asm: &[String], witness: &[String]) -> Result<Vec<String>, String> {
+    let instrs = ir::lift(asm);
+    let mut scheduler = Scheduler {
+        live: live_values(&instrs, witness),
+        instrs: &instrs,
+        witness,
+        stacks: Stacks {
+            main: witness
+                .iter()
+                .rev()
+                .map(|w| Slot::Named(w.clone()))
+                .collect(),
+            alt: Vec::new(),
+        },
+        out: Vec::with_capacity(asm.len()),
+    };
+    let mut branches: Vec<Branch> = Vec::new();
+    let mut i = 0;
+    while i < instrs.len() {
+        if let Some(live) = scheduler.live[i] {
+            match &instrs[i] {
+                Instr::Placeholder(_) => {
+                    i += scheduler.read(i, !live)?;
+                    continue;
+                }
+                _ => scheduler.bind(i, live)?,
+            }
+            i += 1;
+            continue;
+        }
+        let out = &mut scheduler.out;
+        match &instrs[i] {
+            Instr::Op(op) if op == OP_IF || op == OP_NOTIF => {
+                pop(&mut scheduler.stacks.main, 1, op)?;
+                branches.push(Branch {
+                    start: scheduler.stacks.clone(),
+                    then: None,
+                });
+                out.push(op.clone());
+            }
+            Instr::Op(op) if op == OP_ELSE => {
+                let branch = branches.last_mut().ok_or("OP_ELSE outside OP_IF")?;
+                let then = core::mem::replace(&mut scheduler.stacks, branch.start.clone());
+                branch.then = Some((then, out.len()));
+                out.push(op.clone());
+            }
+            Instr::Op(op) if op == OP_ENDIF => {
+                let branch = branches.pop().ok_or("OP_ENDIF outside OP_IF")?;
+                scheduler.merge(branch)?;
+                scheduler.out.push(op.clone());
+            }
+            instr => {
+                step(&instrs, i, &mut scheduler.stacks)?;
+                out.push(instr.to_asm());
+            }
+        }
+        i += 1;
+    }
+    Ok(scheduler.out)
+}
+
+struct Scheduler<'a> {
+    instrs: &'a [Instr],
+    witness: &'a [String],
+    /// For each read and binding, whether a later read can see its value
+    live: Vec<Option<bool>>,
+    stacks: Stacks,
+    out: Vec<String>,
+}
+
+impl Scheduler<'_> {
+    /// Read the value at `i`, and with it the reads right after it that
+    /// are left as they are. Returns how many reads were done.
+    fn read(&mut self, i: usize, is_last: bool) -> Result<usize, String> {
+        let Instr::Placeholder(name) = &self.instrs[i] else {
+            unreachable!("only placeholders are reads");
+        };
+        let main = &mut self.stacks.main;
+        let Some(at) = main.iter().rposition(|s| *s == Slot::Named(name.clone())) else {
+            // A value read before it is bound is filled in as a template
+            self.out.push(self.instrs[i].to_asm());
+            main.push(Slot::Temp);
+            return Ok(1);
+        };
+        if main[at + 1..].contains(&Slot::Unknown) {
+            return Err(format!(
+                "cannot find <{}> under the values of an opcode with an unknown stack effect",
+                name
+            ));
+        }
+        let depth = main.len() - 1 - at;
+
+        if !is_last {
+            match depth {
+                0 => self.out.push(OP_DUP.to_string()),
+                1 => self.out.push(OP_OVER.to_string()),
+                _ => {
+                    self.out.push(depth.to_string());
+                    self.out.push(OP_PICK.to_string());
+                }
+            }
+            main.push(Slot::Temp);
+            return Ok(1);
+        }
+
+        // Last reads of the values on top, in the order they are stacked
+        let run = self.live[i..]
+            .iter()
+            .zip(&self.instrs[i..])
+            .take_while(|(live, instr)| {
+                **live == Some(false) && matches!(instr, Instr::Placeholder(_))
+            })
+            .count();
+        for length in (1..=run.min(main.len())).rev() {
+            let top = main.len() - length;
+            let stacked = (0..length).all(|k| match (&self.instrs[i + k], &main[top + k]) {
+                (Instr::Placeholder(read), Slot::Named(slot)) => read == slot,
+                _ => false,
+            });
+            if stacked {
+                for k in 0..length {
+                    main[top + k] = Slot::Temp;
+                    if let Instr::Placeholder(read) = &self.instrs[i + k] {
+                        if self.witness.contains(read) {
+                            self.out.push(self.instrs[i + k].to_asm());
+                        }
+                    }
+                }
+                return Ok(length);
+            }
+        }
+        main.remove(at);
+        main.push(Slot::Temp);
+        self.out.extend(roll(depth));
+        Ok(1)
+    }
+
+    /// Bind the value on top to the name marked at `i`, in place of the
+    /// value the name had, or drop it if nothing reads it.
+    fn bind(&mut self, i: usize, live: bool) -> Result<(), String> {
+        let Instr::Raw(marker) = &self.instrs[i] else {
+            unreachable!("only markers are bindings");
+        };
+        let name = &marker[1..];
+        let main = &mut self.stacks.main;
+        if main.last() != Some(&Slot::Temp) {
+            // Reads of a value the scheduler cannot follow are templates
+            return Ok(());
+        }
+        main.pop();
+        let old = Slot::Named(name.to_string());
+        if let Some(at) = main.iter().rposition(|slot| *slot == old) {
+            if main[at + 1..].contains(&Slot::Unknown) {
+                return Err(format!(
+                    "cannot drop <{}> under the values of an opcode with an unknown stack effect",
+                    name
+                ));
+            }
+            // Under the new value
+            let depth = main.len() - at;
+            main.remove(at);
+            self.out.extend(drop(depth));
+        }
+        match live {
+            true => main.push(old),
+            false => self.out.push(OP_DROP.to_string()),
+        }
+        Ok(())
+    }
+
+    /// Join the branches of `branch` at its `OP_ENDIF`: each drops the
+    /// values the other consumed, and the else branch stacks what is left
+    /// as the then branch does. A missing `OP_ELSE` is added for that.
+    fn merge(&mut self, branch: Branch) -> Result<(), String> {
+        let (mut then, at, mut other) = match branch.then {
+            Some((then, at)) => (then, Some(at), self.stacks.clone()),
+            None => (self.stacks.clone(), None, branch.start),
+        };
+        let then_drops = drop_consumed(&mut then.main, &other.main)?;
+        let mut else_drops = drop_consumed(&mut other.main, &then.main)?;
+        if let Some(moves) = restack(&mut other.main, &then.main) {
+            else_drops.extend(moves);
+        }
+        match at {
+            Some(at) => {
+                self.out.splice(at..at, then_drops);
+                self.out.extend(else_drops);
+            }
+            None => {
+                self.out.extend(then_drops);
+                if !else_drops.is_empty() {
+                    self.out.push(OP_ELSE.to_string());
+                    self.out.extend(else_drops);
+                }
+            }
+        }
+        self.stacks = Stacks {
+            main: join(then.main, &other.main),
+            alt: join(then.alt, &other.alt),
+        };
+        Ok(())
+    }
+}
+
+/// `OP_ROLL` of the value `depth` under the top, in its shortest form.
+fn roll(depth: usize) -> Vec<String> {
+    match depth {
+        0 => Vec::new(),
+        1 => vec![OP_SWAP.to_string()],
+        2 => vec![OP_ROT.to_string()],
+        _ => vec![depth.to_string(), OP_ROLL.to_string()],
+    }
+}
+
+/// Drop the value `depth` under the top, in its shortest form.
+fn drop(depth: usize) -> Vec<String> {
+    match depth {
+        0 => vec![OP_DROP.to_string()],
+        1 => vec![OP_NIP.to_string()],
+        _ => vec![depth.to_string(), OP_ROLL.to_string(), OP_DROP.to_string()],
+    }
+}
+
+/// Drop the named values of `stack` that `other` no longer has, returning
+/// the instructions that do.
+fn drop_consumed(stack: &mut Vec<Slot>, other: &[Slot]) -> Result<Vec<String>, String> {
+    let mut drops = Vec::new();
+    while let Some(at) = stack
+        .iter()
+        .rposition(|slot| matches!(slot, Slot::Named(_)) && !other.contains(slot))
+    {
+        if stack[at + 1..].contains(&Slot::Unknown) {
+            return Err(
+                "cannot drop a value under the values of an opcode with an unknown stack effect"
+                    .to_string(),
+            );
+        }
+        drops.extend(drop(stack.len() - 1 - at));
+        stack.remove(at);
+    }
+    Ok(drops)
+}
+
+/// Move the values of `stack` into the order of `target`, which holds the
+/// same values, returning the instructions that do. Computed values keep
+/// their order among themselves. `None` if they do not hold the same.
+fn restack(stack: &mut Vec<Slot>, target: &[Slot]) -> Option<Vec<String>> {
+    if stack.len() != target.len() || stack.contains(&Slot::Unknown) {
+        return None;
+    }
+    let start = stack.iter().zip(target).take_while(|(a, b)| a == b).count();
+    let mut pending: Vec<Slot> = stack[start..].to_vec();
+    let mut moves = Vec::new();
+    for (moved, slot) in target[start..].iter().enumerate() {
+        let at = pending.iter().position(|p| p == slot)?;
+        moves.extend(roll(pending.len() - 1 - at + moved));
+        pending.remove(at);
+    }
+    stack.truncate(start);
+    stack.extend_from_slice(&target[start..]);
+    Some(moves)
+}
+
+/// The stack after two branches that left `a` and `b`: where they differ,
+/// nothing is known from the first difference up.
+fn join(mut a: Vec<Slot>, b: &[Slot]) -> Vec<Slot> {
+    if a != b {
+        let common = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+        a.truncate(common);
+        a.push(Slot::Unknown);
+    }
+    a
+}
+
+/// For each read of a witness element or bound value in `instrs`, and each
+/// binding, whether a later read can see the value it reads or binds. Reads
+/// in the two branches of an `if` cannot see each other's values, and a
+/// binding hides the value before it from the reads it comes before on
+/// every path. Other instructions are `None`.
+fn live_values(instrs: &[Instr], witness: &[String]) -> Vec<Option<bool>> {
+    // The branches each instruction is in, as (`if`, else branch) pairs
+    let mut paths: Vec<Vec<(usize, bool)>> = Vec::with_capacity(instrs.len());
+    let mut open: Vec<(usize, bool)> = Vec::new();
+    let mut ifs = 0;
+    for instr in instrs {
+        match instr {
+            Instr::Op(op) if op == OP_IF || op == OP_NOTIF => {
+                paths.push(open.clone());
+                open.push((ifs, false));
+                ifs += 1;
+            }
+            Instr::Op(op) if op == OP_ELSE => {
+                if let Some(branch) = open.last_mut() {
+                    branch.1 = true;
+                }
+                paths.push(open.clone());
+            }
+            Instr::Op(op) if op == OP_ENDIF => {
+                open.pop();
+                paths.push(open.clone());
+            }
+            _ => paths.push(open.clone()),
+        }
+    }
+    let exclusive = |a: &[(usize, bool)], b: &[(usize, bool)]| {
+        a.iter().any(|(id, arm)| {
+            b.iter()
+                .any(|(other, other_arm)| id == other && arm != other_arm)
+        })
+    };
+    // Whether every path through `b` passes `a`
+    let within =
+        |a: &[(usize, bool)], b: &[(usize, bool)]| a.iter().all(|branch| b.contains(branch));
+
+    let bindings: Vec<Option<&str>> = instrs
+        .iter()
+        .map(|instr| match instr {
+            Instr::Raw(token) => token.strip_prefix('='),
+            _ => None,
+        })
+        .collect();
+    let reads: Vec<Option<&str>> = instrs
+        .iter()
+        .map(|instr| match instr {
+            Instr::Placeholder(name)
+                if witness.contains(name) || bindings.contains(&Some(name.as_str())) =>
+            {
+                Some(name.as_str())
+            }
+            _ => None,
+        })
+        .collect();
+    (0..instrs.len())
+        .map(|i| {
+            let name = reads[i].or(bindings[i])?;
+            let sees = |j: usize| {
+                reads[j] == Some(name)
+                    && !exclusive(&paths[i], &paths[j])
+                    && !(i + 1..j).any(|k| {
+                        bindings[k] == Some(name)
+                            && (within(&paths[k], &paths[i]) || within(&paths[k], &paths[j]))
+                    })
+            };
+            Some((i + 1..instrs.len()).any(sees))
+        })
+        .collect()
+}
+
+/// Pop `count` values for `op`. Values an opcode with an unknown stack
+/// effect left are popped from without knowing how many are left.
+fn pop(main: &mut Vec<Slot>, count: usize, op: &str) -> Result<(), String> {
+    for _ in 0..count {
+        match main.last() {
+            Some(Slot::Unknown) => return Ok(()),
+            Some(Slot::Named(name)) => {
+                return Err(format!("{} consumes <{}> without reading it", op, name))
+            }
+            Some(Slot::Temp) => {
+                main.pop();
+            }
+            None => return Err(format!("{} pops an empty stack", op)),
+        }
+    }
+    Ok(())
+}
+
+/// Follow the instruction at `i`, which is not a read or a branch, on
+/// `stacks`.
+fn step(instrs: &[Instr], i: usize, stacks: &mut Stacks) -> Result<(), String> {
+    let Instr::Op(op) = &instrs[i] else {
+        // Numbers, data and the other placeholders are pushes
+        stacks.main.push(Slot::Temp);
+        return Ok(());
+    };
+    let main = &mut stacks.main;
+    // Stack operations move the values they do not copy
+    let known = |main: &[Slot], n: usize| {
+        main.len() >= n && !main[main.len() - n..].contains(&Slot::Unknown)
+    };
+    let depth = i.checked_sub(1).and_then(|p| match &instrs[p] {
+        Instr::Num(n) => usize::try_from(*n).ok(),
+        Instr::Op(o) => ir::small_int(o).and_then(|n| usize::try_from(n).ok()),
+        _ => None,
+    });
+    match op.as_str() {
+        "OP_DUP" if known(main, 1) => main.push(Slot::Temp),
+        "OP_2DUP" if known(main, 2) => main.extend([Slot::Temp, Slot::Temp]),
+        "OP_OVER" if known(main, 2) => main.push(Slot::Temp),
+        "OP_SWAP" if known(main, 2) => {
+            let len = main.len();
+            main.swap(len - 1, len - 2);
+        }
+        "OP_ROT" if known(main, 3) => {
+            let third = main.remove(main.len() - 3);
+            main.push(third);
+        }
+        "OP_TUCK" if known(main, 2) => {
+            let len = main.len();
+            main.insert(len - 2, Slot::Temp);
+        }
+        "OP_NIP" if known(main, 2) => {
+            let top = main.pop().expect("checked above");
+            pop(main, 1, op)?;
+            main.push(top);
+        }
+        "OP_PICK" | "OP_ROLL" if depth.is_some_and(|d| known(main, d + 2)) => {
+            let depth = depth.expect("checked above");
+            main.pop();
+            if op == OP_PICK {
+                main.push(Slot::Temp);
+            } else {
+                let value = main.remove(main.len() - 1 - depth);
+                main.push(value);
+            }
+        }
+        "OP_TOALTSTACK" if known(main, 1) => {
+            let top = main.pop().expect("checked above");
+            stacks.alt.push(top);
+        }
+        "OP_FROMALTSTACK" => {
+            let top = stacks.alt.pop().unwrap_or(Slot::Temp);
+            main.push(top);
+        }
+        "OP_INSPECTINPUTOUTPOINT" => {
+            pop(main, 1, op)?;
+            main.extend([Slot::Temp, Slot::Temp]);
+        }
+        "OP_INSPECTASSETGROUP" => {
+            pop(main, 3, op)?;
+            main.extend([Slot::Temp, Slot::Temp, Slot::Temp]);
+        }
+        // An asset ID, as two values, or -1, which no asset ID the leaf
+        // compares it with equals
+        "OP_INSPECTASSETGROUPCTRL" => {
+            pop(main, 1, op)?;
+            main.extend([Slot::Temp, Slot::Temp]);
+        }
+        // The issuance is compared as a single value, which it is for an
+        // input without one
+        "OP_INSPECTINPUTISSUANCE" => {
+            pop(main, 1, op)?;
+            main.push(Slot::Temp);
+        }
+        _ => match ir::stack_effect(instrs, i) {
+            Some((pops, pushes)) => {
+                pop(main, pops, op)?;
+                main.extend((0..pushes).map(|_| Slot::Temp));
+            }
+            // Whatever it does, it leaves the values not consumed yet alone
+            None => {
+                let named = main.iter().rposition(|s| matches!(s, Slot::Named(_)));
+                main.truncate(named.map_or(0, |at| at + 1));
+                main.push(Slot::Unknown);
+            }
+        },
+    }
+    Ok(())
+}
@@ -0,0 +1,189 @@
+//! The `witnessTemplate` of a function variant.
+//!
+//! The template is the variant's `witnessSchema` turned into the witness
+//! stack a spender builds, bottom first, each item saying what it is: a
+//! signature, the operator's co-signature, the preimage of a hash lock, an
+//! index into the transaction's inputs, outputs or asset groups or into an
+//! array, or any other value. SDKs can fill it in order instead of reading
+//! the `<name>` placeholders of the ASM.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use super::substitute_loop_body;
+use crate::models::{
+    Contract, Expression, Function, Requirement, Statement, WitnessElement, WitnessItem,
+    WitnessRole, DEFAULT_ARRAY_LENGTH,
+};
+use crate::typechecker::ArkType;
+
+/// Build the template of a variant whose witness elements are `schema`.
+///
+/// On a cooperative path of a contract with a server key the element named
+/// `serverSig` is the server's signature, wherever the schema puts it.
+pub(super) fn witness_template(
+    function: &Function,
+    contract: &Contract,
+    server_variant: bool,
+    schema: &[WitnessElement],
+) -> Vec<WitnessItem> {
+    let mut uses = Uses::default();
+    uses.statements(&function.statements, &contract.array_lengths(function));
+    let server_sig = server_variant && contract.has_server_key;
+
+    schema
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(index, element)| {
+            let role = if server_sig && element.name == "serverSig" {
+                WitnessRole::ServerSignature
+            } else if ArkType::parse(&element.elem_type).is_signature() {
+                WitnessRole::Signature
+            } else if uses.preimages.contains(&element.name) {
+                WitnessRole::Preimage
+            } else if uses.indices.contains(&element.name) {
+                WitnessRole::Index
+            } else {
+                WitnessRole::Value
+            };
+            WitnessItem {
+                index,
+                name: element.name.clone(),
+                role,
+                item_type: element.elem_type.clone(),
+                encoding: element.encoding.clone(),
+                sighash: element.sighash,
+            }
+        })
+        .collect()
+}
+
+/// Names a function uses as hash-lock preimages and as indices.
+#[derive(Default)]
+struct Uses {
+    preimages: BTreeSet<String>,
+    indices: BTreeSet<String>,
+}
+
+impl Uses {
+    fn statements(&mut self, statements: &[Statement], arrays: &BTreeMap<String, usize>) {
+        for stmt in statements {
+            match stmt {
+                Statement::Require(Requirement::HashEqual { preimage, .. }, _) => {
+                    self.preimages.insert(preimage.clone());
+                }
+                Statement::Require(Requirement::Comparison { left, right, .. }, _) => {
+                    self.expression(left, false);
+                    self.expression(right, false);
+                }
+                Statement::Require(..) => {}
+                Statement::LetBinding { value, .. } | Statement::VarAssign { value, .. } => {
+                    self.expression(value, false)
+                }
+                Statement::IfElse {
+                    condition,
+                    then_body,
+                    else_body,
+                    ..
+                } => {
+                    self.expression(condition, false);
+                    self.statements(then_body, arrays);
+                    if let Some(else_body) = else_body {
+                        self.statements(else_body, arrays);
+                    }
+                }
+                Statement::ForIn {
+                    index_var,
+                    value_var,
+                    iterable,
+                    body,
+                } => {
+                    // Loops over arrays refer to the flattened elements
+                    if let Expression::Variable(array) = iterable {
+                        let length = arrays.get(array).copied();
+                        for k in 0..length.unwrap_or(DEFAULT_ARRAY_LENGTH) {
+                            let body =
+                                substitute_loop_body(body, index_var, value_var, k, Some(array));
+                            self.statements(&body, arrays);
+                        }
+                    }
+                    self.expression(iterable, false);
+                    self.statements(body, arrays);
+                }
+            }
+        }
+    }
+
+    /// Walk `expr`, which is an index when `index` is set.
+    fn expression(&mut self, expr: &Expression, index: bool) {
+        match expr {
+            Expression::Variable(name) if index => {
+                self.indices.insert(name.to_string());
+            }
+            Expression::AssetLookup { index, .. }
+            | Expression::AssetCount { index, .. }
+            | Expression::InputIntrospection { index, .. }
+            | Expression::OutputIntrospection { index, .. }
+            | Expression::GroupSum { index, .. }
+            | Expression::GroupNumIO { index, .. } => self.expression(index, true),
+            Expression::AssetAt {
+                io_index: first,
+                asset_index: second,
+                ..
+            }
+            | Expression::GroupIOAccess {
+                group_index: first,
+                io_index: second,
+                ..
+            } => {
+                self.expression(first, true);
+                self.expression(second, true);
+            }
+            Expression::ArrayIndex { array, index } => {
+                self.expression(array, false);
+                self.expression(index, true);
+            }
+            // Arithmetic on an index is still an index: tx.outputs[i + 1]
+            Expression::BinaryOp { left, right, .. } => {
+                self.expression(left, index);
+                self.expression(right, index);
+            }
+            Expression::Sha256Update {
+                context: first,
+                chunk: second,
+            }
+            | Expression::Sha256Finalize {
+                context: first,
+                last_chunk: second,
+            } => {
+                self.expression(first, false);
+                self.expression(second, false);
+            }
+            Expression::Sha256Initialize { data: value }
+            | Expression::Neg64 { value }
+            | Expression::Not { value }
+            | Expression::Le64ToScriptNum { value }
+            | Expression::Le32ToLe64 { value } => self.expression(value, false),
+            Expression::EcMulScalarVerify {
+                scalar: first,
+                point_p: second,
+                point_q: third,
+            }
+            | Expression::TweakVerify {
+                point_p: first,
+                tweak: second,
+                point_q: third,
+            } => {
+                self.expression(first, false);
+                self.expression(second, false);
+                self.expression(third, false);
+            }
+            Expression::ContractInstance { args, .. } => {
+                args.iter().for_each(|arg| self.expression(arg, false))
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,255 @@
+//! Built-in network constants.
+//!
+//! `BLOCKS_PER_DAY`, `DUST_LIMIT` and the other names in [`CONSTANTS`] can be
+//! used wherever a number can, in expressions and as `exit` and `renew`
+//! option values. They, and options given as a duration such as `48h`, are
+//! resolved at compile time for the target network
+//! ([`CompileOptions::network`](crate::compiler::CompileOptions::network)),
+//! so the same source gets the right block counts on a network with faster
+//! blocks. A contract can declare its own with `const DUST = 546;`; they
+//! are substituted the same way. A contract or function parameter with the
+//! same name shadows the constant.
+
+use alloc::collections::BTreeMap;
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::models::{Contract, Duration, Expression, Requirement, Statement};
+use crate::taproot::Network;
+
+/// Names of the built-in constants.
+pub const CONSTANTS: [&str; 5] = [
+    "BLOCKS_PER_HOUR",
+    "BLOCKS_PER_DAY",
+    "BLOCKS_PER_WEEK",
+    "BLOCKS_PER_MONTH",
+    "DUST_LIMIT",
+];
+
+/// Value of the constant `name` on `network`.
+pub fn value(name: &str, network: Network) -> Option<u64> {
+    let blocks_per_hour = match network {
+        Network::Mutinynet => 120,
+        _ => 6,
+    };
+    let value = match name {
+        "BLOCKS_PER_HOUR" => blocks_per_hour,
+        "BLOCKS_PER_DAY" => 24 * blocks_per_hour,
+        "BLOCKS_PER_WEEK" => 7 * 24 * blocks_per_hour,
+        "BLOCKS_PER_MONTH" => 30 * 24 * blocks_per_hour,
+        // Dust threshold of a P2TR output at the default relay fee
+        "DUST_LIMIT" => 330,
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Number of blocks `duration` spans on `network`, rounded up; `None` on
+/// overflow.
+pub fn blocks(duration: Duration, network: Network) -> Option<u64> {
+    match duration {
+        Duration::Blocks(blocks) => Some(blocks),
+        Duration::Seconds(seconds) => {
+            let blocks_per_hour = value("BLOCKS_PER_HOUR", network)?;
+            Some(seconds.checked_mul(blocks_per_hour)?.div_ceil(3600))
+        }
+    }
+}
+
+/// Whether `name` is a built-in constant.
+pub fn is_constant(name: &str) -> bool {
+    CONSTANTS.contains(&name)
+}
+
+/// Replace every use of a constant in `contract`, built-in or declared by
+/// the contract, by its value on `network`, including `exit` and `renew`
+/// options given by name or as a duration such as `48h`.
+pub fn resolve(contract: &mut Contract, network: Network) -> Result<(), String> {
+    for (option, name) in core::mem::take(&mut contract.named_options) {
+        let declared = contract.constants.get(&name).copied();
+        let value = match Duration::parse(&name) {
+            Some(duration) => blocks(duration, network),
+            None => declared.or_else(|| value(&name, network)),
+        };
+        let value = value.ok_or_else(|| {
+            format!(
+                "Option '{}' must be a number, a duration or a built-in constant, got '{}'",
+                option, name
+            )
+        })?;
+        match option.as_str() {
+            "exit" => contract.exit_timelock = Some(value),
+            "renew" => contract.renewal_timelock = Some(value),
+            _ => {}
+        }
+    }
+
+    for function in &mut contract.functions {
+        let shadowed: Vec<String> = contract
+            .parameters
+            .iter()
+            .chain(&function.parameters)
+            .map(|p| p.name.clone())
+            .collect();
+        let resolver = Resolver {
+            network,
+            declared: &contract.constants,
+            shadowed: &shadowed,
+        };
+        resolver.statements(&mut function.statements);
+    }
+    Ok(())
+}
+
+struct Resolver<'a> {
+    network: Network,
+    /// Constants the contract declares
+    declared: &'a BTreeMap<String, u64>,
+    /// Parameters in scope, which shadow constants
+    shadowed: &'a [String],
+}
+
+impl Resolver<'_> {
+    fn lookup(&self, name: &str) -> Option<u64> {
+        if self.shadowed.iter().any(|s| s == name) {
+            return None;
+        }
+        self.declared
+            .get(name)
+            .copied()
+            .or_else(|| value(name, self.network))
+    }
+
+    fn statements(&self, statements: &mut [Statement]) {
+        for statement in statements {
+            match statement {
+                Statement::Require(requirement, _) => self.requirement(requirement),
+                Statement::LetBinding { value, .. } | Statement::VarAssign { value, .. } => {
+                    self.expression(value)
+                }
+                Statement::IfElse {
+                    condition,
+                    then_body,
+                    else_body,
+                    ..
+                } => {
+                    self.expression(condition);
+                    self.statements(then_body);
+                    if let Some(else_body) = else_body {
+                        self.statements(else_body);
+                    }
+                }
+                Statement::ForIn { iterable, body, .. } => {
+                    self.expression(iterable);
+                    self.statements(body);
+                }
+            }
+        }
+    }
+
+    fn requirement(&self, requirement: &mut Requirement) {
+        match requirement {
+            Requirement::After {
+                locktime,
+                timelock_var,
+            } => {
+                if let Some(value) = timelock_var.as_deref().and_then(|v| self.lookup(v)) {
+                    *locktime = value;
+                    *timelock_var = None;
+                }
+            }
+            Requirement::Older { age, timelock_var } => {
+                if let Some(value) = timelock_var.as_deref().and_then(|v| self.lookup(v)) {
+                    *age = Duration::Blocks(value);
+                    *timelock_var = None;
+                }
+            }
+            Requirement::Comparison { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            }
+            _ => {}
+        }
+    }
+
+    fn expression(&self, expression: &mut Expression) {
+        match expression {
+            Expression::Variable(name) => {
+                if let Some(value) = self.lookup(name) {
+                    *expression = Expression::Literal(value.to_string());
+                }
+            }
+            Expression::AssetLookup { index, .. }
+            | Expression::AssetCount { index, .. }
+            | Expression::InputIntrospection { index, .. }
+            | Expression::OutputIntrospection { index, .. }
+            | Expression::GroupSum { index, .. }
+            | Expression::GroupNumIO { index, .. } => self.expression(index),
+            Expression::AssetAt {
+                io_index,
+                asset_index,
+                ..
+            } => {
+                self.expression(io_index);
+                self.expression(asset_index);
+            }
+            Expression::GroupIOAccess {
+                group_index,
+                io_index,
+                ..
+            } => {
+                self.expression(group_index);
+                self.expression(io_index);
+            }
+            Expression::BinaryOp { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            }
+            Expression::ArrayIndex { array, index } => {
+                self.expression(array);
+                self.expression(index);
+            }
+            Expression::Sha256Initialize { data } => self.expression(data),
+            Expression::Sha256Update { context, chunk } => {
+                self.expression(context);
+                self.expression(chunk);
+            }
+            Expression::Sha256Finalize {
+                context,
+                last_chunk,
+            } => {
+                self.expression(context);
+                self.expression(last_chunk);
+            }
+            Expression::Neg64 { value }
+            | Expression::Not { value }
+            | Expression::Le64ToScriptNum { value }
+            | Expression::Le32ToLe64 { value } => self.expression(value),
+            Expression::EcMulScalarVerify {
+                scalar,
+                point_p,
+                point_q,
+            } => {
+                self.expression(scalar);
+                self.expression(point_p);
+                self.expression(point_q);
+            }
+            Expression::TweakVerify {
+                point_p,
+                tweak,
+                point_q,
+            } => {
+                self.expression(point_p);
+                self.expression(tweak);
+                self.expression(point_q);
+            }
+            Expression::ContractInstance { args, .. } => {
+                args.iter_mut().for_each(|a| self.expression(a))
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! Script disassembly, and matching leaves back to their artifact.
+//!
+//! [`disassemble`] reads a serialized leaf script into the same
+//! [`Operation`]s an artifact's `operations` list: opcodes by name and data
+//! pushes as `0x` hex. [`parse_asm`] does the same for ASM text. Neither
+//! knows where the values came from; [`identify`] finds the function
+//! variant of an artifact whose leaf a script is, and which push holds
+//! each constructor argument and the server key. Audit tooling can tell
+//! which contract an output commits to without the arguments it was
+//! linked with.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use serde::Serialize;
+
+use crate::compiler;
+use crate::encoding::encode_num;
+use crate::models::{ContractJson, Operation, OperationData};
+use crate::opcodes::opcode_name;
+
+const OP_PUSHDATA1: u8 = 0x4c;
+const OP_PUSHDATA2: u8 = 0x4d;
+const OP_PUSHDATA4: u8 = 0x4e;
+const OP_1NEGATE: u8 = 0x4f;
+
+/// A leaf recognized as one function variant of an artifact.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeafMatch {
+    pub function: String,
+    pub server_variant: bool,
+    /// The value of each constructor input and runtime value the leaf
+    /// pushes, in script order, each once
+    pub bindings: Vec<Binding>,
+}
+
+/// A value the artifact leaves as a `<name>` placeholder, read from the
+/// script.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Binding {
+    pub name: String,
+    /// What the artifact says the placeholder is: a
+    /// [`ConstructorParam`](OperationData::ConstructorParam) or a
+    /// [`Runtime`](OperationData::Runtime) value
+    pub data: OperationData,
+    /// Index of the push among the disassembled operations
+    pub position: usize,
+    /// The pushed bytes, hex; a small integer pushed as an opcode is its
+    /// script number encoding
+    pub hex: String,
+}
+
+/// One instruction of a serialized script.
+struct Instruction<'a> {
+    /// The instruction's bytes, opcode and data
+    raw: &'a [u8],
+    /// The value it pushes, for data pushes and small integers
+    value: Option<Vec<u8>>,
+}
+
+/// Disassemble a serialized leaf script.
+///
+/// Fails on a push that runs past the end of the script. Bytes that are
+/// no known opcode are kept as `OP_UNKNOWN_0x..` opcodes.
+pub fn disassemble(script: &[u8]) -> Result<Vec<Operation>, String> {
+    Ok(decode(script)?
+        .iter()
+        .map(|instruction| describe(instruction.raw))
+        .collect())
+}
+
+/// [`disassemble`] a leaf script written as hex.
+pub fn disassemble_hex(script: &str) -> Result<Vec<Operation>, String> {
+    disassemble(&hex_script(script)?)
+}
+
+/// Describe each whitespace-separated token of `asm`, as a compiled
+/// artifact's `operations` do without its parameters and witness: every
+/// `<name>` placeholder is a [`Local`](OperationData::Local) unless it is
+/// a runtime value.
+pub fn parse_asm(asm: &str) -> Vec<Operation> {
+    let tokens: Vec<String> = asm.split_whitespace().map(ToString::to_string).collect();
+    compiler::operations(&tokens, &[], &[])
+}
+
+/// Find the function variant of `artifact` whose leaf `script` is, with
+/// the value of every placeholder it pushes.
+///
+/// Placeholders match any push of their size, the same name must push the
+/// same value each time, and witness elements, which the script leaves
+/// out, match nothing. Leaves with opcodes that have no encoding yet or
+/// with values computed in the leaf never match. The artifact's first
+/// matching variant is returned.
+pub fn identify(artifact: &ContractJson, script: &[u8]) -> Result<Option<LeafMatch>, String> {
+    let instructions = decode(script)?;
+    Ok(artifact.functions.iter().find_map(|function| {
+        let template = match function.operations.is_empty() {
+            true => compiler::operations(
+                &function.asm,
+                &artifact.parameters,
+                &function.witness_schema,
+            ),
+            false => function.operations.clone(),
+        };
+        let bindings = bind(&template, &instructions)?;
+        Some(LeafMatch {
+            function: function.name.clone(),
+            server_variant: function.server_variant,
+            bindings,
+        })
+    }))
+}
+
+/// Match `instructions` against the operations of a leaf.
+fn bind(template: &[Operation], instructions: &[Instruction]) -> Option<Vec<Binding>> {
+    let mut bindings: Vec<Binding> = Vec::new();
+    let mut next = instructions.iter().enumerate();
+    for operation in template {
+        let (name, size) = match &operation.data {
+            OperationData::FunctionInput { .. } => continue,
+            OperationData::Local { .. } => return None,
+            OperationData::Opcode | OperationData::Push { .. } => {
+                let expected = compiler::assemble(core::slice::from_ref(&operation.op), |_, _| {
+                    Err("no placeholders here".to_string())
+                })
+                .ok()?;
+                let (_, instruction) = next.next()?;
+                if instruction.raw != expected {
+                    return None;
+                }
+                continue;
+            }
+            OperationData::ConstructorParam { name, size, .. } => (name, *size),
+            OperationData::Runtime { name, size } => (name, Some(*size)),
+        };
+        let (position, instruction) = next.next()?;
+        let value = instruction.value.as_ref()?;
+        if size.is_some_and(|size| value.len() != size) {
+            return None;
+        }
+        let hex = hex::encode(value);
+        match bindings.iter().find(|b| &b.name == name) {
+            Some(bound) if bound.hex != hex => return None,
+            Some(_) => {}
+            None => bindings.push(Binding {
+                name: name.clone(),
+                data: operation.data.clone(),
+                position,
+                hex,
+            }),
+        }
+    }
+    match next.next() {
+        Some(_) => None,
+        None => Some(bindings),
+    }
+}
+
+/// Split a script into its instructions.
+fn decode(script: &[u8]) -> Result<Vec<Instruction<'_>>, String> {
+    let mut instructions = Vec::new();
+    let mut at = 0;
+    while at < script.len() {
+        let opcode = script[at];
+        let (header, len) = match opcode {
+            0x01..=0x4b => (1, Some(usize::from(opcode))),
+            OP_PUSHDATA1 => (2, length(script, at + 1, 1)),
+            OP_PUSHDATA2 => (3, length(script, at + 1, 2)),
+            OP_PUSHDATA4 => (5, length(script, at + 1, 4)),
+            _ => (1, Some(0)),
+        };
+        let end = len
+            .map(|len| at + header + len)
+            .filter(|end| *end <= script.len())
+            .ok_or_else(|| format!("Script ends inside the push at byte {}", at))?;
+        let value = match opcode {
+            0x00 => Some(vec![]),
+            0x01..=OP_PUSHDATA4 => Some(script[at + header..end].to_vec()),
+            OP_1NEGATE => Some(encode_num(-1)),
+            0x51..=0x60 => Some(encode_num(i64::from(opcode - 0x50))),
+            _ => None,
+        };
+        instructions.push(Instruction {
+            raw: &script[at..end],
+            value,
+        });
+        at = end;
+    }
+    Ok(instructions)
+}
+
+/// The little-endian push length of `width` bytes at `at`, if the script
+/// holds it.
+fn length(script: &[u8], at: usize, width: usize) -> Option<usize> {
+    let bytes = script.get(at..at + width)?;
+    Some(
+        bytes
+            .iter()
+            .rev()
+            .fold(0, |len, byte| (len << 8) | usize::from(*byte)),
+    )
+}
+
+/// The operation of one instruction's bytes.
+fn describe(raw: &[u8]) -> Operation {
+    let opcode = raw[0];
+    if (0x01..=OP_PUSHDATA4).contains(&opcode) {
+        let header = match opcode {
+            OP_PUSHDATA1 => 2,
+            OP_PUSHDATA2 => 3,
+            OP_PUSHDATA4 => 5,
+            _ => 1,
+        };
+        let data = &raw[header..];
+        return Operation {
+            op: format!("0x{}", hex::encode(data)),
+            data: OperationData::Push {
+                hex: hex::encode(data),
+                size: data.len(),
+            },
+        };
+    }
+    Operation {
+        op: match opcode_name(opcode) {
+            Some(name) => name.to_string(),
+            None => format!("OP_UNKNOWN_0x{:02x}", opcode),
+        },
+        data: OperationData::Opcode,
+    }
+}
+
+fn hex_script(script: &str) -> Result<Vec<u8>, String> {
+    let script = script.trim();
+    hex::decode(script.strip_prefix("0x").unwrap_or(script))
+        .map_err(|e| format!("Invalid script hex: {}", e))
+}
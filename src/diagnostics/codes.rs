@@ -0,0 +1,152 @@
+//! Stable codes for diagnostics, with the explanations `arkadec --explain`
+//! prints.
+//!
+//! Codes are assigned from the stage that reported a problem and its
+//! message, so errors raised anywhere in the compiler get one without
+//! threading codes through every `Result`. The hundreds digit groups them:
+//! names and types (`E00xx`), signatures and script checks (`E01xx`),
+//! syntax, targets and options (`E02xx`), code generation (`E03xx`) and
+//! static analysis (`E04xx`). A code, once published, keeps its meaning.
+
+/// A diagnostic code and its documentation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorCode {
+    /// `E` and four digits, such as `E0102`
+    pub code: &'static str,
+    pub title: &'static str,
+    /// Markdown: what the code means, an erroneous example and a fix
+    pub explanation: &'static str,
+}
+
+macro_rules! codes {
+    ($($code:literal => $title:literal,)*) => {
+        /// Every code, in numeric order.
+        pub const CODES: &[ErrorCode] = &[$(ErrorCode {
+            code: $code,
+            title: $title,
+            explanation: include_str!(concat!("explanations/", $code, ".md")),
+        },)*];
+    };
+}
+
+codes! {
+    "E0001" => "Unknown identifier",
+    "E0002" => "Duplicate declaration",
+    "E0003" => "Invalid array length",
+    "E0004" => "Invalid macro or function call",
+    "E0005" => "Type mismatch",
+    "E0006" => "Swapped signature arguments",
+    "E0007" => "Mixed integer encodings",
+    "E0008" => "Unknown property",
+    "E0101" => "Invalid multisig keys",
+    "E0102" => "Multisig threshold out of range",
+    "E0103" => "Impossible size bounds",
+    "E0104" => "Invalid server key",
+    "E0105" => "Timelock out of range",
+    "E0106" => "Invalid sighash flags",
+    "E0201" => "Syntax error",
+    "E0202" => "Source limit exceeded",
+    "E0203" => "Unavailable on target",
+    "E0204" => "Invalid option value",
+    "E0205" => "Unknown option",
+    "E0206" => "Unsupported compiler version",
+    "E0207" => "Removed syntax",
+    "E0300" => "Code generation failed",
+    "E0301" => "Resource budget exceeded",
+    "E0302" => "Warnings denied",
+    "E0303" => "Script limit exceeded",
+    "E0401" => "Unsatisfiable path",
+    "E0402" => "Redundant requirement",
+    "E0403" => "Malleable witness",
+    "E0404" => "Timelock conflict",
+    "E0405" => "Deprecated syntax",
+    "E0406" => "Dead code",
+}
+
+/// The documentation of `code`, case-insensitively.
+pub fn explain(code: &str) -> Option<&'static ErrorCode> {
+    CODES
+        .iter()
+        .find(|entry| entry.code.eq_ignore_ascii_case(code))
+}
+
+/// The code of a problem `stage` reported with `message`. `stage` is
+/// `parse`, `type`, `compile` or an analysis code, as in
+/// [`Diagnostic::code`](super::Diagnostic::code).
+pub fn classify(stage: &str, message: &str) -> &'static str {
+    let has = |pattern: &str| message.contains(pattern);
+    match stage {
+        "unsat" => return "E0401",
+        "redundant" => return "E0402",
+        "malleable" => return "E0403",
+        "timelock" => return "E0404",
+        "deprecated" => return "E0405",
+        "dead" => return "E0406",
+        _ => {}
+    }
+    // Denied warnings quote the warnings, which match other codes
+    if message.starts_with("Warnings denied") {
+        return "E0302";
+    }
+    if has("undeclared variable")
+        || has("undeclared event")
+        || has("which is not one of its parameters")
+        || has("which is not a parameter of")
+    {
+        "E0001"
+    } else if has("twice") || has("already a built-in") {
+        "E0002"
+    } else if has("has length 0")
+        || has("which is not a generic parameter")
+        || has("generic parameter '")
+        || has("Generic parameter '")
+    {
+        "E0003"
+    } else if has("expands itself")
+        || has("calls itself")
+        || has("argument(s), got")
+        || has("cannot be called")
+    {
+        "E0004"
+    } else if has("appear swapped") {
+        "E0006"
+    } else if has("mixes uint64le") {
+        "E0007"
+    } else if has("unknown property") {
+        "E0008"
+    } else if has("is not an array parameter") || has("Number of pubkeys") {
+        "E0101"
+    } else if has("m-of-n multisig") {
+        "E0102"
+    } else if has("Size bounds of") || has("size(): ") {
+        "E0103"
+    } else if has("Option 'server'") {
+        "E0104"
+    } else if has("Relative timelock of") || has("Absolute timelock ") {
+        "E0105"
+    } else if has("Sighash flags") {
+        "E0106"
+    } else if has("more than the standard limit of") {
+        "E0303"
+    } else if has("more than the limit of") || has("levels deep") {
+        "E0202"
+    } else if has("not available on this target") {
+        "E0203"
+    } else if has("Unknown option") {
+        "E0205"
+    } else if has("Contract requires compiler") || has("compiler version requirement") {
+        "E0206"
+    } else if has("was removed in edition") {
+        "E0207"
+    } else if has("Option '") || has("option value") {
+        "E0204"
+    } else if has("exceed the budget of") {
+        "E0301"
+    } else if stage == "type" || has("for event '") || has("has type '") || has("compares type '") {
+        "E0005"
+    } else if stage == "parse" || message.starts_with("Parse error") {
+        "E0201"
+    } else {
+        "E0300"
+    }
+}
@@ -0,0 +1,237 @@
+//! Structured diagnostics for editors.
+//!
+//! [`check`] runs the same front end as the compiler — parser, type checker,
+//! static analyses and code generation — but collects what each stage
+//! reports instead of stopping at the first error, so a warning is still
+//! shown while the contract is otherwise fine and the location of a syntax
+//! error can be underlined. [`analyze`] bundles those diagnostics with the
+//! formatted source, the artifact and leaf sizes, for editors that want
+//! everything from a single call.
+//!
+//! Every diagnostic carries a stable code such as `E0102`, explained by
+//! [`explain`] and `arkadec --explain`. Only [`analyze`], which formats the
+//! source, requires `std`.
+
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "std")]
+use crate::formatter;
+use crate::models::{ContractJson, Span};
+#[cfg(feature = "std")]
+use crate::models::{ResourceCounts, WitnessSize};
+use crate::parser::{ParseError, Rule};
+use crate::taproot::Network;
+use crate::{analysis, compiler, constants, parser, typechecker};
+
+mod codes;
+
+pub use codes::{classify, explain, ErrorCode, CODES};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The contract does not compile
+    Error,
+    /// The contract compiles, but something looks wrong
+    Warning,
+}
+
+/// One problem found in a source file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Stage or analysis that reported it: `parse`, `type`, `compile`, or
+    /// an analysis code such as `unsat`
+    pub code: String,
+    /// Stable code such as `E0102`, documented by [`explain`]
+    pub id: String,
+    pub message: String,
+    /// 1-based start line, when the problem has a location
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// 1-based start column
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    #[serde(rename = "endLine", skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    #[serde(rename = "endColumn", skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<usize>,
+}
+
+impl Diagnostic {
+    fn new(severity: Severity, code: &str, message: impl Into<String>) -> Self {
+        let message = message.into();
+        Diagnostic {
+            severity,
+            code: code.to_string(),
+            id: classify(code, &message).to_string(),
+            message,
+            line: None,
+            column: None,
+            end_line: None,
+            end_column: None,
+        }
+    }
+
+    fn at(mut self, start: (usize, usize), end: (usize, usize)) -> Self {
+        self.line = Some(start.0);
+        self.column = Some(start.1);
+        self.end_line = Some(end.0);
+        self.end_column = Some(end.1);
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl core::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}[{}]", severity, self.id)?;
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, " at {}:{}", line, column)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Check `source` and return every error and warning, in source-stage
+/// order. An empty list means the contract compiles cleanly.
+pub fn check(source: &str) -> Vec<Diagnostic> {
+    check_and_compile(source).0
+}
+
+/// Everything an editor shows for one version of a source file.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Analysis {
+    /// The source as [`formatter::format`] lays it out, if it parses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formatted: Option<String>,
+    pub diagnostics: Vec<Diagnostic>,
+    /// The compiled contract, if it compiles
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<ContractJson>,
+    /// Size and resource use of every leaf of the artifact
+    pub leaves: Vec<LeafSize>,
+}
+
+/// Size and resource use of one leaf.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeafSize {
+    pub function: String,
+    #[serde(rename = "serverVariant")]
+    pub server_variant: bool,
+    #[serde(rename = "witnessSize")]
+    pub witness_size: WitnessSize,
+    /// Virtual size the witness adds to a spending transaction
+    pub vbytes: usize,
+    #[serde(flatten)]
+    pub resources: ResourceCounts,
+}
+
+/// Format, check and compile `source` in one pass.
+#[cfg(feature = "std")]
+pub fn analyze(source: &str) -> Analysis {
+    let (diagnostics, artifact) = check_and_compile(source);
+    let formatted = match diagnostics.first() {
+        Some(d) if d.code == "parse" => None,
+        _ => formatter::format(source).ok(),
+    };
+    let leaves = artifact
+        .iter()
+        .flat_map(|artifact| {
+            artifact.functions.iter().map(|f| LeafSize {
+                function: f.name.clone(),
+                server_variant: f.server_variant,
+                witness_size: f.witness_size,
+                vbytes: f.witness_size.vbytes(),
+                resources: artifact
+                    .metrics
+                    .iter()
+                    .find(|m| m.function == f.name && m.server_variant == f.server_variant)
+                    .map(|m| m.resources)
+                    .unwrap_or_default(),
+            })
+        })
+        .collect();
+    Analysis {
+        formatted,
+        diagnostics,
+        artifact,
+        leaves,
+    }
+}
+
+fn check_and_compile(source: &str) -> (Vec<Diagnostic>, Option<ContractJson>) {
+    let mut contract = match parser::parse(source) {
+        Ok(contract) => contract,
+        Err(e) => return (vec![parse_error(source, e.as_ref())], None),
+    };
+    // An unknown constant is reported when compiling below
+    let _ = constants::resolve(&mut contract, Network::default());
+
+    // Fatal type errors are reported when compiling below
+    let mut diagnostics: Vec<Diagnostic> = typechecker::check_contract(&contract)
+        .into_iter()
+        .filter(|e| !e.fatal)
+        .map(|e| Diagnostic::new(Severity::Warning, "type", e.message))
+        .collect();
+
+    for finding in analysis::check_contract(&contract) {
+        let diagnostic = Diagnostic::new(Severity::Warning, finding.code, finding.message);
+        diagnostics.push(match finding.spans.first() {
+            Some(span) => diagnostic.at((span.line, span.column), end_of(source, span)),
+            None => diagnostic,
+        });
+    }
+
+    match compiler::compile(source) {
+        Ok(artifact) => (diagnostics, Some(artifact)),
+        Err(e) => {
+            let diagnostic = Diagnostic::new(Severity::Error, "compile", e.message());
+            diagnostics.push(match e.span() {
+                Some(span) => diagnostic.at((span.line, span.column), end_of(source, &span)),
+                None => diagnostic,
+            });
+            (diagnostics, None)
+        }
+    }
+}
+
+fn parse_error(source: &str, error: &(dyn core::error::Error + 'static)) -> Diagnostic {
+    if let Some(error) = error.downcast_ref::<ParseError>() {
+        let diagnostic = Diagnostic::new(Severity::Error, "parse", error.message.clone());
+        return match &error.span {
+            Some(span) => diagnostic.at((span.line, span.column), end_of(source, span)),
+            None => diagnostic,
+        };
+    }
+    let Some(error) = error.downcast_ref::<pest::error::Error<Rule>>() else {
+        return Diagnostic::new(Severity::Error, "parse", error.to_string());
+    };
+    let (start, end) = match error.line_col {
+        pest::error::LineColLocation::Pos(pos) => (pos, (pos.0, pos.1 + 1)),
+        pest::error::LineColLocation::Span(start, end) => (start, end),
+    };
+    Diagnostic::new(Severity::Error, "parse", error.variant.message()).at(start, end)
+}
+
+/// 1-based line and column of the end of `span`.
+fn end_of(source: &str, span: &Span) -> (usize, usize) {
+    let before = source.get(..span.end).unwrap_or(source);
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, column)
+}
@@ -0,0 +1,347 @@
+//! Script pushes for runtime values.
+//!
+//! Codegen leaves constructor arguments and witness elements as `<name>`
+//! placeholders and relies on each being pushed in a particular way:
+//! integers as minimal script numbers, keys x-only, asset ids as a txid and
+//! a group index. [`Value::parse`] checks a value a user supplies as text
+//! against the type of its parameter; [`Value::push`] then appends it to a
+//! leaf script and [`Value::stack`] gives the elements it becomes in a
+//! witness. The linker, and anything that builds witnesses or transactions,
+//! go through these so that they agree with codegen.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use crate::typechecker::ArkType;
+
+/// A runtime value, checked against its type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    /// An `int`, or a `bool` as 0 or 1
+    Num(i64),
+    Bytes(Vec<u8>),
+    /// A compressed key, pushed x-only
+    Pubkey(Vec<u8>),
+    /// An asset id, pushed as its txid and then its group index, the
+    /// `<name_txid>` and `<name_gidx>` of an asset lookup
+    AssetId {
+        txid: [u8; 32],
+        gidx: u16,
+    },
+}
+
+impl Value {
+    /// Check `value` against `param_type`.
+    ///
+    /// Integers are decimal, booleans `true` or `false` (or `1` and `0`)
+    /// and byte types hex, with or without `0x`. An `asset` is its txid in
+    /// hex and its group index, as `txid:gidx`. A signature may be empty.
+    pub fn parse(value: &str, param_type: &str) -> Result<Value, String> {
+        let value = value.trim();
+        let ark_type = ArkType::parse(param_type);
+        match ark_type {
+            ArkType::Int => value
+                .parse::<i64>()
+                .map(Value::Num)
+                .map_err(|_| format!("expected an integer, got '{}'", value)),
+            ArkType::Bool => match value {
+                "true" | "1" => Ok(Value::Num(1)),
+                "false" | "0" => Ok(Value::Num(0)),
+                _ => Err(format!("expected true or false, got '{}'", value)),
+            },
+            ArkType::Asset if value.contains(':') => Value::asset_id(value),
+            // An empty signature is the BIP 342 "no signature": a check
+            // given one fails without failing the script, as a key that does
+            // not sign in a threshold
+            _ if ark_type.is_signature() && hex_bytes(value)?.is_empty() => {
+                Ok(Value::Bytes(Vec::new()))
+            }
+            ArkType::SchnorrSig => {
+                let bytes = hex_bytes(value)?;
+                split_sighash(&bytes)?;
+                Ok(Value::Bytes(bytes))
+            }
+            _ => {
+                let bytes = hex_bytes(value)?;
+                let expected = match ark_type {
+                    ArkType::Pubkey => Some(33),
+                    ArkType::Signature | ArkType::DataSig => Some(64),
+                    ArkType::Bytes20 => Some(20),
+                    ArkType::Bytes32 | ArkType::Asset => Some(32),
+                    ArkType::Uint64Le => Some(8),
+                    ArkType::Uint32Le => Some(4),
+                    _ => None,
+                };
+                match expected {
+                    Some(len) if bytes.len() != len => Err(format!(
+                        "expected {} bytes for {}, got {}",
+                        len,
+                        param_type,
+                        bytes.len()
+                    )),
+                    _ if ark_type == ArkType::Pubkey && !matches!(bytes[0], 2 | 3) => {
+                        Err("expected a compressed public key".to_string())
+                    }
+                    _ if ark_type == ArkType::Pubkey => Ok(Value::Pubkey(bytes)),
+                    _ => Ok(Value::Bytes(bytes)),
+                }
+            }
+        }
+    }
+
+    /// Check a value built in code against `param_type`, as [`parse`]
+    /// checks one written out, and return it as `parse` would.
+    ///
+    /// [`parse`]: Value::parse
+    pub fn check(self, param_type: &str) -> Result<Value, String> {
+        match (self, ArkType::parse(param_type)) {
+            (Value::Num(n), ArkType::Int) => Ok(Value::Num(n)),
+            (Value::Num(n), ArkType::Bool) => match n {
+                0 | 1 => Ok(Value::Num(n)),
+                _ => Err(format!("expected true or false, got {}", n)),
+            },
+            (Value::AssetId { txid, gidx }, ArkType::Asset) => Ok(Value::AssetId { txid, gidx }),
+            (Value::Bytes(bytes) | Value::Pubkey(bytes), ark_type)
+                if !matches!(ark_type, ArkType::Int | ArkType::Bool) =>
+            {
+                Value::parse(&hex::encode(bytes), param_type)
+            }
+            (value, _) => Err(format!(
+                "expected a value of type {}, got {}",
+                param_type,
+                value.kind()
+            )),
+        }
+    }
+
+    /// What kind of value this is, for errors.
+    fn kind(&self) -> &'static str {
+        match self {
+            Value::Num(_) => "an integer",
+            Value::Bytes(_) => "bytes",
+            Value::Pubkey(_) => "a public key",
+            Value::AssetId { .. } => "an asset id",
+        }
+    }
+
+    /// Parse an asset id written `txid:gidx`.
+    pub fn asset_id(value: &str) -> Result<Value, String> {
+        let (txid, gidx) = value
+            .trim()
+            .split_once(':')
+            .ok_or_else(|| format!("expected an asset id as txid:gidx, got '{}'", value))?;
+        let txid = hex_bytes(txid)?
+            .try_into()
+            .map_err(|_| format!("expected a 32-byte asset txid, got '{}'", txid))?;
+        let gidx = gidx
+            .parse()
+            .map_err(|_| format!("expected an asset group index, got '{}'", gidx))?;
+        Ok(Value::AssetId { txid, gidx })
+    }
+
+    /// The txid and group index of an asset id, as the values of its
+    /// `<name_txid>` and `<name_gidx>` placeholders.
+    pub fn decompose(&self) -> Option<(Value, Value)> {
+        match self {
+            Value::AssetId { txid, gidx } => {
+                Some((Value::Bytes(txid.to_vec()), Value::Num(i64::from(*gidx))))
+            }
+            _ => None,
+        }
+    }
+
+    /// The value as ASM, one token per push.
+    pub fn tokens(&self) -> Vec<String> {
+        match self {
+            Value::Num(n) => vec![n.to_string()],
+            Value::Bytes(bytes) | Value::Pubkey(bytes) => vec![hex::encode(bytes)],
+            Value::AssetId { txid, gidx } => vec![hex::encode(txid), gidx.to_string()],
+        }
+    }
+
+    /// Append the value's pushes to a leaf script.
+    pub fn push(&self, script: &mut Vec<u8>) {
+        match self {
+            Value::Num(n) => push_num(script, *n),
+            Value::Bytes(bytes) => push_data(script, bytes),
+            Value::Pubkey(key) => push_data(script, &key[1..]),
+            Value::AssetId { txid, gidx } => {
+                push_data(script, txid);
+                push_num(script, i64::from(*gidx));
+            }
+        }
+    }
+
+    /// The witness stack elements the value becomes, bottom first.
+    pub fn stack(&self) -> Vec<Vec<u8>> {
+        match self {
+            Value::Num(n) => vec![encode_num(*n)],
+            Value::Bytes(bytes) => vec![bytes.clone()],
+            Value::Pubkey(key) => vec![key[1..].to_vec()],
+            Value::AssetId { txid, gidx } => vec![txid.to_vec(), encode_num(i64::from(*gidx))],
+        }
+    }
+}
+
+/// Sighash flags a 65-byte BIP-342 signature may end with. A 64-byte
+/// signature commits with `SIGHASH_DEFAULT`, which is never written out.
+pub const SIGHASH_FLAGS: [u8; 6] = [0x01, 0x02, 0x03, 0x81, 0x82, 0x83];
+
+/// Split a `schnorrsig` into its 64-byte BIP-340 signature and its sighash
+/// flag, `None` for `SIGHASH_DEFAULT`.
+pub fn split_sighash(signature: &[u8]) -> Result<(&[u8], Option<u8>), String> {
+    match signature.len() {
+        64 => Ok((signature, None)),
+        65 if SIGHASH_FLAGS.contains(&signature[64]) => Ok((&signature[..64], Some(signature[64]))),
+        65 => Err(format!("invalid sighash flag 0x{:02x}", signature[64])),
+        len => Err(format!(
+            "expected 64 bytes for schnorrsig, or 65 with a sighash flag, got {}",
+            len
+        )),
+    }
+}
+
+/// Base sighash modes, by name.
+const SIGHASH_MODES: [(&str, u8); 3] = [
+    ("SIGHASH_ALL", 0x01),
+    ("SIGHASH_NONE", 0x02),
+    ("SIGHASH_SINGLE", 0x03),
+];
+
+/// Flag that commits to the signed input only.
+pub const SIGHASH_ANYONECANPAY: u8 = 0x80;
+
+/// The sighash flag written as `names` joined by `|`, as in
+/// `checkSig(sig, key, SIGHASH_SINGLE | ANYONECANPAY)`.
+pub fn sighash_flag(names: &[&str]) -> Result<u8, String> {
+    let written = names.join(" | ");
+    if names == ["SIGHASH_DEFAULT"] {
+        return Ok(0x00);
+    }
+    let mut mode = None;
+    let mut anyone_can_pay = false;
+    for &name in names {
+        match name {
+            "SIGHASH_DEFAULT" => {
+                return Err(format!(
+                    "Sighash flags '{}': SIGHASH_DEFAULT cannot be combined with other flags",
+                    written
+                ))
+            }
+            "ANYONECANPAY" | "SIGHASH_ANYONECANPAY" if !anyone_can_pay => anyone_can_pay = true,
+            "ANYONECANPAY" | "SIGHASH_ANYONECANPAY" => {
+                return Err(format!(
+                    "Sighash flags '{}': ANYONECANPAY is repeated",
+                    written
+                ))
+            }
+            _ => {
+                let (_, value) = SIGHASH_MODES
+                    .iter()
+                    .find(|(mode, _)| *mode == name)
+                    .ok_or_else(|| format!("Unknown sighash flag '{}'", name))?;
+                if mode.replace(*value).is_some() {
+                    return Err(format!(
+                        "Sighash flags '{}': combine at most one of SIGHASH_ALL, SIGHASH_NONE and SIGHASH_SINGLE",
+                        written
+                    ));
+                }
+            }
+        }
+    }
+    match mode {
+        Some(mode) if anyone_can_pay => Ok(mode | SIGHASH_ANYONECANPAY),
+        Some(mode) => Ok(mode),
+        None => Err(format!(
+            "Sighash flags '{}': ANYONECANPAY needs SIGHASH_ALL, SIGHASH_NONE or SIGHASH_SINGLE",
+            written
+        )),
+    }
+}
+
+/// The flag names [`sighash_flag`] reads back as `flag`.
+pub fn sighash_names(flag: u8) -> String {
+    let mode = SIGHASH_MODES
+        .iter()
+        .find(|(_, value)| *value == flag & !SIGHASH_ANYONECANPAY)
+        .map(|(name, _)| *name);
+    match mode {
+        Some(mode) if flag & SIGHASH_ANYONECANPAY != 0 => format!("{} | ANYONECANPAY", mode),
+        Some(mode) => mode.to_string(),
+        None => "SIGHASH_DEFAULT".to_string(),
+    }
+}
+
+/// The bytes a value of `param_type` takes on the stack, when the type
+/// fixes them. Keys are pushed x-only.
+pub fn value_size(param_type: &str) -> Option<usize> {
+    match ArkType::parse(param_type) {
+        ArkType::Pubkey | ArkType::Bytes32 | ArkType::Asset => Some(32),
+        ArkType::Signature | ArkType::DataSig => Some(64),
+        ArkType::Bytes20 => Some(20),
+        ArkType::Uint64Le => Some(8),
+        ArkType::Uint32Le => Some(4),
+        _ => None,
+    }
+}
+
+fn hex_bytes(value: &str) -> Result<Vec<u8>, String> {
+    let hex = value.strip_prefix("0x").unwrap_or(value);
+    hex::decode(hex).map_err(|_| format!("expected hex bytes, got '{}'", value))
+}
+
+/// Encode a number as a minimal CScriptNum.
+pub fn encode_num(n: i64) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut magnitude = n.unsigned_abs();
+    while magnitude > 0 {
+        bytes.push((magnitude & 0xff) as u8);
+        magnitude >>= 8;
+    }
+    // The top bit of the last byte is the sign
+    if bytes.last().is_some_and(|b| b & 0x80 != 0) {
+        bytes.push(if n < 0 { 0x80 } else { 0x00 });
+    } else if n < 0 {
+        if let Some(last) = bytes.last_mut() {
+            *last |= 0x80;
+        }
+    }
+    bytes
+}
+
+/// The ASM push of `n` as an 8-byte little-endian number, the operand
+/// encoding of the 64-bit opcodes.
+pub fn le64_push(n: i64) -> String {
+    format!("0x{}", hex::encode(n.to_le_bytes()))
+}
+
+/// Append the minimal push of a script number.
+pub fn push_num(script: &mut Vec<u8>, n: i64) {
+    match n {
+        0 => script.push(0x00),
+        -1 => script.push(0x4f),
+        1..=16 => script.push(0x50 + n as u8),
+        _ => push_data(script, &encode_num(n)),
+    }
+}
+
+/// Append a data push, with the smallest push opcode that fits.
+pub fn push_data(script: &mut Vec<u8>, data: &[u8]) {
+    match data.len() {
+        len @ 0..=75 => script.push(len as u8),
+        len @ 76..=0xff => script.extend([0x4c, len as u8]),
+        len @ 0x100..=0xffff => {
+            script.push(0x4d);
+            script.extend((len as u16).to_le_bytes());
+        }
+        len => {
+            script.push(0x4e);
+            script.extend((len as u32).to_le_bytes());
+        }
+    }
+    script.extend(data);
+}
@@ -0,0 +1,104 @@
+//! Example contracts.
+//!
+//! The contracts in the repository's `examples/` directory, embedded so that
+//! `arkadec examples` and the WASM package can show working contracts
+//! without a checkout. Each is listed under its file name, without `.ark`.
+
+use serde::Serialize;
+
+/// An embedded example contract.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Example {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub source: &'static str,
+}
+
+/// The embedded examples, by name.
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        name: "arkade_kitties",
+        description: "Commit-reveal breeding with oracle randomness",
+        source: include_str!("../../examples/arkade_kitties.ark"),
+    },
+    Example {
+        name: "beacon",
+        description: "Read-only recursive covenant that keeps every asset group intact",
+        source: include_str!("../../examples/beacon.ark"),
+    },
+    Example {
+        name: "controlled_mint",
+        description: "Asset minting gated by a control asset, with group introspection",
+        source: include_str!("../../examples/controlled_mint.ark"),
+    },
+    Example {
+        name: "fee_adapter",
+        description: "Arithmetic over input and output values to pay a fee",
+        source: include_str!("../../examples/fee_adapter.ark"),
+    },
+    Example {
+        name: "fuji_safe",
+        description: "Collateralized borrowing with oracle-priced liquidation",
+        source: include_str!("../../examples/fuji_safe.ark"),
+    },
+    Example {
+        name: "htlc",
+        description: "Hash time-locked contract",
+        source: include_str!("../../examples/htlc.ark"),
+    },
+    Example {
+        name: "nft_mint",
+        description: "Unique asset creation with isFresh and assetId",
+        source: include_str!("../../examples/nft_mint.ark"),
+    },
+    Example {
+        name: "non_interactive_swap",
+        description: "Asset swap without both parties online at once",
+        source: include_str!("../../examples/non_interactive_swap.ark"),
+    },
+    Example {
+        name: "payment_auth",
+        description: "Authorization-based escrow with timelocked refunds",
+        source: include_str!("../../examples/payment_auth.ark"),
+    },
+    Example {
+        name: "price_beacon",
+        description: "On-chain price oracle using an asset quantity as the price",
+        source: include_str!("../../examples/price_beacon.ark"),
+    },
+    Example {
+        name: "single_sig",
+        description: "Bare VTXO owned by a single key",
+        source: include_str!("../../examples/single_sig.ark"),
+    },
+    Example {
+        name: "stability_offer",
+        description: "Standing liquidity offers for USD-stable positions",
+        source: include_str!("../../examples/stability_offer.ark"),
+    },
+    Example {
+        name: "stable_position",
+        description: "USD-stable balance backed by BTC collateral",
+        source: include_str!("../../examples/stable_position.ark"),
+    },
+    Example {
+        name: "threshold_multisig_htlc",
+        description: "HTLC claimed by a threshold of signers",
+        source: include_str!("../../examples/threshold_multisig_htlc.ark"),
+    },
+    Example {
+        name: "threshold_oracle",
+        description: "Threshold verifier for multi-oracle attestations",
+        source: include_str!("../../examples/threshold_oracle.ark"),
+    },
+    Example {
+        name: "token_vault",
+        description: "Token deposits and withdrawals with asset lookups",
+        source: include_str!("../../examples/token_vault.ark"),
+    },
+];
+
+/// The example named `name`.
+pub fn find(name: &str) -> Option<&'static Example> {
+    EXAMPLES.iter().find(|e| e.name == name)
+}
@@ -0,0 +1,113 @@
+//! Source layout formatter.
+//!
+//! Re-indents a contract by bracket depth (two spaces per level), trims
+//! trailing whitespace, collapses runs of spaces and of blank lines, and
+//! ends the file with a single newline. Line breaks, comments and string
+//! literals are kept as written, so formatting never changes what a
+//! contract means — only how it is laid out.
+//!
+//! [`canonical`] instead prints the parsed contract with
+//! [`crate::printer`], giving one layout for every way of writing it.
+
+use crate::{parser, printer};
+
+const INDENT: &str = "  ";
+
+/// Format `source`. Fails if it does not parse, since the layout of
+/// malformed code cannot be trusted.
+pub fn format(source: &str) -> Result<String, String> {
+    parser::parse(source).map_err(|e| format!("Parse error: {}", e))?;
+
+    let mut out = String::new();
+    let mut depth: usize = 0;
+    let mut blank = false;
+    for line in source.lines() {
+        let line = collapse_spaces(line.trim());
+        if line.is_empty() {
+            // At most one blank line, and none at the start of the file
+            blank = !out.is_empty();
+            continue;
+        }
+
+        // A line like `} else {` is outdented by the brackets it starts with
+        let (opens, closes_first, closes) = brackets(&line);
+        let indent = depth.saturating_sub(closes_first);
+        if blank {
+            out.push('\n');
+            blank = false;
+        }
+        out.push_str(&INDENT.repeat(indent));
+        out.push_str(&line);
+        out.push('\n');
+        depth = (depth + opens).saturating_sub(closes);
+    }
+    Ok(out)
+}
+
+/// Format `source` canonically, by printing its syntax tree. Comments on
+/// the contract and its functions are kept; those inside function bodies
+/// and `require` messages are dropped and calls to macros and internal
+/// functions are expanded, since the tree does not keep them.
+pub fn canonical(source: &str) -> Result<String, String> {
+    let contract = parser::parse(source).map_err(|e| format!("Parse error: {}", e))?;
+    Ok(printer::print(&contract))
+}
+
+/// Collapse runs of spaces and tabs outside strings. Whitespace before a
+/// trailing comment is kept, so aligned comments stay aligned.
+fn collapse_spaces(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut in_string = false;
+    let mut gap = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if !in_string && (c == ' ' || c == '\t') {
+            gap.push(c);
+            continue;
+        }
+        let comment = !in_string && c == '/' && chars.peek() == Some(&'/');
+        if !gap.is_empty() {
+            out.push_str(if comment { &gap } else { " " });
+            gap.clear();
+        }
+        out.push(c);
+        if comment {
+            out.extend(chars);
+            break;
+        }
+        if c == '"' {
+            in_string = !in_string;
+        }
+    }
+    out
+}
+
+/// Brackets opened and closed on a line outside comments and strings, and
+/// how many closing brackets lead the line.
+fn brackets(line: &str) -> (usize, usize, usize) {
+    let (mut opens, mut closes, mut leading) = (0, 0, 0);
+    let mut in_string = false;
+    let mut at_start = true;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_string {
+            in_string = c != '"';
+            continue;
+        }
+        match c {
+            '/' if chars.peek() == Some(&'/') => break,
+            '"' => in_string = true,
+            '{' | '(' | '[' => opens += 1,
+            '}' | ')' | ']' => {
+                closes += 1;
+                if at_start {
+                    leading += 1;
+                }
+                continue;
+            }
+            _ => {}
+        }
+        at_start = false;
+    }
+    (opens, leading, closes)
+}
@@ -0,0 +1,26 @@
+//! Digests of the hash functions hash locks can use.
+//!
+//! `sha256` comes from the `sha2` crate; RIPEMD-160, which `hash160` and
+//! `ripemd160` need, is implemented here so the crate takes no other hash
+//! dependency. The test runner and the vector generator hash through
+//! [`digest`], by the [`HashFunction`] of a hash lock; the interpreter runs
+//! the opcodes codegen emits for it, with `sha2` and [`ripemd160`].
+
+use alloc::vec::Vec;
+use sha2::{Digest, Sha256};
+
+use crate::models::HashFunction;
+
+mod ripemd160;
+
+pub use ripemd160::ripemd160;
+
+/// The digest `function` computes of `data`.
+pub fn digest(function: HashFunction, data: &[u8]) -> Vec<u8> {
+    match function {
+        HashFunction::Sha256 => Sha256::digest(data).to_vec(),
+        HashFunction::Hash256 => Sha256::digest(Sha256::digest(data)).to_vec(),
+        HashFunction::Ripemd160 => ripemd160(data).to_vec(),
+        HashFunction::Hash160 => ripemd160(&Sha256::digest(data)).to_vec(),
+    }
+}
@@ -0,0 +1,110 @@
+//! RIPEMD-160, as specified by Dobbertin, Bosselaers and Preneel.
+
+/// Message word each step of the left line reads.
+const R: [usize; 80] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, //
+    7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5, 2, 14, 11, 8, //
+    3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12, //
+    1, 9, 11, 10, 0, 8, 12, 4, 13, 3, 7, 15, 14, 5, 6, 2, //
+    4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+];
+
+/// Message word each step of the right line reads.
+const R_PRIME: [usize; 80] = [
+    5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12, //
+    6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12, 4, 9, 1, 2, //
+    15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13, //
+    8, 6, 4, 1, 3, 11, 15, 0, 5, 12, 2, 13, 9, 7, 10, 14, //
+    12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+];
+
+/// Left rotation of each step of the left line.
+const S: [u32; 80] = [
+    11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8, //
+    7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15, 9, 11, 7, 13, 12, //
+    11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5, //
+    11, 12, 14, 15, 14, 15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12, //
+    9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+];
+
+/// Left rotation of each step of the right line.
+const S_PRIME: [u32; 80] = [
+    8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6, //
+    9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12, 7, 6, 15, 13, 11, //
+    9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5, //
+    15, 5, 8, 11, 14, 14, 6, 14, 6, 9, 12, 9, 12, 5, 15, 8, //
+    8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+];
+
+/// Round constants of the left line.
+const K: [u32; 5] = [0x00000000, 0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xa953fd4e];
+
+/// Round constants of the right line.
+const K_PRIME: [u32; 5] = [0x50a28be6, 0x5c4dd124, 0x6d703ef3, 0x7a6d76e9, 0x00000000];
+
+/// The RIPEMD-160 digest of `data`.
+pub fn ripemd160(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+    // Padding: a one bit, zeros to 56 bytes mod 64, then the bit length
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64).wrapping_mul(8)).to_le_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut x = [0u32; 16];
+        for (word, bytes) in x.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        compress(&mut state, &x);
+    }
+
+    let mut out = [0u8; 20];
+    for (bytes, word) in out.chunks_exact_mut(4).zip(state) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    out
+}
+
+/// The boolean function of round `round`, 0 to 4.
+fn f(round: usize, x: u32, y: u32, z: u32) -> u32 {
+    match round {
+        0 => x ^ y ^ z,
+        1 => (x & y) | (!x & z),
+        2 => (x | !y) ^ z,
+        3 => (x & z) | (y & !z),
+        _ => x ^ (y | !z),
+    }
+}
+
+fn compress(state: &mut [u32; 5], x: &[u32; 16]) {
+    let [mut a, mut b, mut c, mut d, mut e] = *state;
+    let [mut a2, mut b2, mut c2, mut d2, mut e2] = *state;
+    for j in 0..80 {
+        let round = j / 16;
+        let t = a
+            .wrapping_add(f(round, b, c, d))
+            .wrapping_add(x[R[j]])
+            .wrapping_add(K[round])
+            .rotate_left(S[j])
+            .wrapping_add(e);
+        (a, e, d, c, b) = (e, d, c.rotate_left(10), b, t);
+
+        let t = a2
+            .wrapping_add(f(4 - round, b2, c2, d2))
+            .wrapping_add(x[R_PRIME[j]])
+            .wrapping_add(K_PRIME[round])
+            .rotate_left(S_PRIME[j])
+            .wrapping_add(e2);
+        (a2, e2, d2, c2, b2) = (e2, d2, c2.rotate_left(10), b2, t);
+    }
+    let t = state[1].wrapping_add(c).wrapping_add(d2);
+    state[1] = state[2].wrapping_add(d).wrapping_add(e2);
+    state[2] = state[3].wrapping_add(e).wrapping_add(a2);
+    state[3] = state[4].wrapping_add(a).wrapping_add(b2);
+    state[4] = state[0].wrapping_add(b).wrapping_add(c2);
+    state[0] = t;
+}
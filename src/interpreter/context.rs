@@ -0,0 +1,330 @@
+//! Mock transactions for the interpreter.
+//!
+//! A [`TxContext`] is the spending transaction as introspection opcodes see
+//! it: its inputs and outputs, the Arkade Asset packet and the header fields.
+//! Contexts are assembled with [`TxContext::builder`], which checks that the
+//! asset packet only refers to inputs and outputs that exist.
+
+/// Sequence value that disables relative timelocks on an input.
+pub const SEQUENCE_FINAL: u32 = 0xffff_ffff;
+
+/// An Arkade Asset ID: the genesis transaction and the group index within it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AssetId {
+    pub txid: [u8; 32],
+    pub gidx: u16,
+}
+
+impl AssetId {
+    pub fn new(txid: [u8; 32], gidx: u16) -> Self {
+        AssetId { txid, gidx }
+    }
+}
+
+/// A transaction input and the output it spends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxInput {
+    pub txid: [u8; 32],
+    pub vout: u32,
+    pub sequence: u32,
+    /// Value of the spent output, in satoshis
+    pub value: u64,
+    /// scriptPubKey of the spent output
+    pub script_pubkey: Vec<u8>,
+}
+
+impl TxInput {
+    /// An input spending `value` satoshis locked by `script_pubkey`, with a
+    /// zero outpoint and a final sequence.
+    pub fn new(value: u64, script_pubkey: impl Into<Vec<u8>>) -> Self {
+        TxInput {
+            txid: [0u8; 32],
+            vout: 0,
+            sequence: SEQUENCE_FINAL,
+            value,
+            script_pubkey: script_pubkey.into(),
+        }
+    }
+
+    pub fn outpoint(mut self, txid: [u8; 32], vout: u32) -> Self {
+        self.txid = txid;
+        self.vout = vout;
+        self
+    }
+
+    pub fn sequence(mut self, sequence: u32) -> Self {
+        self.sequence = sequence;
+        self
+    }
+}
+
+/// A transaction output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxOutput {
+    pub value: u64,
+    pub script_pubkey: Vec<u8>,
+}
+
+impl TxOutput {
+    pub fn new(value: u64, script_pubkey: impl Into<Vec<u8>>) -> Self {
+        TxOutput {
+            value,
+            script_pubkey: script_pubkey.into(),
+        }
+    }
+}
+
+/// Where an asset group's input amount comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetInput {
+    /// An input of this transaction
+    Local { input: u32, amount: u64 },
+    /// An intent, identified by its transaction ID
+    Intent { txid: [u8; 32], amount: u64 },
+}
+
+impl AssetInput {
+    pub fn amount(&self) -> u64 {
+        match self {
+            AssetInput::Local { amount, .. } | AssetInput::Intent { amount, .. } => *amount,
+        }
+    }
+}
+
+/// An amount of an asset group assigned to an output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetOutput {
+    pub output: u32,
+    pub amount: u64,
+}
+
+/// One group of the Arkade Asset packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetGroup {
+    pub asset_id: AssetId,
+    pub control: Option<AssetId>,
+    pub metadata_hash: [u8; 32],
+    pub inputs: Vec<AssetInput>,
+    pub outputs: Vec<AssetOutput>,
+}
+
+impl AssetGroup {
+    pub fn new(asset_id: AssetId) -> Self {
+        AssetGroup {
+            asset_id,
+            control: None,
+            metadata_hash: [0u8; 32],
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    pub fn control(mut self, control: AssetId) -> Self {
+        self.control = Some(control);
+        self
+    }
+
+    pub fn metadata_hash(mut self, hash: [u8; 32]) -> Self {
+        self.metadata_hash = hash;
+        self
+    }
+
+    /// Take `amount` of the asset from input `input`.
+    pub fn input(mut self, input: u32, amount: u64) -> Self {
+        self.inputs.push(AssetInput::Local { input, amount });
+        self
+    }
+
+    /// Take `amount` of the asset from the intent `txid`.
+    pub fn intent(mut self, txid: [u8; 32], amount: u64) -> Self {
+        self.inputs.push(AssetInput::Intent { txid, amount });
+        self
+    }
+
+    /// Assign `amount` of the asset to output `output`.
+    pub fn output(mut self, output: u32, amount: u64) -> Self {
+        self.outputs.push(AssetOutput { output, amount });
+        self
+    }
+
+    pub fn sum_inputs(&self) -> u64 {
+        self.inputs.iter().map(AssetInput::amount).sum()
+    }
+
+    pub fn sum_outputs(&self) -> u64 {
+        self.outputs.iter().map(|o| o.amount).sum()
+    }
+}
+
+/// The spending transaction seen by introspection opcodes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxContext {
+    /// Message transaction signatures commit to
+    pub sighash: [u8; 32],
+    pub version: u32,
+    pub locktime: u32,
+    /// Index of the input whose script is being executed
+    pub current_input: u32,
+    pub inputs: Vec<TxInput>,
+    pub outputs: Vec<TxOutput>,
+    pub asset_groups: Vec<AssetGroup>,
+}
+
+impl TxContext {
+    pub fn builder() -> TxContextBuilder {
+        TxContextBuilder::default()
+    }
+
+    pub fn input(&self, index: i64) -> Result<&TxInput, String> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|i| self.inputs.get(i))
+            .ok_or_else(|| format!("input {} out of range", index))
+    }
+
+    pub fn output(&self, index: i64) -> Result<&TxOutput, String> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|i| self.outputs.get(i))
+            .ok_or_else(|| format!("output {} out of range", index))
+    }
+
+    pub fn asset_group(&self, gidx: i64) -> Result<&AssetGroup, String> {
+        usize::try_from(gidx)
+            .ok()
+            .and_then(|i| self.asset_groups.get(i))
+            .ok_or_else(|| format!("asset group {} out of range", gidx))
+    }
+
+    /// Index of the group carrying `asset_id`.
+    pub fn find_asset_group(&self, asset_id: &AssetId) -> Option<usize> {
+        self.asset_groups
+            .iter()
+            .position(|g| &g.asset_id == asset_id)
+    }
+
+    /// Assets assigned to output `output`, in packet order.
+    pub fn output_assets(&self, output: u32) -> Vec<(AssetId, u64)> {
+        self.asset_groups
+            .iter()
+            .filter_map(|g| {
+                let amounts = g.outputs.iter().filter(|o| o.output == output);
+                let mut amounts = amounts.map(|o| o.amount).peekable();
+                amounts.peek()?;
+                Some((g.asset_id, amounts.sum()))
+            })
+            .collect()
+    }
+
+    /// Assets the packet declares for input `input`, in packet order.
+    pub fn input_assets(&self, input: u32) -> Vec<(AssetId, u64)> {
+        self.asset_groups
+            .iter()
+            .filter_map(|g| {
+                let amounts = g.inputs.iter().filter_map(|i| match i {
+                    AssetInput::Local { input: idx, amount } if *idx == input => Some(*amount),
+                    _ => None,
+                });
+                let mut amounts = amounts.peekable();
+                amounts.peek()?;
+                Some((g.asset_id, amounts.sum()))
+            })
+            .collect()
+    }
+}
+
+/// Builder for [`TxContext`].
+///
+/// Defaults to a version 2 transaction with no locktime and an all-zero
+/// sighash, executing input 0.
+#[derive(Debug, Clone)]
+pub struct TxContextBuilder {
+    tx: TxContext,
+}
+
+impl Default for TxContextBuilder {
+    fn default() -> Self {
+        TxContextBuilder {
+            tx: TxContext {
+                sighash: [0u8; 32],
+                version: 2,
+                locktime: 0,
+                current_input: 0,
+                inputs: Vec::new(),
+                outputs: Vec::new(),
+                asset_groups: Vec::new(),
+            },
+        }
+    }
+}
+
+impl TxContextBuilder {
+    pub fn sighash(mut self, sighash: [u8; 32]) -> Self {
+        self.tx.sighash = sighash;
+        self
+    }
+
+    pub fn version(mut self, version: u32) -> Self {
+        self.tx.version = version;
+        self
+    }
+
+    pub fn locktime(mut self, locktime: u32) -> Self {
+        self.tx.locktime = locktime;
+        self
+    }
+
+    pub fn current_input(mut self, index: u32) -> Self {
+        self.tx.current_input = index;
+        self
+    }
+
+    pub fn input(mut self, input: TxInput) -> Self {
+        self.tx.inputs.push(input);
+        self
+    }
+
+    pub fn output(mut self, output: TxOutput) -> Self {
+        self.tx.outputs.push(output);
+        self
+    }
+
+    pub fn asset_group(mut self, group: AssetGroup) -> Self {
+        self.tx.asset_groups.push(group);
+        self
+    }
+
+    /// Finish the context, rejecting references to inputs or outputs that do
+    /// not exist.
+    pub fn build(self) -> Result<TxContext, String> {
+        let tx = self.tx;
+        if tx.current_input as usize >= tx.inputs.len() {
+            return Err(format!(
+                "current input {} out of range ({} inputs)",
+                tx.current_input,
+                tx.inputs.len()
+            ));
+        }
+        for (gidx, group) in tx.asset_groups.iter().enumerate() {
+            for input in &group.inputs {
+                if let AssetInput::Local { input, .. } = input {
+                    if *input as usize >= tx.inputs.len() {
+                        return Err(format!(
+                            "asset group {} spends input {} out of range",
+                            gidx, input
+                        ));
+                    }
+                }
+            }
+            for output in &group.outputs {
+                if output.output as usize >= tx.outputs.len() {
+                    return Err(format!(
+                        "asset group {} assigns output {} out of range",
+                        gidx, output.output
+                    ));
+                }
+            }
+        }
+        Ok(tx)
+    }
+}
@@ -0,0 +1,158 @@
+//! Whole leaves of a compiled artifact.
+
+use std::collections::HashMap;
+
+use super::{cast_to_bool, execute_on, TxContext};
+use crate::ir::Instr;
+use crate::linker;
+use crate::models::{ContractJson, OperationData};
+use crate::witness::WitnessBuilder;
+
+/// Runs one function variant of a compiled contract against a mock
+/// transaction: the constructor arguments are linked into the leaf, the
+/// witness is built as [`WitnessBuilder`] builds it, and the leaf script,
+/// which leaves the witness elements out, runs on top of the witness stack
+/// as consensus runs it. The server signature
+/// and exit timelock the compiler adds are part of the run, so a
+/// cooperative path needs [`linker::SERVER_KEY`] and `serverSig`, and an
+/// exit path an input sequence that has waited out the exit delay.
+///
+/// ```ignore
+/// let tx = TxContext::builder()
+///     .input(TxInput::new(10_000, script_pubkey).sequence(144))
+///     .output(TxOutput::new(9_000, receiver_script))
+///     .build()?;
+/// LeafEvaluator::for_function(&artifact, "claim", false)?
+///     .params(constructor_args)
+///     .arg("receiverSig", sig_hex)
+///     .arg("preimage", preimage_hex)
+///     .run(&tx)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct LeafEvaluator<'a> {
+    artifact: &'a ContractJson,
+    params: HashMap<String, String>,
+    witness: WitnessBuilder<'a>,
+    /// A witness stack given whole, in place of the one `witness` builds
+    stack: Option<Vec<Vec<u8>>>,
+}
+
+impl<'a> LeafEvaluator<'a> {
+    /// Evaluate `function`'s cooperative variant when `server_variant` is
+    /// set, or its exit variant.
+    pub fn for_function(
+        artifact: &'a ContractJson,
+        function: &str,
+        server_variant: bool,
+    ) -> Result<Self, String> {
+        Ok(LeafEvaluator {
+            artifact,
+            params: HashMap::new(),
+            witness: WitnessBuilder::for_function(artifact, function, server_variant)?,
+            stack: None,
+        })
+    }
+
+    /// Set the constructor argument `name`, as [`linker::link`] takes it.
+    /// [`linker::SERVER_KEY`] sets the server key.
+    pub fn param(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.params.insert(name.to_string(), value.into());
+        self
+    }
+
+    /// Set several constructor arguments.
+    pub fn params<I, K, V>(mut self, params: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.params
+            .extend(params.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    /// Set the witness element `name`, as [`WitnessBuilder::arg`] takes it.
+    pub fn arg(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.witness = self.witness.arg(name, value);
+        self
+    }
+
+    /// Set several witness elements.
+    pub fn args<I, K, V>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.witness = self.witness.args(args);
+        self
+    }
+
+    /// Run on `stack`, bottom first, instead of the witness the arguments
+    /// build, for witnesses no [`WitnessBuilder`] builds, such as one with
+    /// an empty signature or an element missing.
+    pub fn witness_stack(mut self, stack: Vec<Vec<u8>>) -> Self {
+        self.stack = Some(stack);
+        self
+    }
+
+    /// Run the leaf and return the final stack, bottom first.
+    ///
+    /// Fails on arguments that do not link or build a witness, on leaves
+    /// with values the interpreter cannot provide, such as `<VTXO:...>`
+    /// templates, and on the first instruction that fails.
+    pub fn execute(&self, tx: &TxContext) -> Result<Vec<Vec<u8>>, String> {
+        let (witness, instrs) = self.prepare()?;
+        execute_on(witness, &instrs, &HashMap::new(), tx)
+    }
+
+    /// Run the leaf and check that it succeeds as a Tapscript leaf must:
+    /// it leaves a clean stack of exactly one element, and that element
+    /// is true.
+    pub fn run(&self, tx: &TxContext) -> Result<(), String> {
+        match self.execute(tx)?.as_slice() {
+            [top] if cast_to_bool(top) => Ok(()),
+            [_] => Err("the leaf left false".to_string()),
+            stack => Err(format!(
+                "the leaf left {} stack elements instead of one",
+                stack.len()
+            )),
+        }
+    }
+
+    /// The witness stack and the instructions that run on it. Fails where
+    /// [`execute`](Self::execute) fails before running anything.
+    pub fn prepare(&self) -> Result<(Vec<Vec<u8>>, Vec<Instr>), String> {
+        let witness = match &self.stack {
+            Some(stack) => stack.clone(),
+            None => self.witness.build()?,
+        };
+        Ok((witness, self.instructions()?))
+    }
+
+    /// The leaf's instructions with the constructor arguments linked in and
+    /// the witness elements, which the script does not push, left out.
+    fn instructions(&self) -> Result<Vec<Instr>, String> {
+        let linked = linker::link(self.artifact, &self.params)?;
+        let function = linked
+            .functions
+            .iter()
+            .find(|f| f.selector == self.witness.function().selector)
+            .expect("linking keeps every function");
+        let mut instrs = Vec::new();
+        for operation in &function.operations {
+            match &operation.data {
+                OperationData::Opcode => instrs.push(Instr::parse(&operation.op)),
+                OperationData::Push { hex, .. } => instrs.push(Instr::Raw(format!("0x{}", hex))),
+                OperationData::FunctionInput { .. } => {}
+                OperationData::ConstructorParam { name, .. }
+                | OperationData::Runtime { name, .. }
+                | OperationData::Local { name } => {
+                    return Err(format!("<{}> has no value to run with", name))
+                }
+            }
+        }
+        Ok(instrs)
+    }
+}
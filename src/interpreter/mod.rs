@@ -0,0 +1,805 @@
+//! Script interpreter.
+//!
+//! Executes lifted leaf instructions against a mock [`TxContext`], so the
+//! behavior of introspection code can be checked without a node. Placeholders
+//! (`<name>`) are resolved from a map of bindings supplied by the caller —
+//! constructor arguments and witness elements alike.
+//!
+//! Stack effects follow the Arkade introspection opcodes: values are pushed as
+//! single elements (8-byte little-endian amounts, whole scriptPubKeys), asset
+//! IDs as a `txid32 gidx` pair, and lookups that find nothing push the
+//! CScriptNum `-1`. 64-bit arithmetic pushes a success flag above its result
+//! and leaves the operands in place on overflow.
+//!
+//! Signature opcodes verify BIP 340 signatures over the mock's `sighash`
+//! (`OP_CHECKSIGFROMSTACK` over a message on the stack) with the BIP 342
+//! rules: an empty signature is a failed check, any other invalid one an
+//! error. They take their operands as consensus does, the public key on
+//! top: `<sig> <pubkey> OP_CHECKSIG` and `OP_CHECKSIGVERIFY`,
+//! `<sig> <msg> <pubkey> OP_CHECKSIGFROMSTACK` and `<sig> <n> <pubkey>
+//! OP_CHECKSIGADD`. Opcodes that read data the mock does not model
+//! (`OP_TXHASH`, `OP_TXWEIGHT`, issuance, nonces) are rejected as
+//! unsupported.
+//!
+//! [`LeafEvaluator`] runs a compiled function variant's whole leaf.
+
+use std::collections::HashMap;
+
+use k256::schnorr::{Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+
+pub mod context;
+mod leaf;
+
+pub use crate::encoding::encode_num;
+pub use context::{
+    AssetGroup, AssetId, AssetInput, AssetOutput, TxContext, TxContextBuilder, TxInput, TxOutput,
+};
+pub use leaf::LeafEvaluator;
+
+use crate::encoding::split_sighash;
+use crate::hashes;
+use crate::ir::{small_int, Instr};
+
+/// Largest CScriptNum operand, in bytes. Five bytes fit any locktime.
+const MAX_NUM_SIZE: usize = 5;
+
+/// Locktimes below this are block heights, above it UNIX timestamps.
+const LOCKTIME_THRESHOLD: i64 = 500_000_000;
+
+const SEQUENCE_DISABLE_FLAG: i64 = 1 << 31;
+const SEQUENCE_TYPE_FLAG: i64 = 1 << 22;
+const SEQUENCE_MASK: i64 = 0xffff;
+
+/// Execute `instrs` and return the final stack, bottom first.
+///
+/// Fails on the first instruction that cannot be executed or whose check
+/// does not hold, naming its position and token.
+pub fn execute(
+    instrs: &[Instr],
+    bindings: &HashMap<String, Vec<u8>>,
+    tx: &TxContext,
+) -> Result<Vec<Vec<u8>>, String> {
+    execute_on(Vec::new(), instrs, bindings, tx)
+}
+
+/// [`execute`] `instrs` on top of `stack`, bottom first, as consensus runs
+/// a leaf script on top of its witness stack.
+pub fn execute_on(
+    stack: Vec<Vec<u8>>,
+    instrs: &[Instr],
+    bindings: &HashMap<String, Vec<u8>>,
+    tx: &TxContext,
+) -> Result<Vec<Vec<u8>>, String> {
+    let mut machine = Machine {
+        tx,
+        bindings,
+        stack,
+        alt: Vec::new(),
+        branches: Vec::new(),
+    };
+    for (i, instr) in instrs.iter().enumerate() {
+        machine
+            .step(instr)
+            .map_err(|e| format!("instruction {} ({}): {}", i, instr.to_asm(), e))?;
+    }
+    if !machine.branches.is_empty() {
+        return Err("unbalanced conditional: missing OP_ENDIF".to_string());
+    }
+    Ok(machine.stack)
+}
+
+/// Execute `instrs` and report whether they leave a true value on top of the
+/// stack, as a tapscript leaf must.
+pub fn succeeds(instrs: &[Instr], bindings: &HashMap<String, Vec<u8>>, tx: &TxContext) -> bool {
+    execute(instrs, bindings, tx)
+        .map(|stack| stack.last().is_some_and(|top| cast_to_bool(top)))
+        .unwrap_or(false)
+}
+
+/// Decode a CScriptNum of at most five bytes.
+pub fn decode_num(bytes: &[u8]) -> Result<i64, String> {
+    if bytes.len() > MAX_NUM_SIZE {
+        return Err(format!(
+            "{}-byte number exceeds {} bytes",
+            bytes.len(),
+            MAX_NUM_SIZE
+        ));
+    }
+    let Some((&last, _)) = bytes.split_last() else {
+        return Ok(0);
+    };
+    let mut n: i64 = 0;
+    for (i, b) in bytes.iter().enumerate() {
+        n |= (*b as i64) << (8 * i);
+    }
+    if last & 0x80 != 0 {
+        n &= !(0x80i64 << (8 * (bytes.len() - 1)));
+        n = -n;
+    }
+    Ok(n)
+}
+
+/// Script truthiness: anything but zero and negative zero.
+pub fn cast_to_bool(bytes: &[u8]) -> bool {
+    match bytes.split_last() {
+        None => false,
+        Some((&last, rest)) => rest.iter().any(|b| *b != 0) || (last != 0 && last != 0x80),
+    }
+}
+
+/// Verify a BIP 340 signature. Compressed (33-byte) keys are accepted and
+/// checked against their x coordinate; empty or malformed signatures fail.
+pub fn verify_schnorr(pubkey: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let x_only = match pubkey.len() {
+        33 => &pubkey[1..],
+        _ => pubkey,
+    };
+    // k256 panics on signatures of the wrong length
+    if x_only.len() != 32 || signature.len() != 64 {
+        return false;
+    }
+    let Ok(key) = VerifyingKey::from_bytes(x_only) else {
+        return false;
+    };
+    let Ok(signature) = Signature::try_from(signature) else {
+        return false;
+    };
+    key.verify_raw(message, &signature).is_ok()
+}
+
+fn encode_bool(b: bool) -> Vec<u8> {
+    if b {
+        vec![1]
+    } else {
+        Vec::new()
+    }
+}
+
+struct Machine<'a> {
+    tx: &'a TxContext,
+    bindings: &'a HashMap<String, Vec<u8>>,
+    stack: Vec<Vec<u8>>,
+    alt: Vec<Vec<u8>>,
+    /// Whether each enclosing `OP_IF`/`OP_NOTIF` branch is being executed
+    branches: Vec<bool>,
+}
+
+impl Machine<'_> {
+    fn executing(&self) -> bool {
+        self.branches.iter().all(|b| *b)
+    }
+
+    fn step(&mut self, instr: &Instr) -> Result<(), String> {
+        let op = match instr {
+            Instr::Op(op) => op.as_str(),
+            _ if !self.executing() => return Ok(()),
+            Instr::Num(n) => {
+                self.stack.push(encode_num(*n));
+                return Ok(());
+            }
+            Instr::Placeholder(name) => {
+                let value = self
+                    .bindings
+                    .get(name)
+                    .ok_or_else(|| format!("no value bound to <{}>", name))?;
+                self.stack.push(value.clone());
+                return Ok(());
+            }
+            Instr::Raw(token) => {
+                let hex = token.strip_prefix("0x").unwrap_or(token);
+                let bytes = hex::decode(hex).map_err(|_| "unknown token".to_string())?;
+                self.stack.push(bytes);
+                return Ok(());
+            }
+        };
+
+        match op {
+            "OP_IF" | "OP_NOTIF" => {
+                let taken = if self.executing() {
+                    let cond = cast_to_bool(&self.pop()?);
+                    cond == (op == "OP_IF")
+                } else {
+                    false
+                };
+                self.branches.push(taken);
+                return Ok(());
+            }
+            "OP_ELSE" => {
+                let top = self.branches.last_mut().ok_or("OP_ELSE without OP_IF")?;
+                *top = !*top;
+                return Ok(());
+            }
+            "OP_ENDIF" => {
+                self.branches.pop().ok_or("OP_ENDIF without OP_IF")?;
+                return Ok(());
+            }
+            _ if !self.executing() => return Ok(()),
+            _ => {}
+        }
+
+        if let Some(()) = self.core(op)? {
+            return Ok(());
+        }
+        if let Some(()) = self.arithmetic64(op)? {
+            return Ok(());
+        }
+        if let Some(()) = self.introspection(op)? {
+            return Ok(());
+        }
+        if let Some(()) = self.signatures(op)? {
+            return Ok(());
+        }
+        Err("unsupported opcode".to_string())
+    }
+
+    fn pop(&mut self) -> Result<Vec<u8>, String> {
+        self.stack
+            .pop()
+            .ok_or_else(|| "stack underflow".to_string())
+    }
+
+    fn pop_num(&mut self) -> Result<i64, String> {
+        decode_num(&self.pop()?)
+    }
+
+    fn pop_bool(&mut self) -> Result<bool, String> {
+        Ok(cast_to_bool(&self.pop()?))
+    }
+
+    fn pop_le64(&mut self) -> Result<i64, String> {
+        let bytes = self.pop()?;
+        let bytes: [u8; 8] = bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| format!("expected an 8-byte number, found {} bytes", bytes.len()))?;
+        Ok(i64::from_le_bytes(bytes))
+    }
+
+    fn pop_txid(&mut self) -> Result<[u8; 32], String> {
+        let bytes = self.pop()?;
+        bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| format!("expected a 32-byte txid, found {} bytes", bytes.len()))
+    }
+
+    fn pop_index(&mut self) -> Result<u32, String> {
+        let n = self.pop_num()?;
+        u32::try_from(n).map_err(|_| format!("invalid index {}", n))
+    }
+
+    fn pop_asset_id(&mut self) -> Result<AssetId, String> {
+        let gidx = self.pop_num()?;
+        let txid = self.pop_txid()?;
+        let gidx = u16::try_from(gidx).map_err(|_| format!("invalid group index {}", gidx))?;
+        Ok(AssetId::new(txid, gidx))
+    }
+
+    fn push_num(&mut self, n: i64) {
+        self.stack.push(encode_num(n));
+    }
+
+    fn push_bool(&mut self, b: bool) {
+        self.stack.push(encode_bool(b));
+    }
+
+    fn push_le64(&mut self, n: u64) {
+        self.stack.push(n.to_le_bytes().to_vec());
+    }
+
+    fn push_asset_id(&mut self, id: &AssetId) {
+        self.stack.push(id.txid.to_vec());
+        self.push_num(id.gidx as i64);
+    }
+
+    fn peek(&self, depth: usize) -> Result<&Vec<u8>, String> {
+        self.stack
+            .len()
+            .checked_sub(depth + 1)
+            .map(|i| &self.stack[i])
+            .ok_or_else(|| "stack underflow".to_string())
+    }
+
+    fn verify(&mut self) -> Result<(), String> {
+        if self.pop_bool()? {
+            Ok(())
+        } else {
+            Err("verification failed".to_string())
+        }
+    }
+
+    /// Constants, stack manipulation, comparisons, CScriptNum arithmetic,
+    /// hashing and timelocks.
+    fn core(&mut self, op: &str) -> Result<Option<()>, String> {
+        if let Some(n) = small_int(op) {
+            self.push_num(n);
+            return Ok(Some(()));
+        }
+        match op {
+            "OP_0" | "OP_FALSE" => self.push_num(0),
+            "OP_TRUE" => self.push_num(1),
+            "OP_1NEGATE" => self.push_num(-1),
+
+            "OP_VERIFY" => self.verify()?,
+            "OP_DROP" => {
+                self.pop()?;
+            }
+            "OP_2DROP" => {
+                self.pop()?;
+                self.pop()?;
+            }
+            "OP_DUP" => {
+                let top = self.peek(0)?.clone();
+                self.stack.push(top);
+            }
+            "OP_2DUP" => {
+                let (a, b) = (self.peek(1)?.clone(), self.peek(0)?.clone());
+                self.stack.push(a);
+                self.stack.push(b);
+            }
+            "OP_OVER" => {
+                let second = self.peek(1)?.clone();
+                self.stack.push(second);
+            }
+            "OP_NIP" => {
+                let top = self.pop()?;
+                self.pop()?;
+                self.stack.push(top);
+            }
+            "OP_SWAP" => {
+                let (b, a) = (self.pop()?, self.pop()?);
+                self.stack.push(b);
+                self.stack.push(a);
+            }
+            "OP_ROT" => {
+                let (c, b, a) = (self.pop()?, self.pop()?, self.pop()?);
+                self.stack.extend([b, c, a]);
+            }
+            "OP_PICK" | "OP_ROLL" => {
+                let n = usize::try_from(self.pop_num()?).map_err(|_| "negative depth")?;
+                let value = self.peek(n)?.clone();
+                if op == "OP_ROLL" {
+                    let i = self.stack.len() - n - 1;
+                    self.stack.remove(i);
+                }
+                self.stack.push(value);
+            }
+            "OP_TOALTSTACK" => {
+                let top = self.pop()?;
+                self.alt.push(top);
+            }
+            "OP_FROMALTSTACK" => {
+                let top = self.alt.pop().ok_or("alt stack underflow")?;
+                self.stack.push(top);
+            }
+            "OP_SIZE" => {
+                let len = self.peek(0)?.len() as i64;
+                self.push_num(len);
+            }
+            "OP_CAT" => {
+                let (b, mut a) = (self.pop()?, self.pop()?);
+                a.extend(b);
+                self.stack.push(a);
+            }
+
+            "OP_EQUAL" | "OP_EQUALVERIFY" => {
+                let equal = self.pop()? == self.pop()?;
+                self.push_bool(equal);
+                if op == "OP_EQUALVERIFY" {
+                    self.verify()?;
+                }
+            }
+            "OP_NOT" => {
+                let n = self.pop_num()?;
+                self.push_bool(n == 0);
+            }
+            "OP_0NOTEQUAL" => {
+                let n = self.pop_num()?;
+                self.push_bool(n != 0);
+            }
+            "OP_BOOLAND" | "OP_BOOLOR" => {
+                let (b, a) = (self.pop_num()? != 0, self.pop_num()? != 0);
+                self.push_bool(if op == "OP_BOOLAND" { a && b } else { a || b });
+            }
+            "OP_WITHIN" => {
+                let (max, min, x) = (self.pop_num()?, self.pop_num()?, self.pop_num()?);
+                self.push_bool(min <= x && x < max);
+            }
+            "OP_ADD" | "OP_SUB" => {
+                let (b, a) = (self.pop_num()?, self.pop_num()?);
+                self.push_num(if op == "OP_ADD" { a + b } else { a - b });
+            }
+            "OP_NUMEQUAL"
+            | "OP_NUMEQUALVERIFY"
+            | "OP_NUMNOTEQUAL"
+            | "OP_LESSTHAN"
+            | "OP_LESSTHANOREQUAL"
+            | "OP_GREATERTHAN"
+            | "OP_GREATERTHANOREQUAL" => {
+                let (b, a) = (self.pop_num()?, self.pop_num()?);
+                self.push_bool(compare(op, a, b));
+                if op == "OP_NUMEQUALVERIFY" {
+                    self.verify()?;
+                }
+            }
+
+            "OP_SHA256" => {
+                let data = self.pop()?;
+                self.stack.push(Sha256::digest(data).to_vec());
+            }
+            "OP_HASH256" => {
+                let data = self.pop()?;
+                self.stack
+                    .push(Sha256::digest(Sha256::digest(data)).to_vec());
+            }
+            "OP_RIPEMD160" => {
+                let data = self.pop()?;
+                self.stack.push(hashes::ripemd160(&data).to_vec());
+            }
+            "OP_HASH160" => {
+                let data = self.pop()?;
+                self.stack
+                    .push(hashes::ripemd160(&Sha256::digest(data)).to_vec());
+            }
+
+            "OP_CHECKLOCKTIMEVERIFY" => {
+                let lock = decode_num(self.peek(0)?)?;
+                self.check_locktime(lock)?;
+            }
+            "OP_CHECKSEQUENCEVERIFY" => {
+                let lock = decode_num(self.peek(0)?)?;
+                self.check_sequence(lock)?;
+            }
+
+            _ => return Ok(None),
+        }
+        Ok(Some(()))
+    }
+
+    /// Signature checks, against the mock sighash or a stack message.
+    fn signatures(&mut self, op: &str) -> Result<Option<()>, String> {
+        match op {
+            "OP_CHECKSIG" | "OP_CHECKSIGVERIFY" => {
+                let pubkey = self.pop()?;
+                let signature = self.pop()?;
+                let valid = self.check_tx_sig(&signature, &pubkey)?;
+                match op {
+                    "OP_CHECKSIG" => self.push_bool(valid),
+                    _ if !valid => return Err("signature check failed".to_string()),
+                    _ => {}
+                }
+            }
+            "OP_CHECKSIGADD" => {
+                let pubkey = self.pop()?;
+                let n = self.pop_num()?;
+                let signature = self.pop()?;
+                let valid = self.check_tx_sig(&signature, &pubkey)?;
+                self.push_num(n + i64::from(valid));
+            }
+            "OP_CHECKSIGFROMSTACK" | "OP_CHECKSIGFROMSTACKVERIFY" => {
+                let pubkey = self.pop()?;
+                let message = self.pop()?;
+                let signature = self.pop()?;
+                let valid = !signature.is_empty();
+                if valid && !verify_schnorr(&pubkey, &message, &signature) {
+                    return Err("invalid signature".to_string());
+                }
+                match op {
+                    "OP_CHECKSIGFROMSTACK" => self.push_bool(valid),
+                    _ if !valid => return Err("signature check failed".to_string()),
+                    _ => {}
+                }
+            }
+            _ => return Ok(None),
+        }
+        Ok(Some(()))
+    }
+
+    /// Whether `signature` signs the mock sighash for `pubkey`: `false` if
+    /// it is empty, an error if it is invalid, as BIP 342 has it.
+    fn check_tx_sig(&self, signature: &[u8], pubkey: &[u8]) -> Result<bool, String> {
+        if signature.is_empty() {
+            return Ok(false);
+        }
+        let (signature, _) = split_sighash(signature)?;
+        match verify_schnorr(pubkey, &self.tx.sighash, signature) {
+            true => Ok(true),
+            false => Err("invalid signature".to_string()),
+        }
+    }
+
+    /// 64-bit arithmetic, comparisons and conversions.
+    fn arithmetic64(&mut self, op: &str) -> Result<Option<()>, String> {
+        match op {
+            "OP_ADD64" | "OP_SUB64" | "OP_MUL64" => {
+                let (b, a) = (self.pop_le64()?, self.pop_le64()?);
+                let result = match op {
+                    "OP_ADD64" => a.checked_add(b),
+                    "OP_SUB64" => a.checked_sub(b),
+                    _ => a.checked_mul(b),
+                };
+                match result {
+                    Some(r) => {
+                        self.push_le64(r as u64);
+                        self.push_num(1);
+                    }
+                    None => self.overflow(&[a, b]),
+                }
+            }
+            "OP_DIV64" => {
+                let (b, a) = (self.pop_le64()?, self.pop_le64()?);
+                match (a.checked_rem_euclid(b), a.checked_div_euclid(b)) {
+                    (Some(rem), Some(quot)) => {
+                        self.push_le64(rem as u64);
+                        self.push_le64(quot as u64);
+                        self.push_num(1);
+                    }
+                    _ => self.overflow(&[a, b]),
+                }
+            }
+            "OP_NEG64" => {
+                let a = self.pop_le64()?;
+                match a.checked_neg() {
+                    Some(r) => {
+                        self.push_le64(r as u64);
+                        self.push_num(1);
+                    }
+                    None => self.overflow(&[a]),
+                }
+            }
+            "OP_LESSTHAN64"
+            | "OP_LESSTHANOREQUAL64"
+            | "OP_GREATERTHAN64"
+            | "OP_GREATERTHANOREQUAL64" => {
+                let (b, a) = (self.pop_le64()?, self.pop_le64()?);
+                self.push_bool(compare(op.trim_end_matches("64"), a, b));
+            }
+            "OP_SCRIPTNUMTOLE64" => {
+                let n = self.pop_num()?;
+                self.push_le64(n as u64);
+            }
+            "OP_LE64TOSCRIPTNUM" => {
+                let n = self.pop_le64()?;
+                if n.unsigned_abs() > i32::MAX as u64 {
+                    return Err(format!("{} does not fit a 4-byte number", n));
+                }
+                self.push_num(n);
+            }
+            "OP_LE32TOLE64" => {
+                let bytes = self.pop()?;
+                let bytes: [u8; 4] = bytes.as_slice().try_into().map_err(|_| {
+                    format!("expected a 4-byte number, found {} bytes", bytes.len())
+                })?;
+                self.push_le64(u32::from_le_bytes(bytes) as u64);
+            }
+            _ => return Ok(None),
+        }
+        Ok(Some(()))
+    }
+
+    /// On overflow the operands are restored and a zero flag pushed.
+    fn overflow(&mut self, operands: &[i64]) {
+        for n in operands {
+            self.push_le64(*n as u64);
+        }
+        self.push_num(0);
+    }
+
+    /// Transaction and Arkade Asset introspection.
+    fn introspection(&mut self, op: &str) -> Result<Option<()>, String> {
+        let tx = self.tx;
+        match op {
+            "OP_PUSHCURRENTINPUTINDEX" => self.push_num(tx.current_input as i64),
+            "OP_INSPECTVERSION" => self.stack.push(tx.version.to_le_bytes().to_vec()),
+            "OP_INSPECTLOCKTIME" => self.stack.push(tx.locktime.to_le_bytes().to_vec()),
+            "OP_INSPECTNUMINPUTS" => self.push_num(tx.inputs.len() as i64),
+            "OP_INSPECTNUMOUTPUTS" => self.push_num(tx.outputs.len() as i64),
+
+            "OP_INSPECTINPUTVALUE" => {
+                let input = tx.input(self.pop_num()?)?;
+                self.push_le64(input.value);
+            }
+            "OP_INSPECTINPUTSCRIPTPUBKEY" => {
+                let input = tx.input(self.pop_num()?)?;
+                self.stack.push(input.script_pubkey.clone());
+            }
+            "OP_INSPECTINPUTSEQUENCE" => {
+                let input = tx.input(self.pop_num()?)?;
+                self.stack.push(input.sequence.to_le_bytes().to_vec());
+            }
+            "OP_INSPECTINPUTOUTPOINT" => {
+                let input = tx.input(self.pop_num()?)?;
+                self.stack.push(input.txid.to_vec());
+                self.stack.push(input.vout.to_le_bytes().to_vec());
+            }
+            "OP_INSPECTOUTPUTVALUE" => {
+                let output = tx.output(self.pop_num()?)?;
+                self.push_le64(output.value);
+            }
+            "OP_INSPECTOUTPUTSCRIPTPUBKEY" => {
+                let output = tx.output(self.pop_num()?)?;
+                self.stack.push(output.script_pubkey.clone());
+            }
+
+            "OP_INSPECTNUMASSETGROUPS" => self.push_num(tx.asset_groups.len() as i64),
+            "OP_INSPECTASSETGROUPASSETID" => {
+                let group = tx.asset_group(self.pop_num()?)?;
+                self.push_asset_id(&group.asset_id);
+            }
+            "OP_INSPECTASSETGROUPCTRL" => {
+                let group = tx.asset_group(self.pop_num()?)?;
+                match &group.control {
+                    Some(id) => self.push_asset_id(id),
+                    None => self.push_num(-1),
+                }
+            }
+            "OP_FINDASSETGROUPBYASSETID" => {
+                let id = self.pop_asset_id()?;
+                match tx.find_asset_group(&id) {
+                    Some(gidx) => self.push_num(gidx as i64),
+                    None => self.push_num(-1),
+                }
+            }
+            "OP_INSPECTASSETGROUPMETADATAHASH" => {
+                let group = tx.asset_group(self.pop_num()?)?;
+                self.stack.push(group.metadata_hash.to_vec());
+            }
+            "OP_INSPECTASSETGROUPNUM" => {
+                let source = self.pop_num()?;
+                let group = tx.asset_group(self.pop_num()?)?;
+                let (inputs, outputs) = (group.inputs.len() as i64, group.outputs.len() as i64);
+                match source {
+                    0 => self.push_num(inputs),
+                    1 => self.push_num(outputs),
+                    2 => {
+                        self.push_num(inputs);
+                        self.push_num(outputs);
+                    }
+                    _ => return Err(format!("invalid source {}", source)),
+                }
+            }
+            "OP_INSPECTASSETGROUPSUM" => {
+                let source = self.pop_num()?;
+                let group = tx.asset_group(self.pop_num()?)?;
+                match source {
+                    0 => self.push_le64(group.sum_inputs()),
+                    1 => self.push_le64(group.sum_outputs()),
+                    2 => {
+                        self.push_le64(group.sum_inputs());
+                        self.push_le64(group.sum_outputs());
+                    }
+                    _ => return Err(format!("invalid source {}", source)),
+                }
+            }
+            "OP_INSPECTASSETGROUP" => {
+                let source = self.pop_num()?;
+                let j = self.pop_index()? as usize;
+                let group = tx.asset_group(self.pop_num()?)?;
+                match source {
+                    0 => match group.inputs.get(j).ok_or("asset input out of range")? {
+                        AssetInput::Local { input, amount } => {
+                            self.push_num(1);
+                            self.stack.push(input.to_le_bytes().to_vec());
+                            self.push_le64(*amount);
+                        }
+                        AssetInput::Intent { txid, amount } => {
+                            self.push_num(2);
+                            self.stack.push(txid.to_vec());
+                            self.push_le64(*amount);
+                        }
+                    },
+                    1 => {
+                        let output = group.outputs.get(j).ok_or("asset output out of range")?;
+                        self.push_num(1);
+                        self.stack.push(output.output.to_le_bytes().to_vec());
+                        self.push_le64(output.amount);
+                    }
+                    _ => return Err(format!("invalid source {}", source)),
+                }
+            }
+
+            "OP_INSPECTOUTASSETCOUNT" | "OP_INSPECTINASSETCOUNT" => {
+                let index = self.pop_index()?;
+                let assets = self.assets_at(op, index);
+                self.push_num(assets.len() as i64);
+            }
+            "OP_INSPECTOUTASSETAT" | "OP_INSPECTINASSETAT" => {
+                let t = self.pop_index()? as usize;
+                let index = self.pop_index()?;
+                let assets = self.assets_at(op, index);
+                let (id, amount) = assets.get(t).ok_or("asset index out of range")?;
+                self.push_asset_id(id);
+                self.push_le64(*amount);
+            }
+            "OP_INSPECTOUTASSETLOOKUP" | "OP_INSPECTINASSETLOOKUP" => {
+                let id = self.pop_asset_id()?;
+                let index = self.pop_index()?;
+                let assets = self.assets_at(op, index);
+                match assets.iter().find(|(asset, _)| *asset == id) {
+                    Some((_, amount)) => self.push_le64(*amount),
+                    None => self.push_num(-1),
+                }
+            }
+
+            _ => return Ok(None),
+        }
+        Ok(Some(()))
+    }
+
+    /// Assets on an output (`OP_INSPECTOUT*`) or input (`OP_INSPECTIN*`).
+    fn assets_at(&self, op: &str, index: u32) -> Vec<(AssetId, u64)> {
+        if op.starts_with("OP_INSPECTOUT") {
+            self.tx.output_assets(index)
+        } else {
+            self.tx.input_assets(index)
+        }
+    }
+
+    /// BIP 65 `OP_CHECKLOCKTIMEVERIFY` against the mock transaction.
+    fn check_locktime(&self, lock: i64) -> Result<(), String> {
+        let locktime = self.tx.locktime as i64;
+        if lock < 0 {
+            return Err("negative locktime".to_string());
+        }
+        if (lock < LOCKTIME_THRESHOLD) != (locktime < LOCKTIME_THRESHOLD) {
+            return Err(format!(
+                "locktime {} and transaction locktime {} use different units",
+                lock, locktime
+            ));
+        }
+        if lock > locktime {
+            return Err(format!(
+                "locktime {} not reached (transaction locktime {})",
+                lock, locktime
+            ));
+        }
+        if self.tx.input(self.tx.current_input as i64)?.sequence == context::SEQUENCE_FINAL {
+            return Err("input sequence is final".to_string());
+        }
+        Ok(())
+    }
+
+    /// BIP 112 `OP_CHECKSEQUENCEVERIFY` against the mock transaction.
+    fn check_sequence(&self, lock: i64) -> Result<(), String> {
+        if lock < 0 {
+            return Err("negative sequence".to_string());
+        }
+        if lock & SEQUENCE_DISABLE_FLAG != 0 {
+            return Ok(());
+        }
+        if self.tx.version < 2 {
+            return Err("relative timelocks need transaction version 2".to_string());
+        }
+        let sequence = self.tx.input(self.tx.current_input as i64)?.sequence as i64;
+        if sequence & SEQUENCE_DISABLE_FLAG != 0 {
+            return Err("input sequence disables relative timelocks".to_string());
+        }
+        if (lock & SEQUENCE_TYPE_FLAG) != (sequence & SEQUENCE_TYPE_FLAG) {
+            return Err(format!(
+                "sequence {} and input sequence {} use different units",
+                lock, sequence
+            ));
+        }
+        if lock & SEQUENCE_MASK > sequence & SEQUENCE_MASK {
+            return Err(format!(
+                "relative timelock {} not reached (input sequence {})",
+                lock & SEQUENCE_MASK,
+                sequence & SEQUENCE_MASK
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Apply a numeric comparison opcode (without any `64` suffix).
+fn compare(op: &str, a: i64, b: i64) -> bool {
+    match op {
+        "OP_NUMEQUAL" | "OP_NUMEQUALVERIFY" => a == b,
+        "OP_NUMNOTEQUAL" => a != b,
+        "OP_LESSTHAN" => a < b,
+        "OP_LESSTHANOREQUAL" => a <= b,
+        "OP_GREATERTHAN" => a > b,
+        _ => a >= b,
+    }
+}
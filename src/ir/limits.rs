@@ -0,0 +1,89 @@
+//! Script standardness limits.
+//!
+//! Tapscript itself drops most of the legacy script limits, but relays and
+//! signers built on the legacy rules still reject leaves beyond them, and a
+//! loop unrolled over a long array or a wide multisig gets there quickly.
+//! [`ScriptLimits`] holds those limits, and [`ScriptLimits::check`] fails on
+//! the first one a generated leaf exceeds.
+
+use super::Instr;
+use alloc::{format, string::String};
+
+/// Limits every generated leaf must stay within.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptLimits {
+    /// Most bytes of serialized script
+    pub max_script_size: usize,
+    /// Most bytes of data a single push may put on the stack
+    pub max_element_size: usize,
+    /// Most opcodes other than pushes
+    pub max_opcodes: usize,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        ScriptLimits {
+            max_script_size: 10_000,
+            max_element_size: 520,
+            max_opcodes: 201,
+        }
+    }
+}
+
+impl ScriptLimits {
+    /// No limits at all.
+    pub const NONE: ScriptLimits = ScriptLimits {
+        max_script_size: usize::MAX,
+        max_element_size: usize::MAX,
+        max_opcodes: usize::MAX,
+    };
+
+    /// Check a leaf of `script_len` serialized bytes, naming the first limit
+    /// it exceeds.
+    pub fn check(&self, instrs: &[Instr], script_len: usize) -> Result<(), String> {
+        if script_len > self.max_script_size {
+            return Err(format!(
+                "script is {} bytes, more than the standard limit of {}",
+                script_len, self.max_script_size
+            ));
+        }
+        if let Some(size) = instrs
+            .iter()
+            .filter_map(element_size)
+            .find(|size| *size > self.max_element_size)
+        {
+            return Err(format!(
+                "pushes a {}-byte element, more than the standard limit of {}",
+                size, self.max_element_size
+            ));
+        }
+        let opcodes = instrs.iter().filter(|i| is_counted(i)).count();
+        if opcodes > self.max_opcodes {
+            return Err(format!(
+                "has {} opcodes, more than the standard limit of {}",
+                opcodes, self.max_opcodes
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Bytes of the data a literal push puts on the stack.
+fn element_size(instr: &Instr) -> Option<usize> {
+    match instr {
+        Instr::Raw(token) => token.strip_prefix("0x").map(|hex| hex.len() / 2),
+        _ => None,
+    }
+}
+
+/// Whether an instruction counts as an opcode: any but the pushes of
+/// small numbers, as in the legacy limit.
+fn is_counted(instr: &Instr) -> bool {
+    match instr {
+        Instr::Op(op) => {
+            !matches!(op.as_str(), "OP_0" | "OP_FALSE" | "OP_TRUE" | "OP_1NEGATE")
+                && super::small_int(op).is_none()
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,889 @@
+//! Leaf-level intermediate representation and optimizer.
+//!
+//! The code generator emits every tapscript leaf as a flat list of ASM tokens.
+//! This module lifts those tokens into typed instructions with known stack
+//! effects, runs optimization passes over them and lowers the result back to
+//! ASM. Passes only reason about straight-line basic blocks (split at
+//! `OP_IF`/`OP_NOTIF`/`OP_ELSE`/`OP_ENDIF`) and treat any opcode with an
+//! unknown stack effect as a barrier, so they never change what a leaf
+//! computes — only how many bytes it takes to compute it.
+//!
+//! Passes, in pipeline order:
+//!
+//! 1. **Copy propagation** — `let` bindings are forwarded into their uses
+//!    instead of being left on the stack and referenced as `<name>`.
+//! 2. **Constant folding** — 64-bit arithmetic on two literals, such as
+//!    `144 2 OP_MUL64 OP_VERIFY`, is replaced with its result, and adding or
+//!    subtracting `0` or multiplying by `1` is dropped. Division, which
+//!    leaves a remainder under its quotient, and anything that overflows are
+//!    left for the script to compute.
+//! 3. **Value numbering** — every value pushed in a block is numbered by the
+//!    opcode that produced it and the numbers of its operands. When the same
+//!    introspection read is computed more than once in a block, the first
+//!    result is copied to the alt stack and later reads are replaced with
+//!    `OP_FROMALTSTACK`, provided this saves bytes.
+//! 4. **Peephole** — adjacent instructions that undo each other
+//!    (`OP_DUP OP_DROP`, `OP_TOALTSTACK OP_FROMALTSTACK`, and a script number
+//!    converted to 64 bits and straight back) are removed, and a check
+//!    followed by `OP_VERIFY` becomes its `VERIFY` form, such as
+//!    `OP_EQUALVERIFY`. [`optimize_peephole`] runs this pass alone.
+
+use alloc::collections::BTreeMap;
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::ops::Range;
+
+pub mod limits;
+pub mod resources;
+
+use crate::encoding;
+use crate::opcodes::{
+    OP_0, OP_ADD64, OP_CHECKSIG, OP_CHECKSIGFROMSTACK, OP_CHECKSIGFROMSTACKVERIFY,
+    OP_CHECKSIGVERIFY, OP_DROP, OP_DUP, OP_ELSE, OP_ENDIF, OP_EQUAL, OP_EQUALVERIFY, OP_FALSE,
+    OP_FROMALTSTACK, OP_IF, OP_LE64TOSCRIPTNUM, OP_MUL64, OP_NOTIF, OP_NUMEQUAL, OP_NUMEQUALVERIFY,
+    OP_SCRIPTNUMTOLE64, OP_SUB64, OP_TOALTSTACK, OP_VERIFY,
+};
+
+/// Script bytes assumed for a placeholder with no known push size: a
+/// 32-byte element plus its push opcode.
+const DEFAULT_PLACEHOLDER_SIZE: usize = 33;
+
+/// Script bytes of a `<VTXO:...>` placeholder: a 34-byte P2TR scriptPubKey
+/// plus its push opcode.
+const VTXO_PLACEHOLDER_SIZE: usize = 35;
+
+/// A single lifted ASM instruction.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Instr {
+    /// An opcode such as `OP_CHECKSIG`
+    Op(String),
+    /// A numeric literal push such as `144`
+    Num(i64),
+    /// A `<name>` placeholder, resolved when the contract is instantiated
+    /// (constructor arguments) or spent (witness elements)
+    Placeholder(String),
+    /// Any other token, carried through verbatim
+    Raw(String),
+}
+
+impl Instr {
+    /// Lift a single ASM token.
+    pub fn parse(token: &str) -> Instr {
+        if token.starts_with("OP_") {
+            Instr::Op(token.to_string())
+        } else if let Some(name) = token.strip_prefix('<').and_then(|t| t.strip_suffix('>')) {
+            Instr::Placeholder(name.to_string())
+        } else if let Ok(n) = token.parse::<i64>() {
+            Instr::Num(n)
+        } else {
+            Instr::Raw(token.to_string())
+        }
+    }
+
+    /// Lower back to an ASM token.
+    pub fn to_asm(&self) -> String {
+        match self {
+            Instr::Op(op) => op.clone(),
+            Instr::Num(n) => n.to_string(),
+            Instr::Placeholder(name) => format!("<{}>", name),
+            Instr::Raw(token) => token.clone(),
+        }
+    }
+
+    fn is_op(&self, op: &str) -> bool {
+        matches!(self, Instr::Op(o) if o == op)
+    }
+}
+
+/// Lift ASM tokens into instructions.
+pub fn lift(asm: &[String]) -> Vec<Instr> {
+    asm.iter().map(|t| Instr::parse(t)).collect()
+}
+
+/// Lower instructions back into ASM tokens.
+pub fn lower(instrs: &[Instr]) -> Vec<String> {
+    instrs.iter().map(Instr::to_asm).collect()
+}
+
+/// A top-level `let` binding and the instructions that compute its value.
+///
+/// The compiler names the value with a `=name` token right after them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalBinding {
+    pub name: String,
+    pub range: Range<usize>,
+}
+
+/// Move the bindings from `at` on by one instruction, after one was
+/// inserted there.
+pub(crate) fn shift_locals(locals: &mut [LocalBinding], at: usize) {
+    for local in locals.iter_mut().filter(|l| l.range.start >= at) {
+        local.range = local.range.start + 1..local.range.end + 1;
+    }
+}
+
+/// Facts about a leaf that the optimizer cannot recover from the ASM alone.
+#[derive(Debug, Clone, Default)]
+pub struct LeafContext {
+    /// Placeholders supplied by the spender. They occupy no script bytes and
+    /// reads that depend on them are never rewritten.
+    pub witness: Vec<String>,
+    /// Script bytes (push opcode included) of placeholders baked into the leaf
+    pub push_sizes: BTreeMap<String, usize>,
+    /// Top-level `let` bindings eligible for copy propagation
+    pub locals: Vec<LocalBinding>,
+}
+
+/// The result of optimizing one leaf.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptimizedLeaf {
+    pub asm: Vec<String>,
+    pub size_before: usize,
+    pub size_after: usize,
+}
+
+/// Run the full optimization pipeline over one leaf.
+pub fn optimize(asm: &[String], ctx: &LeafContext) -> OptimizedLeaf {
+    let instrs = lift(asm);
+    let size_before = script_size(&instrs, ctx);
+
+    let instrs = propagate_copies(&instrs, &ctx.locals);
+    let instrs = fold_constants(&instrs);
+    let instrs = dedup_reads(&instrs, ctx);
+    let instrs = peephole(&instrs);
+
+    OptimizedLeaf {
+        size_after: script_size(&instrs, ctx),
+        asm: lower(&instrs),
+        size_before,
+    }
+}
+
+/// Run only the peephole pass over one leaf.
+pub fn optimize_peephole(asm: &[String], ctx: &LeafContext) -> OptimizedLeaf {
+    let instrs = lift(asm);
+    let size_before = script_size(&instrs, ctx);
+    let instrs = peephole(&instrs);
+
+    OptimizedLeaf {
+        size_after: script_size(&instrs, ctx),
+        asm: lower(&instrs),
+        size_before,
+    }
+}
+
+/// Estimate the serialized script size of a leaf in bytes.
+///
+/// Witness placeholders are not part of the script and count as zero.
+pub fn script_size(instrs: &[Instr], ctx: &LeafContext) -> usize {
+    instrs.iter().map(|i| instr_size(i, ctx)).sum()
+}
+
+fn instr_size(instr: &Instr, ctx: &LeafContext) -> usize {
+    match instr {
+        Instr::Op(_) => 1,
+        // Hex data is pushed whole; anything else is taken for one opcode
+        Instr::Raw(token) => match token.strip_prefix("0x") {
+            Some(hex) => push_data_size(hex.len() / 2),
+            None => 1,
+        },
+        Instr::Num(n) => num_push_size(*n),
+        Instr::Placeholder(name) => {
+            if ctx.witness.iter().any(|w| w == name) {
+                0
+            } else if let Some(size) = ctx.push_sizes.get(name) {
+                *size
+            } else if name.starts_with("VTXO:") {
+                VTXO_PLACEHOLDER_SIZE
+            } else {
+                DEFAULT_PLACEHOLDER_SIZE
+            }
+        }
+    }
+}
+
+/// Size of the smallest push of `len` bytes of data.
+fn push_data_size(len: usize) -> usize {
+    match len {
+        0..=75 => 1 + len,
+        76..=0xff => 2 + len,
+        0x100..=0xffff => 3 + len,
+        _ => 5 + len,
+    }
+}
+
+/// Size of the minimal push for a script number.
+fn num_push_size(n: i64) -> usize {
+    if (-1..=16).contains(&n) {
+        return 1;
+    }
+    let mut magnitude = n.unsigned_abs();
+    let mut len = 0;
+    while magnitude > 0 {
+        len += 1;
+        magnitude >>= 8;
+    }
+    // A sign bit that collides with the top magnitude bit needs an extra byte
+    if (n.unsigned_abs() >> (len * 8 - 1)) & 1 == 1 {
+        len += 1;
+    }
+    1 + len
+}
+
+// ─── Stack Effects ─────────────────────────────────────────────────────────────
+
+/// Does this instruction end the current basic block?
+fn is_block_boundary(instr: &Instr) -> bool {
+    [OP_IF, OP_NOTIF, OP_ELSE, OP_ENDIF]
+        .iter()
+        .any(|op| instr.is_op(op))
+}
+
+/// `(pops, pushes)` of the instruction at `i`, or `None` when unknown.
+///
+/// The asset group opcodes take a trailing `source` operand that decides how
+/// many values they push, so the preceding instruction is consulted.
+pub(crate) fn stack_effect(instrs: &[Instr], i: usize) -> Option<(usize, usize)> {
+    let op = match &instrs[i] {
+        Instr::Num(_) | Instr::Placeholder(_) => return Some((0, 1)),
+        // Data pushes, such as u64le literals
+        Instr::Raw(token) if token.starts_with("0x") => return Some((0, 1)),
+        Instr::Raw(_) => return None,
+        Instr::Op(op) => op.as_str(),
+    };
+
+    let effect = match op {
+        "OP_0" | "OP_FALSE" | "OP_TRUE" | "OP_1NEGATE" => (0, 1),
+        _ if small_int(op).is_some() => (0, 1),
+
+        // Introspection without operands
+        "OP_PUSHCURRENTINPUTINDEX"
+        | "OP_INSPECTVERSION"
+        | "OP_INSPECTLOCKTIME"
+        | "OP_INSPECTNUMINPUTS"
+        | "OP_INSPECTNUMOUTPUTS"
+        | "OP_TXWEIGHT"
+        | "OP_TXHASH"
+        | "OP_INSPECTNUMASSETGROUPS" => (0, 1),
+
+        // Introspection indexed by a single operand
+        "OP_INSPECTINPUTVALUE"
+        | "OP_INSPECTINPUTSCRIPTPUBKEY"
+        | "OP_INSPECTINPUTSEQUENCE"
+        | "OP_INSPECTOUTPUTVALUE"
+        | "OP_INSPECTOUTPUTSCRIPTPUBKEY"
+        | "OP_INSPECTOUTPUTNONCE"
+        | "OP_INSPECTINASSETCOUNT"
+        | "OP_INSPECTOUTASSETCOUNT"
+        | "OP_INSPECTASSETGROUPMETADATAHASH" => (1, 1),
+        "OP_INSPECTASSETGROUPASSETID" => (1, 2),
+        "OP_FINDASSETGROUPBYASSETID" => (2, 1),
+        "OP_INSPECTINASSETAT" | "OP_INSPECTOUTASSETAT" => (2, 3),
+        "OP_INSPECTINASSETLOOKUP" | "OP_INSPECTOUTASSETLOOKUP" => (3, 1),
+        "OP_INSPECTASSETGROUPSUM" | "OP_INSPECTASSETGROUPNUM" => {
+            let source = i.checked_sub(1).and_then(|p| match &instrs[p] {
+                Instr::Num(n) => Some(*n),
+                Instr::Op(o) if o == OP_0 || o == OP_FALSE => Some(0),
+                Instr::Op(o) => small_int(o),
+                _ => None,
+            })?;
+            match source {
+                0 | 1 => (2, 1),
+                2 => (2, 2),
+                _ => return None,
+            }
+        }
+
+        // Signatures
+        "OP_CHECKSIG" => (2, 1),
+        "OP_CHECKSIGVERIFY" => (2, 0),
+        "OP_CHECKSIGADD" => (3, 1),
+        "OP_CHECKSIGFROMSTACK" => (3, 1),
+        "OP_CHECKSIGFROMSTACKVERIFY" => (3, 0),
+
+        // Timelocks leave their operand on the stack
+        "OP_CHECKLOCKTIMEVERIFY" | "OP_CHECKSEQUENCEVERIFY" => (1, 1),
+
+        // Comparisons and logic
+        "OP_EQUAL"
+        | "OP_NUMEQUAL"
+        | "OP_LESSTHAN"
+        | "OP_LESSTHANOREQUAL"
+        | "OP_GREATERTHAN"
+        | "OP_GREATERTHANOREQUAL"
+        | "OP_LESSTHAN64"
+        | "OP_LESSTHANOREQUAL64"
+        | "OP_GREATERTHAN64"
+        | "OP_GREATERTHANOREQUAL64"
+        | "OP_BOOLAND"
+        | "OP_BOOLOR"
+        | "OP_ADD"
+        | "OP_SUB" => (2, 1),
+        "OP_EQUALVERIFY" | "OP_NUMEQUALVERIFY" => (2, 0),
+        "OP_NOT" | "OP_0NOTEQUAL" => (1, 1),
+        // Pushes the length of the top element, leaving it in place
+        "OP_SIZE" => (1, 2),
+        "OP_VERIFY" | "OP_DROP" => (1, 0),
+        "OP_NIP" | "OP_CAT" => (2, 1),
+        "OP_WITHIN" => (3, 1),
+
+        // Hashing
+        "OP_SHA256" | "OP_HASH160" | "OP_HASH256" | "OP_RIPEMD160" => (1, 1),
+        "OP_SHA256INITIALIZE" => (1, 1),
+        "OP_SHA256UPDATE" | "OP_SHA256FINALIZE" => (2, 1),
+
+        // 64-bit arithmetic pushes a success flag above the result
+        "OP_ADD64" | "OP_SUB64" | "OP_MUL64" => (2, 2),
+        "OP_DIV64" => (2, 3),
+        "OP_NEG64" => (1, 2),
+        "OP_SCRIPTNUMTOLE64" | "OP_LE64TOSCRIPTNUM" | "OP_LE32TOLE64" => (1, 1),
+
+        // Elliptic curve checks
+        "OP_ECMULSCALARVERIFY" | "OP_TWEAKVERIFY" => (3, 0),
+
+        _ => return None,
+    };
+    Some(effect)
+}
+
+/// `OP_1`..`OP_16` as a number.
+pub(crate) fn small_int(op: &str) -> Option<i64> {
+    op.strip_prefix("OP_")
+        .and_then(|n| n.parse::<i64>().ok())
+        .filter(|n| (1..=16).contains(n))
+}
+
+/// Does the instruction read transaction state?
+fn is_introspection(instr: &Instr) -> bool {
+    match instr {
+        Instr::Op(op) => {
+            op.starts_with("OP_INSPECT")
+                || op == "OP_PUSHCURRENTINPUTINDEX"
+                || op == "OP_FINDASSETGROUPBYASSETID"
+                || op == "OP_TXWEIGHT"
+                || op == "OP_TXHASH"
+        }
+        _ => false,
+    }
+}
+
+/// Does `instrs` push exactly one value without touching anything below it?
+fn produces_single_value(instrs: &[Instr]) -> bool {
+    let mut depth = 0usize;
+    for i in 0..instrs.len() {
+        if instrs[i].is_op(OP_DUP) {
+            // A DUP on an empty relative stack would read below the range
+            if depth == 0 {
+                return false;
+            }
+            depth += 1;
+            continue;
+        }
+        if is_block_boundary(&instrs[i]) {
+            return false;
+        }
+        match stack_effect(instrs, i) {
+            Some((pops, pushes)) if pops <= depth => depth = depth - pops + pushes,
+            _ => return false,
+        }
+    }
+    depth == 1
+}
+
+// ─── Copy Propagation ──────────────────────────────────────────────────────────
+
+/// Forward `let` bindings into their uses.
+///
+/// A binding's instructions are removed from where it was declared, with the
+/// token that names their value, and spliced in at every `<name>` that
+/// refers to it. Bindings whose value is not a self-contained single push,
+/// or that are referenced before they are declared, are left alone.
+fn propagate_copies(instrs: &[Instr], locals: &[LocalBinding]) -> Vec<Instr> {
+    let eligible: Vec<&LocalBinding> = locals
+        .iter()
+        .filter(|l| l.range.end <= instrs.len() && !l.range.is_empty())
+        .filter(|l| produces_single_value(&instrs[l.range.clone()]))
+        .filter(|l| {
+            !instrs[..l.range.start]
+                .iter()
+                .any(|i| matches!(i, Instr::Placeholder(n) if n == &l.name))
+        })
+        .collect();
+
+    let mut defs: BTreeMap<&str, Vec<Instr>> = BTreeMap::new();
+    let mut out = Vec::with_capacity(instrs.len());
+    let mut i = 0;
+    while i < instrs.len() {
+        if let Some(local) = eligible.iter().find(|l| l.range.start == i) {
+            let value = splice_defs(&instrs[local.range.clone()], &defs);
+            defs.insert(local.name.as_str(), value);
+            i = local.range.end;
+            if matches!(&instrs.get(i), Some(Instr::Raw(t)) if t.strip_prefix('=') == Some(&local.name))
+            {
+                i += 1;
+            }
+            continue;
+        }
+        out.extend(splice_defs(&instrs[i..=i], &defs));
+        i += 1;
+    }
+    out
+}
+
+fn splice_defs(instrs: &[Instr], defs: &BTreeMap<&str, Vec<Instr>>) -> Vec<Instr> {
+    let mut out = Vec::with_capacity(instrs.len());
+    for instr in instrs {
+        match instr {
+            Instr::Placeholder(name) if defs.contains_key(name.as_str()) => {
+                out.extend(defs[name.as_str()].iter().cloned());
+            }
+            _ => out.push(instr.clone()),
+        }
+    }
+    out
+}
+
+// ─── Constant Folding ──────────────────────────────────────────────────────────
+
+/// Evaluate 64-bit arithmetic on u64le literals and drop arithmetic
+/// identities. The 64-bit opcodes take 8-byte operands, so script number
+/// pushes are never folded, and a folded result is an 8-byte push too.
+/// A literal converted between the two encodings is pushed converted.
+///
+/// Each operation is folded as it is reached, so a chain such as
+/// `144 * 2 * 3` collapses into a single literal.
+fn fold_constants(instrs: &[Instr]) -> Vec<Instr> {
+    let mut out: Vec<Instr> = Vec::with_capacity(instrs.len());
+    for instr in instrs {
+        if let Some(converted) = out.last().and_then(|value| convert(value, instr)) {
+            out.pop();
+            out.push(converted);
+            continue;
+        }
+        out.push(instr.clone());
+        // Every 64-bit operation is followed by a check of its success flag
+        let n = out.len();
+        if n < 4 || !out[n - 1].is_op(OP_VERIFY) {
+            continue;
+        }
+        let op = match &out[n - 2] {
+            Instr::Op(op) if op == OP_ADD64 || op == OP_SUB64 || op == OP_MUL64 => op.clone(),
+            _ => continue,
+        };
+        let identity = if op == OP_MUL64 { 1 } else { 0 };
+        let right = constant(&out[n - 3]);
+
+        if let (Some(left), Some(right)) = (constant(&out[n - 4]), right) {
+            let folded = match op.as_str() {
+                OP_ADD64 => left.checked_add(right),
+                OP_SUB64 => left.checked_sub(right),
+                _ => left.checked_mul(right),
+            };
+            if let Some(value) = folded {
+                out.truncate(n - 4);
+                out.push(Instr::Raw(encoding::le64_push(value)));
+            }
+        } else if right == Some(identity) {
+            out.truncate(n - 3);
+        } else if op != OP_SUB64 {
+            // The left operand is the identity: it is pushed just before
+            // the computation of the right one
+            let start = (0..n - 2)
+                .rev()
+                .find(|&j| produces_single_value(&out[j..n - 2]));
+            if let Some(start) = start.filter(|&j| j > 0) {
+                if constant(&out[start - 1]) == Some(identity) {
+                    out.truncate(n - 2);
+                    out.remove(start - 1);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// The literal `value` converted by `op`, when `op` converts between script
+/// numbers and u64le and the value is in the 4-byte range both convert.
+fn convert(value: &Instr, op: &Instr) -> Option<Instr> {
+    let fits = |n: i64| n.unsigned_abs() <= i32::MAX as u64;
+    match value {
+        Instr::Num(n) if op.is_op(OP_SCRIPTNUMTOLE64) && fits(*n) => {
+            Some(Instr::Raw(encoding::le64_push(*n)))
+        }
+        _ if op.is_op(OP_LE64TOSCRIPTNUM) => constant(value).filter(|n| fits(*n)).map(Instr::Num),
+        _ => None,
+    }
+}
+
+/// The number an 8-byte u64le literal push puts on the stack.
+fn constant(instr: &Instr) -> Option<i64> {
+    let Instr::Raw(token) = instr else {
+        return None;
+    };
+    let bytes = hex::decode(token.strip_prefix("0x")?).ok()?;
+    Some(i64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+// ─── Peephole ──────────────────────────────────────────────────────────────────
+
+/// Checks with a `VERIFY` form that does the same in one opcode.
+const VERIFY_FORMS: [(&str, &str); 4] = [
+    (OP_EQUAL, OP_EQUALVERIFY),
+    (OP_NUMEQUAL, OP_NUMEQUALVERIFY),
+    (OP_CHECKSIG, OP_CHECKSIGVERIFY),
+    (OP_CHECKSIGFROMSTACK, OP_CHECKSIGFROMSTACKVERIFY),
+];
+
+/// The `VERIFY` form of a check, if it has one.
+pub(crate) fn verify_form(op: &str) -> Option<&'static str> {
+    VERIFY_FORMS
+        .iter()
+        .find(|(check, _)| *check == op)
+        .map(|(_, verify)| *verify)
+}
+
+/// Pairs of adjacent opcodes that leave the stacks as they found them.
+const NO_OPS: [(&str, &str); 3] = [
+    (OP_DUP, OP_DROP),
+    (OP_TOALTSTACK, OP_FROMALTSTACK),
+    (OP_SCRIPTNUMTOLE64, OP_LE64TOSCRIPTNUM),
+];
+
+/// Remove and merge redundant adjacent instructions.
+///
+/// Each instruction is matched against the one before it once that one has
+/// been rewritten, so removing a pair can expose another around it.
+fn peephole(instrs: &[Instr]) -> Vec<Instr> {
+    let mut out: Vec<Instr> = Vec::with_capacity(instrs.len());
+    for instr in instrs {
+        let Some(Instr::Op(previous)) = out.last() else {
+            out.push(instr.clone());
+            continue;
+        };
+        if NO_OPS
+            .iter()
+            .any(|(first, second)| previous == first && instr.is_op(second))
+        {
+            out.pop();
+        } else if let Some((_, verify)) = VERIFY_FORMS
+            .iter()
+            .find(|(check, _)| previous == check && instr.is_op(OP_VERIFY))
+        {
+            out.pop();
+            out.push(Instr::Op(verify.to_string()));
+        } else {
+            out.push(instr.clone());
+        }
+    }
+    out
+}
+
+// ─── Value Numbering ───────────────────────────────────────────────────────────
+
+/// A value on the symbolic stack.
+#[derive(Debug, Clone)]
+struct Slot {
+    id: usize,
+    /// Value number, `None` when the value depends on something unknown
+    vn: Option<usize>,
+    /// First instruction of the contiguous range that computes this value
+    start: Option<usize>,
+    /// Last instruction of that range, extended over checks on the value
+    end: usize,
+    /// The value this one was copied or derived from via `OP_DUP`
+    base: Option<usize>,
+    /// Computed only from literals
+    constant: bool,
+}
+
+/// A self-contained computation of one value.
+#[derive(Debug, Clone)]
+struct Occurrence {
+    vn: usize,
+    block: usize,
+    range: Range<usize>,
+}
+
+/// Number every value in the leaf and collect self-contained computations.
+fn number_values(instrs: &[Instr]) -> Vec<Occurrence> {
+    let mut table: BTreeMap<(String, Vec<usize>, usize), usize> = BTreeMap::new();
+    let mut occurrences = Vec::new();
+    let mut stack: Vec<Slot> = Vec::new();
+    let mut block = 0;
+    let mut next_id = 0;
+
+    let retire = |slot: &Slot, block: usize, occurrences: &mut Vec<Occurrence>| {
+        if let (Some(vn), Some(start), None) = (slot.vn, slot.start, slot.base) {
+            occurrences.push(Occurrence {
+                vn,
+                block,
+                range: start..slot.end + 1,
+            });
+        }
+    };
+
+    for i in 0..instrs.len() {
+        let effect = stack_effect(instrs, i);
+        if is_block_boundary(&instrs[i]) || (effect.is_none() && !instrs[i].is_op(OP_DUP)) {
+            for slot in stack.drain(..) {
+                retire(&slot, block, &mut occurrences);
+            }
+            block += 1;
+            continue;
+        }
+
+        if instrs[i].is_op(OP_DUP) {
+            let copy = match stack.last() {
+                Some(top) => Slot {
+                    id: next_id,
+                    vn: top.vn,
+                    start: Some(i),
+                    end: i,
+                    base: Some(top.id),
+                    constant: top.constant,
+                },
+                None => unknown_slot(next_id, i),
+            };
+            next_id += 1;
+            stack.push(copy);
+            continue;
+        }
+
+        let (pops, pushes) = effect.unwrap_or((0, 0));
+        let split = stack.len().saturating_sub(pops);
+        let inputs: Vec<Slot> = stack.drain(split..).collect();
+        let underflow = inputs.len() < pops;
+        for slot in &inputs {
+            retire(slot, block, &mut occurrences);
+        }
+
+        let vns: Option<Vec<usize>> = if underflow {
+            None
+        } else {
+            inputs.iter().map(|s| s.vn).collect()
+        };
+        let contiguous = !underflow
+            && inputs.iter().all(|s| s.start.is_some())
+            && inputs
+                .windows(2)
+                .all(|w| w[0].end + 1 == w[1].start.unwrap())
+            && inputs.last().is_none_or(|s| s.end + 1 == i);
+        let bases: Vec<usize> = inputs.iter().filter_map(|s| s.base).collect();
+        let derived_from = match bases.first() {
+            Some(b) if bases.iter().all(|x| x == b) => Some(*b),
+            _ => None,
+        };
+        let mixed = inputs.iter().any(|s| s.base.is_none() && !s.constant) && !bases.is_empty();
+        let constant = !underflow
+            && inputs.iter().all(|s| s.constant)
+            && !matches!(instrs[i], Instr::Placeholder(_))
+            && !is_introspection(&instrs[i]);
+
+        if pushes == 0 {
+            // A check that consumes only values derived from the slot now on
+            // top belongs to that slot's computation.
+            if let (Some(b), true, false) = (derived_from, contiguous, mixed) {
+                if let Some(top) = stack.last_mut() {
+                    let first = inputs.first().and_then(|s| s.start);
+                    if top.id == b && first == Some(top.end + 1) {
+                        top.end = i;
+                    }
+                }
+            }
+            continue;
+        }
+
+        for k in 0..pushes {
+            let vn = vns.as_ref().map(|vns| {
+                let key = (instrs[i].to_asm(), vns.clone(), k);
+                let n = table.len();
+                *table.entry(key).or_insert(n)
+            });
+            let start = if pops == 0 {
+                Some(i)
+            } else if contiguous && !mixed && pushes == 1 {
+                inputs[0].start
+            } else {
+                None
+            };
+            stack.push(Slot {
+                id: next_id,
+                vn,
+                start,
+                end: i,
+                base: if pops == 0 { None } else { derived_from },
+                constant,
+            });
+            next_id += 1;
+        }
+    }
+
+    for slot in stack.drain(..) {
+        retire(&slot, block, &mut occurrences);
+    }
+    occurrences
+}
+
+fn unknown_slot(id: usize, i: usize) -> Slot {
+    Slot {
+        id,
+        vn: None,
+        start: None,
+        end: i,
+        base: None,
+        constant: false,
+    }
+}
+
+/// A read computed once and recalled from the alt stack afterwards.
+#[derive(Debug, Clone)]
+struct Reuse {
+    first: Range<usize>,
+    repeats: Vec<Range<usize>>,
+}
+
+impl Reuse {
+    fn ranges(&self) -> impl Iterator<Item = &Range<usize>> {
+        core::iter::once(&self.first).chain(self.repeats.iter())
+    }
+
+    /// Bytes saved by stashing the first read and recalling it later.
+    fn savings(&self, instrs: &[Instr], ctx: &LeafContext) -> isize {
+        let removed: usize = self
+            .repeats
+            .iter()
+            .map(|r| script_size(&instrs[r.clone()], ctx))
+            .sum();
+        // OP_DUP OP_TOALTSTACK once, OP_FROMALTSTACK OP_DUP OP_TOALTSTACK for
+        // every recall but the last, which is a bare OP_FROMALTSTACK.
+        let added = 2 + 3 * (self.repeats.len() - 1) + 1;
+        removed as isize - added as isize
+    }
+}
+
+/// Deduplicate identical introspection reads within each basic block.
+fn dedup_reads(instrs: &[Instr], ctx: &LeafContext) -> Vec<Instr> {
+    let mut groups: BTreeMap<(usize, usize), Vec<Range<usize>>> = BTreeMap::new();
+    for occ in number_values(instrs) {
+        let body = &instrs[occ.range.clone()];
+        let reads_tx = body.iter().any(is_introspection);
+        let reads_witness = body
+            .iter()
+            .any(|i| matches!(i, Instr::Placeholder(n) if ctx.witness.contains(n)));
+        if reads_tx && !reads_witness {
+            groups
+                .entry((occ.block, occ.vn))
+                .or_default()
+                .push(occ.range);
+        }
+    }
+
+    let mut candidates: Vec<Reuse> = groups
+        .into_values()
+        .filter_map(|mut ranges| {
+            ranges.sort_by_key(|r| r.start);
+            let mut kept: Vec<Range<usize>> = Vec::new();
+            for r in ranges {
+                if kept.last().is_none_or(|k| k.end <= r.start) {
+                    kept.push(r);
+                }
+            }
+            let first = kept.remove(0);
+            (!kept.is_empty()).then_some(Reuse {
+                first,
+                repeats: kept,
+            })
+        })
+        .filter(|c| c.savings(instrs, ctx) > 0)
+        .collect();
+    candidates.sort_by_key(|c| (-c.savings(instrs, ctx), c.first.start));
+
+    let mut chosen: Vec<Reuse> = Vec::new();
+    for candidate in candidates {
+        let overlaps = chosen.iter().flat_map(Reuse::ranges).any(|a| {
+            candidate
+                .ranges()
+                .any(|b| a.start < b.end && b.start < a.end)
+        });
+        if overlaps {
+            continue;
+        }
+        chosen.push(candidate);
+        if !alt_stack_balanced(&chosen) {
+            chosen.pop();
+        }
+    }
+
+    if chosen.is_empty() {
+        return instrs.to_vec();
+    }
+
+    let mut out = Vec::with_capacity(instrs.len());
+    let mut i = 0;
+    while i < instrs.len() {
+        let recall = chosen.iter().find_map(|c| {
+            let pos = c.repeats.iter().position(|r| r.start == i)?;
+            Some((c.repeats[pos].end, pos + 1 == c.repeats.len()))
+        });
+        if let Some((end, last)) = recall {
+            out.push(Instr::Op(OP_FROMALTSTACK.to_string()));
+            if !last {
+                out.push(Instr::Op(OP_DUP.to_string()));
+                out.push(Instr::Op(OP_TOALTSTACK.to_string()));
+            }
+            i = end;
+            continue;
+        }
+        out.push(instrs[i].clone());
+        if chosen.iter().any(|c| c.first.end == i + 1) {
+            out.push(Instr::Op(OP_DUP.to_string()));
+            out.push(Instr::Op(OP_TOALTSTACK.to_string()));
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Check that the alt stack pushes and recalls of `reuses` nest correctly.
+fn alt_stack_balanced(reuses: &[Reuse]) -> bool {
+    // (position, reuse index, is stash)
+    let mut events: Vec<(usize, usize, bool)> = Vec::new();
+    for (n, r) in reuses.iter().enumerate() {
+        events.push((r.first.end - 1, n, true));
+        for repeat in &r.repeats {
+            events.push((repeat.start, n, false));
+        }
+    }
+    events.sort();
+
+    let mut alt: Vec<usize> = Vec::new();
+    let mut remaining: Vec<usize> = reuses.iter().map(|r| r.repeats.len()).collect();
+    for (_, n, stash) in events {
+        if stash {
+            alt.push(n);
+            continue;
+        }
+        if alt.last() != Some(&n) {
+            return false;
+        }
+        remaining[n] -= 1;
+        if remaining[n] == 0 {
+            alt.pop();
+        }
+    }
+    alt.is_empty()
+}
+
+/// Placeholder push sizes for constructor parameters of the given types.
+pub fn push_size_for_type(param_type: &str) -> usize {
+    match param_type {
+        "pubkey" | "bytes32" => 33,
+        "bytes20" => 21,
+        "bool" => 1,
+        "int" => 5,
+        _ => DEFAULT_PLACEHOLDER_SIZE,
+    }
+}
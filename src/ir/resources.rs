@@ -0,0 +1,133 @@
+//! Per-leaf resource accounting.
+//!
+//! Relayers bound how much work a single tapscript leaf may do. This module
+//! counts the expensive operations of a leaf along its most expensive path
+//! and checks the counts against a [`ResourceBudget`]. Each category is
+//! maximized independently over `OP_IF`/`OP_ELSE` branches, so a leaf stays
+//! within budget exactly when every path through it does.
+
+use super::Instr;
+use crate::models::ResourceCounts;
+use crate::opcodes::{
+    OP_ADD64, OP_CHECKMULTISIG, OP_CHECKSIG, OP_CHECKSIGADD, OP_CHECKSIGFROMSTACK,
+    OP_CHECKSIGFROMSTACKVERIFY, OP_CHECKSIGVERIFY, OP_DIV64, OP_ELSE, OP_ENDIF,
+    OP_FINDASSETGROUPBYASSETID, OP_GREATERTHAN64, OP_GREATERTHANOREQUAL64, OP_IF, OP_INPUTBYTECODE,
+    OP_INPUTOUTPOINT, OP_INPUTSEQUENCE, OP_INPUTVALUE, OP_LESSTHAN64, OP_LESSTHANOREQUAL64,
+    OP_MUL64, OP_NEG64, OP_NOTIF, OP_PUSHCURRENTINPUTINDEX, OP_SUB64, OP_TXHASH, OP_TXWEIGHT,
+};
+use alloc::{format, string::String};
+
+const SIGNATURE_OPS: &[&str] = &[
+    OP_CHECKSIG,
+    OP_CHECKSIGVERIFY,
+    OP_CHECKSIGADD,
+    OP_CHECKMULTISIG,
+    OP_CHECKSIGFROMSTACK,
+    OP_CHECKSIGFROMSTACKVERIFY,
+];
+
+/// Introspection opcodes not covered by the `OP_INSPECT` prefix.
+const INTROSPECTION_OPS: &[&str] = &[
+    OP_PUSHCURRENTINPUTINDEX,
+    OP_TXHASH,
+    OP_TXWEIGHT,
+    OP_FINDASSETGROUPBYASSETID,
+    OP_INPUTBYTECODE,
+    OP_INPUTOUTPOINT,
+    OP_INPUTSEQUENCE,
+    OP_INPUTVALUE,
+];
+
+const ARITHMETIC64_OPS: &[&str] = &[
+    OP_ADD64,
+    OP_SUB64,
+    OP_MUL64,
+    OP_DIV64,
+    OP_NEG64,
+    OP_LESSTHAN64,
+    OP_LESSTHANOREQUAL64,
+    OP_GREATERTHAN64,
+    OP_GREATERTHANOREQUAL64,
+];
+
+/// Upper bounds on the operations a single leaf may execute. `None` leaves a
+/// category unbounded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceBudget {
+    pub max_sigops: Option<usize>,
+    pub max_introspection_ops: Option<usize>,
+    pub max_arithmetic64_ops: Option<usize>,
+}
+
+impl ResourceBudget {
+    /// Check a leaf's counts, naming the first category over budget.
+    pub fn check(&self, counts: &ResourceCounts) -> Result<(), String> {
+        let limits = [
+            ("signature operations", counts.sigops, self.max_sigops),
+            (
+                "introspection operations",
+                counts.introspection_ops,
+                self.max_introspection_ops,
+            ),
+            (
+                "64-bit arithmetic operations",
+                counts.arithmetic64_ops,
+                self.max_arithmetic64_ops,
+            ),
+        ];
+        for (what, used, limit) in limits {
+            if let Some(limit) = limit {
+                if used > limit {
+                    return Err(format!("{} {} exceed the budget of {}", used, what, limit));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Count the operations of a leaf along its most expensive path.
+pub fn count(instrs: &[Instr]) -> ResourceCounts {
+    block(instrs, 0).0
+}
+
+/// Count from `pos` up to the `OP_ELSE`/`OP_ENDIF` closing the current
+/// block, returning the counts and the position of that terminator.
+fn block(instrs: &[Instr], mut pos: usize) -> (ResourceCounts, usize) {
+    let mut counts = ResourceCounts::default();
+    while pos < instrs.len() {
+        let instr = &instrs[pos];
+        if instr.is_op(OP_ELSE) || instr.is_op(OP_ENDIF) {
+            break;
+        }
+        if instr.is_op(OP_IF) || instr.is_op(OP_NOTIF) {
+            let (then_counts, end) = block(instrs, pos + 1);
+            let (else_counts, end) = if instrs.get(end).is_some_and(|i| i.is_op(OP_ELSE)) {
+                block(instrs, end + 1)
+            } else {
+                (ResourceCounts::default(), end)
+            };
+            counts.sigops += then_counts.sigops.max(else_counts.sigops);
+            counts.introspection_ops += then_counts
+                .introspection_ops
+                .max(else_counts.introspection_ops);
+            counts.arithmetic64_ops += then_counts
+                .arithmetic64_ops
+                .max(else_counts.arithmetic64_ops);
+            pos = end + 1;
+            continue;
+        }
+        if let Instr::Op(op) = instr {
+            let op = op.as_str();
+            if SIGNATURE_OPS.contains(&op) {
+                counts.sigops += 1;
+            } else if op.starts_with("OP_INSPECT") || INTROSPECTION_OPS.contains(&op) {
+                counts.introspection_ops += 1;
+            } else if ARITHMETIC64_OPS.contains(&op) {
+                counts.arithmetic64_ops += 1;
+            }
+        }
+        pos += 1;
+    }
+    (counts, pos)
+}
@@ -0,0 +1,118 @@
+//! Compatibility with TapLang, the language's former name.
+//!
+//! TapLang sources (`.tap`) are Arkade Script apart from multisig, written
+//! `checkMultisig([keys], [sigs])` with the signatures passed explicitly.
+//! The current parser keeps that call as raw text and compiles it to a
+//! placeholder, so [`translate`] rewrites it into the threshold form before
+//! compiling. TapLang artifacts (`"compiler": { "name": "taplang" }`) lack
+//! the witness schemas, sizes and metrics of current ones and describe
+//! their leaves with `OP_CHECKMULTISIG`, which tapscript disables;
+//! [`upgrade`] recompiles the source they embed into a current artifact.
+//!
+//! `CompileOptions::legacy_taplang` (`arkadec --legacy-taplang`) turns the
+//! translation on, and `arkadec upgrade` upgrades an artifact.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::compiler::{self, CompileOptions};
+use crate::models::{Contract, ContractJson, Expression, Requirement, Statement};
+
+/// Rewrite every `require(checkMultisig([keys], [sigs]))` into
+/// `require(checkMultisig([keys], m))`, `m` being the number of signatures.
+pub fn translate(contract: &mut Contract) -> Result<(), String> {
+    for function in &mut contract.functions {
+        translate_statements(&mut function.statements)
+            .map_err(|e| format!("fn {}: {}", function.name, e))?;
+    }
+    Ok(())
+}
+
+fn translate_statements(statements: &mut [Statement]) -> Result<(), String> {
+    for stmt in statements {
+        match stmt {
+            Statement::Require(req, _) => {
+                if let Requirement::Comparison {
+                    left: Expression::Property(call),
+                    op,
+                    right: Expression::Literal(value),
+                } = req
+                {
+                    if op == "==" && value == "true" {
+                        if let Some(multisig) = signature_array_multisig(call)? {
+                            *req = multisig;
+                        }
+                    }
+                }
+            }
+            Statement::IfElse {
+                then_body,
+                else_body,
+                ..
+            } => {
+                translate_statements(then_body)?;
+                if let Some(else_body) = else_body {
+                    translate_statements(else_body)?;
+                }
+            }
+            Statement::ForIn { body, .. } => translate_statements(body)?,
+            Statement::LetBinding { .. } | Statement::VarAssign { .. } => {}
+        }
+    }
+    Ok(())
+}
+
+/// The threshold multisig `call` stands for, if it is a TapLang
+/// `checkMultisig([keys], [sigs])`.
+fn signature_array_multisig(call: &str) -> Result<Option<Requirement>, String> {
+    let Some(args) = call
+        .trim()
+        .strip_prefix("checkMultisig")
+        .map(str::trim_start)
+        .and_then(|rest| rest.strip_prefix('('))
+        .and_then(|rest| rest.strip_suffix(')'))
+    else {
+        return Ok(None);
+    };
+    let invalid = || format!("cannot translate '{}'", call);
+    let (keys, sigs) = args.split_once(']').ok_or_else(invalid)?;
+    let list = |items: &str| -> Vec<String> {
+        items
+            .trim()
+            .trim_start_matches(',')
+            .trim()
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .split(',')
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect()
+    };
+    let (pubkeys, sigs) = (list(keys), list(sigs));
+    if pubkeys.is_empty() || sigs.is_empty() || sigs.len() > pubkeys.len() {
+        return Err(invalid());
+    }
+    Ok(Some(Requirement::CheckMultisig {
+        pubkeys,
+        threshold: sigs.len() as u16,
+    }))
+}
+
+/// Recompile the source a TapLang artifact embeds, with the TapLang
+/// translation on, into a current artifact.
+pub fn upgrade(artifact: &str, options: &CompileOptions) -> Result<ContractJson, String> {
+    let artifact: serde_json::Value =
+        serde_json::from_str(artifact).map_err(|e| format!("Invalid artifact: {}", e))?;
+    let name = artifact["contractName"].as_str().unwrap_or("the contract");
+    let source = artifact["source"]
+        .as_str()
+        .ok_or_else(|| format!("The artifact of {} has no source to recompile", name))?;
+    let options = CompileOptions {
+        legacy_taplang: true,
+        ..options.clone()
+    };
+    compiler::compile_with_options(source, &options).map_err(String::from)
+}
@@ -1,18 +1,79 @@
+//! The parse and compile path (`parser`, `resolver`, `typechecker`,
+//! `analysis`, `compiler` and the modules they use, `diagnostics` and
+//! `taproot`) only needs `alloc`: built without the default `std`
+//! feature, the crate is `no_std`, so artifacts can be checked against
+//! their source in enclaves and embedded signers. Tooling
+//! around it — the test runner, interpreter, bindings, linker and editor
+//! support — requires `std`.
+//!
+//! Compiling is safe from any number of threads at once: the parse and
+//! compile path keeps no global or static state, the parser is a unit
+//! struct whose grammar is compiled in, and the default
+//! [`parser::ParseLimits`] keep its recursion within the stack of a
+//! spawned thread.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod analysis;
+#[cfg(feature = "testing")]
+pub mod arbitrary;
+#[cfg(feature = "std")]
+pub mod bindings;
+pub mod catalog;
 pub mod compiler;
+pub mod constants;
+pub mod decompile;
+pub mod diagnostics;
+pub mod encoding;
+#[cfg(feature = "std")]
+pub mod examples;
+#[cfg(feature = "std")]
+pub mod formatter;
+pub mod hashes;
+#[cfg(feature = "std")]
+pub mod interpreter;
+pub mod ir;
+pub mod legacy;
+#[cfg(feature = "std")]
+pub mod linker;
 pub mod models;
 pub mod opcodes;
 pub mod parser;
+pub mod printer;
+pub mod resolver;
+#[cfg(feature = "std")]
+pub mod semantic;
+#[cfg(feature = "bitcoin")]
+pub mod spend;
+pub mod taproot;
+pub mod templates;
+#[cfg(feature = "std")]
+pub mod testing;
 pub mod typechecker;
+#[cfg(feature = "std")]
+pub mod vectors;
+#[cfg(feature = "std")]
+pub mod witness;
 
 #[cfg(feature = "wasm")]
 pub mod wasm;
 
+#[cfg(feature = "std")]
+pub use compiler::Compiler;
+pub use compiler::{Clock, CompileOptions};
+pub use ir::limits::ScriptLimits;
+pub use ir::resources::ResourceBudget;
 pub use models::{
-    Contract, ContractJson, Expression, Function, Parameter, Requirement, WitnessElement,
+    CompileError, Contract, ContractDescription, ContractJson, Edition, Expression, Function,
+    LeafMetrics, OptimizationReport, Parameter, Requirement, ResourceCounts, Span, WitnessElement,
     DEFAULT_ARRAY_LENGTH,
 };
 pub use typechecker::{ArkType, TypeError};
 
+use alloc::boxed::Box;
+
 /// Compile Arkade Script source code to a JSON-serializable structure
 ///
 /// This function takes Arkade Script source code as input, parses it into an AST,
@@ -63,9 +124,32 @@ pub use typechecker::{ArkType, TypeError};
 /// let json = serde_json::to_string_pretty(&result.unwrap()).unwrap();
 /// println!("{}", json);
 /// ```
-pub fn compile(source_code: &str) -> Result<ContractJson, Box<dyn std::error::Error>> {
+pub fn compile(source_code: &str) -> Result<ContractJson, Box<dyn core::error::Error>> {
     match compiler::compile(source_code) {
         Ok(output) => Ok(output),
         Err(err) => Err(err.into()),
     }
 }
+
+/// Compile Arkade Script source code with explicit [`CompileOptions`]
+///
+/// Behaves like [`compile`], but lets callers opt into the IR optimizer and
+/// resource budgets. When `options.optimize` is set,
+/// `ContractJson::optimizations` records the script size of every function
+/// variant before and after optimization. Compilation fails if a leaf
+/// exceeds `options.budget`.
+pub fn compile_with_options(
+    source_code: &str,
+    options: &CompileOptions,
+) -> Result<ContractJson, Box<dyn core::error::Error>> {
+    compiler::compile_with_options(source_code, options).map_err(|err| err.into())
+}
+
+/// Describe a contract from its source without compiling it
+///
+/// Only parses: the result lists the contract name, the constructor
+/// parameters and the public functions with their inputs, as declared. Use
+/// it to populate forms while a full [`compile`] is still running.
+pub fn describe(source_code: &str) -> Result<ContractDescription, Box<dyn core::error::Error>> {
+    Ok(parser::parse(source_code)?.describe())
+}
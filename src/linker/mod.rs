@@ -0,0 +1,347 @@
+//! Constructor argument linking.
+//!
+//! An artifact's leaf scripts refer to constructor arguments as `<name>`
+//! placeholders. [`link`] replaces them with concrete values — hex for byte
+//! types, decimal for integers — after checking each value against the
+//! parameter's declared type, and the server key too when one is given.
+//! A `new P2TR(...)` template whose arguments are all linked becomes the
+//! scriptPubKey it stands for.
+//! Each linked function's `scriptHex` is its leaf script, without the
+//! witness elements, once it is fully concrete, and its `operations`
+//! describe the linked `asm`. [`link_with_address`] also
+//! derives the contract's taproot address and descriptor from the leaf
+//! scripts, the script tree with its output key, and each leaf's merkle
+//! proof and control block; [`descriptor`] gives the descriptor alone, with
+//! every leaf script written out.
+//! [`ContractJson::instantiate`] links arguments built in code as
+//! [`Value`]s and insists on fully concrete leaves.
+
+use std::collections::HashMap;
+
+use crate::compiler;
+use crate::encoding::{push_data, Value};
+use crate::models::{variant_label, AbiFunction, ContractJson, LeafProof};
+use crate::taproot::{self, Network, TaprootOutput};
+
+/// Argument name under which the server key is linked.
+pub const SERVER_KEY: &str = "SERVER_KEY";
+
+/// Link `args` into `artifact`, returning the linked copy.
+///
+/// Every constructor input needs an argument; arguments that name no input
+/// are rejected. `<SERVER_KEY>` is linked if `args` contains [`SERVER_KEY`]
+/// and left in place otherwise. An `internalKey` naming a constructor input
+/// gets the argument, x-only. Witness placeholders are kept, and the linked
+/// artifact has no constructor inputs left.
+pub fn link(
+    artifact: &ContractJson,
+    args: &HashMap<String, String>,
+) -> Result<ContractJson, String> {
+    link_values(artifact, &bind(artifact, args)?)
+}
+
+impl ContractJson {
+    /// Bind constructor arguments given as values rather than text, and
+    /// return the linked artifact, as [`link`] does.
+    ///
+    /// Each value is checked against its input's type, a pubkey being
+    /// compressed and every fixed-size byte type the right length. Every
+    /// leaf must come out fully concrete, as [`link_with_address`] needs
+    /// it, so each function has its `scriptHex`.
+    ///
+    /// ```ignore
+    /// let instance = artifact.instantiate(HashMap::from([
+    ///     ("owner".to_string(), Value::Pubkey(owner_key)),
+    ///     ("timeout".to_string(), Value::Num(850_000)),
+    ///     (SERVER_KEY.to_string(), Value::Pubkey(server_key)),
+    /// ]))?;
+    /// ```
+    pub fn instantiate(&self, params: HashMap<String, Value>) -> Result<ContractJson, String> {
+        let values = bind_values(self, params)?;
+        for function in &self.functions {
+            leaf_script(function, &values)?;
+        }
+        link_values(self, &values)
+    }
+}
+
+/// Link checked `values` into `artifact`.
+fn link_values(
+    artifact: &ContractJson,
+    values: &HashMap<String, Value>,
+) -> Result<ContractJson, String> {
+    let mut linked = artifact.clone();
+    linked.parameters.clear();
+    if let Some(key) = &mut linked.internal_key {
+        *key = linked_key(key, values);
+        if let Some(graph) = &mut linked.spend_graph {
+            graph.key_path.internal_key = key.clone();
+        }
+    }
+    for function in &mut linked.functions {
+        function.script_hex = leaf_script(function, values).ok().map(hex::encode);
+        let mut asm = Vec::with_capacity(function.asm.len());
+        let mut operations = Vec::with_capacity(function.asm.len());
+        for token in &function.asm {
+            let name = token.strip_prefix('<').and_then(|t| t.strip_suffix('>'));
+            if let Some(script) = name.and_then(|name| instance(name, values)) {
+                let script = script?;
+                let token = format!("0x{}", hex::encode(&script));
+                operations.push(compiler::push(token.clone(), &script));
+                asm.push(token);
+                continue;
+            }
+            match name.and_then(|name| values.get(name)) {
+                // One token per stack element, each pushed as `stack` has it
+                Some(value) => {
+                    for (token, bytes) in value.tokens().into_iter().zip(value.stack()) {
+                        asm.push(token.clone());
+                        operations.push(compiler::push(token, &bytes));
+                    }
+                }
+                None => {
+                    asm.push(token.clone());
+                    operations.push(compiler::operation(token, &[], &function.witness_schema));
+                }
+            }
+        }
+        function.asm = asm;
+        function.operations = operations;
+    }
+    Ok(linked)
+}
+
+/// [`link`], and derive the address on `network` and the `tr()` descriptor
+/// of the contract's taproot output. The output is keyed with the
+/// artifact's `internalKey`, or the NUMS point if it has none. Each
+/// function's `proof` is set to its leaf's place in the output, from which
+/// a control block can be built without the other leaves.
+///
+/// Every leaf must be fully concrete: the server key is needed if the
+/// contract has one, and leaves with `<VTXO:...>` or other unresolved
+/// placeholders, or with opcodes that have no encoding yet, are rejected.
+pub fn link_with_address(
+    artifact: &ContractJson,
+    args: &HashMap<String, String>,
+    network: Network,
+) -> Result<ContractJson, String> {
+    let leaves = leaf_scripts(artifact, args)?;
+    let leaf_hashes: Vec<[u8; 32]> = leaves.iter().map(|l| taproot::leaf_hash(l)).collect();
+    let mut linked = link(artifact, args)?;
+    let output = taproot_output(linked.internal_key.as_deref(), leaves)?;
+
+    for (index, function) in linked.functions.iter_mut().enumerate() {
+        function.proof = Some(LeafProof {
+            leaf_hash: hex::encode(leaf_hashes[index]),
+            merkle_path: output
+                .merkle_proof(index)?
+                .iter()
+                .map(hex::encode)
+                .collect(),
+            parity: output.output_key_parity,
+            control_block: hex::encode(output.control_block(index)?),
+        });
+    }
+    linked.taproot = Some(output.tree());
+    linked.address = Some(output.address(network));
+    linked.descriptor = Some(output.descriptor());
+    if let Some(graph) = &mut linked.spend_graph {
+        graph.descriptor = linked.descriptor.clone();
+    }
+    Ok(linked)
+}
+
+/// The `tr()` descriptor of the contract's taproot output with `args`
+/// linked in, as [`link_with_address`] records it, without linking the
+/// rest of the artifact.
+pub fn descriptor(
+    artifact: &ContractJson,
+    args: &HashMap<String, String>,
+) -> Result<String, String> {
+    let leaves = leaf_scripts(artifact, args)?;
+    let values = bind(artifact, args)?;
+    let internal_key = artifact
+        .internal_key
+        .as_deref()
+        .map(|key| linked_key(key, &values));
+    Ok(taproot_output(internal_key.as_deref(), leaves)?.descriptor())
+}
+
+/// Read constructor arguments from a JSON object of names to values, as
+/// `arkadec link --params` takes them. Values are strings, written as
+/// [`Value::parse`] reads them, or numbers and booleans; the server key
+/// may be given as [`SERVER_KEY`].
+pub fn parse_params(json: &str) -> Result<HashMap<String, String>, String> {
+    let object: serde_json::Map<String, serde_json::Value> = serde_json::from_str(json)
+        .map_err(|e| format!("Expected a JSON object of argument names to values: {}", e))?;
+    object
+        .into_iter()
+        .map(|(name, value)| {
+            let value = match value {
+                serde_json::Value::String(text) => text,
+                serde_json::Value::Number(number) => number.to_string(),
+                serde_json::Value::Bool(flag) => flag.to_string(),
+                other => {
+                    return Err(format!(
+                        "Argument '{}' must be a string, number or boolean, got {}",
+                        name, other
+                    ))
+                }
+            };
+            Ok((name, value))
+        })
+        .collect()
+}
+
+/// The output keyed with `internal_key`, x-only hex, or the NUMS point.
+fn taproot_output(
+    internal_key: Option<&str>,
+    leaves: Vec<Vec<u8>>,
+) -> Result<TaprootOutput, String> {
+    match internal_key {
+        Some(key) => {
+            let mut internal_key = [0u8; 32];
+            hex::decode_to_slice(key, &mut internal_key)
+                .map_err(|_| format!("Invalid internal key '{}'", key))?;
+            TaprootOutput::with_internal_key(internal_key, leaves)
+        }
+        None => TaprootOutput::new(leaves),
+    }
+}
+
+/// `internalKey` with the argument for its `<name>`, x-only, if `values`
+/// has one.
+fn linked_key(key: &str, values: &HashMap<String, Value>) -> String {
+    let name = key.strip_prefix('<').and_then(|k| k.strip_suffix('>'));
+    match name.and_then(|name| values.get(name)) {
+        Some(Value::Pubkey(bytes)) => hex::encode(&bytes[1..]),
+        _ => key.to_string(),
+    }
+}
+
+/// Assemble the leaf script of every function variant, in artifact order,
+/// with `args` linked in and the witness elements left out.
+///
+/// Fails like [`link_with_address`] on leaves that are not fully concrete.
+pub fn leaf_scripts(
+    artifact: &ContractJson,
+    args: &HashMap<String, String>,
+) -> Result<Vec<Vec<u8>>, String> {
+    let values = bind(artifact, args)?;
+    artifact
+        .functions
+        .iter()
+        .map(|function| leaf_script(function, &values))
+        .collect()
+}
+
+/// Read `args` as the types of the artifact's constructor inputs, then
+/// [`bind_values`].
+fn bind(
+    artifact: &ContractJson,
+    args: &HashMap<String, String>,
+) -> Result<HashMap<String, Value>, String> {
+    let mut values = HashMap::new();
+    for (name, value) in args {
+        let value = if name == SERVER_KEY {
+            Value::parse(value, "pubkey").map_err(|e| format!("Server key: {}", e))?
+        } else if let Some(param) = artifact.parameters.iter().find(|p| &p.name == name) {
+            Value::parse(value, &param.param_type)
+                .map_err(|e| format!("Argument '{}': {}", name, e))?
+        } else if is_asset(artifact, name) {
+            Value::asset_id(value).map_err(|e| format!("Argument '{}': {}", name, e))?
+        } else {
+            return Err(format!(
+                "Contract {} has no parameter '{}'",
+                artifact.name, name
+            ));
+        };
+        values.insert(name.clone(), value);
+    }
+    bind_values(artifact, values)
+}
+
+/// Check `args` against the artifact's constructor inputs.
+fn bind_values(
+    artifact: &ContractJson,
+    mut args: HashMap<String, Value>,
+) -> Result<HashMap<String, Value>, String> {
+    let mut values = HashMap::new();
+    for param in &artifact.parameters {
+        if values.contains_key(&param.name) {
+            continue;
+        }
+        // An asset id looked up by the contract is linked as one
+        // `txid:gidx` argument, or as its two parts
+        let asset = param
+            .name
+            .strip_suffix("_txid")
+            .filter(|base| !args.contains_key(&param.name) && is_asset(artifact, base));
+        if let Some(base) = asset.filter(|base| args.contains_key(*base)) {
+            let (txid, gidx) = args[base]
+                .decompose()
+                .ok_or_else(|| format!("Argument '{}': expected an asset id", base))?;
+            values.insert(param.name.clone(), txid);
+            values.insert(format!("{}_gidx", base), gidx);
+            args.remove(base);
+            continue;
+        }
+        let value = args
+            .remove(&param.name)
+            .ok_or_else(|| format!("Missing argument '{}'", param.name))?;
+        let value = value
+            .check(&param.param_type)
+            .map_err(|e| format!("Argument '{}': {}", param.name, e))?;
+        values.insert(param.name.clone(), value);
+    }
+    if let Some(key) = args.remove(SERVER_KEY) {
+        let value = key
+            .check("pubkey")
+            .map_err(|e| format!("Server key: {}", e))?;
+        values.insert(SERVER_KEY.to_string(), value);
+    }
+    if let Some(unknown) = args.keys().find(|name| !values.contains_key(*name)) {
+        return Err(format!(
+            "Contract {} has no parameter '{}'",
+            artifact.name, unknown
+        ));
+    }
+    Ok(values)
+}
+
+/// Whether `name` is an asset id the artifact takes as its `<name_txid>`
+/// and `<name_gidx>` inputs.
+fn is_asset(artifact: &ContractJson, name: &str) -> bool {
+    let input = |suffix: &str| {
+        let input = format!("{}{}", name, suffix);
+        artifact.parameters.iter().any(|p| p.name == input)
+    };
+    input("_txid") && input("_gidx")
+}
+
+/// Assemble the leaf script of a function variant.
+fn leaf_script(function: &AbiFunction, values: &HashMap<String, Value>) -> Result<Vec<u8>, String> {
+    let variant = variant_label(function.server_variant);
+    let fail = |e: String| format!("fn {} ({} variant): {}", function.name, variant, e);
+
+    compiler::assemble(&function.asm, |name, script| {
+        if let Some(value) = values.get(name) {
+            value.push(script);
+        } else if let Some(script_pubkey) = instance(name, values) {
+            push_data(script, &script_pubkey?);
+        } else if !function.witness_schema.iter().any(|w| w.name == name) {
+            return Err(format!("<{}> is not linked", name));
+        }
+        Ok(())
+    })
+    .map_err(fail)
+}
+
+/// The scriptPubKey of the `new` template `name`, if it is one the linker
+/// computes and `values` has all its arguments.
+fn instance(name: &str, values: &HashMap<String, Value>) -> Option<Result<Vec<u8>, String>> {
+    compiler::p2tr_template(name, |arg| match values.get(arg)? {
+        Value::Pubkey(bytes) | Value::Bytes(bytes) => Some(bytes.clone()),
+        _ => None,
+    })
+}
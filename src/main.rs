@@ -1,13 +1,16 @@
-use clap::Parser as ClapParser;
+use arkade_compiler::compiler;
+use arkade_compiler::models::variant_label;
+use arkade_compiler::parser::{self, ScriptTarget};
+use arkade_compiler::taproot::Network;
+use arkade_compiler::{
+    bindings, decompile, diagnostics, examples, legacy, linker, templates, testing, vectors,
+    ContractJson, ResourceBudget,
+};
+use clap::{Parser as ClapParser, Subcommand, ValueEnum};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::Path;
 
-mod compiler;
-mod models;
-mod opcodes;
-mod parser;
-mod typechecker;
-
 /// Arkade Compiler CLI
 ///
 /// This is the command-line interface for the Arkade Compiler.
@@ -23,20 +26,310 @@ mod typechecker;
 /// Each script path includes a serverVariant flag. When using the script:
 /// - If serverVariant is true, use the script as-is
 /// - If serverVariant is false, libraries should add an exit delay timelock
-///   (default 48 hours) for additional security
+///   (`exitDelay` blocks, 48 hours by default) for additional security
 
 // CLI arguments
 #[derive(ClapParser, Debug)]
 #[command(name = "arkadec")]
 #[command(about = "Arkade Compiler for Bitcoin Taproot scripts", long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Source file path (.ark)
-    #[arg(required = true)]
-    file: String,
+    #[arg(required_unless_present = "explain")]
+    file: Option<String>,
+
+    /// Print the explanation of an error code, such as `E0102`, and exit
+    #[arg(long, value_name = "CODE")]
+    explain: Option<String>,
 
-    /// Output file path (defaults to source filename with .json extension)
+    /// Output file path (defaults to source filename with .json, or .d.ts
+    /// or .rs for `--emit dts` and `--emit rust`)
     #[arg(short, long)]
     output: Option<String>,
+
+    /// What to write: the JSON artifact, or TypeScript or Rust bindings for it
+    #[arg(long, value_enum, default_value_t = Emit::Json)]
+    emit: Emit,
+
+    /// Run the IR optimizer over every generated leaf
+    #[arg(short = 'O', long)]
+    optimize: bool,
+
+    /// How much of the optimizer to run: 0 none, 1 the peephole pass only,
+    /// 2 everything, as `-O`
+    #[arg(
+        long,
+        value_name = "LEVEL",
+        conflicts_with = "optimize",
+        value_parser = clap::value_parser!(u8).range(0..=2)
+    )]
+    opt_level: Option<u8>,
+
+    /// Fail if any leaf path executes more signature operations than this
+    #[arg(long, value_name = "N")]
+    max_sigops: Option<usize>,
+
+    /// Fail if any leaf path executes more introspection opcodes than this
+    #[arg(long, value_name = "N")]
+    max_introspection: Option<usize>,
+
+    /// Fail if any leaf path executes more 64-bit arithmetic opcodes than this
+    #[arg(long, value_name = "N")]
+    max_arith64: Option<usize>,
+
+    /// Network that constants such as `BLOCKS_PER_DAY` are resolved for
+    #[arg(long, value_name = "NETWORK", default_value = "bitcoin")]
+    target: Network,
+
+    /// Script flavour to compile for: `arkade`, or `tapscript` to reject
+    /// Arkade-only syntax
+    #[arg(long, value_name = "TARGET", default_value = "arkade")]
+    script_target: ScriptTarget,
+
+    /// Ignore unknown names in the `options` block instead of failing
+    #[arg(long)]
+    lenient_options: bool,
+
+    /// Accept TapLang sources (.tap), translating their
+    /// `checkMultisig([keys], [sigs])` into the threshold form
+    #[arg(long)]
+    legacy_taplang: bool,
+
+    /// Embed the source without comments or layout, with its hash, so
+    /// that equivalent sources give identical artifacts
+    #[arg(long)]
+    normalize_source: bool,
+
+    /// Leave `updatedAt` out, or take it from SOURCE_DATE_EPOCH, and sort
+    /// the artifact's keys, so that the same source compiles to the same
+    /// bytes
+    #[arg(long)]
+    reproducible: bool,
+
+    /// Fail if the contract compiles with warnings
+    #[arg(long)]
+    deny_warnings: bool,
+
+    /// Print at most N warnings or denied warnings, counting the rest
+    #[arg(long, value_name = "N")]
+    max_errors: Option<usize>,
+
+    /// Fix a generic parameter of the contract, e.g. `--generic N=5`
+    #[arg(long = "generic", value_name = "NAME=VALUE", value_parser = parse_generic)]
+    generics: Vec<(String, usize)>,
+}
+
+/// Parse a `--param NAME=VALUE` argument
+fn parse_param(arg: &str) -> Result<(String, String), String> {
+    let (name, value) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=VALUE, got '{}'", arg))?;
+    Ok((name.to_string(), value.to_string()))
+}
+
+/// Parse a `--generic NAME=VALUE` argument
+fn parse_generic(arg: &str) -> Result<(String, usize), String> {
+    let (name, value) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=VALUE, got '{}'", arg))?;
+    let value = value
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid length", value))?;
+    Ok((name.to_string(), value))
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Emit {
+    /// The compiled artifact
+    Json,
+    /// TypeScript declarations for the artifact's inputs
+    Dts,
+    /// A typed Rust struct with a method per spend path
+    Rust,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum AstFormat {
+    /// The AST as JSON, with source spans
+    Json,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print the estimated witness size of every spend path
+    Size {
+        /// Source file path (.ark)
+        file: String,
+
+        /// Estimate the size of optimized leaves
+        #[arg(short = 'O', long)]
+        optimize: bool,
+    },
+    /// Generate valid and invalid example witness stacks for every spend path
+    Vectors {
+        /// Source file path (.ark)
+        file: String,
+
+        /// Write the vectors to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Run a contract's test cases and report requirement coverage
+    Test {
+        /// Source file path (.ark)
+        file: String,
+
+        /// Test suite (defaults to the source filename with .test.json)
+        suite: Option<String>,
+
+        /// Print the results and coverage as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Bind constructor arguments into a compiled artifact and derive its
+    /// address and descriptor
+    Link {
+        /// Compiled artifact (.json)
+        artifact: String,
+
+        /// A constructor argument, e.g. `--param owner=02ab...`
+        #[arg(long = "param", value_name = "NAME=VALUE", value_parser = parse_param)]
+        params: Vec<(String, String)>,
+
+        /// A JSON object of constructor arguments, e.g. `{"owner": "02ab..."}`;
+        /// `--param` overrides it
+        #[arg(long = "params", value_name = "FILE")]
+        params_file: Option<String>,
+
+        /// Server public key, for contracts with a server
+        #[arg(long, value_name = "HEX")]
+        server_key: Option<String>,
+
+        /// Network to encode the address for
+        #[arg(long, default_value = "bitcoin")]
+        network: Network,
+
+        /// Write the linked artifact to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Print the tr() output descriptor of a compiled artifact with its
+    /// constructor arguments, leaves written as raw() scripts
+    Descriptor {
+        /// Compiled artifact (.json)
+        artifact: String,
+
+        /// A constructor argument, e.g. `--param owner=02ab...`
+        #[arg(long = "param", value_name = "NAME=VALUE", value_parser = parse_param)]
+        params: Vec<(String, String)>,
+
+        /// A JSON object of constructor arguments, e.g. `{"owner": "02ab..."}`;
+        /// `--param` overrides it
+        #[arg(long = "params", value_name = "FILE")]
+        params_file: Option<String>,
+
+        /// Server public key, for contracts with a server
+        #[arg(long, value_name = "HEX")]
+        server_key: Option<String>,
+    },
+    /// Disassemble a leaf script, given as hex or ASM, into its operations,
+    /// and find the function of an artifact it is the leaf of
+    Decompile {
+        /// The script, as hex or as quoted ASM
+        script: String,
+
+        /// Compiled or linked artifact (.json) to match the script against
+        #[arg(long)]
+        artifact: Option<String>,
+    },
+    /// Print a base64 PSBT spending a contract output through one function,
+    /// with the leaf script, control block and witness placeholders
+    #[cfg(feature = "bitcoin")]
+    Psbt {
+        /// Compiled artifact (.json)
+        artifact: String,
+
+        /// Function to spend through
+        #[arg(long)]
+        function: String,
+
+        /// Spend through the exit variant instead of the cooperative one
+        #[arg(long)]
+        exit: bool,
+
+        /// A constructor argument, e.g. `--param owner=02ab...`
+        #[arg(long = "param", value_name = "NAME=VALUE", value_parser = parse_param)]
+        params: Vec<(String, String)>,
+
+        /// A JSON object of constructor arguments, e.g. `{"owner": "02ab..."}`;
+        /// `--param` overrides it
+        #[arg(long = "params", value_name = "FILE")]
+        params_file: Option<String>,
+
+        /// Server public key, for contracts with a server
+        #[arg(long, value_name = "HEX")]
+        server_key: Option<String>,
+
+        /// A witness element known up front, e.g. `--arg preimage=ab...`
+        #[arg(long = "arg", value_name = "NAME=VALUE", value_parser = parse_param)]
+        args: Vec<(String, String)>,
+
+        /// The contract output to spend
+        #[arg(long, value_name = "TXID:VOUT", default_value_t = bitcoin::OutPoint::null())]
+        outpoint: bitcoin::OutPoint,
+
+        /// Value of the contract output, in satoshis
+        #[arg(long, value_name = "SATS", default_value_t = 0)]
+        amount: u64,
+    },
+    /// Recompile the source of a TapLang artifact into a current artifact
+    Upgrade {
+        /// TapLang artifact (.json)
+        artifact: String,
+
+        /// Write the upgraded artifact to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Print the parsed syntax tree of a contract
+    Ast {
+        /// Source file path (.ark)
+        file: String,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = AstFormat::Json)]
+        format: AstFormat,
+    },
+    /// List the embedded example contracts, or print one
+    Examples {
+        /// Example to print
+        name: Option<String>,
+    },
+    /// Write a new contract from a template
+    New {
+        /// Template to start from
+        #[arg(long, value_name = "NAME")]
+        template: String,
+
+        /// Hash function of the hash lock (htlc)
+        #[arg(long, value_name = "FUNCTION")]
+        hash: Option<String>,
+
+        /// Blocks the unilateral exit paths wait (htlc, vault)
+        #[arg(long, value_name = "BLOCKS")]
+        timeout: Option<String>,
+
+        /// Blocks within which the contract must be renewed (vault)
+        #[arg(long, value_name = "BLOCKS")]
+        renew: Option<String>,
+
+        /// Output file path (defaults to the template name with .ark)
+        #[arg(short, long)]
+        output: Option<String>,
+    },
 }
 
 /// Main function for the Arkade Compiler CLI
@@ -51,43 +344,467 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Parse CLI arguments
     let args = Args::parse();
 
-    // Ensure file has .ark extension
-    let file_path = Path::new(&args.file);
-    if file_path.extension().unwrap_or_default() != "ark" {
-        return Err("Input file must have .ark extension".into());
+    match &args.command {
+        Some(Command::Size { file, optimize }) => return print_sizes(file, *optimize),
+        Some(Command::Vectors { file, output }) => return write_vectors(file, output.as_deref()),
+        Some(Command::Test { file, suite, json }) => {
+            return run_tests(file, suite.as_deref(), *json)
+        }
+        Some(Command::Link {
+            artifact,
+            params,
+            params_file,
+            server_key,
+            network,
+            output,
+        }) => {
+            let args = link_args(params, params_file.as_deref(), server_key.as_deref())?;
+            return link_artifact(artifact, &args, *network, output.as_deref());
+        }
+        Some(Command::Descriptor {
+            artifact,
+            params,
+            params_file,
+            server_key,
+        }) => {
+            let args = link_args(params, params_file.as_deref(), server_key.as_deref())?;
+            return print_descriptor(artifact, &args);
+        }
+        Some(Command::Decompile { script, artifact }) => {
+            return print_decompiled(script, artifact.as_deref())
+        }
+        #[cfg(feature = "bitcoin")]
+        Some(Command::Psbt {
+            artifact,
+            function,
+            exit,
+            params,
+            params_file,
+            server_key,
+            args: witness,
+            outpoint,
+            amount,
+        }) => {
+            let args = link_args(params, params_file.as_deref(), server_key.as_deref())?;
+            let artifact = read_artifact(artifact)?;
+            let psbt = arkade_compiler::spend::SpendBuilder::new(&artifact)
+                .params(args)
+                .function(function, !exit)
+                .args(witness.iter().cloned())
+                .psbt(*outpoint, bitcoin::Amount::from_sat(*amount))?;
+            println!("{}", psbt);
+            return Ok(());
+        }
+        Some(Command::Upgrade { artifact, output }) => {
+            return upgrade_artifact(artifact, output.as_deref())
+        }
+        Some(Command::Ast { file, format }) => return print_ast(file, *format),
+        Some(Command::Examples { name }) => return print_examples(name.as_deref()),
+        Some(Command::New {
+            template,
+            hash,
+            timeout,
+            renew,
+            output,
+        }) => {
+            let values = [("hash", hash), ("timeout", timeout), ("renew", renew)]
+                .into_iter()
+                .filter_map(|(name, value)| Some((name.to_string(), value.clone()?)))
+                .collect();
+            return write_template(template, &values, output.as_deref());
+        }
+        None => {}
     }
+    if let Some(code) = &args.explain {
+        return explain(code);
+    }
+    let file = args.file.as_deref().unwrap_or_default();
 
     // Read source code
-    let source_code = fs::read_to_string(&args.file)?;
+    let source_code = if args.legacy_taplang && Path::new(file).extension() == Some("tap".as_ref())
+    {
+        fs::read_to_string(file)?
+    } else {
+        read_source(file)?
+    };
 
     // Compile source code to JSON
-    let output = match compiler::compile(&source_code) {
-        Ok(json) => json,
-        Err(err) => {
-            eprintln!("Compilation error: {}", err);
-            return Err(err.into());
-        }
+    let options = compiler::CompileOptions {
+        optimize: args.optimize || args.opt_level == Some(2),
+        peephole: args.opt_level == Some(1),
+        budget: ResourceBudget {
+            max_sigops: args.max_sigops,
+            max_introspection_ops: args.max_introspection,
+            max_arithmetic64_ops: args.max_arith64,
+        },
+        generics: args.generics.iter().cloned().collect(),
+        network: args.target,
+        script_target: args.script_target,
+        lenient_options: args.lenient_options,
+        legacy_taplang: args.legacy_taplang,
+        normalize_source: args.normalize_source,
+        deny_warnings: args.deny_warnings,
+        max_errors: args.max_errors,
+        reproducible: args.reproducible,
+        ..Default::default()
     };
+    let output = compile(&source_code, &options)?;
 
-    // Print any type-check warnings to stderr
-    for w in &output.warnings {
-        eprintln!("{}", w);
+    // Report the effect of the optimizer on each leaf
+    for r in &output.optimizations {
+        eprintln!(
+            "optimized {} ({}): {} -> {} bytes",
+            r.function,
+            variant_label(r.server_variant),
+            r.size_before,
+            r.size_after
+        );
     }
 
     // Determine output path
     let output_path = match args.output {
         Some(path) => path,
         None => {
-            let stem = file_path.file_stem().unwrap_or_default().to_string_lossy();
-            format!("{}.json", stem)
+            let stem = Path::new(file)
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy();
+            match args.emit {
+                Emit::Json => format!("{}.json", stem),
+                Emit::Dts => format!("{}.d.ts", stem),
+                Emit::Rust => format!("{}.rs", stem),
+            }
         }
     };
 
-    // Write output JSON
-    let json = serde_json::to_string_pretty(&output)?;
-    fs::write(&output_path, json)?;
+    // Write the artifact or its declarations
+    let contents = match args.emit {
+        Emit::Json if args.reproducible => output.to_reproducible_json()?,
+        Emit::Json => serde_json::to_string_pretty(&output)?,
+        Emit::Dts => bindings::typescript::generate(&output),
+        Emit::Rust => bindings::rust::generate(&output),
+    };
+    fs::write(&output_path, contents)?;
 
     println!("Compilation successful. Output written to {}", output_path);
 
     Ok(())
 }
+
+/// Read an Arkade Script source file, rejecting anything but `.ark` files
+fn read_source(file: &str) -> Result<String, Box<dyn std::error::Error>> {
+    if Path::new(file).extension().unwrap_or_default() != "ark" {
+        return Err("Input file must have .ark extension".into());
+    }
+    Ok(fs::read_to_string(file)?)
+}
+
+/// Compile source code, printing the error or any warnings to stderr
+fn compile(
+    source_code: &str,
+    options: &compiler::CompileOptions,
+) -> Result<ContractJson, Box<dyn std::error::Error>> {
+    let output = match compiler::compile_with_options(source_code, options) {
+        Ok(json) => json,
+        Err(err) => {
+            eprintln!("Compilation error [{}]: {}", err.code(), err);
+            if let Some(excerpt) = err.excerpt() {
+                eprintln!("{}", excerpt);
+            }
+            eprintln!(
+                "For more information, run `arkadec --explain {}`",
+                err.code()
+            );
+            return Err(String::from(err).into());
+        }
+    };
+
+    // Print any type-check warnings to stderr
+    let shown = options.max_errors.unwrap_or(usize::MAX);
+    for w in output.warnings.iter().take(shown) {
+        eprintln!("{}", w);
+    }
+    if output.warnings.len() > shown {
+        eprintln!("... and {} more", output.warnings.len() - shown);
+    }
+
+    Ok(output)
+}
+
+/// `arkadec --explain`: print the explanation of an error code
+fn explain(code: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let entry =
+        diagnostics::explain(code).ok_or_else(|| format!("unknown error code '{}'", code))?;
+    print!("{}", entry.explanation);
+    Ok(())
+}
+
+/// `arkadec size`: print the witness size estimate of every leaf
+fn print_sizes(file: &str, optimize: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let source_code = read_source(file)?;
+    let options = compiler::CompileOptions {
+        optimize,
+        ..Default::default()
+    };
+    let output = compile(&source_code, &options)?;
+
+    println!("{}", output.name);
+    for f in &output.functions {
+        let variant = variant_label(f.server_variant);
+        let size = &f.witness_size;
+        println!(
+            "  {} ({}): {} bytes, {} vB ({} stack + {} script + {} control block)",
+            f.name,
+            variant,
+            size.total,
+            size.vbytes(),
+            size.stack,
+            size.script,
+            size.control_block
+        );
+    }
+
+    Ok(())
+}
+
+/// `arkadec vectors`: emit test vectors as JSON
+fn write_vectors(file: &str, output: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let source_code = read_source(file)?;
+    let vectors = vectors::generate(&source_code)?;
+    let json = serde_json::to_string_pretty(&vectors)?;
+
+    match output {
+        Some(path) => {
+            fs::write(path, json)?;
+            println!("Wrote {} test vectors to {}", vectors.vectors.len(), path);
+        }
+        None => println!("{}", json),
+    }
+
+    Ok(())
+}
+
+/// `arkadec upgrade`: recompile a TapLang artifact
+fn upgrade_artifact(
+    artifact: &str,
+    output: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let json = fs::read_to_string(artifact)?;
+    let upgraded = legacy::upgrade(&json, &compiler::CompileOptions::default())?;
+    let json = serde_json::to_string_pretty(&upgraded)?;
+
+    match output {
+        Some(path) => {
+            fs::write(path, json)?;
+            println!("Upgraded {} to {}", artifact, path);
+        }
+        None => println!("{}", json),
+    }
+
+    Ok(())
+}
+
+/// `arkadec ast`: print the parsed AST
+fn print_ast(file: &str, format: AstFormat) -> Result<(), Box<dyn std::error::Error>> {
+    let source_code = read_source(file)?;
+    let contract = parser::parse(&source_code)?;
+    match format {
+        AstFormat::Json => println!("{}", serde_json::to_string_pretty(&contract)?),
+    }
+    Ok(())
+}
+
+/// `arkadec test`: run a test suite and print its results and coverage
+fn run_tests(
+    file: &str,
+    suite: Option<&str>,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let source_code = read_source(file)?;
+    let suite_path = match suite {
+        Some(path) => path.to_string(),
+        None => Path::new(file)
+            .with_extension("test.json")
+            .to_string_lossy()
+            .into_owned(),
+    };
+    let suite: testing::TestSuite = serde_json::from_str(&fs::read_to_string(&suite_path)?)
+        .map_err(|e| format!("Invalid test suite {}: {}", suite_path, e))?;
+    let report = testing::run(&source_code, &suite)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!(
+            "running {} cases for {}",
+            report.results.len(),
+            report.contract_name
+        );
+        for r in &report.results {
+            let status = if r.ok() { "ok" } else { "FAILED" };
+            match (&r.message, r.ok()) {
+                (Some(message), false) => {
+                    println!("  {} ... {} ({})", r.name, status, message)
+                }
+                _ => println!("  {} ... {}", r.name, status),
+            }
+        }
+        print!("{}", report.coverage);
+        println!(
+            "{} passed, {} failed",
+            report.results.len() - report.failures(),
+            report.failures()
+        );
+    }
+
+    match report.failures() {
+        0 => Ok(()),
+        n => Err(format!("{} test case(s) failed", n).into()),
+    }
+}
+
+/// `arkadec link`: bind constructor arguments and derive the address
+/// The constructor arguments of `arkadec link`, `descriptor` and `psbt`:
+/// those in the `--params` file, then each `--param`, then the server key
+fn link_args(
+    params: &[(String, String)],
+    params_file: Option<&str>,
+    server_key: Option<&str>,
+) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+    let mut args = match params_file {
+        Some(path) => linker::parse_params(&fs::read_to_string(path)?)
+            .map_err(|e| format!("Invalid parameters {}: {}", path, e))?,
+        None => HashMap::new(),
+    };
+    args.extend(params.iter().cloned());
+    if let Some(key) = server_key {
+        args.insert(linker::SERVER_KEY.to_string(), key.to_string());
+    }
+    Ok(args)
+}
+
+fn read_artifact(path: &str) -> Result<ContractJson, Box<dyn std::error::Error>> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)
+        .map_err(|e| format!("Invalid artifact {}: {}", path, e))?)
+}
+
+fn print_descriptor(
+    path: &str,
+    args: &HashMap<String, String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", linker::descriptor(&read_artifact(path)?, args)?);
+    Ok(())
+}
+
+/// `arkadec decompile`: the script's operations and, with an artifact, the
+/// function it matches, as JSON
+fn print_decompiled(
+    script: &str,
+    artifact: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let hex = script.trim().strip_prefix("0x").unwrap_or(script.trim());
+    let (operations, bytes) = if hex.len().is_multiple_of(2)
+        && hex.bytes().all(|b| b.is_ascii_hexdigit())
+    {
+        let bytes = hex::decode(hex)?;
+        (decompile::disassemble(&bytes)?, Some(bytes))
+    } else {
+        // ASM with placeholders left in cannot be assembled to match
+        let tokens: Vec<String> = script.split_whitespace().map(str::to_string).collect();
+        let bytes = compiler::assemble(&tokens, |name, _| Err(format!("<{}> is not linked", name)));
+        (decompile::parse_asm(script), bytes.ok())
+    };
+    let leaf = match (artifact, bytes) {
+        (Some(path), Some(bytes)) => decompile::identify(&read_artifact(path)?, &bytes)?,
+        (Some(_), None) => return Err("Only a fully concrete script can be matched".into()),
+        (None, _) => None,
+    };
+    let json = serde_json::json!({ "operations": operations, "match": leaf });
+    println!("{}", serde_json::to_string_pretty(&json)?);
+    Ok(())
+}
+
+fn link_artifact(
+    path: &str,
+    args: &HashMap<String, String>,
+    network: Network,
+    output: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let artifact = read_artifact(path)?;
+
+    // Scripts that cannot be assembled yet are still linked, without an address
+    let linked = match linker::link_with_address(&artifact, args, network) {
+        Ok(linked) => linked,
+        Err(e) => {
+            let linked = linker::link(&artifact, args)?;
+            eprintln!("warning: no address derived: {}", e);
+            linked
+        }
+    };
+    let json = serde_json::to_string_pretty(&linked)?;
+
+    match output {
+        Some(path) => {
+            fs::write(path, json)?;
+            match &linked.address {
+                Some(address) => println!("Linked {} at {} to {}", linked.name, address, path),
+                None => println!("Linked {} to {}", linked.name, path),
+            }
+        }
+        None => println!("{}", json),
+    }
+
+    Ok(())
+}
+
+/// Write the template named `name`, populated from `values`, to a new file
+fn write_template(
+    name: &str,
+    values: &BTreeMap<String, String>,
+    output: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let template = templates::find(name).ok_or_else(|| {
+        let names: Vec<&str> = templates::TEMPLATES.iter().map(|t| t.name).collect();
+        format!(
+            "Unknown template '{}' (templates: {})",
+            name,
+            names.join(", ")
+        )
+    })?;
+    let source = template.render(values)?;
+
+    let output_path = match output {
+        Some(path) => path.to_string(),
+        None => format!("{}.ark", template.name),
+    };
+    if Path::new(&output_path).exists() {
+        return Err(format!("{} already exists", output_path).into());
+    }
+    fs::write(&output_path, source)?;
+
+    println!("Template {} written to {}", template.name, output_path);
+    Ok(())
+}
+
+/// List the embedded examples, or print the source of the one named `name`
+fn print_examples(name: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(name) = name else {
+        let width = examples::EXAMPLES
+            .iter()
+            .map(|e| e.name.len())
+            .max()
+            .unwrap_or_default();
+        for example in examples::EXAMPLES {
+            println!("{:width$}  {}", example.name, example.description);
+        }
+        return Ok(());
+    };
+    let example = examples::find(name).ok_or_else(|| {
+        format!(
+            "Unknown example '{}'; run `arkadec examples` to list them",
+            name
+        )
+    })?;
+    print!("{}", example.source);
+    Ok(())
+}
@@ -1,4 +1,12 @@
+use alloc::collections::BTreeMap;
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 /// The number of elements that array-typed parameters (e.g. `pubkey[]`) are
 /// flattened into throughout the pipeline.
@@ -14,13 +22,21 @@ use serde::{Deserialize, Serialize};
 /// only need fewer elements.
 pub const DEFAULT_ARRAY_LENGTH: usize = 3;
 
-/// JSON output structures
-///
-/// These structures are used to represent the compiled contract in a format
-/// that can be serialized to JSON.
+/// Element type and length of an array type: `T[n]` has `n` elements, and
+/// `T[]` — or `T[N]` before generic parameter `N` is fixed — has
+/// [`DEFAULT_ARRAY_LENGTH`]. `None` if `param_type` is not an array.
+pub fn array_type(param_type: &str) -> Option<(&str, usize)> {
+    let (element, length) = param_type.strip_suffix(']')?.split_once('[')?;
+    Some((element, length.parse().unwrap_or(DEFAULT_ARRAY_LENGTH)))
+}
+
+// JSON output structures
+//
+// These structures are used to represent the compiled contract in a format
+// that can be serialized to JSON.
 
 /// Parameter in a contract or function
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Parameter {
     /// Parameter name
     pub name: String,
@@ -39,6 +55,21 @@ pub struct FunctionInput {
     pub param_type: String,
 }
 
+/// An event in the ABI, with the types of its fields
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AbiEvent {
+    pub name: String,
+    pub inputs: Vec<FunctionInput>,
+}
+
+/// An event a function emits, with the inputs giving its fields: function
+/// inputs, whose values are in the witness, or constructor inputs
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AbiEmit {
+    pub event: String,
+    pub args: Vec<String>,
+}
+
 /// Requirement for a function
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RequireStatement {
@@ -59,16 +90,17 @@ pub struct RequireStatement {
 /// The `encoding` field is a stable identifier that code generators
 /// (TypeScript, Go, …) can switch on to pick the correct serializer:
 ///
-/// | encoding        | description                                   |
-/// |-----------------|-----------------------------------------------|
-/// | `compressed-33` | 33-byte SEC-compressed secp256k1 public key  |
-/// | `schnorr-64`    | 64-byte Schnorr signature (BIP-340)           |
-/// | `raw`           | arbitrary byte array (caller decides length)  |
-/// | `raw-20`        | 20-byte array (e.g., HASH160)                 |
-/// | `raw-32`        | 32-byte array (e.g., SHA256, txid)            |
-/// | `scriptnum`     | Bitcoin CScriptNum (variable-length LE)       |
-/// | `le64`          | 8-byte unsigned little-endian int64           |
-/// | `le32`          | 4-byte unsigned little-endian int32           |
+/// | encoding          | description                                     |
+/// |-------------------|-------------------------------------------------|
+/// | `xonly-32`        | 32-byte x-only secp256k1 public key (BIP-340)   |
+/// | `schnorr-64`      | 64-byte Schnorr signature (BIP-340)             |
+/// | `schnorr-sighash` | Schnorr signature, 65 bytes with a sighash flag |
+/// | `raw`             | arbitrary byte array (caller decides length)    |
+/// | `raw-20`          | 20-byte array (e.g., HASH160)                   |
+/// | `raw-32`          | 32-byte array (e.g., SHA256, txid)              |
+/// | `scriptnum`       | Bitcoin CScriptNum (variable-length LE)         |
+/// | `le64`            | 8-byte unsigned little-endian int64             |
+/// | `le32`            | 4-byte unsigned little-endian int32             |
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct WitnessElement {
     /// Parameter name (matches an `<name>` placeholder in `asm`)
@@ -78,6 +110,89 @@ pub struct WitnessElement {
     pub elem_type: String,
     /// Wire-encoding descriptor for client stub generators
     pub encoding: String,
+    /// Sighash flag a signature must be made with, when `checkSig` pins
+    /// one. The script appends it, so the element is the bare 64 bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sighash: Option<u8>,
+}
+
+/// What the spender provides for a [`WitnessItem`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum WitnessRole {
+    /// A signature over the spending transaction, or over a message for
+    /// `checkSigFromStack`
+    Signature,
+    /// The Arkade operator's co-signature on a cooperative path
+    ServerSignature,
+    /// The preimage of a hash lock
+    Preimage,
+    /// An input, output or array index
+    Index,
+    /// Any other argument
+    Value,
+}
+
+/// One item of a function variant's witness stack; see
+/// [`AbiFunction::witness_template`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WitnessItem {
+    /// Position in the witness, 0 at the bottom of the stack
+    pub index: usize,
+    /// The `witnessSchema` element the item carries
+    pub name: String,
+    pub role: WitnessRole,
+    /// Arkade Script type string, as in the schema
+    #[serde(rename = "type")]
+    pub item_type: String,
+    /// Wire encoding, as in the schema
+    pub encoding: String,
+    /// Sighash flag a signature must be made with, as in the schema
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sighash: Option<u8>,
+}
+
+/// One `asm` token of a leaf and what it stands for; see
+/// [`AbiFunction::operations`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Operation {
+    /// The token, as in `asm`
+    pub op: String,
+    pub data: OperationData,
+}
+
+/// What an [`Operation`] is. Sizes are the bytes a value takes on the
+/// stack, given when its type fixes them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum OperationData {
+    /// An opcode, such as `OP_CHECKSIG`
+    Opcode,
+    /// A constant the script pushes: a number, minimally encoded, or data
+    Push { hex: String, size: usize },
+    /// A constructor argument, pushed once the contract is linked
+    ConstructorParam {
+        name: String,
+        #[serde(rename = "type")]
+        param_type: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        size: Option<usize>,
+    },
+    /// A `witnessSchema` element, which the spender supplies and the
+    /// script leaves out
+    FunctionInput {
+        name: String,
+        #[serde(rename = "type")]
+        param_type: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        size: Option<usize>,
+    },
+    /// A value the Ark runtime provides: the server key or the
+    /// scriptPubKey of a `<VTXO:...>` template
+    Runtime { name: String, size: usize },
+    /// A value the leaf computes and codegen refers to by name, such as a
+    /// `let` binding
+    Local { name: String },
 }
 
 /// Function definition in the ABI
@@ -95,6 +210,10 @@ pub struct AbiFunction {
     /// the tapscript leaf and not part of the witness.
     #[serde(rename = "witnessSchema")]
     pub witness_schema: Vec<WitnessElement>,
+    /// The witness stack the spender builds, bottom first: `witnessSchema`
+    /// in reverse, with what each item is
+    #[serde(rename = "witnessTemplate", default)]
+    pub witness_template: Vec<WitnessItem>,
     /// Whether this is a server variant
     #[serde(rename = "serverVariant")]
     pub server_variant: bool,
@@ -102,6 +221,114 @@ pub struct AbiFunction {
     pub require: Vec<RequireStatement>,
     /// Assembly instructions
     pub asm: Vec<String>,
+    /// Each `asm` token, described
+    #[serde(default)]
+    pub operations: Vec<Operation>,
+    /// Estimated size of the witness needed to spend this leaf
+    #[serde(rename = "witnessSize", default)]
+    pub witness_size: WitnessSize,
+    /// Estimated script bytes, witness bytes and weight of a spend of
+    /// this leaf
+    #[serde(rename = "sizeEstimate", default)]
+    pub size_estimate: SizeEstimate,
+    /// Events a spend of the function emits, for indexers
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub emits: Vec<AbiEmit>,
+    /// Identifier of the variant, from its signature; see [`selector`]
+    #[serde(default)]
+    pub selector: String,
+    /// Position of the leaf in the script tree, which is artifact order
+    #[serde(rename = "leafIndex", default)]
+    pub leaf_index: usize,
+    /// Where the leaf sits in the taproot output, set when linking with an
+    /// address
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub proof: Option<LeafProof>,
+    /// The serialized leaf script, hex, without the witness elements; set
+    /// once every other placeholder in `asm` is linked
+    #[serde(rename = "scriptHex", skip_serializing_if = "Option::is_none", default)]
+    pub script_hex: Option<String>,
+}
+
+/// What a control block for a leaf needs besides the internal key.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LeafProof {
+    /// Tapleaf hash of the leaf script, hex
+    #[serde(rename = "leafHash")]
+    pub leaf_hash: String,
+    /// Sibling hashes from the leaf up to the root, hex
+    #[serde(rename = "merklePath")]
+    pub merkle_path: Vec<String>,
+    /// Whether the output key's y coordinate is odd: the low bit of the
+    /// control block's first byte
+    pub parity: bool,
+    /// The whole control block, hex: leaf version and parity, internal
+    /// key, then `merkle_path`
+    #[serde(rename = "controlBlock", default)]
+    pub control_block: String,
+}
+
+/// Domain tag of [`ContractJson::compute_artifact_hash`].
+const ARTIFACT_HASH_TAG: &str = "arkade-artifact/v1";
+
+/// How a function variant is named in messages and selectors: `server`
+/// for the cooperative variant, `exit` for the unilateral one.
+pub fn variant_label(server_variant: bool) -> &'static str {
+    if server_variant {
+        "server"
+    } else {
+        "exit"
+    }
+}
+
+/// Selector of a function variant: the first four bytes, in hex, of the
+/// SHA-256 of its signature, `name(type,...)` with the declared input types,
+/// followed by `:server` or `:exit`. It changes only when the signature does.
+pub fn selector(name: &str, input_types: &[&str], server_variant: bool) -> String {
+    let signature = format!(
+        "{}({}):{}",
+        name,
+        input_types.join(","),
+        variant_label(server_variant)
+    );
+    hex::encode(&Sha256::digest(signature.as_bytes())[..4])
+}
+
+/// Estimated witness of a script-path spend, in bytes (= weight units).
+///
+/// Each part includes its CompactSize length prefix; `total` also counts the
+/// leading number of witness items.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WitnessSize {
+    /// Witness stack elements supplied by the spender
+    pub stack: usize,
+    /// The leaf script
+    pub script: usize,
+    /// The taproot control block
+    #[serde(rename = "controlBlock")]
+    pub control_block: usize,
+    /// The whole witness
+    pub total: usize,
+}
+
+impl WitnessSize {
+    /// Virtual size the witness adds to a transaction.
+    pub fn vbytes(&self) -> usize {
+        self.total.div_ceil(4)
+    }
+}
+
+/// What spending a leaf costs, for budgeting fees before the contract is
+/// instantiated.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SizeEstimate {
+    /// The leaf script, with constructor arguments at their usual size
+    pub script: usize,
+    /// The whole witness at its largest, as [`WitnessSize::total`]
+    pub witness: usize,
+    /// Weight units the input spending the leaf adds to a transaction: its
+    /// non-witness bytes count four times, its witness once
+    pub weight: usize,
 }
 
 /// JSON output for a contract
@@ -112,14 +339,506 @@ pub struct ContractJson {
     #[serde(rename = "constructorInputs")]
     pub parameters: Vec<Parameter>,
     pub functions: Vec<AbiFunction>,
+    /// Delay of the exit variants in blocks: the `exit` option, or
+    /// [`DEFAULT_EXIT_DELAY`] when the contract has none
+    #[serde(rename = "exitDelay", default = "default_exit_delay")]
+    pub exit_delay: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub source: Option<String>,
+    /// SHA-256 of `source`, hex, when it is normalized
+    /// (`CompileOptions::normalize_source`)
+    #[serde(
+        rename = "sourceHash",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub source_hash: Option<String>,
+    /// Fingerprint of the compiled scripts,
+    /// [`ContractJson::compute_artifact_hash`] of the artifact as compiled.
+    /// Linking keeps it, so a linked artifact names the one it came from.
+    #[serde(rename = "artifactHash", default)]
+    pub artifact_hash: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub compiler: Option<CompilerInfo>,
     #[serde(rename = "updatedAt", skip_serializing_if = "Option::is_none")]
     pub updated_at: Option<String>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub warnings: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub optimizations: Vec<OptimizationReport>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub metrics: Vec<LeafMetrics>,
+    /// Values the contract's generic parameters were fixed to
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub generics: BTreeMap<String, usize>,
+    /// Address of the contract's taproot output, once linked
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub address: Option<String>,
+    /// `tr()` descriptor of the contract's taproot output, once linked
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub descriptor: Option<String>,
+    /// x-only internal key of the taproot output, hex, when the contract
+    /// fixes one with the `internalKey` option
+    #[serde(
+        rename = "internalKey",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub internal_key: Option<String>,
+    /// Edition of the language the source is written in
+    #[serde(default)]
+    pub edition: Edition,
+    /// Events the contract declares, for indexers
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub events: Vec<AbiEvent>,
+    /// Every way the output can be spent, in one place
+    #[serde(
+        rename = "spendGraph",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub spend_graph: Option<SpendGraph>,
+    /// The script tree of the taproot output and the key it tweaks the
+    /// internal key into, once linked
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub taproot: Option<TaprootTree>,
+}
+
+/// A contract's taproot output: its keys and its script tree, with every
+/// node's hash.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TaprootTree {
+    /// x-only, hex
+    #[serde(rename = "internalKey")]
+    pub internal_key: String,
+    /// x-only, hex: the internal key tweaked by `merkle_root`
+    #[serde(rename = "outputKey")]
+    pub output_key: String,
+    /// Whether the output key's y coordinate is odd
+    pub parity: bool,
+    #[serde(rename = "merkleRoot")]
+    pub merkle_root: String,
+    pub root: TapNode,
+}
+
+/// A node of a taproot script tree.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum TapNode {
+    /// The leaf of the function variant at `leaf_index`, with its tapleaf
+    /// hash
+    Leaf {
+        #[serde(rename = "leafIndex")]
+        leaf_index: usize,
+        hash: String,
+    },
+    /// Two subtrees, with the tapbranch hash committing to both
+    Branch {
+        hash: String,
+        left: Box<TapNode>,
+        right: Box<TapNode>,
+    },
+}
+
+impl TapNode {
+    pub fn hash(&self) -> &str {
+        match self {
+            TapNode::Leaf { hash, .. } | TapNode::Branch { hash, .. } => hash,
+        }
+    }
+}
+
+/// The spending picture of a contract's taproot output: the key path, and
+/// each function's cooperative and exit leaves.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SpendGraph {
+    #[serde(rename = "keyPath")]
+    pub key_path: KeyPath,
+    /// One per public function, in declaration order
+    pub paths: Vec<SpendPath>,
+    /// Renewal timelock in blocks, from the `renew` option. The server
+    /// renews through the cooperative leaves; there is no leaf of its own.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub renew: Option<u64>,
+    /// `tr()` descriptor of the whole output, once linked
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub descriptor: Option<String>,
+}
+
+/// The key-path spend of the output.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct KeyPath {
+    /// x-only internal key, hex, or a `<name>` placeholder until linked
+    #[serde(rename = "internalKey")]
+    pub internal_key: String,
+    /// Whether someone can hold the internal key's secret: `false` for the
+    /// NUMS point and keys derived from it
+    pub spendable: bool,
+}
+
+/// A function's two leaves.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SpendPath {
+    pub function: String,
+    /// The cooperative leaf, signed by the server
+    pub server: PathLeaf,
+    /// The unilateral exit leaf, after `exitDelay` blocks
+    pub exit: PathLeaf,
+}
+
+/// A leaf of a [`SpendPath`], by its place in `functions` and the tree.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PathLeaf {
+    #[serde(rename = "leafIndex")]
+    pub leaf_index: usize,
+    pub selector: String,
+}
+
+impl SpendGraph {
+    /// The graph of `artifact`'s functions. `spendable` says whether the
+    /// internal key has a known secret; without an internal key the output
+    /// is keyed with the NUMS point.
+    pub fn new(artifact: &ContractJson, spendable: bool, renew: Option<u64>) -> Self {
+        let leaf = |name: &str, server_variant: bool| {
+            artifact
+                .functions
+                .iter()
+                .find(|f| f.name == name && f.server_variant == server_variant)
+                .map(|f| PathLeaf {
+                    leaf_index: f.leaf_index,
+                    selector: f.selector.clone(),
+                })
+        };
+        let mut paths: Vec<SpendPath> = Vec::new();
+        for function in &artifact.functions {
+            if paths.iter().any(|p| p.function == function.name) {
+                continue;
+            }
+            if let (Some(server), Some(exit)) =
+                (leaf(&function.name, true), leaf(&function.name, false))
+            {
+                paths.push(SpendPath {
+                    function: function.name.clone(),
+                    server,
+                    exit,
+                });
+            }
+        }
+        SpendGraph {
+            key_path: KeyPath {
+                internal_key: artifact
+                    .internal_key
+                    .clone()
+                    .unwrap_or_else(|| crate::taproot::NUMS_INTERNAL_KEY.into()),
+                spendable,
+            },
+            paths,
+            renew,
+            descriptor: artifact.descriptor.clone(),
+        }
+    }
+}
+
+/// Exit delay of a contract without an `exit` option: 48 hours of blocks.
+pub const DEFAULT_EXIT_DELAY: u64 = 288;
+
+fn default_exit_delay() -> u64 {
+    DEFAULT_EXIT_DELAY
+}
+
+/// A timelock written with a unit: `144 blocks`, `48h` or `2 days`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Duration {
+    Blocks(u64),
+    Seconds(u64),
+}
+
+impl Duration {
+    /// Parse a duration literal; `None` if `text` is not one or overflows.
+    pub fn parse(text: &str) -> Option<Duration> {
+        let digits = text.find(|c: char| !c.is_ascii_digit())?;
+        let (amount, unit) = text.split_at(digits);
+        let amount: u64 = amount.parse().ok()?;
+        match unit.trim_start() {
+            "blocks" | "block" => Some(Duration::Blocks(amount)),
+            "hours" | "hour" | "h" => amount.checked_mul(3600).map(Duration::Seconds),
+            "days" | "day" | "d" => amount.checked_mul(86400).map(Duration::Seconds),
+            _ => None,
+        }
+    }
+
+    /// The BIP68 sequence a relative timelock of this duration checks for:
+    /// a block count, or with [`SEQUENCE_TYPE_FLAG`] set, a number of
+    /// 512-second units, rounded up. Fails if it does not fit in 16 bits.
+    pub fn sequence(self) -> Result<u64, String> {
+        match self {
+            Duration::Blocks(blocks) if blocks <= 0xffff => Ok(blocks),
+            Duration::Blocks(blocks) => Err(format!(
+                "Relative timelock of {} blocks exceeds the maximum of 65535",
+                blocks
+            )),
+            Duration::Seconds(seconds) if seconds.div_ceil(512) <= 0xffff => {
+                Ok(SEQUENCE_TYPE_FLAG | seconds.div_ceil(512))
+            }
+            Duration::Seconds(seconds) => Err(format!(
+                "Relative timelock of {} seconds exceeds the maximum of {} (65535 units of 512 seconds)",
+                seconds,
+                0xffff * 512
+            )),
+        }
+    }
+}
+
+/// Bit of a BIP68 sequence that makes it count 512-second units rather
+/// than blocks.
+pub const SEQUENCE_TYPE_FLAG: u64 = 1 << 22;
+
+impl core::fmt::Display for Duration {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match *self {
+            Duration::Blocks(blocks) => write!(f, "{}", blocks),
+            Duration::Seconds(86400) => write!(f, "1 day"),
+            Duration::Seconds(seconds) if seconds % 86400 == 0 => {
+                write!(f, "{} days", seconds / 86400)
+            }
+            Duration::Seconds(3600) => write!(f, "1 hour"),
+            Duration::Seconds(seconds) => write!(f, "{} hours", seconds / 3600),
+        }
+    }
+}
+
+/// Hash function of a hash lock: `sha256(preimage) == hash` and its
+/// `hash160`, `ripemd160` and `hash256` counterparts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashFunction {
+    #[default]
+    Sha256,
+    /// RIPEMD-160 of SHA-256, as in P2PKH and most cross-chain HTLCs
+    Hash160,
+    Ripemd160,
+    /// SHA-256 applied twice
+    Hash256,
+}
+
+impl HashFunction {
+    pub const ALL: [HashFunction; 4] = [
+        HashFunction::Sha256,
+        HashFunction::Hash160,
+        HashFunction::Ripemd160,
+        HashFunction::Hash256,
+    ];
+
+    /// The built-in function name, as written in source.
+    pub fn name(self) -> &'static str {
+        match self {
+            HashFunction::Sha256 => "sha256",
+            HashFunction::Hash160 => "hash160",
+            HashFunction::Ripemd160 => "ripemd160",
+            HashFunction::Hash256 => "hash256",
+        }
+    }
+
+    /// The function named `name`, if it is one.
+    pub fn from_name(name: &str) -> Option<HashFunction> {
+        HashFunction::ALL.into_iter().find(|f| f.name() == name)
+    }
+
+    /// Split a call such as `hash160(secret)`, as kept in
+    /// [`Expression::Property`], into the function and its argument.
+    pub fn split_call(text: &str) -> Option<(HashFunction, &str)> {
+        let (name, rest) = text.trim().split_once('(')?;
+        let argument = rest.strip_suffix(')')?;
+        Some((HashFunction::from_name(name.trim())?, argument.trim()))
+    }
+
+    /// The opcode computing this hash.
+    pub fn opcode(self) -> &'static str {
+        match self {
+            HashFunction::Sha256 => crate::opcodes::OP_SHA256,
+            HashFunction::Hash160 => crate::opcodes::OP_HASH160,
+            HashFunction::Ripemd160 => crate::opcodes::OP_RIPEMD160,
+            HashFunction::Hash256 => crate::opcodes::OP_HASH256,
+        }
+    }
+
+    /// Size in bytes of the digest.
+    pub fn digest_size(self) -> usize {
+        match self {
+            HashFunction::Sha256 | HashFunction::Hash256 => 32,
+            HashFunction::Hash160 | HashFunction::Ripemd160 => 20,
+        }
+    }
+}
+
+impl core::fmt::Display for HashFunction {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Non-witness bytes of a transaction: version, locktime and the input and
+/// output counts.
+const TX_OVERHEAD_BYTES: usize = 10;
+
+/// Weight of the segwit marker and flag bytes.
+const SEGWIT_MARKER_WEIGHT: usize = 2;
+
+/// Non-witness bytes of an input: outpoint, empty scriptSig and sequence.
+pub(crate) const INPUT_BYTES: usize = 41;
+
+/// Bytes of a P2TR output: amount, script length and a 34-byte script.
+const P2TR_OUTPUT_BYTES: usize = 43;
+
+impl ContractJson {
+    /// The SHA-256, in hex, of what the artifact commits to on chain: the
+    /// contract name, the constructor inputs and every function variant's
+    /// name, `serverVariant` and `asm`, in artifact order, after the tag
+    /// `arkade-artifact/v1`. Strings are written as their UTF-8 length and
+    /// bytes, and lists as their length and items, each length 4 bytes
+    /// little-endian; `serverVariant` is one byte. The source, timestamps,
+    /// warnings and estimates are left out, so any build of the same
+    /// contract by the same compiler gives the same hash.
+    pub fn compute_artifact_hash(&self) -> String {
+        fn write(hasher: &mut Sha256, bytes: &[u8]) {
+            hasher.update((bytes.len() as u32).to_le_bytes());
+            hasher.update(bytes);
+        }
+        fn write_len(hasher: &mut Sha256, len: usize) {
+            hasher.update((len as u32).to_le_bytes());
+        }
+
+        let mut hasher = Sha256::new();
+        write(&mut hasher, ARTIFACT_HASH_TAG.as_bytes());
+        write(&mut hasher, self.name.as_bytes());
+        write_len(&mut hasher, self.parameters.len());
+        for param in &self.parameters {
+            write(&mut hasher, param.name.as_bytes());
+            write(&mut hasher, param.param_type.as_bytes());
+        }
+        write_len(&mut hasher, self.functions.len());
+        for function in &self.functions {
+            write(&mut hasher, function.name.as_bytes());
+            hasher.update([u8::from(function.server_variant)]);
+            write_len(&mut hasher, function.asm.len());
+            for token in &function.asm {
+                write(&mut hasher, token.as_bytes());
+            }
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    /// The artifact as pretty JSON with the keys of every object sorted,
+    /// so that equal artifacts serialize to the same bytes; see
+    /// `CompileOptions::reproducible`.
+    pub fn to_reproducible_json(&self) -> Result<String, String> {
+        let value = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        serde_json::to_string_pretty(&sort_keys(value)).map_err(|e| e.to_string())
+    }
+
+    /// The cooperative variant of function `name` when `server_variant` is
+    /// set, or its exit variant.
+    pub fn find_function(&self, name: &str, server_variant: bool) -> Result<&AbiFunction, String> {
+        self.functions
+            .iter()
+            .find(|f| f.name == name && f.server_variant == server_variant)
+            .ok_or_else(|| {
+                format!(
+                    "Contract {} has no {} variant of function '{}'",
+                    self.name,
+                    variant_label(server_variant),
+                    name
+                )
+            })
+    }
+
+    /// The function variant with the given [`selector`].
+    pub fn function_by_selector(&self, selector: &str) -> Option<&AbiFunction> {
+        self.functions.iter().find(|f| f.selector == selector)
+    }
+
+    /// Estimated virtual size of a transaction spending one VTXO of this
+    /// contract through `function` into a single P2TR output.
+    pub fn estimate_vsize(&self, function: &str, server_variant: bool) -> Result<usize, String> {
+        let abi = self.find_function(function, server_variant)?;
+
+        let base = TX_OVERHEAD_BYTES + INPUT_BYTES + P2TR_OUTPUT_BYTES;
+        let weight = base * 4 + SEGWIT_MARKER_WEIGHT + abi.witness_size.total;
+        Ok(weight.div_ceil(4))
+    }
+
+    /// Estimated fee in satoshis for the spend described by
+    /// [`estimate_vsize`](Self::estimate_vsize) at `feerate_sat_vb`, rounded up.
+    pub fn estimate_fee(
+        &self,
+        function: &str,
+        server_variant: bool,
+        feerate_sat_vb: f64,
+    ) -> Result<u64, String> {
+        if !feerate_sat_vb.is_finite() || feerate_sat_vb < 0.0 {
+            return Err(format!("Invalid fee rate: {} sat/vB", feerate_sat_vb));
+        }
+        let vsize = self.estimate_vsize(function, server_variant)?;
+        // `f64::ceil` needs std
+        let fee = vsize as f64 * feerate_sat_vb;
+        let whole = fee as u64;
+        Ok(if (whole as f64) < fee {
+            whole + 1
+        } else {
+            whole
+        })
+    }
+}
+
+/// `value` with the keys of every object in sorted order, however the map
+/// type of `serde_json` orders them.
+fn sort_keys(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let sorted: BTreeMap<String, serde_json::Value> =
+                map.into_iter().map(|(k, v)| (k, sort_keys(v))).collect();
+            serde_json::Value::Object(sorted.into_iter().collect())
+        }
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.into_iter().map(sort_keys).collect())
+        }
+        other => other,
+    }
+}
+
+/// Script size of one function variant before and after IR optimization
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct OptimizationReport {
+    pub function: String,
+    #[serde(rename = "serverVariant")]
+    pub server_variant: bool,
+    #[serde(rename = "sizeBefore")]
+    pub size_before: usize,
+    #[serde(rename = "sizeAfter")]
+    pub size_after: usize,
+}
+
+/// Operations executed by one function variant along its most expensive path
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct LeafMetrics {
+    pub function: String,
+    #[serde(rename = "serverVariant")]
+    pub server_variant: bool,
+    #[serde(flatten)]
+    pub resources: ResourceCounts,
+}
+
+/// Resource usage of a tapscript leaf
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceCounts {
+    /// Signature checks (`OP_CHECKSIG*`, `OP_CHECKSIGFROMSTACK*`)
+    pub sigops: usize,
+    /// Transaction and asset introspection opcodes
+    #[serde(rename = "introspectionOps")]
+    pub introspection_ops: usize,
+    /// 64-bit arithmetic and comparison opcodes
+    #[serde(rename = "arithmetic64Ops")]
+    pub arithmetic64_ops: usize,
 }
 
 /// Compiler information
@@ -129,16 +848,74 @@ pub struct CompilerInfo {
     pub version: String,
 }
 
-/// AST structures
-///
-/// These structures represent the parsed abstract syntax tree (AST)
-/// of an Arkade Script contract.
+// AST structures
+//
+// These structures represent the parsed abstract syntax tree (AST)
+// of an Arkade Script contract. They serialize under their Rust names,
+// source spans included, so tools can store and exchange parsed contracts
+// (`arkadec ast`).
+
+/// A version of the language, chosen with `pragma arkade <year>;` at the
+/// top of a source file. Later editions change defaults; a contract without
+/// a pragma is read in the first edition, so it compiles as it always has.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Edition {
+    #[default]
+    #[serde(rename = "2024")]
+    Edition2024,
+    /// Hash locks check the preimage size unless `strictHashlocks = false`
+    #[serde(rename = "2025")]
+    Edition2025,
+}
+
+impl Edition {
+    /// Every edition, oldest first.
+    pub const ALL: [Edition; 2] = [Edition::Edition2024, Edition::Edition2025];
+
+    /// The year that names the edition.
+    pub fn year(self) -> u16 {
+        match self {
+            Edition::Edition2024 => 2024,
+            Edition::Edition2025 => 2025,
+        }
+    }
+
+    /// The edition named `year`.
+    pub fn from_year(year: u16) -> Option<Edition> {
+        Edition::ALL
+            .into_iter()
+            .find(|edition| edition.year() == year)
+    }
+
+    /// Whether `strictHashlocks` is on when the options block does not set
+    /// it.
+    pub fn strict_hashlocks(self) -> bool {
+        self >= Edition::Edition2025
+    }
+}
+
+impl core::fmt::Display for Edition {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.year())
+    }
+}
 
 /// Contract AST
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Contract {
+    /// The edition the `pragma` selects, if the source has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edition: Option<Edition>,
+    /// The compiler versions the `pragma compiler` accepts, as written
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compiler: Option<String>,
     /// Contract name
     pub name: String,
+    /// Generic parameters (`contract Escrow<N>(...)`), fixed at compile time
+    pub generics: Vec<String>,
+    /// Options given by the name of a built-in constant or as a duration
+    /// (option to value, as written), resolved at compile time
+    pub named_options: BTreeMap<String, String>,
     /// Contract parameters
     pub parameters: Vec<Parameter>,
     /// Ark-specific renewal timelock (in blocks)
@@ -148,14 +925,224 @@ pub struct Contract {
     /// Whether this contract uses the Arkade operator key for the cooperative path.
     /// The operator key is always injected externally — it is never a constructor parameter.
     pub has_server_key: bool,
+    /// Name the `server` option gives the operator key, as written
+    pub server_key: Option<String>,
+    /// The `internalKey` option, as written
+    pub internal_key: Option<String>,
+    /// Whether hash locks also check that the preimage is 32 bytes: the
+    /// `strictHashlocks` option, or the edition's default
+    pub strict_hashlocks: bool,
+    /// Constants the contract declares (`const DUST = 546;`), substituted
+    /// at compile time
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub constants: BTreeMap<String, u64>,
+    /// Events, declared for indexers; they do not affect the script
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<Event>,
     /// Contract functions
     pub functions: Vec<Function>,
     /// Imported contract file paths (declared via `import "path.ark";`)
     pub imports: Vec<String>,
+    /// Comments around the contract, from the options block on
+    #[serde(default, skip_serializing_if = "Trivia::is_empty")]
+    pub trivia: Trivia,
+}
+
+impl Contract {
+    /// The edition the contract is written in: the pragma's, or the first.
+    pub fn edition(&self) -> Edition {
+        self.edition.unwrap_or_default()
+    }
+
+    /// The contract's interface as declared in source, without compiling it.
+    pub fn describe(&self) -> ContractDescription {
+        ContractDescription {
+            name: self.name.clone(),
+            parameters: self.parameters.clone(),
+            functions: self
+                .functions
+                .iter()
+                .filter(|f| !f.is_internal)
+                .map(|f| FunctionDescription {
+                    name: f.name.clone(),
+                    inputs: f.parameters.clone(),
+                })
+                .collect(),
+            has_server_key: self.has_server_key,
+            exit_timelock: self.exit_timelock,
+            imports: self.imports.clone(),
+            generics: self.generics.clone(),
+        }
+    }
+
+    /// The contract with the comments of it and its functions removed.
+    pub fn without_trivia(&self) -> Contract {
+        let mut contract = self.clone();
+        contract.trivia = Trivia::default();
+        for function in &mut contract.functions {
+            function.trivia = Trivia::default();
+        }
+        contract
+    }
+
+    /// Delay of the exit leaves in blocks: the `exit` option, or
+    /// [`DEFAULT_EXIT_DELAY`] when there is none.
+    pub fn exit_delay(&self) -> u64 {
+        self.exit_timelock.unwrap_or(DEFAULT_EXIT_DELAY)
+    }
+
+    /// The contract with its generic parameters fixed to `values`: every
+    /// array type sized by a generic parameter gets that length. Fails if a
+    /// generic parameter has no value or `values` names one the contract
+    /// does not declare.
+    pub fn specialize(&self, values: &BTreeMap<String, usize>) -> Result<Contract, String> {
+        if let Some(unknown) = values.keys().find(|k| !self.generics.contains(k)) {
+            return Err(format!(
+                "Contract {} has no generic parameter '{}'",
+                self.name, unknown
+            ));
+        }
+        let mut contract = self.clone();
+        if self.generics.is_empty() {
+            return Ok(contract);
+        }
+        for generic in &self.generics {
+            match values.get(generic) {
+                Some(0) => {
+                    return Err(format!(
+                        "Generic parameter '{}' must be at least 1",
+                        generic
+                    ))
+                }
+                Some(_) => {}
+                None => {
+                    return Err(format!(
+                        "Missing value for generic parameter '{}' of contract {}",
+                        generic, self.name
+                    ))
+                }
+            }
+        }
+
+        let fix = |param: &mut Parameter| {
+            if let Some((element, length)) = param
+                .param_type
+                .strip_suffix(']')
+                .and_then(|t| t.split_once('['))
+            {
+                if let Some(value) = values.get(length) {
+                    param.param_type = format!("{}[{}]", element, value);
+                }
+            }
+        };
+        contract.parameters.iter_mut().for_each(fix);
+        for function in &mut contract.functions {
+            function.parameters.iter_mut().for_each(fix);
+        }
+        contract.generics.clear();
+        Ok(contract)
+    }
+
+    /// Length of every array parameter of the contract and of `function`.
+    pub fn array_lengths(&self, function: &Function) -> BTreeMap<String, usize> {
+        self.parameters
+            .iter()
+            .chain(&function.parameters)
+            .filter_map(|p| array_type(&p.param_type).map(|(_, len)| (p.name.clone(), len)))
+            .collect()
+    }
+}
+
+/// Keys and threshold of a `checkMultisig`. Over a `pubkey[]` parameter
+/// the requirement holds the single key `name[]`, standing for every element
+/// of the array in `arrays` (name to length). A threshold of 0 means every
+/// key must sign.
+pub fn multisig_keys(
+    pubkeys: &[String],
+    threshold: u16,
+    arrays: &BTreeMap<String, usize>,
+) -> Result<(Vec<String>, u16), String> {
+    let array = match pubkeys {
+        [key] => key.strip_suffix("[]"),
+        _ => None,
+    };
+    let Some(array) = array else {
+        let threshold = if threshold == 0 {
+            pubkeys.len() as u16
+        } else {
+            threshold
+        };
+        return Ok((pubkeys.to_vec(), threshold));
+    };
+    let length = arrays
+        .get(array)
+        .copied()
+        .ok_or_else(|| format!("checkMultisig: '{}' is not an array parameter", array))?;
+    let keys = (0..length).map(|i| format!("{}_{}", array, i)).collect();
+    let threshold = if threshold == 0 {
+        length as u16
+    } else {
+        threshold
+    };
+    Ok((keys, threshold))
+}
+
+/// Outline of a contract read from its source alone.
+///
+/// Parameters are listed as declared: unlike the artifact's
+/// `constructorInputs`, arrays are not flattened and asset IDs are not split
+/// into `_txid`/`_gidx` pairs.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ContractDescription {
+    #[serde(rename = "contractName")]
+    pub name: String,
+    #[serde(rename = "constructorInputs")]
+    pub parameters: Vec<Parameter>,
+    /// Public functions, in declaration order
+    pub functions: Vec<FunctionDescription>,
+    #[serde(rename = "hasServerKey")]
+    pub has_server_key: bool,
+    /// Exit timelock from the options block, in blocks
+    #[serde(rename = "exitTimelock", skip_serializing_if = "Option::is_none")]
+    pub exit_timelock: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub imports: Vec<String>,
+    /// Generic parameters, to be fixed when compiling
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub generics: Vec<String>,
+}
+
+/// A public function's signature.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FunctionDescription {
+    pub name: String,
+    #[serde(rename = "functionInputs")]
+    pub inputs: Vec<Parameter>,
+}
+
+/// The contract's source, as [`crate::printer::print`] writes it
+impl core::fmt::Display for Contract {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&crate::printer::print(self))
+    }
+}
+
+/// `event Name(type field, ...);`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    pub name: String,
+    pub parameters: Vec<Parameter>,
+}
+
+/// `emit Name(arg, ...);`, each argument a function or contract parameter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Emit {
+    pub event: String,
+    pub args: Vec<String>,
 }
 
 /// Function AST
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Function {
     /// Function name
     pub name: String,
@@ -165,13 +1152,19 @@ pub struct Function {
     pub statements: Vec<Statement>,
     /// Whether this is an internal function
     pub is_internal: bool,
+    /// Events a spend of the function emits
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub emits: Vec<Emit>,
+    /// Comments around the function's declaration
+    #[serde(default, skip_serializing_if = "Trivia::is_empty")]
+    pub trivia: Trivia,
 }
 
 /// Statement AST - represents any executable statement in a function body
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Statement {
     /// require(expr, "message");
-    Require(Requirement),
+    Require(Requirement, Span),
     /// let name = expr;
     LetBinding { name: String, value: Expression },
     /// name = expr; (variable reassignment)
@@ -181,6 +1174,8 @@ pub enum Statement {
         condition: Expression,
         then_body: Vec<Statement>,
         else_body: Option<Vec<Statement>>,
+        /// Location of the condition
+        span: Span,
     },
     /// for (index_var, value_var) in iterable { body }
     ForIn {
@@ -191,11 +1186,241 @@ pub enum Statement {
     },
 }
 
+/// Location of a construct in the source text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Span {
+    /// Byte offset of the first character
+    pub start: usize,
+    /// Byte offset one past the last character
+    pub end: usize,
+    /// 1-based line of `start`
+    pub line: usize,
+    /// 1-based column of `start`
+    pub column: usize,
+}
+
+impl core::fmt::Display for Span {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Why a source failed to compile, located in the source where the
+/// compiler can tell, so it can be shown as a caret diagnostic
+/// ([`CompileError::render`]).
+///
+/// Displays as the message the compiler always reported, and converts into
+/// it, so code that handled errors as a `String` keeps working.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum CompileError {
+    /// The source does not parse
+    Parse(ErrorDetail),
+    /// The source parses, but is not a contract the compiler accepts
+    Compile(ErrorDetail),
+}
+
+/// What a [`CompileError`] reports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorDetail {
+    pub message: String,
+    /// Stable code such as `E0001`, explained by `arkadec --explain`
+    pub code: String,
+    /// Where the problem is, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+    /// The source line `span` starts on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+}
+
+impl ErrorDetail {
+    fn new(source: &str, stage: &str, message: String, span: Option<Span>) -> Self {
+        ErrorDetail {
+            code: crate::diagnostics::classify(stage, &message).to_string(),
+            snippet: span
+                .and_then(|s| source.lines().nth(s.line.checked_sub(1)?))
+                .map(String::from),
+            message,
+            span,
+        }
+    }
+}
+
+impl CompileError {
+    /// A syntax error in `source`, at `span` if known.
+    pub fn parse(source: &str, message: impl Into<String>, span: Option<Span>) -> Self {
+        CompileError::Parse(ErrorDetail::new(source, "parse", message.into(), span))
+    }
+
+    /// An error found after parsing `source`, located at the first
+    /// `line:column` its message gives, if any.
+    pub fn compile(source: &str, message: impl Into<String>) -> Self {
+        let message = message.into();
+        let span = locate(source, &message);
+        CompileError::Compile(ErrorDetail::new(source, "compile", message, span))
+    }
+
+    pub fn detail(&self) -> &ErrorDetail {
+        match self {
+            CompileError::Parse(detail) | CompileError::Compile(detail) => detail,
+        }
+    }
+
+    /// `parse` or `compile`, as in [`Diagnostic::code`](crate::diagnostics::Diagnostic::code)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CompileError::Parse(_) => "parse",
+            CompileError::Compile(_) => "compile",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.detail().message
+    }
+
+    pub fn code(&self) -> &str {
+        &self.detail().code
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.detail().span
+    }
+
+    pub fn snippet(&self) -> Option<&str> {
+        self.detail().snippet.as_deref()
+    }
+
+    /// The source line of the error with its span underlined, if located:
+    ///
+    /// ```text
+    ///  --> 9:5
+    ///   |
+    /// 9 |     require(checkSig(ownerSig, ghost));
+    ///   |     ^^^^^^^
+    /// ```
+    pub fn excerpt(&self) -> Option<String> {
+        let (span, snippet) = (self.span()?, self.snippet()?);
+        let column = snippet
+            .char_indices()
+            .nth(span.column.saturating_sub(1))
+            .map_or(snippet.len(), |(i, _)| i);
+        let width = (span.end - span.start).min(snippet.len() - column);
+        // Keep tabs, so the carets line up under them
+        let indent: String = snippet[..column]
+            .chars()
+            .map(|c| if c == '\t' { c } else { ' ' })
+            .collect();
+        let carets = "^".repeat(snippet[column..column + width].chars().count().max(1));
+        let gutter = " ".repeat(span.line.to_string().len());
+        Some(format!(
+            "{gutter}--> {span}\n{gutter} |\n{} | {snippet}\n{gutter} | {indent}{carets}",
+            span.line
+        ))
+    }
+
+    /// The error as a caret diagnostic: `error[CODE]: message`, then the
+    /// [`excerpt`](Self::excerpt) if located.
+    pub fn render(&self) -> String {
+        let header = format!("error[{}]: {}", self.code(), self.message());
+        match self.excerpt() {
+            Some(excerpt) => format!("{}\n{}", header, excerpt),
+            None => header,
+        }
+    }
+}
+
+impl core::fmt::Display for CompileError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CompileError::Parse(ErrorDetail {
+                message,
+                span: Some(span),
+                ..
+            }) => write!(f, "Parse error at {}: {}", span, message),
+            CompileError::Parse(detail) => write!(f, "Parse error: {}", detail.message),
+            CompileError::Compile(detail) => f.write_str(&detail.message),
+        }
+    }
+}
+
+impl core::error::Error for CompileError {}
+
+impl From<CompileError> for String {
+    fn from(error: CompileError) -> String {
+        error.to_string()
+    }
+}
+
+/// The span of the first `at line:column` in `message`, through the end
+/// of the name there.
+fn locate(source: &str, message: &str) -> Option<Span> {
+    message.match_indices(" at ").find_map(|(i, _)| {
+        let rest = &message[i + 4..];
+        let end = rest
+            .find(|c: char| !c.is_ascii_digit() && c != ':')
+            .unwrap_or(rest.len());
+        let (line, column) = rest[..end].trim_end_matches(':').split_once(':')?;
+        span_at(source, line.parse().ok()?, column.parse().ok()?)
+    })
+}
+
+/// The span of the name at 1-based `line` and `column` of `source`, or of
+/// the one character there.
+pub(crate) fn span_at(source: &str, line: usize, column: usize) -> Option<Span> {
+    let line_start = match line {
+        0 => return None,
+        1 => 0,
+        _ => source.match_indices('\n').nth(line - 2)?.0 + 1,
+    };
+    let text = source[line_start..].split('\n').next()?;
+    let (offset, first) = text.char_indices().nth(column.checked_sub(1)?)?;
+    let is_name = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let length = if is_name(first) {
+        text[offset..]
+            .find(|c| !is_name(c))
+            .unwrap_or(text.len() - offset)
+    } else {
+        first.len_utf8()
+    };
+    Some(Span {
+        start: line_start + offset,
+        end: line_start + offset + length,
+        line,
+        column,
+    })
+}
+
+/// Comments attached to an AST node, without the `//` and the space after
+/// it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Trivia {
+    /// Comment lines directly above the node
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub leading: Vec<String>,
+    /// Comment at the end of the node's first line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trailing: Option<String>,
+}
+
+impl Trivia {
+    /// Whether there are no comments.
+    pub fn is_empty(&self) -> bool {
+        self.leading.is_empty() && self.trailing.is_none()
+    }
+}
+
 /// Requirement AST
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Requirement {
-    /// Check signature requirement
-    CheckSig { signature: String, pubkey: String },
+    /// Check signature requirement; `sighash` pins the sighash flag the
+    /// signature must be made with
+    CheckSig {
+        signature: String,
+        pubkey: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sighash: Option<u8>,
+    },
     /// Check signature from stack requirement (signature verified against a message)
     CheckSigFromStack {
         signature: String,
@@ -207,13 +1432,29 @@ pub enum Requirement {
         pubkeys: Vec<String>,
         threshold: u16,
     },
-    /// After requirement
+    /// Absolute timelock requirement: `tx.time >= locktime`, or the value
+    /// of `timelock_var`, against nLockTime; a block height below
+    /// 500000000, a timestamp from it
     After {
-        blocks: u64,
+        locktime: u64,
         timelock_var: Option<String>,
     },
-    /// Hash equal requirement
-    HashEqual { preimage: String, hash: String },
+    /// Relative timelock requirement: `tx.age >= age`, or the value of
+    /// `timelock_var` in blocks
+    Older {
+        age: Duration,
+        timelock_var: Option<String>,
+    },
+    /// Hash equal requirement: `function(preimage) == hash`
+    HashEqual {
+        preimage: String,
+        hash: String,
+        #[serde(default)]
+        function: HashFunction,
+    },
+    /// Size bounds requirement: `size(value) between min and max`, both
+    /// bounds inclusive, in bytes
+    SizeBetween { value: String, min: u64, max: u64 },
     /// Comparison requirement
     Comparison {
         left: Expression,
@@ -223,7 +1464,7 @@ pub enum Requirement {
 }
 
 /// Source of an asset lookup (input or output)
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AssetLookupSource {
     /// tx.inputs[i]
     Input,
@@ -232,7 +1473,7 @@ pub enum AssetLookupSource {
 }
 
 /// Source of an asset group sum (inputs or outputs)
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum GroupSumSource {
     /// sumInputs (source=0)
     Inputs,
@@ -241,7 +1482,7 @@ pub enum GroupSumSource {
 }
 
 /// Source for per-group input/output access
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum GroupIOSource {
     /// inputs (source=0)
     Inputs,
@@ -250,7 +1491,7 @@ pub enum GroupIOSource {
 }
 
 /// Expression AST
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Expression {
     /// Variable reference
     Variable(String),
@@ -296,6 +1537,8 @@ pub enum Expression {
         op: String,
         right: Box<Expression>,
     },
+    /// Negation of a condition: !value
+    Not { value: Box<Expression> },
     /// Asset group find: tx.assetGroups.find(assetId) → csn index
     GroupFind { asset_id: String },
     /// Asset group property: group.sumInputs, group.delta, etc.
@@ -329,6 +1572,9 @@ pub enum Expression {
     ArrayLength(String),
     /// CheckSig expression result (for use in if conditions)
     CheckSigExpr { signature: String, pubkey: String },
+    /// checkSigCount([pkA, pkB]): how many of the keys signed, from the
+    /// same CHECKSIG/CHECKSIGADD chain as checkMultisig
+    CheckSigCount { pubkeys: Vec<String> },
     /// CheckSigFromStack expression result
     CheckSigFromStackExpr {
         signature: String,
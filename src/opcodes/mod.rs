@@ -32,7 +32,9 @@ pub const OP_CHECKSIGADD: &str = "OP_CHECKSIGADD";
 
 // Comparisons
 pub const OP_EQUAL: &str = "OP_EQUAL";
+pub const OP_EQUALVERIFY: &str = "OP_EQUALVERIFY";
 pub const OP_NUMEQUAL: &str = "OP_NUMEQUAL";
+pub const OP_NUMEQUALVERIFY: &str = "OP_NUMEQUALVERIFY";
 pub const OP_GREATERTHANOREQUAL: &str = "OP_GREATERTHANOREQUAL";
 pub const OP_GREATERTHANOREQUAL64: &str = "OP_GREATERTHANOREQUAL64";
 pub const OP_LESSTHANOREQUAL: &str = "OP_LESSTHANOREQUAL";
@@ -41,9 +43,13 @@ pub const OP_GREATERTHAN: &str = "OP_GREATERTHAN";
 pub const OP_GREATERTHAN64: &str = "OP_GREATERTHAN64";
 pub const OP_LESSTHAN: &str = "OP_LESSTHAN";
 pub const OP_LESSTHAN64: &str = "OP_LESSTHAN64";
+pub const OP_WITHIN: &str = "OP_WITHIN";
 
 // Cryptography
 pub const OP_SHA256: &str = "OP_SHA256";
+pub const OP_HASH256: &str = "OP_HASH256";
+pub const OP_RIPEMD160: &str = "OP_RIPEMD160";
+pub const OP_HASH160: &str = "OP_HASH160";
 pub const OP_SHA256UPDATE: &str = "OP_SHA256UPDATE";
 pub const OP_SHA256INITIALIZE: &str = "OP_SHA256INITIALIZE";
 pub const OP_SHA256FINALIZE: &str = "OP_SHA256FINALIZE";
@@ -52,6 +58,15 @@ pub const OP_SHA256FINALIZE: &str = "OP_SHA256FINALIZE";
 pub const OP_DROP: &str = "OP_DROP";
 pub const OP_DUP: &str = "OP_DUP";
 pub const OP_NIP: &str = "OP_NIP";
+pub const OP_OVER: &str = "OP_OVER";
+pub const OP_PICK: &str = "OP_PICK";
+pub const OP_ROLL: &str = "OP_ROLL";
+pub const OP_ROT: &str = "OP_ROT";
+pub const OP_SWAP: &str = "OP_SWAP";
+pub const OP_TOALTSTACK: &str = "OP_TOALTSTACK";
+pub const OP_FROMALTSTACK: &str = "OP_FROMALTSTACK";
+pub const OP_SIZE: &str = "OP_SIZE";
+pub const OP_CAT: &str = "OP_CAT";
 
 // Type conversions
 pub const OP_LE64TOSCRIPTNUM: &str = "OP_LE64TOSCRIPTNUM";
@@ -64,8 +79,11 @@ pub const OP_TWEAKVERIFY: &str = "OP_TWEAKVERIFY";
 
 // Conditionals
 pub const OP_NOT: &str = "OP_NOT";
+pub const OP_BOOLAND: &str = "OP_BOOLAND";
+pub const OP_BOOLOR: &str = "OP_BOOLOR";
 pub const OP_FALSE: &str = "OP_FALSE";
 pub const OP_IF: &str = "OP_IF";
+pub const OP_NOTIF: &str = "OP_NOTIF";
 pub const OP_ENDIF: &str = "OP_ENDIF";
 pub const OP_ELSE: &str = "OP_ELSE";
 
@@ -113,3 +131,246 @@ pub const OP_INPUTBYTECODE: &str = "OP_INPUTBYTECODE";
 pub const OP_INPUTVALUE: &str = "OP_INPUTVALUE";
 pub const OP_INPUTSEQUENCE: &str = "OP_INPUTSEQUENCE";
 pub const OP_INPUTOUTPOINT: &str = "OP_INPUTOUTPOINT";
+
+/// The byte an opcode is serialized as in a leaf script.
+///
+/// Bitcoin opcodes take their standard values and the introspection,
+/// streaming SHA256, 64-bit arithmetic and elliptic curve opcodes the
+/// Elements tapscript values. Opcodes with no settled encoding yet, such as
+/// the asset group opcodes, give `None`.
+pub fn opcode_byte(op: &str) -> Option<u8> {
+    let byte = match op {
+        "OP_0" | "OP_FALSE" => 0x00,
+        "OP_1NEGATE" => 0x4f,
+        "OP_1" | "OP_TRUE" => 0x51,
+        "OP_2" => 0x52,
+        "OP_3" => 0x53,
+        "OP_4" => 0x54,
+        "OP_5" => 0x55,
+        "OP_6" => 0x56,
+        "OP_7" => 0x57,
+        "OP_8" => 0x58,
+        "OP_9" => 0x59,
+        "OP_10" => 0x5a,
+        "OP_11" => 0x5b,
+        "OP_12" => 0x5c,
+        "OP_13" => 0x5d,
+        "OP_14" => 0x5e,
+        "OP_15" => 0x5f,
+        "OP_16" => 0x60,
+        "OP_NOP" => 0x61,
+        "OP_IF" => 0x63,
+        "OP_NOTIF" => 0x64,
+        "OP_ELSE" => 0x67,
+        "OP_ENDIF" => 0x68,
+        "OP_VERIFY" => 0x69,
+        "OP_RETURN" => 0x6a,
+        "OP_TOALTSTACK" => 0x6b,
+        "OP_FROMALTSTACK" => 0x6c,
+        "OP_2DROP" => 0x6d,
+        "OP_2DUP" => 0x6e,
+        "OP_DEPTH" => 0x74,
+        "OP_DROP" => 0x75,
+        "OP_DUP" => 0x76,
+        "OP_NIP" => 0x77,
+        "OP_OVER" => 0x78,
+        "OP_PICK" => 0x79,
+        "OP_ROLL" => 0x7a,
+        "OP_ROT" => 0x7b,
+        "OP_SWAP" => 0x7c,
+        "OP_TUCK" => 0x7d,
+        "OP_CAT" => 0x7e,
+        "OP_SIZE" => 0x82,
+        "OP_EQUAL" => 0x87,
+        "OP_EQUALVERIFY" => 0x88,
+        "OP_1ADD" => 0x8b,
+        "OP_1SUB" => 0x8c,
+        "OP_NEGATE" => 0x8f,
+        "OP_ABS" => 0x90,
+        "OP_NOT" => 0x91,
+        "OP_0NOTEQUAL" => 0x92,
+        "OP_ADD" => 0x93,
+        "OP_SUB" => 0x94,
+        "OP_BOOLAND" => 0x9a,
+        "OP_BOOLOR" => 0x9b,
+        "OP_NUMEQUAL" => 0x9c,
+        "OP_NUMEQUALVERIFY" => 0x9d,
+        "OP_NUMNOTEQUAL" => 0x9e,
+        "OP_LESSTHAN" => 0x9f,
+        "OP_GREATERTHAN" => 0xa0,
+        "OP_LESSTHANOREQUAL" => 0xa1,
+        "OP_GREATERTHANOREQUAL" => 0xa2,
+        "OP_MIN" => 0xa3,
+        "OP_MAX" => 0xa4,
+        "OP_WITHIN" => 0xa5,
+        "OP_RIPEMD160" => 0xa6,
+        "OP_SHA1" => 0xa7,
+        "OP_SHA256" => 0xa8,
+        "OP_HASH160" => 0xa9,
+        "OP_HASH256" => 0xaa,
+        "OP_CHECKSIG" => 0xac,
+        "OP_CHECKSIGVERIFY" => 0xad,
+        "OP_CHECKMULTISIG" => 0xae,
+        "OP_CHECKMULTISIGVERIFY" => 0xaf,
+        "OP_CHECKLOCKTIMEVERIFY" => 0xb1,
+        "OP_CHECKSEQUENCEVERIFY" => 0xb2,
+        "OP_CHECKSIGADD" => 0xba,
+        "OP_CHECKSIGFROMSTACK" => 0xc1,
+        "OP_CHECKSIGFROMSTACKVERIFY" => 0xc2,
+        "OP_SHA256INITIALIZE" => 0xc4,
+        "OP_SHA256UPDATE" => 0xc5,
+        "OP_SHA256FINALIZE" => 0xc6,
+        "OP_INSPECTINPUTOUTPOINT" => 0xc7,
+        "OP_INSPECTINPUTVALUE" => 0xc9,
+        "OP_INSPECTINPUTSCRIPTPUBKEY" => 0xca,
+        "OP_INSPECTINPUTSEQUENCE" => 0xcb,
+        "OP_INSPECTINPUTISSUANCE" => 0xcc,
+        "OP_PUSHCURRENTINPUTINDEX" => 0xcd,
+        "OP_INSPECTOUTPUTVALUE" => 0xcf,
+        "OP_INSPECTOUTPUTNONCE" => 0xd0,
+        "OP_INSPECTOUTPUTSCRIPTPUBKEY" => 0xd1,
+        "OP_INSPECTVERSION" => 0xd2,
+        "OP_INSPECTLOCKTIME" => 0xd3,
+        "OP_INSPECTNUMINPUTS" => 0xd4,
+        "OP_INSPECTNUMOUTPUTS" => 0xd5,
+        "OP_TXWEIGHT" => 0xd6,
+        "OP_ADD64" => 0xd7,
+        "OP_SUB64" => 0xd8,
+        "OP_MUL64" => 0xd9,
+        "OP_DIV64" => 0xda,
+        "OP_NEG64" => 0xdb,
+        "OP_LESSTHAN64" => 0xdc,
+        "OP_LESSTHANOREQUAL64" => 0xdd,
+        "OP_GREATERTHAN64" => 0xde,
+        "OP_GREATERTHANOREQUAL64" => 0xdf,
+        "OP_SCRIPTNUMTOLE64" => 0xe0,
+        "OP_LE64TOSCRIPTNUM" => 0xe1,
+        "OP_LE32TOLE64" => 0xe2,
+        "OP_ECMULSCALARVERIFY" => 0xe3,
+        "OP_TWEAKVERIFY" => 0xe4,
+        _ => return None,
+    };
+    Some(byte)
+}
+
+/// Every opcode with an encoding, under the name [`opcode_name`] gives it.
+const ENCODED: &[&str] = &[
+    "OP_0",
+    "OP_1NEGATE",
+    "OP_1",
+    "OP_2",
+    "OP_3",
+    "OP_4",
+    "OP_5",
+    "OP_6",
+    "OP_7",
+    "OP_8",
+    "OP_9",
+    "OP_10",
+    "OP_11",
+    "OP_12",
+    "OP_13",
+    "OP_14",
+    "OP_15",
+    "OP_16",
+    "OP_NOP",
+    "OP_IF",
+    "OP_NOTIF",
+    "OP_ELSE",
+    "OP_ENDIF",
+    "OP_VERIFY",
+    "OP_RETURN",
+    "OP_TOALTSTACK",
+    "OP_FROMALTSTACK",
+    "OP_2DROP",
+    "OP_2DUP",
+    "OP_DEPTH",
+    "OP_DROP",
+    "OP_DUP",
+    "OP_NIP",
+    "OP_OVER",
+    "OP_PICK",
+    "OP_ROLL",
+    "OP_ROT",
+    "OP_SWAP",
+    "OP_TUCK",
+    "OP_CAT",
+    "OP_SIZE",
+    "OP_EQUAL",
+    "OP_EQUALVERIFY",
+    "OP_1ADD",
+    "OP_1SUB",
+    "OP_NEGATE",
+    "OP_ABS",
+    "OP_NOT",
+    "OP_0NOTEQUAL",
+    "OP_ADD",
+    "OP_SUB",
+    "OP_BOOLAND",
+    "OP_BOOLOR",
+    "OP_NUMEQUAL",
+    "OP_NUMEQUALVERIFY",
+    "OP_NUMNOTEQUAL",
+    "OP_LESSTHAN",
+    "OP_GREATERTHAN",
+    "OP_LESSTHANOREQUAL",
+    "OP_GREATERTHANOREQUAL",
+    "OP_MIN",
+    "OP_MAX",
+    "OP_WITHIN",
+    "OP_RIPEMD160",
+    "OP_SHA1",
+    "OP_SHA256",
+    "OP_HASH160",
+    "OP_HASH256",
+    "OP_CHECKSIG",
+    "OP_CHECKSIGVERIFY",
+    "OP_CHECKMULTISIG",
+    "OP_CHECKMULTISIGVERIFY",
+    "OP_CHECKLOCKTIMEVERIFY",
+    "OP_CHECKSEQUENCEVERIFY",
+    "OP_CHECKSIGADD",
+    "OP_CHECKSIGFROMSTACK",
+    "OP_CHECKSIGFROMSTACKVERIFY",
+    "OP_SHA256INITIALIZE",
+    "OP_SHA256UPDATE",
+    "OP_SHA256FINALIZE",
+    "OP_INSPECTINPUTOUTPOINT",
+    "OP_INSPECTINPUTVALUE",
+    "OP_INSPECTINPUTSCRIPTPUBKEY",
+    "OP_INSPECTINPUTSEQUENCE",
+    "OP_INSPECTINPUTISSUANCE",
+    "OP_PUSHCURRENTINPUTINDEX",
+    "OP_INSPECTOUTPUTVALUE",
+    "OP_INSPECTOUTPUTNONCE",
+    "OP_INSPECTOUTPUTSCRIPTPUBKEY",
+    "OP_INSPECTVERSION",
+    "OP_INSPECTLOCKTIME",
+    "OP_INSPECTNUMINPUTS",
+    "OP_INSPECTNUMOUTPUTS",
+    "OP_TXWEIGHT",
+    "OP_ADD64",
+    "OP_SUB64",
+    "OP_MUL64",
+    "OP_DIV64",
+    "OP_NEG64",
+    "OP_LESSTHAN64",
+    "OP_LESSTHANOREQUAL64",
+    "OP_GREATERTHAN64",
+    "OP_GREATERTHANOREQUAL64",
+    "OP_SCRIPTNUMTOLE64",
+    "OP_LE64TOSCRIPTNUM",
+    "OP_LE32TOLE64",
+    "OP_ECMULSCALARVERIFY",
+    "OP_TWEAKVERIFY",
+];
+
+/// The opcode serialized as `byte`, the inverse of [`opcode_byte`]. Bytes
+/// with more than one name, such as `OP_0` and `OP_FALSE`, get the first
+/// one; data pushes and bytes no encoded opcode uses give `None`.
+pub fn opcode_name(byte: u8) -> Option<&'static str> {
+    ENCODED
+        .iter()
+        .copied()
+        .find(|op| opcode_byte(op) == Some(byte))
+}
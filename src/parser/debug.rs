@@ -5,6 +5,10 @@ use std::fs;
 
 #[derive(Parser)]
 #[grammar = "parser/grammar.pest"]
+#[grammar = "parser/assets.pest"]
+#[grammar = "parser/introspection.pest"]
+#[grammar = "parser/loops.pest"]
+#[grammar = "parser/opcodes.pest"]
 pub struct ArkadeParser;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
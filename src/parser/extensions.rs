@@ -0,0 +1,165 @@
+//! Grammar extensions and the targets that support them.
+//!
+//! The grammar is split into a core, which compiles to plain tapscript, and
+//! the extensions in `assets.pest`, `introspection.pest`, `loops.pest` and
+//! `opcodes.pest`. The parser accepts all of them; [`check`] then rejects
+//! any use of an extension the [`ScriptTarget`] does not support, pointing
+//! at the offending syntax.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::fmt;
+use pest::error::{Error, ErrorVariant};
+use pest::iterators::{Pair, Pairs};
+use serde::Serialize;
+
+use super::Rule;
+
+/// A grammar extension on top of the core language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Extension {
+    /// Asset lookups and asset groups
+    Assets,
+    /// Transaction, input and output introspection
+    Introspection,
+    /// `for` loops, unrolled at compile time
+    Loops,
+    /// Arkade-only opcodes such as `checkSigFromStack` and `tweakVerify`
+    Opcodes,
+}
+
+impl Extension {
+    fn description(self) -> &'static str {
+        match self {
+            Extension::Assets => "asset introspection",
+            Extension::Introspection => "transaction introspection",
+            Extension::Loops => "a for loop",
+            Extension::Opcodes => "an Arkade opcode",
+        }
+    }
+
+    /// The extension a rule belongs to, if any. Comparisons are not mapped
+    /// themselves, so a use is reported at the operand that needs it.
+    fn of(pair: &Pair<Rule>) -> Option<Extension> {
+        match pair.as_rule() {
+            Rule::asset_lookup
+            | Rule::asset_count
+            | Rule::asset_at
+            | Rule::asset_group_access
+            | Rule::group_property_comparison
+            | Rule::identifier_property_access => Some(Extension::Assets),
+            Rule::tx_introspection
+            | Rule::input_introspection
+            | Rule::output_introspection
+            | Rule::this_property_access
+            | Rule::constructor => Some(Extension::Introspection),
+            // `tx.time` and `tx.age` are the core timelocks; every other
+            // property is not
+            Rule::tx_property_access => match compact(pair.as_str()).as_str() {
+                "tx.time" | "tx.age" => None,
+                p if p.starts_with("tx.assetGroups") => Some(Extension::Assets),
+                _ => Some(Extension::Introspection),
+            },
+            Rule::for_stmt => Some(Extension::Loops),
+            Rule::check_sig_from_stack
+            | Rule::check_sig_from_stack_verify
+            | Rule::sha256_initialize
+            | Rule::sha256_update
+            | Rule::sha256_finalize
+            | Rule::neg64_func
+            | Rule::le64_to_script_num
+            | Rule::le32_to_le64
+            | Rule::ec_mul_scalar_verify
+            | Rule::tweak_verify => Some(Extension::Opcodes),
+            _ => None,
+        }
+    }
+}
+
+/// The script flavour a contract is compiled for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScriptTarget {
+    /// Arkade Script, with every extension
+    #[default]
+    Arkade,
+    /// Plain tapscript: the core language, with loops unrolled
+    Tapscript,
+}
+
+impl ScriptTarget {
+    /// Every target, the default first
+    pub const ALL: [ScriptTarget; 2] = [ScriptTarget::Arkade, ScriptTarget::Tapscript];
+
+    /// Whether contracts for this target may use `extension`.
+    pub fn supports(self, extension: Extension) -> bool {
+        match self {
+            ScriptTarget::Arkade => true,
+            ScriptTarget::Tapscript => extension == Extension::Loops,
+        }
+    }
+}
+
+impl fmt::Display for ScriptTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ScriptTarget::Arkade => "arkade",
+            ScriptTarget::Tapscript => "tapscript",
+        })
+    }
+}
+
+impl core::str::FromStr for ScriptTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "arkade" => Ok(ScriptTarget::Arkade),
+            "tapscript" => Ok(ScriptTarget::Tapscript),
+            _ => Err(format!(
+                "unknown script target '{}' (expected arkade or tapscript)",
+                s
+            )),
+        }
+    }
+}
+
+/// Reject the first use, in source order, of an extension `target` does
+/// not support.
+pub(super) fn check(pairs: Pairs<Rule>, target: ScriptTarget) -> Result<(), Error<Rule>> {
+    for pair in pairs {
+        if let Some(extension) = Extension::of(&pair) {
+            if !target.supports(extension) {
+                let message = format!(
+                    "`{}` uses {}, which is not available on this target ({})",
+                    snippet(pair.as_str()),
+                    extension.description(),
+                    target
+                );
+                return Err(Error::new_from_span(
+                    ErrorVariant::CustomError { message },
+                    pair.as_span(),
+                ));
+            }
+        }
+        check(pair.into_inner(), target)?;
+    }
+    Ok(())
+}
+
+/// `text` without whitespace.
+fn compact(text: &str) -> String {
+    text.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+/// The first line of `text`, for quoting in a message.
+fn snippet(text: &str) -> String {
+    match text.lines().next() {
+        Some(line) if line.len() < text.len() => format!("{} ...", line.trim_end()),
+        Some(line) => line.to_string(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,175 @@
+//! Limits on the size of the source a parse accepts.
+//!
+//! The grammar and the AST builder recurse once per nesting level, so
+//! adversarial input such as thousands of nested parentheses would overflow
+//! the stack, and very large input takes long to parse. [`ParseLimits`]
+//! rejects both up front with an error instead.
+//!
+//! A chain of `else if` branches nests an `if` in the else branch of the
+//! one before it, so it counts towards the nesting depth.
+//!
+//! A chain of operators nests too: `a + b + c` is `(a + b) + c`, and the
+//! code generator recurses once per operator. The default limits keep the
+//! deepest recursion they allow within a 2 MiB thread stack, the default
+//! for spawned threads, so compiling on worker threads cannot overflow.
+
+use alloc::format;
+use pest::iterators::{Pair, Pairs};
+
+use super::{ParseError, Rule};
+use crate::models::Span;
+
+/// Limits on the source a parse accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    /// Most bytes of source
+    pub max_source_bytes: usize,
+    /// Most levels of nested parentheses, brackets and braces, and most
+    /// `else if` branches in one chain, each of which nests an `if`
+    pub max_depth: usize,
+    /// Most expressions, counting every operand, operator and
+    /// sub-expression in the syntax tree
+    pub max_expressions: usize,
+    /// Most arithmetic operators on one path down an expression, each of
+    /// which nests the syntax tree a level deeper
+    pub max_operator_depth: usize,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        ParseLimits {
+            max_source_bytes: 256 * 1024,
+            max_depth: 32,
+            max_expressions: 50_000,
+            max_operator_depth: 128,
+        }
+    }
+}
+
+impl ParseLimits {
+    /// Check the source's size and nesting, before it is parsed.
+    pub(super) fn check_source(&self, source: &str) -> Result<(), ParseError> {
+        if source.len() > self.max_source_bytes {
+            return Err(format!(
+                "Source is {} bytes, more than the limit of {}",
+                source.len(),
+                self.max_source_bytes
+            )
+            .into());
+        }
+
+        let mut depth = 0usize;
+        let (mut line, mut column) = (1, 0);
+        let mut chars = source.char_indices().peekable();
+        while let Some((offset, c)) = chars.next() {
+            column += 1;
+            match c {
+                '\n' => (line, column) = (line + 1, 0),
+                // Brackets in comments and strings do not nest
+                '/' if chars.peek().is_some_and(|(_, next)| *next == '/') => {
+                    while chars.next_if(|(_, next)| *next != '\n').is_some() {}
+                }
+                '"' => {
+                    for (_, next) in chars.by_ref() {
+                        if next == '\n' {
+                            (line, column) = (line + 1, 0);
+                        } else {
+                            column += 1;
+                        }
+                        if next == '"' {
+                            break;
+                        }
+                    }
+                }
+                '(' | '[' | '{' => {
+                    depth += 1;
+                    if depth > self.max_depth {
+                        let span = Span {
+                            start: offset,
+                            end: offset + 1,
+                            line,
+                            column,
+                        };
+                        return Err(ParseError::new(
+                            format!("Source nests more than {} levels deep", self.max_depth),
+                            span,
+                        ));
+                    }
+                }
+                ')' | ']' | '}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the number of expressions in the parsed source.
+    pub(super) fn check_tree(&self, pairs: &Pairs<Rule>) -> Result<(), ParseError> {
+        let expressions = pairs.clone().flatten().count();
+        if expressions > self.max_expressions {
+            return Err(format!(
+                "Source has {} expressions, more than the limit of {}",
+                expressions, self.max_expressions
+            )
+            .into());
+        }
+        for chain in pairs
+            .clone()
+            .flatten()
+            .filter(|p| p.as_rule() == Rule::if_stmt)
+        {
+            let mut branches = chain.into_inner().filter(|p| p.as_rule() == Rule::else_if);
+            if let Some(branch) = branches.nth(self.max_depth) {
+                return Err(ParseError::new(
+                    format!("Source nests more than {} levels deep", self.max_depth),
+                    super::span_of(&branch),
+                ));
+            }
+        }
+        for pair in pairs.clone() {
+            let (depth, deepest) = operator_depth(pair);
+            if depth > self.max_operator_depth {
+                return Err(ParseError::new(
+                    format!(
+                        "Expression nests more than {} operators deep",
+                        self.max_operator_depth
+                    ),
+                    super::span_of(&deepest),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The most operators on one path down `pair`, and the outermost
+/// operator chain on that path.
+fn operator_depth(pair: Pair<Rule>) -> (usize, Pair<Rule>) {
+    if pair.as_rule() != Rule::general_expression {
+        let inner = pair.clone().into_inner().map(operator_depth);
+        return inner.max_by_key(|(depth, _)| *depth).unwrap_or((0, pair));
+    }
+    // Operators nest as the parser groups them, with only arithmetic counted
+    let mut deepest: Option<(usize, Pair<Rule>)> = None;
+    let chain = super::precedence()
+        .map_primary(|operand| {
+            let (depth, inner) = operator_depth(operand);
+            if deepest.as_ref().is_none_or(|(max, _)| depth > *max) {
+                deepest = Some((depth, inner));
+            }
+            depth
+        })
+        .map_prefix(|_, depth| depth)
+        .map_infix(|left, op, right| {
+            let arithmetic = matches!(
+                op.as_rule(),
+                Rule::add_op | Rule::sub_op | Rule::mul_op | Rule::div_op | Rule::mod_op
+            );
+            left.max(right) + usize::from(arithmetic)
+        })
+        .parse(pair.clone().into_inner());
+    match deepest {
+        Some((depth, inner)) if depth == chain => (depth, inner),
+        _ => (chain, pair),
+    }
+}
@@ -0,0 +1,322 @@
+//! Macro definitions and their expansion.
+//!
+//! `macro name(a, b) { ... }` inside a contract defines a block of
+//! statements that a call `name(x, y);` pastes in place. A function marked
+//! `internal` is expanded the same way wherever another function calls it,
+//! its typed parameters standing for the arguments. Expansion happens
+//! at parse time, on source text: every use of a parameter is replaced by
+//! the argument as written, and the result is parsed as a block. It is
+//! hygienic: names the body binds are renamed on each expansion, so they
+//! never clash with the caller's, and every other name in the body must be
+//! a parameter of the macro, function or contract or a constant, so the
+//! caller's variables are never captured.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use pest::iterators::Pair;
+use pest::Parser;
+
+use super::{parse_block, span_of, ArkadeParser, ParseError, Rule};
+use crate::constants;
+use crate::models::{Parameter, Span, Statement};
+
+/// Macros defined in a contract.
+pub(super) struct Macros<'i> {
+    definitions: BTreeMap<String, Macro<'i>>,
+    /// Expansions so far, numbering the renamed bindings
+    expansions: usize,
+    /// Macros being expanded, innermost last
+    active: Vec<String>,
+}
+
+struct Macro<'i> {
+    kind: Kind,
+    params: Vec<String>,
+    /// Names the body binds
+    locals: BTreeSet<String>,
+    body: Pair<'i, Rule>,
+}
+
+impl<'i> Macros<'i> {
+    /// Collect the `macro_def`s and internal functions among a contract's
+    /// items, checking that their bodies only refer to their parameters,
+    /// their own bindings, the contract's parameters and constants.
+    pub(super) fn collect(
+        items: &[Pair<'i, Rule>],
+        contract_params: &[Parameter],
+        contract_constants: &BTreeMap<String, u64>,
+    ) -> Result<Self, ParseError> {
+        let mut definitions = BTreeMap::new();
+        for item in items {
+            let kind = match item.as_rule() {
+                Rule::macro_def => Kind::Macro,
+                Rule::function if is_internal(item) => Kind::Function,
+                _ => continue,
+            };
+            let span = span_of(item);
+            let mut inner = item.clone().into_inner();
+            let name = inner.next().map(|p| p.as_str()).unwrap_or_default();
+            let params: Vec<String> = inner
+                .next()
+                .map(|p| p.into_inner().filter_map(parameter_name).collect())
+                .unwrap_or_default();
+            let body = inner
+                .find(|p| p.as_rule() != Rule::function_modifier)
+                .ok_or_else(|| {
+                    ParseError::new(format!("Missing body of {} '{}'", kind.noun(), name), span)
+                })?;
+
+            if let Some(param) = params
+                .iter()
+                .enumerate()
+                .find(|(i, p)| params[..*i].contains(p))
+                .map(|(_, p)| p)
+            {
+                return Err(ParseError::new(
+                    format!(
+                        "{} '{}' declares parameter '{}' twice",
+                        kind.title(),
+                        name,
+                        param
+                    ),
+                    span,
+                ));
+            }
+
+            let mut locals = BTreeSet::new();
+            let mut references = Vec::new();
+            names(body.clone(), &mut locals, &mut references);
+            // An internal function is also checked on its own when compiling,
+            // which reports the names it does not declare
+            if let Some(free) = references.iter().find(|r| {
+                let r = r.as_str();
+                kind == Kind::Macro
+                    && !params.iter().any(|p| p == r)
+                    && !locals.contains(r)
+                    && !contract_params.iter().any(|p| p.name == r)
+                    && !contract_constants.contains_key(r)
+                    && !constants::is_constant(r)
+            }) {
+                return Err(ParseError::new(
+                    format!(
+                        "{} '{}' refers to '{}', which is not one of its parameters, \
+                         a contract parameter or a constant",
+                        kind.title(),
+                        name,
+                        free.as_str()
+                    ),
+                    span_of(free),
+                ));
+            }
+
+            let macro_def = Macro {
+                kind,
+                params,
+                locals,
+                body,
+            };
+            if let Some(other) = definitions.insert(name.to_string(), macro_def) {
+                let message = if other.kind == kind {
+                    format!("{} '{}' is defined twice", kind.title(), name)
+                } else {
+                    format!("'{}' is defined twice, as a macro and a function", name)
+                };
+                return Err(ParseError::new(message, span));
+            }
+        }
+        Ok(Macros {
+            definitions,
+            expansions: 0,
+            active: Vec::new(),
+        })
+    }
+
+    /// Expand a call to macro or internal function `name` with arguments
+    /// `args` (as written), giving the statements it stands for. They are
+    /// located at `call`.
+    pub(super) fn expand(
+        &mut self,
+        name: &str,
+        args: &[&str],
+        call: &Span,
+    ) -> Result<Vec<Statement>, String> {
+        let definition = self.definitions.get(name).ok_or_else(|| {
+            format!(
+                "'{}' is not a macro or an internal function, so it cannot be called",
+                name
+            )
+        })?;
+        let kind = definition.kind;
+        if self.active.iter().any(|m| m == name) {
+            return Err(match kind {
+                Kind::Macro => format!("Macro '{}' expands itself", name),
+                Kind::Function => format!("Function '{}' calls itself", name),
+            });
+        }
+        if args.len() != definition.params.len() {
+            return Err(format!(
+                "{} '{}' takes {} argument(s), got {}",
+                kind.title(),
+                name,
+                definition.params.len(),
+                args.len()
+            ));
+        }
+
+        self.expansions += 1;
+        let suffix = format!("_{}{}", name, self.expansions);
+        let text = substitute(definition, args, &suffix);
+
+        let block = ArkadeParser::parse(Rule::block, &text)
+            .map_err(|e| {
+                format!(
+                    "In expansion of {} '{}' at line {}: {}",
+                    kind.noun(),
+                    name,
+                    call.line,
+                    e.variant.message()
+                )
+            })?
+            .next()
+            .ok_or_else(|| format!("Empty expansion of {} '{}'", kind.noun(), name))?;
+
+        self.active.push(name.to_string());
+        let statements = parse_block(block, self);
+        self.active.pop();
+
+        // The expansion's own locations are within the substituted text
+        let mut statements = statements.map_err(|e| e.message)?;
+        relocate(&mut statements, call);
+        Ok(statements)
+    }
+}
+
+/// What defines an expansion: a `macro`, or a function marked `internal`.
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Macro,
+    Function,
+}
+
+impl Kind {
+    fn noun(self) -> &'static str {
+        match self {
+            Kind::Macro => "macro",
+            Kind::Function => "function",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Kind::Macro => "Macro",
+            Kind::Function => "Function",
+        }
+    }
+}
+
+fn is_internal(function: &Pair<Rule>) -> bool {
+    function
+        .clone()
+        .into_inner()
+        .any(|p| p.as_rule() == Rule::function_modifier)
+}
+
+/// The name a macro's parameter, or a function's after its type, declares.
+fn parameter_name(parameter: Pair<Rule>) -> Option<String> {
+    match parameter.as_rule() {
+        Rule::parameter => parameter.into_inner().last(),
+        _ => Some(parameter),
+    }
+    .map(|id| id.as_str().to_string())
+}
+
+/// Source of `definition`'s body with parameters replaced by `args` and
+/// bindings renamed with `suffix`.
+fn substitute(definition: &Macro, args: &[&str], suffix: &str) -> String {
+    let body = &definition.body;
+    let base = body.as_span().start();
+    let source = body.as_str();
+
+    let mut identifiers = Vec::new();
+    let mut locals = BTreeSet::new();
+    names(body.clone(), &mut locals, &mut identifiers);
+
+    let mut out = String::with_capacity(source.len());
+    let mut copied = 0;
+    for identifier in identifiers {
+        let name = identifier.as_str();
+        let replacement = match definition.params.iter().position(|p| p == name) {
+            Some(i) => args[i].to_string(),
+            None if definition.locals.contains(name) => format!("{}{}", name, suffix),
+            None => continue,
+        };
+        let span = identifier.as_span();
+        out.push_str(&source[copied..span.start() - base]);
+        out.push_str(&replacement);
+        copied = span.end() - base;
+    }
+    out.push_str(&source[copied..]);
+    out
+}
+
+/// Collect, in source order, the identifiers in `pair` that name values —
+/// not properties, methods, callees or contracts — and the names it binds.
+fn names<'i>(pair: Pair<'i, Rule>, locals: &mut BTreeSet<String>, out: &mut Vec<Pair<'i, Rule>>) {
+    let rule = pair.as_rule();
+    let text = pair.as_str();
+    for (i, child) in pair.into_inner().enumerate() {
+        if child.as_rule() != Rule::identifier {
+            names(child, locals, out);
+            continue;
+        }
+        let name_of_something_else = match rule {
+            Rule::tx_property_part => true,
+            Rule::tx_property_body => text.starts_with("input"),
+            Rule::tx_method_call
+            | Rule::function_call
+            | Rule::function_call_stmt
+            | Rule::constructor => i == 0,
+            _ => false,
+        };
+        if name_of_something_else {
+            continue;
+        }
+        let binds = match rule {
+            Rule::let_binding => i == 0,
+            Rule::variable_declaration => i == 1,
+            Rule::for_stmt => i < 2,
+            _ => false,
+        };
+        if binds {
+            locals.insert(child.as_str().to_string());
+        }
+        out.push(child);
+    }
+}
+
+/// Point the spans of expanded statements at the macro call.
+fn relocate(statements: &mut [Statement], call: &Span) {
+    for statement in statements {
+        match statement {
+            Statement::Require(_, span) => *span = *call,
+            Statement::IfElse {
+                then_body,
+                else_body,
+                span,
+                ..
+            } => {
+                *span = *call;
+                relocate(then_body, call);
+                if let Some(else_body) = else_body {
+                    relocate(else_body, call);
+                }
+            }
+            Statement::ForIn { body, .. } => relocate(body, call),
+            Statement::LetBinding { .. } | Statement::VarAssign { .. } => {}
+        }
+    }
+}
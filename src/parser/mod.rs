@@ -1,37 +1,163 @@
+use crate::constants;
+use crate::encoding;
 use crate::models::{
-    AssetLookupSource, Contract, Expression, Function, GroupIOSource, GroupSumSource, Parameter,
-    Requirement, Statement,
+    AssetLookupSource, Contract, Duration, Edition, Emit, Event, Expression, Function,
+    GroupIOSource, GroupSumSource, HashFunction, Parameter, Requirement, Span, Statement, Trivia,
 };
+use alloc::collections::BTreeMap;
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::str::FromStr;
 use pest::iterators::{Pair, Pairs};
+use pest::pratt_parser::{Assoc, Op, PrattParser};
 use pest::Parser;
 use pest_derive::Parser;
-use std::str::FromStr;
 
-/// Pest parser generated from grammar.pest
+mod extensions;
+mod limits;
+mod macros;
+mod options;
+mod tokens;
+mod trivia;
+
+pub use extensions::{Extension, ScriptTarget};
+pub use limits::ParseLimits;
+use macros::Macros;
+pub use options::OPTIONS;
+pub use tokens::{tokenize, Token, TokenKind, BUILTINS, KEYWORDS};
+use trivia::Comments;
+
+/// Pest parser generated from the core grammar and its extensions
 #[derive(Parser)]
 #[grammar = "parser/grammar.pest"]
+#[grammar = "parser/assets.pest"]
+#[grammar = "parser/introspection.pest"]
+#[grammar = "parser/loops.pest"]
+#[grammar = "parser/opcodes.pest"]
 pub struct ArkadeParser;
 
+/// An error building the AST from source the grammar accepts, located at
+/// the construct it concerns where known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    pub span: Option<Span>,
+}
+
+impl ParseError {
+    fn new(message: impl Into<String>, span: Span) -> Self {
+        ParseError {
+            message: message.into(),
+            span: Some(span),
+        }
+    }
+
+    /// Locate the error at `span` unless it is located already.
+    fn or_at(mut self, span: Span) -> Self {
+        self.span.get_or_insert(span);
+        self
+    }
+}
+
+impl From<String> for ParseError {
+    fn from(message: String) -> Self {
+        ParseError {
+            message,
+            span: None,
+        }
+    }
+}
+
+impl From<&str> for ParseError {
+    fn from(message: &str) -> Self {
+        ParseError::from(message.to_string())
+    }
+}
+
+/// Displays the message alone; the location is in `span`.
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl core::error::Error for ParseError {}
+
 /// Parse Arkade Script source code into a Contract AST.
 ///
 /// This is the main entry point for the parser. It tokenizes the source code
 /// using the Pest grammar and builds a typed AST.
-pub fn parse(source_code: &str) -> Result<Contract, Box<dyn std::error::Error>> {
+pub fn parse(source_code: &str) -> Result<Contract, Box<dyn core::error::Error>> {
+    parse_for_target(source_code, ScriptTarget::Arkade)
+}
+
+/// Parse source code for `target`, rejecting syntax from grammar extensions
+/// the target does not support.
+pub fn parse_for_target(
+    source_code: &str,
+    target: ScriptTarget,
+) -> Result<Contract, Box<dyn core::error::Error>> {
+    let options = ParseOptions {
+        target,
+        ..Default::default()
+    };
+    parse_with_options(source_code, &options)
+}
+
+/// Options controlling parsing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Script flavour the source is written for
+    pub target: ScriptTarget,
+    /// Limits on the size of the source
+    pub limits: ParseLimits,
+    /// Ignore unknown names in the `options` block and leave option values
+    /// to be checked when compiling, instead of rejecting them here
+    pub lenient_options: bool,
+}
+
+/// Parse source code with `options`.
+pub fn parse_with_options(
+    source_code: &str,
+    options: &ParseOptions,
+) -> Result<Contract, Box<dyn core::error::Error>> {
+    options.limits.check_source(source_code)?;
     let pairs = ArkadeParser::parse(Rule::main, source_code)?;
-    let ast = build_ast(pairs)?;
+    options.limits.check_tree(&pairs)?;
+    extensions::check(pairs.clone(), options.target)?;
+    if !options.lenient_options {
+        options::check(pairs.clone())?;
+    }
+    let comments = Comments::scan(source_code);
+    let ast = build_ast(pairs, &comments)?;
     Ok(ast)
 }
 
 /// Build a Contract AST from parsed Pest pairs
-fn build_ast(pairs: Pairs<Rule>) -> Result<Contract, String> {
+fn build_ast(pairs: Pairs<Rule>, comments: &Comments) -> Result<Contract, ParseError> {
     let mut contract = Contract {
+        edition: None,
+        compiler: None,
         name: String::new(),
+        generics: Vec::new(),
+        named_options: BTreeMap::new(),
         parameters: Vec::new(),
         renewal_timelock: None,
         exit_timelock: None,
         has_server_key: false,
+        server_key: None,
+        internal_key: None,
+        strict_hashlocks: false,
+        constants: BTreeMap::new(),
+        events: Vec::new(),
         functions: Vec::new(),
         imports: Vec::new(),
+        trivia: Trivia::default(),
     };
 
     for pair in pairs {
@@ -39,6 +165,7 @@ fn build_ast(pairs: Pairs<Rule>) -> Result<Contract, String> {
             Rule::main => {
                 for inner_pair in pair.into_inner() {
                     match inner_pair.as_rule() {
+                        Rule::pragma => parse_pragma(&mut contract, inner_pair)?,
                         Rule::import_stmt => {
                             // Extract the import path (string literal without quotes)
                             if let Some(path_pair) = inner_pair.into_inner().next() {
@@ -49,14 +176,14 @@ fn build_ast(pairs: Pairs<Rule>) -> Result<Contract, String> {
                             }
                         }
                         Rule::contract => {
-                            parse_contract(&mut contract, inner_pair)?;
+                            parse_contract(&mut contract, inner_pair, comments)?;
                         }
                         _ => {}
                     }
                 }
             }
             Rule::contract => {
-                parse_contract(&mut contract, pair)?;
+                parse_contract(&mut contract, pair, comments)?;
             }
             _ => {}
         }
@@ -65,14 +192,88 @@ fn build_ast(pairs: Pairs<Rule>) -> Result<Contract, String> {
     Ok(contract)
 }
 
+/// Parse `pragma arkade <year>;`, which selects the edition, or
+/// `pragma compiler <versions>;`, which must accept this compiler
+fn parse_pragma(contract: &mut Contract, pair: Pair<Rule>) -> Result<(), ParseError> {
+    let span = span_of(&pair);
+    let pragma = pair.into_inner().next().ok_or("Empty pragma")?;
+    let value = pragma
+        .clone()
+        .into_inner()
+        .next()
+        .map_or("", |v| v.as_str());
+    match pragma.as_rule() {
+        Rule::edition_pragma => {
+            if contract.edition.is_some() {
+                return Err(ParseError::new("The edition is selected twice", span));
+            }
+            let edition = value
+                .parse()
+                .ok()
+                .and_then(Edition::from_year)
+                .ok_or_else(|| {
+                    let editions: Vec<String> =
+                        Edition::ALL.iter().map(|e| e.to_string()).collect();
+                    ParseError::new(
+                        format!(
+                            "Unknown edition {} (expected {})",
+                            value,
+                            editions.join(" or ")
+                        ),
+                        span,
+                    )
+                })?;
+            contract.edition = Some(edition);
+            contract.strict_hashlocks = edition.strict_hashlocks();
+        }
+        _ => {
+            if contract.compiler.is_some() {
+                return Err(ParseError::new(
+                    "The compiler version is required twice",
+                    span,
+                ));
+            }
+            let value = value.trim();
+            check_compiler_version(value).map_err(|e| ParseError::new(e, span))?;
+            contract.compiler = Some(value.to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Check that `requirement`, such as `>=0.4, <0.6`, accepts this compiler.
+fn check_compiler_version(requirement: &str) -> Result<(), String> {
+    let version = env!("CARGO_PKG_VERSION");
+    let req = semver::VersionReq::parse(requirement).map_err(|e| {
+        format!(
+            "Invalid compiler version requirement '{}': {}",
+            requirement, e
+        )
+    })?;
+    let current = semver::Version::parse(version).expect("the package version is semver");
+    if req.matches(&current) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Contract requires compiler {}, but this is arkadec {}",
+            requirement, version
+        ))
+    }
+}
+
 /// Parse a contract definition including options block, name, parameters, and functions
-fn parse_contract(contract: &mut Contract, pair: Pair<Rule>) -> Result<(), String> {
+fn parse_contract(
+    contract: &mut Contract,
+    pair: Pair<Rule>,
+    comments: &Comments,
+) -> Result<(), ParseError> {
+    contract.trivia = comments.around(&span_of(&pair));
     let mut inner_pairs = pair.into_inner().peekable();
 
     // Optional options block
     if inner_pairs
         .peek()
-        .map_or(false, |p| p.as_rule() == Rule::options_block)
+        .is_some_and(|p| p.as_rule() == Rule::options_block)
     {
         if let Some(options_block) = inner_pairs.next() {
             parse_options_block(contract, options_block)?;
@@ -82,28 +283,90 @@ fn parse_contract(contract: &mut Contract, pair: Pair<Rule>) -> Result<(), Strin
     // Contract name (required)
     contract.name = match inner_pairs.next() {
         Some(name) => name.as_str().to_string(),
-        None => return Err("Missing contract name".to_string()),
+        None => return Err("Missing contract name".into()),
     };
 
+    // Generic parameters (optional)
+    if inner_pairs
+        .peek()
+        .is_some_and(|p| p.as_rule() == Rule::generic_params)
+    {
+        if let Some(generics) = inner_pairs.next() {
+            for generic in generics.into_inner() {
+                let name = generic.as_str().to_string();
+                if contract.generics.contains(&name) {
+                    return Err(ParseError::new(
+                        format!("Generic parameter '{}' is declared twice", name),
+                        span_of(&generic),
+                    ));
+                }
+                contract.generics.push(name);
+            }
+        }
+    }
+
     // Parameters (optional)
     if let Some(param_list) = inner_pairs.next() {
-        contract.parameters = parse_parameters(param_list)?;
+        let span = span_of(&param_list);
+        contract.parameters = parse_parameters(param_list).map_err(|e| ParseError::new(e, span))?;
     }
 
-    // Functions
-    for func_pair in inner_pairs {
-        if func_pair.as_rule() == Rule::function {
-            let func = parse_function(func_pair)?;
-            contract.functions.push(func);
+    // Constants, which macros and functions may use before they are declared
+    let items: Vec<Pair<Rule>> = inner_pairs.collect();
+    for item in items.iter().filter(|p| p.as_rule() == Rule::const_decl) {
+        parse_constant(contract, item.clone())?;
+    }
+
+    // Macros, which functions may call before they are defined
+    let mut macros = Macros::collect(&items, &contract.parameters, &contract.constants)?;
+
+    // Events and functions
+    for item in items {
+        match item.as_rule() {
+            Rule::event_decl => contract.events.push(parse_event(item)?),
+            Rule::function => {
+                let trivia = comments.around(&span_of(&item));
+                let func = parse_function(item, &mut macros)?;
+                contract.functions.push(Function { trivia, ..func });
+            }
+            _ => {}
+        }
+    }
+
+    // Array lengths are numbers or declared generic parameters
+    let parameters = contract
+        .parameters
+        .iter()
+        .chain(contract.functions.iter().flat_map(|f| &f.parameters));
+    for param in parameters {
+        let length = param
+            .param_type
+            .strip_suffix(']')
+            .and_then(|t| t.split_once('['))
+            .map_or("", |(_, length)| length);
+        if length == "0" {
+            return Err(format!("Array parameter '{}' has length 0", param.name).into());
+        }
+        if !length.is_empty()
+            && length.parse::<usize>().is_err()
+            && !contract.generics.iter().any(|g| g == length)
+        {
+            return Err(format!(
+                "Array parameter '{}' is sized by '{}', which is not a generic parameter of contract {}",
+                param.name, length, contract.name
+            )
+            .into());
         }
     }
     Ok(())
 }
 
-/// Parse the options block (server key, exit timelock, renewal timelock)
-fn parse_options_block(contract: &mut Contract, pair: Pair<Rule>) -> Result<(), String> {
+/// Parse the options block (server key, exit timelock, renewal timelock,
+/// internal key, strict hash locks)
+fn parse_options_block(contract: &mut Contract, pair: Pair<Rule>) -> Result<(), ParseError> {
     for option_pair in pair.into_inner() {
         if option_pair.as_rule() == Rule::option_setting {
+            let span = span_of(&option_pair);
             let mut inner = option_pair.into_inner();
             let option_name = match inner.next() {
                 Some(name) => name.as_str(),
@@ -111,39 +374,113 @@ fn parse_options_block(contract: &mut Contract, pair: Pair<Rule>) -> Result<(),
             };
             let option_value = match inner.next() {
                 Some(value) => value.as_str(),
-                None => return Err(format!("Missing {} option value", option_name)),
+                None => {
+                    return Err(ParseError::new(
+                        format!("Missing {} option value", option_name),
+                        span,
+                    ))
+                }
             };
 
             match option_name {
                 "server" => {
                     // The Arkade operator key is always injected externally.
-                    // The RHS value is ignored — it must never be a constructor parameter.
+                    // The RHS only names it; it is checked when compiling.
                     contract.has_server_key = true;
+                    contract.server_key = Some(option_value.to_string());
                 }
-                "renew" => {
-                    if let Ok(value) = option_value.parse::<u64>() {
-                        contract.renewal_timelock = Some(value);
+                "renew" | "exit" => {
+                    let timelock = match option_value.parse::<u64>() {
+                        Ok(value) => Some(value),
+                        // A constant such as BLOCKS_PER_DAY or a duration such
+                        // as 48h, resolved for the network when compiling
+                        Err(_) => {
+                            contract
+                                .named_options
+                                .insert(option_name.to_string(), option_value.to_string());
+                            None
+                        }
+                    };
+                    if option_name == "renew" {
+                        contract.renewal_timelock = timelock;
+                    } else {
+                        contract.exit_timelock = timelock;
                     }
                 }
-                "exit" => {
-                    if let Ok(value) = option_value.parse::<u64>() {
-                        contract.exit_timelock = Some(value);
-                    }
+                "internalKey" => {
+                    contract.internal_key = Some(option_value.to_string());
+                }
+                "strictHashlocks" => {
+                    contract.strict_hashlocks = match option_value {
+                        "true" => true,
+                        "false" => false,
+                        _ => {
+                            return Err(ParseError::new(
+                                format!(
+                                    "Option 'strictHashlocks' must be true or false, got '{}'",
+                                    option_value
+                                ),
+                                span,
+                            ))
+                        }
+                    };
                 }
-                _ => {} // Ignore unknown options
+                _ => {} // Rejected by `options::check` unless lenient
             }
         }
     }
     Ok(())
 }
 
+/// Parse an event declaration
+fn parse_constant(contract: &mut Contract, pair: Pair<Rule>) -> Result<(), ParseError> {
+    let span = span_of(&pair);
+    let mut inner = pair.into_inner();
+    let name = inner.next().map_or("", |name| name.as_str()).to_string();
+    let value = inner.next().map_or("", |value| value.as_str());
+    if constants::is_constant(&name) {
+        return Err(ParseError::new(
+            format!("Constant '{}' is already a built-in constant", name),
+            span,
+        ));
+    }
+    let value = value.parse::<u64>().map_err(|_| {
+        ParseError::new(
+            format!("Constant '{}' must be a non-negative integer", name),
+            span,
+        )
+    })?;
+    if contract.constants.insert(name.clone(), value).is_some() {
+        return Err(ParseError::new(
+            format!("Constant '{}' is declared twice", name),
+            span,
+        ));
+    }
+    Ok(())
+}
+
+fn parse_event(pair: Pair<Rule>) -> Result<Event, ParseError> {
+    let mut inner = pair.into_inner();
+    let name = inner.next().map_or("", |name| name.as_str()).to_string();
+    let parameters = match inner.next() {
+        Some(param_list) => {
+            let span = span_of(&param_list);
+            parse_parameters(param_list).map_err(|e| ParseError::new(e, span))?
+        }
+        None => Vec::new(),
+    };
+    Ok(Event { name, parameters })
+}
+
 /// Parse a function definition
-fn parse_function(pair: Pair<Rule>) -> Result<Function, String> {
+fn parse_function(pair: Pair<Rule>, macros: &mut Macros) -> Result<Function, ParseError> {
     let mut func = Function {
         name: String::new(),
         parameters: Vec::new(),
         statements: Vec::new(),
         is_internal: false,
+        emits: Vec::new(),
+        trivia: Trivia::default(),
     };
 
     let mut inner_pairs = pair.into_inner();
@@ -151,56 +488,91 @@ fn parse_function(pair: Pair<Rule>) -> Result<Function, String> {
     // Function name (required)
     func.name = match inner_pairs.next() {
         Some(name) => name.as_str().to_string(),
-        None => return Err("Missing function name".to_string()),
+        None => return Err("Missing function name".into()),
     };
 
     // Parameters
     if let Some(param_list) = inner_pairs.next() {
-        func.parameters = parse_parameters(param_list)?;
+        let span = span_of(&param_list);
+        func.parameters = parse_parameters(param_list).map_err(|e| ParseError::new(e, span))?;
     }
 
-    // Check for function modifier (internal) and body
-    match inner_pairs.next() {
-        Some(next_pair) => {
-            if next_pair.as_rule() == Rule::function_modifier {
-                func.is_internal = true;
-                for req_pair in inner_pairs {
-                    parse_function_body(&mut func, req_pair)?;
-                }
-            } else {
-                parse_function_body(&mut func, next_pair)?;
-                for req_pair in inner_pairs {
-                    parse_function_body(&mut func, req_pair)?;
-                }
-            }
+    // Function modifier (internal) and body
+    for next_pair in inner_pairs {
+        if next_pair.as_rule() == Rule::function_modifier {
+            func.is_internal = true;
+            continue;
         }
-        None => {} // Empty function body
-    };
+        for req_pair in next_pair.into_inner() {
+            parse_function_body(&mut func, req_pair, macros)?;
+        }
+    }
 
     Ok(func)
 }
 
 /// Parse a statement in a function body (require, let binding, function call, variable declaration)
-fn parse_function_body(func: &mut Function, pair: Pair<Rule>) -> Result<(), String> {
+fn parse_function_body(
+    func: &mut Function,
+    pair: Pair<Rule>,
+    macros: &mut Macros,
+) -> Result<(), ParseError> {
+    let span = span_of(&pair);
+    parse_statement(func, pair, macros).map_err(|e| e.or_at(span))
+}
+
+/// Parse a statement into `func`. Errors not located more precisely are
+/// located at the statement by the caller.
+fn parse_statement(
+    func: &mut Function,
+    pair: Pair<Rule>,
+    macros: &mut Macros,
+) -> Result<(), ParseError> {
     match pair.as_rule() {
+        Rule::emit_stmt => {
+            // After the `emit` keyword
+            let mut inner = pair.into_inner().skip(1);
+            let event = inner.next().map_or("", |name| name.as_str()).to_string();
+            let args = inner.map(|arg| arg.as_str().to_string()).collect();
+            func.emits.push(Emit { event, args });
+            Ok(())
+        }
         Rule::require_stmt => {
+            let span = span_of(&pair);
             let mut inner = pair.into_inner();
             let expr = match inner.next() {
                 Some(expr) => expr,
                 None => {
-                    return Err(format!(
-                        "Parse error: Invalid arguments to function {}",
-                        func.name
-                    ))
+                    return Err(
+                        format!("Parse error: Invalid arguments to function {}", func.name).into(),
+                    )
                 }
             };
-            let requirement = parse_complex_expression(expr)?;
+            let requirement = match expr.as_rule() {
+                Rule::general_expression => match parse_general_expression(expr)? {
+                    Expression::BinaryOp { left, op, right }
+                        if !matches!(op.as_str(), "+" | "-" | "*" | "/" | "%") =>
+                    {
+                        Requirement::Comparison {
+                            left: *left,
+                            op,
+                            right: *right,
+                        }
+                    }
+                    condition => Requirement::Comparison {
+                        left: condition,
+                        op: "==".to_string(),
+                        right: Expression::Literal("true".to_string()),
+                    },
+                },
+                _ => parse_complex_expression(expr)?,
+            };
 
             // Capture optional error message (stored in requirement metadata)
             let _message = inner.next().map(|p| p.as_str().to_string());
 
             // Wrap the requirement in a Statement::Require
-            func.statements.push(Statement::Require(requirement));
+            func.statements.push(Statement::Require(requirement, span));
             Ok(())
         }
         Rule::let_binding => {
@@ -238,23 +610,47 @@ fn parse_function_body(func: &mut Function, pair: Pair<Rule>) -> Result<(), Stri
             let condition_pair = inner
                 .next()
                 .ok_or_else(|| "Parse error: Missing condition in if statement".to_string())?;
+            let span = span_of(&condition_pair);
             let condition = parse_general_expression(condition_pair)?;
 
             let then_block = inner
                 .next()
                 .ok_or_else(|| "Parse error: Missing then block in if statement".to_string())?;
-            let then_body = parse_block(then_block)?;
-
-            let else_body = if let Some(else_block) = inner.next() {
-                Some(parse_block(else_block)?)
-            } else {
-                None
-            };
+            let then_body = parse_block(then_block, macros)?;
+
+            let mut branches = Vec::new();
+            let mut else_body = None;
+            for pair in inner {
+                if pair.as_rule() == Rule::else_if {
+                    let mut inner = pair.into_inner();
+                    let condition_pair = inner.next().ok_or_else(|| {
+                        "Parse error: Missing condition in else if branch".to_string()
+                    })?;
+                    let span = span_of(&condition_pair);
+                    let condition = parse_general_expression(condition_pair)?;
+                    let block = inner.next().ok_or_else(|| {
+                        "Parse error: Missing block in else if branch".to_string()
+                    })?;
+                    branches.push((condition, parse_block(block, macros)?, span));
+                } else {
+                    else_body = Some(parse_block(pair, macros)?);
+                }
+            }
+            // `else if` is an `if` nested alone in the else branch before it
+            for (condition, then_body, span) in branches.into_iter().rev() {
+                else_body = Some(vec![Statement::IfElse {
+                    condition,
+                    then_body,
+                    else_body,
+                    span,
+                }]);
+            }
 
             func.statements.push(Statement::IfElse {
                 condition,
                 then_body,
                 else_body,
+                span,
             });
             Ok(())
         }
@@ -277,7 +673,7 @@ fn parse_function_body(func: &mut Function, pair: Pair<Rule>) -> Result<(), Stri
             let body_block = inner
                 .next()
                 .ok_or_else(|| "Parse error: Missing body in for loop".to_string())?;
-            let body = parse_block(body_block)?;
+            let body = parse_block(body_block, macros)?;
 
             func.statements.push(Statement::ForIn {
                 index_var,
@@ -288,7 +684,12 @@ fn parse_function_body(func: &mut Function, pair: Pair<Rule>) -> Result<(), Stri
             Ok(())
         }
         Rule::function_call_stmt => {
-            // Function calls to internal helpers — not yet fully supported
+            // Calls to macros and internal functions are expanded in place
+            let span = span_of(&pair);
+            let mut inner = pair.into_inner();
+            let name = inner.next().map(|p| p.as_str()).unwrap_or_default();
+            let args: Vec<&str> = inner.map(|arg| arg.as_str()).collect();
+            func.statements.extend(macros.expand(name, &args, &span)?);
             Ok(())
         }
         Rule::variable_declaration => {
@@ -312,10 +713,22 @@ fn parse_function_body(func: &mut Function, pair: Pair<Rule>) -> Result<(), Stri
     }
 }
 
+/// Source location of a parsed pair
+fn span_of(pair: &Pair<Rule>) -> Span {
+    let span = pair.as_span();
+    let (line, column) = span.start_pos().line_col();
+    Span {
+        start: span.start(),
+        end: span.end(),
+        line,
+        column,
+    }
+}
+
 // ─── Expression Parsing ────────────────────────────────────────────────────────
 
 // Parse a block of statements
-fn parse_block(pair: Pair<Rule>) -> Result<Vec<Statement>, String> {
+fn parse_block(pair: Pair<Rule>, macros: &mut Macros) -> Result<Vec<Statement>, ParseError> {
     let mut statements = Vec::new();
 
     for inner in pair.into_inner() {
@@ -325,44 +738,50 @@ fn parse_block(pair: Pair<Rule>) -> Result<Vec<Statement>, String> {
             parameters: Vec::new(),
             statements: Vec::new(),
             is_internal: false,
+            emits: Vec::new(),
+            trivia: Trivia::default(),
         };
 
-        parse_function_body(&mut temp_func, inner)?;
+        parse_function_body(&mut temp_func, inner, macros)?;
         statements.extend(temp_func.statements);
     }
 
     Ok(statements)
 }
 
+/// The precedence of the operators in a `general_expression`, loosest
+/// first.
+fn precedence() -> PrattParser<Rule> {
+    PrattParser::new()
+        .op(Op::infix(Rule::or_op, Assoc::Left))
+        .op(Op::infix(Rule::and_op, Assoc::Left))
+        .op(Op::infix(Rule::comparison_operator, Assoc::Left))
+        .op(Op::infix(Rule::add_op, Assoc::Left) | Op::infix(Rule::sub_op, Assoc::Left))
+        .op(Op::infix(Rule::mul_op, Assoc::Left)
+            | Op::infix(Rule::div_op, Assoc::Left)
+            | Op::infix(Rule::mod_op, Assoc::Left))
+        .op(Op::prefix(Rule::not_op))
+}
+
 // Parse general expression (with operator precedence)
 fn parse_general_expression(pair: Pair<Rule>) -> Result<Expression, String> {
     match pair.as_rule() {
-        Rule::general_expression | Rule::comparison_expr => {
-            // Unwrap and parse the inner expression
-            let mut inner = pair.into_inner();
-            if let Some(first) = inner.next() {
-                let left = parse_additive_expr(first)?;
-
-                // Check for comparison operator
-                if let Some(op_pair) = inner.next() {
-                    let op = op_pair.as_str().to_string();
-                    let right_pair = inner.next().ok_or("Missing right side of comparison")?;
-                    let right = parse_additive_expr(right_pair)?;
-                    Ok(Expression::BinaryOp {
-                        left: Box::new(left),
-                        op,
-                        right: Box::new(right),
-                    })
-                } else {
-                    Ok(left)
-                }
-            } else {
-                Err("Empty expression".to_string())
-            }
-        }
-        Rule::additive_expr => parse_additive_expr(pair),
-        Rule::multiplicative_expr => parse_multiplicative_expr(pair),
-        Rule::unary_expr | Rule::primary_expr => parse_primary_expr(pair),
+        Rule::general_expression => precedence()
+            .map_primary(parse_primary_expr)
+            .map_prefix(|_, value| {
+                Ok(Expression::Not {
+                    value: Box::new(value?),
+                })
+            })
+            .map_infix(|left, op, right| {
+                Ok(Expression::BinaryOp {
+                    left: Box::new(left?),
+                    op: op.as_str().to_string(),
+                    right: Box::new(right?),
+                })
+            })
+            .parse(pair.into_inner()),
+        Rule::primary_expr => parse_primary_expr(pair),
         Rule::identifier => Ok(Expression::Variable(pair.as_str().to_string())),
         Rule::number_literal => Ok(Expression::Literal(pair.as_str().to_string())),
         Rule::tx_property_access => parse_tx_property_to_expr(pair),
@@ -374,74 +793,14 @@ fn parse_general_expression(pair: Pair<Rule>) -> Result<Expression, String> {
     }
 }
 
-// Parse additive expression (+ and -)
-fn parse_additive_expr(pair: Pair<Rule>) -> Result<Expression, String> {
-    match pair.as_rule() {
-        Rule::additive_expr => {
-            let mut inner = pair.into_inner();
-            let first = inner
-                .next()
-                .ok_or("Missing first operand in additive expression")?;
-            let mut result = parse_multiplicative_expr(first)?;
-
-            // Process remaining operands
-            while let Some(op_pair) = inner.next() {
-                let op = op_pair.as_str().to_string();
-                let right_pair = inner
-                    .next()
-                    .ok_or("Missing right operand in additive expression")?;
-                let right = parse_multiplicative_expr(right_pair)?;
-                result = Expression::BinaryOp {
-                    left: Box::new(result),
-                    op,
-                    right: Box::new(right),
-                };
-            }
-
-            Ok(result)
-        }
-        _ => parse_multiplicative_expr(pair),
-    }
-}
-
-// Parse multiplicative expression (* and /)
-fn parse_multiplicative_expr(pair: Pair<Rule>) -> Result<Expression, String> {
-    match pair.as_rule() {
-        Rule::multiplicative_expr => {
-            let mut inner = pair.into_inner();
-            let first = inner
-                .next()
-                .ok_or("Missing first operand in multiplicative expression")?;
-            let mut result = parse_primary_expr(first)?;
-
-            // Process remaining operands
-            while let Some(op_pair) = inner.next() {
-                let op = op_pair.as_str().to_string();
-                let right_pair = inner
-                    .next()
-                    .ok_or("Missing right operand in multiplicative expression")?;
-                let right = parse_primary_expr(right_pair)?;
-                result = Expression::BinaryOp {
-                    left: Box::new(result),
-                    op,
-                    right: Box::new(right),
-                };
-            }
-
-            Ok(result)
-        }
-        _ => parse_primary_expr(pair),
-    }
-}
-
 // Parse primary expression (atoms)
 fn parse_primary_expr(pair: Pair<Rule>) -> Result<Expression, String> {
     match pair.as_rule() {
-        Rule::primary_expr | Rule::unary_expr => {
+        Rule::primary_expr => {
             let inner = pair.into_inner().next().ok_or("Empty primary expression")?;
             parse_primary_expr(inner)
         }
-        Rule::general_expression | Rule::comparison_expr => {
+        Rule::general_expression => {
             // Parenthesized expression
             parse_general_expression(pair)
         }
@@ -450,15 +809,32 @@ fn parse_primary_expr(pair: Pair<Rule>) -> Result<Expression, String> {
         Rule::tx_property_access => parse_tx_property_to_expr(pair),
         Rule::this_property_access => Ok(Expression::Property(pair.as_str().to_string())),
         Rule::check_sig => {
+            let text = pair.as_str();
             let mut inner = pair.into_inner();
             let signature = inner
                 .next()
                 .ok_or("Missing signature")?
                 .as_str()
                 .to_string();
-            let pubkey = inner.next().ok_or("Missing pubkey")?.as_str().to_string();
+            let pubkey = parse_key_arg(inner.next().ok_or("Missing pubkey")?);
+            if inner.next().is_some() {
+                return Err(format!(
+                    "Sighash flags in '{}' are only supported in require(checkSig(...))",
+                    text
+                ));
+            }
             Ok(Expression::CheckSigExpr { signature, pubkey })
         }
+        Rule::check_sig_count => {
+            let pubkeys = pair
+                .into_inner()
+                .next()
+                .ok_or("Missing public keys")?
+                .into_inner()
+                .map(|p| p.as_str().to_string())
+                .collect();
+            Ok(Expression::CheckSigCount { pubkeys })
+        }
         Rule::check_sig_from_stack => {
             let mut inner = pair.into_inner();
             let signature = inner
@@ -474,7 +850,7 @@ fn parse_primary_expr(pair: Pair<Rule>) -> Result<Expression, String> {
                 message,
             })
         }
-        Rule::sha256_func => {
+        Rule::hash_func => {
             // For now, represent as property
             Ok(Expression::Property(pair.as_str().to_string()))
         }
@@ -498,8 +874,6 @@ fn parse_primary_expr(pair: Pair<Rule>) -> Result<Expression, String> {
         Rule::tx_introspection => parse_tx_introspection_to_expression(pair),
         Rule::constructor => parse_constructor_to_expression(pair),
         Rule::function_call => Ok(Expression::Property(pair.as_str().to_string())),
-        Rule::additive_expr => parse_additive_expr(pair),
-        Rule::multiplicative_expr => parse_multiplicative_expr(pair),
         _ => {
             // Default to treating as a property string
             Ok(Expression::Property(pair.as_str().to_string()))
@@ -514,9 +888,12 @@ fn parse_complex_expression(pair: Pair<Rule>) -> Result<Requirement, String> {
         Rule::check_sig_from_stack => parse_check_sig_from_stack(pair),
         Rule::check_multisig => parse_check_multisig(pair),
         Rule::time_comparison => parse_time_comparison(pair),
+        Rule::age_comparison => parse_age_comparison(pair),
+        Rule::after_call => parse_after_call(pair),
         Rule::identifier_comparison => parse_identifier_comparison(pair),
         Rule::property_comparison => parse_property_comparison(pair),
         Rule::hash_comparison => parse_hash_comparison(pair),
+        Rule::size_bounds => parse_size_bounds(pair),
         Rule::binary_operation => parse_binary_operation(pair),
         Rule::asset_lookup_comparison => parse_asset_lookup_comparison(pair),
         Rule::asset_count_comparison => parse_asset_count_comparison(pair),
@@ -650,12 +1027,37 @@ fn parse_check_sig(pair: Pair<Rule>) -> Result<Requirement, String> {
         .ok_or("Missing signature")?
         .as_str()
         .to_string();
-    let pubkey = inner
-        .next()
-        .ok_or("Missing public key")?
-        .as_str()
-        .to_string();
-    Ok(Requirement::CheckSig { signature, pubkey })
+    let pubkey = parse_key_arg(inner.next().ok_or("Missing public key")?);
+    let sighash = inner.next().map(parse_sighash_flags).transpose()?;
+    Ok(Requirement::CheckSig {
+        signature,
+        pubkey,
+        sighash,
+    })
+}
+
+/// Parse `SIGHASH_SINGLE | ANYONECANPAY` → the sighash flag byte
+fn parse_sighash_flags(pair: Pair<Rule>) -> Result<u8, String> {
+    let names: Vec<&str> = pair.into_inner().map(|flag| flag.as_str()).collect();
+    encoding::sighash_flag(&names)
+}
+
+/// Name of the key a checkSig pubkey argument refers to. An element at a
+/// constant index is the flattened parameter (`oracles[1]` → `oracles_1`);
+/// one at a loop variable stays `oracles[i]` until the loop is unrolled.
+fn parse_key_arg(pair: Pair<Rule>) -> String {
+    if pair.as_rule() != Rule::array_index_access {
+        return pair.as_str().to_string();
+    }
+    let mut inner = pair.into_inner();
+    let array = inner.next().map(|p| p.as_str()).unwrap_or_default();
+    match inner.next() {
+        Some(index) if index.as_rule() == Rule::number_literal => {
+            format!("{}_{}", array, index.as_str())
+        }
+        Some(index) => format!("{}[{}]", array, index.as_str()),
+        None => array.to_string(),
+    }
 }
 
 /// Parse checkSigFromStack(sig, pubkey, message) → CheckSigFromStack requirement
@@ -680,6 +1082,10 @@ fn parse_check_sig_from_stack(pair: Pair<Rule>) -> Result<Requirement, String> {
 }
 
 /// Parse checkMultisig([pubkeys], threshold) → CheckMultisig requirement
+///
+/// `checkMultisig(signers, threshold)` over a `pubkey[]` parameter gives the
+/// single key `signers[]`, expanded to the array's elements when compiling;
+/// see [`crate::models::multisig_keys`].
 fn parse_check_multisig(pair: Pair<Rule>) -> Result<Requirement, String> {
     let mut inner = pair
         .into_inner()
@@ -692,37 +1098,107 @@ fn parse_check_multisig(pair: Pair<Rule>) -> Result<Requirement, String> {
     // The next item is a threshold number
     let next = inner.next();
 
-    let pubkeys: Vec<String> = pubkeys_array
-        .into_inner()
-        .map(|p| p.as_str().to_string())
-        .collect();
-    match next {
-        Some(next_pair) => {
-            // m-of-n threshold multisig
-            let threshold = match u16::from_str(next_pair.as_str()) {
-                Ok(threshold) => threshold,
-                Err(e) => {
-                    return Err(format!("{}", e));
-                }
-            };
+    let pubkeys = if pubkeys_array.as_rule() == Rule::identifier {
+        alloc::vec![format!("{}[]", pubkeys_array.as_str())]
+    } else {
+        pubkeys_array
+            .into_inner()
+            .map(|p| p.as_str().to_string())
+            .collect()
+    };
+    // Without a threshold every key must sign, marked by 0
+    let threshold = match next {
+        Some(next_pair) => match u16::from_str(next_pair.as_str()) {
+            Ok(0) => return Err("m-of-n multisig cannot succeed with threshold(m) of 0".into()),
+            Ok(threshold) => threshold,
+            Err(e) => return Err(invalid_threshold(next_pair.as_str(), e)),
+        },
+        None => 0,
+    };
+    Ok(Requirement::CheckMultisig { pubkeys, threshold })
+}
 
-            Ok(Requirement::CheckMultisig { pubkeys, threshold })
-        }
-        None => {
-            // An n-of-n multisig should be created by optionally omitting the threshold from checkMultisig arguments
-            let threshold = pubkeys.len() as u16;
-            Ok(Requirement::CheckMultisig { pubkeys, threshold })
-        }
-    }
+fn invalid_threshold(literal: &str, error: core::num::ParseIntError) -> String {
+    format!("Invalid checkMultisig threshold '{}': {}", literal, error)
 }
 
-/// Parse tx.time >= variable → After requirement
+/// Parse tx.time >= locktime → After requirement at a block height or a
+/// timestamp
 fn parse_time_comparison(pair: Pair<Rule>) -> Result<Requirement, String> {
-    let mut inner = pair.into_inner();
-    let timelock_var = inner.next().ok_or("Missing timelock")?.as_str().to_string();
+    let locktime = pair.into_inner().next().ok_or("Missing timelock")?;
+    if locktime.as_rule() == Rule::identifier {
+        return Ok(Requirement::After {
+            locktime: 0,
+            timelock_var: Some(locktime.as_str().to_string()),
+        });
+    }
     Ok(Requirement::After {
-        blocks: 0,
-        timelock_var: Some(timelock_var),
+        locktime: absolute_locktime(locktime.as_str())?,
+        timelock_var: None,
+    })
+}
+
+/// Parse after(timestamp) → After requirement at a median-time-past
+/// timestamp, which nLockTime tells from a block height by being at least
+/// 500000000
+fn parse_after_call(pair: Pair<Rule>) -> Result<Requirement, String> {
+    let timestamp = pair.into_inner().next().ok_or("Missing timestamp")?;
+    if timestamp.as_rule() == Rule::identifier {
+        return Ok(Requirement::After {
+            locktime: 0,
+            timelock_var: Some(timestamp.as_str().to_string()),
+        });
+    }
+    let value = absolute_locktime(timestamp.as_str())?;
+    if value < 500_000_000 {
+        return Err(format!(
+            "Absolute timelock {} is below 500000000, so it would be a block height; use tx.time >= {} for a height",
+            value, value
+        ));
+    }
+    Ok(Requirement::After {
+        locktime: value,
+        timelock_var: None,
+    })
+}
+
+/// An nLockTime literal, which must fit in 32 bits
+fn absolute_locktime(text: &str) -> Result<u64, String> {
+    text.parse::<u64>()
+        .ok()
+        .filter(|v| *v <= u64::from(u32::MAX))
+        .ok_or_else(|| {
+            format!(
+                "Absolute timelock {} exceeds the maximum nLockTime of {}",
+                text,
+                u32::MAX
+            )
+        })
+}
+
+/// Parse tx.age >= duration → Older requirement
+fn parse_age_comparison(pair: Pair<Rule>) -> Result<Requirement, String> {
+    let age = pair
+        .into_inner()
+        .next()
+        .ok_or("Missing relative timelock")?;
+    if age.as_rule() == Rule::identifier {
+        return Ok(Requirement::Older {
+            age: Duration::Blocks(0),
+            timelock_var: Some(age.as_str().to_string()),
+        });
+    }
+    let text = age.as_str();
+    let age = match text.parse::<u64>() {
+        Ok(blocks) => Duration::Blocks(blocks),
+        Err(_) => {
+            Duration::parse(text).ok_or_else(|| format!("Invalid relative timelock '{}'", text))?
+        }
+    };
+    age.sequence()?;
+    Ok(Requirement::Older {
+        age,
+        timelock_var: None,
     })
 }
 
@@ -748,7 +1224,7 @@ fn parse_identifier_comparison(pair: Pair<Rule>) -> Result<Requirement, String>
     // Special case for time comparisons
     if left == "tx.time" && op == ">=" {
         return Ok(Requirement::After {
-            blocks: 0,
+            locktime: 0,
             timelock_var: Some(right),
         });
     }
@@ -792,19 +1268,62 @@ fn parse_property_comparison(pair: Pair<Rule>) -> Result<Requirement, String> {
     Ok(Requirement::Comparison { left, op, right })
 }
 
-/// Parse sha256(preimage) == hash → HashEqual requirement
+/// Parse sha256(preimage) == hash, or its hash160, ripemd160 and hash256
+/// forms → HashEqual requirement
 fn parse_hash_comparison(pair: Pair<Rule>) -> Result<Requirement, String> {
     let mut inner = pair.into_inner();
-    let sha256_func = inner.next().ok_or("Missing hash function")?;
-    let mut sha256_inner = sha256_func.into_inner();
-    let preimage = sha256_inner
+    let hash_func = inner.next().ok_or("Missing hash function")?;
+    let mut hash_inner = hash_func.into_inner();
+    let name = hash_inner.next().ok_or("Missing hash function")?.as_str();
+    let function =
+        HashFunction::from_name(name).ok_or_else(|| format!("Unknown hash function '{}'", name))?;
+    let preimage = hash_inner
         .next()
         .ok_or("Missing preimage")?
         .as_str()
         .to_string();
     let hash = inner.next().ok_or("Missing the hash")?.as_str().to_string();
 
-    Ok(Requirement::HashEqual { preimage, hash })
+    Ok(Requirement::HashEqual {
+        preimage,
+        hash,
+        function,
+    })
+}
+
+/// Parse size(value) between min and max → SizeBetween requirement
+fn parse_size_bounds(pair: Pair<Rule>) -> Result<Requirement, String> {
+    let mut inner = pair.into_inner();
+    let value = inner
+        .next()
+        .ok_or("Missing sized value")?
+        .as_str()
+        .to_string();
+    let mut bound = || -> Result<u64, String> {
+        let literal = inner.next().ok_or("Missing size bound")?.as_str();
+        // The upper bound plus one is pushed for OP_WITHIN as a 4-byte number
+        literal
+            .parse::<u64>()
+            .ok()
+            .filter(|bound| *bound < i32::MAX as u64)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid size bound '{}' for '{}': must be at most {}",
+                    literal,
+                    value,
+                    i32::MAX - 1
+                )
+            })
+    };
+    let (min, max) = (bound()?, bound()?);
+    if min > max {
+        return Err(format!(
+            "Size bounds of '{}' are empty: {} is greater than {}",
+            value, min, max
+        ));
+    }
+
+    Ok(Requirement::SizeBetween { value, min, max })
 }
 
 /// Parse binary operation: expr op expr → Comparison requirement
@@ -1797,7 +2316,9 @@ fn parse_constructor_args(pair: Pair<Rule>) -> Result<Vec<Expression>, String> {
 /// Parse tx_property_access into the appropriate Expression type
 /// Handles special patterns like tx.assetGroups[idx].sumInputs/sumOutputs
 fn parse_tx_property_to_expr(pair: Pair<Rule>) -> Result<Expression, String> {
-    let text = pair.as_str();
+    // A trailing optional part of the rule takes the whitespace before it,
+    // even when it is absent (`for (k, group) in tx.assetGroups {`)
+    let text = pair.as_str().trim_end();
 
     // Handle tx.assetGroups.find(assetId)
     if text.starts_with("tx.assetGroups.find(") && text.ends_with(")") {
@@ -1852,10 +2373,9 @@ fn parse_tx_property_to_expr(pair: Pair<Rule>) -> Result<Expression, String> {
     if text.starts_with("tx.input.current") {
         let property = if text == "tx.input.current" {
             None
-        } else if let Some(rest) = text.strip_prefix("tx.input.current.") {
-            Some(rest.to_string())
         } else {
-            None
+            text.strip_prefix("tx.input.current.")
+                .map(|rest| rest.to_string())
         };
         return Ok(Expression::CurrentInput(property));
     }
@@ -1866,7 +2386,7 @@ fn parse_tx_property_to_expr(pair: Pair<Rule>) -> Result<Expression, String> {
 
 /// Convert a tx_property_access or this_property_access pair into an Expression
 fn parse_tx_property_to_expression(pair: Pair<Rule>) -> Expression {
-    let property_access = pair.as_str().to_string();
+    let property_access = pair.as_str().trim_end().to_string();
 
     // Special handling for tx.input.current
     if property_access.starts_with("tx.input.current") {
@@ -1913,19 +2433,17 @@ fn parse_parameters(params: Pair<Rule>) -> Result<Vec<Parameter>, String> {
             let mut param_inner = param_pair.into_inner();
             let param_type = match param_inner.next() {
                 Some(type_pair) => {
-                    // data_type is now a compound rule: base_type ~ ("[]")?
-                    // Extract the base type and check for array suffix
+                    // data_type is a compound rule: base_type ~ ("[" ~ array_length? ~ "]")?
+                    // Normalize it to `base`, `base[]` or `base[length]`
                     let type_text = type_pair.as_str().trim();
-                    if type_text.ends_with("[]") {
-                        type_text.to_string()
-                    } else {
-                        // Parse inner to get just the base type
-                        let mut type_inner = type_pair.into_inner();
-                        if let Some(base) = type_inner.next() {
-                            base.as_str().to_string()
-                        } else {
-                            type_text.to_string()
-                        }
+                    let mut type_inner = type_pair.clone().into_inner();
+                    match type_inner.next() {
+                        Some(base) if type_text.ends_with(']') => match type_inner.next() {
+                            Some(length) => format!("{}[{}]", base.as_str(), length.as_str()),
+                            None => format!("{}[]", base.as_str()),
+                        },
+                        Some(base) => base.as_str().to_string(),
+                        None => type_text.to_string(),
                     }
                 }
                 None => return Err("Parameter is missing data type".to_string()),
@@ -0,0 +1,73 @@
+//! Strict checking of the `options` block.
+//!
+//! The grammar accepts any `name = value` setting, and the parser only reads
+//! the names it knows, so a misspelt `exitt = 144` would silently leave the
+//! contract without its timelock. [`check`] rejects unknown names, with a
+//! suggestion when one is close to a known name, and values of the wrong
+//! kind for their option, such as a string for `exit`, pointing at the
+//! offending setting.
+
+use alloc::{format, string::String};
+use pest::error::{Error, ErrorVariant};
+use pest::iterators::{Pair, Pairs};
+
+use super::Rule;
+use crate::catalog::{self, EntryKind};
+
+/// Names the `options` block accepts
+pub const OPTIONS: &[&str] = &["server", "exit", "renew", "internalKey", "strictHashlocks"];
+
+/// Reject unknown options and option values of the wrong shape in `pairs`.
+pub(super) fn check(pairs: Pairs<Rule>) -> Result<(), Error<Rule>> {
+    for pair in pairs {
+        if pair.as_rule() == Rule::option_setting {
+            if let Some(message) = problem(&pair) {
+                return Err(Error::new_from_span(
+                    ErrorVariant::CustomError { message },
+                    pair.as_span(),
+                ));
+            }
+        } else {
+            check(pair.into_inner())?;
+        }
+    }
+    Ok(())
+}
+
+/// What is wrong with the setting `pair`, if anything.
+fn problem(pair: &Pair<Rule>) -> Option<String> {
+    let mut inner = pair.clone().into_inner();
+    let name = inner.next()?.as_str();
+    let value = inner.next()?;
+    let is_identifier = value.as_rule() == Rule::identifier;
+    let expected = match name {
+        // `server` is checked against the parameters, and constant names are
+        // resolved, when compiling
+        "exit" | "renew"
+            if !is_identifier
+                && !matches!(
+                    value.as_rule(),
+                    Rule::number_literal | Rule::duration_literal
+                ) =>
+        {
+            "a number, a duration or a built-in constant"
+        }
+        "internalKey" if !is_identifier => "unspendable, nums or a pubkey parameter",
+        "server" | "exit" | "renew" | "internalKey" | "strictHashlocks" => return None,
+        _ => {
+            let hint = match catalog::suggest(EntryKind::Option, name) {
+                Some(option) => format!("did you mean '{}'?", option.name),
+                None => {
+                    String::from("expected server, exit, renew, internalKey or strictHashlocks")
+                }
+            };
+            return Some(format!("Unknown option '{}': {}", name, hint));
+        }
+    };
+    Some(format!(
+        "Option '{}' must be {}, got {}",
+        name,
+        expected,
+        value.as_str()
+    ))
+}
@@ -0,0 +1,158 @@
+//! A token stream for syntax highlighting.
+//!
+//! Literals, types and identifiers are matched with the grammar's own
+//! rules, so highlighting agrees with the parser on where a number or a
+//! name ends. The tokenizer never fails: text the grammar has no token for
+//! becomes [`TokenKind::Unknown`], and an editor can keep highlighting
+//! while the source is being typed.
+
+use alloc::vec::Vec;
+use pest::Parser;
+use serde::{Deserialize, Serialize};
+
+use super::{ArkadeParser, Rule};
+use crate::models::Span;
+
+/// Words the grammar reserves for its own structure
+pub const KEYWORDS: &[&str] = &[
+    "and", "between", "const", "contract", "else", "emit", "event", "for", "function", "if",
+    "import", "in", "internal", "let", "macro", "new", "options", "pragma", "require",
+];
+
+/// Built-in functions, and the roots of transaction and contract
+/// introspection
+pub const BUILTINS: &[&str] = &[
+    "after",
+    "checkMultisig",
+    "checkSig",
+    "checkSigCount",
+    "checkSigFromStack",
+    "checkSigFromStackVerify",
+    "ecMulScalarVerify",
+    "hash160",
+    "hash256",
+    "le32ToLe64",
+    "le64ToScriptNum",
+    "neg64",
+    "ripemd160",
+    "sha256",
+    "sha256Finalize",
+    "sha256Initialize",
+    "sha256Update",
+    "size",
+    "this",
+    "tweakVerify",
+    "tx",
+    "verifyScalarMul",
+    "verifyTweak",
+];
+
+/// Longest first, so `>=` is not read as `>` and `=`
+const OPERATORS: &[&str] = &[
+    ">=", "<=", "==", "!=", "&&", "||", ">", "<", "=", "!", "+", "-", "*", "/", "%",
+];
+
+const PUNCTUATION: &[char] = &['(', ')', '{', '}', '[', ']', ';', ',', '.'];
+
+/// What a token is, for choosing its color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenKind {
+    Keyword,
+    /// A parameter type such as `pubkey` or `bytes32`
+    Type,
+    Builtin,
+    Identifier,
+    Number,
+    String,
+    /// A `//` comment, up to the end of its line
+    Comment,
+    Operator,
+    Punctuation,
+    /// Text the grammar has no token for
+    Unknown,
+}
+
+/// One token of the source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+/// Split `source` into tokens, skipping whitespace.
+pub fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut start = Span {
+        start: 0,
+        end: 0,
+        line: 1,
+        column: 1,
+    };
+    while let Some(c) = source[start.start..].chars().next() {
+        let rest = &source[start.start..];
+        let (kind, len) = if c.is_whitespace() {
+            start = advance(start, &rest[..c.len_utf8()]);
+            continue;
+        } else if rest.starts_with("//") {
+            (TokenKind::Comment, rest.find('\n').unwrap_or(rest.len()))
+        } else if let Some(len) = matched(Rule::string_literal, rest) {
+            (TokenKind::String, len)
+        } else if let Some(len) = matched(Rule::number_literal, rest) {
+            (TokenKind::Number, len)
+        } else if let Some(len) = matched(Rule::identifier, rest) {
+            (classify(&rest[..len]), len)
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(*op)) {
+            (TokenKind::Operator, op.len())
+        } else if PUNCTUATION.contains(&c) {
+            (TokenKind::Punctuation, 1)
+        } else {
+            (TokenKind::Unknown, c.len_utf8())
+        };
+        let next = advance(start, &rest[..len]);
+        tokens.push(Token {
+            kind,
+            span: Span {
+                end: next.start,
+                ..start
+            },
+        });
+        start = next;
+    }
+    tokens
+}
+
+/// The kind of a word the grammar reads as an identifier
+fn classify(word: &str) -> TokenKind {
+    if matched(Rule::base_type, word) == Some(word.len()) {
+        TokenKind::Type
+    } else if KEYWORDS.contains(&word) {
+        TokenKind::Keyword
+    } else if BUILTINS.contains(&word) {
+        TokenKind::Builtin
+    } else {
+        TokenKind::Identifier
+    }
+}
+
+/// Length of the text `rule` matches at the start of `text`
+fn matched(rule: Rule, text: &str) -> Option<usize> {
+    let pair = ArkadeParser::parse(rule, text).ok()?.next()?;
+    let len = pair.as_span().end();
+    (len > 0).then_some(len)
+}
+
+/// The position after `text`, which starts at `position`
+fn advance(position: Span, text: &str) -> Span {
+    let mut next = position;
+    for c in text.chars() {
+        next.start += c.len_utf8();
+        if c == '\n' {
+            next.line += 1;
+            next.column = 1;
+        } else {
+            next.column += 1;
+        }
+    }
+    next
+}
@@ -0,0 +1,76 @@
+//! Comments, attached to the AST as [`Trivia`].
+//!
+//! The grammar skips comments, so they are read from the source separately
+//! and attached by line: the comment lines directly above a node lead it,
+//! and a comment after code on the node's first line trails it.
+
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::models::{Span, Trivia};
+
+/// A `//` comment.
+struct Comment {
+    /// Whether the comment is alone on its line
+    own_line: bool,
+    /// Text after the `//` and the space after it, without trailing space
+    text: String,
+}
+
+/// The comments in a source, by 1-based line.
+pub(super) struct Comments {
+    lines: BTreeMap<usize, Comment>,
+}
+
+impl Comments {
+    /// Find the comments in `source`, skipping `//` in strings.
+    pub(super) fn scan(source: &str) -> Self {
+        let mut lines = BTreeMap::new();
+        for (index, line) in source.lines().enumerate() {
+            let mut in_string = false;
+            let mut chars = line.char_indices().peekable();
+            while let Some((offset, c)) = chars.next() {
+                match c {
+                    '"' => in_string = !in_string,
+                    '/' if !in_string && chars.peek().is_some_and(|(_, next)| *next == '/') => {
+                        let text = &line[offset + 2..];
+                        let text = text.strip_prefix(' ').unwrap_or(text).trim_end();
+                        let comment = Comment {
+                            own_line: line[..offset].trim().is_empty(),
+                            text: text.to_string(),
+                        };
+                        lines.insert(index + 1, comment);
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Comments { lines }
+    }
+
+    /// The comments around the node at `span`.
+    pub(super) fn around(&self, span: &Span) -> Trivia {
+        let mut leading = Vec::new();
+        let mut line = span.line;
+        while let Some(comment) = line
+            .checked_sub(1)
+            .and_then(|above| self.lines.get(&above))
+            .filter(|comment| comment.own_line)
+        {
+            leading.push(comment.text.clone());
+            line -= 1;
+        }
+        leading.reverse();
+
+        let trailing = self
+            .lines
+            .get(&span.line)
+            .filter(|comment| !comment.own_line)
+            .map(|comment| comment.text.clone());
+        Trivia { leading, trailing }
+    }
+}
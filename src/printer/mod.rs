@@ -0,0 +1,552 @@
+//! AST-to-source printer.
+//!
+//! Renders a parsed [`Contract`] back to Arkade Script. Parsing the output
+//! gives the same AST, up to source locations, for every contract the
+//! parser produces: the printer writes each node in the form the parser
+//! reads it from, with the parentheses operator precedence requires.
+//!
+//! The output is canonical: one statement per line, two spaces per level
+//! and single spaces around operators. [`crate::formatter::canonical`]
+//! formats with it, and a contract built in code is saved through it with
+//! `contract.to_string()`. The comments of the contract and its functions
+//! are printed from their [`crate::models::Trivia`]; comments inside
+//! function bodies, macros and `require` messages are not in the AST, so
+//! they are not printed.
+
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::encoding;
+use crate::models::{
+    AssetLookupSource, Contract, Expression, Function, GroupIOSource, GroupSumSource, Requirement,
+    Statement,
+};
+
+const INDENT: &str = "  ";
+
+/// Source for `contract`.
+pub fn print(contract: &Contract) -> String {
+    let mut out = String::new();
+    if let Some(edition) = contract.edition {
+        out.push_str(&format!("pragma arkade {};\n", edition));
+    }
+    if let Some(compiler) = &contract.compiler {
+        out.push_str(&format!("pragma compiler {};\n", compiler));
+    }
+    if contract.edition.is_some() || contract.compiler.is_some() {
+        out.push('\n');
+    }
+    for import in &contract.imports {
+        out.push_str(&format!("import \"{}\";\n", import));
+    }
+    if !contract.imports.is_empty() {
+        out.push('\n');
+    }
+
+    // The contract's comments go around its first line, which opens the
+    // options block if there is one
+    print_comments(&contract.trivia.leading, "", &mut out);
+    let mut trailing = contract.trivia.trailing.as_deref();
+    let options = options(contract);
+    if !options.is_empty() {
+        out.push_str("options {");
+        print_trailing(trailing.take(), &mut out);
+        for (name, value) in options {
+            out.push_str(&format!("{}{} = {};\n", INDENT, name, value));
+        }
+        out.push_str("}\n\n");
+    }
+
+    out.push_str("contract ");
+    out.push_str(&contract.name);
+    if !contract.generics.is_empty() {
+        out.push_str(&format!("<{}>", contract.generics.join(", ")));
+    }
+    out.push_str(&format!("({}) {{", parameters(&contract.parameters)));
+    print_trailing(trailing, &mut out);
+    for (name, value) in &contract.constants {
+        out.push_str(&format!("{}const {} = {};\n", INDENT, name, value));
+    }
+    if !contract.constants.is_empty()
+        && (!contract.events.is_empty() || !contract.functions.is_empty())
+    {
+        out.push('\n');
+    }
+    for event in &contract.events {
+        out.push_str(&format!(
+            "{}event {}({});\n",
+            INDENT,
+            event.name,
+            parameters(&event.parameters)
+        ));
+    }
+    if !contract.events.is_empty() && !contract.functions.is_empty() {
+        out.push('\n');
+    }
+    for (i, function) in contract.functions.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        print_function(function, &mut out);
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// The options block's settings, in the order they are conventionally
+/// written.
+fn options(contract: &Contract) -> Vec<(&'static str, String)> {
+    let mut options = Vec::new();
+    if contract.has_server_key {
+        let server = contract.server_key.as_deref().unwrap_or("server");
+        options.push(("server", server.to_string()));
+    }
+    let timelock = |name: &str, blocks: Option<u64>| {
+        blocks
+            .map(|b| b.to_string())
+            .or_else(|| contract.named_options.get(name).cloned())
+    };
+    if let Some(renew) = timelock("renew", contract.renewal_timelock) {
+        options.push(("renew", renew));
+    }
+    if let Some(exit) = timelock("exit", contract.exit_timelock) {
+        options.push(("exit", exit));
+    }
+    if let Some(key) = &contract.internal_key {
+        options.push(("internalKey", key.clone()));
+    }
+    // Only where it differs from the edition's default
+    if contract.strict_hashlocks != contract.edition().strict_hashlocks() {
+        options.push(("strictHashlocks", contract.strict_hashlocks.to_string()));
+    }
+    options
+}
+
+fn parameters(parameters: &[crate::models::Parameter]) -> String {
+    parameters
+        .iter()
+        .map(|p| format!("{} {}", p.param_type, p.name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn print_function(function: &Function, out: &mut String) {
+    print_comments(&function.trivia.leading, INDENT, out);
+    out.push_str(&format!(
+        "{}function {}({}){} {{",
+        INDENT,
+        function.name,
+        parameters(&function.parameters),
+        if function.is_internal {
+            " internal"
+        } else {
+            ""
+        }
+    ));
+    print_trailing(function.trivia.trailing.as_deref(), out);
+    print_block(&function.statements, 2, out);
+    for emit in &function.emits {
+        out.push_str(&format!(
+            "{}emit {}({});\n",
+            INDENT.repeat(2),
+            emit.event,
+            emit.args.join(", ")
+        ));
+    }
+    out.push_str(INDENT);
+    out.push_str("}\n");
+}
+
+/// Comment lines, each on its own line at `indent`.
+fn print_comments(comments: &[String], indent: &str, out: &mut String) {
+    for comment in comments {
+        out.push_str(indent);
+        out.push_str(&comment_text(comment));
+        out.push('\n');
+    }
+}
+
+/// Ends a line, after a trailing comment if there is one.
+fn print_trailing(comment: Option<&str>, out: &mut String) {
+    if let Some(comment) = comment {
+        out.push(' ');
+        out.push_str(&comment_text(comment));
+    }
+    out.push('\n');
+}
+
+fn comment_text(comment: &str) -> String {
+    if comment.is_empty() {
+        "//".to_string()
+    } else {
+        format!("// {}", comment)
+    }
+}
+
+fn print_block(statements: &[Statement], depth: usize, out: &mut String) {
+    for statement in statements {
+        print_statement(statement, depth, out);
+    }
+}
+
+fn print_statement(statement: &Statement, depth: usize, out: &mut String) {
+    let indent = INDENT.repeat(depth);
+    match statement {
+        Statement::Require(requirement, _) => {
+            out.push_str(&format!(
+                "{}require({});\n",
+                indent,
+                print_requirement(requirement)
+            ));
+        }
+        Statement::LetBinding { name, value } => {
+            out.push_str(&format!(
+                "{}let {} = {};\n",
+                indent,
+                name,
+                print_expression(value)
+            ));
+        }
+        Statement::VarAssign { name, value } => {
+            out.push_str(&format!(
+                "{}{} = {};\n",
+                indent,
+                name,
+                print_expression(value)
+            ));
+        }
+        Statement::IfElse {
+            condition,
+            then_body,
+            else_body,
+            ..
+        } => {
+            out.push_str(&format!(
+                "{}if ({}) {{\n",
+                indent,
+                print_expression(condition)
+            ));
+            print_block(then_body, depth + 1, out);
+            let mut else_body = else_body;
+            // An else branch holding nothing but an `if` reads as `else if`
+            while let Some(
+                [Statement::IfElse {
+                    condition,
+                    then_body,
+                    else_body: next,
+                    ..
+                }],
+            ) = else_body.as_deref()
+            {
+                out.push_str(&format!(
+                    "{}}} else if ({}) {{\n",
+                    indent,
+                    print_expression(condition)
+                ));
+                print_block(then_body, depth + 1, out);
+                else_body = next;
+            }
+            if let Some(else_body) = else_body {
+                out.push_str(&format!("{}}} else {{\n", indent));
+                print_block(else_body, depth + 1, out);
+            }
+            out.push_str(&format!("{}}}\n", indent));
+        }
+        Statement::ForIn {
+            index_var,
+            value_var,
+            iterable,
+            body,
+        } => {
+            out.push_str(&format!(
+                "{}for ({}, {}) in {} {{\n",
+                indent,
+                index_var,
+                value_var,
+                print_expression(iterable)
+            ));
+            print_block(body, depth + 1, out);
+            out.push_str(&format!("{}}}\n", indent));
+        }
+    }
+}
+
+/// Source for the condition of a `require`.
+pub fn print_requirement(requirement: &Requirement) -> String {
+    match requirement {
+        Requirement::CheckSig {
+            signature,
+            pubkey,
+            sighash: None,
+        } => format!("checkSig({}, {})", signature, pubkey),
+        Requirement::CheckSig {
+            signature,
+            pubkey,
+            sighash: Some(flag),
+        } => format!(
+            "checkSig({}, {}, {})",
+            signature,
+            pubkey,
+            encoding::sighash_names(*flag)
+        ),
+        Requirement::CheckSigFromStack {
+            signature,
+            pubkey,
+            message,
+        } => format!("checkSigFromStack({}, {}, {})", signature, pubkey, message),
+        Requirement::CheckMultisig { pubkeys, threshold } => {
+            // A `pubkey[]` parameter is the single key `name[]`; a threshold
+            // of 0 means every key
+            let keys = match pubkeys.as_slice() {
+                [array] if array.ends_with("[]") => array.trim_end_matches("[]").to_string(),
+                _ => format!("[{}]", pubkeys.join(", ")),
+            };
+            match threshold {
+                0 => format!("checkMultisig({})", keys),
+                t => format!("checkMultisig({}, {})", keys, t),
+            }
+        }
+        Requirement::After {
+            timelock_var: Some(var),
+            ..
+        } => format!("tx.time >= {}", var),
+        Requirement::After { locktime, .. } => format!("tx.time >= {}", locktime),
+        Requirement::Older {
+            timelock_var: Some(var),
+            ..
+        } => format!("tx.age >= {}", var),
+        Requirement::Older { age, .. } => format!("tx.age >= {}", age),
+        Requirement::HashEqual {
+            preimage,
+            hash,
+            function,
+        } => format!("{}({}) == {}", function, preimage, hash),
+        Requirement::SizeBetween { value, min, max } => {
+            format!("size({}) between {} and {}", value, min, max)
+        }
+        // A builtin or property required on its own
+        Requirement::Comparison { left, op, right }
+            if op == "==" && matches!(right, Expression::Literal(l) if l == "true") =>
+        {
+            print_expression(left)
+        }
+        // Arithmetic required on its own, as the few fixed forms parse it
+        Requirement::Comparison { left, op, right } if precedence(op) > 2 => {
+            format!("{} {} {}", print_operand(left), op, print_operand(right))
+        }
+        Requirement::Comparison { left, op, right } => print_expression(&Expression::BinaryOp {
+            left: Box::new(left.clone()),
+            op: op.clone(),
+            right: Box::new(right.clone()),
+        }),
+    }
+}
+
+/// An operand of arithmetic required on its own, which is never
+/// parenthesized.
+fn print_operand(expression: &Expression) -> String {
+    match expression {
+        Expression::BinaryOp { left, op, right } => {
+            format!("{} {} {}", print_operand(left), op, print_operand(right))
+        }
+        _ => print_expression(expression),
+    }
+}
+
+/// Binding strength of a binary operator, from `||` (loosest) to
+/// multiplication and division.
+fn precedence(op: &str) -> u8 {
+    match op {
+        "*" | "/" | "%" => 4,
+        "+" | "-" => 3,
+        "&&" => 1,
+        "||" => 0,
+        _ => 2,
+    }
+}
+
+/// Source for an expression.
+pub fn print_expression(expression: &Expression) -> String {
+    match expression {
+        Expression::Variable(name) | Expression::Literal(name) | Expression::Property(name) => {
+            name.clone()
+        }
+        Expression::CurrentInput(None) => "tx.input.current".to_string(),
+        Expression::CurrentInput(Some(property)) => format!("tx.input.current.{}", property),
+        Expression::AssetLookup {
+            source,
+            index,
+            asset_id,
+        } => format!(
+            "tx.{}[{}].assets.lookup({})",
+            io(source),
+            print_expression(index),
+            asset_id
+        ),
+        Expression::AssetCount { source, index } => {
+            format!(
+                "tx.{}[{}].assets.length",
+                io(source),
+                print_expression(index)
+            )
+        }
+        Expression::AssetAt {
+            source,
+            io_index,
+            asset_index,
+            property,
+        } => format!(
+            "tx.{}[{}].assets[{}].{}",
+            io(source),
+            print_expression(io_index),
+            print_expression(asset_index),
+            property
+        ),
+        Expression::TxIntrospection { property } => format!("tx.{}", property),
+        Expression::InputIntrospection { index, property } => {
+            format!("tx.inputs[{}].{}", print_expression(index), property)
+        }
+        Expression::OutputIntrospection { index, property } => {
+            format!("tx.outputs[{}].{}", print_expression(index), property)
+        }
+        Expression::BinaryOp { left, op, right } => {
+            let strength = precedence(op);
+            // Comparisons do not chain, and the rest associate to the left
+            let left_needs = match left.as_ref() {
+                Expression::BinaryOp { op: inner, .. } => {
+                    precedence(inner) < strength || (strength == 2 && precedence(inner) == 2)
+                }
+                _ => false,
+            };
+            let right_needs = match right.as_ref() {
+                Expression::BinaryOp { op: inner, .. } => precedence(inner) <= strength,
+                _ => false,
+            };
+            format!(
+                "{} {} {}",
+                parenthesize(left, left_needs),
+                op,
+                parenthesize(right, right_needs)
+            )
+        }
+        Expression::Not { value } => format!(
+            "!{}",
+            parenthesize(value, matches!(**value, Expression::BinaryOp { .. }))
+        ),
+        Expression::GroupFind { asset_id } => format!("tx.assetGroups.find({})", asset_id),
+        Expression::GroupProperty { group, property } if group.starts_with("assetGroups[") => {
+            format!("tx.{}.{}", group, property)
+        }
+        Expression::GroupProperty { group, property } => format!("{}.{}", group, property),
+        Expression::AssetGroupsLength => "tx.assetGroups.length".to_string(),
+        Expression::GroupSum { index, source } => format!(
+            "tx.assetGroups[{}].{}",
+            print_expression(index),
+            match source {
+                GroupSumSource::Inputs => "sumInputs",
+                GroupSumSource::Outputs => "sumOutputs",
+            }
+        ),
+        Expression::GroupNumIO { index, source } => format!(
+            "tx.assetGroups[{}].{}",
+            print_expression(index),
+            match source {
+                GroupIOSource::Inputs => "numInputs",
+                GroupIOSource::Outputs => "numOutputs",
+            }
+        ),
+        Expression::GroupIOAccess {
+            group_index,
+            io_index,
+            source,
+            property,
+        } => {
+            let access = format!(
+                "tx.assetGroups[{}].{}[{}]",
+                print_expression(group_index),
+                match source {
+                    GroupIOSource::Inputs => "inputs",
+                    GroupIOSource::Outputs => "outputs",
+                },
+                print_expression(io_index)
+            );
+            match property {
+                Some(property) => format!("{}.{}", access, property),
+                None => access,
+            }
+        }
+        Expression::ArrayIndex { array, index } => {
+            format!("{}[{}]", print_expression(array), print_expression(index))
+        }
+        Expression::ArrayLength(array) => format!("{}.length", array),
+        Expression::CheckSigExpr { signature, pubkey } => {
+            format!("checkSig({}, {})", signature, pubkey)
+        }
+        Expression::CheckSigCount { pubkeys } => {
+            format!("checkSigCount([{}])", pubkeys.join(", "))
+        }
+        Expression::CheckSigFromStackExpr {
+            signature,
+            pubkey,
+            message,
+        } => format!("checkSigFromStack({}, {}, {})", signature, pubkey, message),
+        Expression::Sha256Initialize { data } => call("sha256Initialize", &[data]),
+        Expression::Sha256Update { context, chunk } => call("sha256Update", &[context, chunk]),
+        Expression::Sha256Finalize {
+            context,
+            last_chunk,
+        } => call("sha256Finalize", &[context, last_chunk]),
+        Expression::Neg64 { value } => call("neg64", &[value]),
+        Expression::Le64ToScriptNum { value } => call("le64ToScriptNum", &[value]),
+        Expression::Le32ToLe64 { value } => call("le32ToLe64", &[value]),
+        Expression::EcMulScalarVerify {
+            scalar,
+            point_p,
+            point_q,
+        } => call("verifyScalarMul", &[scalar, point_p, point_q]),
+        Expression::TweakVerify {
+            point_p,
+            tweak,
+            point_q,
+        } => call("verifyTweak", &[point_p, tweak, point_q]),
+        Expression::CheckSigFromStackVerify {
+            signature,
+            pubkey,
+            message,
+        } => format!(
+            "checkSigFromStackVerify({}, {}, {})",
+            signature, pubkey, message
+        ),
+        Expression::ContractInstance {
+            contract_name,
+            args,
+        } => {
+            let args: Vec<String> = args.iter().map(print_expression).collect();
+            format!("new {}({})", contract_name, args.join(", "))
+        }
+    }
+}
+
+fn parenthesize(expression: &Expression, parentheses: bool) -> String {
+    if parentheses {
+        format!("({})", print_expression(expression))
+    } else {
+        print_expression(expression)
+    }
+}
+
+fn call(name: &str, args: &[&Expression]) -> String {
+    let args: Vec<String> = args.iter().map(|a| print_expression(a)).collect();
+    format!("{}({})", name, args.join(", "))
+}
+
+fn io(source: &AssetLookupSource) -> &'static str {
+    match source {
+        AssetLookupSource::Input => "inputs",
+        AssetLookupSource::Output => "outputs",
+    }
+}
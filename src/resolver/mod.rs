@@ -0,0 +1,283 @@
+//! Name resolution.
+//!
+//! Every name a function's statements use must be declared: a constructor
+//! parameter, a parameter of the function, or a name the function binds
+//! with `let`, a typed declaration or a `for` loop, and `name_i` for
+//! the elements of an array parameter `name`. Code generation writes
+//! any other name as a `<name>` placeholder, which nothing ever fills in,
+//! so [`check`] fails the compilation on the first undeclared name instead,
+//! suggesting the declared name closest to it.
+//!
+//! It runs after generic parameters and built-in constants are substituted,
+//! so neither needs declaring here.
+
+use alloc::{collections::BTreeSet, format, string::String, vec::Vec};
+
+use crate::catalog;
+use crate::models::{array_type, Contract, Expression, Function, Requirement, Span, Statement};
+
+/// Fail on the first name a function uses without declaring it.
+pub fn check(contract: &Contract) -> Result<(), String> {
+    for function in &contract.functions {
+        let parameters = || contract.parameters.iter().chain(&function.parameters);
+        // An array's elements are also written `name_i` once indexed
+        let elements: Vec<String> = parameters()
+            .filter_map(|p| array_type(&p.param_type).map(|(_, length)| (p, length)))
+            .flat_map(|(p, length)| (0..length).map(move |i| format!("{}_{}", p.name, i)))
+            .collect();
+        let mut declared: BTreeSet<&str> = parameters()
+            .map(|p| p.name.as_str())
+            .chain(elements.iter().map(String::as_str))
+            .collect();
+        bindings(&function.statements, &mut declared);
+
+        let mut uses = Vec::new();
+        statements(&function.statements, None, &mut uses);
+        if let Some((name, span)) = uses.into_iter().find(|(name, _)| !declared.contains(name)) {
+            return Err(undeclared(function, name, span, &declared));
+        }
+    }
+    Ok(())
+}
+
+fn undeclared(
+    function: &Function,
+    name: &str,
+    span: Option<Span>,
+    declared: &BTreeSet<&str>,
+) -> String {
+    let location = span.map(|s| format!(" at {}", s)).unwrap_or_default();
+    let hint = declared
+        .iter()
+        .map(|candidate| (catalog::distance(name, candidate), candidate))
+        .filter(|(edits, _)| *edits <= 2)
+        .min_by_key(|(edits, _)| *edits)
+        .map(|(_, candidate)| format!("; did you mean '{}'?", candidate))
+        .unwrap_or_default();
+    format!(
+        "fn {}: undeclared variable '{}'{}{}",
+        function.name, name, location, hint
+    )
+}
+
+/// The names `statements` bind, at any depth.
+fn bindings<'a>(statements: &'a [Statement], declared: &mut BTreeSet<&'a str>) {
+    for statement in statements {
+        match statement {
+            Statement::LetBinding { name, .. } => {
+                declared.insert(name);
+            }
+            Statement::IfElse {
+                then_body,
+                else_body,
+                ..
+            } => {
+                bindings(then_body, declared);
+                if let Some(else_body) = else_body {
+                    bindings(else_body, declared);
+                }
+            }
+            Statement::ForIn {
+                index_var,
+                value_var,
+                body,
+                ..
+            } => {
+                declared.insert(index_var);
+                declared.insert(value_var);
+                bindings(body, declared);
+            }
+            Statement::Require(..) | Statement::VarAssign { .. } => {}
+        }
+    }
+}
+
+/// The names `statements` use, with the location of the statement where
+/// it has one, in source order.
+fn statements<'a>(
+    statements: &'a [Statement],
+    span: Option<Span>,
+    uses: &mut Vec<(&'a str, Option<Span>)>,
+) {
+    for statement in statements {
+        match statement {
+            Statement::Require(requirement, span) => {
+                self::requirement(requirement, &mut |name| uses.push((name, Some(*span))));
+            }
+            Statement::LetBinding { value, .. } => {
+                expression(value, &mut |name| uses.push((name, span)));
+            }
+            Statement::VarAssign { name, value } => {
+                uses.push((name, span));
+                expression(value, &mut |name| uses.push((name, span)));
+            }
+            Statement::IfElse {
+                condition,
+                then_body,
+                else_body,
+                span,
+            } => {
+                expression(condition, &mut |name| uses.push((name, Some(*span))));
+                self::statements(then_body, Some(*span), uses);
+                if let Some(else_body) = else_body {
+                    self::statements(else_body, Some(*span), uses);
+                }
+            }
+            Statement::ForIn { iterable, body, .. } => {
+                expression(iterable, &mut |name| uses.push((name, span)));
+                self::statements(body, span, uses);
+            }
+        }
+    }
+}
+
+fn requirement<'a>(requirement: &'a Requirement, used: &mut impl FnMut(&'a str)) {
+    match requirement {
+        Requirement::CheckSig {
+            signature, pubkey, ..
+        } => {
+            name(signature, used);
+            name(pubkey, used);
+        }
+        Requirement::CheckSigFromStack {
+            signature,
+            pubkey,
+            message,
+        } => {
+            name(signature, used);
+            name(pubkey, used);
+            name(message, used);
+        }
+        Requirement::CheckMultisig { pubkeys, .. } => pubkeys.iter().for_each(|k| name(k, used)),
+        Requirement::After { timelock_var, .. } | Requirement::Older { timelock_var, .. } => {
+            if let Some(var) = timelock_var {
+                name(var, used);
+            }
+        }
+        Requirement::HashEqual { preimage, hash, .. } => {
+            name(preimage, used);
+            name(hash, used);
+        }
+        Requirement::SizeBetween { value, .. } => name(value, used),
+        Requirement::Comparison { left, right, .. } => {
+            expression(left, used);
+            expression(right, used);
+        }
+    }
+}
+
+fn expression<'a>(expr: &'a Expression, used: &mut impl FnMut(&'a str)) {
+    match expr {
+        Expression::Variable(var) => name(var, used),
+        Expression::Literal(_)
+        | Expression::Property(_)
+        | Expression::CurrentInput(_)
+        | Expression::TxIntrospection { .. }
+        | Expression::AssetGroupsLength => {}
+        Expression::AssetLookup {
+            index, asset_id, ..
+        } => {
+            expression(index, used);
+            name(asset_id, used);
+        }
+        Expression::AssetCount { index, .. }
+        | Expression::InputIntrospection { index, .. }
+        | Expression::OutputIntrospection { index, .. }
+        | Expression::GroupSum { index, .. }
+        | Expression::GroupNumIO { index, .. } => expression(index, used),
+        Expression::AssetAt {
+            io_index,
+            asset_index,
+            ..
+        } => {
+            expression(io_index, used);
+            expression(asset_index, used);
+        }
+        Expression::BinaryOp { left, right, .. } => {
+            expression(left, used);
+            expression(right, used);
+        }
+        Expression::GroupFind { asset_id } => name(asset_id, used),
+        Expression::GroupProperty { group, .. } => name(group, used),
+        Expression::GroupIOAccess {
+            group_index,
+            io_index,
+            ..
+        } => {
+            expression(group_index, used);
+            expression(io_index, used);
+        }
+        Expression::ArrayIndex { array, index } => {
+            expression(array, used);
+            expression(index, used);
+        }
+        Expression::ArrayLength(array) => name(array, used),
+        Expression::CheckSigExpr { signature, pubkey } => {
+            name(signature, used);
+            name(pubkey, used);
+        }
+        Expression::CheckSigCount { pubkeys } => pubkeys.iter().for_each(|k| name(k, used)),
+        Expression::CheckSigFromStackExpr {
+            signature,
+            pubkey,
+            message,
+        }
+        | Expression::CheckSigFromStackVerify {
+            signature,
+            pubkey,
+            message,
+        } => {
+            name(signature, used);
+            name(pubkey, used);
+            name(message, used);
+        }
+        Expression::Sha256Initialize { data } => expression(data, used),
+        Expression::Sha256Update { context, chunk } => {
+            expression(context, used);
+            expression(chunk, used);
+        }
+        Expression::Sha256Finalize {
+            context,
+            last_chunk,
+        } => {
+            expression(context, used);
+            expression(last_chunk, used);
+        }
+        Expression::Neg64 { value }
+        | Expression::Not { value }
+        | Expression::Le64ToScriptNum { value }
+        | Expression::Le32ToLe64 { value } => expression(value, used),
+        Expression::EcMulScalarVerify {
+            scalar,
+            point_p,
+            point_q,
+        } => {
+            expression(scalar, used);
+            expression(point_p, used);
+            expression(point_q, used);
+        }
+        Expression::TweakVerify {
+            point_p,
+            tweak,
+            point_q,
+        } => {
+            expression(point_p, used);
+            expression(tweak, used);
+            expression(point_q, used);
+        }
+        Expression::ContractInstance { args, .. } => args.iter().for_each(|a| expression(a, used)),
+    }
+}
+
+/// Report `text` if it is a name rather than a literal.
+fn name<'a>(text: &'a str, used: &mut impl FnMut(&'a str)) {
+    let is_name = text
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !matches!(text, "true" | "false");
+    if is_name {
+        used(text);
+    }
+}
@@ -0,0 +1,237 @@
+//! Semantic tokens for editors.
+//!
+//! [`parser::tokenize`] tells keywords from identifiers; this module tells
+//! identifiers apart by what they name. A [`SymbolTable`] built from the
+//! parsed contract resolves each name in the function it appears in, so a
+//! name bound by `let` in one function is a local only there. [`encode`]
+//! packs the result in the Language Server Protocol's semantic-token
+//! format, with [`TOKEN_TYPES`] as the legend.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Contract, Span, Statement};
+use crate::parser::{self, TokenKind};
+
+/// What an identifier names. The order is the index [`encode`] uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SemanticKind {
+    /// A constructor, function or macro parameter, or a generic parameter
+    Parameter,
+    /// A name bound by `let`, a typed declaration or a `for` loop
+    Local,
+    /// A built-in function, or `tx` and `this`
+    Builtin,
+    /// A property read with `.`, such as `time` in `tx.time`
+    TxProperty,
+    /// The contract being declared, or one instantiated with `new`
+    ContractName,
+}
+
+/// Token type names for the LSP legend, indexed by [`SemanticKind`]
+pub const TOKEN_TYPES: &[&str] = &[
+    "parameter",
+    "local",
+    "builtin",
+    "txProperty",
+    "contractName",
+];
+
+/// An identifier and what it names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SemanticToken {
+    pub kind: SemanticKind,
+    pub span: Span,
+}
+
+/// The names a contract declares, by scope.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+    /// Constructor and generic parameters, visible everywhere
+    pub parameters: BTreeSet<String>,
+    /// Each function's own parameters and locals
+    pub functions: BTreeMap<String, Scope>,
+}
+
+/// The names declared inside one function.
+#[derive(Debug, Clone, Default)]
+pub struct Scope {
+    pub parameters: BTreeSet<String>,
+    pub locals: BTreeSet<String>,
+}
+
+impl SymbolTable {
+    /// Collect the declarations of `contract`.
+    pub fn of(contract: &Contract) -> Self {
+        let parameters = contract
+            .parameters
+            .iter()
+            .map(|p| p.name.clone())
+            .chain(contract.generics.iter().cloned())
+            .collect();
+        let functions = contract
+            .functions
+            .iter()
+            .map(|function| {
+                let mut scope = Scope {
+                    parameters: function.parameters.iter().map(|p| p.name.clone()).collect(),
+                    locals: BTreeSet::new(),
+                };
+                collect_locals(&function.statements, &mut scope.locals);
+                (function.name.clone(), scope)
+            })
+            .collect();
+        SymbolTable {
+            parameters,
+            functions,
+        }
+    }
+
+    /// What `name` refers to inside `scope`, if it is declared.
+    fn resolve(&self, scope: Option<&Scope>, name: &str) -> Option<SemanticKind> {
+        if let Some(scope) = scope {
+            if scope.locals.contains(name) {
+                return Some(SemanticKind::Local);
+            }
+            if scope.parameters.contains(name) {
+                return Some(SemanticKind::Parameter);
+            }
+        }
+        self.parameters
+            .contains(name)
+            .then_some(SemanticKind::Parameter)
+    }
+}
+
+fn collect_locals(statements: &[Statement], locals: &mut BTreeSet<String>) {
+    for statement in statements {
+        match statement {
+            Statement::LetBinding { name, .. } => {
+                locals.insert(name.clone());
+            }
+            Statement::IfElse {
+                then_body,
+                else_body,
+                ..
+            } => {
+                collect_locals(then_body, locals);
+                if let Some(else_body) = else_body {
+                    collect_locals(else_body, locals);
+                }
+            }
+            Statement::ForIn {
+                index_var,
+                value_var,
+                body,
+                ..
+            } => {
+                locals.insert(index_var.clone());
+                locals.insert(value_var.clone());
+                collect_locals(body, locals);
+            }
+            Statement::Require(..) | Statement::VarAssign { .. } => {}
+        }
+    }
+}
+
+/// Classify the identifiers of `source`.
+///
+/// Builtins, properties and contract names are found even if `source` does
+/// not parse; parameters and locals need the symbol table, so they are only
+/// reported for source that does.
+pub fn semantic_tokens(source: &str) -> Vec<SemanticToken> {
+    let symbols = parser::parse(source)
+        .map(|contract| SymbolTable::of(&contract))
+        .unwrap_or_default();
+    let tokens = parser::tokenize(source);
+    let text = |index: usize| {
+        tokens
+            .get(index)
+            .map(|token: &parser::Token| &source[token.span.start..token.span.end])
+    };
+
+    let mut result = Vec::new();
+    // The function or macro whose header or body the tokens are in
+    let mut scope: Option<&Scope> = None;
+    let mut macro_scope: Option<Scope> = None;
+    for (index, token) in tokens.iter().enumerate() {
+        let previous = index.checked_sub(1).and_then(text);
+        let kind = match token.kind {
+            TokenKind::Keyword if text(index) == Some("function") => {
+                scope = text(index + 1).and_then(|name| symbols.functions.get(name));
+                macro_scope = None;
+                None
+            }
+            TokenKind::Keyword if text(index) == Some("macro") => {
+                scope = None;
+                macro_scope = Some(macro_parameters(&tokens, index, source));
+                None
+            }
+            TokenKind::Builtin => Some(SemanticKind::Builtin),
+            TokenKind::Identifier => {
+                let name = text(index).unwrap_or_default();
+                match previous {
+                    Some(".") => Some(SemanticKind::TxProperty),
+                    Some("contract") | Some("new") => Some(SemanticKind::ContractName),
+                    _ => symbols.resolve(macro_scope.as_ref().or(scope), name),
+                }
+            }
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            result.push(SemanticToken {
+                kind,
+                span: token.span,
+            });
+        }
+    }
+    result
+}
+
+/// The untyped parameters of the macro declared at `tokens[index]`.
+fn macro_parameters(tokens: &[parser::Token], index: usize, source: &str) -> Scope {
+    let parameters = tokens[index..]
+        .iter()
+        .skip_while(|token| &source[token.span.start..token.span.end] != "(")
+        .take_while(|token| &source[token.span.start..token.span.end] != ")")
+        .filter(|token| token.kind == TokenKind::Identifier)
+        .map(|token| source[token.span.start..token.span.end].to_string())
+        .collect();
+    Scope {
+        parameters,
+        locals: BTreeSet::new(),
+    }
+}
+
+/// Encode `tokens` of `source` as LSP semantic-token data.
+///
+/// Each token is five integers: line delta, start delta (from the previous
+/// token on the same line, else from the line start), length, type index
+/// into [`TOKEN_TYPES`] and modifiers (none are defined). Lines are
+/// 0-based and positions count UTF-16 code units, as the protocol requires.
+pub fn encode(source: &str, tokens: &[SemanticToken]) -> Vec<u32> {
+    let mut data = Vec::with_capacity(tokens.len() * 5);
+    let (mut last_line, mut last_start) = (0, 0);
+    for token in tokens {
+        let line = token.span.line as u32 - 1;
+        let line_start = source[..token.span.start]
+            .rfind('\n')
+            .map_or(0, |offset| offset + 1);
+        let start = utf16_len(&source[line_start..token.span.start]);
+        let length = utf16_len(&source[token.span.start..token.span.end]);
+        let delta_start = if line == last_line {
+            start - last_start
+        } else {
+            start
+        };
+        data.extend([line - last_line, delta_start, length, token.kind as u32, 0]);
+        (last_line, last_start) = (line, start);
+    }
+    data
+}
+
+fn utf16_len(text: &str) -> u32 {
+    text.encode_utf16().count() as u32
+}
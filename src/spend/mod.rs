@@ -0,0 +1,289 @@
+//! Complete tapscript witnesses, as rust-bitcoin types.
+//!
+//! [`SpendBuilder`] puts the other steps of spending a contract together:
+//! it links the constructor arguments ([`linker`](crate::linker)), commits
+//! to the leaf scripts in the contract's taproot tree
+//! ([`taproot`](crate::taproot)) and builds the path's witness elements
+//! ([`WitnessBuilder`]), then appends the leaf script and its control block.
+//! The result goes straight into a transaction input.
+//!
+//! [`SpendBuilder::psbt`] stops short of the witness: it returns a PSBT
+//! whose input carries the leaf script, its control block and the output
+//! being spent, with a placeholder for each witness element that signers
+//! fill in.
+
+use std::collections::{BTreeMap, HashMap};
+
+use bitcoin::hashes::Hash;
+use bitcoin::psbt::{raw::ProprietaryKey, Psbt};
+use bitcoin::taproot::{ControlBlock, LeafVersion, TapNodeHash};
+use bitcoin::transaction::Version;
+use bitcoin::{
+    absolute, Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
+    XOnlyPublicKey,
+};
+
+use crate::encoding::Value;
+use crate::linker;
+use crate::models::{variant_label, AbiFunction, ContractJson};
+use crate::taproot::TaprootOutput;
+use crate::witness::WitnessBuilder;
+
+/// Prefix of the proprietary keys [`SpendBuilder::psbt`] writes.
+pub const PSBT_PREFIX: &[u8] = b"arkade";
+
+/// Proprietary subtype of a witness element placeholder. The key is the
+/// element's index in the witness, bottom first, then its name; the value
+/// is its stack items, consensus-encoded as a witness, or empty while the
+/// element is still to be filled in.
+pub const PSBT_WITNESS_ELEMENT: u8 = 0x00;
+
+/// Builds the witness spending one function variant of a contract.
+///
+/// ```ignore
+/// let spend = SpendBuilder::new(&artifact)
+///     .params(constructor_args)
+///     .function("claim", false)
+///     .arg("receiverSig", sig_hex)
+///     .arg("preimage", preimage_hex)
+///     .build()?;
+/// tx.input[0].witness = spend.witness;
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpendBuilder<'a> {
+    artifact: &'a ContractJson,
+    params: HashMap<String, String>,
+    function: Option<(String, bool)>,
+    args: BTreeMap<String, String>,
+    internal_key: Option<XOnlyPublicKey>,
+}
+
+/// A spend of a contract output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Spend {
+    /// The witness elements, then the leaf script and its control block
+    pub witness: Witness,
+    /// The leaf script spent through
+    pub leaf_script: ScriptBuf,
+    pub control_block: ControlBlock,
+    /// The scriptPubKey of the output being spent
+    pub script_pubkey: ScriptBuf,
+}
+
+impl<'a> SpendBuilder<'a> {
+    pub fn new(artifact: &'a ContractJson) -> Self {
+        SpendBuilder {
+            artifact,
+            params: HashMap::new(),
+            function: None,
+            args: BTreeMap::new(),
+            internal_key: None,
+        }
+    }
+
+    /// Set the constructor argument `name`, as [`linker::link`] takes it.
+    /// [`linker::SERVER_KEY`] sets the server key.
+    pub fn param(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.params.insert(name.to_string(), value.into());
+        self
+    }
+
+    /// Set several constructor arguments.
+    pub fn params<I, K, V>(mut self, params: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.params
+            .extend(params.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    /// Spend through `function`'s cooperative variant when `server_variant`
+    /// is set, or through its exit variant.
+    pub fn function(mut self, name: &str, server_variant: bool) -> Self {
+        self.function = Some((name.to_string(), server_variant));
+        self
+    }
+
+    /// Set the witness element `name`, as [`WitnessBuilder::arg`] takes it.
+    pub fn arg(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.args.insert(name.to_string(), value.into());
+        self
+    }
+
+    /// Set several witness elements.
+    pub fn args<I, K, V>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.args
+            .extend(args.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    /// Key the output with `key`, in place of the artifact's `internalKey`
+    /// or, without one, the NUMS point.
+    pub fn internal_key(mut self, key: XOnlyPublicKey) -> Self {
+        self.internal_key = Some(key);
+        self
+    }
+
+    /// Assemble the spend.
+    pub fn build(&self) -> Result<Spend, String> {
+        let (name, server_variant) = self
+            .function
+            .as_ref()
+            .ok_or("No function to spend through")?;
+        let items = WitnessBuilder::for_function(self.artifact, name, *server_variant)?
+            .args(self.args.clone())
+            .build()?;
+        let leaf = self.leaf()?;
+
+        let mut witness = Witness::from_slice(&items);
+        witness.push(leaf.script.as_bytes());
+        witness.push(leaf.control_block.serialize());
+        Ok(Spend {
+            witness,
+            leaf_script: leaf.script,
+            control_block: leaf.control_block,
+            script_pubkey: ScriptBuf::from_bytes(leaf.output.script_pubkey()),
+        })
+    }
+
+    /// A PSBT spending `value` at `previous_output` through the function.
+    ///
+    /// The input carries the output being spent, the internal key and
+    /// merkle root of its tree, and the leaf script with its control block.
+    /// Every witness element gets a [`PSBT_WITNESS_ELEMENT`] entry, holding
+    /// the value set with [`arg`](Self::arg) if there is one. An exit variant
+    /// sets the input's sequence to the exit delay. The spender adds the
+    /// outputs and any lock time.
+    pub fn psbt(&self, previous_output: OutPoint, value: Amount) -> Result<Psbt, String> {
+        let leaf = self.leaf()?;
+        let function = leaf.function;
+        let variant = variant_label(function.server_variant);
+        let fail = |e: String| format!("fn {} ({} variant): {}", function.name, variant, e);
+        if let Some(unknown) = self
+            .args
+            .keys()
+            .find(|name| !function.witness_schema.iter().any(|w| &w.name == *name))
+        {
+            return Err(fail(format!("no witness element '{}'", unknown)));
+        }
+
+        let sequence = if function.server_variant {
+            Sequence::ENABLE_LOCKTIME_NO_RBF
+        } else {
+            let delay = u16::try_from(self.artifact.exit_delay).map_err(|_| {
+                format!(
+                    "Exit delay {} does not fit a sequence",
+                    self.artifact.exit_delay
+                )
+            })?;
+            Sequence::from_height(delay)
+        };
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output,
+                script_sig: ScriptBuf::new(),
+                sequence,
+                witness: Witness::new(),
+            }],
+            output: vec![],
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx).map_err(|e| format!("Invalid PSBT: {}", e))?;
+
+        let input = &mut psbt.inputs[0];
+        input.witness_utxo = Some(TxOut {
+            value,
+            script_pubkey: ScriptBuf::from_bytes(leaf.output.script_pubkey()),
+        });
+        input.tap_internal_key = Some(
+            XOnlyPublicKey::from_slice(&leaf.output.internal_key)
+                .map_err(|e| format!("Invalid internal key: {}", e))?,
+        );
+        input.tap_merkle_root = Some(TapNodeHash::from_byte_array(leaf.output.merkle_root));
+        input
+            .tap_scripts
+            .insert(leaf.control_block, (leaf.script, LeafVersion::TapScript));
+
+        // The schema is in the order the script consumes the elements, so
+        // the witness carries it reversed
+        for (index, element) in function.witness_schema.iter().rev().enumerate() {
+            let value = match self.args.get(&element.name) {
+                Some(value) => {
+                    let value = Value::parse(value, &element.elem_type)
+                        .map_err(|e| fail(format!("witness element '{}': {}", element.name, e)))?;
+                    bitcoin::consensus::serialize(&Witness::from_slice(&value.stack()))
+                }
+                None => Vec::new(),
+            };
+            let mut key = vec![u8::try_from(index).map_err(|_| "Too many witness elements")?];
+            key.extend(element.name.as_bytes());
+            input.proprietary.insert(
+                ProprietaryKey {
+                    prefix: PSBT_PREFIX.to_vec(),
+                    subtype: PSBT_WITNESS_ELEMENT,
+                    key,
+                },
+                value,
+            );
+        }
+        Ok(psbt)
+    }
+
+    /// The function variant spent through, its leaf and the output.
+    fn leaf(&self) -> Result<Leaf<'a>, String> {
+        let (name, server_variant) = self
+            .function
+            .as_ref()
+            .ok_or("No function to spend through")?;
+        let function = self.artifact.find_function(name, *server_variant)?;
+        let index = self
+            .artifact
+            .functions
+            .iter()
+            .position(|f| core::ptr::eq(f, function))
+            .expect("the function is one of the artifact's");
+
+        let leaves = linker::leaf_scripts(self.artifact, &self.params)?;
+        let internal_key = match self.internal_key {
+            Some(key) => Some(key.serialize()),
+            None => linker::link(self.artifact, &self.params)?
+                .internal_key
+                .map(|key| {
+                    let mut bytes = [0u8; 32];
+                    hex::decode_to_slice(&key, &mut bytes)
+                        .map_err(|_| format!("Invalid internal key '{}'", key))?;
+                    Ok::<_, String>(bytes)
+                })
+                .transpose()?,
+        };
+        let script = ScriptBuf::from_bytes(leaves[index].clone());
+        let output = match internal_key {
+            Some(key) => TaprootOutput::with_internal_key(key, leaves)?,
+            None => TaprootOutput::new(leaves)?,
+        };
+        let control_block = ControlBlock::decode(&output.control_block(index)?)
+            .map_err(|e| format!("Invalid control block: {}", e))?;
+        Ok(Leaf {
+            function,
+            script,
+            control_block,
+            output,
+        })
+    }
+}
+
+struct Leaf<'a> {
+    function: &'a AbiFunction,
+    script: ScriptBuf,
+    control_block: ControlBlock,
+    output: TaprootOutput,
+}
@@ -0,0 +1,471 @@
+//! Taproot outputs of linked contracts.
+//!
+//! The leaf scripts of a contract are committed to in a balanced script
+//! tree, in artifact order: adjacent leaves are paired level by level, and
+//! a leftover leaf moves up a level unpaired. This is the tree the witness
+//! size estimates assume. [`TaprootOutput::new`] puts the tree under the
+//! BIP 341 NUMS point, so the output can only be spent through one of its
+//! leaves; [`TaprootOutput::with_internal_key`] puts it under any key, such
+//! as an [`unspendable_key`] or a key whose holder can spend by key path.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use k256::elliptic_curve::ff::PrimeField;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::{ProjectivePoint, PublicKey, Scalar};
+use sha2::{Digest, Sha256};
+
+use crate::models::{TapNode, TaprootTree};
+
+/// The BIP 341 NUMS point `H`, an x-only key no one knows the secret of.
+pub const NUMS_INTERNAL_KEY: &str =
+    "50929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0";
+
+/// Leaf version of tapscript leaves.
+pub const TAPSCRIPT_LEAF_VERSION: u8 = 0xc0;
+
+/// The network an address is encoded for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Network {
+    #[default]
+    Bitcoin,
+    Testnet,
+    Signet,
+    /// Signet with 30-second blocks
+    Mutinynet,
+    Regtest,
+}
+
+impl Network {
+    /// Human-readable part of the network's segwit addresses.
+    pub fn hrp(self) -> &'static str {
+        match self {
+            Network::Bitcoin => "bc",
+            Network::Testnet | Network::Signet | Network::Mutinynet => "tb",
+            Network::Regtest => "bcrt",
+        }
+    }
+}
+
+impl core::str::FromStr for Network {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bitcoin" | "mainnet" => Ok(Network::Bitcoin),
+            "testnet" => Ok(Network::Testnet),
+            "signet" => Ok(Network::Signet),
+            "mutinynet" => Ok(Network::Mutinynet),
+            "regtest" => Ok(Network::Regtest),
+            _ => Err(format!(
+                "unknown network '{}' (expected bitcoin, testnet, signet, mutinynet or regtest)",
+                s
+            )),
+        }
+    }
+}
+
+/// The output committing to a set of leaf scripts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaprootOutput {
+    /// x-only internal key
+    pub internal_key: [u8; 32],
+    /// x-only tweaked output key
+    pub output_key: [u8; 32],
+    /// Whether the output key's y coordinate is odd
+    pub output_key_parity: bool,
+    /// Root of the script tree
+    pub merkle_root: [u8; 32],
+    tree: Node,
+    leaves: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Leaf(usize),
+    Branch(alloc::boxed::Box<Node>, alloc::boxed::Box<Node>),
+}
+
+impl TaprootOutput {
+    /// Commit to `leaves` under the NUMS internal key.
+    pub fn new(leaves: Vec<Vec<u8>>) -> Result<Self, String> {
+        Self::with_internal_key(unspendable_key(None)?, leaves)
+    }
+
+    /// Commit to `leaves` under `internal_key`.
+    pub fn with_internal_key(internal_key: [u8; 32], leaves: Vec<Vec<u8>>) -> Result<Self, String> {
+        if leaves.is_empty() {
+            return Err("A taproot tree needs at least one leaf".into());
+        }
+        let tree = balanced_tree(leaves.len());
+        let merkle_root = node_hash(&tree, &leaves);
+        let (output_key, output_key_parity) = tweak(&internal_key, &merkle_root)?;
+        Ok(TaprootOutput {
+            internal_key,
+            output_key,
+            output_key_parity,
+            merkle_root,
+            tree,
+            leaves,
+        })
+    }
+
+    /// The output's scriptPubKey: `OP_1 <output key>`.
+    pub fn script_pubkey(&self) -> Vec<u8> {
+        let mut script = Vec::with_capacity(34);
+        script.extend([0x51, 0x20]);
+        script.extend(self.output_key);
+        script
+    }
+
+    /// The output's bech32m address on `network`.
+    pub fn address(&self, network: Network) -> String {
+        segwit_address(network.hrp(), 1, &self.output_key)
+    }
+
+    /// A `tr()` output descriptor, with checksum. Leaves are written as
+    /// `raw(HEX)`, as Arkade scripts are not miniscript. Bitcoin Core takes
+    /// `raw()` only at the top level, so it does not import this; it spells
+    /// out the whole tree for tools that read it.
+    pub fn descriptor(&self) -> String {
+        let body = format!(
+            "tr({},{})",
+            hex::encode(self.internal_key),
+            self.describe(&self.tree)
+        );
+        let checksum = descriptor_checksum(&body);
+        format!("{}#{}", body, checksum)
+    }
+
+    /// The control block spending through leaf `index`: leaf version and
+    /// output key parity, internal key, then the merkle path from the leaf
+    /// up.
+    pub fn control_block(&self, index: usize) -> Result<Vec<u8>, String> {
+        let path = self.merkle_proof(index)?;
+        let mut block = Vec::with_capacity(33 + 32 * path.len());
+        block.push(TAPSCRIPT_LEAF_VERSION | u8::from(self.output_key_parity));
+        block.extend(self.internal_key);
+        for hash in path {
+            block.extend(hash);
+        }
+        Ok(block)
+    }
+
+    /// The sibling hashes from leaf `index` up to the root: the merkle
+    /// path of its control block.
+    pub fn merkle_proof(&self, index: usize) -> Result<Vec<[u8; 32]>, String> {
+        let mut path = Vec::new();
+        if !merkle_path(&self.tree, index, &self.leaves, &mut path) {
+            return Err(format!("The script tree has no leaf {}", index));
+        }
+        Ok(path)
+    }
+
+    /// The output's keys and script tree, for the artifact.
+    pub fn tree(&self) -> TaprootTree {
+        TaprootTree {
+            internal_key: hex::encode(self.internal_key),
+            output_key: hex::encode(self.output_key),
+            parity: self.output_key_parity,
+            merkle_root: hex::encode(self.merkle_root),
+            root: self.tap_node(&self.tree).0,
+        }
+    }
+
+    fn tap_node(&self, node: &Node) -> (TapNode, [u8; 32]) {
+        match node {
+            Node::Leaf(i) => {
+                let hash = leaf_hash(&self.leaves[*i]);
+                let leaf = TapNode::Leaf {
+                    leaf_index: *i,
+                    hash: hex::encode(hash),
+                };
+                (leaf, hash)
+            }
+            Node::Branch(left, right) => {
+                let (left, left_hash) = self.tap_node(left);
+                let (right, right_hash) = self.tap_node(right);
+                let hash = branch_hash(&left_hash, &right_hash);
+                let branch = TapNode::Branch {
+                    hash: hex::encode(hash),
+                    left: left.into(),
+                    right: right.into(),
+                };
+                (branch, hash)
+            }
+        }
+    }
+
+    /// The script tree below `node`, in descriptor syntax.
+    fn describe(&self, node: &Node) -> String {
+        match node {
+            Node::Leaf(i) => format!("raw({})", hex::encode(&self.leaves[*i])),
+            Node::Branch(left, right) => {
+                format!("{{{},{}}}", self.describe(left), self.describe(right))
+            }
+        }
+    }
+}
+
+/// BIP 341 tagged hash.
+fn tagged_hash(tag: &str, parts: &[&[u8]]) -> [u8; 32] {
+    let tag = Sha256::digest(tag.as_bytes());
+    let mut hasher = Sha256::new();
+    hasher.update(tag);
+    hasher.update(tag);
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+/// Hash of a tapscript leaf.
+pub fn leaf_hash(script: &[u8]) -> [u8; 32] {
+    let mut len = Vec::new();
+    write_compact_size(&mut len, script.len());
+    tagged_hash("TapLeaf", &[&[TAPSCRIPT_LEAF_VERSION], &len, script])
+}
+
+fn branch_hash(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    tagged_hash("TapBranch", &[first, second])
+}
+
+/// Pair `count` leaves level by level.
+fn balanced_tree(count: usize) -> Node {
+    let mut level: Vec<Node> = (0..count).map(Node::Leaf).collect();
+    while level.len() > 1 {
+        let mut next = Vec::with_capacity(level.len().div_ceil(2));
+        let mut nodes = level.into_iter();
+        while let Some(left) = nodes.next() {
+            match nodes.next() {
+                Some(right) => next.push(Node::Branch(left.into(), right.into())),
+                None => next.push(left),
+            }
+        }
+        level = next;
+    }
+    level.remove(0)
+}
+
+fn node_hash(node: &Node, leaves: &[Vec<u8>]) -> [u8; 32] {
+    match node {
+        Node::Leaf(i) => leaf_hash(&leaves[*i]),
+        Node::Branch(left, right) => {
+            branch_hash(&node_hash(left, leaves), &node_hash(right, leaves))
+        }
+    }
+}
+
+/// A provably unspendable x-only internal key: the NUMS point `H`, or
+/// `H + r·G` for a tweak `r`. Whoever knows `r` can check that no one knows
+/// the key's secret, while to anyone else the tweaked key looks like any
+/// other.
+pub fn unspendable_key(tweak: Option<&[u8; 32]>) -> Result<[u8; 32], String> {
+    let mut nums = [0u8; 32];
+    hex::decode_to_slice(NUMS_INTERNAL_KEY, &mut nums)
+        .map_err(|e| format!("Invalid internal key: {}", e))?;
+    match tweak {
+        Some(r) => add_scalar(&nums, r).map(|(key, _)| key),
+        None => Ok(nums),
+    }
+}
+
+/// The scriptPubKey `OP_1 <Q>` of a taproot output with `internal_key`,
+/// committing to the script tree with `merkle_root`, or to no scripts as
+/// BIP 86 specifies.
+pub fn p2tr_script_pubkey(
+    internal_key: &[u8; 32],
+    merkle_root: Option<&[u8; 32]>,
+) -> Result<Vec<u8>, String> {
+    let t = match merkle_root {
+        Some(root) => tagged_hash("TapTweak", &[internal_key, root]),
+        None => tagged_hash("TapTweak", &[internal_key]),
+    };
+    let (output_key, _) = add_scalar(internal_key, &t)?;
+    let mut script = Vec::with_capacity(34);
+    script.extend([0x51, 0x20]);
+    script.extend(output_key);
+    Ok(script)
+}
+
+/// The x-only key `P + t·G` with `t = hashTapTweak(P || merkle_root)`, and
+/// whether its y coordinate is odd.
+fn tweak(internal_key: &[u8; 32], merkle_root: &[u8; 32]) -> Result<([u8; 32], bool), String> {
+    add_scalar(
+        internal_key,
+        &tagged_hash("TapTweak", &[internal_key, merkle_root]),
+    )
+}
+
+/// The x-only key `P + t·G`, and whether its y coordinate is odd.
+fn add_scalar(key: &[u8; 32], t: &[u8; 32]) -> Result<([u8; 32], bool), String> {
+    let mut sec1 = [0x02; 33];
+    sec1[1..].copy_from_slice(key);
+    let point = PublicKey::from_sec1_bytes(&sec1)
+        .map_err(|_| "Internal key is not a point on the curve".to_string())?;
+
+    let t = Option::<Scalar>::from(Scalar::from_repr((*t).into()))
+        .ok_or_else(|| "Taproot tweak is out of range".to_string())?;
+
+    let tweaked = (point.to_projective() + ProjectivePoint::GENERATOR * t).to_affine();
+    let encoded = tweaked.to_encoded_point(true);
+    let mut output_key = [0u8; 32];
+    output_key.copy_from_slice(&encoded.as_bytes()[1..33]);
+    Ok((output_key, encoded.as_bytes()[0] == 0x03))
+}
+
+/// Collect the sibling hashes from leaf `index` up to `node`, if `node`
+/// contains it.
+fn merkle_path(node: &Node, index: usize, leaves: &[Vec<u8>], path: &mut Vec<[u8; 32]>) -> bool {
+    match node {
+        Node::Leaf(i) => *i == index,
+        Node::Branch(left, right) => {
+            if merkle_path(left, index, leaves, path) {
+                path.push(node_hash(right, leaves));
+                true
+            } else if merkle_path(right, index, leaves, path) {
+                path.push(node_hash(left, leaves));
+                true
+            } else {
+                false
+            }
+        }
+    }
+}
+
+/// Append a Bitcoin CompactSize encoding of `n`.
+fn write_compact_size(out: &mut Vec<u8>, n: usize) {
+    match n {
+        0..=0xfc => out.push(n as u8),
+        0xfd..=0xffff => {
+            out.push(0xfd);
+            out.extend((n as u16).to_le_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(0xfe);
+            out.extend((n as u32).to_le_bytes());
+        }
+        _ => {
+            out.push(0xff);
+            out.extend((n as u64).to_le_bytes());
+        }
+    }
+}
+
+// ─── Bech32m ──────────────────────────────────────────────────────────────────
+
+const BECH32_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+
+fn bech32_polymod(values: &[u8]) -> u32 {
+    const GEN: [u32; 5] = [
+        0x3b6a_57b2,
+        0x2650_8e6d,
+        0x1ea1_19fa,
+        0x3d42_33dd,
+        0x2a14_62b3,
+    ];
+    let mut chk: u32 = 1;
+    for v in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x1ff_ffff) << 5) ^ u32::from(*v);
+        for (i, g) in GEN.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+/// A bech32m segwit address (BIP 350) for a witness program.
+fn segwit_address(hrp: &str, version: u8, program: &[u8]) -> String {
+    let mut data = alloc::vec![version];
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    for byte in program {
+        acc = (acc << 8) | u32::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            data.push(((acc >> bits) & 31) as u8);
+        }
+    }
+    if bits > 0 {
+        data.push(((acc << (5 - bits)) & 31) as u8);
+    }
+
+    let mut values: Vec<u8> = hrp.bytes().map(|c| c >> 5).collect();
+    values.push(0);
+    values.extend(hrp.bytes().map(|c| c & 31));
+    values.extend(&data);
+    values.extend([0; 6]);
+    let polymod = bech32_polymod(&values) ^ BECH32M_CONST;
+
+    let mut address = String::from(hrp);
+    address.push('1');
+    for d in data {
+        address.push(BECH32_CHARSET[d as usize] as char);
+    }
+    for i in 0..6 {
+        address.push(BECH32_CHARSET[((polymod >> (5 * (5 - i))) & 31) as usize] as char);
+    }
+    address
+}
+
+// ─── Descriptor checksum ──────────────────────────────────────────────────────
+
+const DESCRIPTOR_INPUT_CHARSET: &str =
+    "0123456789()[],'/*abcdefgh@:$%{}IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+
+fn descriptor_polymod(mut chk: u64, value: u64) -> u64 {
+    const GEN: [u64; 5] = [
+        0xf5_dee5_1989,
+        0xa9_fdca_3312,
+        0x1b_ab10_e32d,
+        0x37_06b1_677a,
+        0x64_4d62_6ffd,
+    ];
+    let top = chk >> 35;
+    chk = ((chk & 0x7_ffff_ffff) << 5) ^ value;
+    for (i, g) in GEN.iter().enumerate() {
+        if (top >> i) & 1 == 1 {
+            chk ^= g;
+        }
+    }
+    chk
+}
+
+/// The 8-character checksum of an output descriptor.
+fn descriptor_checksum(descriptor: &str) -> String {
+    let mut chk: u64 = 1;
+    let mut class_count = 0;
+    let mut classes: u64 = 0;
+    for c in descriptor.chars() {
+        // Every character used here is in the input charset
+        let position = DESCRIPTOR_INPUT_CHARSET.find(c).unwrap_or(0) as u64;
+        chk = descriptor_polymod(chk, position & 31);
+        classes = classes * 3 + (position >> 5);
+        class_count += 1;
+        if class_count == 3 {
+            chk = descriptor_polymod(chk, classes);
+            classes = 0;
+            class_count = 0;
+        }
+    }
+    if class_count > 0 {
+        chk = descriptor_polymod(chk, classes);
+    }
+    for _ in 0..8 {
+        chk = descriptor_polymod(chk, 0);
+    }
+    chk ^= 1;
+    (0..8)
+        .map(|i| BECH32_CHARSET[((chk >> (5 * (7 - i))) & 31) as usize] as char)
+        .collect()
+}
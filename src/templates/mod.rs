@@ -0,0 +1,145 @@
+//! Contract templates.
+//!
+//! [`TEMPLATES`] embeds ready-to-edit contracts for common patterns, which
+//! `arkadec new --template <name>` writes out. A template's source marks the
+//! values it takes as `{{name}}`; [`Template::render`] checks the values it
+//! is given against the template's [`TemplateOption`]s and fills them in,
+//! using defaults for the rest. Every rendered template compiles without
+//! warnings.
+
+use alloc::collections::BTreeMap;
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::constants;
+
+/// A contract template.
+#[derive(Debug, Clone, Copy)]
+pub struct Template {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub options: &'static [TemplateOption],
+    /// Source with a `{{name}}` placeholder for each option
+    source: &'static str,
+}
+
+/// A value a template is populated from.
+#[derive(Debug, Clone, Copy)]
+pub struct TemplateOption {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub default: &'static str,
+    pub kind: OptionKind,
+}
+
+/// The values a template option accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionKind {
+    /// A number of blocks, or a built-in constant such as `BLOCKS_PER_DAY`
+    Blocks,
+    /// One of the listed words
+    OneOf(&'static [&'static str]),
+}
+
+/// The embedded templates.
+pub const TEMPLATES: &[Template] = &[
+    Template {
+        name: "htlc",
+        description: "Hash time-locked contract: claim with a preimage, refund after a timeout",
+        options: &[
+            TemplateOption {
+                name: "hash",
+                description: "Hash function of the hash lock",
+                default: "sha256",
+                kind: OptionKind::OneOf(&["sha256"]),
+            },
+            TemplateOption {
+                name: "timeout",
+                description: "Blocks the unilateral exit paths wait",
+                default: "144",
+                kind: OptionKind::Blocks,
+            },
+        ],
+        source: include_str!("htlc.ark"),
+    },
+    Template {
+        name: "vault",
+        description: "Vault: delayed withdrawal by the owner, immediate clawback to a recovery key",
+        options: &[
+            TemplateOption {
+                name: "timeout",
+                description: "Blocks the unilateral exit paths wait",
+                default: "288",
+                kind: OptionKind::Blocks,
+            },
+            TemplateOption {
+                name: "renew",
+                description: "Blocks within which the vault must be renewed",
+                default: "BLOCKS_PER_WEEK",
+                kind: OptionKind::Blocks,
+            },
+        ],
+        source: include_str!("vault.ark"),
+    },
+];
+
+/// The template named `name`.
+pub fn find(name: &str) -> Option<&'static Template> {
+    TEMPLATES.iter().find(|t| t.name == name)
+}
+
+impl Template {
+    /// The template's source populated from `values` (option name to
+    /// value), with defaults for the options not given.
+    pub fn render(&self, values: &BTreeMap<String, String>) -> Result<String, String> {
+        if let Some(name) = values
+            .keys()
+            .find(|name| !self.options.iter().any(|o| o.name == name.as_str()))
+        {
+            return Err(format!(
+                "Template '{}' has no option '{}' (options: {})",
+                self.name,
+                name,
+                self.option_names().join(", ")
+            ));
+        }
+
+        let mut source = self.source.to_string();
+        for option in self.options {
+            let value = values
+                .get(option.name)
+                .map(String::as_str)
+                .unwrap_or(option.default);
+            option.check(value)?;
+            source = source.replace(&format!("{{{{{}}}}}", option.name), value);
+        }
+        Ok(source)
+    }
+
+    fn option_names(&self) -> Vec<&'static str> {
+        self.options.iter().map(|o| o.name).collect()
+    }
+}
+
+impl TemplateOption {
+    fn check(&self, value: &str) -> Result<(), String> {
+        let valid = match self.kind {
+            OptionKind::Blocks => value.parse::<u64>().is_ok() || constants::is_constant(value),
+            OptionKind::OneOf(words) => words.contains(&value),
+        };
+        if valid {
+            return Ok(());
+        }
+        let expected = match self.kind {
+            OptionKind::Blocks => "a number of blocks or a built-in constant".to_string(),
+            OptionKind::OneOf(words) => format!("one of {}", words.join(", ")),
+        };
+        Err(format!(
+            "Option '{}' must be {}, got '{}'",
+            self.name, expected, value
+        ))
+    }
+}
@@ -0,0 +1,224 @@
+//! Requirement and branch coverage.
+//!
+//! Every `require` and `if` of a public function is listed up front, keyed by
+//! its source location, and each test case adds what its evaluation touched.
+//! A statement inside a loop is one entry however many times the loop body
+//! runs, and is counted at most once per case.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::eval::Trace;
+use crate::models::{Contract, Span, Statement};
+
+/// Coverage of every public function of a contract.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Coverage {
+    pub functions: Vec<FunctionCoverage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionCoverage {
+    pub name: String,
+    pub requirements: Vec<RequirementCoverage>,
+    pub branches: Vec<BranchCoverage>,
+}
+
+/// How often a `require` was checked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequirementCoverage {
+    pub line: usize,
+    pub column: usize,
+    /// The statement as written
+    pub source: String,
+    /// Cases in which the requirement held
+    pub held: usize,
+    /// Cases that failed on the requirement
+    pub failed: usize,
+}
+
+impl RequirementCoverage {
+    pub fn exercised(&self) -> bool {
+        self.held + self.failed > 0
+    }
+}
+
+/// How often each side of an `if` was taken.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchCoverage {
+    pub line: usize,
+    pub column: usize,
+    #[serde(rename = "thenTaken")]
+    pub then_taken: usize,
+    #[serde(rename = "elseTaken")]
+    pub else_taken: usize,
+}
+
+impl Coverage {
+    /// Empty coverage for the public functions of `contract`.
+    pub(crate) fn new(contract: &Contract, source: &str) -> Self {
+        let functions = contract
+            .functions
+            .iter()
+            .filter(|f| !f.is_internal)
+            .map(|f| {
+                let mut coverage = FunctionCoverage {
+                    name: f.name.clone(),
+                    requirements: Vec::new(),
+                    branches: Vec::new(),
+                };
+                coverage.collect(&f.statements, source);
+                coverage
+            })
+            .collect();
+        Coverage { functions }
+    }
+
+    /// Add the trace of one case run against `function`.
+    pub(crate) fn record(&mut self, function: &str, trace: &Trace) {
+        let Some(coverage) = self.functions.iter_mut().find(|f| f.name == function) else {
+            return;
+        };
+
+        let mut held = HashSet::new();
+        let mut failed = HashSet::new();
+        for (span, ok) in &trace.requirements {
+            if *ok {
+                held.insert((span.line, span.column));
+            } else {
+                failed.insert((span.line, span.column));
+            }
+        }
+        for req in &mut coverage.requirements {
+            let at = (req.line, req.column);
+            if failed.contains(&at) {
+                req.failed += 1;
+            } else if held.contains(&at) {
+                req.held += 1;
+            }
+        }
+
+        let taken: HashSet<_> = trace
+            .branches
+            .iter()
+            .map(|(span, then)| (span.line, span.column, *then))
+            .collect();
+        for branch in &mut coverage.branches {
+            if taken.contains(&(branch.line, branch.column, true)) {
+                branch.then_taken += 1;
+            }
+            if taken.contains(&(branch.line, branch.column, false)) {
+                branch.else_taken += 1;
+            }
+        }
+    }
+
+    /// Requirements checked by at least one case, out of all requirements.
+    pub fn requirements_exercised(&self) -> (usize, usize) {
+        let all = self.functions.iter().flat_map(|f| &f.requirements);
+        let total = all.clone().count();
+        (all.filter(|r| r.exercised()).count(), total)
+    }
+
+    /// Branch sides taken by at least one case, out of all branch sides.
+    pub fn branches_taken(&self) -> (usize, usize) {
+        let all = self.functions.iter().flat_map(|f| &f.branches);
+        let sides = all.clone().count() * 2;
+        let taken = all
+            .map(|b| (b.then_taken > 0) as usize + (b.else_taken > 0) as usize)
+            .sum();
+        (taken, sides)
+    }
+}
+
+impl FunctionCoverage {
+    fn collect(&mut self, statements: &[Statement], source: &str) {
+        for stmt in statements {
+            match stmt {
+                Statement::Require(_, span) => self.requirements.push(RequirementCoverage {
+                    line: span.line,
+                    column: span.column,
+                    source: snippet(source, span),
+                    held: 0,
+                    failed: 0,
+                }),
+                Statement::IfElse {
+                    then_body,
+                    else_body,
+                    span,
+                    ..
+                } => {
+                    self.branches.push(BranchCoverage {
+                        line: span.line,
+                        column: span.column,
+                        then_taken: 0,
+                        else_taken: 0,
+                    });
+                    self.collect(then_body, source);
+                    if let Some(else_body) = else_body {
+                        self.collect(else_body, source);
+                    }
+                }
+                Statement::ForIn { body, .. } => self.collect(body, source),
+                Statement::LetBinding { .. } | Statement::VarAssign { .. } => {}
+            }
+        }
+    }
+}
+
+/// The source text of a span on a single line.
+fn snippet(source: &str, span: &Span) -> String {
+    source
+        .get(span.start..span.end)
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (reqs, total_reqs) = self.requirements_exercised();
+        let (sides, total_sides) = self.branches_taken();
+        writeln!(
+            f,
+            "coverage: {}/{} requirements exercised, {}/{} branches taken",
+            reqs, total_reqs, sides, total_sides
+        )?;
+        for function in &self.functions {
+            writeln!(f, "  fn {}", function.name)?;
+            for req in &function.requirements {
+                let counts = if req.exercised() {
+                    format!("held {}, failed {}", req.held, req.failed)
+                } else {
+                    "never exercised".to_string()
+                };
+                writeln!(
+                    f,
+                    "    {}:{}  {}  {}",
+                    req.line, req.column, req.source, counts
+                )?;
+            }
+            for branch in &function.branches {
+                writeln!(
+                    f,
+                    "    {}:{}  if: then {}, else {}",
+                    branch.line,
+                    branch.column,
+                    taken(branch.then_taken),
+                    taken(branch.else_taken)
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn taken(count: usize) -> String {
+    match count {
+        0 => "never taken".to_string(),
+        n => format!("taken {}", n),
+    }
+}
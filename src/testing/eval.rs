@@ -0,0 +1,437 @@
+//! Source-level evaluation of a function body.
+//!
+//! Statements are walked as written: `if` conditions pick a branch, loops are
+//! unrolled the way the compiler unrolls them and every `require` is checked
+//! against the case's values and mock transaction. Signature, hash-lock and
+//! arithmetic checks are evaluated directly; introspection expressions are
+//! lowered with the code generator and run on the interpreter, so they read
+//! the transaction exactly as the compiled leaf does.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::compiler::{emit_expression_asm, substitute_loop_body};
+use crate::encoding::split_sighash;
+use crate::hashes;
+use crate::interpreter::{self, cast_to_bool, decode_num, encode_num, verify_schnorr, TxContext};
+use crate::ir::{lift, Instr};
+use crate::models::{
+    multisig_keys, Expression, HashFunction, Requirement, Span, Statement, DEFAULT_ARRAY_LENGTH,
+};
+
+/// What one evaluation touched.
+#[derive(Debug, Default)]
+pub(crate) struct Trace {
+    /// Requirements evaluated, with whether they held
+    pub requirements: Vec<(Span, bool)>,
+    /// `if` statements evaluated, with whether the then-branch was taken
+    pub branches: Vec<(Span, bool)>,
+}
+
+/// Why a function body did not run to completion.
+#[derive(Debug)]
+pub(crate) enum Stop {
+    /// A requirement did not hold
+    Failed { span: Span, reason: String },
+    /// Script computing a binding or branch condition failed
+    Aborted(String),
+    /// The body could not be evaluated
+    Error(String),
+}
+
+pub(crate) struct Evaluator<'a> {
+    tx: &'a TxContext,
+    values: HashMap<String, Vec<u8>>,
+    /// Length of each array parameter in scope
+    arrays: BTreeMap<String, usize>,
+    /// Why the last expression handed to the interpreter failed, if it did
+    failure: Option<String>,
+    pub trace: Trace,
+}
+
+impl<'a> Evaluator<'a> {
+    pub fn new(
+        tx: &'a TxContext,
+        values: HashMap<String, Vec<u8>>,
+        arrays: BTreeMap<String, usize>,
+    ) -> Self {
+        Evaluator {
+            tx,
+            values,
+            arrays,
+            failure: None,
+            trace: Trace::default(),
+        }
+    }
+
+    pub fn run(&mut self, statements: &[Statement]) -> Result<(), Stop> {
+        for stmt in statements {
+            match stmt {
+                Statement::Require(req, span) => {
+                    let outcome = match self.requirement(req) {
+                        Err(e) => self.failure.take().map(Err).ok_or(Stop::Error(e))?,
+                        Ok(outcome) => outcome,
+                    };
+                    self.trace.requirements.push((*span, outcome.is_ok()));
+                    if let Err(reason) = outcome {
+                        return Err(Stop::Failed {
+                            span: *span,
+                            reason,
+                        });
+                    }
+                }
+                Statement::LetBinding { name, value } | Statement::VarAssign { name, value } => {
+                    let value = self.expression(value).map_err(|e| self.stop(e))?;
+                    self.values.insert(name.clone(), value);
+                }
+                Statement::IfElse {
+                    condition,
+                    then_body,
+                    else_body,
+                    span,
+                } => {
+                    let condition = self.expression(condition).map_err(|e| self.stop(e))?;
+                    let taken = cast_to_bool(&condition);
+                    self.trace.branches.push((*span, taken));
+                    if taken {
+                        self.run(then_body)?;
+                    } else if let Some(else_body) = else_body {
+                        self.run(else_body)?;
+                    }
+                }
+                Statement::ForIn {
+                    index_var,
+                    value_var,
+                    iterable,
+                    body,
+                } => match iterable {
+                    Expression::Property(prop) if prop == "tx.assetGroups" => {
+                        for k in 0..self.tx.asset_groups.len() {
+                            let body = substitute_loop_body(body, index_var, value_var, k, None);
+                            self.run(&body)?;
+                        }
+                    }
+                    Expression::Variable(array) => {
+                        let length = self.arrays.get(array).copied();
+                        for k in 0..length.unwrap_or(DEFAULT_ARRAY_LENGTH) {
+                            let body =
+                                substitute_loop_body(body, index_var, value_var, k, Some(array));
+                            self.run(&body)?;
+                        }
+                    }
+                    _ => self.run(body)?,
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Check a requirement outside the function body, such as the server
+    /// signature the compiler adds, without tracing it. Returns why it
+    /// failed or could not be checked.
+    pub fn check(&mut self, req: &Requirement) -> Result<(), String> {
+        match self.requirement(req) {
+            Ok(outcome) => outcome,
+            Err(e) => Err(self.failure.take().unwrap_or(e)),
+        }
+    }
+
+    fn stop(&mut self, error: String) -> Stop {
+        match self.failure.take() {
+            Some(reason) => Stop::Aborted(reason),
+            None => Stop::Error(error),
+        }
+    }
+
+    /// Check a requirement. The outer error means it could not be evaluated,
+    /// the inner one that it does not hold.
+    fn requirement(&mut self, req: &Requirement) -> Result<Result<(), String>, String> {
+        let holds = match req {
+            Requirement::CheckSig {
+                signature, pubkey, ..
+            }
+            | Requirement::CheckSigFromStack {
+                signature, pubkey, ..
+            } => {
+                let valid = match req {
+                    Requirement::CheckSigFromStack { message, .. } => {
+                        self.check_sig(signature, pubkey, self.value(message)?)?
+                    }
+                    // A pinned flag is appended by the script: the witness
+                    // holds the bare 64-byte signature
+                    Requirement::CheckSig {
+                        sighash: Some(_), ..
+                    } => self.check_sig(signature, pubkey, &self.tx.sighash)?,
+                    _ => self.check_tx_sig(signature, pubkey)?,
+                };
+                if !valid {
+                    return Ok(Err(format!(
+                        "`{}` is not a valid signature by `{}`",
+                        signature, pubkey
+                    )));
+                }
+                true
+            }
+            Requirement::CheckMultisig { pubkeys, threshold } => {
+                let (pubkeys, threshold) = multisig_keys(pubkeys, *threshold, &self.arrays)?;
+                let mut valid = 0;
+                for pubkey in &pubkeys {
+                    if self.check_tx_sig(&format!("{}Sig", pubkey), pubkey)? {
+                        valid += 1;
+                    }
+                }
+                if valid < threshold as usize {
+                    return Ok(Err(format!(
+                        "{} of {} required signatures are valid",
+                        valid, threshold
+                    )));
+                }
+                true
+            }
+            Requirement::After {
+                locktime,
+                timelock_var,
+            } => {
+                let lock = match timelock_var {
+                    Some(var) => format!("<{}>", var),
+                    None => locktime.to_string(),
+                };
+                let script = lift(&[lock, "OP_CHECKLOCKTIMEVERIFY".to_string()]);
+                if let Err(e) = interpreter::execute(&script, &self.values, self.tx) {
+                    return Ok(Err(script_failure(&e).to_string()));
+                }
+                true
+            }
+            Requirement::Older { age, timelock_var } => {
+                let lock = match timelock_var {
+                    Some(var) => format!("<{}>", var),
+                    None => age.sequence()?.to_string(),
+                };
+                let script = lift(&[lock, "OP_CHECKSEQUENCEVERIFY".to_string()]);
+                if let Err(e) = interpreter::execute(&script, &self.values, self.tx) {
+                    return Ok(Err(script_failure(&e).to_string()));
+                }
+                true
+            }
+            Requirement::HashEqual {
+                preimage,
+                hash,
+                function,
+            } => hashes::digest(*function, self.value(preimage)?) == *self.value(hash)?,
+            Requirement::SizeBetween { value, min, max } => {
+                (*min..=*max).contains(&(self.value(value)?.len() as u64))
+            }
+            Requirement::Comparison { left, op, right } => {
+                let expected = match right {
+                    Expression::Variable(b) | Expression::Literal(b) if b == "true" => Some(true),
+                    Expression::Variable(b) | Expression::Literal(b) if b == "false" => Some(false),
+                    _ => None,
+                };
+                match expected {
+                    // `require(flag)` and `flag == true` test truthiness
+                    Some(expected) if op == "==" || op == "!=" => {
+                        (cast_to_bool(&self.expression(left)?) == expected) == (op == "==")
+                    }
+                    _ => {
+                        let (left, right) = (self.expression(left)?, self.expression(right)?);
+                        compare(&left, op, &right)?
+                    }
+                }
+            }
+        };
+        Ok(if holds {
+            Ok(())
+        } else {
+            Err("condition does not hold".to_string())
+        })
+    }
+
+    fn value(&self, name: &str) -> Result<&Vec<u8>, String> {
+        self.values
+            .get(name)
+            .ok_or_else(|| format!("no value for `{}`", name))
+    }
+
+    fn check_sig(&self, signature: &str, pubkey: &str, message: &[u8]) -> Result<bool, String> {
+        Ok(verify_schnorr(
+            self.value(pubkey)?,
+            message,
+            self.value(signature)?,
+        ))
+    }
+
+    /// A transaction signature: 64 bytes for `SIGHASH_DEFAULT`, or 65 with
+    /// the sighash flag last. The mock sighash does not depend on the flag.
+    fn check_tx_sig(&self, signature: &str, pubkey: &str) -> Result<bool, String> {
+        Ok(match split_sighash(self.value(signature)?) {
+            Ok((signature, _)) => verify_schnorr(self.value(pubkey)?, &self.tx.sighash, signature),
+            Err(_) => false,
+        })
+    }
+
+    /// `checkMultisig([keys...], [signatures...])`: every signature must be
+    /// valid for the key at the same position.
+    fn legacy_multisig(&self, args: &str) -> Result<bool, String> {
+        let lists: Vec<Vec<&str>> = args
+            .split(']')
+            .filter_map(|part| part.trim().trim_start_matches(',').trim().strip_prefix('['))
+            .map(|list| list.split(',').map(str::trim).collect())
+            .collect();
+        let [pubkeys, signatures] = lists.as_slice() else {
+            return Err(format!("cannot evaluate `checkMultisig({})`", args));
+        };
+        if pubkeys.len() != signatures.len() {
+            return Ok(false);
+        }
+        for (pubkey, signature) in pubkeys.iter().zip(signatures) {
+            if !self.check_tx_sig(signature, pubkey)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Evaluate an expression to the bytes it leaves on the stack.
+    fn expression(&mut self, expr: &Expression) -> Result<Vec<u8>, String> {
+        match expr {
+            Expression::Variable(name) | Expression::Literal(name)
+                if name == "true" || name == "false" =>
+            {
+                Ok(encode_bool(name == "true"))
+            }
+            Expression::Variable(name) => self.value(name).cloned(),
+            Expression::Property(prop) => {
+                let prop = prop.trim();
+                if let Some((function, arg)) = HashFunction::split_call(prop) {
+                    Ok(hashes::digest(function, self.value(arg)?))
+                } else if let Some(args) = prop
+                    .strip_prefix("checkMultisig(")
+                    .and_then(|p| p.strip_suffix(')'))
+                {
+                    Ok(encode_bool(self.legacy_multisig(args)?))
+                } else if prop == "tx.time" {
+                    Ok(encode_num(self.tx.locktime as i64))
+                } else {
+                    Err(format!("cannot evaluate `{}`", prop))
+                }
+            }
+            Expression::CheckSigCount { pubkeys } => {
+                let mut valid = 0;
+                for pubkey in pubkeys {
+                    if self.check_tx_sig(&format!("{}Sig", pubkey), pubkey)? {
+                        valid += 1;
+                    }
+                }
+                Ok(encode_num(valid))
+            }
+            Expression::CheckSigExpr { signature, pubkey } => {
+                Ok(encode_bool(self.check_tx_sig(signature, pubkey)?))
+            }
+            Expression::CheckSigFromStackExpr {
+                signature,
+                pubkey,
+                message,
+            }
+            | Expression::CheckSigFromStackVerify {
+                signature,
+                pubkey,
+                message,
+            } => {
+                let message = self.value(message)?.clone();
+                Ok(encode_bool(self.check_sig(signature, pubkey, &message)?))
+            }
+            Expression::Not { value } => Ok(encode_bool(!cast_to_bool(&self.expression(value)?))),
+            Expression::BinaryOp { left, op, right } => {
+                let (left, right) = (self.expression(left)?, self.expression(right)?);
+                match op.as_str() {
+                    "+" | "-" | "*" | "/" | "%" => arithmetic(&left, op, &right),
+                    _ => Ok(encode_bool(compare(&left, op, &right)?)),
+                }
+            }
+            _ => {
+                let mut asm = Vec::new();
+                emit_expression_asm(expr, &mut asm);
+                let script = lift(&asm);
+                for instr in &script {
+                    if let Instr::Placeholder(name) = instr {
+                        self.value(name)?;
+                    }
+                }
+                // With every value bound, a failing script is the contract
+                // rejecting the transaction rather than a harness error
+                let mut stack = interpreter::execute(&script, &self.values, self.tx)
+                    .inspect_err(|e| self.failure = Some(script_failure(e).to_string()))?;
+                match (stack.pop(), stack.is_empty()) {
+                    (Some(value), true) => Ok(value),
+                    _ => Err(format!(
+                        "expression `{}` does not produce a single value",
+                        asm.join(" ")
+                    )),
+                }
+            }
+        }
+    }
+}
+
+/// The reason of an interpreter error, without the failing instruction.
+fn script_failure(error: &str) -> &str {
+    error.split_once("): ").map_or(error, |(_, reason)| reason)
+}
+
+fn encode_bool(b: bool) -> Vec<u8> {
+    if b {
+        vec![1]
+    } else {
+        Vec::new()
+    }
+}
+
+/// A value as a number: 8-byte values are little-endian amounts, anything
+/// shorter a CScriptNum.
+fn number(value: &[u8]) -> Result<i64, String> {
+    match <[u8; 8]>::try_from(value) {
+        Ok(bytes) => Ok(i64::from_le_bytes(bytes)),
+        Err(_) => decode_num(value),
+    }
+}
+
+fn compare(left: &[u8], op: &str, right: &[u8]) -> Result<bool, String> {
+    if matches!(op, "==" | "!=") {
+        // Byte strings of equal length compare as bytes, mixed encodings of
+        // a number compare by value
+        let equal = left == right
+            || (left.len() != right.len()
+                && matches!((number(left), number(right)), (Ok(a), Ok(b)) if a == b));
+        return Ok(equal == (op == "=="));
+    }
+    match op {
+        "&&" => return Ok(cast_to_bool(left) && cast_to_bool(right)),
+        "||" => return Ok(cast_to_bool(left) || cast_to_bool(right)),
+        _ => {}
+    }
+    let (a, b) = (number(left)?, number(right)?);
+    match op {
+        "<" => Ok(a < b),
+        "<=" => Ok(a <= b),
+        ">" => Ok(a > b),
+        ">=" => Ok(a >= b),
+        _ => Err(format!("unsupported operator `{}`", op)),
+    }
+}
+
+/// Arithmetic stays in 64-bit little-endian once either operand is.
+fn arithmetic(left: &[u8], op: &str, right: &[u8]) -> Result<Vec<u8>, String> {
+    let (a, b) = (number(left)?, number(right)?);
+    let result = match op {
+        "+" => a.checked_add(b),
+        "-" => a.checked_sub(b),
+        "*" => a.checked_mul(b),
+        "%" => a.checked_rem_euclid(b),
+        _ => a.checked_div_euclid(b),
+    }
+    .ok_or_else(|| format!("arithmetic overflow in {} {} {}", a, op, b))?;
+    if left.len() == 8 || right.len() == 8 {
+        Ok(result.to_le_bytes().to_vec())
+    } else {
+        Ok(encode_num(result))
+    }
+}
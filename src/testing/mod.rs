@@ -0,0 +1,529 @@
+//! Contract test harness.
+//!
+//! A [`TestSuite`] lists cases that each call one function with a set of
+//! values and a mock transaction, and say whether the call should pass. The
+//! harness runs the compiled leaf of the function on the
+//! [`interpreter`](crate::interpreter), server signature or exit timelock
+//! included, and evaluates the function body at the source level to record
+//! which requirements and branches every case exercised, so authors can see
+//! the parts of a contract no test reaches. A case whose leaf and source
+//! disagree, or whose leaf cannot run, is an error.
+//!
+//! Cases run the cooperative leaf unless they set `"serverVariant": false`.
+//! Any constructor argument or witness element a case leaves out gets the
+//! deterministic dummy value [`vectors`](crate::vectors) would give it, the
+//! server key included, and the transaction signs the same placeholder
+//! sighash by default, so signatures are valid unless a case overrides them.
+//!
+//! Suites are JSON. Values are hex strings, JSON numbers (encoded as
+//! CScriptNums) or booleans:
+//!
+//! ```json
+//! {
+//!   "constructorInputs": { "amount": 5000 },
+//!   "cases": [
+//!     {
+//!       "name": "pays the recipient",
+//!       "function": "pay",
+//!       "tx": { "outputs": [{ "value": 6000, "scriptPubKey": "5120..." }] },
+//!       "expect": "pass"
+//!     }
+//!   ]
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+mod coverage;
+mod eval;
+
+pub use coverage::{BranchCoverage, Coverage, FunctionCoverage, RequirementCoverage};
+
+use crate::compiler;
+use crate::constants;
+use crate::interpreter::{
+    decode_num, encode_num, AssetGroup, AssetId, LeafEvaluator, TxContext, TxInput, TxOutput,
+};
+use crate::linker::SERVER_KEY;
+use crate::models::{AbiFunction, Contract, ContractJson, Duration, Function, Requirement};
+use crate::parser;
+use crate::taproot::Network;
+use crate::typechecker::ArkType;
+use crate::vectors;
+use eval::{Evaluator, Stop};
+
+/// Input sequence that enables the transaction locktime.
+const LOCKTIME_SEQUENCE: u32 = 0xffff_fffe;
+
+/// A set of test cases for one contract.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TestSuite {
+    /// Constructor arguments shared by every case
+    #[serde(rename = "constructorInputs", default)]
+    pub constructor_inputs: HashMap<String, serde_json::Value>,
+    /// Transaction used by cases that do not give their own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx: Option<TxSpec>,
+    pub cases: Vec<TestCase>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestCase {
+    pub name: String,
+    pub function: String,
+    /// Witness elements, and any other value the case overrides
+    #[serde(default)]
+    pub witness: HashMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx: Option<TxSpec>,
+    /// Run the cooperative leaf, or the exit leaf when unset
+    #[serde(rename = "serverVariant", default = "cooperative")]
+    pub server_variant: bool,
+    pub expect: Expectation,
+}
+
+fn cooperative() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Expectation {
+    Pass,
+    Fail,
+}
+
+/// JSON form of a [`TxContext`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TxSpec {
+    /// Defaults to the placeholder sighash of the test vectors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sighash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+    #[serde(default)]
+    pub locktime: u32,
+    #[serde(rename = "currentInput", default)]
+    pub current_input: u32,
+    /// Defaults to a single input
+    #[serde(default)]
+    pub inputs: Vec<InputSpec>,
+    #[serde(default)]
+    pub outputs: Vec<OutputSpec>,
+    #[serde(rename = "assetGroups", default)]
+    pub asset_groups: Vec<AssetGroupSpec>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InputSpec {
+    #[serde(default)]
+    pub value: u64,
+    #[serde(rename = "scriptPubKey", default)]
+    pub script_pubkey: String,
+    /// Defaults to `0xfffffffe`, which enables the transaction locktime
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub txid: Option<String>,
+    #[serde(default)]
+    pub vout: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OutputSpec {
+    #[serde(default)]
+    pub value: u64,
+    #[serde(rename = "scriptPubKey", default)]
+    pub script_pubkey: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetIdSpec {
+    pub txid: String,
+    pub gidx: u16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetGroupSpec {
+    #[serde(rename = "assetId")]
+    pub asset_id: AssetIdSpec,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub control: Option<AssetIdSpec>,
+    #[serde(
+        rename = "metadataHash",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub metadata_hash: Option<String>,
+    #[serde(default)]
+    pub inputs: Vec<AssetAmountSpec>,
+    #[serde(default)]
+    pub outputs: Vec<AssetAmountSpec>,
+}
+
+/// An amount of an asset taken from an input or assigned to an output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetAmountSpec {
+    pub index: u32,
+    pub amount: u64,
+}
+
+impl TxSpec {
+    pub fn to_context(&self) -> Result<TxContext, String> {
+        let sighash = match &self.sighash {
+            Some(hex) => bytes32(hex, "sighash")?,
+            None => vectors::sighash(),
+        };
+        let mut builder = TxContext::builder()
+            .sighash(sighash)
+            .locktime(self.locktime)
+            .current_input(self.current_input);
+        if let Some(version) = self.version {
+            builder = builder.version(version);
+        }
+
+        let default_input = [InputSpec::default()];
+        let inputs = if self.inputs.is_empty() {
+            &default_input[..]
+        } else {
+            &self.inputs[..]
+        };
+        for input in inputs {
+            let mut tx_input = TxInput::new(input.value, hex_bytes(&input.script_pubkey)?);
+            if let Some(txid) = &input.txid {
+                tx_input = tx_input.outpoint(bytes32(txid, "txid")?, input.vout);
+            }
+            tx_input = tx_input.sequence(input.sequence.unwrap_or(LOCKTIME_SEQUENCE));
+            builder = builder.input(tx_input);
+        }
+        for output in &self.outputs {
+            builder = builder.output(TxOutput::new(
+                output.value,
+                hex_bytes(&output.script_pubkey)?,
+            ));
+        }
+        for group in &self.asset_groups {
+            let mut asset_group = AssetGroup::new(group.asset_id.to_asset_id()?);
+            if let Some(control) = &group.control {
+                asset_group = asset_group.control(control.to_asset_id()?);
+            }
+            if let Some(hash) = &group.metadata_hash {
+                asset_group = asset_group.metadata_hash(bytes32(hash, "metadataHash")?);
+            }
+            for input in &group.inputs {
+                asset_group = asset_group.input(input.index, input.amount);
+            }
+            for output in &group.outputs {
+                asset_group = asset_group.output(output.index, output.amount);
+            }
+            builder = builder.asset_group(asset_group);
+        }
+        builder.build()
+    }
+}
+
+impl AssetIdSpec {
+    fn to_asset_id(&self) -> Result<AssetId, String> {
+        Ok(AssetId::new(bytes32(&self.txid, "asset txid")?, self.gidx))
+    }
+}
+
+/// The outcome of one case.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaseResult {
+    pub name: String,
+    pub function: String,
+    pub expect: Expectation,
+    /// Whether the leaf succeeded and every requirement held
+    pub passed: bool,
+    /// What failed, or why the case could not be evaluated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Set when the case could not be evaluated, or the leaf and the
+    /// source disagree
+    #[serde(default)]
+    pub error: bool,
+}
+
+impl CaseResult {
+    /// Whether the case behaved as expected.
+    pub fn ok(&self) -> bool {
+        !self.error && self.passed == (self.expect == Expectation::Pass)
+    }
+}
+
+/// The results and coverage of a test suite.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestReport {
+    #[serde(rename = "contractName")]
+    pub contract_name: String,
+    pub results: Vec<CaseResult>,
+    pub coverage: Coverage,
+}
+
+impl TestReport {
+    pub fn failures(&self) -> usize {
+        self.results.iter().filter(|r| !r.ok()).count()
+    }
+}
+
+/// Run a test suite against a contract.
+pub fn run(source_code: &str, suite: &TestSuite) -> Result<TestReport, String> {
+    let mut contract = parser::parse(source_code).map_err(|e| format!("Parse error: {}", e))?;
+    constants::resolve(&mut contract, Network::default())?;
+    let output = compiler::compile(source_code)?;
+    let defaults = vectors::Values::for_contract(&contract);
+
+    let mut constructor = HashMap::new();
+    for param in &output.parameters {
+        let value = match suite.constructor_inputs.get(&param.name) {
+            Some(value) => json_value(value, &param.name)?,
+            None => defaults.bytes_of(&param.name, &param.param_type)?,
+        };
+        constructor.insert(param.name.clone(), value);
+    }
+    for name in suite.constructor_inputs.keys() {
+        if !constructor.contains_key(name) {
+            return Err(format!("`{}` is not a constructor parameter", name));
+        }
+    }
+
+    let mut coverage = Coverage::new(&contract, source_code);
+    let mut results = Vec::new();
+    for case in &suite.cases {
+        let mut result = CaseResult {
+            name: case.name.clone(),
+            function: case.function.clone(),
+            expect: case.expect,
+            passed: false,
+            message: None,
+            error: false,
+        };
+
+        let prepared = prepare_case(case, suite, &contract, &output, &defaults, &constructor);
+        let (function, tx, values) = match prepared {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                result.error = true;
+                result.message = Some(e);
+                results.push(result);
+                continue;
+            }
+        };
+
+        let abi = match output.find_function(&case.function, case.server_variant) {
+            Ok(abi) => abi,
+            Err(e) => {
+                result.error = true;
+                result.message = Some(e);
+                results.push(result);
+                continue;
+            }
+        };
+
+        let source = evaluate(case, &contract, function, abi, &tx, &values, &mut coverage);
+        let leaf = run_leaf(&output, abi, &values, &tx);
+        let outcome = match (leaf, source) {
+            (Err(e), _) => {
+                result.error = true;
+                Err(format!("the compiled leaf cannot run: {}", e))
+            }
+            (_, Err(e)) => {
+                result.error = true;
+                Err(e)
+            }
+            (Ok(Ok(())), Ok(Ok(()))) => Ok(()),
+            (Ok(Err(_)), Ok(Err(failure))) => Err(failure),
+            (Ok(Ok(())), Ok(Err(failure))) => {
+                result.error = true;
+                Err(format!(
+                    "the compiled leaf succeeds, but the source fails: {}",
+                    failure
+                ))
+            }
+            (Ok(Err(e)), Ok(Ok(()))) => {
+                result.error = true;
+                Err(format!(
+                    "the compiled leaf fails, but the source holds: {}",
+                    e
+                ))
+            }
+        };
+        result.passed = outcome.is_ok();
+        result.message = outcome.err();
+        results.push(result);
+    }
+
+    Ok(TestReport {
+        contract_name: output.name,
+        results,
+        coverage,
+    })
+}
+
+/// Evaluate a case at the source level: the function body, recording what
+/// it exercised, then the server signature or exit timelock. An exit leaf
+/// that falls back to signatures from every key, because the body reads
+/// the transaction, is evaluated as those signatures instead of the body.
+///
+/// The outer error means the case could not be evaluated, the inner one
+/// what failed.
+fn evaluate(
+    case: &TestCase,
+    contract: &Contract,
+    function: &Function,
+    abi: &AbiFunction,
+    tx: &TxContext,
+    values: &HashMap<String, Vec<u8>>,
+    coverage: &mut Coverage,
+) -> Result<Result<(), String>, String> {
+    let mut evaluator = Evaluator::new(tx, values.clone(), contract.array_lengths(function));
+    if !abi.server_variant && compiler::function_uses_introspection(function) {
+        for element in &abi.witness_schema {
+            let pubkey = element.name.strip_suffix("Sig").unwrap_or(&element.name);
+            let signature = Requirement::CheckSig {
+                signature: element.name.clone(),
+                pubkey: pubkey.to_string(),
+                sighash: None,
+            };
+            if let Err(failure) = evaluator.check(&signature) {
+                return Ok(Err(failure));
+            }
+        }
+    } else {
+        let body = evaluator.run(&function.statements);
+        coverage.record(&case.function, &evaluator.trace);
+        match body {
+            Ok(()) => {}
+            Err(Stop::Failed { span, reason }) => {
+                return Ok(Err(format!("require at {} failed: {}", span, reason)))
+            }
+            Err(Stop::Aborted(reason)) => return Ok(Err(format!("script failed: {}", reason))),
+            Err(Stop::Error(e)) => return Err(e),
+        }
+    }
+
+    if !abi.server_variant {
+        let exit = Requirement::Older {
+            age: Duration::Blocks(contract.exit_delay()),
+            timelock_var: None,
+        };
+        Ok(evaluator
+            .check(&exit)
+            .map_err(|e| format!("exit timelock: {}", e)))
+    } else if contract.has_server_key {
+        let server = Requirement::CheckSig {
+            signature: "serverSig".to_string(),
+            pubkey: SERVER_KEY.to_string(),
+            sighash: None,
+        };
+        Ok(evaluator
+            .check(&server)
+            .map_err(|e| format!("server signature: {}", e)))
+    } else {
+        Ok(Ok(()))
+    }
+}
+
+/// Run the compiled leaf of `abi` with the case's values. The outer error
+/// means the leaf cannot run, the inner one that it fails.
+fn run_leaf(
+    output: &ContractJson,
+    abi: &AbiFunction,
+    values: &HashMap<String, Vec<u8>>,
+    tx: &TxContext,
+) -> Result<Result<(), String>, String> {
+    let mut leaf = LeafEvaluator::for_function(output, &abi.name, abi.server_variant)?
+        .param(SERVER_KEY, hex::encode(&values[SERVER_KEY]));
+    for param in &output.parameters {
+        let value = &values[&param.name];
+        let text = match ArkType::parse(&param.param_type) {
+            ArkType::Int | ArkType::Bool => decode_num(value)?.to_string(),
+            _ => hex::encode(value),
+        };
+        leaf = leaf.param(&param.name, text);
+    }
+    // The schema lists the element consumed first, on top, first
+    let stack = abi
+        .witness_schema
+        .iter()
+        .rev()
+        .map(|element| values[&element.name].clone())
+        .collect();
+    let leaf = leaf.witness_stack(stack);
+    leaf.prepare()?;
+    Ok(leaf.run(tx))
+}
+
+/// A case's function, transaction and values.
+type Prepared<'c> = (&'c Function, TxContext, HashMap<String, Vec<u8>>);
+
+/// Resolve a case's function, transaction and values.
+fn prepare_case<'c>(
+    case: &TestCase,
+    suite: &TestSuite,
+    contract: &'c Contract,
+    output: &ContractJson,
+    defaults: &vectors::Values,
+    constructor: &HashMap<String, Vec<u8>>,
+) -> Result<Prepared<'c>, String> {
+    let function = contract
+        .functions
+        .iter()
+        .find(|f| f.name == case.function && !f.is_internal)
+        .ok_or_else(|| format!("Contract has no public function `{}`", case.function))?;
+
+    let tx = match case.tx.as_ref().or(suite.tx.as_ref()) {
+        Some(spec) => spec.to_context()?,
+        None => TxSpec::default().to_context()?,
+    };
+
+    let mut values = constructor.clone();
+    values.insert(
+        SERVER_KEY.to_string(),
+        defaults.bytes_of(SERVER_KEY, "pubkey")?,
+    );
+    for abi in output.functions.iter().filter(|f| f.name == case.function) {
+        for element in &abi.witness_schema {
+            if !values.contains_key(&element.name) {
+                let value = defaults.witness_bytes(&abi.name, element)?;
+                values.insert(element.name.clone(), value);
+            }
+        }
+    }
+    for (name, value) in &case.witness {
+        values.insert(name.clone(), json_value(value, name)?);
+    }
+
+    Ok((function, tx, values))
+}
+
+/// Decode a value given in a suite.
+fn json_value(value: &serde_json::Value, name: &str) -> Result<Vec<u8>, String> {
+    match value {
+        serde_json::Value::String(hex) => {
+            hex_bytes(hex).map_err(|_| format!("Value of `{}` is not valid hex: {}", name, hex))
+        }
+        serde_json::Value::Number(n) => n
+            .as_i64()
+            .map(encode_num)
+            .ok_or_else(|| format!("Value of `{}` is not an integer: {}", name, n)),
+        serde_json::Value::Bool(b) => Ok(if *b { vec![1] } else { Vec::new() }),
+        _ => Err(format!(
+            "Value of `{}` must be a hex string, number or boolean",
+            name
+        )),
+    }
+}
+
+fn hex_bytes(hex: &str) -> Result<Vec<u8>, String> {
+    hex::decode(hex.strip_prefix("0x").unwrap_or(hex)).map_err(|e| e.to_string())
+}
+
+fn bytes32(hex: &str, what: &str) -> Result<[u8; 32], String> {
+    hex_bytes(hex)?
+        .try_into()
+        .map_err(|_| format!("{} must be 32 bytes", what))
+}
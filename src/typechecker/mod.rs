@@ -1,3 +1,4 @@
+use alloc::collections::BTreeMap;
 /// Type system for Arkade Script.
 ///
 /// Provides:
@@ -5,11 +6,19 @@
 ///   including wire-encoding metadata used by client stub generators
 /// - `infer_type`: expression-level type inference
 /// - `check_contract` / `check_function`: requirement-level type checking
-///   that returns a list of `TypeError`s (currently non-fatal — the caller
-///   decides how to surface them)
-use std::collections::HashMap;
-
-use crate::models::{Contract, Expression, Function, Requirement, Statement, DEFAULT_ARRAY_LENGTH};
+///   that returns a list of `TypeError`s; most are warnings, but operators
+///   and signature checks given values of the wrong type are fatal
+/// - `check_types`: the first fatal `TypeError`, as a compile error
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use crate::catalog::{self, EntryKind};
+use crate::models::{array_type, Contract, Expression, Function, Requirement, Statement};
 
 // ─── Type Enum ────────────────────────────────────────────────────────────────
 
@@ -23,8 +32,14 @@ pub enum ArkType {
     // ── Declared types (match grammar data_type rule) ──────────────────────
     /// 33-byte compressed secp256k1 public key
     Pubkey,
-    /// 64-byte Schnorr signature
+    /// 64-byte Schnorr signature, accepted by every signature check
     Signature,
+    /// Transaction signature for `checkSig`: 64 bytes, or 65 with a
+    /// trailing sighash flag
+    SchnorrSig,
+    /// Signature over a stack message for `checkSigFromStack`: 64 bytes,
+    /// never with a sighash flag
+    DataSig,
     /// Arbitrary-length byte array
     Bytes,
     /// 20-byte array (e.g., HASH160 output)
@@ -58,12 +73,14 @@ pub enum ArkType {
 impl ArkType {
     /// Parse from a grammar `data_type` string (e.g., `"pubkey"`, `"bytes32[]"`).
     pub fn parse(s: &str) -> ArkType {
-        if let Some(inner) = s.strip_suffix("[]") {
+        if let Some((inner, _)) = array_type(s) {
             return ArkType::Array(Box::new(ArkType::parse(inner)));
         }
         match s {
             "pubkey" => ArkType::Pubkey,
             "signature" => ArkType::Signature,
+            "schnorrsig" => ArkType::SchnorrSig,
+            "datasig" => ArkType::DataSig,
             "bytes" => ArkType::Bytes,
             "bytes20" => ArkType::Bytes20,
             "bytes32" => ArkType::Bytes32,
@@ -80,8 +97,9 @@ impl ArkType {
     /// (TypeScript, Go, etc.) can switch on them to pick the right serializer.
     pub fn encoding(&self) -> &'static str {
         match self {
-            ArkType::Pubkey => "compressed-33",
-            ArkType::Signature => "schnorr-64",
+            ArkType::Pubkey => "xonly-32",
+            ArkType::Signature | ArkType::DataSig => "schnorr-64",
+            ArkType::SchnorrSig => "schnorr-sighash",
             ArkType::Bytes => "raw",
             ArkType::Bytes20 => "raw-20",
             ArkType::Bytes32 => "raw-32",
@@ -100,6 +118,8 @@ impl ArkType {
         match self {
             ArkType::Pubkey => "pubkey".to_string(),
             ArkType::Signature => "signature".to_string(),
+            ArkType::SchnorrSig => "schnorrsig".to_string(),
+            ArkType::DataSig => "datasig".to_string(),
             ArkType::Bytes => "bytes".to_string(),
             ArkType::Bytes20 => "bytes20".to_string(),
             ArkType::Bytes32 => "bytes32".to_string(),
@@ -112,6 +132,14 @@ impl ArkType {
             ArkType::Unknown => "unknown".to_string(),
         }
     }
+
+    /// Whether this is `signature`, `schnorrsig` or `datasig`.
+    pub fn is_signature(&self) -> bool {
+        matches!(
+            self,
+            ArkType::Signature | ArkType::SchnorrSig | ArkType::DataSig
+        )
+    }
 }
 
 // ─── Type Errors ──────────────────────────────────────────────────────────────
@@ -121,34 +149,45 @@ impl ArkType {
 pub struct TypeError {
     /// Human-readable description of the problem.
     pub message: String,
+    /// Whether the script would be meaningless, such as a key compared
+    /// with a number, rather than merely suspect. [`check_types`] fails
+    /// the compilation on these; the rest are warnings.
+    pub fatal: bool,
 }
 
 impl TypeError {
     fn new(msg: impl Into<String>) -> Self {
         TypeError {
             message: msg.into(),
+            fatal: false,
+        }
+    }
+
+    fn fatal(msg: impl Into<String>) -> Self {
+        TypeError {
+            message: msg.into(),
+            fatal: true,
         }
     }
 }
 
 // ─── Scope ────────────────────────────────────────────────────────────────────
 
-type Scope = HashMap<String, ArkType>;
+type Scope = BTreeMap<String, ArkType>;
 
 fn build_scope(params: &[crate::models::Parameter]) -> Scope {
     params
         .iter()
         .flat_map(|p| {
-            if p.param_type.ends_with("[]") {
-                let base = p.param_type.trim_end_matches("[]");
+            if let Some((base, length)) = array_type(&p.param_type) {
                 let elem_type = ArkType::parse(base);
                 // Register the bare name as the array type, plus each flattened
-                // index form (name_0 … name_{N-1}).  The count must match
-                // DEFAULT_ARRAY_LENGTH so the type checker and the compiler
-                // always agree on how many elements exist.
+                // index form (name_0 … name_{N-1}).  The count comes from
+                // array_type so the type checker and the compiler always
+                // agree on how many elements exist.
                 let mut entries =
                     vec![(p.name.clone(), ArkType::Array(Box::new(elem_type.clone())))];
-                for i in 0..DEFAULT_ARRAY_LENGTH {
+                for i in 0..length {
                     entries.push((format!("{}_{}", p.name, i), elem_type.clone()));
                 }
                 entries
@@ -163,8 +202,8 @@ fn build_scope(params: &[crate::models::Parameter]) -> Scope {
 
 /// Type-check an entire contract.
 ///
-/// Returns all type errors found across all functions.
-/// Currently non-fatal — the compiler emits these as warnings.
+/// Returns all type errors found across all functions. The compiler
+/// emits those that are not [`TypeError::fatal`] as warnings.
 pub fn check_contract(contract: &Contract) -> Vec<TypeError> {
     let constructor_scope = build_scope(&contract.parameters);
     contract
@@ -174,6 +213,218 @@ pub fn check_contract(contract: &Contract) -> Vec<TypeError> {
         .collect()
 }
 
+/// Fail on the first fatal type error: an ordering comparison or
+/// arithmetic on a value that is not a number, an equality between a
+/// number and a byte string, or a signature check given a value of the
+/// wrong type. Each would otherwise compile to opcodes that never do what
+/// the source says.
+pub fn check_types(contract: &Contract) -> Result<(), String> {
+    match check_contract(contract).into_iter().find(|e| e.fatal) {
+        Some(error) => Err(error.message),
+        None => Ok(()),
+    }
+}
+
+/// Check the name the `server` option gives the operator key.
+///
+/// The key is injected by the Ark runtime, so the name need not be a
+/// constructor parameter; but when it is one, that parameter must be a
+/// `pubkey`. Unlike type errors, a bad server option is fatal.
+pub fn check_server_key(contract: &Contract) -> Result<(), String> {
+    let Some(name) = &contract.server_key else {
+        return Ok(());
+    };
+    let is_identifier = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+    if !is_identifier {
+        return Err(format!(
+            "Option 'server' must name the operator key, got {}",
+            name
+        ));
+    }
+    match contract.parameters.iter().find(|p| &p.name == name) {
+        Some(param) if ArkType::parse(&param.param_type) != ArkType::Pubkey => Err(format!(
+            "Option 'server' names parameter '{}' of type {}, but the server key must be a pubkey",
+            name, param.param_type
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Check that every `tx.*` and `this.*` path the contract reads is one
+/// [`catalog`] lists, suggesting the closest for a near-miss. An unknown
+/// path would otherwise compile to a placeholder no runtime fills in, so
+/// this too is fatal.
+pub fn check_properties(contract: &Contract) -> Result<(), String> {
+    for function in &contract.functions {
+        let mut paths = Vec::new();
+        statement_paths(&function.statements, &mut paths);
+        if let Some(path) = paths.into_iter().find(|p| !catalog::is_property_path(p)) {
+            let hint = match catalog::suggest(EntryKind::TxProperty, &path) {
+                Some(entry) => format!("; did you mean '{}'?", entry.name),
+                None => String::new(),
+            };
+            return Err(format!(
+                "fn {}: unknown property '{}'{}",
+                function.name, path, hint
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check events: declared once each, and emitted with a parameter of the
+/// function or the contract, of the field's type, for every field.
+pub fn check_events(contract: &Contract) -> Result<(), String> {
+    for (i, event) in contract.events.iter().enumerate() {
+        if contract.events[..i].iter().any(|e| e.name == event.name) {
+            return Err(format!("Event '{}' is declared twice", event.name));
+        }
+    }
+    for function in &contract.functions {
+        for emit in &function.emits {
+            let fail = |e: String| format!("fn {}: {}", function.name, e);
+            let event = contract
+                .events
+                .iter()
+                .find(|e| e.name == emit.event)
+                .ok_or_else(|| fail(format!("emits undeclared event '{}'", emit.event)))?;
+            if emit.args.len() != event.parameters.len() {
+                return Err(fail(format!(
+                    "{} value(s) for event '{}', which has {} field(s)",
+                    emit.args.len(),
+                    event.name,
+                    event.parameters.len()
+                )));
+            }
+            for (field, arg) in event.parameters.iter().zip(&emit.args) {
+                let param = function
+                    .parameters
+                    .iter()
+                    .chain(&contract.parameters)
+                    .find(|p| &p.name == arg)
+                    .ok_or_else(|| {
+                        fail(format!(
+                            "event '{}' field '{}' is given '{}', which is not a parameter of the function or contract",
+                            event.name, field.name, arg
+                        ))
+                    })?;
+                if ArkType::parse(&param.param_type) != ArkType::parse(&field.param_type) {
+                    return Err(fail(format!(
+                        "{} '{}' for event '{}' field '{}' of type {}",
+                        param.param_type, arg, event.name, field.name, field.param_type
+                    )));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Collect the property paths `statements` read.
+fn statement_paths(statements: &[Statement], paths: &mut Vec<String>) {
+    for statement in statements {
+        match statement {
+            Statement::Require(Requirement::Comparison { left, right, .. }, _) => {
+                expression_paths(left, paths);
+                expression_paths(right, paths);
+            }
+            Statement::Require(..) => {}
+            Statement::LetBinding { value, .. } | Statement::VarAssign { value, .. } => {
+                expression_paths(value, paths)
+            }
+            Statement::IfElse {
+                condition,
+                then_body,
+                else_body,
+                ..
+            } => {
+                expression_paths(condition, paths);
+                statement_paths(then_body, paths);
+                if let Some(else_body) = else_body {
+                    statement_paths(else_body, paths);
+                }
+            }
+            Statement::ForIn { iterable, body, .. } => {
+                expression_paths(iterable, paths);
+                statement_paths(body, paths);
+            }
+        }
+    }
+}
+
+fn expression_paths(expression: &Expression, paths: &mut Vec<String>) {
+    match expression {
+        Expression::Property(path) if path.starts_with("tx.") || path.starts_with("this.") => {
+            paths.push(path.clone())
+        }
+        Expression::CurrentInput(Some(property)) => {
+            paths.push(format!("tx.input.current.{}", property))
+        }
+        Expression::AssetLookup { index, .. }
+        | Expression::AssetCount { index, .. }
+        | Expression::InputIntrospection { index, .. }
+        | Expression::OutputIntrospection { index, .. }
+        | Expression::GroupSum { index, .. }
+        | Expression::GroupNumIO { index, .. } => expression_paths(index, paths),
+        Expression::AssetAt {
+            io_index: first,
+            asset_index: second,
+            ..
+        }
+        | Expression::GroupIOAccess {
+            group_index: first,
+            io_index: second,
+            ..
+        }
+        | Expression::BinaryOp {
+            left: first,
+            right: second,
+            ..
+        }
+        | Expression::ArrayIndex {
+            array: first,
+            index: second,
+        }
+        | Expression::Sha256Update {
+            context: first,
+            chunk: second,
+        }
+        | Expression::Sha256Finalize {
+            context: first,
+            last_chunk: second,
+        } => {
+            expression_paths(first, paths);
+            expression_paths(second, paths);
+        }
+        Expression::Sha256Initialize { data: value }
+        | Expression::Neg64 { value }
+        | Expression::Not { value }
+        | Expression::Le64ToScriptNum { value }
+        | Expression::Le32ToLe64 { value } => expression_paths(value, paths),
+        Expression::EcMulScalarVerify {
+            scalar: first,
+            point_p: second,
+            point_q: third,
+        }
+        | Expression::TweakVerify {
+            point_p: first,
+            tweak: second,
+            point_q: third,
+        } => {
+            expression_paths(first, paths);
+            expression_paths(second, paths);
+            expression_paths(third, paths);
+        }
+        Expression::ContractInstance { args, .. } => {
+            args.iter().for_each(|arg| expression_paths(arg, paths))
+        }
+        _ => {}
+    }
+}
+
 fn check_function(function: &Function, constructor_scope: &Scope) -> Vec<TypeError> {
     let mut scope = constructor_scope.clone();
     // Merge function parameters into scope
@@ -207,10 +458,11 @@ fn check_statement(
     fn_name: &str,
 ) {
     match stmt {
-        Statement::Require(req) => {
+        Statement::Require(req, _) => {
             check_requirement(req, scope, errors, fn_name);
         }
         Statement::LetBinding { name, value } => {
+            check_operators(value, scope, errors, fn_name);
             let t = infer_type(value, scope);
             // Seed the scope so downstream uses of `name` get the inferred type.
             scope.insert(name.clone(), t);
@@ -222,6 +474,7 @@ fn check_statement(
                     fn_name, name
                 )));
             }
+            check_operators(value, scope, errors, fn_name);
             let t = infer_type(value, scope);
             // Update scope with the new type in case it changed.
             scope.insert(name.clone(), t);
@@ -230,7 +483,9 @@ fn check_statement(
             condition,
             then_body,
             else_body,
+            ..
         } => {
+            check_operators(condition, scope, errors, fn_name);
             let cond_type = infer_type(condition, scope);
             if cond_type != ArkType::Bool && cond_type != ArkType::Unknown {
                 errors.push(TypeError::new(format!(
@@ -264,21 +519,23 @@ fn check_statement(
 
 fn check_requirement(req: &Requirement, scope: &Scope, errors: &mut Vec<TypeError>, fn_name: &str) {
     match req {
-        Requirement::CheckSig { signature, pubkey } => {
+        Requirement::CheckSig {
+            signature, pubkey, ..
+        } => {
             // Detect swapped arguments first (more actionable message).
             let sig_t = scope.get(signature.as_str());
             let pk_t = scope.get(pubkey.as_str());
-            if sig_t == Some(&ArkType::Pubkey) && pk_t == Some(&ArkType::Signature) {
-                errors.push(TypeError::new(format!(
+            if sig_t == Some(&ArkType::Pubkey) && pk_t.is_some_and(ArkType::is_signature) {
+                errors.push(TypeError::fatal(format!(
                     "fn {}: checkSig({}, {}) — arguments appear swapped: expected (signature, pubkey)",
                     fn_name, signature, pubkey
                 )));
                 return;
             }
-            expect_type(
+            expect_signature(
                 scope,
                 signature,
-                &ArkType::Signature,
+                &ArkType::SchnorrSig,
                 errors,
                 fn_name,
                 &format!("checkSig() arg 1 '{}'", signature),
@@ -299,17 +556,17 @@ fn check_requirement(req: &Requirement, scope: &Scope, errors: &mut Vec<TypeErro
         } => {
             let sig_t = scope.get(signature.as_str());
             let pk_t = scope.get(pubkey.as_str());
-            if sig_t == Some(&ArkType::Pubkey) && pk_t == Some(&ArkType::Signature) {
-                errors.push(TypeError::new(format!(
+            if sig_t == Some(&ArkType::Pubkey) && pk_t.is_some_and(ArkType::is_signature) {
+                errors.push(TypeError::fatal(format!(
                     "fn {}: checkSigFromStack({}, {}, {}) — first two arguments appear swapped",
                     fn_name, signature, pubkey, message
                 )));
                 return;
             }
-            expect_type(
+            expect_signature(
                 scope,
                 signature,
-                &ArkType::Signature,
+                &ArkType::DataSig,
                 errors,
                 fn_name,
                 &format!("checkSigFromStack() arg 1 '{}'", signature),
@@ -335,27 +592,51 @@ fn check_requirement(req: &Requirement, scope: &Scope, errors: &mut Vec<TypeErro
                 );
             }
         }
-        Requirement::HashEqual { hash, .. } => {
-            // The hash value should be bytes32.
+        Requirement::HashEqual { hash, function, .. } => {
+            // The hash value should be as wide as the digest.
+            let expected = match function.digest_size() {
+                20 => ArkType::Bytes20,
+                _ => ArkType::Bytes32,
+            };
             if let Some(t) = scope.get(hash.as_str()) {
-                if *t != ArkType::Bytes32 && *t != ArkType::Bytes && *t != ArkType::Unknown {
+                if *t != expected && *t != ArkType::Bytes && *t != ArkType::Unknown {
                     errors.push(TypeError::new(format!(
-                        "fn {}: sha256 comparison: '{}' has type '{}', expected bytes32",
+                        "fn {}: {} comparison: '{}' has type '{}', expected {}",
                         fn_name,
+                        function,
                         hash,
-                        t.as_str()
+                        t.as_str(),
+                        expected.as_str()
                     )));
                 }
             }
         }
+        Requirement::SizeBetween { value, min, max } => {
+            // Fixed-width values either always or never pass the bounds.
+            let width = match scope.get(value.as_str()) {
+                Some(ArkType::Pubkey) => Some(33),
+                Some(ArkType::Signature | ArkType::DataSig) => Some(64),
+                Some(ArkType::Bytes20) => Some(20),
+                Some(ArkType::Bytes32) => Some(32),
+                _ => None,
+            };
+            if let Some(width) = width.filter(|w| !(*min..=*max).contains(w)) {
+                errors.push(TypeError::new(format!(
+                    "fn {}: size(): '{}' is always {} bytes, not between {} and {}",
+                    fn_name, value, width, min, max
+                )));
+            }
+        }
         Requirement::Comparison { left, op, right } => {
+            check_operator(left, op, right, scope, errors, fn_name);
             let lt = infer_type(left, scope);
             let rt = infer_type(right, scope);
             // Warn when one side is Uint64Le and the other is a plain Int —
             // these require explicit conversion opcodes (OP_SCRIPTNUMTOLE64 /
             // OP_LE64TOSCRIPTNUM) and the compiler inserts them automatically,
             // but it's good to flag the mismatch for contract authors.
-            if lt != ArkType::Unknown && rt != ArkType::Unknown {
+            let logical = op == "&&" || op == "||";
+            if !logical && lt != ArkType::Unknown && rt != ArkType::Unknown {
                 let left_64 = lt == ArkType::Uint64Le;
                 let right_64 = rt == ArkType::Uint64Le;
                 if left_64 != right_64 {
@@ -370,7 +651,91 @@ fn check_requirement(req: &Requirement, scope: &Scope, errors: &mut Vec<TypeErro
                 }
             }
         }
-        Requirement::After { .. } => {} // No type checking needed
+        Requirement::After { .. } | Requirement::Older { .. } => {} // No type checking needed
+    }
+}
+
+/// Check every operator in `expr`.
+fn check_operators(expr: &Expression, scope: &Scope, errors: &mut Vec<TypeError>, fn_name: &str) {
+    if let Expression::BinaryOp { left, op, right } = expr {
+        check_operator(left, op, right, scope, errors, fn_name);
+    }
+}
+
+/// Check that `op` can apply to its operands: arithmetic and ordering need
+/// numbers on both sides, and equality needs both sides numbers or both
+/// byte strings.
+fn check_operator(
+    left: &Expression,
+    op: &str,
+    right: &Expression,
+    scope: &Scope,
+    errors: &mut Vec<TypeError>,
+    fn_name: &str,
+) {
+    check_operators(left, scope, errors, fn_name);
+    check_operators(right, scope, errors, fn_name);
+    let (lt, rt) = (operand_type(left, scope), operand_type(right, scope));
+    match op {
+        "+" | "-" | "*" | "/" | "%" | ">=" | "<=" | ">" | "<" => {
+            for (side, expr, t) in [("left", left, &lt), ("right", right, &rt)] {
+                if is_number(t) == Some(false) {
+                    let name = match expr {
+                        Expression::Variable(name) => format!(" '{}'", name),
+                        _ => String::new(),
+                    };
+                    errors.push(TypeError::fatal(format!(
+                        "fn {}: '{}' needs numbers, but its {} operand{} has type '{}'",
+                        fn_name,
+                        op,
+                        side,
+                        name,
+                        t.as_str()
+                    )));
+                    return;
+                }
+            }
+        }
+        "==" | "!=" => {
+            if let (Some(l), Some(r)) = (is_number(&lt), is_number(&rt)) {
+                if l != r {
+                    errors.push(TypeError::fatal(format!(
+                        "fn {}: '{}' compares type '{}' with type '{}'",
+                        fn_name,
+                        op,
+                        lt.as_str(),
+                        rt.as_str()
+                    )));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The type of an operand, for [`check_operator`].
+fn operand_type(expr: &Expression, scope: &Scope) -> ArkType {
+    match expr {
+        // The parser compares expressions that verify themselves with `true`
+        Expression::Literal(value) if value == "true" || value == "false" => ArkType::Unknown,
+        _ => infer_type(expr, scope),
+    }
+}
+
+/// Whether values of type `t` are numbers, or byte strings; `None` if
+/// unknown or neither.
+fn is_number(t: &ArkType) -> Option<bool> {
+    match t {
+        ArkType::Int | ArkType::Bool | ArkType::Uint64Le | ArkType::Uint32Le => Some(true),
+        ArkType::Pubkey
+        | ArkType::Signature
+        | ArkType::SchnorrSig
+        | ArkType::DataSig
+        | ArkType::Bytes
+        | ArkType::Bytes20
+        | ArkType::Bytes32
+        | ArkType::Asset => Some(false),
+        ArkType::Array(_) | ArkType::Unknown => None,
     }
 }
 
@@ -382,9 +747,9 @@ fn expect_type(
     fn_name: &str,
     label: &str,
 ) {
-    if let Some(actual) = scope.get(name) {
+    if let Some(actual) = lookup(scope, name) {
         if actual != expected && *actual != ArkType::Unknown {
-            errors.push(TypeError::new(format!(
+            errors.push(TypeError::fatal(format!(
                 "fn {}: {} has type '{}', expected '{}'",
                 fn_name,
                 label,
@@ -395,6 +760,37 @@ fn expect_type(
     }
 }
 
+/// Like [`expect_type`] for the signature argument of a signature check
+/// taking `kind` signatures: a plain `signature` is accepted by both.
+fn expect_signature(
+    scope: &Scope,
+    name: &str,
+    kind: &ArkType,
+    errors: &mut Vec<TypeError>,
+    fn_name: &str,
+    label: &str,
+) {
+    let expected = match lookup(scope, name) {
+        Some(ArkType::Signature) => return,
+        Some(ArkType::SchnorrSig | ArkType::DataSig) => kind,
+        _ => &ArkType::Signature,
+    };
+    expect_type(scope, name, expected, errors, fn_name, label);
+}
+
+/// The type of `name`, where an element `arr[i]` has the array's element
+/// type.
+fn lookup<'a>(scope: &'a Scope, name: &str) -> Option<&'a ArkType> {
+    let element = name
+        .strip_suffix(']')
+        .and_then(|n| n.split_once('['))
+        .and_then(|(array, _)| match scope.get(array) {
+            Some(ArkType::Array(elem)) => Some(elem.as_ref()),
+            _ => None,
+        });
+    element.or_else(|| scope.get(name))
+}
+
 // ─── Type Inference ───────────────────────────────────────────────────────────
 
 /// Infer the `ArkType` of an expression given the current variable scope.
@@ -478,10 +874,12 @@ pub fn infer_type(expr: &Expression, scope: &Scope) -> ArkType {
 
         // Conversion and arithmetic
         Expression::Neg64 { .. } => ArkType::Uint64Le,
+        Expression::Not { .. } => ArkType::Bool,
         Expression::Le64ToScriptNum { .. } => ArkType::Int,
         Expression::Le32ToLe64 { .. } => ArkType::Uint64Le,
 
         // Crypto expressions
+        Expression::CheckSigCount { .. } => ArkType::Int,
         Expression::CheckSigExpr { .. }
         | Expression::CheckSigFromStackExpr { .. }
         | Expression::CheckSigFromStackVerify { .. }
@@ -507,7 +905,7 @@ pub fn infer_type(expr: &Expression, scope: &Scope) -> ArkType {
             let lt = infer_type(left, scope);
             let rt = infer_type(right, scope);
             match op.as_str() {
-                "+" | "-" | "*" | "/" => {
+                "+" | "-" | "*" | "/" | "%" => {
                     // If either side is 64-bit, the result is 64-bit.
                     if lt == ArkType::Uint64Le || rt == ArkType::Uint64Le {
                         ArkType::Uint64Le
@@ -515,7 +913,7 @@ pub fn infer_type(expr: &Expression, scope: &Scope) -> ArkType {
                         ArkType::Int
                     }
                 }
-                "==" | "!=" | ">=" | "<=" | ">" | "<" => ArkType::Bool,
+                "==" | "!=" | ">=" | "<=" | ">" | "<" | "&&" | "||" => ArkType::Bool,
                 _ => ArkType::Unknown,
             }
         }
@@ -0,0 +1,463 @@
+//! Test vector generation.
+//!
+//! Produces example witness stacks for every function variant of a contract,
+//! filled with deterministic dummy data: keys are derived from parameter
+//! names, hash locks are given matching preimages and signatures are BIP-340
+//! signatures over a fixed placeholder sighash (or over the signed message,
+//! for `checkSigFromStack`). Of the keys of a `checkMultisig`, as many as
+//! its threshold sign and the others leave their signatures empty; every
+//! key of a `checkSigCount` signs. Alongside each valid stack the generator emits
+//! deliberately broken variants — empty signatures, wrong preimages and
+//! truncated stacks — that any conforming implementation must reject.
+//!
+//! "Valid" covers signature, hash-lock and size checks only. Introspection and
+//! timelock conditions depend on the spending transaction and are not
+//! modeled.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::schnorr::SigningKey;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::compiler::{self, substitute_loop_body};
+use crate::constants;
+use crate::hashes;
+use crate::models::{
+    array_type, multisig_keys, Contract, Expression, Function, HashFunction, Requirement,
+    Statement, WitnessElement, DEFAULT_ARRAY_LENGTH,
+};
+use crate::parser;
+use crate::taproot::Network;
+use crate::typechecker::ArkType;
+
+/// Domain separator for every value the generator derives.
+const TAG: &str = "arkade-vectors";
+
+/// Name the server key is derived from.
+const SERVER_KEY: &str = "SERVER_KEY";
+
+/// Test vectors for one contract.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestVectors {
+    #[serde(rename = "contractName")]
+    pub contract_name: String,
+    /// Message every transaction signature commits to
+    pub sighash: String,
+    /// Compressed public key standing in for the Arkade operator
+    #[serde(rename = "serverKey")]
+    pub server_key: String,
+    /// Values the dummy contract is instantiated with
+    #[serde(rename = "constructorInputs")]
+    pub constructor_inputs: Vec<VectorValue>,
+    pub vectors: Vec<TestVector>,
+}
+
+/// A named, hex-encoded value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorValue {
+    pub name: String,
+    pub hex: String,
+}
+
+/// One witness stack for one function variant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestVector {
+    pub function: String,
+    #[serde(rename = "serverVariant")]
+    pub server_variant: bool,
+    /// Whether the stack is expected to satisfy the leaf
+    pub valid: bool,
+    pub description: String,
+    /// Witness elements in `witnessSchema` order
+    pub witness: Vec<VectorValue>,
+}
+
+/// Generate test vectors for every function variant of a contract.
+pub fn generate(source_code: &str) -> Result<TestVectors, String> {
+    let mut contract = parser::parse(source_code).map_err(|e| format!("Parse error: {}", e))?;
+    constants::resolve(&mut contract, Network::default())?;
+    let output = compiler::compile(source_code)?;
+
+    let values = Values::for_contract(&contract);
+
+    let constructor_inputs = output
+        .parameters
+        .iter()
+        .map(|p| values.value(&p.name, &p.param_type))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut vectors = Vec::new();
+    for abi in &output.functions {
+        let witness = abi
+            .witness_schema
+            .iter()
+            .map(|e| values.element(&abi.name, e))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let vector = |valid: bool, description: String, witness: Vec<VectorValue>| TestVector {
+            function: abi.name.clone(),
+            server_variant: abi.server_variant,
+            valid,
+            description,
+            witness,
+        };
+
+        vectors.push(vector(
+            true,
+            "all signature and hash-lock checks satisfied".to_string(),
+            witness.clone(),
+        ));
+
+        for (i, element) in abi.witness_schema.iter().enumerate() {
+            let mut broken = witness.clone();
+            // A key that does not sign leaves its signature empty already,
+            // and a count may hold without the signature
+            if broken[i].hex.is_empty() || values.is_counted(&abi.name, &element.name) {
+                continue;
+            }
+            if ArkType::parse(&element.elem_type).is_signature() {
+                broken[i].hex.clear();
+                vectors.push(vector(
+                    false,
+                    format!("empty signature for `{}`", element.name),
+                    broken,
+                ));
+            } else if values.preimages.contains_key(&element.name) {
+                broken[i].hex = flip_first_byte(&broken[i].hex);
+                vectors.push(vector(
+                    false,
+                    format!("wrong preimage for `{}`", element.name),
+                    broken,
+                ));
+            }
+        }
+
+        if let Some(last) = abi.witness_schema.last() {
+            let mut truncated = witness.clone();
+            truncated.pop();
+            vectors.push(vector(
+                false,
+                format!("missing witness element `{}`", last.name),
+                truncated,
+            ));
+        }
+    }
+
+    Ok(TestVectors {
+        contract_name: output.name,
+        sighash: hex::encode(sighash()),
+        server_key: hex::encode(public_key(SERVER_KEY)?),
+        constructor_inputs,
+        vectors,
+    })
+}
+
+/// What the generator knows about how values relate to each other.
+#[derive(Default)]
+pub(crate) struct Values {
+    /// Preimage name -> name of the hash it must match
+    preimages: HashMap<String, String>,
+    /// Names of hashes some preimage must match -> the function hashing it
+    hashes: HashMap<String, HashFunction>,
+    /// Signature name -> key name and, for `checkSigFromStack`, the message
+    signers: HashMap<String, (String, Option<String>)>,
+    /// Name -> inclusive size bounds, in bytes
+    sizes: HashMap<String, (u64, u64)>,
+    /// (Function, signature) -> the key signing it in a chain, or `None`
+    /// for a key that does not sign and leaves it empty
+    chains: HashMap<(String, String), Option<String>>,
+    /// (Function, signature) of the chains of `checkSigCount`, where a
+    /// signature fewer may still be enough
+    counted: HashSet<(String, String)>,
+    /// Keys of the chains of the function being collected, and how many of
+    /// them sign: a threshold, or every key of a count
+    pending: Vec<(Vec<String>, Option<usize>)>,
+    /// Signatures the function being collected checks other than in a chain
+    checked: HashSet<String>,
+}
+
+impl Values {
+    pub(crate) fn for_contract(contract: &Contract) -> Self {
+        let mut values = Values::default();
+        for function in &contract.functions {
+            let arrays = contract.array_lengths(function);
+            values.collect(&function.statements, &arrays);
+            values.bind_chains(function);
+        }
+        values
+    }
+
+    /// Give the signatures of the function's chains their keys, as the
+    /// compiler binds them: a key's `<key>Sig` if the function takes it,
+    /// and otherwise the function's signatures no other check reads, in
+    /// order. The first `threshold` keys of a chain sign.
+    fn bind_chains(&mut self, function: &Function) {
+        let signatures: Vec<String> = function
+            .parameters
+            .iter()
+            .flat_map(|p| match array_type(&p.param_type) {
+                Some((base, length)) if ArkType::parse(base).is_signature() => {
+                    (0..length).map(|i| format!("{}_{}", p.name, i)).collect()
+                }
+                None if ArkType::parse(&p.param_type).is_signature() => vec![p.name.clone()],
+                _ => Vec::new(),
+            })
+            .collect();
+        let pending = std::mem::take(&mut self.pending);
+        let checked = std::mem::take(&mut self.checked);
+        let named: HashSet<String> = pending
+            .iter()
+            .flat_map(|(keys, _)| keys.iter().map(|key| format!("{}Sig", key)))
+            .collect();
+        let mut free = signatures
+            .iter()
+            .filter(|s| !checked.contains(*s) && !named.contains(*s));
+        let mut bound: HashMap<String, String> = HashMap::new();
+        for (keys, threshold) in pending {
+            for (i, key) in keys.iter().enumerate() {
+                let name = format!("{}Sig", key);
+                let signature = match bound.get(&name) {
+                    Some(signature) => signature.clone(),
+                    None if signatures.contains(&name) => name.clone(),
+                    None => free.next().cloned().unwrap_or_else(|| name.clone()),
+                };
+                bound.insert(name, signature.clone());
+                let element = (function.name.clone(), signature);
+                let signer = (i < threshold.unwrap_or(keys.len())).then(|| key.clone());
+                if threshold.is_none() {
+                    self.counted.insert(element.clone());
+                }
+                self.chains.entry(element).or_insert(signer);
+            }
+        }
+    }
+
+    fn collect(&mut self, statements: &[Statement], arrays: &BTreeMap<String, usize>) {
+        for stmt in statements {
+            match stmt {
+                Statement::Require(req, _) => match req {
+                    Requirement::HashEqual {
+                        preimage,
+                        hash,
+                        function,
+                    } => {
+                        self.preimages.insert(preimage.clone(), hash.clone());
+                        self.hashes.insert(hash.clone(), *function);
+                    }
+                    Requirement::CheckSig {
+                        signature, pubkey, ..
+                    } => {
+                        self.signer(signature, pubkey, None);
+                    }
+                    Requirement::CheckSigFromStack {
+                        signature,
+                        pubkey,
+                        message,
+                    } => self.signer(signature, pubkey, Some(message)),
+                    Requirement::CheckMultisig { pubkeys, threshold } => {
+                        if let Ok((keys, threshold)) = multisig_keys(pubkeys, *threshold, arrays) {
+                            self.pending.push((keys, Some(threshold as usize)));
+                        }
+                    }
+                    Requirement::SizeBetween { value, min, max } => {
+                        self.sizes.insert(value.clone(), (*min, *max));
+                    }
+                    Requirement::Comparison { left, .. } => self.expression(left),
+                    _ => {}
+                },
+                Statement::IfElse {
+                    condition,
+                    then_body,
+                    else_body,
+                    ..
+                } => {
+                    self.expression(condition);
+                    self.collect(then_body, arrays);
+                    if let Some(else_stmts) = else_body {
+                        self.collect(else_stmts, arrays);
+                    }
+                }
+                Statement::ForIn {
+                    index_var,
+                    value_var,
+                    iterable,
+                    body,
+                } => {
+                    // Loops over arrays refer to the flattened elements
+                    if let Expression::Variable(array) = iterable {
+                        let length = arrays.get(array).copied();
+                        for k in 0..length.unwrap_or(DEFAULT_ARRAY_LENGTH) {
+                            let body =
+                                substitute_loop_body(body, index_var, value_var, k, Some(array));
+                            self.collect(&body, arrays);
+                        }
+                    }
+                    self.collect(body, arrays);
+                }
+                Statement::LetBinding { value, .. } | Statement::VarAssign { value, .. } => {
+                    self.expression(value)
+                }
+            }
+        }
+    }
+
+    fn expression(&mut self, expr: &Expression) {
+        match expr {
+            Expression::CheckSigExpr { signature, pubkey } => self.signer(signature, pubkey, None),
+            Expression::CheckSigFromStackExpr {
+                signature,
+                pubkey,
+                message,
+            }
+            | Expression::CheckSigFromStackVerify {
+                signature,
+                pubkey,
+                message,
+            } => self.signer(signature, pubkey, Some(message)),
+            Expression::CheckSigCount { pubkeys } => self.pending.push((pubkeys.clone(), None)),
+            _ => {}
+        }
+    }
+
+    fn signer(&mut self, signature: &str, pubkey: &str, message: Option<&String>) {
+        self.checked.insert(signature.to_string());
+        self.signers
+            .entry(signature.to_string())
+            .or_insert_with(|| (pubkey.to_string(), message.cloned()));
+    }
+
+    /// The value of a witness element of `function`.
+    fn element(&self, function: &str, element: &WitnessElement) -> Result<VectorValue, String> {
+        Ok(VectorValue {
+            name: element.name.clone(),
+            hex: hex::encode(self.witness_bytes(function, element)?),
+        })
+    }
+
+    /// Whether `signature` of `function` is counted by a `checkSigCount`.
+    fn is_counted(&self, function: &str, signature: &str) -> bool {
+        self.counted
+            .contains(&(function.to_string(), signature.to_string()))
+    }
+
+    /// The raw bytes of a witness element of `function`: a signature in a
+    /// chain is its key's, or empty if the key does not sign.
+    pub(crate) fn witness_bytes(
+        &self,
+        function: &str,
+        element: &WitnessElement,
+    ) -> Result<Vec<u8>, String> {
+        match self
+            .chains
+            .get(&(function.to_string(), element.name.clone()))
+        {
+            Some(Some(key)) => sign(key, &sighash()),
+            Some(None) => Ok(Vec::new()),
+            None => self.bytes_of(&element.name, &element.elem_type),
+        }
+    }
+
+    /// The dummy value of a named constructor argument or witness element.
+    fn value(&self, name: &str, param_type: &str) -> Result<VectorValue, String> {
+        Ok(VectorValue {
+            name: name.to_string(),
+            hex: hex::encode(self.bytes_of(name, param_type)?),
+        })
+    }
+
+    /// The raw bytes of [`Values::value`].
+    pub(crate) fn bytes_of(&self, name: &str, param_type: &str) -> Result<Vec<u8>, String> {
+        Ok(match param_type {
+            "pubkey" => public_key(name)?,
+            "signature" | "schnorrsig" | "datasig" => self.signature(name)?,
+            "int" | "bool" => vec![1],
+            "uint64le" => 1u64.to_le_bytes().to_vec(),
+            "uint32le" => 1u32.to_le_bytes().to_vec(),
+            "bytes20" => self.bytes(name)[..20].to_vec(),
+            _ => self.bytes(name),
+        })
+    }
+
+    /// A byte-string value, honoring hash-lock relations and size bounds.
+    fn bytes(&self, name: &str) -> Vec<u8> {
+        if let Some(hash) = self.preimages.get(name) {
+            preimage_for(hash)
+        } else if let Some(&function) = self.hashes.get(name) {
+            hashes::digest(function, &preimage_for(name))
+        } else if let Some(&(min, max)) = self.sizes.get(name) {
+            let value = tagged(&["value", name]);
+            let size = (value.len() as u64).clamp(min, max) as usize;
+            value.iter().copied().cycle().take(size).collect()
+        } else {
+            tagged(&["value", name]).to_vec()
+        }
+    }
+
+    fn signature(&self, name: &str) -> Result<Vec<u8>, String> {
+        let (key, message) = match self.signers.get(name) {
+            Some((key, message)) => (key.clone(), message.clone()),
+            // Cooperative and N-of-N exit signatures are named after their key
+            None if name == "serverSig" => (SERVER_KEY.to_string(), None),
+            None => (name.strip_suffix("Sig").unwrap_or(name).to_string(), None),
+        };
+        let message = match message {
+            Some(message) => self.bytes(&message),
+            None => sighash().to_vec(),
+        };
+        sign(&key, &message).map_err(|e| format!("Failed to sign for `{}`: {}", name, e))
+    }
+}
+
+/// A BIP-340 signature of `message` by the dummy key named `key`.
+fn sign(key: &str, message: &[u8]) -> Result<Vec<u8>, String> {
+    let signature = signing_key(key)?
+        .sign_raw(message, &[0u8; 32])
+        .map_err(|e| e.to_string())?;
+    Ok(signature.to_bytes().to_vec())
+}
+
+fn preimage_for(hash: &str) -> Vec<u8> {
+    tagged(&["preimage", hash]).to_vec()
+}
+
+/// The placeholder sighash transaction signatures commit to.
+pub(crate) fn sighash() -> [u8; 32] {
+    tagged(&["sighash"])
+}
+
+fn signing_key(name: &str) -> Result<SigningKey, String> {
+    SigningKey::from_bytes(&tagged(&["key", name]))
+        .map_err(|e| format!("Failed to derive key `{}`: {}", name, e))
+}
+
+/// The compressed public key of the dummy key named `name`.
+fn public_key(name: &str) -> Result<Vec<u8>, String> {
+    let key = signing_key(name)?;
+    let secret = k256::SecretKey::from(key.as_nonzero_scalar());
+    Ok(secret
+        .public_key()
+        .to_encoded_point(true)
+        .as_bytes()
+        .to_vec())
+}
+
+fn tagged(parts: &[&str]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(TAG);
+    for part in parts {
+        hasher.update("/");
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+fn flip_first_byte(hex_value: &str) -> String {
+    let mut bytes = hex::decode(hex_value).unwrap_or_default();
+    match bytes.first_mut() {
+        Some(b) => *b ^= 0xff,
+        None => bytes.push(0xff),
+    }
+    hex::encode(bytes)
+}
@@ -2,9 +2,301 @@
 //!
 //! This module provides WebAssembly bindings for the compiler,
 //! allowing it to be used in web browsers.
+//!
+//! Values cross the boundary as plain JavaScript objects. Their TypeScript
+//! shapes are declared below and merged by wasm-bindgen into the package's
+//! `.d.ts`, so `compile` returns an `ArkadeArtifact` rather than `any`.
 
+use serde::Serialize;
 use wasm_bindgen::prelude::*;
 
+use crate::diagnostics;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &'static str = r#"
+/** A constructor parameter or function input */
+export interface Parameter {
+  name: string;
+  /** Arkade Script type, e.g. `"pubkey"`, `"bytes32"` or `"int"` */
+  type: string;
+}
+
+/** A value the spender supplies in the witness */
+export interface WitnessElement {
+  /** Matches a `<name>` placeholder in `asm` */
+  name: string;
+  type: string;
+  encoding: "xonly-32" | "schnorr-64" | "schnorr-sighash" | "raw" | "raw-20" | "raw-32" | "scriptnum" | "le64" | "le32" | "array" | "unknown";
+  /** Sighash flag the signature must be made with; the script appends it */
+  sighash?: number;
+}
+
+/** One item of the witness stack, as `witnessTemplate` lists them */
+export interface WitnessItem {
+  /** Position in the witness, 0 at the bottom of the stack */
+  index: number;
+  /** The `witnessSchema` element the item carries */
+  name: string;
+  role: "signature" | "serverSignature" | "preimage" | "index" | "value";
+  type: string;
+  encoding: WitnessElement["encoding"];
+  sighash?: number;
+}
+
+/** What an `asm` token is; sizes are stack bytes, given when the type fixes them */
+export type OperationData =
+  | { kind: "opcode" }
+  | { kind: "push"; hex: string; size: number }
+  | { kind: "constructorParam"; name: string; type: string; size?: number }
+  | { kind: "functionInput"; name: string; type: string; size?: number }
+  | { kind: "runtime"; name: string; size: number }
+  | { kind: "local"; name: string };
+
+/** One `asm` token and what it stands for */
+export interface Operation {
+  op: string;
+  data: OperationData;
+}
+
+export interface RequireStatement {
+  type: string;
+  message?: string;
+}
+
+/** Estimated witness of a script-path spend, in bytes */
+export interface WitnessSize {
+  stack: number;
+  script: number;
+  controlBlock: number;
+  total: number;
+}
+
+/** What spending a leaf costs before the contract is instantiated */
+export interface SizeEstimate {
+  /** Leaf script bytes, with constructor arguments at their usual size */
+  script: number;
+  /** Whole witness bytes at their largest */
+  witness: number;
+  /** Weight units the spending input adds: 4 per non-witness byte, 1 per witness byte */
+  weight: number;
+}
+
+/** One tapscript leaf: a function's cooperative or exit variant */
+export interface AbiFunction {
+  name: string;
+  functionInputs: Parameter[];
+  witnessSchema: WitnessElement[];
+  /** The witness stack to build, bottom first */
+  witnessTemplate: WitnessItem[];
+  /** `true` for the cooperative path, `false` for the unilateral exit */
+  serverVariant: boolean;
+  require: RequireStatement[];
+  /** Script assembly; `<name>` tokens are placeholders */
+  asm: string[];
+  /** Each `asm` token, described */
+  operations: Operation[];
+  witnessSize: WitnessSize;
+  sizeEstimate: SizeEstimate;
+  /** First 4 bytes, hex, of SHA-256 of `name(type,...):server` or `:exit` */
+  selector: string;
+  /** Position of the leaf in the script tree */
+  leafIndex: number;
+  /** The leaf's place in the taproot output, set by linking with an address */
+  proof?: LeafProof;
+  /** Serialized leaf script, hex, without the witness; set once linked */
+  scriptHex?: string;
+  /** Events a spend emits, each field given by an input's name */
+  emits?: { event: string; args: string[] }[];
+}
+
+/** What a control block needs besides the internal key */
+export interface LeafProof {
+  leafHash: string;
+  /** Sibling hashes, hex, from the leaf up to the root */
+  merklePath: string[];
+  /** Whether the output key's y coordinate is odd */
+  parity: boolean;
+  /** Leaf version and parity, internal key, then `merklePath`, hex */
+  controlBlock: string;
+}
+
+/** The script tree of a linked contract's output and the keys it joins */
+export interface TaprootTree {
+  /** x-only, hex */
+  internalKey: string;
+  /** x-only, hex: the internal key tweaked by `merkleRoot` */
+  outputKey: string;
+  parity: boolean;
+  merkleRoot: string;
+  root: TapNode;
+}
+
+/** A leaf, by its place in `functions`, or a branch of two subtrees */
+export type TapNode =
+  | { leafIndex: number; hash: string }
+  | { hash: string; left: TapNode; right: TapNode };
+
+/** A leaf of a spend path, by its place in `functions` and the tree */
+export interface PathLeaf {
+  leafIndex: number;
+  selector: string;
+}
+
+/** Every way a contract's output can be spent */
+export interface SpendGraph {
+  keyPath: {
+    /** x-only, hex, or a `<name>` placeholder until linked */
+    internalKey: string;
+    /** `false` for the NUMS point and keys derived from it */
+    spendable: boolean;
+  };
+  /** Each public function's cooperative and exit leaves */
+  paths: { function: string; server: PathLeaf; exit: PathLeaf }[];
+  /** Renewal timelock in blocks, from the `renew` option */
+  renew?: number;
+  /** `tr()` descriptor of the whole output, once linked */
+  descriptor?: string;
+}
+
+/** An event declared for indexers; it does not affect the script */
+export interface AbiEvent {
+  name: string;
+  inputs: Parameter[];
+}
+
+export interface OptimizationReport {
+  function: string;
+  serverVariant: boolean;
+  sizeBefore: number;
+  sizeAfter: number;
+}
+
+export interface LeafMetrics {
+  function: string;
+  serverVariant: boolean;
+  sigops: number;
+  introspectionOps: number;
+  arithmetic64Ops: number;
+}
+
+/** A compiled contract, as written by `arkadec` */
+export interface ArkadeArtifact {
+  contractName: string;
+  constructorInputs: Parameter[];
+  functions: AbiFunction[];
+  exitDelay: number;
+  source?: string;
+  /** SHA-256 of `source`, hex, when the source is normalized */
+  sourceHash?: string;
+  /** SHA-256, hex, of the name, constructor inputs and every variant's `asm` */
+  artifactHash: string;
+  compiler?: { name: string; version: string };
+  updatedAt?: string;
+  warnings?: string[];
+  optimizations?: OptimizationReport[];
+  metrics?: LeafMetrics[];
+  generics?: Record<string, number>;
+  address?: string;
+  descriptor?: string;
+  internalKey?: string;
+  edition?: "2024" | "2025";
+  events?: AbiEvent[];
+  spendGraph?: SpendGraph;
+  /** Set by linking with an address */
+  taproot?: TaprootTree;
+}
+
+/** A public function's signature, as declared */
+export interface FunctionDescription {
+  name: string;
+  functionInputs: Parameter[];
+}
+
+/** A contract's interface, read by `describe` without compiling */
+export interface ContractDescription {
+  contractName: string;
+  /** As declared: arrays and asset IDs are not flattened */
+  constructorInputs: Parameter[];
+  functions: FunctionDescription[];
+  hasServerKey: boolean;
+  exitTimelock?: number;
+  imports?: string[];
+  generics?: string[];
+}
+
+/** A problem reported by `validate` */
+export interface Diagnostic {
+  severity: "error" | "warning";
+  /** `"parse"`, `"type"`, `"compile"` or an analysis code such as `"unsat"` */
+  code: string;
+  /** Stable code such as `"E0102"`, explained by `arkadec --explain` */
+  id: string;
+  message: string;
+  line?: number;
+  column?: number;
+  endLine?: number;
+  endColumn?: number;
+}
+
+/** Size and resource use of one leaf, as reported by `analyze` */
+export interface LeafSize {
+  function: string;
+  serverVariant: boolean;
+  witnessSize: WitnessSize;
+  vbytes: number;
+  sigops: number;
+  introspectionOps: number;
+  arithmetic64Ops: number;
+}
+
+/** Everything `analyze` reports for one version of a source file */
+export interface Analysis {
+  /** Formatted source, absent if the source does not parse */
+  formatted?: string;
+  diagnostics: Diagnostic[];
+  /** Absent if the source does not compile */
+  artifact?: ArkadeArtifact;
+  leaves: LeafSize[];
+}
+
+/** Location of a token in the source */
+export interface Span {
+  /** Byte offsets of the first character and one past the last */
+  start: number;
+  end: number;
+  /** 1-based line and column of `start` */
+  line: number;
+  column: number;
+}
+
+/** A token found by `tokenize`, for syntax highlighting */
+export interface Token {
+  kind: "keyword" | "type" | "builtin" | "identifier" | "number" | "string" | "comment" | "operator" | "punctuation" | "unknown";
+  span: Span;
+}
+
+/** A built-in name, as listed by `catalog` */
+export interface CatalogEntry {
+  kind: "builtin" | "txProperty" | "groupProperty" | "option" | "constant";
+  /** As written in source, with `i`, `o`, `t` and `k` for indices */
+  name: string;
+  /** Parameters and result type, or the value type of a property */
+  signature: string;
+  doc: string;
+  /** The grammar extension it needs, absent for the core language */
+  extension?: "assets" | "introspection" | "loops" | "opcodes";
+  targets: ("arkade" | "tapscript")[];
+}
+
+/** An example contract embedded in the compiler */
+export interface Example {
+  /** File name in the repository's `examples/`, without `.ark` */
+  name: string;
+  description: string;
+  source: string;
+}
+"#;
+
 /// Initialize panic hook for better error messages in the browser console
 #[wasm_bindgen(start)]
 pub fn init() {
@@ -12,20 +304,137 @@ pub fn init() {
     console_error_panic_hook::set_once();
 }
 
-/// Compile Arkade Script source code to JSON
+/// Error thrown by `compile`
+#[wasm_bindgen]
+pub struct CompileError(crate::models::CompileError);
+
+#[wasm_bindgen]
+impl CompileError {
+    /// `"parse"` for syntax errors, `"compile"` for everything else
+    #[wasm_bindgen(getter, unchecked_return_type = "\"parse\" | \"compile\"")]
+    pub fn kind(&self) -> String {
+        self.0.kind().to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.0.message().to_string()
+    }
+
+    /// Stable code such as `"E0001"`, explained by `arkadec --explain`
+    #[wasm_bindgen(getter)]
+    pub fn code(&self) -> String {
+        self.0.code().to_string()
+    }
+
+    /// 1-based line of the error, when known
+    #[wasm_bindgen(getter)]
+    pub fn line(&self) -> Option<usize> {
+        self.0.span().map(|span| span.line)
+    }
+
+    /// 1-based column of the error, when known
+    #[wasm_bindgen(getter)]
+    pub fn column(&self) -> Option<usize> {
+        self.0.span().map(|span| span.column)
+    }
+
+    /// The source line of the error, when known
+    #[wasm_bindgen(getter)]
+    pub fn snippet(&self) -> Option<String> {
+        self.0.snippet().map(String::from)
+    }
+
+    /// The error with its source line and a caret under the problem, as
+    /// `arkadec` prints it
+    pub fn render(&self) -> String {
+        self.0.render()
+    }
+
+    #[wasm_bindgen(js_name = toString)]
+    pub fn display(&self) -> String {
+        match self.0.span() {
+            Some(span) => format!("{} error at {}: {}", self.0.kind(), span, self.0.message()),
+            None => format!("{} error: {}", self.0.kind(), self.0.message()),
+        }
+    }
+}
+
+impl CompileError {
+    fn unlocated(message: String) -> Self {
+        CompileError(crate::models::CompileError::compile("", message))
+    }
+}
+
+/// Convert to a plain object, with maps as objects rather than `Map`s.
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, String> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| format!("Serialization error: {}", e))
+}
+
+/// Compile Arkade Script source code to an artifact
 ///
 /// # Arguments
 /// * `source` - The Arkade Script source code
 ///
 /// # Returns
-/// A JSON string containing the compiled contract, or an error message
-#[wasm_bindgen]
-pub fn compile(source: &str) -> Result<String, String> {
-    match crate::compiler::compile(source) {
-        Ok(contract_json) => serde_json::to_string_pretty(&contract_json)
-            .map_err(|e| format!("Serialization error: {}", e)),
-        Err(e) => Err(e),
-    }
+/// The compiled contract, or throws a `CompileError`
+#[wasm_bindgen(unchecked_return_type = "ArkadeArtifact")]
+pub fn compile(source: &str) -> Result<JsValue, CompileError> {
+    let artifact = crate::compiler::compile(source).map_err(CompileError)?;
+    to_js(&artifact).map_err(CompileError::unlocated)
+}
+
+/// Describe a contract without compiling it
+///
+/// Only parses the source, so it is fast enough to call on every edit.
+///
+/// # Arguments
+/// * `source` - The Arkade Script source code
+///
+/// # Returns
+/// The contract name, constructor parameters and public function
+/// signatures, or throws a `CompileError` of kind `"parse"`
+#[wasm_bindgen(unchecked_return_type = "ContractDescription")]
+pub fn describe(source: &str) -> Result<JsValue, CompileError> {
+    let contract = crate::parser::parse(source)
+        .map_err(|e| CompileError(crate::compiler::parse_error(source, e.as_ref())))?;
+    to_js(&contract.describe()).map_err(CompileError::unlocated)
+}
+
+/// Split source code into tokens for syntax highlighting
+///
+/// Uses the grammar's own definitions of literals, types and identifiers.
+/// Never throws: text that is no token has kind `"unknown"`.
+///
+/// # Arguments
+/// * `source` - The Arkade Script source code
+///
+/// # Returns
+/// Every token except whitespace, in source order
+#[wasm_bindgen(unchecked_return_type = "Token[]")]
+pub fn tokenize(source: &str) -> Result<JsValue, JsError> {
+    to_js(&crate::parser::tokenize(source)).map_err(|e| JsError::new(&e))
+}
+
+/// Classify identifiers for semantic highlighting
+///
+/// # Arguments
+/// * `source` - The Arkade Script source code
+///
+/// # Returns
+/// LSP semantic-token data: five integers per parameter, local, builtin,
+/// property or contract name, with types indexed into `semanticTokenTypes()`
+#[wasm_bindgen(js_name = semanticTokens)]
+pub fn semantic_tokens(source: &str) -> Vec<u32> {
+    crate::semantic::encode(source, &crate::semantic::semantic_tokens(source))
+}
+
+/// The token type legend for `semanticTokens`
+#[wasm_bindgen(js_name = semanticTokenTypes, unchecked_return_type = "string[]")]
+pub fn semantic_token_types() -> Result<JsValue, JsError> {
+    to_js(&crate::semantic::TOKEN_TYPES).map_err(|e| JsError::new(&e))
 }
 
 /// Get the compiler version
@@ -34,17 +443,55 @@ pub fn version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
 }
 
-/// Validate Arkade Script source code without generating output
+/// Check Arkade Script source code without generating output
+///
+/// Runs the parser, type checker, static analyses and code generator and
+/// collects everything they report, so editors can show warnings while the
+/// contract is otherwise valid.
 ///
 /// # Arguments
 /// * `source` - The Arkade Script source code
 ///
 /// # Returns
-/// `true` if the source is valid, otherwise returns an error message
-#[wasm_bindgen]
-pub fn validate(source: &str) -> Result<bool, String> {
-    match crate::compile(source) {
-        Ok(_) => Ok(true),
-        Err(e) => Err(e.to_string()),
-    }
+/// Every diagnostic found. The source is valid if none is an error.
+#[wasm_bindgen(unchecked_return_type = "Diagnostic[]")]
+pub fn validate(source: &str) -> Result<JsValue, JsError> {
+    to_js(&diagnostics::check(source)).map_err(|e| JsError::new(&e))
+}
+
+/// Format, check and compile Arkade Script source code in one call
+///
+/// Editors that re-run on every change can use this instead of calling
+/// `validate` and `compile` separately, so the source crosses into WASM
+/// and is compiled only once.
+///
+/// # Arguments
+/// * `source` - The Arkade Script source code
+///
+/// # Returns
+/// The formatted source, every diagnostic, the artifact if the source
+/// compiles, and the size of each leaf
+#[wasm_bindgen(unchecked_return_type = "Analysis")]
+pub fn analyze(source: &str) -> Result<JsValue, JsError> {
+    to_js(&diagnostics::analyze(source)).map_err(|e| JsError::new(&e))
+}
+
+/// List the language's built-in names, for completion and hover
+///
+/// # Returns
+/// Every builtin function, `tx.*` property, group property, option key and
+/// constant, with its signature, a description and the targets it is
+/// available on
+#[wasm_bindgen(unchecked_return_type = "CatalogEntry[]")]
+pub fn catalog() -> Result<JsValue, JsError> {
+    to_js(&crate::catalog::CATALOG).map_err(|e| JsError::new(&e))
+}
+
+/// List the example contracts embedded in the compiler
+///
+/// # Returns
+/// Every example with its name, a one-line description and its source
+#[wasm_bindgen(unchecked_return_type = "Example[]")]
+pub fn examples() -> Result<JsValue, JsError> {
+    to_js(&crate::examples::EXAMPLES).map_err(|e| JsError::new(&e))
 }
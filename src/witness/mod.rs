@@ -0,0 +1,96 @@
+//! Witness stacks for spending a compiled contract.
+//!
+//! A leaf script leaves its witness elements out and expects them on the
+//! stack, the first it consumes on top. [`WitnessBuilder`] takes the values
+//! of a function variant's `witnessSchema` by name, checks each against its
+//! type and encodes it as [`encoding`](crate::encoding) does, and returns
+//! the witness items in the order a transaction carries them, before the
+//! leaf script and control block.
+
+use std::collections::BTreeMap;
+
+use crate::encoding::Value;
+use crate::models::{variant_label, AbiFunction, ContractJson};
+
+/// Builds the witness of one function variant.
+///
+/// ```ignore
+/// let witness = WitnessBuilder::for_function(&artifact, "claim", false)?
+///     .arg("receiverSig", sig_hex)
+///     .arg("preimage", preimage_hex)
+///     .build()?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct WitnessBuilder<'a> {
+    function: &'a AbiFunction,
+    args: BTreeMap<String, String>,
+}
+
+impl<'a> WitnessBuilder<'a> {
+    /// Start the witness of `function`'s cooperative variant when
+    /// `server_variant` is set, or of its exit variant.
+    pub fn for_function(
+        artifact: &'a ContractJson,
+        function: &str,
+        server_variant: bool,
+    ) -> Result<Self, String> {
+        Ok(WitnessBuilder {
+            function: artifact.find_function(function, server_variant)?,
+            args: BTreeMap::new(),
+        })
+    }
+
+    /// The function variant the witness is for.
+    pub fn function(&self) -> &'a AbiFunction {
+        self.function
+    }
+
+    /// Set the witness element `name`, written as [`Value::parse`] reads it.
+    pub fn arg(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.args.insert(name.to_string(), value.into());
+        self
+    }
+
+    /// Set several witness elements.
+    pub fn args<I, K, V>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.args
+            .extend(args.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    /// The witness items, bottom of the stack first.
+    ///
+    /// Every element of the schema needs a value of its type, and values
+    /// for names the schema does not list are rejected.
+    pub fn build(&self) -> Result<Vec<Vec<u8>>, String> {
+        let function = self.function;
+        let variant = variant_label(function.server_variant);
+        let fail = |e: String| format!("fn {} ({} variant): {}", function.name, variant, e);
+
+        if let Some(unknown) = self
+            .args
+            .keys()
+            .find(|name| !function.witness_schema.iter().any(|w| &w.name == *name))
+        {
+            return Err(fail(format!("no witness element '{}'", unknown)));
+        }
+        let mut items = Vec::new();
+        // The schema is in the order the script consumes the elements, so
+        // the first goes on top of the stack, at the end of the witness
+        for element in function.witness_schema.iter().rev() {
+            let value = self
+                .args
+                .get(&element.name)
+                .ok_or_else(|| fail(format!("missing witness element '{}'", element.name)))?;
+            let value = Value::parse(value, &element.elem_type)
+                .map_err(|e| fail(format!("witness element '{}': {}", element.name, e)))?;
+            items.extend(value.stack());
+        }
+        Ok(items)
+    }
+}
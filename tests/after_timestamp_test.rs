@@ -0,0 +1,83 @@
+use arkade_compiler::compile;
+use arkade_compiler::diagnostics::classify;
+use arkade_compiler::encoding::push_num;
+
+const CODE: &str = r#"
+contract Deadline(pubkey owner, int deadline) {
+  function claim(signature ownerSig) {
+    require(after(1700000000));
+    require(after(deadline));
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+
+#[test]
+fn test_after_compiles_to_checklocktimeverify() {
+    let artifact = compile(CODE).unwrap();
+    let claim = &artifact.functions[0];
+    assert_eq!(
+        claim.asm[..6],
+        [
+            "1700000000",
+            "OP_CHECKLOCKTIMEVERIFY",
+            "OP_DROP",
+            "<deadline>",
+            "OP_CHECKLOCKTIMEVERIFY",
+            "OP_DROP"
+        ]
+    );
+    assert_eq!(claim.require[0].req_type, "after");
+    assert_eq!(
+        claim.require[0].message.as_deref(),
+        Some("Timelock until timestamp 1700000000")
+    );
+}
+
+#[test]
+fn test_timestamps_with_the_high_bit_push_five_bytes() {
+    let code = CODE.replace("1700000000", "3000000000");
+    let artifact = compile(&code).unwrap();
+    assert_eq!(artifact.functions[0].asm[0], "3000000000");
+    let mut script = Vec::new();
+    push_num(&mut script, 3_000_000_000);
+    assert_eq!(script, [0x05, 0x00, 0x5e, 0xd0, 0xb2, 0x00]);
+}
+
+#[test]
+fn test_after_takes_timestamps_only() {
+    for (timestamp, message) in [
+        (
+            "800000",
+            "Absolute timelock 800000 is below 500000000, so it would be a block height",
+        ),
+        (
+            "4294967296",
+            "Absolute timelock 4294967296 exceeds the maximum nLockTime of 4294967295",
+        ),
+    ] {
+        let code = CODE.replace("1700000000", timestamp);
+        let err = compile(&code).unwrap_err().to_string();
+        assert!(err.contains(message), "{}", err);
+        assert_eq!(classify("parse", &err), "E0105");
+    }
+}
+
+#[test]
+fn test_tx_time_takes_the_height_after_rejects() {
+    let code = CODE.replace("after(1700000000)", "tx.time >= 800000");
+    let artifact = compile(&code).unwrap();
+    let claim = &artifact.functions[0];
+    assert_eq!(
+        claim.asm[..3],
+        ["800000", "OP_CHECKLOCKTIMEVERIFY", "OP_DROP"]
+    );
+    assert_eq!(
+        claim.require[0].message.as_deref(),
+        Some("Timelock until block height 800000")
+    );
+    assert_eq!(
+        claim.require[1].message.as_deref(),
+        Some("Timelock until deadline")
+    );
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "std")]
+
+use arkade_compiler::diagnostics::analyze;
+
+#[test]
+fn test_analyze_returns_everything_for_a_valid_contract() {
+    let code = "contract Single(pubkey owner) {\nfunction spend(signature ownerSig) {\nrequire(checkSig(ownerSig, owner));\n}\n}\n";
+    let analysis = analyze(code);
+
+    assert!(
+        analysis.diagnostics.is_empty(),
+        "{:?}",
+        analysis.diagnostics
+    );
+    assert_eq!(
+        analysis.formatted.as_deref(),
+        Some("contract Single(pubkey owner) {\n  function spend(signature ownerSig) {\n    require(checkSig(ownerSig, owner));\n  }\n}\n")
+    );
+
+    let artifact = analysis.artifact.expect("contract compiles");
+    assert_eq!(analysis.leaves.len(), artifact.functions.len());
+    for (leaf, function) in analysis.leaves.iter().zip(&artifact.functions) {
+        assert_eq!(leaf.function, function.name);
+        assert_eq!(leaf.server_variant, function.server_variant);
+        assert_eq!(leaf.vbytes, function.witness_size.vbytes());
+        assert!(leaf.resources.sigops >= 1);
+    }
+}
+
+#[test]
+fn test_analyze_unparseable_source_has_only_diagnostics() {
+    let analysis = analyze("contract X(pubkey a) {\n  function f(signature s) {\n");
+
+    assert!(analysis.formatted.is_none());
+    assert!(analysis.artifact.is_none());
+    assert!(analysis.leaves.is_empty());
+    assert_eq!(analysis.diagnostics.len(), 1);
+    assert_eq!(analysis.diagnostics[0].code, "parse");
+}
+
+#[test]
+fn test_analyze_serializes_for_javascript() {
+    let code = r#"
+contract Single(pubkey owner) {
+  function spend(signature ownerSig) {
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let json = serde_json::to_value(analyze(code)).unwrap();
+
+    assert!(json["formatted"].is_string());
+    assert_eq!(json["artifact"]["contractName"], "Single");
+    let leaf = &json["leaves"][0];
+    assert!(leaf["serverVariant"].is_boolean());
+    assert!(leaf["witnessSize"]["total"].is_number());
+    assert!(leaf["vbytes"].is_number());
+    assert!(leaf["sigops"].is_number());
+}
@@ -0,0 +1,76 @@
+use arkade_compiler::compile;
+use arkade_compiler::models::{Expression, Requirement, Statement};
+use arkade_compiler::parser::parse;
+
+const CODE: &str = r#"
+contract Lots(pubkey owner, int fee, int amount, int bonus, int lots, int minimum, int rest) {
+  function split(signature ownerSig, int inputs) {
+    require(tx.outputs[0].value >= tx.input.current.value - fee * inputs);
+    require((amount + bonus) / lots >= minimum);
+    require(amount % lots == rest);
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+
+fn asm(code: &str) -> String {
+    let artifact = compile(code).unwrap();
+    let function = artifact
+        .functions
+        .iter()
+        .find(|f| f.server_variant)
+        .unwrap();
+    function.asm.join(" ")
+}
+
+#[test]
+fn test_multiplication_binds_tighter_than_subtraction() {
+    let contract = parse(CODE).unwrap();
+    let Statement::Require(Requirement::Comparison { right, .. }, _) =
+        &contract.functions[0].statements[0]
+    else {
+        panic!("expected a comparison");
+    };
+    let Expression::BinaryOp { op, right, .. } = right else {
+        panic!("expected arithmetic");
+    };
+    assert_eq!(op, "-");
+    assert!(matches!(right.as_ref(), Expression::BinaryOp { op, .. } if op == "*"));
+}
+
+#[test]
+fn test_arithmetic_lowers_to_64_bit_opcodes() {
+    let asm = asm(CODE);
+    // The witness element is rolled up from under the values pushed over it
+    assert!(asm.starts_with(
+        "0 OP_INSPECTOUTPUTVALUE OP_PUSHCURRENTINPUTINDEX OP_INSPECTINPUTVALUE \
+         <fee> OP_SCRIPTNUMTOLE64 3 OP_ROLL OP_SCRIPTNUMTOLE64 OP_MUL64 OP_VERIFY \
+         OP_SUB64 OP_VERIFY OP_GREATERTHANOREQUAL64 OP_VERIFY"
+    ));
+    // The quotient is kept, and the witness value it is compared with converted
+    assert!(asm.contains(
+        "<amount> OP_SCRIPTNUMTOLE64 <bonus> OP_SCRIPTNUMTOLE64 OP_ADD64 OP_VERIFY \
+         <lots> OP_SCRIPTNUMTOLE64 OP_DIV64 OP_VERIFY OP_NIP \
+         <minimum> OP_SCRIPTNUMTOLE64 OP_GREATERTHANOREQUAL64 OP_VERIFY"
+    ));
+    // The remainder is kept
+    assert!(asm.contains(
+        "<amount> OP_SCRIPTNUMTOLE64 <lots> OP_SCRIPTNUMTOLE64 OP_DIV64 OP_VERIFY OP_DROP \
+         <rest> OP_SCRIPTNUMTOLE64 OP_EQUALVERIFY"
+    ));
+}
+
+#[test]
+fn test_arithmetic_in_let_bindings() {
+    let bound = CODE.replace(
+        "    require((amount + bonus) / lots >= minimum);\n",
+        "    let perLot = (amount + bonus) / lots;\n    require(perLot >= minimum);\n",
+    );
+    let contract = parse(&bound).unwrap();
+    let Statement::LetBinding { value, .. } = &contract.functions[0].statements[1] else {
+        panic!("expected a let binding");
+    };
+    assert!(matches!(value, Expression::BinaryOp { op, left, .. }
+        if op == "/" && matches!(left.as_ref(), Expression::BinaryOp { op, .. } if op == "+")));
+    assert!(asm(&bound).contains("OP_ADD64 OP_VERIFY <lots> OP_SCRIPTNUMTOLE64 OP_DIV64"));
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "std")]
+
+use std::collections::HashMap;
+
+use arkade_compiler::compile;
+use arkade_compiler::linker::link;
+use arkade_compiler::ContractJson;
+
+const SINGLE: &str = r#"
+contract Single(pubkey owner) {
+  function spend(signature ownerSig) {
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+
+const RESTYLED: &str = r#"
+// The same contract, written differently
+contract Single(pubkey owner) {
+  function spend(signature ownerSig) { require(checkSig(ownerSig, owner)); }
+}
+"#;
+
+#[test]
+fn test_artifact_hash_is_recorded_and_recomputable() {
+    let artifact = compile(SINGLE).unwrap();
+    assert_eq!(artifact.artifact_hash.len(), 64);
+    assert_eq!(artifact.artifact_hash, artifact.compute_artifact_hash());
+
+    let json = serde_json::to_string(&artifact).unwrap();
+    let parsed: ContractJson = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.compute_artifact_hash(), artifact.artifact_hash);
+}
+
+#[test]
+fn test_artifact_hash_ignores_source_layout() {
+    let first = compile(SINGLE).unwrap();
+    let second = compile(RESTYLED).unwrap();
+    assert_ne!(first.source, second.source);
+    assert_eq!(first.artifact_hash, second.artifact_hash);
+}
+
+#[test]
+fn test_artifact_hash_commits_to_the_scripts() {
+    let artifact = compile(SINGLE).unwrap();
+    let renamed = compile(&SINGLE.replace("Single", "Solo")).unwrap();
+    assert_ne!(artifact.artifact_hash, renamed.artifact_hash);
+
+    let mut tampered = artifact.clone();
+    tampered.functions[1].asm.swap(0, 1);
+    assert_ne!(tampered.compute_artifact_hash(), artifact.artifact_hash);
+
+    let mut flipped = artifact.clone();
+    flipped.functions[0].server_variant = false;
+    assert_ne!(flipped.compute_artifact_hash(), artifact.artifact_hash);
+}
+
+#[test]
+fn test_linking_keeps_the_artifact_hash() {
+    let artifact = compile(SINGLE).unwrap();
+    let args: HashMap<String, String> =
+        HashMap::from([("owner".to_string(), format!("02{}", "11".repeat(32)))]);
+    let linked = link(&artifact, &args).unwrap();
+    assert_eq!(linked.artifact_hash, artifact.artifact_hash);
+    assert_ne!(linked.compute_artifact_hash(), artifact.artifact_hash);
+}
@@ -0,0 +1,130 @@
+#![cfg(feature = "testing")]
+
+use arkade_compiler::models::{Contract, Span, Statement};
+use arkade_compiler::parser::parse;
+use arkade_compiler::{compile, printer};
+use proptest::prelude::*;
+
+fn clear_spans(statements: &mut [Statement]) {
+    for statement in statements {
+        match statement {
+            Statement::Require(_, span) => *span = Span::default(),
+            Statement::IfElse {
+                then_body,
+                else_body,
+                span,
+                ..
+            } => {
+                *span = Span::default();
+                clear_spans(then_body);
+                if let Some(else_body) = else_body {
+                    clear_spans(else_body);
+                }
+            }
+            Statement::ForIn { body, .. } => clear_spans(body),
+            Statement::LetBinding { .. } | Statement::VarAssign { .. } => {}
+        }
+    }
+}
+
+fn parse_without_spans(source: &str) -> Contract {
+    let mut contract = parse(source).unwrap_or_else(|e| panic!("{}\n\n{}", e, source));
+    for function in &mut contract.functions {
+        clear_spans(&mut function.statements);
+    }
+    contract
+}
+
+proptest! {
+    #[test]
+    fn test_printed_contracts_parse_back(contract in any::<Contract>()) {
+        let source = printer::print(&contract);
+        prop_assert_eq!(parse_without_spans(&source), contract, "{}", source);
+    }
+
+    #[test]
+    fn test_printing_is_stable(contract in any::<Contract>()) {
+        let source = printer::print(&contract);
+        prop_assert_eq!(printer::print(&parse_without_spans(&source)), source);
+    }
+
+    #[test]
+    fn test_contracts_round_trip_through_json(contract in any::<Contract>()) {
+        let json = serde_json::to_string(&contract).unwrap();
+        prop_assert_eq!(serde_json::from_str::<Contract>(&json).unwrap(), contract);
+    }
+
+    #[test]
+    fn test_compiling_printed_contracts_does_not_panic(contract in any::<Contract>()) {
+        // Most generated contracts do not typecheck; only panics fail
+        let _ = compile(&printer::print(&contract));
+    }
+}
+
+#[test]
+fn test_arithmetic_on_an_asset_lookup_parses() {
+    let source = r#"
+contract Issuance(bytes32 assetId, int amount) {
+  function mint() {
+    require(tx.outputs[0].assets.lookup(assetId) >= tx.inputs[0].assets.lookup(assetId) + amount);
+  }
+}
+"#;
+    let contract = parse(source).unwrap();
+    let Statement::Require(requirement, _) = &contract.functions[0].statements[0] else {
+        panic!("not a require");
+    };
+    assert_eq!(
+        printer::print_requirement(requirement),
+        "tx.outputs[0].assets.lookup(assetId) >= tx.inputs[0].assets.lookup(assetId) + amount"
+    );
+}
+
+#[test]
+fn test_assignments_to_names_starting_with_let() {
+    let source = r#"
+contract Counter(int start) {
+  function count() {
+    int letter = start;
+    letter = letter + 1;
+    require(letter > start);
+  }
+}
+"#;
+    let contract = parse(source).unwrap();
+    assert!(matches!(
+        &contract.functions[0].statements[1],
+        Statement::VarAssign { name, .. } if name == "letter"
+    ));
+}
+
+#[test]
+fn test_nested_expressions_keep_their_grouping() {
+    let source = r#"
+contract Bounds(int a, int b, int c, int d) {
+  function spend() {
+    require((a + b) * 2 >= c - d);
+    require(a - (b - c) == d % (a * b));
+    require(!(a > b) && c < d || a == d);
+  }
+}
+"#;
+    let contract = parse_without_spans(source);
+    let printed: Vec<String> = contract.functions[0]
+        .statements
+        .iter()
+        .map(|statement| match statement {
+            Statement::Require(requirement, _) => printer::print_requirement(requirement),
+            _ => panic!("not a require"),
+        })
+        .collect();
+    assert_eq!(
+        printed,
+        [
+            "(a + b) * 2 >= c - d",
+            "a - (b - c) == d % (a * b)",
+            "!(a > b) && c < d || a == d"
+        ]
+    );
+    assert_eq!(parse_without_spans(&printer::print(&contract)), contract);
+}
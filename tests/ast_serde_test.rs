@@ -0,0 +1,55 @@
+#![cfg(feature = "std")]
+
+use arkade_compiler::examples::{self, EXAMPLES};
+use arkade_compiler::models::{Contract, Statement};
+use arkade_compiler::parser::parse;
+
+#[test]
+fn test_examples_round_trip_through_json() {
+    for example in EXAMPLES {
+        let contract = parse(example.source).unwrap();
+        let json = serde_json::to_string(&contract).unwrap();
+        let restored: Contract = serde_json::from_str(&json)
+            .unwrap_or_else(|e| panic!("example {} does not deserialize: {}", example.name, e));
+        assert_eq!(restored, contract, "{}", example.name);
+    }
+}
+
+#[test]
+fn test_json_keeps_spans() {
+    let contract = parse(examples::find("htlc").unwrap().source).unwrap();
+    let json = serde_json::to_value(&contract).unwrap();
+    let Statement::Require(_, span) = &contract.functions[0].statements[0] else {
+        panic!("not a require");
+    };
+    let require = &json["functions"][0]["statements"][0]["Require"];
+    assert_eq!(require[1]["line"], span.line);
+    assert_eq!(require[1]["column"], span.column);
+}
+
+#[test]
+fn test_ast_cli_command() {
+    use std::fs;
+    use tempfile::tempdir;
+
+    let temp_dir = tempdir().unwrap();
+    let input_path = temp_dir.path().join("htlc.ark");
+    let source = examples::find("htlc").unwrap().source;
+    fs::write(&input_path, source).unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_arkadec"))
+        .arg("ast")
+        .arg(input_path.to_str().unwrap())
+        .arg("--format")
+        .arg("json")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "CLI failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let printed: Contract = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(printed, parse(source).unwrap());
+}
@@ -1,6 +1,7 @@
 use arkade_compiler::compile;
 use arkade_compiler::opcodes::{
-    OP_2, OP_CHECKLOCKTIMEVERIFY, OP_CHECKSIG, OP_CHECKSIGADD, OP_DROP, OP_NUMEQUAL,
+    OP_2, OP_CHECKLOCKTIMEVERIFY, OP_CHECKSIG, OP_CHECKSIGADD, OP_CHECKSIGVERIFY, OP_DROP,
+    OP_NUMEQUALVERIFY,
 };
 
 #[test]
@@ -17,7 +18,8 @@ options {
 
 contract SingleSig(
   pubkey user,
-  pubkey server
+  pubkey server,
+  int timelock
 ) {
   // Cooperative spend path (user + server)
   function cooperative(signature userSig, signature serverSig) {
@@ -41,11 +43,12 @@ contract SingleSig(
     assert_eq!(output.name, "SingleSig");
 
     // Verify parameters
-    assert_eq!(output.parameters.len(), 2);
+    assert_eq!(output.parameters.len(), 3);
     assert_eq!(output.parameters[0].name, "user");
     assert_eq!(output.parameters[0].param_type, "pubkey");
     assert_eq!(output.parameters[1].name, "server");
     assert_eq!(output.parameters[1].param_type, "pubkey");
+    assert_eq!(output.parameters[2].name, "timelock");
 
     // Verify functions - we have 4 functions (2 functions x 2 variants)
     assert_eq!(output.functions.len(), 4);
@@ -71,16 +74,18 @@ contract SingleSig(
     );
 
     // Check assembly instructions
-    assert_eq!(cooperative_function.asm.len(), 9);
-    assert_eq!(cooperative_function.asm[0], "<user>");
-    assert_eq!(cooperative_function.asm[1], OP_CHECKSIG);
-    assert_eq!(cooperative_function.asm[2], "<server>");
-    assert_eq!(cooperative_function.asm[3], OP_CHECKSIGADD);
-    assert_eq!(cooperative_function.asm[4], OP_2);
-    assert_eq!(cooperative_function.asm[5], OP_NUMEQUAL);
-    assert_eq!(cooperative_function.asm[6], "<SERVER_KEY>");
-    assert_eq!(cooperative_function.asm[7], "<serverSig>");
-    assert_eq!(cooperative_function.asm[8], OP_CHECKSIG);
+    assert_eq!(cooperative_function.asm.len(), 11);
+    assert_eq!(cooperative_function.asm[0], "OP_OVER");
+    assert_eq!(cooperative_function.asm[1], "OP_SWAP");
+    assert_eq!(cooperative_function.asm[2], "<user>");
+    assert_eq!(cooperative_function.asm[3], OP_CHECKSIG);
+    assert_eq!(cooperative_function.asm[4], "<server>");
+    assert_eq!(cooperative_function.asm[5], OP_CHECKSIGADD);
+    assert_eq!(cooperative_function.asm[6], OP_2);
+    assert_eq!(cooperative_function.asm[7], OP_NUMEQUALVERIFY);
+    assert_eq!(cooperative_function.asm[8], "<serverSig>");
+    assert_eq!(cooperative_function.asm[9], "<SERVER_KEY>");
+    assert_eq!(cooperative_function.asm[10], OP_CHECKSIG);
 
     // Verify timeout function with server variant
     let timeout_function = output
@@ -91,13 +96,13 @@ contract SingleSig(
 
     // Check assembly instructions
     assert_eq!(timeout_function.asm.len(), 9);
-    assert_eq!(timeout_function.asm[0], "<user>");
-    assert_eq!(timeout_function.asm[1], "<userSig>");
-    assert_eq!(timeout_function.asm[2], OP_CHECKSIG);
-    assert_eq!(timeout_function.asm[3], "<timelock>"); // Variable reference
+    assert_eq!(timeout_function.asm[0], "<userSig>");
+    assert_eq!(timeout_function.asm[1], "<user>");
+    assert_eq!(timeout_function.asm[2], OP_CHECKSIGVERIFY);
+    assert_eq!(timeout_function.asm[3], "<timelock>");
     assert_eq!(timeout_function.asm[4], OP_CHECKLOCKTIMEVERIFY);
     assert_eq!(timeout_function.asm[5], OP_DROP);
-    assert_eq!(timeout_function.asm[6], "<SERVER_KEY>");
-    assert_eq!(timeout_function.asm[7], "<serverSig>");
+    assert_eq!(timeout_function.asm[6], "<serverSig>");
+    assert_eq!(timeout_function.asm[7], "<SERVER_KEY>");
     assert_eq!(timeout_function.asm[8], OP_CHECKSIG);
 }
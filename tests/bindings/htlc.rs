@@ -0,0 +1,91 @@
+// Rust bindings for the HTLC contract, generated by arkadec 0.1.0.
+// Do not edit: regenerate from the artifact.
+
+/// The HTLC contract with its constructor arguments bound.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HTLC {
+    pub sender: [u8; 33],
+    pub receiver: [u8; 33],
+    pub hash: Vec<u8>,
+    pub refund_time: i64,
+    /// Key of the Ark server co-signing cooperative paths
+    pub server_key: [u8; 33],
+}
+
+/// A leaf of HTLC and the witness that spends it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HTLCSpend {
+    /// Leaf script as ASM, with the contract's arguments linked in
+    pub script: Vec<String>,
+    /// Witness stack elements, in witness schema order
+    pub witness: Vec<Vec<u8>>,
+}
+
+#[allow(clippy::too_many_arguments)]
+impl HTLC {
+    /// Spend `together` through the cooperative path.
+    pub fn together(&self, sender_sig: [u8; 64], receiver_sig: [u8; 64], server_sig: [u8; 64]) -> HTLCSpend {
+        HTLCSpend {
+            script: self.link(&["<receiverSig>", "<senderSig>", "<sender>", "OP_CHECKSIG", "<receiver>", "OP_CHECKSIGADD", "OP_2", "OP_NUMEQUALVERIFY", "<serverSig>", "<SERVER_KEY>", "OP_CHECKSIG"]),
+            witness: vec![sender_sig.to_vec(), receiver_sig.to_vec(), server_sig.to_vec()],
+        }
+    }
+
+    /// Spend `together` through the unilateral exit path.
+    pub fn together_exit(&self, sender_sig: [u8; 64], receiver_sig: [u8; 64]) -> HTLCSpend {
+        HTLCSpend {
+            script: self.link(&["<receiverSig>", "<senderSig>", "<sender>", "OP_CHECKSIG", "<receiver>", "OP_CHECKSIGADD", "OP_2", "OP_NUMEQUAL", "144", "OP_CHECKSEQUENCEVERIFY", "OP_DROP"]),
+            witness: vec![sender_sig.to_vec(), receiver_sig.to_vec()],
+        }
+    }
+
+    /// Spend `refund` through the cooperative path.
+    pub fn refund(&self, sender_sig: [u8; 64], server_sig: [u8; 64]) -> HTLCSpend {
+        HTLCSpend {
+            script: self.link(&["<senderSig>", "<sender>", "OP_CHECKSIGVERIFY", "<refundTime>", "OP_CHECKLOCKTIMEVERIFY", "OP_DROP", "<serverSig>", "<SERVER_KEY>", "OP_CHECKSIG"]),
+            witness: vec![sender_sig.to_vec(), server_sig.to_vec()],
+        }
+    }
+
+    /// Spend `refund` through the unilateral exit path.
+    pub fn refund_exit(&self, sender_sig: [u8; 64]) -> HTLCSpend {
+        HTLCSpend {
+            script: self.link(&["<senderSig>", "<sender>", "OP_CHECKSIG", "<refundTime>", "OP_CHECKLOCKTIMEVERIFY", "OP_DROP", "144", "OP_CHECKSEQUENCEVERIFY", "OP_DROP"]),
+            witness: vec![sender_sig.to_vec()],
+        }
+    }
+
+    /// Spend `claim` through the cooperative path.
+    pub fn claim(&self, receiver_sig: [u8; 64], preimage: &[u8], server_sig: [u8; 64]) -> HTLCSpend {
+        HTLCSpend {
+            script: self.link(&["<receiverSig>", "<receiver>", "OP_CHECKSIGVERIFY", "<preimage>", "OP_SHA256", "<hash>", "OP_EQUALVERIFY", "<serverSig>", "<SERVER_KEY>", "OP_CHECKSIG"]),
+            witness: vec![receiver_sig.to_vec(), preimage.to_vec(), server_sig.to_vec()],
+        }
+    }
+
+    /// Spend `claim` through the unilateral exit path.
+    pub fn claim_exit(&self, receiver_sig: [u8; 64], preimage: &[u8]) -> HTLCSpend {
+        HTLCSpend {
+            script: self.link(&["<receiverSig>", "<receiver>", "OP_CHECKSIGVERIFY", "<preimage>", "OP_SHA256", "<hash>", "OP_EQUAL", "144", "OP_CHECKSEQUENCEVERIFY", "OP_DROP"]),
+            witness: vec![receiver_sig.to_vec(), preimage.to_vec()],
+        }
+    }
+
+    fn link(&self, asm: &[&str]) -> Vec<String> {
+        asm.iter()
+            .map(|token| match *token {
+                "<sender>" => Self::hex(&self.sender),
+                "<receiver>" => Self::hex(&self.receiver),
+                "<hash>" => Self::hex(&self.hash),
+                "<refundTime>" => self.refund_time.to_string(),
+                "<SERVER_KEY>" => Self::hex(&self.server_key),
+                _ => token.to_string(),
+            })
+            .collect()
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
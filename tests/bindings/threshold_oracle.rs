@@ -0,0 +1,82 @@
+// Rust bindings for the ThresholdOracle contract, generated by arkadec 0.1.0.
+// Do not edit: regenerate from the artifact.
+
+/// The ThresholdOracle contract with its constructor arguments bound.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThresholdOracle {
+    pub token_asset_id_txid: [u8; 32],
+    pub token_asset_id_gidx: i64,
+    pub ctrl_asset_id_txid: [u8; 32],
+    pub ctrl_asset_id_gidx: i64,
+    pub oracles_0: [u8; 33],
+    pub oracles_1: [u8; 33],
+    pub oracles_2: [u8; 33],
+    pub threshold: i64,
+    /// Key of the Ark server co-signing cooperative paths
+    pub server_key: [u8; 33],
+}
+
+/// A leaf of ThresholdOracle and the witness that spends it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThresholdOracleSpend {
+    /// Leaf script as ASM, with the contract's arguments linked in
+    pub script: Vec<String>,
+    /// Witness stack elements, in witness schema order
+    pub witness: Vec<Vec<u8>>,
+}
+
+#[allow(clippy::too_many_arguments)]
+impl ThresholdOracle {
+    /// Spend `attest` through the cooperative path.
+    pub fn attest(&self, amount: i64, message_hash: [u8; 32], oracle_sigs_0: [u8; 64], oracle_sigs_1: [u8; 64], oracle_sigs_2: [u8; 64], server_sig: [u8; 64], recipient_pk: [u8; 33]) -> ThresholdOracleSpend {
+        ThresholdOracleSpend {
+            script: self.link(&["OP_DUP", "0", "OP_GREATERTHAN", "OP_VERIFY", "0", "3", "OP_ROLL", "3", "OP_PICK", "<oracles_0>", "OP_CHECKSIGFROMSTACK", "OP_IF", "OP_SCRIPTNUMTOLE64", "0x0100000000000000", "OP_ADD64", "OP_VERIFY", "OP_LE64TOSCRIPTNUM", "OP_ENDIF", "3", "OP_ROLL", "3", "OP_PICK", "<oracles_1>", "OP_CHECKSIGFROMSTACK", "OP_IF", "OP_SCRIPTNUMTOLE64", "0x0100000000000000", "OP_ADD64", "OP_VERIFY", "OP_LE64TOSCRIPTNUM", "OP_ENDIF", "3", "OP_ROLL", "3", "OP_ROLL", "<oracles_2>", "OP_CHECKSIGFROMSTACK", "OP_IF", "OP_SCRIPTNUMTOLE64", "0x0100000000000000", "OP_ADD64", "OP_VERIFY", "OP_LE64TOSCRIPTNUM", "OP_ENDIF", "<threshold>", "OP_GREATERTHANOREQUAL", "OP_VERIFY", "0", "<ctrlAssetId_txid>", "<ctrlAssetId_gidx>", "OP_INSPECTINASSETLOOKUP", "OP_DUP", "OP_1NEGATE", "OP_EQUAL", "OP_NOT", "OP_VERIFY", "0x0000000000000000", "OP_GREATERTHAN64", "OP_VERIFY", "1", "<tokenAssetId_txid>", "<tokenAssetId_gidx>", "OP_INSPECTOUTASSETLOOKUP", "OP_DUP", "OP_1NEGATE", "OP_EQUAL", "OP_NOT", "OP_VERIFY", "OP_SWAP", "OP_SCRIPTNUMTOLE64", "OP_GREATERTHANOREQUAL64", "OP_VERIFY", "1", "OP_INSPECTOUTPUTSCRIPTPUBKEY", "<VTXO:SingleSig(<recipientPk>)>", "OP_EQUALVERIFY", "0", "OP_INSPECTOUTPUTSCRIPTPUBKEY", "OP_PUSHCURRENTINPUTINDEX", "OP_INSPECTINPUTSCRIPTPUBKEY", "OP_EQUALVERIFY", "<serverSig>", "<SERVER_KEY>", "OP_CHECKSIG"]),
+            witness: vec![Self::script_num(amount), message_hash.to_vec(), oracle_sigs_0.to_vec(), oracle_sigs_1.to_vec(), oracle_sigs_2.to_vec(), server_sig.to_vec(), recipient_pk.to_vec()],
+        }
+    }
+
+    /// Spend `attest` through the unilateral exit path.
+    pub fn attest_exit(&self, recipient_pk: [u8; 33], recipient_pk_sig: [u8; 64]) -> ThresholdOracleSpend {
+        ThresholdOracleSpend {
+            script: self.link(&["<recipientPkSig>", "<recipientPk>", "OP_CHECKSIG", "288", "OP_CHECKSEQUENCEVERIFY", "OP_DROP"]),
+            witness: vec![recipient_pk.to_vec(), recipient_pk_sig.to_vec()],
+        }
+    }
+
+    fn link(&self, asm: &[&str]) -> Vec<String> {
+        asm.iter()
+            .map(|token| match *token {
+                "<tokenAssetId_txid>" => Self::hex(&self.token_asset_id_txid),
+                "<tokenAssetId_gidx>" => self.token_asset_id_gidx.to_string(),
+                "<ctrlAssetId_txid>" => Self::hex(&self.ctrl_asset_id_txid),
+                "<ctrlAssetId_gidx>" => self.ctrl_asset_id_gidx.to_string(),
+                "<oracles_0>" => Self::hex(&self.oracles_0),
+                "<oracles_1>" => Self::hex(&self.oracles_1),
+                "<oracles_2>" => Self::hex(&self.oracles_2),
+                "<threshold>" => self.threshold.to_string(),
+                "<SERVER_KEY>" => Self::hex(&self.server_key),
+                _ => token.to_string(),
+            })
+            .collect()
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn script_num(n: i64) -> Vec<u8> {
+        let mut abs = n.unsigned_abs();
+        let mut bytes = Vec::new();
+        while abs > 0 {
+            bytes.push((abs & 0xff) as u8);
+            abs >>= 8;
+        }
+        match bytes.last_mut() {
+            Some(last) if *last & 0x80 != 0 => bytes.push(if n < 0 { 0x80 } else { 0 }),
+            Some(last) if n < 0 => *last |= 0x80,
+            _ => {}
+        }
+        bytes
+    }
+}
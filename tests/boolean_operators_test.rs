@@ -0,0 +1,90 @@
+use arkade_compiler::compile;
+use arkade_compiler::models::{Expression, Requirement, Statement};
+use arkade_compiler::parser::parse;
+use arkade_compiler::printer::print;
+
+const CODE: &str = r#"
+contract Escape(pubkey owner, pubkey backup, int amount, bytes script) {
+  function spend(signature ownerSig, signature backupSig, int nonce) {
+    require(nonce == amount && checkSig(ownerSig, owner) || checkSig(backupSig, backup));
+    require(tx.outputs[0].value >= amount && (nonce > 2 || nonce < 0), "bad output");
+  }
+}
+"#;
+
+fn asm(code: &str) -> String {
+    let artifact = compile(code).unwrap();
+    let function = artifact
+        .functions
+        .iter()
+        .find(|f| f.server_variant)
+        .unwrap();
+    function.asm.join(" ")
+}
+
+#[test]
+fn test_and_binds_tighter_than_or() {
+    let contract = parse(CODE).unwrap();
+    let Statement::Require(Requirement::Comparison { left, op, right }, _) =
+        &contract.functions[0].statements[0]
+    else {
+        panic!("expected a comparison");
+    };
+    assert_eq!(op, "||");
+    assert!(matches!(left, Expression::BinaryOp { op, .. } if op == "&&"));
+    assert!(matches!(right, Expression::CheckSigExpr { .. }));
+}
+
+#[test]
+fn test_conditions_combine_with_booland_and_boolor() {
+    let asm = asm(CODE);
+    // The nonce is copied for the reads after it, and each signature
+    // rolled up from under it
+    assert!(asm.starts_with(
+        "OP_DUP <amount> OP_EQUAL OP_ROT <owner> OP_CHECKSIG OP_BOOLAND \
+         OP_ROT <backup> OP_CHECKSIG OP_BOOLOR"
+    ));
+    // The 64-bit comparison keeps its result for OP_BOOLAND
+    assert!(asm.contains(
+        "0 OP_INSPECTOUTPUTVALUE <amount> OP_SCRIPTNUMTOLE64 OP_GREATERTHANOREQUAL64 \
+         OP_OVER 2 OP_GREATERTHAN OP_ROT 0 OP_LESSTHAN OP_BOOLOR OP_BOOLAND"
+    ));
+}
+
+#[test]
+fn test_printed_conditions_keep_their_grouping() {
+    let contract = parse(CODE).unwrap();
+    let printed = print(&contract);
+    assert!(printed.contains(
+        "require(nonce == amount && checkSig(ownerSig, owner) || checkSig(backupSig, backup));"
+    ));
+    assert!(printed.contains("require(tx.outputs[0].value >= amount && (nonce > 2 || nonce < 0));"));
+    assert_eq!(asm(&printed), asm(CODE));
+}
+
+#[test]
+fn test_negation_compiles_to_op_not() {
+    let code = r#"
+contract Fresh(pubkey owner, bytes script, int amount) {
+  function spend(signature ownerSig, int spent) {
+    require(!spent);
+    require(tx.outputs[0].scriptPubKey != script && !(tx.outputs[1].value >= amount));
+    require(!(spent == 1 || spent == 2));
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let script = asm(code);
+    assert!(script.starts_with("OP_DUP OP_NOT "));
+    assert!(script.contains(
+        "0 OP_INSPECTOUTPUTSCRIPTPUBKEY <script> OP_EQUAL OP_NOT \
+         1 OP_INSPECTOUTPUTVALUE <amount> OP_SCRIPTNUMTOLE64 OP_GREATERTHANOREQUAL64 OP_NOT \
+         OP_BOOLAND"
+    ));
+    assert!(script.contains("OP_DUP 1 OP_EQUAL OP_SWAP 2 OP_EQUAL OP_BOOLOR OP_NOT"));
+
+    let printed = print(&parse(code).unwrap());
+    assert!(printed.contains("require(!spent);"));
+    assert!(printed.contains("require(!(spent == 1 || spent == 2));"));
+    assert_eq!(asm(&printed), script);
+}
@@ -0,0 +1,143 @@
+use arkade_compiler::catalog::{self, EntryKind, CATALOG};
+use arkade_compiler::constants::CONSTANTS;
+use arkade_compiler::parser::{
+    parse_for_target, Extension, ScriptTarget, BUILTINS, KEYWORDS, OPTIONS,
+};
+
+const GRAMMAR: [&str; 5] = [
+    include_str!("../src/parser/grammar.pest"),
+    include_str!("../src/parser/assets.pest"),
+    include_str!("../src/parser/introspection.pest"),
+    include_str!("../src/parser/loops.pest"),
+    include_str!("../src/parser/opcodes.pest"),
+];
+
+fn names(kind: EntryKind) -> Vec<&'static str> {
+    catalog::of_kind(kind).map(|entry| entry.name).collect()
+}
+
+#[test]
+fn test_names_are_unique_and_documented() {
+    for (index, entry) in CATALOG.iter().enumerate() {
+        assert!(!entry.doc.is_empty() && !entry.signature.is_empty());
+        assert!(
+            CATALOG[..index]
+                .iter()
+                .all(|other| other.name != entry.name || other.kind != entry.kind),
+            "{} is listed twice",
+            entry.name
+        );
+    }
+}
+
+#[test]
+fn test_builtins_match_the_tokenizer() {
+    let mut builtins = names(EntryKind::Builtin);
+    builtins.extend(["tx", "this"]);
+    builtins.sort();
+    let mut expected = BUILTINS.to_vec();
+    expected.sort();
+    assert_eq!(builtins, expected);
+    assert!(builtins.iter().all(|name| !KEYWORDS.contains(name)));
+}
+
+#[test]
+fn test_constants_match_the_compiler() {
+    assert_eq!(names(EntryKind::Constant), CONSTANTS.to_vec());
+}
+
+#[test]
+fn test_group_properties_match_the_grammar() {
+    let grammar = GRAMMAR.concat();
+    let rule = grammar
+        .lines()
+        .skip_while(|line| !line.starts_with("group_property = "))
+        .nth(1)
+        .unwrap();
+    let mut properties: Vec<&str> = rule.split('"').skip(1).step_by(2).collect();
+    let mut listed = names(EntryKind::GroupProperty);
+    properties.sort();
+    listed.sort();
+    assert_eq!(listed, properties);
+}
+
+/// A contract using `expression`, with `int` parameters for the indices.
+fn contract_using(expression: &str) -> String {
+    format!(
+        "contract C(int i, int o, int t, int k, bytes32 assetId, int x) {{\n  \
+           function f() {{ require({} == x); }}\n}}",
+        expression
+    )
+}
+
+#[test]
+fn test_tx_properties_parse_on_their_targets() {
+    for entry in catalog::of_kind(EntryKind::TxProperty) {
+        // Complete calls and asset groups, so the property is an expression
+        let expression = match entry.name {
+            "tx.assetGroups[k]" => "tx.assetGroups[k].sumInputs".to_string(),
+            name if name.ends_with(".lookup") || name.ends_with(".find") => {
+                format!("{}(assetId)", name)
+            }
+            name => name.to_string(),
+        };
+        for target in ScriptTarget::ALL {
+            let result = parse_for_target(&contract_using(&expression), target);
+            assert_eq!(
+                result.is_ok(),
+                entry.available_on(target),
+                "{} on {}: {:?}",
+                entry.name,
+                target,
+                result.err()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_options_match_the_parser() {
+    let mut catalogued = names(EntryKind::Option);
+    let mut accepted = OPTIONS.to_vec();
+    catalogued.sort_unstable();
+    accepted.sort_unstable();
+    assert_eq!(catalogued, accepted);
+}
+
+#[test]
+fn test_options_are_accepted() {
+    for name in names(EntryKind::Option) {
+        let value = match name {
+            "strictHashlocks" => "true",
+            "internalKey" => "nums",
+            "server" => "server",
+            _ => "144",
+        };
+        let source = format!(
+            "options {{ {} = {}; }}\ncontract C(pubkey owner) {{\n  \
+               function f(signature s) {{ require(checkSig(s, owner)); }}\n}}",
+            name, value
+        );
+        arkade_compiler::compile(&source).unwrap_or_else(|e| panic!("{}: {}", name, e));
+    }
+}
+
+#[test]
+fn test_availability_per_target() {
+    let sig = catalog::find("checkSigFromStack").unwrap();
+    assert_eq!(sig.extension, Some(Extension::Opcodes));
+    assert_eq!(sig.targets(), vec![ScriptTarget::Arkade]);
+    assert_eq!(
+        catalog::find("tx.time").unwrap().targets(),
+        ScriptTarget::ALL
+    );
+    assert!(catalog::available_on(ScriptTarget::Tapscript).all(|entry| entry.extension.is_none()));
+
+    let json = serde_json::to_value(catalog::find("tx.weight").unwrap()).unwrap();
+    assert_eq!(json["kind"], "txProperty");
+    assert_eq!(json["extension"], "introspection");
+    assert_eq!(json["targets"], serde_json::json!(["arkade"]));
+    let json = serde_json::to_value(catalog::find("checkSig").unwrap()).unwrap();
+    assert!(json.get("extension").is_none());
+    assert_eq!(json["targets"], serde_json::json!(["arkade", "tapscript"]));
+}
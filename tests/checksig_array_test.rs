@@ -0,0 +1,71 @@
+use arkade_compiler::compile;
+
+const COMMITTEE: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Committee(pubkey[3] members, pubkey chair) {
+  function chairAndFirst(signature chairSig, signature firstSig) {
+    require(checkSig(chairSig, chair));
+    require(checkSig(firstSig, members[0]));
+  }
+
+  function all(signature[3] sigs) {
+    for (i, sig) in sigs {
+      require(checkSig(sig, members[i]));
+    }
+  }
+
+  function any(signature sig, int which) {
+    if (checkSig(sig, members[2])) {
+      require(which == 2);
+    }
+  }
+}
+"#;
+
+fn asm(name: &str) -> Vec<String> {
+    let output = compile(COMMITTEE).unwrap();
+    output
+        .functions
+        .iter()
+        .find(|f| f.name == name && f.server_variant)
+        .unwrap()
+        .asm
+        .clone()
+}
+
+#[test]
+fn test_checksig_constant_index() {
+    let asm = asm("chairAndFirst");
+    assert!(asm.contains(&"<members_0>".to_string()), "{:?}", asm);
+    assert!(!asm.iter().any(|t| t.contains('[')));
+}
+
+#[test]
+fn test_checksig_loop_index() {
+    let asm = asm("all");
+    for k in 0..3 {
+        assert!(asm.contains(&format!("<members_{}>", k)), "{:?}", asm);
+        assert!(asm.contains(&format!("<sigs_{}>", k)), "{:?}", asm);
+    }
+}
+
+#[test]
+fn test_checksig_expression_with_element() {
+    let asm = asm("any");
+    assert!(asm.contains(&"<members_2>".to_string()), "{:?}", asm);
+}
+
+#[test]
+fn test_checksig_element_type_is_checked() {
+    let code = COMMITTEE.replace("pubkey[3] members", "bytes32[3] members");
+    let err = compile(&code).unwrap_err().to_string();
+    assert!(
+        err.contains("checkSig() arg 2 'members_0' has type 'bytes32'"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "std")]
+
+use arkade_compiler::compile;
+use arkade_compiler::interpreter::{LeafEvaluator, TxContext, TxInput};
+use arkade_compiler::linker::SERVER_KEY;
+use k256::schnorr::SigningKey;
+
+const QUORUM: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Quorum(pubkey alice, pubkey bob, pubkey carol) {
+  function spend(signature aliceSig, signature bobSig, signature carolSig) {
+    let n = checkSigCount([alice, bob, carol]);
+    require(n >= 2);
+  }
+}
+"#;
+
+const SIGHASH: [u8; 32] = [0x07; 32];
+
+fn signer(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32]).unwrap()
+}
+
+fn pubkey(seed: u8) -> String {
+    format!("02{}", hex::encode(signer(seed).verifying_key().to_bytes()))
+}
+
+/// A signature by `seed`, or an empty one for seed 0
+fn sign(seed: u8) -> String {
+    match seed {
+        0 => String::new(),
+        _ => hex::encode(
+            signer(seed)
+                .sign_raw(&SIGHASH, &[0u8; 32])
+                .unwrap()
+                .to_bytes(),
+        ),
+    }
+}
+
+#[test]
+fn test_checksig_count_counts_the_valid_signatures() {
+    let output = compile(QUORUM).unwrap();
+    let tx = TxContext::builder()
+        .sighash(SIGHASH)
+        .input(TxInput::new(10_000, vec![0x51, 0x20]))
+        .build()
+        .unwrap();
+    let spend = |[alice, bob, carol]: [u8; 3]| {
+        LeafEvaluator::for_function(&output, "spend", true)
+            .unwrap()
+            .params([
+                ("alice", pubkey(1)),
+                ("bob", pubkey(2)),
+                ("carol", pubkey(3)),
+                (SERVER_KEY, pubkey(4)),
+            ])
+            .args([
+                ("aliceSig", sign(alice)),
+                ("bobSig", sign(bob)),
+                ("carolSig", sign(carol)),
+                ("serverSig", sign(4)),
+            ])
+            .run(&tx)
+    };
+    assert_eq!(spend([1, 2, 3]), Ok(()));
+    assert_eq!(spend([1, 0, 3]), Ok(()));
+    assert_eq!(spend([0, 2, 3]), Ok(()));
+    let err = spend([1, 0, 0]).unwrap_err();
+    assert!(err.contains("OP_VERIFY"), "{}", err);
+}
+
+#[test]
+fn test_checksig_count_is_an_int() {
+    let output = compile(QUORUM).unwrap();
+    // The count compares as an int, and its signatures are consumed by
+    // position like checkMultisig's, so none is reported unbound
+    assert!(output.warnings.is_empty(), "{:?}", output.warnings);
+}
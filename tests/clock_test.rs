@@ -0,0 +1,106 @@
+#![cfg(feature = "std")]
+
+use arkade_compiler::{compile, compile_with_options, Clock, CompileOptions};
+
+const CODE: &str = r#"
+contract Single(pubkey owner) {
+  function spend(signature ownerSig) {
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+
+fn with_clock(clock: Clock) -> CompileOptions {
+    CompileOptions {
+        clock,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_fixed_clock_makes_builds_reproducible() {
+    let options = with_clock(Clock::Fixed("2024-01-01T00:00:00+00:00".to_string()));
+    let first = compile_with_options(CODE, &options).unwrap();
+    let second = compile_with_options(CODE, &options).unwrap();
+
+    assert_eq!(
+        first.updated_at.as_deref(),
+        Some("2024-01-01T00:00:00+00:00")
+    );
+    assert_eq!(
+        serde_json::to_string(&first).unwrap(),
+        serde_json::to_string(&second).unwrap()
+    );
+}
+
+#[test]
+fn test_no_clock_omits_updated_at() {
+    let output = compile_with_options(CODE, &with_clock(Clock::None)).unwrap();
+    let json = serde_json::to_value(&output).unwrap();
+
+    assert!(output.updated_at.is_none());
+    assert!(json.get("updatedAt").is_none());
+}
+
+#[test]
+fn test_default_clock_is_the_system_clock() {
+    assert_eq!(CompileOptions::default().clock, Clock::System);
+    let output = compile(CODE).unwrap();
+    let updated_at = output.updated_at.expect("timestamp");
+    assert!(chrono::DateTime::parse_from_rfc3339(&updated_at).is_ok());
+}
+
+// The only test that sets SOURCE_DATE_EPOCH, so that none reads it while
+// another changes it
+#[test]
+fn test_reproducible_builds_follow_source_date_epoch() {
+    let options = CompileOptions {
+        reproducible: true,
+        clock: Clock::Fixed("2024-01-01T00:00:00+00:00".to_string()),
+        ..Default::default()
+    };
+
+    std::env::remove_var("SOURCE_DATE_EPOCH");
+    assert!(compile_with_options(CODE, &options)
+        .unwrap()
+        .updated_at
+        .is_none());
+
+    std::env::set_var("SOURCE_DATE_EPOCH", "1700000000");
+    let first = compile_with_options(CODE, &options).unwrap();
+    assert_eq!(
+        first.updated_at.as_deref(),
+        Some("2023-11-14T22:13:20+00:00")
+    );
+    assert_eq!(Clock::SourceDateEpoch.timestamp(), first.updated_at.clone());
+
+    std::env::set_var("SOURCE_DATE_EPOCH", "yesterday");
+    assert_eq!(Clock::SourceDateEpoch.timestamp(), None);
+    std::env::remove_var("SOURCE_DATE_EPOCH");
+}
+
+#[test]
+fn test_reproducible_json_sorts_keys() {
+    let options = with_clock(Clock::None);
+    let json = compile_with_options(CODE, &options)
+        .unwrap()
+        .to_reproducible_json()
+        .unwrap();
+    assert_eq!(
+        json,
+        compile_with_options(CODE, &options)
+            .unwrap()
+            .to_reproducible_json()
+            .unwrap()
+    );
+
+    let keys: Vec<&str> = json
+        .lines()
+        .filter(|line| line.starts_with("  \""))
+        .map(|line| line.trim().split('"').nth(1).unwrap())
+        .collect();
+    let mut sorted = keys.clone();
+    sorted.sort();
+    assert_eq!(keys, sorted);
+    assert!(keys.contains(&"functions") && keys.contains(&"contractName"));
+}
@@ -0,0 +1,71 @@
+use arkade_compiler::compiler::compile;
+use arkade_compiler::CompileError;
+
+const MISSPELT: &str = r#"contract Vault(pubkey owner) {
+  function spend(signature ownerSig) {
+    require(checkSig(ownerSig, ownr));
+  }
+}
+"#;
+
+#[test]
+fn test_compile_errors_carry_code_span_and_snippet() {
+    let err = compile(MISSPELT).unwrap_err();
+    assert!(matches!(err, CompileError::Compile(_)));
+    assert_eq!(err.code(), "E0001");
+    assert_eq!(
+        err.to_string(),
+        "fn spend: undeclared variable 'ownr' at 3:5; did you mean 'owner'?"
+    );
+    let span = err.span().unwrap();
+    assert_eq!((span.line, span.column), (3, 5));
+    assert_eq!(&MISSPELT[span.start..span.end], "require");
+    assert_eq!(
+        err.snippet(),
+        Some("    require(checkSig(ownerSig, ownr));")
+    );
+    assert_eq!(
+        err.render(),
+        "error[E0001]: fn spend: undeclared variable 'ownr' at 3:5; did you mean 'owner'?\n \
+         --> 3:5\n  |\n3 |     require(checkSig(ownerSig, ownr));\n  |     ^^^^^^^"
+    );
+}
+
+#[test]
+fn test_parse_errors_are_located() {
+    let source = MISSPELT.replace("ownr));", "owner);");
+    let err = compile(&source).unwrap_err();
+    assert!(matches!(err, CompileError::Parse(_)));
+    assert_eq!(err.code(), "E0201");
+    let span = err.span().unwrap();
+    assert_eq!(span.line, 3);
+    assert!(err.to_string().starts_with("Parse error at 3:"), "{}", err);
+    assert!(err
+        .render()
+        .contains("\n3 |     require(checkSig(ownerSig, owner);\n"));
+}
+
+#[test]
+fn test_unlocated_errors_have_no_excerpt() {
+    let source = "contract Empty() {\n  function spend() {\n    require(tx.time >= 1);\n  }\n}\n";
+    let err = compile(&source.replace("tx.time", "tx.fee")).unwrap_err();
+    assert_eq!(err.code(), "E0008");
+    assert_eq!(err.span(), None);
+    assert_eq!(err.excerpt(), None);
+    assert_eq!(err.render(), format!("error[E0008]: {}", err));
+}
+
+#[test]
+fn test_errors_serialize_with_their_kind() {
+    let err = compile(MISSPELT).unwrap_err();
+    let json = serde_json::to_value(&err).unwrap();
+    assert_eq!(json["kind"], "compile");
+    assert_eq!(json["code"], "E0001");
+    assert_eq!(json["span"]["line"], 3);
+    let back: CompileError = serde_json::from_value(json).unwrap();
+    assert_eq!(back, err);
+
+    // Code that treated the error as a message keeps working
+    let message: String = err.into();
+    assert!(message.starts_with("fn spend: undeclared variable 'ownr'"));
+}
@@ -0,0 +1,100 @@
+#![cfg(feature = "std")]
+
+use std::sync::Arc;
+
+use arkade_compiler::compiler::{compile_with_options, DEFAULT_CACHE_CAPACITY};
+use arkade_compiler::{Clock, CompileOptions, Compiler};
+
+fn single_sig(name: &str) -> String {
+    format!(
+        r#"
+options {{
+  server = server;
+  exit = 144;
+}}
+
+contract {}(pubkey user) {{
+  function spend(signature userSig) {{
+    require(checkSig(userSig, user));
+  }}
+}}
+"#,
+        name
+    )
+}
+
+fn fixed_clock() -> CompileOptions {
+    CompileOptions {
+        clock: Clock::Fixed("2025-01-01T00:00:00Z".to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_compiler_matches_the_free_function() {
+    let compiler = Compiler::new(fixed_clock());
+    let source = single_sig("Single");
+    let expected =
+        serde_json::to_value(compile_with_options(&source, &fixed_clock()).unwrap()).unwrap();
+    for _ in 0..2 {
+        let artifact = compiler.compile(&source).unwrap();
+        assert_eq!(serde_json::to_value(artifact).unwrap(), expected);
+    }
+    assert_eq!(compiler.cached(), 1);
+}
+
+#[test]
+fn test_errors_are_remembered_too() {
+    let compiler = Compiler::default();
+    let source = "contract Broken(";
+    let first = compiler.compile(source).unwrap_err();
+    assert_eq!(compiler.compile(source).unwrap_err(), first);
+    assert_eq!(compiler.cached(), 1);
+}
+
+#[test]
+fn test_remembered_artifacts_are_restamped() {
+    let compiler = Compiler::new(CompileOptions {
+        clock: Clock::System,
+        ..Default::default()
+    });
+    let source = single_sig("Single");
+    let first = compiler.compile(&source).unwrap().updated_at.unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    let second = compiler.compile(&source).unwrap().updated_at.unwrap();
+    assert_ne!(first, second);
+}
+
+#[test]
+fn test_cache_capacity_evicts_the_oldest() {
+    let compiler = Compiler::default().cache_capacity(2);
+    for name in ["A", "B", "C"] {
+        compiler.compile(&single_sig(name)).unwrap();
+    }
+    assert_eq!(compiler.cached(), 2);
+    compiler.clear_cache();
+    assert_eq!(compiler.cached(), 0);
+
+    let uncached = Compiler::default().cache_capacity(0);
+    uncached.compile(&single_sig("A")).unwrap();
+    assert_eq!(uncached.cached(), 0);
+    assert_eq!(DEFAULT_CACHE_CAPACITY, 256);
+}
+
+#[test]
+fn test_compiler_is_shared_between_threads() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Compiler>();
+
+    let compiler = Arc::new(Compiler::new(fixed_clock()));
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let compiler = Arc::clone(&compiler);
+            std::thread::spawn(move || compiler.compile(&single_sig(&format!("C{}", i % 2))))
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap().unwrap();
+    }
+    assert_eq!(compiler.cached(), 2);
+}
@@ -0,0 +1,89 @@
+#![cfg(feature = "std")]
+
+use std::sync::Arc;
+use std::thread;
+
+use arkade_compiler::compiler::{compile_with_options, CompileOptions};
+use arkade_compiler::examples::EXAMPLES;
+use arkade_compiler::{Clock, CompileError, Compiler, ContractJson, ScriptLimits};
+
+/// Stack of a thread spawned without a size
+const DEFAULT_THREAD_STACK: usize = 2 * 1024 * 1024;
+
+fn options() -> CompileOptions {
+    CompileOptions {
+        clock: Clock::Fixed("2025-01-01T00:00:00Z".to_string()),
+        ..Default::default()
+    }
+}
+
+fn outcome(result: Result<ContractJson, CompileError>) -> Result<serde_json::Value, CompileError> {
+    result.map(|artifact| serde_json::to_value(artifact).unwrap())
+}
+
+#[test]
+fn test_compile_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<CompileOptions>();
+    assert_send_sync::<ContractJson>();
+    assert_send_sync::<Compiler>();
+}
+
+#[test]
+fn test_concurrent_compilations_agree_with_sequential_ones() {
+    let expected: Vec<_> = EXAMPLES
+        .iter()
+        .map(|example| outcome(compile_with_options(example.source, &options())))
+        .collect();
+    let expected = Arc::new(expected);
+    let shared = Arc::new(Compiler::new(options()).cache_capacity(4));
+
+    let handles: Vec<_> = (0..8)
+        .map(|worker| {
+            let expected = Arc::clone(&expected);
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || {
+                for round in 0..4 {
+                    // Each worker walks the examples from a different place
+                    for i in 0..EXAMPLES.len() {
+                        let index = (i + worker + round) % EXAMPLES.len();
+                        let source = EXAMPLES[index].source;
+                        let result = if (worker + i) % 2 == 0 {
+                            compile_with_options(source, &options())
+                        } else {
+                            shared.compile(source)
+                        };
+                        assert_eq!(outcome(result), expected[index], "{}", EXAMPLES[index].name);
+                    }
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+fn test_deepest_source_the_limits_allow_compiles_on_a_worker_thread() {
+    let nesting = 30;
+    let operators = 128;
+    let sum = vec!["b"; operators + 1].join(" + ");
+    let source = format!(
+        "contract Deep(int a) {{\n  function spend(int b) {{\n    int x = {}{}{};\n    \
+         require(x > a);\n  }}\n}}\n",
+        "(".repeat(nesting),
+        sum,
+        ")".repeat(nesting)
+    );
+    // The leaf is far past the script limits, which are checked last
+    let options = CompileOptions {
+        script_limits: ScriptLimits::NONE,
+        ..options()
+    };
+    let handle = thread::Builder::new()
+        .stack_size(DEFAULT_THREAD_STACK)
+        .spawn(move || compile_with_options(&source, &options).map(|_| ()))
+        .unwrap();
+    handle.join().unwrap().unwrap();
+}
@@ -0,0 +1,117 @@
+use arkade_compiler::compiler::compile;
+use arkade_compiler::diagnostics::classify;
+use arkade_compiler::parser::parse;
+use arkade_compiler::printer::print;
+
+const PAYOUT: &str = r#"
+options {
+  server = server;
+  exit = EXIT_DELAY;
+}
+
+contract Payout(pubkey owner) {
+  const DUST = 546;
+  const EXIT_DELAY = 144;
+
+  function pay(signature sig) {
+    require(tx.outputs[0].value >= DUST);
+    require(checkSig(sig, owner));
+  }
+
+  function sweep(signature sig) {
+    int change = tx.outputs[1].value - DUST;
+    require(change >= DUST);
+    require(checkSig(sig, owner));
+  }
+}
+"#;
+
+#[test]
+fn test_constants_are_parsed() {
+    let contract = parse(PAYOUT).unwrap();
+    assert_eq!(contract.constants.get("DUST"), Some(&546));
+    assert_eq!(contract.constants.get("EXIT_DELAY"), Some(&144));
+    let printed = print(&contract);
+    assert!(printed.contains("  const DUST = 546;\n  const EXIT_DELAY = 144;\n\n"));
+    assert_eq!(print(&parse(&printed).unwrap()), printed);
+}
+
+#[test]
+fn test_constants_are_substituted() {
+    let literal = PAYOUT
+        .replace("  const DUST = 546;\n  const EXIT_DELAY = 144;\n\n", "")
+        .replace("EXIT_DELAY", "144")
+        .replace("DUST", "546");
+    let with_constants = compile(PAYOUT).unwrap();
+    let with_literals = compile(&literal).unwrap();
+    for (a, b) in with_constants
+        .functions
+        .iter()
+        .zip(&with_literals.functions)
+    {
+        assert_eq!(a.asm, b.asm);
+    }
+    assert!(with_constants
+        .functions
+        .iter()
+        .flat_map(|f| &f.asm)
+        .all(|token| !token.contains("DUST")));
+}
+
+#[test]
+fn test_constants_can_be_used_in_macros_before_their_declaration() {
+    let source = r#"
+contract Payout(pubkey owner) {
+  macro aboveDust(index) { require(tx.outputs[index].value >= DUST); }
+
+  function pay(signature sig) {
+    aboveDust(0);
+    require(checkSig(sig, owner));
+  }
+
+  const DUST = 546;
+}
+"#;
+    compile(source).unwrap();
+}
+
+#[test]
+fn test_parameters_shadow_constants() {
+    let source = r#"
+contract Payout(pubkey owner, int DUST) {
+  const DUST = 546;
+
+  function pay(signature sig) {
+    require(tx.outputs[0].value >= DUST);
+    require(checkSig(sig, owner));
+  }
+}
+"#;
+    let artifact = compile(source).unwrap();
+    assert!(artifact.functions[0].asm.contains(&"<DUST>".to_string()));
+}
+
+#[test]
+fn test_invalid_declarations_are_errors() {
+    for (declarations, message, code) in [
+        (
+            "const DUST = 546;\n  const DUST = 330;",
+            "Constant 'DUST' is declared twice",
+            "E0002",
+        ),
+        (
+            "const DUST_LIMIT = 546;",
+            "Constant 'DUST_LIMIT' is already a built-in constant",
+            "E0002",
+        ),
+    ] {
+        let source = format!(
+            "contract Payout(pubkey owner) {{\n  {}\n  function pay(signature sig) {{\n    \
+             require(checkSig(sig, owner));\n  }}\n}}\n",
+            declarations
+        );
+        let err = compile(&source).unwrap_err().to_string();
+        assert!(err.contains(message), "{}", err);
+        assert_eq!(classify("compile", &err), code);
+    }
+}
@@ -142,8 +142,8 @@ contract RecursiveVtxo(pubkey ownerPk) {
 
     // The comparison operator must be present
     assert!(
-        send_coop.asm.iter().any(|op| op == "OP_EQUAL"),
-        "Missing OP_EQUAL in {:?}",
+        send_coop.asm.iter().any(|op| op == "OP_EQUALVERIFY"),
+        "Missing OP_EQUALVERIFY in {:?}",
         send_coop.asm
     );
 }
@@ -267,7 +267,7 @@ contract RecursiveVtxo(pubkey ownerPk) {
 fn test_cooperative_path_asm_order() {
     // Verify exact cooperative ASM (only the user's require statement + server sig):
     //   0 OP_INSPECTOUTPUTSCRIPTPUBKEY <VTXO:SingleSig(<ownerPk>)> OP_EQUAL
-    //   <SERVER_KEY> <serverSig> OP_CHECKSIG
+    //   <serverSig> <SERVER_KEY> OP_CHECKSIG
     let code = r#"
 import "single_sig.ark";
 
@@ -294,9 +294,9 @@ contract RecursiveVtxo(pubkey ownerPk) {
         "0",
         "OP_INSPECTOUTPUTSCRIPTPUBKEY",
         "<VTXO:SingleSig(<ownerPk>)>",
-        "OP_EQUAL",
-        "<SERVER_KEY>",
+        "OP_EQUALVERIFY",
         "<serverSig>",
+        "<SERVER_KEY>",
         "OP_CHECKSIG",
     ];
 
@@ -313,7 +313,7 @@ fn test_exit_path_asm_order() {
     // Verify exact exit ASM: N-of-N CHECKSIG chain + timelock.
     // ContractInstance uses non-Bitcoin-Script opcodes, so exit path falls
     // back to pure Bitcoin Script (no introspection opcodes allowed).
-    //   <ownerPk> <ownerPkSig> OP_CHECKSIG
+    //   <ownerPkSig> <ownerPk> OP_CHECKSIG
     //   144 OP_CHECKSEQUENCEVERIFY OP_DROP
     let code = r#"
 import "single_sig.ark";
@@ -338,8 +338,8 @@ contract RecursiveVtxo(pubkey ownerPk) {
         .expect("No exit send function");
 
     let expected: &[&str] = &[
-        "<ownerPk>",
         "<ownerPkSig>",
+        "<ownerPk>",
         "OP_CHECKSIG",
         "144",
         "OP_CHECKSEQUENCEVERIFY",
@@ -864,12 +864,18 @@ contract SelfRef(pubkey ownerPk) {
         .find(|f| f.name == "renew" && f.server_variant)
         .expect("No cooperative renew function");
 
-    assert!(
-        renew_coop
-            .asm
-            .iter()
-            .any(|op| op.contains("VTXO:SelfRef(<ownerPk>)")),
-        "Missing VTXO:SelfRef(<ownerPk>) in {:?}",
+    // Its own scriptPubKey is the current input's
+    assert_eq!(
+        renew_coop.asm[..5],
+        [
+            "0",
+            "OP_INSPECTOUTPUTSCRIPTPUBKEY",
+            "OP_PUSHCURRENTINPUTINDEX",
+            "OP_INSPECTINPUTSCRIPTPUBKEY",
+            "OP_EQUALVERIFY"
+        ],
+        "{:?}",
         renew_coop.asm
     );
+    assert!(renew_coop.asm.iter().all(|op| !op.contains("VTXO:")));
 }
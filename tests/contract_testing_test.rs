@@ -0,0 +1,290 @@
+#![cfg(feature = "std")]
+
+use arkade_compiler::testing::{self, TestSuite};
+
+const HTLC: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract HTLC(pubkey sender, pubkey receiver, bytes hash, int refundTime) {
+  function refund(signature senderSig) {
+    require(checkSig(senderSig, sender));
+    require(tx.time >= refundTime);
+  }
+
+  function claim(signature receiverSig, bytes preimage) {
+    require(checkSig(receiverSig, receiver));
+    require(sha256(preimage) == hash);
+  }
+}
+"#;
+
+fn suite(json: &str) -> TestSuite {
+    serde_json::from_str(json).unwrap()
+}
+
+#[test]
+fn test_cases_pass_and_fail_as_expected() {
+    let suite = suite(
+        r#"{
+  "constructorInputs": { "refundTime": 800000 },
+  "cases": [
+    { "name": "claim", "function": "claim", "expect": "pass" },
+    { "name": "wrong preimage", "function": "claim", "witness": { "preimage": "00" }, "expect": "fail" },
+    { "name": "forged signature", "function": "claim", "witness": { "receiverSig": "" }, "expect": "fail" },
+    { "name": "refund", "function": "refund", "tx": { "locktime": 800001 }, "expect": "pass" }
+  ]
+}"#,
+    );
+    let report = testing::run(HTLC, &suite).unwrap();
+
+    assert_eq!(report.failures(), 0, "{:?}", report.results);
+    assert_eq!(
+        report.results[1].message.as_deref(),
+        Some("require at 15:5 failed: condition does not hold")
+    );
+    assert_eq!(
+        report.results[2].message.as_deref(),
+        Some("require at 14:5 failed: `receiverSig` is not a valid signature by `receiver`")
+    );
+}
+
+#[test]
+fn test_requirement_coverage() {
+    let suite = suite(
+        r#"{
+  "cases": [
+    { "name": "claim", "function": "claim", "expect": "pass" },
+    { "name": "wrong preimage", "function": "claim", "witness": { "preimage": "00" }, "expect": "fail" }
+  ]
+}"#,
+    );
+    let report = testing::run(HTLC, &suite).unwrap();
+    let coverage = &report.coverage;
+
+    assert_eq!(coverage.requirements_exercised(), (2, 4));
+    let claim = &coverage.functions[1];
+    assert_eq!(
+        claim.requirements[0].source,
+        "require(checkSig(receiverSig, receiver));"
+    );
+    assert_eq!(
+        (claim.requirements[0].held, claim.requirements[0].failed),
+        (2, 0)
+    );
+    assert_eq!(
+        (claim.requirements[1].held, claim.requirements[1].failed),
+        (1, 1)
+    );
+
+    let text = coverage.to_string();
+    assert!(
+        text.contains("coverage: 2/4 requirements exercised"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("10:5  require(tx.time >= refundTime);  never exercised"),
+        "{}",
+        text
+    );
+}
+
+#[test]
+fn test_branch_coverage() {
+    let code = r#"
+contract Paths(pubkey alice, pubkey bob) {
+  function spend(bool useAlice, signature sig) {
+    if (useAlice) {
+      require(checkSig(sig, alice));
+    } else {
+      require(checkSig(sig, bob));
+    }
+  }
+}
+"#;
+    let suite = suite(
+        r#"{
+  "cases": [
+    { "name": "alice", "function": "spend", "witness": { "useAlice": true }, "expect": "pass" }
+  ]
+}"#,
+    );
+    let report = testing::run(code, &suite).unwrap();
+    assert_eq!(report.failures(), 0, "{:?}", report.results);
+    let spend = &report.coverage.functions[0];
+
+    assert_eq!(report.coverage.branches_taken(), (1, 2));
+    assert_eq!(
+        (spend.branches[0].then_taken, spend.branches[0].else_taken),
+        (1, 0)
+    );
+    assert!(!spend.requirements[1].exercised());
+    assert!(report
+        .coverage
+        .to_string()
+        .contains("4:9  if: then taken 1, else never taken"));
+}
+
+#[test]
+fn test_introspection_against_mock_transaction() {
+    let code = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Pay(pubkey owner, int amount) {
+  function pay(signature ownerSig) {
+    require(tx.outputs[0].value >= amount);
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let suite = suite(
+        r#"{
+  "constructorInputs": { "amount": 5000 },
+  "cases": [
+    { "name": "enough", "function": "pay", "tx": { "outputs": [{ "value": 6000 }] }, "expect": "pass" },
+    { "name": "too little", "function": "pay", "tx": { "outputs": [{ "value": 4000 }] }, "expect": "pass" }
+  ]
+}"#,
+    );
+    let report = testing::run(code, &suite).unwrap();
+
+    assert!(report.results[0].ok());
+    assert!(!report.results[1].ok());
+    assert_eq!(report.failures(), 1);
+    assert_eq!(
+        report.results[1].message.as_deref(),
+        Some("require at 9:5 failed: condition does not hold")
+    );
+}
+
+#[test]
+fn test_unknown_function_is_an_error() {
+    let suite = suite(r#"{ "cases": [{ "name": "x", "function": "nope", "expect": "fail" }] }"#);
+    let report = testing::run(HTLC, &suite).unwrap();
+
+    assert!(report.results[0].error);
+    assert!(!report.results[0].ok());
+}
+
+#[test]
+fn test_failing_introspection_script_fails_the_requirement() {
+    let code = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Holder(bytes32 assetId, pubkey owner) {
+  function spend(signature ownerSig) {
+    require(tx.inputs[0].assets.lookup(assetId) > 0);
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let suite =
+        suite(r#"{ "cases": [{ "name": "no assets", "function": "spend", "expect": "fail" }] }"#);
+    let report = testing::run(code, &suite).unwrap();
+
+    assert!(report.results[0].ok(), "{:?}", report.results);
+    assert!(report.results[0]
+        .message
+        .as_deref()
+        .unwrap()
+        .starts_with("require at 9:5 failed: "));
+    assert_eq!(report.coverage.functions[0].requirements[0].failed, 1);
+}
+
+#[test]
+fn test_server_signature_and_exit_timelock_are_run() {
+    let suite = suite(
+        r#"{
+  "constructorInputs": { "refundTime": 800000 },
+  "cases": [
+    { "name": "no server", "function": "claim", "witness": { "serverSig": "" }, "expect": "fail" },
+    { "name": "exit too early", "function": "claim", "serverVariant": false, "expect": "fail" },
+    {
+      "name": "exit",
+      "function": "claim",
+      "serverVariant": false,
+      "tx": { "inputs": [{ "sequence": 144 }] },
+      "expect": "pass"
+    }
+  ]
+}"#,
+    );
+    let report = testing::run(HTLC, &suite).unwrap();
+
+    assert_eq!(report.failures(), 0, "{:?}", report.results);
+    assert_eq!(
+        report.results[0].message.as_deref(),
+        Some("server signature: `serverSig` is not a valid signature by `SERVER_KEY`")
+    );
+    assert!(report.results[1]
+        .message
+        .as_deref()
+        .unwrap()
+        .starts_with("exit timelock: "));
+}
+
+#[test]
+fn test_the_leaf_finds_the_elements_a_branch_reads() {
+    // The flag is read first, and each branch checks a signature under it
+    let code = r#"
+contract Paths(pubkey alice, pubkey bob) {
+  function spend(signature aliceSig, signature bobSig, bool useAlice) {
+    if (useAlice) {
+      require(checkSig(aliceSig, alice));
+    } else {
+      require(checkSig(bobSig, bob));
+    }
+  }
+}
+"#;
+    let suite = suite(
+        r#"{
+  "cases": [
+    { "name": "alice", "function": "spend", "witness": { "useAlice": true }, "expect": "pass" },
+    { "name": "bob", "function": "spend", "witness": { "useAlice": false }, "expect": "pass" },
+    { "name": "forged", "function": "spend", "witness": { "useAlice": false, "bobSig": "" }, "expect": "fail" }
+  ]
+}"#,
+    );
+    let report = testing::run(code, &suite).unwrap();
+
+    assert_eq!(report.failures(), 0, "{:?}", report.results);
+}
+
+#[test]
+fn test_leaf_and_source_must_agree() {
+    // The source compares numbers by value, but the leaf compares the
+    // bytes of a number padded with a zero byte
+    let code = r#"
+contract Paths(pubkey alice) {
+  function spend(signature aliceSig, int x) {
+    require(checkSig(aliceSig, alice));
+    require(x == 1);
+  }
+}
+"#;
+    let suite = suite(
+        r#"{
+  "cases": [
+    { "name": "padded", "function": "spend", "witness": { "x": "0100" }, "expect": "pass" }
+  ]
+}"#,
+    );
+    let report = testing::run(code, &suite).unwrap();
+
+    assert!(report.results[0].error);
+    assert!(report.results[0]
+        .message
+        .as_deref()
+        .unwrap()
+        .starts_with("the compiled leaf fails, but the source holds: "));
+}
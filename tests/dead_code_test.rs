@@ -0,0 +1,107 @@
+use arkade_compiler::{compile, compile_with_options, CompileOptions, ContractJson};
+
+const CODE: &str = r#"
+contract Dead(pubkey owner, pubkey backup, int unlockTime) {
+  function spend(signature ownerSig) {
+    require(1 == 1);
+    if (2 * 3 < 5) {
+      require(tx.time >= unlockTime);
+    } else {
+      int delay = unlockTime;
+      require(tx.input.current.sequence >= delay);
+    }
+    if (true) {
+      require(checkSig(ownerSig, owner));
+    }
+  }
+}
+"#;
+
+fn optimized(code: &str) -> ContractJson {
+    compile_with_options(
+        code,
+        &CompileOptions {
+            optimize: true,
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+fn server_asm(output: &ContractJson) -> Vec<String> {
+    output
+        .functions
+        .iter()
+        .find(|f| f.name == "spend" && f.server_variant)
+        .unwrap()
+        .asm
+        .clone()
+}
+
+#[test]
+fn test_dead_code_is_reported() {
+    let output = compile(CODE).unwrap();
+    let dead: Vec<&String> = output
+        .warnings
+        .iter()
+        .filter(|w| w.starts_with("warning[dead]"))
+        .collect();
+    assert_eq!(
+        dead,
+        [
+            "warning[dead]: fn spend: require at 4:5 always holds",
+            "warning[dead]: fn spend: condition at 5:9 is always false; its branch is never taken",
+            "warning[dead]: fn spend: condition at 11:9 is always true",
+        ]
+    );
+
+    // Without the optimizer, the dead code is still compiled
+    let asm = server_asm(&output);
+    assert_eq!(asm.iter().filter(|op| *op == "OP_IF").count(), 2);
+    assert!(asm.contains(&"OP_CHECKLOCKTIMEVERIFY".to_string()));
+}
+
+#[test]
+fn test_optimizer_removes_dead_code() {
+    let output = optimized(CODE);
+    let asm = server_asm(&output);
+    assert!(!asm.contains(&"OP_IF".to_string()), "{:?}", asm);
+    assert!(!asm.contains(&"OP_ELSE".to_string()), "{:?}", asm);
+    assert!(!asm.contains(&"OP_CHECKLOCKTIMEVERIFY".to_string()));
+    // The branch taken is kept, bindings and all
+    assert!(asm.contains(&"OP_INSPECTINPUTSEQUENCE".to_string()));
+    assert!(asm.contains(&"OP_CHECKSIG".to_string()));
+    assert_eq!(output.warnings.len(), compile(CODE).unwrap().warnings.len());
+
+    // The size before is that of the leaf as written
+    let report = output
+        .optimizations
+        .iter()
+        .find(|r| r.function == "spend" && r.server_variant)
+        .unwrap();
+    let written = compile(CODE).unwrap();
+    let written = server_asm(&written);
+    assert!(report.size_before > report.size_after);
+    assert!(written.len() > asm.len());
+}
+
+#[test]
+fn test_conditions_on_parameters_are_kept() {
+    let code = r#"
+contract Live(pubkey owner, int unlockTime) {
+  function spend(signature ownerSig, int mode) {
+    if (mode == 1) {
+      require(tx.time >= unlockTime);
+    }
+    require(unlockTime >= 0);
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let output = optimized(code);
+    assert!(!output
+        .warnings
+        .iter()
+        .any(|w| w.starts_with("warning[dead]")));
+    assert!(server_asm(&output).contains(&"OP_IF".to_string()));
+}
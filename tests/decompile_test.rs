@@ -0,0 +1,183 @@
+#![cfg(feature = "std")]
+
+use std::collections::HashMap;
+
+use arkade_compiler::compile;
+use arkade_compiler::decompile::{disassemble, disassemble_hex, identify, parse_asm};
+use arkade_compiler::encoding::Value;
+use arkade_compiler::linker::SERVER_KEY;
+use arkade_compiler::models::{ContractJson, OperationData};
+use arkade_compiler::opcodes::{opcode_byte, opcode_name};
+
+const LOCK: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Lock(pubkey sender, pubkey receiver, bytes32 hash, int refundTime) {
+  function claim(signature receiverSig, bytes32 preimage) {
+    require(checkSig(receiverSig, receiver));
+    require(sha256(preimage) == hash);
+  }
+
+  function refund(signature senderSig) {
+    require(checkSig(senderSig, sender));
+    require(tx.time >= refundTime);
+  }
+}
+"#;
+
+fn key(prefix: u8, byte: u8) -> Vec<u8> {
+    let mut key = vec![prefix];
+    key.extend([byte; 32]);
+    key
+}
+
+fn instance(artifact: &ContractJson, refund_time: i64) -> ContractJson {
+    let values: HashMap<String, Value> = [
+        ("sender", Value::Pubkey(key(0x02, 0x11))),
+        ("receiver", Value::Pubkey(key(0x03, 0x22))),
+        ("hash", Value::Bytes(vec![0xab; 32])),
+        ("refundTime", Value::Num(refund_time)),
+        (SERVER_KEY, Value::Pubkey(key(0x03, 0x33))),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value))
+    .collect();
+    artifact.instantiate(values).unwrap()
+}
+
+fn script(artifact: &ContractJson, name: &str, server_variant: bool) -> Vec<u8> {
+    let function = artifact
+        .functions
+        .iter()
+        .find(|f| f.name == name && f.server_variant == server_variant)
+        .unwrap();
+    hex::decode(function.script_hex.as_ref().unwrap()).unwrap()
+}
+
+#[test]
+fn test_disassembly_matches_the_linked_operations() {
+    let linked = instance(&compile(LOCK).unwrap(), 850_000);
+    for function in &linked.functions {
+        let ops = disassemble_hex(function.script_hex.as_ref().unwrap()).unwrap();
+        let expected: Vec<_> = function
+            .operations
+            .iter()
+            .filter(|o| !matches!(o.data, OperationData::FunctionInput { .. }))
+            .map(|o| o.data.clone())
+            .collect();
+        let data: Vec<_> = ops.into_iter().map(|o| o.data).collect();
+        assert_eq!(data, expected, "{}", function.name);
+    }
+}
+
+#[test]
+fn test_identify_recovers_the_function_and_arguments() {
+    let artifact = compile(LOCK).unwrap();
+    let linked = instance(&artifact, 850_000);
+
+    let leaf = identify(&artifact, &script(&linked, "refund", true))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        (leaf.function.as_str(), leaf.server_variant),
+        ("refund", true)
+    );
+    let bindings: Vec<_> = leaf
+        .bindings
+        .iter()
+        .map(|b| (b.name.as_str(), b.position, b.hex.clone()))
+        .collect();
+    assert_eq!(
+        bindings,
+        [
+            ("sender", 0, "11".repeat(32)),
+            ("refundTime", 2, "50f80c".to_string()),
+            (SERVER_KEY, 5, "33".repeat(32)),
+        ]
+    );
+    assert!(matches!(
+        leaf.bindings[0].data,
+        OperationData::ConstructorParam { ref param_type, .. } if param_type == "pubkey"
+    ));
+
+    let leaf = identify(&artifact, &script(&linked, "claim", false))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        (leaf.function.as_str(), leaf.server_variant),
+        ("claim", false)
+    );
+}
+
+#[test]
+fn test_small_integers_bind_from_their_opcodes() {
+    let artifact = compile(LOCK).unwrap();
+    let linked = instance(&artifact, 5);
+    let leaf = identify(&artifact, &script(&linked, "refund", false))
+        .unwrap()
+        .unwrap();
+    let refund_time = leaf
+        .bindings
+        .iter()
+        .find(|b| b.name == "refundTime")
+        .unwrap();
+    assert_eq!(refund_time.hex, "05");
+    assert_eq!(disassemble(&[0x55]).unwrap()[0].op, "OP_5");
+}
+
+#[test]
+fn test_foreign_scripts_do_not_match() {
+    let artifact = compile(LOCK).unwrap();
+    let mut foreign = script(&instance(&artifact, 850_000), "refund", true);
+    // OP_CHECKSIG becomes OP_CHECKSIGVERIFY
+    let at = foreign.iter().position(|b| *b == 0xac).unwrap();
+    foreign[at] = 0xad;
+    assert_eq!(identify(&artifact, &foreign).unwrap(), None);
+
+    assert_eq!(
+        disassemble(&[0x20, 0x11]).unwrap_err(),
+        "Script ends inside the push at byte 0"
+    );
+    assert_eq!(disassemble(&[0xff]).unwrap()[0].op, "OP_UNKNOWN_0xff");
+}
+
+#[test]
+fn test_asm_is_described_token_by_token() {
+    let ops = parse_asm("OP_DUP <SERVER_KEY> 0xab 144 <x>");
+    let data: Vec<_> = ops.into_iter().map(|o| o.data).collect();
+    assert_eq!(
+        data,
+        [
+            OperationData::Opcode,
+            OperationData::Runtime {
+                name: SERVER_KEY.to_string(),
+                size: 32,
+            },
+            OperationData::Push {
+                hex: "ab".to_string(),
+                size: 1,
+            },
+            OperationData::Push {
+                hex: "9000".to_string(),
+                size: 2,
+            },
+            OperationData::Local {
+                name: "x".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_opcode_names_round_trip() {
+    for byte in 0..=u8::MAX {
+        if let Some(name) = opcode_name(byte) {
+            assert_eq!(opcode_byte(name), Some(byte));
+        }
+    }
+    assert_eq!(opcode_name(0x00), Some("OP_0"));
+    assert_eq!(opcode_name(0x20), None);
+}
@@ -0,0 +1,100 @@
+use arkade_compiler::compiler::{compile_with_options, CompileOptions};
+use arkade_compiler::diagnostics::classify;
+
+// Three unbound signatures, each a malleability warning
+const LOOSE: &str = r#"
+contract Loose(pubkey owner) {
+  function spend(signature ownerSig, signature a, signature b, signature c) {
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+
+fn denied(max_errors: Option<usize>) -> CompileOptions {
+    CompileOptions {
+        deny_warnings: true,
+        max_errors,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_warnings_are_allowed_by_default() {
+    let artifact = compile_with_options(LOOSE, &CompileOptions::default()).unwrap();
+    assert_eq!(artifact.warnings.len(), 3);
+}
+
+#[test]
+fn test_denied_warnings_fail_compilation() {
+    let err = compile_with_options(LOOSE, &denied(None))
+        .unwrap_err()
+        .to_string();
+    let lines: Vec<&str> = err.lines().collect();
+    assert_eq!(lines[0], "Warnings denied: the contract has 3");
+    assert_eq!(lines.len(), 4);
+    assert!(lines[1..]
+        .iter()
+        .all(|line| line.starts_with("  warning[malleable]")));
+    assert_eq!(classify("compile", &err), "E0302");
+}
+
+#[test]
+fn test_max_errors_limits_the_list() {
+    let err = compile_with_options(LOOSE, &denied(Some(1)))
+        .unwrap_err()
+        .to_string();
+    let lines: Vec<&str> = err.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[2], "  ... and 2 more");
+}
+
+#[test]
+fn test_clean_contracts_compile_with_denied_warnings() {
+    let source = r#"
+contract SingleSig(pubkey owner) {
+  function spend(signature ownerSig) {
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    assert!(compile_with_options(source, &denied(None)).is_ok());
+}
+
+#[test]
+fn test_deny_warnings_cli() {
+    use std::fs;
+    use tempfile::tempdir;
+
+    let temp_dir = tempdir().unwrap();
+    let input_path = temp_dir.path().join("loose.ark");
+    fs::write(&input_path, LOOSE).unwrap();
+    let run = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_arkadec"))
+            .arg(input_path.to_str().unwrap())
+            .arg("-o")
+            .arg(temp_dir.path().join("loose.json"))
+            .args(args)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let output = run(&["--max-errors", "2"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(
+        stderr.matches("warning[malleable]").count(),
+        2,
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("... and 1 more"), "{}", stderr);
+
+    let output = run(&["--deny-warnings"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Compilation error [E0302]: Warnings denied"),
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,106 @@
+use arkade_compiler::analysis::DEPRECATIONS;
+use arkade_compiler::compiler::compile;
+use arkade_compiler::diagnostics::{self, classify};
+use arkade_compiler::{parser, printer, Edition};
+
+fn joint(pragma: &str, condition: &str) -> String {
+    format!(
+        r#"{}
+contract Joint(pubkey alice, pubkey bob) {{
+  function spend(signature aliceSig, signature bobSig) {{
+    require({});
+  }}
+}}
+"#,
+        pragma, condition
+    )
+}
+
+fn deprecations(source: &str) -> Vec<String> {
+    compile(source)
+        .unwrap()
+        .warnings
+        .into_iter()
+        .filter(|w| w.starts_with("warning[deprecated]"))
+        .collect()
+}
+
+#[test]
+fn test_implicit_threshold_is_deprecated() {
+    let source = joint("", "checkMultisig([alice, bob])");
+    assert_eq!(
+        deprecations(&source),
+        [
+            "warning[deprecated]: fn spend: checkMultisig without a threshold at 4:5 is \
+             deprecated and will be removed in edition 2025; write the threshold, as in \
+             checkMultisig([a, b], 2)"
+        ]
+    );
+    let ids: Vec<String> = diagnostics::check(&source)
+        .into_iter()
+        .map(|d| d.id)
+        .collect();
+    assert_eq!(ids, ["E0405"]);
+
+    // Still n-of-n
+    let explicit = joint("", "checkMultisig([alice, bob], 2)");
+    assert!(deprecations(&explicit).is_empty());
+    let asm = |source: &str| -> Vec<Vec<String>> {
+        compile(source)
+            .unwrap()
+            .functions
+            .into_iter()
+            .map(|f| f.asm)
+            .collect()
+    };
+    assert_eq!(asm(&source), asm(&explicit));
+}
+
+#[test]
+fn test_signature_array_multisig_is_deprecated() {
+    let source = joint("", "checkMultisig([alice, bob], [aliceSig, bobSig])");
+    let warnings = deprecations(&source);
+    assert_eq!(warnings.len(), 1);
+    assert!(
+        warnings[0].contains("checkMultisig(keys, sigs) at 4:5 is deprecated"),
+        "{}",
+        warnings[0]
+    );
+}
+
+#[test]
+fn test_removing_edition_rejects_deprecated_constructs() {
+    for condition in [
+        "checkMultisig([alice, bob])",
+        "checkMultisig([alice, bob], [aliceSig, bobSig])",
+    ] {
+        let err = compile(&joint("pragma arkade 2025;", condition))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("was removed in edition 2025"), "{}", err);
+        assert_eq!(classify("compile", &err), "E0207");
+    }
+    compile(&joint(
+        "pragma arkade 2025;",
+        "checkMultisig([alice, bob], 2)",
+    ))
+    .unwrap();
+}
+
+#[test]
+fn test_printer_keeps_the_written_form() {
+    for condition in [
+        "checkMultisig([alice, bob])",
+        "checkMultisig([alice, bob], 2)",
+    ] {
+        let contract = parser::parse(&joint("", condition)).unwrap();
+        assert!(printer::print(&contract).contains(condition));
+    }
+}
+
+#[test]
+fn test_deprecations_outlive_the_first_edition() {
+    for deprecation in DEPRECATIONS {
+        assert!(deprecation.removed_in > Edition::default());
+    }
+}
@@ -0,0 +1,82 @@
+use arkade_compiler::describe;
+
+#[test]
+fn test_describe_lists_declared_interface() {
+    let code = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Oracle(bytes32 assetId, pubkey[] oracles, int threshold) {
+  function attest(signature[] oracleSigs, int amount) {
+    require(amount > 0);
+  }
+
+  function helper(int x) internal {
+    require(x > 0);
+  }
+}
+"#;
+    let description = describe(code).unwrap();
+
+    assert_eq!(description.name, "Oracle");
+    let params: Vec<_> = description
+        .parameters
+        .iter()
+        .map(|p| (p.name.as_str(), p.param_type.as_str()))
+        .collect();
+    assert_eq!(
+        params,
+        vec![
+            ("assetId", "bytes32"),
+            ("oracles", "pubkey[]"),
+            ("threshold", "int")
+        ]
+    );
+    assert_eq!(description.functions.len(), 1);
+    assert_eq!(description.functions[0].name, "attest");
+    assert_eq!(description.functions[0].inputs[0].param_type, "signature[]");
+    assert!(description.has_server_key);
+    assert_eq!(description.exit_timelock, Some(144));
+}
+
+#[test]
+fn test_describe_does_not_need_valid_codegen() {
+    // Parses fine but references an undefined variable
+    let code = r#"
+contract Broken(pubkey owner) {
+  function spend(signature ownerSig) {
+    require(checkSig(ownerSig, nobody));
+  }
+}
+"#;
+    let description = describe(code).unwrap();
+    assert_eq!(description.functions[0].name, "spend");
+}
+
+#[test]
+fn test_describe_serializes_like_the_artifact() {
+    let code = r#"
+contract Single(pubkey owner) {
+  function spend(signature ownerSig) {
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let json = serde_json::to_value(describe(code).unwrap()).unwrap();
+
+    assert_eq!(json["contractName"], "Single");
+    assert_eq!(json["constructorInputs"][0]["type"], "pubkey");
+    assert_eq!(
+        json["functions"][0]["functionInputs"][0]["name"],
+        "ownerSig"
+    );
+    assert_eq!(json["hasServerKey"], false);
+    assert!(json.get("exitTimelock").is_none());
+}
+
+#[test]
+fn test_describe_reports_parse_errors() {
+    assert!(describe("contract {").is_err());
+}
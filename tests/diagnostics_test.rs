@@ -0,0 +1,80 @@
+use arkade_compiler::diagnostics::{check, Severity};
+
+#[test]
+fn test_valid_contract_has_no_diagnostics() {
+    let code = r#"
+contract Single(pubkey owner) {
+  function spend(signature ownerSig) {
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    assert!(check(code).is_empty(), "{:?}", check(code));
+}
+
+#[test]
+fn test_parse_error_is_located() {
+    let code =
+        "contract X(pubkey a) {\n  function f(signature s) {\n    require(checkSig(s, a)\n  }\n}\n";
+    let diagnostics = check(code);
+
+    assert_eq!(diagnostics.len(), 1);
+    let error = &diagnostics[0];
+    assert!(error.is_error());
+    assert_eq!(error.code, "parse");
+    // The missing `)` is noticed at the `}` that follows
+    assert_eq!((error.line, error.column), (Some(4), Some(3)));
+    assert!(!error.message.contains("-->"), "{}", error.message);
+}
+
+#[test]
+fn test_analysis_warnings_carry_spans() {
+    let code = r#"
+contract Bounds(pubkey owner) {
+  function spend(signature ownerSig, int amount) {
+    require(amount >= 1000, "too small");
+    require(amount > 10, "dust");
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let diagnostics = check(code);
+    let redundant = diagnostics.iter().find(|d| d.code == "redundant").unwrap();
+
+    assert_eq!(redundant.severity, Severity::Warning);
+    assert_eq!((redundant.line, redundant.column), (Some(5), Some(5)));
+    assert_eq!(
+        (redundant.end_line, redundant.end_column),
+        (Some(5), Some(34))
+    );
+    assert!(!diagnostics.iter().any(|d| d.is_error()));
+}
+
+#[test]
+fn test_type_warnings_are_reported() {
+    let code = r#"
+contract Mixed(pubkey owner) {
+  function spend(signature ownerSig, int amount) {
+    require(tx.outputs[0].value >= amount);
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let diagnostics = check(code);
+
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert_eq!(diagnostics[0].code, "type");
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert!(diagnostics[0].message.contains("mixes uint64le"));
+}
+
+#[test]
+fn test_diagnostics_serialize_for_editors() {
+    let code =
+        "contract X(pubkey a) {\n  function f(signature s) {\n    require(checkSig(s, a)\n  }\n}\n";
+    let json = serde_json::to_value(check(code)).unwrap();
+
+    assert_eq!(json[0]["severity"], "error");
+    assert_eq!(json[0]["line"], 4);
+    assert_eq!(json[0]["endColumn"], 4);
+}
@@ -0,0 +1,119 @@
+use arkade_compiler::compiler::compile;
+use arkade_compiler::diagnostics::classify;
+use arkade_compiler::opcodes::{OP_SHA256, OP_SIZE};
+use arkade_compiler::{parser, printer, Edition};
+
+fn htlc(pragma: &str, options: &str) -> String {
+    format!(
+        r#"{}
+options {{
+  server = server;{}
+}}
+
+contract HTLC(pubkey receiver, bytes32 hash) {{
+  function claim(signature receiverSig, bytes32 preimage) {{
+    require(sha256(preimage) == hash);
+    require(checkSig(receiverSig, receiver));
+  }}
+}}
+"#,
+        pragma, options
+    )
+}
+
+fn claim_asm(code: &str) -> Vec<String> {
+    compile(code)
+        .unwrap()
+        .functions
+        .into_iter()
+        .find(|f| f.name == "claim" && f.server_variant)
+        .unwrap()
+        .asm
+}
+
+#[test]
+fn test_source_without_pragma_is_edition_2024() {
+    let artifact = compile(&htlc("", "")).unwrap();
+    assert_eq!(artifact.edition, Edition::Edition2024);
+    let json = serde_json::to_value(&artifact).unwrap();
+    assert_eq!(json["edition"], "2024");
+    assert_eq!(claim_asm(&htlc("", ""))[..2], ["<preimage>", OP_SHA256]);
+}
+
+#[test]
+fn test_edition_2025_makes_hashlocks_strict() {
+    let source = htlc("pragma arkade 2025;", "");
+    assert_eq!(compile(&source).unwrap().edition, Edition::Edition2025);
+    assert_eq!(claim_asm(&source)[..2], ["<preimage>", OP_SIZE]);
+
+    // An explicit option still wins
+    let source = htlc("pragma arkade 2025;", "\n  strictHashlocks = false;");
+    assert_eq!(claim_asm(&source)[..2], ["<preimage>", OP_SHA256]);
+}
+
+#[test]
+fn test_unknown_edition_is_rejected() {
+    let err = compile(&htlc("pragma arkade 2023;", ""))
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("Unknown edition 2023 (expected 2024 or 2025)"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_printer_keeps_the_edition_and_overrides() {
+    for source in [
+        htlc("pragma arkade 2025;", ""),
+        htlc("pragma arkade 2025;", "\n  strictHashlocks = false;"),
+        htlc("pragma arkade 2024;", "\n  strictHashlocks = true;"),
+    ] {
+        let contract = parser::parse(&source).unwrap();
+        let printed = printer::print(&contract);
+        let reparsed = parser::parse(&printed).unwrap();
+        assert_eq!(reparsed.edition, contract.edition, "{}", printed);
+        assert_eq!(
+            reparsed.strict_hashlocks, contract.strict_hashlocks,
+            "{}",
+            printed
+        );
+    }
+}
+
+#[test]
+fn test_compiler_pragma_accepts_this_version() {
+    let source = htlc("pragma arkade 2025;\npragma compiler >=0.1, <1.0;", "");
+    compile(&source).unwrap();
+    let contract = parser::parse(&source).unwrap();
+    assert_eq!(contract.compiler.as_deref(), Some(">=0.1, <1.0"));
+    let reparsed = parser::parse(&printer::print(&contract)).unwrap();
+    assert_eq!(reparsed.compiler, contract.compiler);
+}
+
+#[test]
+fn test_compiler_pragma_rejects_other_versions() {
+    let version = env!("CARGO_PKG_VERSION");
+    let err = compile(&htlc("pragma compiler >=9.0;", ""))
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains(&format!(
+            "Contract requires compiler >=9.0, but this is arkadec {}",
+            version
+        )),
+        "{}",
+        err
+    );
+    assert_eq!(classify("compile", &err), "E0206");
+
+    let err = compile(&htlc("pragma compiler newest;", ""))
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("Invalid compiler version requirement 'newest'"),
+        "{}",
+        err
+    );
+}
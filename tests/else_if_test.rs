@@ -0,0 +1,206 @@
+#![cfg(feature = "std")]
+
+use arkade_compiler::compiler::compile;
+use arkade_compiler::interpreter::{LeafEvaluator, TxContext, TxInput};
+use arkade_compiler::models::Statement;
+use arkade_compiler::parser::{parse, ParseError};
+use arkade_compiler::{formatter, printer};
+
+fn contract(body: &str) -> String {
+    format!(
+        r#"
+contract Tiers(pubkey owner, pubkey backup, int unlockTime) {{
+  function spend(signature sig, int tier) {{
+{}
+  }}
+}}
+"#,
+        body
+    )
+}
+
+const CHAIN: &str = "    if (tier == 1) {
+      require(checkSig(sig, owner));
+    } else if (tier == 2) {
+      require(checkSig(sig, backup));
+    } else if (tx.time >= unlockTime) {
+      require(tier > 2);
+    } else {
+      require(tier == 0);
+    }";
+
+#[test]
+fn test_else_if_nests_in_the_else_branch() {
+    let parsed = parse(&contract(CHAIN)).unwrap();
+    let mut statements = &parsed.functions[0].statements;
+    let mut conditions = 0;
+    while let [Statement::IfElse {
+        else_body: Some(else_body),
+        ..
+    }] = statements.as_slice()
+    {
+        conditions += 1;
+        statements = else_body;
+    }
+    assert_eq!(conditions, 3);
+    assert!(matches!(statements.as_slice(), [Statement::Require(..)]));
+
+    // Without a final else, the innermost if has no else branch
+    let source = contract(
+        "    if (tier == 1) {\n      require(checkSig(sig, owner));\n    } else if (tier == 2) {\n      require(checkSig(sig, backup));\n    }",
+    );
+    let parsed = parse(&source).unwrap();
+    let Statement::IfElse {
+        else_body: Some(else_body),
+        ..
+    } = &parsed.functions[0].statements[0]
+    else {
+        panic!("expected an if");
+    };
+    assert!(matches!(
+        else_body.as_slice(),
+        [Statement::IfElse {
+            else_body: None,
+            ..
+        }]
+    ));
+}
+
+#[test]
+fn test_else_if_compiles_to_nested_branches() {
+    let artifact = compile(&contract(CHAIN)).unwrap();
+    let asm = &artifact.functions[0].asm;
+    let count = |op: &str| asm.iter().filter(|token| *token == op).count();
+    assert_eq!(count("OP_IF"), 3);
+    assert_eq!(count("OP_ELSE"), 3);
+    assert_eq!(count("OP_ENDIF"), 3);
+    // Each else opens the next if before any branch closes
+    let branches: Vec<&str> = asm
+        .iter()
+        .map(String::as_str)
+        .filter(|token| matches!(*token, "OP_IF" | "OP_ELSE" | "OP_ENDIF"))
+        .collect();
+    assert_eq!(
+        branches,
+        [
+            "OP_IF", "OP_ELSE", "OP_IF", "OP_ELSE", "OP_IF", "OP_ELSE", "OP_ENDIF", "OP_ENDIF",
+            "OP_ENDIF"
+        ]
+    );
+}
+
+#[test]
+fn test_a_check_in_only_one_branch_is_verified_in_it() {
+    let source = contract(
+        "    if (tier == 1) {\n      require(checkSig(sig, owner));\n    } else if (tier == 2) {\n      require(checkSig(sig, backup));\n    }",
+    );
+    let artifact = compile(&source).unwrap();
+    let asm = artifact.functions[0].asm.join(" ");
+    // The inner if has no else, so neither branch of the outer one is sure
+    // to leave a result. The branch that does not check the signature drops
+    // it, and the first the tier it leaves.
+    assert!(
+        asm.contains("<owner> OP_CHECKSIGVERIFY OP_DROP OP_ELSE"),
+        "{}",
+        asm
+    );
+    assert!(
+        asm.contains("<sig> <backup> OP_CHECKSIGVERIFY OP_ELSE OP_DROP OP_ENDIF OP_ENDIF"),
+        "{}",
+        asm
+    );
+}
+
+const CHOICE: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Choice(int a, int b) {
+  function pick(int choice, int x) {
+    if (choice == 0) {
+      require(x == a);
+    } else if (choice == 1) {
+      require(x == b);
+    } else if (choice == 2) {
+      require(x > a + b);
+    } else {
+      require(x == choice);
+    }
+  }
+}
+"#;
+
+#[test]
+fn test_every_branch_of_a_chain_runs_on_its_discriminant() {
+    let artifact = compile(CHOICE).unwrap();
+    let asm = artifact.functions[1].asm.join(" ");
+    // The discriminant stays on the stack, copied for each test
+    assert_eq!(asm.matches("OP_DUP").count(), 3, "{}", asm);
+    assert_eq!(asm.matches("<choice>").count(), 1, "{}", asm);
+
+    let tx = TxContext::builder()
+        .input(TxInput::new(10_000, vec![0x51, 0x20]).sequence(144))
+        .build()
+        .unwrap();
+    let pick = |choice: &str, x: &str| {
+        LeafEvaluator::for_function(&artifact, "pick", false)
+            .unwrap()
+            .param("a", "10")
+            .param("b", "20")
+            .arg("choice", choice)
+            .arg("x", x)
+            .run(&tx)
+    };
+    for (choice, x, holds) in [
+        ("0", "10", true),
+        ("0", "20", false),
+        ("1", "20", true),
+        ("1", "10", false),
+        ("2", "31", true),
+        ("2", "30", false),
+        ("3", "3", true),
+        ("7", "7", true),
+        ("7", "10", false),
+    ] {
+        assert_eq!(pick(choice, x).is_ok(), holds, "{} {}", choice, x);
+    }
+}
+
+#[test]
+fn test_else_if_prints_and_formats_as_a_chain() {
+    let source = contract(CHAIN);
+    let printed = printer::print(&parse(&source).unwrap());
+    assert!(printed.contains("} else if (tier == 2) {"), "{}", printed);
+    assert!(
+        printed.contains("} else if (tx.time >= unlockTime) {"),
+        "{}",
+        printed
+    );
+    assert_eq!(printer::print(&parse(&printed).unwrap()), printed);
+
+    let formatted = formatter::format(&source).unwrap();
+    assert!(
+        formatted.contains("\n    } else if (tier == 2) {\n"),
+        "{}",
+        formatted
+    );
+}
+
+#[test]
+fn test_long_else_if_chains_count_towards_the_depth_limit() {
+    let chain = |branches: usize| {
+        contract(&format!(
+            "    if (tier == 0) {{\n      require(checkSig(sig, owner));\n    }}{}",
+            " else if (tier == 1) {\n      require(checkSig(sig, backup));\n    }".repeat(branches)
+        ))
+    };
+    parse(&chain(32)).unwrap();
+
+    let err = parse(&chain(33)).unwrap_err();
+    let err = err.downcast_ref::<ParseError>().unwrap();
+    assert_eq!(err.message, "Source nests more than 32 levels deep");
+    // Each branch starts on the line the one before it closes
+    assert_eq!(err.span.unwrap().line, 6 + 2 * 32);
+}
@@ -0,0 +1,138 @@
+#![cfg(feature = "std")]
+
+use std::collections::HashMap;
+
+use arkade_compiler::compile;
+use arkade_compiler::encoding::{encode_num, push_data, push_num, Value};
+use arkade_compiler::linker::link;
+use arkade_compiler::witness::WitnessBuilder;
+
+#[test]
+fn test_numbers_are_minimal_script_numbers() {
+    assert_eq!(encode_num(0), Vec::<u8>::new());
+    assert_eq!(encode_num(127), vec![0x7f]);
+    assert_eq!(encode_num(128), vec![0x80, 0x00]);
+    assert_eq!(encode_num(-1), vec![0x81]);
+    assert_eq!(encode_num(-256), vec![0x00, 0x81]);
+
+    let mut script = Vec::new();
+    for n in [0, -1, 16, 17] {
+        push_num(&mut script, n);
+    }
+    assert_eq!(script, vec![0x00, 0x4f, 0x60, 0x01, 0x11]);
+}
+
+#[test]
+fn test_data_pushes_use_the_smallest_opcode() {
+    let push = |len: usize| {
+        let mut script = Vec::new();
+        push_data(&mut script, &vec![0xab; len]);
+        script[..script.len() - len].to_vec()
+    };
+    assert_eq!(push(75), vec![75]);
+    assert_eq!(push(76), vec![0x4c, 76]);
+    assert_eq!(push(300), vec![0x4d, 0x2c, 0x01]);
+}
+
+#[test]
+fn test_values_are_checked_against_their_type() {
+    assert_eq!(Value::parse(" 42 ", "int"), Ok(Value::Num(42)));
+    assert_eq!(Value::parse("true", "bool"), Ok(Value::Num(1)));
+    assert_eq!(
+        Value::parse("0xabcd", "bytes"),
+        Ok(Value::Bytes(vec![0xab, 0xcd]))
+    );
+    assert_eq!(
+        Value::parse("abcd", "bytes32"),
+        Err("expected 32 bytes for bytes32, got 2".to_string())
+    );
+    assert_eq!(
+        Value::parse(&format!("04{}", "11".repeat(32)), "pubkey"),
+        Err("expected a compressed public key".to_string())
+    );
+    // A key that does not sign in a threshold leaves its signature empty
+    assert_eq!(Value::parse("", "signature"), Ok(Value::Bytes(Vec::new())));
+    assert_eq!(
+        Value::parse("0x", "schnorrsig"),
+        Ok(Value::Bytes(Vec::new()))
+    );
+    assert_eq!(
+        Value::parse("abcd", "signature"),
+        Err("expected 64 bytes for signature, got 2".to_string())
+    );
+}
+
+#[test]
+fn test_pubkeys_are_pushed_x_only() {
+    let key = Value::parse(&format!("02{}", "11".repeat(32)), "pubkey").unwrap();
+    let mut script = Vec::new();
+    key.push(&mut script);
+    assert_eq!(script[0], 32);
+    assert_eq!(key.stack(), vec![vec![0x11; 32]]);
+}
+
+#[test]
+fn test_pubkey_witness_elements_are_labelled_and_sized_x_only() {
+    let code = r#"
+contract Delegated(pubkey owner) {
+  function spend(pubkey delegate, signature delegateSig, signature ownerSig) {
+    require(checkSig(ownerSig, owner));
+    require(checkSig(delegateSig, delegate));
+  }
+}
+"#;
+    let artifact = compile(code).unwrap();
+    let spend = artifact.find_function("spend", false).unwrap();
+    let delegate = spend
+        .witness_schema
+        .iter()
+        .find(|e| e.name == "delegate")
+        .unwrap();
+    assert_eq!(delegate.encoding, "xonly-32");
+
+    let witness = WitnessBuilder::for_function(&artifact, "spend", false)
+        .unwrap()
+        .arg("delegate", format!("03{}", "11".repeat(32)))
+        .arg("delegateSig", "22".repeat(64))
+        .arg("ownerSig", "33".repeat(64))
+        .build()
+        .unwrap();
+    assert!(witness.contains(&vec![0x11; 32]));
+    // Three elements with their length prefixes: 1 + 32 + 2 * (1 + 64)
+    assert_eq!(spend.witness_size.stack, 163);
+}
+
+#[test]
+fn test_asset_ids_are_a_txid_and_a_group_index() {
+    let id = Value::parse(&format!("{}:3", "cd".repeat(32)), "asset").unwrap();
+    assert_eq!(
+        id,
+        Value::AssetId {
+            txid: [0xcd; 32],
+            gidx: 3
+        }
+    );
+    assert_eq!(id.stack(), vec![vec![0xcd; 32], vec![3]]);
+    let mut script = Vec::new();
+    id.push(&mut script);
+    assert_eq!(script[0], 32);
+    assert_eq!(script[33..], [0x53]);
+    assert!(Value::asset_id("cd:x").is_err());
+}
+
+#[test]
+fn test_linker_accepts_asset_ids_for_decomposed_parameters() {
+    let artifact = compile(include_str!("../examples/controlled_mint.ark")).unwrap();
+    let args: HashMap<String, String> = [
+        ("tokenAssetId", format!("{}:7", "cd".repeat(32))),
+        ("ctrlAssetId", format!("{}:0", "ef".repeat(32))),
+        ("issuerPk", format!("02{}", "11".repeat(32))),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value))
+    .collect();
+    let linked = link(&artifact, &args).unwrap();
+    let burn = linked.functions.iter().find(|f| f.name == "burn").unwrap();
+    let txid = burn.asm.iter().position(|t| *t == "cd".repeat(32)).unwrap();
+    assert_eq!(burn.asm[txid + 1], "7");
+}
@@ -0,0 +1,142 @@
+use arkade_compiler::compiler::{compile_with_options, CompileOptions};
+use arkade_compiler::diagnostics::{self, classify, explain, CODES};
+use arkade_compiler::parser::ScriptTarget;
+
+fn ids(source: &str) -> Vec<String> {
+    diagnostics::check(source)
+        .into_iter()
+        .map(|d| d.id)
+        .collect()
+}
+
+#[test]
+fn test_codes_are_documented() {
+    for (index, entry) in CODES.iter().enumerate() {
+        assert!(
+            entry
+                .explanation
+                .starts_with(&format!("# {}: {}\n", entry.code, entry.title)),
+            "{}",
+            entry.code
+        );
+        assert!(index == 0 || CODES[index - 1].code < entry.code);
+    }
+    assert_eq!(explain("e0102").unwrap().code, "E0102");
+    assert!(explain("E9999").is_none());
+}
+
+#[test]
+fn test_erroneous_examples_report_their_code() {
+    for entry in CODES {
+        // The first example, where it is a whole contract
+        let Some(example) = entry.explanation.split("```solidity\n").nth(1) else {
+            continue;
+        };
+        let example = example.split("```").next().unwrap();
+        if !example.starts_with("contract") || example.contains("...") {
+            continue;
+        }
+        let reported = ids(example);
+        let expected = match entry.code {
+            // Needs an option or target `check` does not set
+            "E0003" | "E0203" => continue,
+            code => code,
+        };
+        assert!(
+            reported.iter().any(|id| id == expected),
+            "{}: {:?}",
+            entry.code,
+            reported
+        );
+    }
+}
+
+#[test]
+fn test_compile_errors_are_classified() {
+    let source = r#"
+contract Oracle(pubkey oracle) {
+  function settle(signature sig, bytes message) {
+    require(checkSigFromStack(sig, oracle, message));
+  }
+}
+"#;
+    let options = CompileOptions {
+        script_target: ScriptTarget::Tapscript,
+        ..Default::default()
+    };
+    let err = compile_with_options(source, &options)
+        .unwrap_err()
+        .to_string();
+    assert_eq!(classify("compile", &err), "E0203");
+
+    let source = r#"
+contract Board<N>(pubkey[N] members) {
+  function spend() {
+    require(checkMultisig(members, 4));
+  }
+}
+"#;
+    let options = CompileOptions {
+        generics: [("N".to_string(), 3)].into(),
+        ..Default::default()
+    };
+    let err = compile_with_options(source, &options)
+        .unwrap_err()
+        .to_string();
+    assert_eq!(classify("compile", &err), "E0102");
+
+    let err = compile_with_options(source, &CompileOptions::default())
+        .unwrap_err()
+        .to_string();
+    assert_eq!(classify("compile", &err), "E0003");
+}
+
+#[test]
+fn test_every_diagnostic_has_a_code() {
+    assert_eq!(ids("contract {"), vec!["E0201"]);
+    let source = r#"
+options { server = server; exit = ONE_DAY; }
+contract SingleSig(pubkey owner) {
+  function spend(signature ownerSig) {
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    assert_eq!(ids(source), vec!["E0204"]);
+}
+
+#[test]
+fn test_explain_cli() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_arkadec"))
+        .args(["--explain", "E0203"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(text, explain("E0203").unwrap().explanation);
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_arkadec"))
+        .args(["--explain", "E9999"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_cli_errors_name_their_code() {
+    use std::fs;
+    use tempfile::tempdir;
+
+    let temp_dir = tempdir().unwrap();
+    let input_path = temp_dir.path().join("broken.ark");
+    fs::write(&input_path, "contract Broken(pubkey owner) {").unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_arkadec"))
+        .arg(input_path.to_str().unwrap())
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Compilation error [E0201]"), "{}", stderr);
+    assert!(stderr.contains("arkadec --explain E0201"), "{}", stderr);
+}
@@ -0,0 +1,124 @@
+use arkade_compiler::compiler::compile;
+use arkade_compiler::diagnostics::classify;
+use arkade_compiler::{parser, printer};
+
+fn htlc(events: &str, emits: &str) -> String {
+    format!(
+        r#"options {{
+  server = server;
+  exit = 144;
+}}
+
+contract HTLC(pubkey receiver, bytes32 hash) {{{}
+  function claim(signature receiverSig, bytes32 preimage) {{
+    require(sha256(preimage) == hash);
+    require(checkSig(receiverSig, receiver));{}
+  }}
+}}
+"#,
+        events, emits
+    )
+}
+
+const CLAIMED: &str = "\n  event Claimed(bytes32 preimage, pubkey receiver);\n";
+const EMIT: &str = "\n    emit Claimed(preimage, receiver);";
+
+#[test]
+fn test_events_are_in_the_artifact() {
+    let artifact = compile(&htlc(CLAIMED, EMIT)).unwrap();
+    let json = serde_json::to_value(&artifact).unwrap();
+    assert_eq!(
+        json["events"],
+        serde_json::json!([{
+            "name": "Claimed",
+            "inputs": [
+                { "name": "preimage", "type": "bytes32" },
+                { "name": "receiver", "type": "pubkey" }
+            ]
+        }])
+    );
+    for function in json["functions"].as_array().unwrap() {
+        assert_eq!(
+            function["emits"],
+            serde_json::json!([{ "event": "Claimed", "args": ["preimage", "receiver"] }])
+        );
+    }
+
+    // Omitted when there are none
+    let json = serde_json::to_value(compile(&htlc("", "")).unwrap()).unwrap();
+    assert!(json.get("events").is_none());
+    assert!(json["functions"][0].get("emits").is_none());
+}
+
+#[test]
+fn test_events_do_not_change_the_script() {
+    let asm = |source: &str| -> Vec<Vec<String>> {
+        compile(source)
+            .unwrap()
+            .functions
+            .into_iter()
+            .map(|f| f.asm)
+            .collect()
+    };
+    assert_eq!(asm(&htlc(CLAIMED, EMIT)), asm(&htlc("", "")));
+}
+
+#[test]
+fn test_emits_are_checked_against_the_declaration() {
+    for (events, emit, message, code) in [
+        (
+            "",
+            EMIT,
+            "fn claim: emits undeclared event 'Claimed'",
+            "E0001",
+        ),
+        (
+            CLAIMED,
+            "\n    emit Claimed(preimage);",
+            "fn claim: 1 value(s) for event 'Claimed', which has 2 field(s)",
+            "E0005",
+        ),
+        (
+            CLAIMED,
+            "\n    emit Claimed(preimage, secret);",
+            "fn claim: event 'Claimed' field 'receiver' is given 'secret', which is not a \
+             parameter of the function or contract",
+            "E0001",
+        ),
+        (
+            CLAIMED,
+            "\n    emit Claimed(hash, receiverSig);",
+            "fn claim: signature 'receiverSig' for event 'Claimed' field 'receiver' of type pubkey",
+            "E0005",
+        ),
+        (
+            "\n  event Claimed(bytes32 preimage);\n  event Claimed(int amount);\n",
+            "",
+            "Event 'Claimed' is declared twice",
+            "E0002",
+        ),
+    ] {
+        let err = compile(&htlc(events, emit)).unwrap_err().to_string();
+        assert_eq!(err, message);
+        assert_eq!(classify("compile", &err), code, "{}", err);
+    }
+}
+
+#[test]
+fn test_printer_keeps_events() {
+    let contract = parser::parse(&htlc(CLAIMED, EMIT)).unwrap();
+    let printed = printer::print(&contract);
+    assert!(printed.contains("  event Claimed(bytes32 preimage, pubkey receiver);\n\n"));
+    assert!(printed.contains("    emit Claimed(preimage, receiver);\n  }"));
+    let reparsed = parser::parse(&printed).unwrap();
+    assert_eq!(reparsed.events, contract.events);
+    assert_eq!(reparsed.functions[0].emits, contract.functions[0].emits);
+}
+
+#[test]
+fn test_emit_prefixed_names_are_not_emits() {
+    let source = htlc("", "\n    int emitted = 1;");
+    let contract = parser::parse(&source).unwrap();
+    assert!(contract.functions[0].emits.is_empty());
+    assert_eq!(contract.functions[0].statements.len(), 3);
+}
@@ -0,0 +1,123 @@
+#![cfg(feature = "std")]
+
+use std::fs;
+
+use std::collections::HashMap;
+
+use arkade_compiler::compile;
+use arkade_compiler::examples::{self, EXAMPLES};
+use arkade_compiler::linker::{link, SERVER_KEY};
+use arkade_compiler::models::ContractJson;
+use arkade_compiler::opcodes::opcode_byte;
+
+const G: &str = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+
+#[test]
+fn test_every_example_file_is_embedded() {
+    let mut files: Vec<String> = fs::read_dir("examples")
+        .unwrap()
+        .filter_map(|entry| {
+            let path = entry.unwrap().path();
+            (path.extension()? == "ark").then(|| path.file_stem().unwrap().to_string_lossy().into())
+        })
+        .collect();
+    files.sort();
+
+    let names: Vec<&str> = EXAMPLES.iter().map(|e| e.name).collect();
+    assert_eq!(names, files);
+}
+
+#[test]
+fn test_embedded_examples_compile() {
+    for example in EXAMPLES {
+        let output = compile(example.source)
+            .unwrap_or_else(|e| panic!("example {} does not compile: {}", example.name, e));
+        assert!(!output.name.is_empty());
+    }
+}
+
+#[test]
+fn test_find_example() {
+    let htlc = examples::find("htlc").unwrap();
+    assert_eq!(htlc.source, include_str!("../examples/htlc.ark"));
+    assert!(examples::find("htlc.ark").is_none());
+}
+
+// Every leaf of `artifact`, linked with placeholder arguments, assembles,
+// except where it uses an opcode with no settled encoding yet
+fn assert_leaves_assemble(artifact: &ContractJson, name: &str) {
+    let mut args: HashMap<String, String> = artifact
+        .parameters
+        .iter()
+        .map(|param| {
+            let value = match param.param_type.as_str() {
+                "pubkey" => G.to_string(),
+                "int" => "1".to_string(),
+                _ => "00".repeat(32),
+            };
+            (param.name.clone(), value)
+        })
+        .collect();
+    args.insert(SERVER_KEY.to_string(), G.to_string());
+
+    let linked = link(artifact, &args).unwrap_or_else(|e| panic!("{}: {}", name, e));
+    for function in &linked.functions {
+        let unencoded = function
+            .asm
+            .iter()
+            .any(|token| token.starts_with("OP_") && opcode_byte(token).is_none());
+        assert!(
+            function.script_hex.is_some() || unencoded,
+            "{}: fn {} (server variant: {}) does not assemble: {}",
+            name,
+            function.name,
+            function.server_variant,
+            function.asm.join(" ")
+        );
+        // Such a leaf is otherwise linked: only the witness and the VTXO
+        // templates the server resolves are left
+        for token in function.asm.iter().filter(|token| token.starts_with('<')) {
+            let placeholder = &token[1..token.len() - 1];
+            assert!(
+                placeholder.starts_with("VTXO:")
+                    || function
+                        .witness_schema
+                        .iter()
+                        .any(|e| e.name == placeholder),
+                "{}: fn {} has {} unlinked",
+                name,
+                function.name,
+                token
+            );
+        }
+    }
+}
+
+// The checked-in artifacts are what `cargo run --example compile_all`
+// writes today, apart from their timestamp, and their leaves assemble
+#[test]
+fn test_checked_in_artifacts_are_current() {
+    let mut checked = 0;
+    for entry in fs::read_dir("examples").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let source = fs::read_to_string(path.with_extension("ark")).unwrap();
+        let mut expected: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        expected.as_object_mut().unwrap().remove("updatedAt");
+
+        let mut output = compile(&source).unwrap();
+        output.updated_at = None;
+        assert_eq!(
+            serde_json::to_value(&output).unwrap(),
+            expected,
+            "{} is stale; regenerate it with `cargo run --example compile_all`",
+            path.display()
+        );
+        assert_leaves_assemble(&output, &path.display().to_string());
+        checked += 1;
+    }
+    assert_eq!(checked, 8);
+}
@@ -0,0 +1,59 @@
+use arkade_compiler::compile;
+use arkade_compiler::models::DEFAULT_EXIT_DELAY;
+
+fn source(options: &str) -> String {
+    format!(
+        r#"
+options {{
+  server = server;
+  {}
+}}
+
+contract SingleSig(pubkey user) {{
+  function spend(signature userSig) {{
+    require(checkSig(userSig, user));
+  }}
+}}
+"#,
+        options
+    )
+}
+
+#[test]
+fn test_exit_delay_from_option() {
+    let output = compile(&source("exit = 144;")).unwrap();
+    assert_eq!(output.exit_delay, 144);
+
+    let json = serde_json::to_value(&output).unwrap();
+    assert_eq!(json["exitDelay"], 144);
+}
+
+#[test]
+fn test_exit_delay_defaults_without_option() {
+    let output = compile(&source("")).unwrap();
+    assert_eq!(output.exit_delay, DEFAULT_EXIT_DELAY);
+    assert_eq!(DEFAULT_EXIT_DELAY, 288);
+
+    let json = serde_json::to_value(&output).unwrap();
+    assert_eq!(json["exitDelay"], 288);
+}
+
+#[test]
+fn test_exit_variants_use_default_delay() {
+    let output = compile(&source("")).unwrap();
+    let exit = output
+        .functions
+        .iter()
+        .find(|f| f.name == "spend" && !f.server_variant)
+        .unwrap();
+    assert!(exit.asm.ends_with(&[
+        "288".to_string(),
+        "OP_CHECKSEQUENCEVERIFY".to_string(),
+        "OP_DROP".to_string()
+    ]));
+    assert!(exit
+        .require
+        .iter()
+        .any(|r| r.req_type == "older"
+            && r.message.as_deref() == Some("Exit timelock of 288 blocks")));
+}
@@ -0,0 +1,69 @@
+use arkade_compiler::compile;
+
+const SINGLE_SIG_CODE: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract SingleSig(pubkey user) {
+  function spend(signature userSig) {
+    require(checkSig(userSig, user));
+  }
+}
+"#;
+
+#[test]
+fn test_vsize_adds_overheads_to_witness() {
+    let output = compile(SINGLE_SIG_CODE).unwrap();
+    let witness = output
+        .functions
+        .iter()
+        .find(|f| f.name == "spend" && f.server_variant)
+        .unwrap()
+        .witness_size;
+
+    // 94 non-witness bytes (tx overhead, one input, one P2TR output) at 4 WU
+    // each, plus the segwit marker and the witness itself
+    let expected = (94 * 4 + 2 + witness.total).div_ceil(4);
+    assert_eq!(output.estimate_vsize("spend", true).unwrap(), expected);
+}
+
+#[test]
+fn test_fee_scales_with_feerate() {
+    let output = compile(SINGLE_SIG_CODE).unwrap();
+    let vsize = output.estimate_vsize("spend", false).unwrap() as u64;
+
+    assert_eq!(output.estimate_fee("spend", false, 1.0).unwrap(), vsize);
+    assert_eq!(
+        output.estimate_fee("spend", false, 10.0).unwrap(),
+        vsize * 10
+    );
+    // Fractional rates round up
+    assert_eq!(
+        output.estimate_fee("spend", false, 1.5).unwrap(),
+        (vsize as f64 * 1.5).ceil() as u64
+    );
+}
+
+#[test]
+fn test_cooperative_spend_costs_more_than_exit() {
+    let output = compile(SINGLE_SIG_CODE).unwrap();
+    let server = output.estimate_fee("spend", true, 2.0).unwrap();
+    let exit = output.estimate_fee("spend", false, 2.0).unwrap();
+    // The cooperative leaf carries an extra signature
+    assert!(server > exit);
+}
+
+#[test]
+fn test_unknown_function_and_bad_feerate() {
+    let output = compile(SINGLE_SIG_CODE).unwrap();
+    let err = output.estimate_fee("missing", true, 1.0).unwrap_err();
+    assert_eq!(
+        err,
+        "Contract SingleSig has no server variant of function 'missing'"
+    );
+
+    assert!(output.estimate_fee("spend", true, -1.0).is_err());
+    assert!(output.estimate_fee("spend", true, f64::NAN).is_err());
+}
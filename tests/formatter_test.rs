@@ -0,0 +1,100 @@
+#![cfg(feature = "std")]
+
+use arkade_compiler::formatter::{canonical, format};
+
+#[test]
+fn test_reindents_by_bracket_depth() {
+    let code = "contract Single(\n      pubkey owner\n) {\nfunction spend(signature ownerSig) {\n        require(checkSig(ownerSig,   owner));\n}\n}";
+    assert_eq!(
+        format(code).unwrap(),
+        "contract Single(\n  pubkey owner\n) {\n  function spend(signature ownerSig) {\n    require(checkSig(ownerSig, owner));\n  }\n}\n"
+    );
+}
+
+#[test]
+fn test_else_is_outdented() {
+    let code = r#"contract Paths(pubkey a, pubkey b) {
+  function spend(signature aSig, signature bSig, bool useA) {
+    if (useA) {
+    require(checkSig(aSig, a));
+        } else {
+    require(checkSig(bSig, b));
+    }
+  }
+}
+"#;
+    let formatted = format(code).unwrap();
+    assert!(
+        formatted.contains("      require(checkSig(aSig, a));\n    } else {\n      require"),
+        "{}",
+        formatted
+    );
+}
+
+#[test]
+fn test_comments_strings_and_blank_lines() {
+    let code = "\n\n// Owner   only\ncontract Single(pubkey owner) {   \n\n\n\n  function spend(signature ownerSig) {\n    require(checkSig(ownerSig, owner), \"bad  sig\");   // aligned\n  }\n}\n\n";
+    assert_eq!(
+        format(code).unwrap(),
+        "// Owner   only\ncontract Single(pubkey owner) {\n\n  function spend(signature ownerSig) {\n    require(checkSig(ownerSig, owner), \"bad  sig\");   // aligned\n  }\n}\n"
+    );
+}
+
+#[test]
+fn test_formatting_is_idempotent_and_preserves_meaning() {
+    for entry in std::fs::read_dir("examples").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|e| e != "ark") {
+            continue;
+        }
+        let source = std::fs::read_to_string(&path).unwrap();
+        let Ok(formatted) = format(&source) else {
+            continue;
+        };
+        assert_eq!(format(&formatted).unwrap(), formatted, "{:?}", path);
+
+        let (Ok(before), Ok(after)) = (
+            arkade_compiler::compile(&source),
+            arkade_compiler::compile(&formatted),
+        ) else {
+            continue;
+        };
+        for (a, b) in before.functions.iter().zip(&after.functions) {
+            assert_eq!(a.asm, b.asm, "{:?} fn {}", path, a.name);
+        }
+    }
+}
+
+#[test]
+fn test_unparseable_source_is_not_formatted() {
+    assert!(format("contract {").is_err());
+}
+
+#[test]
+fn test_canonical_layout() {
+    let code = "options { server=server; exit=144; }\ncontract Single(pubkey owner){\n  // Owner only\n  function spend(signature ownerSig){ require(checkSig(ownerSig,owner), \"bad sig\"); int total=(1+2)*3; }\n}";
+    assert_eq!(
+        canonical(code).unwrap(),
+        "options {\n  server = server;\n  exit = 144;\n}\n\ncontract Single(pubkey owner) {\n  // Owner only\n  function spend(signature ownerSig) {\n    require(checkSig(ownerSig, owner));\n    let total = (1 + 2) * 3;\n  }\n}\n"
+    );
+}
+
+#[test]
+fn test_canonical_formatting_is_idempotent_and_preserves_meaning() {
+    for example in arkade_compiler::examples::EXAMPLES {
+        let formatted = canonical(example.source).unwrap();
+        assert_eq!(
+            canonical(&formatted).unwrap(),
+            formatted,
+            "{}",
+            example.name
+        );
+
+        let before = arkade_compiler::compile(example.source).unwrap();
+        let after = arkade_compiler::compile(&formatted)
+            .unwrap_or_else(|e| panic!("{}: {}\n\n{}", example.name, e, formatted));
+        for (a, b) in before.functions.iter().zip(&after.functions) {
+            assert_eq!(a.asm, b.asm, "{} fn {}", example.name, a.name);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+
+use arkade_compiler::compiler::{compile_with_options, CompileOptions};
+use arkade_compiler::parser::parse;
+
+const ESCROW_CODE: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Escrow<N>(pubkey[N] parties, pubkey arbiter, int threshold) {
+  function release(bytes32 message, signature[N] sigs) {
+    int valid = 0;
+    for (i, sig) in sigs {
+      if (checkSigFromStack(sig, parties[i], message)) {
+        valid = valid + 1;
+      }
+    }
+    require(valid >= threshold);
+  }
+
+  function arbitrate(signature arbiterSig) {
+    require(checkSig(arbiterSig, arbiter));
+  }
+}
+"#;
+
+fn options(n: usize) -> CompileOptions {
+    CompileOptions {
+        generics: BTreeMap::from([("N".to_string(), n)]),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_generic_parameters_are_parsed() {
+    let contract = parse(ESCROW_CODE).unwrap();
+    assert_eq!(contract.generics, vec!["N".to_string()]);
+    assert_eq!(contract.parameters[0].param_type, "pubkey[N]");
+    assert_eq!(
+        contract.functions[0].parameters[1].param_type,
+        "signature[N]"
+    );
+}
+
+#[test]
+fn test_each_instantiation_gets_its_own_artifact() {
+    for n in [2, 5] {
+        let output = compile_with_options(ESCROW_CODE, &options(n)).unwrap();
+        assert_eq!(output.generics.get("N"), Some(&n));
+
+        let parties: Vec<&str> = output
+            .parameters
+            .iter()
+            .filter(|p| p.name.starts_with("parties_"))
+            .map(|p| p.param_type.as_str())
+            .collect();
+        assert_eq!(parties, vec!["pubkey"; n]);
+
+        let release = output
+            .functions
+            .iter()
+            .find(|f| f.name == "release" && f.server_variant)
+            .unwrap();
+        assert_eq!(release.function_inputs.len(), n + 1);
+        assert_eq!(
+            release
+                .asm
+                .iter()
+                .filter(|op| op.starts_with("<parties_"))
+                .count(),
+            n
+        );
+        assert!(release.asm.contains(&format!("<parties_{}>", n - 1)));
+        assert!(!release.asm.contains(&format!("<parties_{}>", n)));
+    }
+}
+
+#[test]
+fn test_generic_values_are_required_and_checked() {
+    let missing = compile_with_options(ESCROW_CODE, &CompileOptions::default())
+        .unwrap_err()
+        .to_string();
+    assert!(
+        missing.contains("Missing value for generic parameter 'N'"),
+        "{}",
+        missing
+    );
+
+    let mut unknown = options(2);
+    unknown.generics.insert("M".to_string(), 3);
+    let err = compile_with_options(ESCROW_CODE, &unknown)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("no generic parameter 'M'"), "{}", err);
+
+    let err = compile_with_options(ESCROW_CODE, &options(0))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("at least 1"), "{}", err);
+}
+
+#[test]
+fn test_array_lengths_must_be_numbers_or_generics() {
+    let undeclared = ESCROW_CODE.replace("Escrow<N>", "Escrow");
+    let err = parse(&undeclared).unwrap_err().to_string();
+    assert!(err.contains("not a generic parameter"), "{}", err);
+
+    let fixed = undeclared.replace("[N]", "[2]");
+    let output = compile_with_options(&fixed, &CompileOptions::default()).unwrap();
+    assert!(output.generics.is_empty());
+    assert!(output.parameters.iter().any(|p| p.name == "parties_1"));
+    assert!(!output.parameters.iter().any(|p| p.name == "parties_2"));
+}
@@ -0,0 +1,146 @@
+#![cfg(feature = "std")]
+
+use arkade_compiler::compile;
+use arkade_compiler::hashes::{self, ripemd160};
+use arkade_compiler::models::{HashFunction, Requirement, Statement};
+use arkade_compiler::opcodes::{OP_EQUALVERIFY, OP_HASH160, OP_HASH256, OP_RIPEMD160, OP_SHA256};
+use arkade_compiler::parser::parse;
+use arkade_compiler::printer::print;
+use arkade_compiler::testing::{self, TestSuite};
+use arkade_compiler::vectors::{self, VectorValue};
+
+fn htlc(function: &str, hash_type: &str) -> String {
+    format!(
+        r#"
+options {{
+  server = server;
+  exit = 144;
+}}
+
+contract Swap(pubkey receiver, {hash_type} hash) {{
+  function claim(signature receiverSig, bytes32 preimage) {{
+    require({function}(preimage) == hash);
+    require(checkSig(receiverSig, receiver));
+  }}
+}}
+"#
+    )
+}
+
+fn claim_asm(code: &str) -> Vec<String> {
+    compile(code)
+        .unwrap()
+        .functions
+        .into_iter()
+        .find(|f| f.name == "claim" && f.server_variant)
+        .unwrap()
+        .asm
+}
+
+#[test]
+fn test_hash_functions_compile_to_their_opcodes() {
+    for (function, hash_type, opcode) in [
+        ("sha256", "bytes32", OP_SHA256),
+        ("hash256", "bytes32", OP_HASH256),
+        ("hash160", "bytes20", OP_HASH160),
+        ("ripemd160", "bytes20", OP_RIPEMD160),
+    ] {
+        let asm = claim_asm(&htlc(function, hash_type));
+        assert_eq!(asm[..4], ["<preimage>", opcode, "<hash>", OP_EQUALVERIFY]);
+    }
+}
+
+#[test]
+fn test_hash_functions_parse_and_print() {
+    let code = htlc("hash160", "bytes20");
+    let contract = parse(&code).unwrap();
+    let Statement::Require(Requirement::HashEqual { function, .. }, _) =
+        &contract.functions[0].statements[0]
+    else {
+        panic!("expected a hash lock");
+    };
+    assert_eq!(*function, HashFunction::Hash160);
+
+    let printed = print(&contract);
+    assert!(printed.contains("require(hash160(preimage) == hash);"));
+    assert_eq!(print(&parse(&printed).unwrap()), printed);
+}
+
+#[test]
+fn test_hash_width_must_match_the_digest() {
+    let warnings = |function: &str, hash_type: &str| -> Vec<String> {
+        compile(&htlc(function, hash_type))
+            .unwrap()
+            .warnings
+            .into_iter()
+            .filter(|w| w.starts_with("warning[type]"))
+            .collect()
+    };
+    assert_eq!(
+        warnings("hash160", "bytes32"),
+        ["warning[type]: fn claim: hash160 comparison: 'hash' has type 'bytes32', expected bytes20"]
+    );
+    assert_eq!(
+        warnings("hash256", "bytes20"),
+        ["warning[type]: fn claim: hash256 comparison: 'hash' has type 'bytes20', expected bytes32"]
+    );
+    assert!(warnings("ripemd160", "bytes20").is_empty());
+}
+
+#[test]
+fn test_ripemd160_digests() {
+    assert_eq!(
+        hex::encode(ripemd160(b"")),
+        "9c1185a5c5e9fc54612808977ee8f548b2258d31"
+    );
+    assert_eq!(
+        hex::encode(ripemd160(b"abc")),
+        "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"
+    );
+    assert_eq!(
+        hex::encode(ripemd160(&[b'a'; 1_000_000])),
+        "52783243c1697bdbe16d37f97f68f08325dc1528"
+    );
+    assert_eq!(
+        hex::encode(hashes::digest(HashFunction::Hash160, b"")),
+        "b472a266d0bd89c13706a4132ccfb16f7c3b9fcb"
+    );
+}
+
+#[test]
+fn test_hash160_locks_run_in_contract_tests() {
+    let code = htlc("hash160", "bytes20");
+    let preimage = [7u8; 32];
+    let hash = hashes::digest(HashFunction::Hash160, &preimage);
+    let suite: TestSuite = serde_json::from_str(&format!(
+        r#"{{
+  "constructorInputs": {{ "hash": "{}" }},
+  "cases": [
+    {{ "name": "preimage", "function": "claim", "witness": {{ "preimage": "{}" }}, "expect": "pass" }},
+    {{ "name": "wrong preimage", "function": "claim", "witness": {{ "preimage": "{}" }}, "expect": "fail" }}
+  ]
+}}"#,
+        hex::encode(&hash),
+        hex::encode(preimage),
+        hex::encode([8u8; 32])
+    ))
+    .unwrap();
+    let report = testing::run(&code, &suite).unwrap();
+    assert_eq!(report.failures(), 0, "{:?}", report.results);
+}
+
+#[test]
+fn test_vectors_match_the_hash_function() {
+    let value = |values: &[VectorValue], name: &str| {
+        hex::decode(&values.iter().find(|v| v.name == name).unwrap().hex).unwrap()
+    };
+    let vectors = vectors::generate(&htlc("ripemd160", "bytes20")).unwrap();
+    let claim = vectors
+        .vectors
+        .iter()
+        .find(|v| v.function == "claim" && v.valid)
+        .unwrap();
+    let hash = value(&vectors.constructor_inputs, "hash");
+    assert_eq!(hash.len(), 20);
+    assert_eq!(ripemd160(&value(&claim.witness, "preimage")).to_vec(), hash);
+}
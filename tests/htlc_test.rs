@@ -1,7 +1,7 @@
 use arkade_compiler::compile;
 use arkade_compiler::opcodes::{
-    OP_2, OP_CHECKLOCKTIMEVERIFY, OP_CHECKSIG, OP_CHECKSIGADD, OP_DROP, OP_EQUAL, OP_NUMEQUAL,
-    OP_SHA256,
+    OP_2, OP_CHECKLOCKTIMEVERIFY, OP_CHECKSIG, OP_CHECKSIGADD, OP_CHECKSIGVERIFY, OP_DROP,
+    OP_EQUALVERIFY, OP_NUMEQUALVERIFY, OP_SHA256,
 };
 use serde_json::Value;
 use std::fs;
@@ -81,16 +81,18 @@ contract HTLC(
     assert_eq!(together_function.function_inputs[1].param_type, "signature");
 
     // Check assembly instructions
-    assert_eq!(together_function.asm.len(), 9);
-    assert_eq!(together_function.asm[0], "<sender>");
-    assert_eq!(together_function.asm[1], OP_CHECKSIG);
-    assert_eq!(together_function.asm[2], "<receiver>");
-    assert_eq!(together_function.asm[3], OP_CHECKSIGADD);
-    assert_eq!(together_function.asm[4], OP_2);
-    assert_eq!(together_function.asm[5], OP_NUMEQUAL);
-    assert_eq!(together_function.asm[6], "<SERVER_KEY>");
-    assert_eq!(together_function.asm[7], "<serverSig>");
-    assert_eq!(together_function.asm[8], OP_CHECKSIG);
+    assert_eq!(together_function.asm.len(), 11);
+    assert_eq!(together_function.asm[0], "<receiverSig>");
+    assert_eq!(together_function.asm[1], "<senderSig>");
+    assert_eq!(together_function.asm[2], "<sender>");
+    assert_eq!(together_function.asm[3], OP_CHECKSIG);
+    assert_eq!(together_function.asm[4], "<receiver>");
+    assert_eq!(together_function.asm[5], OP_CHECKSIGADD);
+    assert_eq!(together_function.asm[6], OP_2);
+    assert_eq!(together_function.asm[7], OP_NUMEQUALVERIFY);
+    assert_eq!(together_function.asm[8], "<serverSig>");
+    assert_eq!(together_function.asm[9], "<SERVER_KEY>");
+    assert_eq!(together_function.asm[10], OP_CHECKSIG);
     // Verify refund function with server variant
     let refund_function = output
         .functions
@@ -100,14 +102,14 @@ contract HTLC(
 
     // Check assembly instructions
     assert_eq!(refund_function.asm.len(), 9);
-    assert_eq!(refund_function.asm[0], "<sender>");
-    assert_eq!(refund_function.asm[1], "<senderSig>");
-    assert_eq!(refund_function.asm[2], OP_CHECKSIG);
-    assert_eq!(refund_function.asm[3], "<refundTime>"); // Variable reference
+    assert_eq!(refund_function.asm[0], "<senderSig>");
+    assert_eq!(refund_function.asm[1], "<sender>");
+    assert_eq!(refund_function.asm[2], OP_CHECKSIGVERIFY);
+    assert_eq!(refund_function.asm[3], "<refundTime>");
     assert_eq!(refund_function.asm[4], OP_CHECKLOCKTIMEVERIFY);
     assert_eq!(refund_function.asm[5], OP_DROP);
-    assert_eq!(refund_function.asm[6], "<SERVER_KEY>");
-    assert_eq!(refund_function.asm[7], "<serverSig>");
+    assert_eq!(refund_function.asm[6], "<serverSig>");
+    assert_eq!(refund_function.asm[7], "<SERVER_KEY>");
     assert_eq!(refund_function.asm[8], OP_CHECKSIG);
 
     // Verify claim function with server variant
@@ -119,15 +121,15 @@ contract HTLC(
 
     // Check assembly instructions
     assert_eq!(claim_function.asm.len(), 10);
-    assert_eq!(claim_function.asm[0], "<receiver>");
-    assert_eq!(claim_function.asm[1], "<receiverSig>");
-    assert_eq!(claim_function.asm[2], OP_CHECKSIG);
+    assert_eq!(claim_function.asm[0], "<receiverSig>");
+    assert_eq!(claim_function.asm[1], "<receiver>");
+    assert_eq!(claim_function.asm[2], OP_CHECKSIGVERIFY);
     assert_eq!(claim_function.asm[3], "<preimage>");
     assert_eq!(claim_function.asm[4], OP_SHA256);
     assert_eq!(claim_function.asm[5], "<hash>");
-    assert_eq!(claim_function.asm[6], OP_EQUAL);
-    assert_eq!(claim_function.asm[7], "<SERVER_KEY>");
-    assert_eq!(claim_function.asm[8], "<serverSig>");
+    assert_eq!(claim_function.asm[6], OP_EQUALVERIFY);
+    assert_eq!(claim_function.asm[7], "<serverSig>");
+    assert_eq!(claim_function.asm[8], "<SERVER_KEY>");
     assert_eq!(claim_function.asm[9], OP_CHECKSIG);
 }
 
@@ -0,0 +1,142 @@
+#![cfg(feature = "std")]
+
+use std::collections::HashMap;
+
+use arkade_compiler::compile;
+use arkade_compiler::encoding::Value;
+use arkade_compiler::linker::{link, SERVER_KEY};
+
+const HTLC: &str = include_str!("../examples/htlc.ark");
+
+const LOCK: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Lock(pubkey sender, pubkey receiver, bytes32 hash, int refundTime) {
+  function claim(signature receiverSig, bytes32 preimage) {
+    require(checkSig(receiverSig, receiver));
+    require(sha256(preimage) == hash);
+  }
+
+  function refund(signature senderSig) {
+    require(checkSig(senderSig, sender));
+    require(tx.time >= refundTime);
+  }
+}
+"#;
+
+fn key(prefix: u8, byte: u8) -> Vec<u8> {
+    let mut key = vec![prefix];
+    key.extend([byte; 32]);
+    key
+}
+
+fn lock_values() -> HashMap<String, Value> {
+    [
+        ("sender", Value::Pubkey(key(0x02, 0x11))),
+        ("receiver", Value::Bytes(key(0x03, 0x22))),
+        ("hash", Value::Bytes(vec![0xab; 32])),
+        ("refundTime", Value::Num(850_000)),
+        (SERVER_KEY, Value::Pubkey(key(0x03, 0x33))),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value))
+    .collect()
+}
+
+fn with(name: &str, value: Value) -> HashMap<String, Value> {
+    let mut values = lock_values();
+    values.insert(name.to_string(), value);
+    values
+}
+
+#[test]
+fn test_instantiate_matches_linking_text() {
+    let artifact = compile(LOCK).unwrap();
+    let instance = artifact.instantiate(lock_values()).unwrap();
+
+    let args = [
+        ("sender", format!("02{}", "11".repeat(32))),
+        ("receiver", format!("03{}", "22".repeat(32))),
+        ("hash", "ab".repeat(32)),
+        ("refundTime", "850000".to_string()),
+        (SERVER_KEY, format!("03{}", "33".repeat(32))),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value))
+    .collect();
+    assert_eq!(
+        serde_json::to_value(&instance).unwrap(),
+        serde_json::to_value(link(&artifact, &args).unwrap()).unwrap()
+    );
+
+    assert!(instance.parameters.is_empty());
+    assert!(instance.functions.iter().all(|f| f.script_hex.is_some()));
+    let refund = instance
+        .functions
+        .iter()
+        .find(|f| f.name == "refund" && f.server_variant)
+        .unwrap();
+    assert_eq!(refund.asm[1], format!("02{}", "11".repeat(32)));
+    assert!(refund.asm.contains(&"850000".to_string()));
+}
+
+#[test]
+fn test_instantiate_checks_types_and_lengths() {
+    let artifact = compile(LOCK).unwrap();
+    for (name, value, error) in [
+        (
+            "sender",
+            Value::Num(1),
+            "Argument 'sender': expected a value of type pubkey, got an integer",
+        ),
+        (
+            "hash",
+            Value::Bytes(vec![0xab; 20]),
+            "Argument 'hash': expected 32 bytes for bytes32, got 20",
+        ),
+        (
+            "receiver",
+            Value::Pubkey(key(0x04, 0x22)),
+            "Argument 'receiver': expected a compressed public key",
+        ),
+        (
+            "refundTime",
+            Value::Bytes(vec![0x01]),
+            "Argument 'refundTime': expected a value of type int, got bytes",
+        ),
+        (
+            SERVER_KEY,
+            Value::Bytes(vec![0x33; 32]),
+            "Server key: expected 33 bytes for pubkey, got 32",
+        ),
+        (
+            "owner",
+            Value::Num(1),
+            "Contract Lock has no parameter 'owner'",
+        ),
+    ] {
+        assert_eq!(artifact.instantiate(with(name, value)).unwrap_err(), error);
+    }
+}
+
+#[test]
+fn test_instantiate_needs_every_leaf_concrete() {
+    let artifact = compile(LOCK).unwrap();
+    let mut values = lock_values();
+    values.remove(SERVER_KEY);
+    let err = artifact.instantiate(values.clone()).unwrap_err();
+    assert!(err.contains("<SERVER_KEY> is not linked"), "{}", err);
+
+    // HTLC's leaves, its multisig included, are all concrete
+    let instance = compile(HTLC).unwrap().instantiate(lock_values());
+    assert!(instance.is_ok(), "{:?}", instance.err());
+
+    values.remove("hash");
+    assert_eq!(
+        artifact.instantiate(values).unwrap_err(),
+        "Missing argument 'hash'"
+    );
+}
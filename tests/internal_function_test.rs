@@ -0,0 +1,117 @@
+use arkade_compiler::compile;
+use arkade_compiler::diagnostics::classify;
+use arkade_compiler::parser::parse;
+
+const CALLING_CODE: &str = r#"
+contract Payout(pubkey owner, pubkey backup, int amount) {
+  function paid(int index, pubkey key) internal {
+    int value = tx.outputs[index].value;
+    require(value >= amount);
+    require(tx.outputs[index].scriptPubKey == new P2TR(key));
+  }
+
+  function spend(signature ownerSig) {
+    paid(0, owner);
+    require(checkSig(ownerSig, owner));
+  }
+
+  function recover(signature backupSig) {
+    int value = 1;
+    paid(value, backup);
+    require(checkSig(backupSig, backup));
+  }
+}
+"#;
+
+const INLINED_CODE: &str = r#"
+contract Payout(pubkey owner, pubkey backup, int amount) {
+  function spend(signature ownerSig) {
+    int value_paid1 = tx.outputs[0].value;
+    require(value_paid1 >= amount);
+    require(tx.outputs[0].scriptPubKey == new P2TR(owner));
+    require(checkSig(ownerSig, owner));
+  }
+
+  function recover(signature backupSig) {
+    int value = 1;
+    int value_paid2 = tx.outputs[value].value;
+    require(value_paid2 >= amount);
+    require(tx.outputs[value].scriptPubKey == new P2TR(backup));
+    require(checkSig(backupSig, backup));
+  }
+}
+"#;
+
+#[test]
+fn test_internal_functions_are_inlined_into_their_callers() {
+    let calling = compile(CALLING_CODE).unwrap();
+    let inlined = compile(INLINED_CODE).unwrap();
+
+    // The internal function gets no leaves of its own
+    assert_eq!(calling.functions.len(), inlined.functions.len());
+    for (c, i) in calling.functions.iter().zip(&inlined.functions) {
+        assert_eq!(c.name, i.name);
+        assert_eq!(c.asm, i.asm);
+    }
+}
+
+#[test]
+fn test_internal_functions_can_be_called_before_their_definition() {
+    let code = r#"
+contract Vault(pubkey owner) {
+  function spend(signature ownerSig) {
+    signed(ownerSig);
+  }
+
+  function signed(signature sig) internal {
+    require(checkSig(sig, owner));
+  }
+}
+"#;
+    let contract = parse(code).unwrap();
+    assert_eq!(contract.functions[0].statements.len(), 1);
+    let artifact = compile(code).unwrap();
+    assert!(artifact.functions[0]
+        .asm
+        .contains(&"<ownerSig>".to_string()));
+}
+
+#[test]
+fn test_fuji_safe_inlines_its_helper() {
+    let artifact = compile(include_str!("../examples/fuji_safe.ark")).unwrap();
+    let redeem = artifact
+        .functions
+        .iter()
+        .find(|f| f.name == "redeem" && f.server_variant)
+        .unwrap();
+    assert!(redeem
+        .asm
+        .contains(&"<VTXO:P2TR(<borrowerPk>,<assetCommitmentHash>)>".to_string()));
+    assert!(artifact
+        .functions
+        .iter()
+        .all(|f| f.name != "verifyFujiBurning"));
+}
+
+#[test]
+fn test_call_errors() {
+    let public = CALLING_CODE.replace("paid(0, owner);", "recover(ownerSig);");
+    let arity = CALLING_CODE.replace("paid(0, owner);", "paid(0);");
+    let recursive = CALLING_CODE.replace(
+        "    int value = tx.outputs[index].value;\n",
+        "    paid(index, key);\n",
+    );
+    for (code, message, error_code) in [
+        (
+            public,
+            "'recover' is not a macro or an internal function, so it cannot be called",
+            "E0004",
+        ),
+        (arity, "Function 'paid' takes 2 argument(s), got 1", "E0004"),
+        (recursive, "Function 'paid' calls itself", "E0004"),
+    ] {
+        let err = parse(&code).unwrap_err().to_string();
+        assert_eq!(err, message);
+        assert_eq!(classify("compile", &err), error_code);
+    }
+}
@@ -0,0 +1,392 @@
+use arkade_compiler::ir::{self, LeafContext};
+use arkade_compiler::opcodes::{
+    OP_FROMALTSTACK, OP_INSPECTOUTASSETLOOKUP, OP_INSPECTOUTPUTVALUE, OP_TOALTSTACK,
+};
+use arkade_compiler::{compile, compile_with_options, CompileOptions};
+
+/// Two requirements reading the same asset amount, and a let binding that is
+/// only used once.
+const BOUNDED_CODE: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Bounded(pubkey owner, bytes32 tokenId, int minAmount, int maxAmount) {
+  function spend(signature ownerSig) {
+    require(checkSig(ownerSig, owner));
+    require(tx.outputs[0].assets.lookup(tokenId) >= minAmount, "too low");
+    require(tx.outputs[0].assets.lookup(tokenId) <= maxAmount, "too high");
+  }
+
+  function floor(signature ownerSig) {
+    let out = tx.outputs[0].value;
+    require(out >= minAmount, "too low");
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+
+fn optimized() -> arkade_compiler::ContractJson {
+    compile_with_options(
+        BOUNDED_CODE,
+        &CompileOptions {
+            optimize: true,
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+fn asm_of(output: &arkade_compiler::ContractJson, name: &str, server_variant: bool) -> Vec<String> {
+    output
+        .functions
+        .iter()
+        .find(|f| f.name == name && f.server_variant == server_variant)
+        .unwrap()
+        .asm
+        .clone()
+}
+
+#[test]
+fn test_optimizer_is_opt_in() {
+    let output = compile(BOUNDED_CODE).unwrap();
+    assert!(output.optimizations.is_empty());
+
+    let spend = asm_of(&output, "spend", true);
+    let lookups = spend
+        .iter()
+        .filter(|op| *op == OP_INSPECTOUTASSETLOOKUP)
+        .count();
+    assert_eq!(lookups, 2);
+}
+
+#[test]
+fn test_duplicate_introspection_read_is_recalled() {
+    let output = optimized();
+    let spend = asm_of(&output, "spend", true);
+
+    let lookups = spend
+        .iter()
+        .filter(|op| *op == OP_INSPECTOUTASSETLOOKUP)
+        .count();
+    assert_eq!(lookups, 1, "lookup not deduplicated: {:?}", spend);
+
+    let stash = spend.iter().position(|op| op == OP_TOALTSTACK).unwrap();
+    let recall = spend.iter().position(|op| op == OP_FROMALTSTACK).unwrap();
+    assert!(stash < recall);
+    assert_eq!(spend[recall + 1], "<maxAmount>");
+
+    // The sentinel guard runs once, before the value is stashed
+    let guard = spend.iter().filter(|op| *op == "OP_1NEGATE").count();
+    assert_eq!(guard, 1);
+}
+
+#[test]
+fn test_let_binding_is_propagated() {
+    let output = optimized();
+    let floor = asm_of(&output, "floor", true);
+
+    assert!(!floor.contains(&"<out>".to_string()), "{:?}", floor);
+    let reads = floor
+        .iter()
+        .filter(|op| *op == OP_INSPECTOUTPUTVALUE)
+        .count();
+    assert_eq!(reads, 1);
+}
+
+#[test]
+fn test_optimization_report() {
+    let output = optimized();
+    // 2 functions x 2 variants
+    assert_eq!(output.optimizations.len(), 4);
+
+    let spend = output
+        .optimizations
+        .iter()
+        .find(|r| r.function == "spend" && r.server_variant)
+        .unwrap();
+    assert!(spend.size_after < spend.size_before);
+
+    // The exit path is a plain N-of-N chain with nothing to optimize
+    let exit = output
+        .optimizations
+        .iter()
+        .find(|r| r.function == "spend" && !r.server_variant)
+        .unwrap();
+    assert_eq!(exit.size_after, exit.size_before);
+
+    let json = serde_json::to_string(&output).unwrap();
+    assert!(json.contains("\"sizeBefore\""));
+    assert!(json.contains("\"sizeAfter\""));
+}
+
+#[test]
+fn test_reads_inside_branches_are_not_merged() {
+    let code = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Branchy(pubkey owner, bytes32 tokenId, int minAmount) {
+  function spend(signature ownerSig, int mode) {
+    if (mode == 0) {
+      require(tx.outputs[0].assets.lookup(tokenId) >= minAmount, "low");
+    } else {
+      require(tx.outputs[0].assets.lookup(tokenId) >= minAmount, "low");
+    }
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let output = compile_with_options(
+        code,
+        &CompileOptions {
+            optimize: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let spend = asm_of(&output, "spend", true);
+    assert!(!spend.contains(&OP_TOALTSTACK.to_string()), "{:?}", spend);
+}
+
+#[test]
+fn test_constant_arithmetic_is_folded() {
+    let code = r#"
+contract Epochs(pubkey owner, int epochBlocks) {
+  function spend(signature ownerSig) {
+    let window = 144 * 2 * 3;
+    int delay = epochBlocks + 0;
+    int scaled = 1 * epochBlocks - 0;
+    int negated = 0 - epochBlocks;
+    require(tx.outputs[0].value >= window);
+    require(tx.input.current.sequence >= delay);
+    require(tx.outputs[1].value >= scaled);
+    require(tx.outputs[2].value >= negated);
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let unoptimized = asm_of(&compile(code).unwrap(), "spend", true);
+    assert_eq!(unoptimized.iter().filter(|op| *op == "OP_MUL64").count(), 3);
+
+    let output = compile_with_options(
+        code,
+        &CompileOptions {
+            optimize: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let spend = asm_of(&output, "spend", true).join(" ");
+    assert!(
+        spend.starts_with("0 OP_INSPECTOUTPUTVALUE 0x6003000000000000 "),
+        "{}",
+        spend
+    );
+    assert!(!spend.contains("OP_MUL64"), "{}", spend);
+    // Identities are dropped, and with them the conversion of an int
+    // operand to u64le and back
+    assert!(
+        spend.contains("OP_INSPECTINPUTSEQUENCE <epochBlocks> OP_GREATERTHANOREQUAL "),
+        "{}",
+        spend
+    );
+    assert!(
+        spend.contains(
+            "1 OP_INSPECTOUTPUTVALUE <epochBlocks> OP_SCRIPTNUMTOLE64 OP_GREATERTHANOREQUAL64"
+        ),
+        "{}",
+        spend
+    );
+    // Subtracting from zero negates, so it stays
+    assert_eq!(spend.matches("OP_SUB64").count(), 1, "{}", spend);
+    assert!(spend.contains("0x0000000000000000 <epochBlocks> OP_SCRIPTNUMTOLE64 OP_SUB64"));
+}
+
+#[test]
+fn test_only_u64le_literals_are_folded() {
+    let leaf = |asm: &[&str]| {
+        let asm: Vec<String> = asm.iter().map(|t| t.to_string()).collect();
+        ir::optimize(&asm, &LeafContext::default()).asm
+    };
+    // Script numbers are not operands of the 64-bit opcodes
+    assert_eq!(
+        leaf(&["144", "2", "OP_MUL64", "OP_VERIFY"]),
+        ["144", "2", "OP_MUL64", "OP_VERIFY"]
+    );
+    // Converted literals are pushed converted, and then folded
+    assert_eq!(
+        leaf(&[
+            "144",
+            "OP_SCRIPTNUMTOLE64",
+            "0x0200000000000000",
+            "OP_MUL64",
+            "OP_VERIFY",
+            "OP_LE64TOSCRIPTNUM",
+        ]),
+        ["288"]
+    );
+    // A value out of the 4-byte range fails the conversion, so it stays
+    assert_eq!(
+        leaf(&["0x0000000001000000", "OP_LE64TOSCRIPTNUM"]),
+        ["0x0000000001000000", "OP_LE64TOSCRIPTNUM"]
+    );
+}
+
+#[test]
+fn test_peephole_removes_redundant_pairs() {
+    let asm: Vec<String> = [
+        "<amount>",
+        "OP_SCRIPTNUMTOLE64",
+        "OP_LE64TOSCRIPTNUM",
+        "OP_DUP",
+        "OP_TOALTSTACK",
+        "OP_FROMALTSTACK",
+        "OP_DROP",
+        "<limit>",
+        "OP_NUMEQUAL",
+        "OP_VERIFY",
+        "<owner>",
+        "<ownerSig>",
+        "OP_CHECKSIG",
+        "OP_VERIFY",
+        "OP_1",
+    ]
+    .map(String::from)
+    .to_vec();
+    let leaf = ir::optimize_peephole(&asm, &LeafContext::default());
+    // Removing the alt stack round trip exposes the DUP and DROP
+    assert_eq!(
+        leaf.asm,
+        [
+            "<amount>",
+            "<limit>",
+            "OP_NUMEQUALVERIFY",
+            "<owner>",
+            "<ownerSig>",
+            "OP_CHECKSIGVERIFY",
+            "OP_1"
+        ]
+    );
+    assert_eq!(leaf.size_before - leaf.size_after, 8);
+}
+
+#[test]
+fn test_peephole_only() {
+    let code = r#"
+contract Pay(pubkey owner, int amount) {
+  function spend(signature ownerSig) {
+    require(tx.outputs[0].value == amount);
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let output = compile_with_options(
+        code,
+        &CompileOptions {
+            peephole: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let spend = asm_of(&output, "spend", true).join(" ");
+    assert!(
+        spend.starts_with(
+            "0 OP_INSPECTOUTPUTVALUE <amount> OP_SCRIPTNUMTOLE64 OP_EQUALVERIFY <ownerSig>"
+        ),
+        "{}",
+        spend
+    );
+    // Checks are generated in their VERIFY form, leaving the pass nothing
+    let report = &output.optimizations[0];
+    assert_eq!(report.size_before, report.size_after);
+
+    // The rest of the pipeline does not run
+    let output = compile_with_options(
+        BOUNDED_CODE,
+        &CompileOptions {
+            peephole: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert!(!asm_of(&output, "spend", true).contains(&OP_TOALTSTACK.to_string()));
+}
+
+#[test]
+fn test_opt_level_cli_flag() {
+    use std::fs;
+    use tempfile::tempdir;
+
+    let temp_dir = tempdir().unwrap();
+    let input_path = temp_dir.path().join("bounded.ark");
+    fs::write(&input_path, BOUNDED_CODE).unwrap();
+
+    let run = |args: &[&str]| {
+        let output_path = temp_dir.path().join("bounded.json");
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_arkadec"))
+            .arg(input_path.to_str().unwrap())
+            .arg("-o")
+            .arg(output_path.to_str().unwrap())
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        (
+            output.status.success(),
+            stderr,
+            fs::read_to_string(&output_path).unwrap_or_default(),
+        )
+    };
+
+    let (ok, stderr, json) = run(&["--opt-level", "0"]);
+    assert!(ok, "{}", stderr);
+    assert!(!json.contains("\"optimizations\""));
+
+    let (ok, stderr, json) = run(&["--opt-level", "1"]);
+    assert!(ok, "{}", stderr);
+    assert!(stderr.contains("optimized spend (server)"), "{}", stderr);
+    assert!(!json.contains(OP_FROMALTSTACK));
+
+    let (ok, stderr, json) = run(&["--opt-level", "2"]);
+    assert!(ok, "{}", stderr);
+    assert!(json.contains(OP_FROMALTSTACK));
+
+    let (ok, stderr, _) = run(&["--opt-level", "3"]);
+    assert!(!ok);
+    assert!(stderr.contains("--opt-level"), "{}", stderr);
+}
+
+#[test]
+fn test_optimize_cli_flag() {
+    use std::fs;
+    use tempfile::tempdir;
+
+    let temp_dir = tempdir().unwrap();
+    let input_path = temp_dir.path().join("bounded.ark");
+    let output_path = temp_dir.path().join("bounded.json");
+    fs::write(&input_path, BOUNDED_CODE).unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_arkadec"))
+        .arg(input_path.to_str().unwrap())
+        .arg("-o")
+        .arg(output_path.to_str().unwrap())
+        .arg("--optimize")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "CLI failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("optimized spend (server)"), "{}", stderr);
+
+    let json = fs::read_to_string(&output_path).unwrap();
+    assert!(json.contains("\"optimizations\""));
+    assert!(json.contains(OP_FROMALTSTACK));
+}
@@ -0,0 +1,387 @@
+#![cfg(feature = "std")]
+
+use arkade_compiler::interpreter::{LeafEvaluator, TxContext, TxInput, TxOutput};
+use arkade_compiler::linker::SERVER_KEY;
+use arkade_compiler::models::ContractJson;
+use arkade_compiler::{compile, compile_with_options, CompileOptions};
+use k256::schnorr::SigningKey;
+use sha2::{Digest, Sha256};
+
+const SWAP: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Swap(pubkey sender, pubkey receiver, bytes32 hash, int refundTime) {
+  function claim(signature receiverSig, bytes32 preimage) {
+    require(checkSig(receiverSig, receiver));
+    require(sha256(preimage) == hash);
+  }
+
+  function refund(signature senderSig) {
+    require(checkSig(senderSig, sender));
+    require(tx.time >= refundTime);
+  }
+}
+"#;
+
+const SIGHASH: [u8; 32] = [0x07; 32];
+const PREIMAGE: [u8; 32] = [0x42; 32];
+const SENDER: u8 = 1;
+const RECEIVER: u8 = 2;
+const SERVER: u8 = 3;
+
+fn signer(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32]).unwrap()
+}
+
+/// The signer's key, compressed, as a constructor argument takes it
+fn pubkey(seed: u8) -> String {
+    format!("02{}", hex::encode(signer(seed).verifying_key().to_bytes()))
+}
+
+fn sign(seed: u8) -> String {
+    hex::encode(
+        signer(seed)
+            .sign_raw(&SIGHASH, &[0u8; 32])
+            .unwrap()
+            .to_bytes(),
+    )
+}
+
+fn tx(locktime: u32, sequence: u32) -> TxContext {
+    TxContext::builder()
+        .sighash(SIGHASH)
+        .locktime(locktime)
+        .input(TxInput::new(10_000, vec![0x51, 0x20]).sequence(sequence))
+        .build()
+        .unwrap()
+}
+
+fn swap<'a>(artifact: &'a ContractJson, function: &str, server_variant: bool) -> LeafEvaluator<'a> {
+    LeafEvaluator::for_function(artifact, function, server_variant)
+        .unwrap()
+        .param("sender", pubkey(SENDER))
+        .param("receiver", pubkey(RECEIVER))
+        .param("hash", hex::encode(Sha256::digest(PREIMAGE)))
+        .param("refundTime", "850000")
+        .param(SERVER_KEY, pubkey(SERVER))
+}
+
+#[test]
+fn test_cooperative_leaf_runs_with_the_server_signature() {
+    let artifact = compile(SWAP).unwrap();
+    let claim = swap(&artifact, "claim", true)
+        .arg("receiverSig", sign(RECEIVER))
+        .arg("preimage", hex::encode(PREIMAGE))
+        .arg("serverSig", sign(SERVER));
+    let tx = tx(0, u32::MAX);
+    assert_eq!(claim.run(&tx), Ok(()));
+    assert_eq!(claim.execute(&tx).unwrap(), [vec![1]]);
+
+    let err = claim
+        .clone()
+        .arg("preimage", "00".repeat(32))
+        .run(&tx)
+        .unwrap_err();
+    assert!(err.contains("OP_EQUALVERIFY"), "{}", err);
+}
+
+#[test]
+fn test_signatures_are_checked_against_the_sighash() {
+    let artifact = compile(SWAP).unwrap();
+    // The sender cannot sign for the receiver
+    let err = swap(&artifact, "claim", true)
+        .arg("receiverSig", sign(SENDER))
+        .arg("preimage", hex::encode(PREIMAGE))
+        .arg("serverSig", sign(SERVER))
+        .run(&tx(0, u32::MAX))
+        .unwrap_err();
+    assert!(
+        err.contains("OP_CHECKSIGVERIFY): invalid signature"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_timelocks_read_the_mock_transaction() {
+    let artifact = compile(SWAP).unwrap();
+    let refund = swap(&artifact, "refund", true)
+        .arg("senderSig", sign(SENDER))
+        .arg("serverSig", sign(SERVER));
+    assert_eq!(refund.run(&tx(850_000, 0xffff_fffe)), Ok(()));
+    let err = refund.run(&tx(849_999, 0xffff_fffe)).unwrap_err();
+    assert!(err.contains("locktime 850000 not reached"), "{}", err);
+
+    // The exit path waits out the exit delay instead of the server
+    let exit = swap(&artifact, "claim", false)
+        .arg("receiverSig", sign(RECEIVER))
+        .arg("preimage", hex::encode(PREIMAGE));
+    assert_eq!(exit.run(&tx(0, 144)), Ok(()));
+    let err = exit.run(&tx(0, 100)).unwrap_err();
+    assert!(err.contains("relative timelock 144 not reached"), "{}", err);
+}
+
+#[test]
+fn test_a_leaf_must_leave_one_true_element() {
+    let artifact = compile(SWAP).unwrap();
+    // The exit path's last check is the hash lock, left unverified
+    let exit = swap(&artifact, "claim", false)
+        .arg("receiverSig", sign(RECEIVER))
+        .arg("preimage", "00".repeat(32));
+    assert_eq!(exit.execute(&tx(0, 144)).unwrap(), [Vec::<u8>::new()]);
+    assert_eq!(
+        exit.run(&tx(0, 144)),
+        Err("the leaf left false".to_string())
+    );
+
+    // A witness element no requirement reads stays on the stack
+    let source = SWAP.replace("signature senderSig", "signature senderSig, int nonce");
+    let artifact = compile(&source).unwrap();
+    let refund = swap(&artifact, "refund", false)
+        .arg("senderSig", sign(SENDER))
+        .arg("nonce", "7");
+    assert_eq!(refund.execute(&tx(850_000, 144)).unwrap().len(), 2);
+    assert_eq!(
+        refund.run(&tx(850_000, 144)),
+        Err("the leaf left 2 stack elements instead of one".to_string())
+    );
+}
+
+#[test]
+fn test_missing_values_are_reported() {
+    let artifact = compile(SWAP).unwrap();
+    let claim = LeafEvaluator::for_function(&artifact, "claim", true)
+        .unwrap()
+        .params([
+            ("sender", pubkey(SENDER)),
+            ("receiver", pubkey(RECEIVER)),
+            ("hash", "00".repeat(32)),
+            ("refundTime", "1".to_string()),
+        ])
+        .args([("receiverSig", sign(RECEIVER)), ("serverSig", sign(SERVER))]);
+    assert_eq!(
+        claim.run(&tx(0, u32::MAX)),
+        Err("fn claim (server variant): missing witness element 'preimage'".to_string())
+    );
+    assert_eq!(
+        claim.arg("preimage", "00".repeat(32)).run(&tx(0, u32::MAX)),
+        Err("<SERVER_KEY> has no value to run with".to_string())
+    );
+    assert_eq!(
+        LeafEvaluator::for_function(&artifact, "settle", true).unwrap_err(),
+        "Contract Swap has no server variant of function 'settle'"
+    );
+}
+
+const ARITHMETIC: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Arithmetic(int a) {
+  function double(int x) {
+    require(x * 2 >= a);
+  }
+
+  function remainder(int x) {
+    require(x % 3 == 1);
+  }
+}
+"#;
+
+fn arithmetic<'a>(artifact: &'a ContractJson, function: &str, x: &str) -> LeafEvaluator<'a> {
+    LeafEvaluator::for_function(artifact, function, false)
+        .unwrap()
+        .param("a", "10")
+        .arg("x", x)
+}
+
+#[test]
+fn test_arithmetic_runs_on_64_bit_operands() {
+    let artifact = compile(ARITHMETIC).unwrap();
+    let tx = tx(0, 144);
+    assert_eq!(arithmetic(&artifact, "double", "5").run(&tx), Ok(()));
+    assert_eq!(arithmetic(&artifact, "double", "6").run(&tx), Ok(()));
+    assert_eq!(
+        arithmetic(&artifact, "double", "4").run(&tx),
+        Err("the leaf left false".to_string())
+    );
+
+    assert_eq!(arithmetic(&artifact, "remainder", "7").run(&tx), Ok(()));
+    assert_eq!(arithmetic(&artifact, "remainder", "1").run(&tx), Ok(()));
+    assert_eq!(
+        arithmetic(&artifact, "remainder", "8").run(&tx),
+        Err("the leaf left false".to_string())
+    );
+}
+
+const EPOCHS: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Epochs(int epochBlocks) {
+  function spend() {
+    require(tx.outputs[0].value >= 144 * 2 * 3);
+    require(tx.outputs[1].value >= 1 * epochBlocks + 0);
+    require(tx.outputs[2].value >= 0 - epochBlocks + 200);
+  }
+}
+"#;
+
+#[test]
+fn test_optimized_leaves_run_as_compiled() {
+    let unoptimized = compile(EPOCHS).unwrap();
+    let optimized = compile_with_options(
+        EPOCHS,
+        &CompileOptions {
+            optimize: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert!(optimized.optimizations[0].size_after < optimized.optimizations[0].size_before);
+
+    let run = |artifact: &ContractJson, values: [u64; 3]| {
+        let mut tx = TxContext::builder()
+            .sighash(SIGHASH)
+            .input(TxInput::new(10_000, vec![0x51, 0x20]));
+        for value in values {
+            tx = tx.output(TxOutput::new(value, vec![0x51]));
+        }
+        LeafEvaluator::for_function(artifact, "spend", true)
+            .unwrap()
+            .param("epochBlocks", "100")
+            .param(SERVER_KEY, pubkey(SERVER))
+            .arg("serverSig", sign(SERVER))
+            .run(&tx.build().unwrap())
+    };
+    for (values, holds) in [
+        ([864, 100, 100], true),
+        ([863, 100, 100], false),
+        ([864, 99, 100], false),
+        ([864, 100, 99], false),
+    ] {
+        assert_eq!(run(&unoptimized, values).is_ok(), holds, "{:?}", values);
+        assert_eq!(run(&optimized, values).is_ok(), holds, "{:?}", values);
+    }
+}
+
+const BOUNDS: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Bounds(int unused) {
+  function between(int x) {
+    require(x > 1);
+    require(x < 10);
+  }
+
+  function either(int x, int y) {
+    require(x == 1 && y != 2);
+    require(!(x == 3) || y == 4);
+  }
+
+  function offset(int x) {
+    let d = x - 5;
+    require(d >= 0);
+    require(d * 2 < x);
+  }
+}
+"#;
+
+#[test]
+fn test_witness_elements_are_read_as_often_as_the_source_reads_them() {
+    let artifact = compile(BOUNDS).unwrap();
+    let tx = tx(0, 144);
+    let between = |x: &str| {
+        LeafEvaluator::for_function(&artifact, "between", false)
+            .unwrap()
+            .param("unused", "0")
+            .arg("x", x)
+            .run(&tx)
+    };
+    assert_eq!(between("5"), Ok(()));
+    assert_eq!(between("2"), Ok(()));
+    assert_eq!(between("9"), Ok(()));
+    assert!(between("1").is_err());
+    assert!(between("10").is_err());
+
+    let either = |x: &str, y: &str| {
+        LeafEvaluator::for_function(&artifact, "either", false)
+            .unwrap()
+            .param("unused", "0")
+            .arg("x", x)
+            .arg("y", y)
+            .run(&tx)
+    };
+    assert_eq!(either("1", "3"), Ok(()));
+    assert_eq!(either("1", "4"), Ok(()));
+    assert!(either("1", "2").is_err());
+    assert!(either("2", "3").is_err());
+
+    let offset = |x: &str| {
+        LeafEvaluator::for_function(&artifact, "offset", false)
+            .unwrap()
+            .param("unused", "0")
+            .arg("x", x)
+            .run(&tx)
+    };
+    assert_eq!(offset("5"), Ok(()));
+    assert_eq!(offset("9"), Ok(()));
+    assert!(offset("4").is_err());
+    assert!(offset("10").is_err());
+}
+
+const BOARD: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Board(pubkey[3] members) {
+  function approve(signature[3] sigs) {
+    require(checkMultisig(members, 2));
+  }
+
+  function all(signature[3] sigs) {
+    require(checkMultisig(members));
+  }
+}
+"#;
+
+#[test]
+fn test_multisig_over_an_array_reads_the_signature_array() {
+    let artifact = compile(BOARD).unwrap();
+    let all = artifact
+        .functions
+        .iter()
+        .find(|f| f.name == "all" && f.server_variant)
+        .unwrap();
+    // The chain consumes the signatures first, the server's last
+    let witness: Vec<&str> = all.witness_schema.iter().map(|w| w.name.as_str()).collect();
+    assert_eq!(witness, ["sigs_0", "sigs_1", "sigs_2", "serverSig"]);
+
+    let board = |sigs: [u8; 3]| {
+        LeafEvaluator::for_function(&artifact, "all", true)
+            .unwrap()
+            .param("members_0", pubkey(SENDER))
+            .param("members_1", pubkey(RECEIVER))
+            .param("members_2", pubkey(4))
+            .param(SERVER_KEY, pubkey(SERVER))
+            .arg("sigs_0", sign(sigs[0]))
+            .arg("sigs_1", sign(sigs[1]))
+            .arg("sigs_2", sign(sigs[2]))
+            .arg("serverSig", sign(SERVER))
+    };
+    let tx = tx(0, u32::MAX);
+    assert_eq!(board([SENDER, RECEIVER, 4]).run(&tx), Ok(()));
+    assert!(board([RECEIVER, SENDER, 4]).run(&tx).is_err());
+}
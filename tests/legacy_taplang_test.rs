@@ -0,0 +1,141 @@
+use arkade_compiler::compiler::{compile_with_options, CompileOptions};
+use arkade_compiler::legacy;
+use arkade_compiler::opcodes::{OP_CHECKMULTISIG, OP_CHECKSIGADD};
+
+const TAPLANG: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Joint(pubkey alice, pubkey bob, pubkey carol) {
+  function spend(signature aliceSig, signature bobSig) {
+    require(checkMultisig([alice, bob, carol], [aliceSig, bobSig]));
+  }
+}
+"#;
+
+fn legacy_options() -> CompileOptions {
+    CompileOptions {
+        legacy_taplang: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_signature_arrays_become_a_threshold() {
+    let artifact = compile_with_options(TAPLANG, &legacy_options()).unwrap();
+    let spend = artifact
+        .functions
+        .iter()
+        .find(|f| f.server_variant)
+        .unwrap();
+    // The signatures are read up front, then checked key by key
+    let chain = spend.asm.iter().position(|t| t == "<alice>").unwrap();
+    assert_eq!(
+        spend.asm[chain..chain + 6],
+        [
+            "<alice>",
+            "OP_CHECKSIG",
+            "<bob>",
+            OP_CHECKSIGADD,
+            "<carol>",
+            OP_CHECKSIGADD
+        ]
+    );
+    assert_eq!(spend.asm[chain + 6], "OP_2");
+    let witness: Vec<&str> = spend
+        .witness_schema
+        .iter()
+        .map(|w| w.name.as_str())
+        .collect();
+    // Carol, who has no signature, gets an element to leave empty
+    assert_eq!(witness, ["aliceSig", "bobSig", "carolSig", "serverSig"]);
+    assert!(artifact.warnings.is_empty(), "{:?}", artifact.warnings);
+
+    // Without the mode, the call is compiled as it was, with a warning
+    let artifact = compile_with_options(TAPLANG, &CompileOptions::default()).unwrap();
+    assert!(artifact.warnings[0].starts_with("warning[deprecated]"));
+}
+
+#[test]
+fn test_more_signatures_than_keys_is_rejected() {
+    let source = TAPLANG.replace("[aliceSig, bobSig]", "[aliceSig, bobSig, aliceSig, bobSig]");
+    let err = compile_with_options(&source, &legacy_options())
+        .unwrap_err()
+        .to_string();
+    assert!(err.starts_with("fn spend: cannot translate"), "{}", err);
+}
+
+#[test]
+fn test_upgrade_recompiles_the_embedded_source() {
+    // An HTLC artifact as TapLang wrote it, with the multisig path on
+    // OP_CHECKMULTISIG
+    let artifact = serde_json::json!({
+        "contractName": "HTLC",
+        "source": include_str!("../examples/htlc.ark"),
+        "compiler": { "name": "taplang", "version": "0.1.0" },
+        "functions": [{
+            "name": "together",
+            "asm": ["OP_2", "<sender>", "<receiver>", "OP_2", OP_CHECKMULTISIG],
+        }],
+    });
+    let upgraded = legacy::upgrade(&artifact.to_string(), &CompileOptions::default()).unwrap();
+    assert_eq!(upgraded.name, "HTLC");
+    assert_eq!(upgraded.compiler.unwrap().name, "arkade-compiler");
+    for function in &upgraded.functions {
+        assert!(!function.asm.iter().any(|op| op == OP_CHECKMULTISIG));
+        assert!(!function.witness_schema.is_empty());
+    }
+
+    let taplang = serde_json::json!({
+        "contractName": "Joint",
+        "source": TAPLANG,
+        "compiler": { "name": "taplang", "version": "0.1.0" },
+    });
+    let upgraded = legacy::upgrade(&taplang.to_string(), &CompileOptions::default()).unwrap();
+    assert_eq!(upgraded.functions.len(), 2);
+}
+
+#[test]
+fn test_upgrade_needs_the_source() {
+    let err = legacy::upgrade(
+        r#"{ "contractName": "Joint", "functions": [] }"#,
+        &CompileOptions::default(),
+    )
+    .unwrap_err();
+    assert_eq!(err, "The artifact of Joint has no source to recompile");
+}
+
+#[test]
+fn test_legacy_taplang_cli() {
+    use std::fs;
+    use tempfile::tempdir;
+
+    let temp_dir = tempdir().unwrap();
+    let input_path = temp_dir.path().join("joint.tap");
+    let output_path = temp_dir.path().join("joint.json");
+    fs::write(&input_path, TAPLANG).unwrap();
+    let run = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_arkadec"))
+            .arg(input_path.to_str().unwrap())
+            .arg("-o")
+            .arg(&output_path)
+            .args(args)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    assert!(!run(&[]).status.success());
+    assert!(run(&["--legacy-taplang"]).status.success());
+    let json = fs::read_to_string(&output_path).unwrap();
+    assert!(json.contains(OP_CHECKSIGADD));
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_arkadec"))
+        .args(["upgrade", "examples/htlc.json"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    let upgraded: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(upgraded["functions"][0]["witnessSchema"].is_array());
+}
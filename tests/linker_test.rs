@@ -0,0 +1,351 @@
+#![cfg(feature = "std")]
+
+use std::collections::HashMap;
+use std::fs;
+
+use arkade_compiler::compile;
+use arkade_compiler::linker::{
+    descriptor, leaf_scripts, link, link_with_address, parse_params, SERVER_KEY,
+};
+use arkade_compiler::taproot::{
+    leaf_hash, Network, TaprootOutput, NUMS_INTERNAL_KEY, TAPSCRIPT_LEAF_VERSION,
+};
+
+const HTLC: &str = include_str!("../examples/htlc.ark");
+const SINGLE_SIG: &str = include_str!("../examples/single_sig.ark");
+
+fn args(pairs: &[(&str, String)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect()
+}
+
+fn htlc_args() -> HashMap<String, String> {
+    args(&[
+        ("sender", format!("02{}", "11".repeat(32))),
+        ("receiver", format!("03{}", "22".repeat(32))),
+        ("hash", "ab".repeat(32)),
+        ("refundTime", "850000".to_string()),
+    ])
+}
+
+#[test]
+fn test_link_substitutes_constructor_arguments() {
+    let artifact = compile(HTLC).unwrap();
+    let linked = link(&artifact, &htlc_args()).unwrap();
+
+    assert!(linked.parameters.is_empty());
+    let refund = linked
+        .functions
+        .iter()
+        .find(|f| f.name == "refund" && !f.server_variant)
+        .unwrap();
+    assert_eq!(refund.asm[0], "<senderSig>");
+    assert_eq!(refund.asm[1], format!("02{}", "11".repeat(32)));
+    assert_eq!(refund.asm[3], "850000");
+
+    let cooperative = linked
+        .functions
+        .iter()
+        .find(|f| f.name == "refund" && f.server_variant)
+        .unwrap();
+    assert!(cooperative.asm.contains(&"<SERVER_KEY>".to_string()));
+}
+
+#[test]
+fn test_link_server_key() {
+    let artifact = compile(HTLC).unwrap();
+    let server_key = format!("03{}", "33".repeat(32));
+    let mut args = htlc_args();
+    args.insert(SERVER_KEY.to_string(), server_key.clone());
+    let linked = link(&artifact, &args).unwrap();
+
+    for function in linked.functions.iter().filter(|f| f.server_variant) {
+        assert!(function.asm.contains(&server_key), "{:?}", function.asm);
+        assert!(!function.asm.contains(&"<SERVER_KEY>".to_string()));
+    }
+}
+
+#[test]
+fn test_link_rejects_bad_arguments() {
+    let artifact = compile(HTLC).unwrap();
+
+    let mut missing = htlc_args();
+    missing.remove("hash");
+    assert_eq!(
+        link(&artifact, &missing).unwrap_err(),
+        "Missing argument 'hash'"
+    );
+
+    let mut short = htlc_args();
+    short.insert("sender".to_string(), "02abcd".to_string());
+    assert_eq!(
+        link(&artifact, &short).unwrap_err(),
+        "Argument 'sender': expected 33 bytes for pubkey, got 3"
+    );
+
+    let mut not_a_number = htlc_args();
+    not_a_number.insert("refundTime".to_string(), "soon".to_string());
+    assert!(link(&artifact, &not_a_number)
+        .unwrap_err()
+        .contains("expected an integer"));
+
+    let mut unknown = htlc_args();
+    unknown.insert("owner".to_string(), "00".to_string());
+    assert_eq!(
+        link(&artifact, &unknown).unwrap_err(),
+        "Contract HTLC has no parameter 'owner'"
+    );
+}
+
+#[test]
+fn test_link_with_address() {
+    let artifact = compile(SINGLE_SIG).unwrap();
+    let user = format!("02{}", "11".repeat(32));
+    let server = format!("03{}", "33".repeat(32));
+    let mut args = args(&[("user", user), (SERVER_KEY, server)]);
+
+    let linked = link_with_address(&artifact, &args, Network::Regtest).unwrap();
+    assert!(linked.address.as_deref().unwrap().starts_with("bcrt1p"));
+
+    // Keys are pushed x-only, witness elements are left out and the two
+    // leaves are paired under the NUMS key
+    let user_push = format!("20{}", "11".repeat(32));
+    let server_push = format!("20{}", "33".repeat(32));
+    let descriptor = linked.descriptor.as_deref().unwrap();
+    assert!(descriptor.starts_with(&format!(
+        "tr({},{{raw({}ad{}ac),raw({}ac029000b275)}})#",
+        NUMS_INTERNAL_KEY, user_push, server_push, user_push
+    )));
+
+    // The same arguments always give the same address
+    let again = link_with_address(&artifact, &args, Network::Regtest).unwrap();
+    assert_eq!(linked.address, again.address);
+
+    // Without the server key the cooperative leaf is not concrete
+    args.remove(SERVER_KEY);
+    let err = link_with_address(&artifact, &args, Network::Regtest).unwrap_err();
+    assert_eq!(err, "fn spend (server variant): <SERVER_KEY> is not linked");
+}
+
+#[test]
+fn test_link_with_unspendable_internal_key() {
+    let source = SINGLE_SIG.replace("exit = 144;", "exit = 144;\n  internalKey = unspendable;");
+    let artifact = compile(&source).unwrap();
+    let internal_key = artifact.internal_key.clone().unwrap();
+    assert_ne!(internal_key, NUMS_INTERNAL_KEY);
+
+    // The key is tweaked by the hash of the contract
+    let renamed = compile(&source.replace("SingleSig", "SingleSigTwo")).unwrap();
+    assert_ne!(renamed.internal_key.as_ref(), Some(&internal_key));
+
+    let user = format!("02{}", "11".repeat(32));
+    let server = format!("03{}", "33".repeat(32));
+    let args = args(&[("user", user), (SERVER_KEY, server)]);
+    let linked = link_with_address(&artifact, &args, Network::Regtest).unwrap();
+    let descriptor = linked.descriptor.unwrap();
+    assert!(descriptor.starts_with(&format!("tr({},", internal_key)));
+
+    let nums = compile(&source.replace("unspendable;", "nums;")).unwrap();
+    assert_eq!(nums.internal_key.as_deref(), Some(NUMS_INTERNAL_KEY));
+
+    let err = compile(&source.replace("unspendable;", "mine;")).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Option 'internalKey' must be unspendable, nums or a pubkey parameter, got 'mine'"
+    );
+}
+
+#[test]
+fn test_link_internal_key_parameter() {
+    let source = SINGLE_SIG.replace("exit = 144;", "exit = 144;\n  internalKey = user;");
+    let artifact = compile(&source).unwrap();
+    assert_eq!(artifact.internal_key.as_deref(), Some("<user>"));
+
+    // The generator point, a valid key
+    let g = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+    let user = format!("02{}", g);
+    let server = format!("03{}", "33".repeat(32));
+    let args = args(&[("user", user), (SERVER_KEY, server)]);
+
+    let linked = link(&artifact, &args).unwrap();
+    assert_eq!(linked.internal_key.as_deref(), Some(g));
+
+    let linked = link_with_address(&artifact, &args, Network::Regtest).unwrap();
+    assert!(linked
+        .descriptor
+        .unwrap()
+        .starts_with(&format!("tr({},", g)));
+}
+
+#[test]
+fn test_linked_leaves_carry_their_merkle_proofs() {
+    let artifact = compile(
+        r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Keys(pubkey alice, pubkey bob) {
+  function alice(signature aliceSig) {
+    require(checkSig(aliceSig, alice));
+  }
+
+  function bob(signature bobSig) {
+    require(checkSig(bobSig, bob));
+  }
+
+  function both(signature aliceSig, signature bobSig) {
+    require(checkMultisig([alice, bob], 2));
+  }
+}
+"#,
+    )
+    .unwrap();
+    let args = args(&[
+        ("alice", format!("02{}", "11".repeat(32))),
+        ("bob", format!("03{}", "22".repeat(32))),
+        (SERVER_KEY, format!("03{}", "33".repeat(32))),
+    ]);
+    assert!(link(&artifact, &args).unwrap().functions[0].proof.is_none());
+
+    let linked = link_with_address(&artifact, &args, Network::Regtest).unwrap();
+    let leaves = leaf_scripts(&artifact, &args).unwrap();
+    let output = TaprootOutput::new(leaves.clone()).unwrap();
+    for (index, function) in linked.functions.iter().enumerate() {
+        let proof = function.proof.as_ref().unwrap();
+        assert_eq!(proof.leaf_hash, hex::encode(leaf_hash(&leaves[index])));
+        assert_eq!(proof.parity, output.output_key_parity);
+
+        // Enough for the control block
+        let mut block = vec![TAPSCRIPT_LEAF_VERSION | u8::from(proof.parity)];
+        block.extend(hex::decode(NUMS_INTERNAL_KEY).unwrap());
+        for hash in &proof.merkle_path {
+            block.extend(hex::decode(hash).unwrap());
+        }
+        assert_eq!(block, output.control_block(index).unwrap());
+        assert_eq!(proof.control_block, hex::encode(&block));
+    }
+    assert_eq!(linked.taproot, Some(output.tree()));
+
+    // Six leaves: four paired twice, the last two once
+    let path_lengths: Vec<usize> = linked
+        .functions
+        .iter()
+        .map(|f| f.proof.as_ref().unwrap().merkle_path.len())
+        .collect();
+    assert_eq!(path_lengths, [3, 3, 3, 3, 2, 2]);
+    let json = serde_json::to_value(&linked).unwrap();
+    assert!(json["functions"][0]["proof"]["merklePath"].is_array());
+    assert_eq!(json["taproot"]["outputKey"], hex::encode(output.output_key));
+    assert!(link(&artifact, &args).unwrap().taproot.is_none());
+}
+
+#[test]
+fn test_link_computes_p2tr_templates() {
+    let source = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Payout(pubkey owner, pubkey recipient) {
+  function pay(signature ownerSig) {
+    require(tx.outputs[0].scriptPubKey == new P2TR(recipient));
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let artifact = compile(source).unwrap();
+    let template = "<VTXO:P2TR(<recipient>)>".to_string();
+    assert!(artifact.functions[0].asm.contains(&template));
+
+    // The first BIP 86 test vector key, with no script tree
+    let args = args(&[
+        ("owner", format!("02{}", "11".repeat(32))),
+        (
+            "recipient",
+            "02cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115".to_string(),
+        ),
+    ]);
+    let script_pubkey = "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c";
+    let linked = link(&artifact, &args).unwrap();
+    let pay = &linked.functions[0];
+    assert!(!pay.asm.contains(&template));
+    assert!(
+        pay.asm.contains(&format!("0x{}", script_pubkey)),
+        "{:?}",
+        pay.asm
+    );
+
+    let mut args = args;
+    args.insert(SERVER_KEY.to_string(), format!("03{}", "33".repeat(32)));
+    let leaves = leaf_scripts(&artifact, &args).unwrap();
+    assert!(hex::encode(&leaves[0]).contains(&format!("22{}", script_pubkey)));
+}
+
+#[test]
+fn test_descriptor_matches_the_linked_artifact() {
+    for source in [
+        SINGLE_SIG.to_string(),
+        SINGLE_SIG.replace("exit = 144;", "exit = 144;\n  internalKey = user;"),
+    ] {
+        let artifact = compile(&source).unwrap();
+        // The generator point, a valid internal key
+        let g = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+        let args = args(&[
+            ("user", format!("02{}", g)),
+            (SERVER_KEY, format!("03{}", "33".repeat(32))),
+        ]);
+        let linked = link_with_address(&artifact, &args, Network::Regtest).unwrap();
+        assert_eq!(
+            Some(descriptor(&artifact, &args).unwrap()),
+            linked.descriptor
+        );
+    }
+}
+
+#[test]
+fn test_params_file() {
+    let args = parse_params(r#"{ "owner": "02ab", "refundTime": 850000, "flag": true }"#).unwrap();
+    assert_eq!(args["owner"], "02ab");
+    assert_eq!(args["refundTime"], "850000");
+    assert_eq!(args["flag"], "true");
+
+    let err = parse_params(r#"{ "keys": ["02ab"] }"#).unwrap_err();
+    assert_eq!(
+        err,
+        "Argument 'keys' must be a string, number or boolean, got [\"02ab\"]"
+    );
+    assert!(parse_params("[]").is_err());
+}
+
+#[test]
+fn test_descriptor_command_reads_a_params_file() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let artifact_path = temp_dir.path().join("single_sig.json");
+    let params_path = temp_dir.path().join("params.json");
+    let artifact = compile(SINGLE_SIG).unwrap();
+    fs::write(&artifact_path, serde_json::to_string(&artifact).unwrap()).unwrap();
+    let user = format!("02{}", "11".repeat(32));
+    let server = format!("03{}", "33".repeat(32));
+    fs::write(
+        &params_path,
+        format!(r#"{{ "user": "{}", "SERVER_KEY": "{}" }}"#, user, server),
+    )
+    .unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_arkadec"))
+        .arg("descriptor")
+        .arg(&artifact_path)
+        .arg("--params")
+        .arg(&params_path)
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "{:?}", output);
+    let expected = descriptor(&artifact, &args(&[("user", user), (SERVER_KEY, server)])).unwrap();
+    assert!(expected.starts_with("tr("));
+    assert_eq!(String::from_utf8(output.stdout).unwrap().trim(), expected);
+}
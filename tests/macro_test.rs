@@ -0,0 +1,138 @@
+use arkade_compiler::compile;
+use arkade_compiler::models::Statement;
+use arkade_compiler::parser::parse;
+
+const MACRO_CODE: &str = r#"
+contract Vault(pubkey owner, pubkey backup) {
+  macro requireOwner(sig) {
+    require(checkSig(sig, owner));
+  }
+
+  macro requireBoth(ownerSig, backupSig) {
+    requireOwner(ownerSig);
+    require(checkSig(backupSig, backup));
+  }
+
+  function spend(signature ownerSig) {
+    requireOwner(ownerSig);
+  }
+
+  function recover(signature a, signature b) {
+    requireBoth(a, b);
+  }
+}
+"#;
+
+const EXPANDED_CODE: &str = r#"
+contract Vault(pubkey owner, pubkey backup) {
+  function spend(signature ownerSig) {
+    require(checkSig(ownerSig, owner));
+  }
+
+  function recover(signature a, signature b) {
+    require(checkSig(a, owner));
+    require(checkSig(b, backup));
+  }
+}
+"#;
+
+#[test]
+fn test_macros_expand_like_hand_written_code() {
+    let with_macros = compile(MACRO_CODE).unwrap();
+    let expanded = compile(EXPANDED_CODE).unwrap();
+
+    assert_eq!(with_macros.functions.len(), expanded.functions.len());
+    for (m, e) in with_macros.functions.iter().zip(&expanded.functions) {
+        assert_eq!(m.name, e.name);
+        assert_eq!(m.asm, e.asm);
+        assert_eq!(m.witness_size.total, e.witness_size.total);
+    }
+}
+
+#[test]
+fn test_macro_bindings_do_not_clash_with_the_caller() {
+    let code = r#"
+contract Fees(pubkey owner) {
+  macro minimum(amount) {
+    let fee = 100;
+    require(amount >= fee);
+  }
+
+  function pay(signature ownerSig, int value) {
+    let fee = 5;
+    minimum(value);
+    require(value >= fee);
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let contract = parse(code).unwrap();
+    let names: Vec<&str> = contract.functions[0]
+        .statements
+        .iter()
+        .filter_map(|s| match s {
+            Statement::LetBinding { name, .. } => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(names.len(), 2);
+    assert_eq!(names[0], "fee");
+    assert_ne!(names[1], "fee");
+}
+
+#[test]
+fn test_expanded_statements_are_located_at_the_call() {
+    let contract = parse(MACRO_CODE).unwrap();
+    let spend = &contract.functions[0];
+
+    match &spend.statements[0] {
+        Statement::Require(_, span) => assert_eq!(span.line, 13),
+        other => panic!("expected a requirement, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_macro_cannot_capture_caller_variables() {
+    let code = r#"
+contract Single(pubkey owner) {
+  macro requireOwner() {
+    require(checkSig(ownerSig, owner));
+  }
+
+  function spend(signature ownerSig) {
+    requireOwner();
+  }
+}
+"#;
+    let err = parse(code).unwrap_err().to_string();
+    assert!(
+        err.contains("Macro 'requireOwner' refers to 'ownerSig'"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_macro_call_errors() {
+    let arity = MACRO_CODE.replace(
+        "requireOwner(ownerSig);\n  }\n\n  function recover",
+        "requireOwner();\n  }\n\n  function recover",
+    );
+    let err = parse(&arity).unwrap_err().to_string();
+    assert_eq!(err, "Macro 'requireOwner' takes 1 argument(s), got 0");
+
+    let recursive = r#"
+contract Loop(pubkey owner) {
+  macro again(sig) {
+    again(sig);
+  }
+
+  function spend(signature ownerSig) {
+    again(ownerSig);
+  }
+}
+"#;
+    let err = parse(recursive).unwrap_err().to_string();
+    assert_eq!(err, "Macro 'again' expands itself");
+}
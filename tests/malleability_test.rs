@@ -0,0 +1,134 @@
+use arkade_compiler::compile;
+
+fn malleability_warnings(code: &str) -> Vec<String> {
+    compile(code)
+        .unwrap()
+        .warnings
+        .into_iter()
+        .filter(|w| w.starts_with("warning[malleable]"))
+        .collect()
+}
+
+#[test]
+fn test_unverified_hash_comparison() {
+    let code = r#"
+contract Hashlock(pubkey owner, bytes32 hash) {
+  function spend(signature ownerSig, bytes32 preimage) {
+    require(checkSig(ownerSig, owner));
+    if (sha256(preimage) == hash) {
+      require(tx.time >= 100);
+    }
+  }
+}
+"#;
+    let warnings = malleability_warnings(code);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(
+        warnings[0].contains("hash comparison on `preimage` at 5:9 is not verified"),
+        "{}",
+        warnings[0]
+    );
+}
+
+#[test]
+fn test_unbound_signature() {
+    let code = r#"
+contract Loose(pubkey owner) {
+  function spend(signature ownerSig, signature extraSig) {
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let warnings = malleability_warnings(code);
+    assert_eq!(
+        warnings,
+        vec!["warning[malleable]: fn spend: signature `extraSig` is not bound by any signature check; a third party can replace it with any value"]
+    );
+}
+
+#[test]
+fn test_signature_only_selecting_a_branch() {
+    let code = r#"
+contract Selector(pubkey owner) {
+  function spend(signature ownerSig) {
+    if (checkSig(ownerSig, owner)) {
+      require(tx.time >= 100);
+    } else {
+      require(tx.time >= 200);
+    }
+  }
+}
+"#;
+    let warnings = malleability_warnings(code);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(warnings[0].contains("signature `ownerSig` only selects the branch at 4:9"));
+}
+
+#[test]
+fn test_bare_witness_boolean() {
+    let code = r#"
+contract Flag(pubkey owner) {
+  function spend(signature ownerSig, bool ok) {
+    require(ok);
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let warnings = malleability_warnings(code);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(warnings[0].contains("boolean `ok` at 4:5"));
+    assert!(warnings[0].contains("any non-zero value"));
+}
+
+#[test]
+fn test_witness_boolean_branch_selector() {
+    let code = r#"
+contract Paths(pubkey alice, pubkey bob) {
+  function spend(signature aliceSig, signature bobSig, bool fast) {
+    if (fast) {
+      require(checkSig(aliceSig, alice));
+    } else {
+      require(checkSig(aliceSig, alice));
+      require(checkSig(bobSig, bob));
+    }
+  }
+}
+"#;
+    let warnings = malleability_warnings(code);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(warnings[0].contains("boolean `fast` selects the branch at 4:9"));
+}
+
+#[test]
+fn test_signature_bound_branches_are_not_reported() {
+    let code = r#"
+contract Paths(pubkey alice, pubkey bob) {
+  function spend(signature aliceSig, signature bobSig, bool useAlice) {
+    if (useAlice) {
+      require(checkSig(aliceSig, alice));
+    } else {
+      require(checkSig(bobSig, bob));
+    }
+  }
+}
+"#;
+    assert!(malleability_warnings(code).is_empty());
+}
+
+#[test]
+fn test_threshold_counting_is_not_reported() {
+    let code = r#"
+contract Quorum(pubkey[] oracles, bytes32 message) {
+  function attest(signature[] oracleSigs) {
+    int valid = 0;
+    for (i, sig) in oracleSigs {
+      if (checkSigFromStack(sig, oracles[i], message)) {
+        valid = valid + 1;
+      }
+    }
+    require(valid >= 2, "quorum");
+  }
+}
+"#;
+    assert!(malleability_warnings(code).is_empty());
+}
@@ -0,0 +1,112 @@
+use arkade_compiler::compiler::{compile_with_options, CompileOptions};
+use arkade_compiler::parser::parse;
+use arkade_compiler::printer::print;
+use arkade_compiler::taproot::Network;
+use arkade_compiler::{compile, constants};
+
+const VAULT_CODE: &str = r#"
+options {
+  server = server;
+  exit = BLOCKS_PER_DAY;
+}
+
+contract Vault(pubkey owner, pubkey backup) {
+  function spend(signature ownerSig) {
+    require(checkSig(ownerSig, owner));
+    require(tx.outputs[0].value >= DUST_LIMIT);
+  }
+
+  function recover(signature backupSig) {
+    require(checkSig(backupSig, backup));
+    require(tx.time >= BLOCKS_PER_WEEK);
+  }
+}
+"#;
+
+fn asm(output: &arkade_compiler::ContractJson, name: &str, server_variant: bool) -> Vec<String> {
+    output
+        .functions
+        .iter()
+        .find(|f| f.name == name && f.server_variant == server_variant)
+        .unwrap()
+        .asm
+        .clone()
+}
+
+#[test]
+fn test_constants_resolve_for_bitcoin_by_default() {
+    let output = compile(VAULT_CODE).unwrap();
+
+    let spend = asm(&output, "spend", true);
+    // Compared with an amount, the dust limit is pushed as u64le
+    assert!(
+        spend.contains(&"0x4a01000000000000".to_string()),
+        "{:?}",
+        spend
+    );
+    assert!(asm(&output, "spend", false).ends_with(&[
+        "144".to_string(),
+        "OP_CHECKSEQUENCEVERIFY".to_string(),
+        "OP_DROP".to_string()
+    ]));
+
+    let recover = asm(&output, "recover", true);
+    assert!(recover.contains(&"1008".to_string()), "{:?}", recover);
+    assert!(!recover.iter().any(|t| t.contains("BLOCKS_PER")));
+}
+
+#[test]
+fn test_constants_resolve_per_target_network() {
+    let options = CompileOptions {
+        network: Network::Mutinynet,
+        ..Default::default()
+    };
+    let output = compile_with_options(VAULT_CODE, &options).unwrap();
+
+    assert!(asm(&output, "spend", false).contains(&"2880".to_string()));
+    assert!(asm(&output, "recover", true).contains(&"20160".to_string()));
+    assert_eq!(
+        constants::value("BLOCKS_PER_DAY", Network::Mutinynet),
+        Some(2880)
+    );
+}
+
+#[test]
+fn test_parameters_shadow_constants() {
+    let code = VAULT_CODE.replace("pubkey backup)", "pubkey backup, int DUST_LIMIT)");
+    let output = compile(&code).unwrap();
+    assert!(asm(&output, "spend", true).contains(&"<DUST_LIMIT>".to_string()));
+}
+
+#[test]
+fn test_unknown_option_constant_is_an_error() {
+    let code = VAULT_CODE.replace("BLOCKS_PER_DAY;", "BLOCKS_PER_FORTNIGHT;");
+    let err = compile(&code).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Option 'exit' must be a number, a duration or a built-in constant, got 'BLOCKS_PER_FORTNIGHT'"
+    );
+}
+
+#[test]
+fn test_durations_convert_to_blocks_per_target_network() {
+    let mutinynet = CompileOptions {
+        network: Network::Mutinynet,
+        ..Default::default()
+    };
+    for (exit, bitcoin, faster) in [
+        ("48h", 288, 5760),
+        ("2 days", 288, 5760),
+        ("1 hour", 6, 120),
+        ("144 blocks", 144, 144),
+    ] {
+        let code = VAULT_CODE.replace("BLOCKS_PER_DAY;", &format!("{};", exit));
+        assert_eq!(compile(&code).unwrap().exit_delay, bitcoin, "{}", exit);
+        let output = compile_with_options(&code, &mutinynet).unwrap();
+        assert_eq!(output.exit_delay, faster, "{}", exit);
+    }
+
+    let code = VAULT_CODE.replace("BLOCKS_PER_DAY;", "48h;");
+    let printed = print(&parse(&code).unwrap());
+    assert!(printed.contains("  exit = 48h;\n"), "{}", printed);
+}
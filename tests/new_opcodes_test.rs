@@ -1,7 +1,8 @@
 use arkade_compiler::compile;
 use arkade_compiler::opcodes::{
-    OP_CHECKSIGFROMSTACKVERIFY, OP_ECMULSCALARVERIFY, OP_LE32TOLE64, OP_LE64TOSCRIPTNUM, OP_NEG64,
-    OP_SHA256FINALIZE, OP_SHA256INITIALIZE, OP_SHA256UPDATE, OP_TWEAKVERIFY,
+    OP_CHECKSIGFROMSTACK, OP_CHECKSIGFROMSTACKVERIFY, OP_ECMULSCALARVERIFY, OP_LE32TOLE64,
+    OP_LE64TOSCRIPTNUM, OP_NEG64, OP_ROT, OP_SHA256FINALIZE, OP_SHA256INITIALIZE, OP_SHA256UPDATE,
+    OP_SWAP, OP_TWEAKVERIFY,
 };
 // ─── Streaming SHA256 Tests ────────────────────────────────────────────
 
@@ -347,6 +348,53 @@ fn test_check_sig_from_stack_verify() {
     );
 }
 
+#[test]
+fn test_check_sig_from_stack_pushes_signature_message_and_key() {
+    let code = r#"
+        options {
+            server = serverKey;
+            exit = 144;
+        }
+
+        contract Oracle(pubkey serverKey, pubkey oracle, pubkey[] signers) {
+            function attest(signature oracleSig, bytes32 outcome) {
+                require(checkSigFromStack(oracleSig, oracle, outcome));
+            }
+
+            function cosign(signature[] sigs, bytes32[] messages) {
+                for (i, sig) in sigs {
+                    require(checkSigFromStack(sig, signers[i], messages[i]));
+                }
+            }
+        }
+    "#;
+
+    let output = compile(code).unwrap();
+    for func in output.functions.iter().filter(|f| f.name == "attest") {
+        assert_eq!(func.require[0].req_type, "signatureFromStack");
+        // The server signature follows the check on the cooperative path
+        let check = match func.server_variant {
+            true => OP_CHECKSIGFROMSTACKVERIFY,
+            false => OP_CHECKSIGFROMSTACK,
+        };
+        assert_eq!(
+            func.asm[..4],
+            ["<oracleSig>", "<outcome>", "<oracle>", check]
+        );
+    }
+
+    // Each unrolled iteration checks its own message
+    let cosign = output
+        .functions
+        .iter()
+        .find(|f| f.name == "cosign" && f.server_variant)
+        .unwrap();
+    assert!(cosign
+        .asm
+        .join(" ")
+        .contains("<sigs_1> <messages_1> <signers_1> OP_CHECKSIGFROMSTACK"));
+}
+
 // ─── Combined Usage Tests ───────────────────────────────────────────────────────
 
 #[test]
@@ -448,3 +496,41 @@ fn test_conversion_chain() {
         asm_str
     );
 }
+
+#[test]
+fn test_ptlc_builtins_have_dedicated_requirement_types() {
+    let code = r#"
+        options {
+            server = serverKey;
+            exit = 144;
+        }
+
+        contract Ptlc(pubkey serverKey, pubkey P, pubkey Q) {
+            function claim(bytes32 t) {
+                require(verifyTweak(P, t, Q));
+            }
+
+            function prove(bytes32 k) {
+                require(verifyScalarMul(k, P, Q));
+            }
+        }
+    "#;
+
+    let output = compile(code).unwrap();
+    let leaf = |name: &str| {
+        output
+            .functions
+            .iter()
+            .find(|f| f.name == name && f.server_variant)
+            .unwrap()
+    };
+
+    let claim = leaf("claim");
+    assert_eq!(claim.require[0].req_type, "tweak");
+    // The witness element is moved over the key pushed before it
+    assert_eq!(claim.asm[..4], ["<Q>", OP_SWAP, "<P>", OP_TWEAKVERIFY]);
+
+    let prove = leaf("prove");
+    assert_eq!(prove.require[0].req_type, "scalarMul");
+    assert_eq!(prove.asm[..4], ["<Q>", "<P>", OP_ROT, OP_ECMULSCALARVERIFY]);
+}
@@ -0,0 +1,176 @@
+#![cfg(feature = "std")]
+
+use std::collections::HashMap;
+
+use arkade_compiler::compile;
+use arkade_compiler::linker::link;
+use arkade_compiler::models::{AbiFunction, ContractJson, OperationData};
+
+const HTLC: &str = include_str!("../examples/htlc.ark");
+
+const PAYOUT: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Payout(pubkey owner, int amount) {
+  function pay(signature ownerSig) {
+    require(checkSig(ownerSig, owner, SIGHASH_SINGLE));
+    require(tx.outputs[0].value >= amount);
+    require(tx.outputs[0].scriptPubKey == new SingleSig(owner));
+  }
+}
+"#;
+
+fn refund(artifact: &ContractJson, server_variant: bool) -> &AbiFunction {
+    artifact
+        .functions
+        .iter()
+        .find(|f| f.name == "refund" && f.server_variant == server_variant)
+        .unwrap()
+}
+
+fn constructor_param(name: &str, param_type: &str, size: Option<usize>) -> OperationData {
+    OperationData::ConstructorParam {
+        name: name.to_string(),
+        param_type: param_type.to_string(),
+        size,
+    }
+}
+
+fn function_input(name: &str, param_type: &str, size: Option<usize>) -> OperationData {
+    OperationData::FunctionInput {
+        name: name.to_string(),
+        param_type: param_type.to_string(),
+        size,
+    }
+}
+
+#[test]
+fn test_every_asm_token_is_described() {
+    let artifact = compile(HTLC).unwrap();
+    for function in &artifact.functions {
+        let ops: Vec<&str> = function.operations.iter().map(|o| o.op.as_str()).collect();
+        assert_eq!(ops, function.asm);
+    }
+
+    let data: Vec<_> = refund(&artifact, true)
+        .operations
+        .iter()
+        .map(|o| o.data.clone())
+        .collect();
+    assert_eq!(
+        data,
+        [
+            function_input("senderSig", "signature", Some(64)),
+            constructor_param("sender", "pubkey", Some(32)),
+            OperationData::Opcode,
+            constructor_param("refundTime", "int", None),
+            OperationData::Opcode,
+            OperationData::Opcode,
+            function_input("serverSig", "signature", Some(64)),
+            OperationData::Runtime {
+                name: "SERVER_KEY".to_string(),
+                size: 32,
+            },
+            OperationData::Opcode,
+        ]
+    );
+
+    let exit = &refund(&artifact, false).operations;
+    assert_eq!(
+        exit[6].data,
+        OperationData::Push {
+            hex: "9000".to_string(),
+            size: 2,
+        }
+    );
+}
+
+#[test]
+fn test_pinned_signatures_and_vtxo_templates() {
+    let artifact = compile(PAYOUT).unwrap();
+    let pay = artifact
+        .functions
+        .iter()
+        .find(|f| f.name == "pay" && f.server_variant)
+        .unwrap();
+    let data = |op: &str| {
+        pay.operations
+            .iter()
+            .find(|o| o.op == op)
+            .map(|o| o.data.clone())
+            .unwrap()
+    };
+    // The script appends the flag, so the witness holds the bare signature
+    assert_eq!(
+        data("<ownerSig>"),
+        function_input("ownerSig", "signature", Some(64))
+    );
+    assert_eq!(
+        data("0x03"),
+        OperationData::Push {
+            hex: "03".to_string(),
+            size: 1,
+        }
+    );
+    assert_eq!(
+        data("<VTXO:SingleSig(<owner>)>"),
+        OperationData::Runtime {
+            name: "VTXO:SingleSig(<owner>)".to_string(),
+            size: 34,
+        }
+    );
+}
+
+#[test]
+fn test_linking_turns_arguments_into_pushes() {
+    let artifact = compile(HTLC).unwrap();
+    let args: HashMap<String, String> = [
+        ("sender", format!("02{}", "11".repeat(32))),
+        ("receiver", format!("03{}", "22".repeat(32))),
+        ("hash", "ab".repeat(32)),
+        ("refundTime", "850000".to_string()),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value))
+    .collect();
+    let linked = link(&artifact, &args).unwrap();
+
+    let refund = refund(&linked, true);
+    let ops: Vec<&str> = refund.operations.iter().map(|o| o.op.as_str()).collect();
+    assert_eq!(ops, refund.asm);
+    // Keys are pushed x-only
+    assert_eq!(
+        refund.operations[1].data,
+        OperationData::Push {
+            hex: "11".repeat(32),
+            size: 32,
+        }
+    );
+    assert_eq!(
+        refund.operations[3].data,
+        OperationData::Push {
+            hex: "50f80c".to_string(),
+            size: 3,
+        }
+    );
+    assert_eq!(
+        refund.operations[0].data,
+        function_input("senderSig", "signature", Some(64))
+    );
+}
+
+#[test]
+fn test_operations_serialize_with_their_kind() {
+    let artifact = compile(HTLC).unwrap();
+    let json = serde_json::to_value(refund(&artifact, true)).unwrap();
+    let key = &json["operations"][1];
+    assert_eq!(key["op"], "<sender>");
+    assert_eq!(key["data"]["kind"], "constructorParam");
+    assert_eq!(key["data"]["type"], "pubkey");
+    assert_eq!(key["data"]["size"], 32);
+    assert_eq!(json["operations"][2]["data"]["kind"], "opcode");
+    assert!(json["operations"][3]["data"].get("size").is_none());
+}
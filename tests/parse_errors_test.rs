@@ -0,0 +1,98 @@
+use arkade_compiler::compile;
+use arkade_compiler::diagnostics::{self, Severity};
+use arkade_compiler::parser::{parse, ParseError};
+
+fn contract(body: &str) -> String {
+    format!(
+        r#"options {{
+  server = server;
+  exit = 144;
+}}
+
+contract Checks(pubkey owner, pubkey[3] keys, bytes memo) {{
+  function spend(signature ownerSig) {{
+    require(checkSig(ownerSig, owner));
+    {}
+  }}
+}}
+"#,
+        body
+    )
+}
+
+/// The error from parsing `source`, which the grammar accepts.
+fn parse_error(source: &str) -> ParseError {
+    let err = parse(source).unwrap_err();
+    err.downcast_ref::<ParseError>()
+        .unwrap_or_else(|| panic!("not a ParseError: {}", err))
+        .clone()
+}
+
+#[test]
+fn test_size_bound_too_large_for_a_script_number() {
+    let source = contract("require(size(memo) between 1 and 18446744073709551615);");
+    let err = parse_error(&source);
+    assert_eq!(
+        err.message,
+        "Invalid size bound '18446744073709551615' for 'memo': must be at most 2147483646"
+    );
+    let span = err.span.unwrap();
+    assert_eq!((span.line, span.column), (9, 5));
+}
+
+#[test]
+fn test_multisig_threshold_out_of_range() {
+    let source = contract("require(checkMultisig(keys, 99999999999999999999));");
+    let err = parse_error(&source);
+    assert_eq!(
+        err.message,
+        "Invalid checkMultisig threshold '99999999999999999999': number too large to fit in target type"
+    );
+
+    let source = contract("require(checkMultisig([owner, owner], 70000));");
+    assert!(parse_error(&source)
+        .message
+        .starts_with("Invalid checkMultisig threshold '70000'"));
+}
+
+#[test]
+fn test_errors_in_nested_blocks_are_located_at_the_inner_statement() {
+    let source =
+        contract("if (ownerSig == ownerSig) {\n      require(size(memo) between 80 and 1);\n    }");
+    let err = parse_error(&source);
+    assert_eq!(
+        err.message,
+        "Size bounds of 'memo' are empty: 80 is greater than 1"
+    );
+    let span = err.span.unwrap();
+    assert_eq!((span.line, span.column), (10, 7));
+}
+
+#[test]
+fn test_contract_level_errors_are_located() {
+    let source = contract("").replace("exit = 144;", "exit = 144;\n  strictHashlocks = yes;");
+    let err = parse_error(&source);
+    let span = err.span.unwrap();
+    assert_eq!((span.line, span.column), (4, 3));
+
+    let source = contract("").replace("contract Checks(", "contract Checks<N, N>(");
+    let span = parse_error(&source).span.unwrap();
+    assert_eq!((span.line, span.column), (6, 20));
+}
+
+#[test]
+fn test_compile_errors_carry_the_location() {
+    let source = contract("require(size(memo) between 80 and 1);");
+    let err = compile(&source).unwrap_err().to_string();
+    assert_eq!(
+        err,
+        "Parse error at 9:5: Size bounds of 'memo' are empty: 80 is greater than 1"
+    );
+
+    let diagnostic = diagnostics::check(&source)
+        .into_iter()
+        .find(|d| d.severity == Severity::Error)
+        .unwrap();
+    assert_eq!(diagnostic.code, "parse");
+    assert_eq!((diagnostic.line, diagnostic.column), (Some(9), Some(5)));
+}
@@ -0,0 +1,118 @@
+use arkade_compiler::compiler::{compile_with_options, CompileOptions};
+use arkade_compiler::parser::{parse, parse_with_options, ParseError, ParseLimits, ParseOptions};
+
+fn nested(depth: usize) -> String {
+    format!(
+        "contract Deep(int a) {{\n  function spend() {{\n    int x = {}a{};\n    require(x > 0);\n  }}\n}}\n",
+        "(".repeat(depth),
+        ")".repeat(depth)
+    )
+}
+
+fn with_limits(limits: ParseLimits) -> ParseOptions {
+    ParseOptions {
+        limits,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_deep_nesting_fails_fast() {
+    // Far deeper than the stack allows
+    let err = parse(&nested(100_000)).unwrap_err();
+    let err = err.downcast_ref::<ParseError>().unwrap();
+    assert_eq!(err.message, "Source nests more than 32 levels deep");
+    let span = err.span.unwrap();
+    // Two braces, then the 31st parenthesis
+    assert_eq!((span.line, span.column), (3, 43));
+
+    parse(&nested(20)).unwrap();
+}
+
+#[test]
+fn test_brackets_in_comments_and_strings_do_not_nest() {
+    let source = format!(
+        "// {}\ncontract Deep(int a) {{\n  function spend() {{\n    require(a > 0, \"{}\");\n  }}\n}}\n",
+        "(".repeat(100),
+        "[".repeat(100)
+    );
+    parse(&source).unwrap();
+}
+
+#[test]
+fn test_source_size_limit() {
+    let limits = ParseLimits {
+        max_source_bytes: 64,
+        ..Default::default()
+    };
+    let source = nested(1);
+    let err = parse_with_options(&source, &with_limits(limits))
+        .unwrap_err()
+        .to_string();
+    assert_eq!(
+        err,
+        format!(
+            "Source is {} bytes, more than the limit of 64",
+            source.len()
+        )
+    );
+}
+
+#[test]
+fn test_expression_limit() {
+    let limits = ParseLimits {
+        max_expressions: 10,
+        ..Default::default()
+    };
+    let err = parse_with_options(&nested(1), &with_limits(limits))
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.ends_with("expressions, more than the limit of 10"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_limits_are_configurable_when_compiling() {
+    let options = CompileOptions {
+        parse_limits: ParseLimits {
+            max_depth: 40,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert!(
+        compile_with_options(&nested(33), &CompileOptions::default())
+            .unwrap_err()
+            .to_string()
+            .contains("Source nests more than 32 levels deep")
+    );
+    compile_with_options(&nested(33), &options).unwrap();
+}
+
+#[test]
+fn test_operator_chains_count_as_nesting() {
+    // `terms` additions, then a product and a difference below the last
+    let chain = |terms: usize| {
+        format!(
+            "contract Long(int a) {{\n  function spend(int b) {{\n    int x = a{};\n    \
+             require(x > 0);\n  }}\n}}\n",
+            " + b * (b - a)".repeat(terms)
+        )
+    };
+    parse(&chain(126)).unwrap();
+
+    // Well within the expression limit, but nested as deep as 129 parentheses
+    let err = parse(&chain(127)).unwrap_err();
+    let err = err.downcast_ref::<ParseError>().unwrap();
+    assert_eq!(err.message, "Expression nests more than 128 operators deep");
+    assert_eq!(err.span.unwrap().line, 3);
+
+    let limits = ParseLimits {
+        max_operator_depth: 5_000,
+        ..Default::default()
+    };
+    parse_with_options(&chain(1_000), &with_limits(limits)).unwrap();
+}
@@ -0,0 +1,158 @@
+use arkade_compiler::compile;
+
+fn warnings(code: &str) -> Vec<String> {
+    compile(code).unwrap().warnings
+}
+
+#[test]
+fn test_contradicting_bounds_are_unsat() {
+    let code = r#"
+contract Bounds(pubkey owner, int cap) {
+  function spend(signature ownerSig, int amount) {
+    require(checkSig(ownerSig, owner));
+    require(amount > 100, "too small");
+    require(amount < 50, "too large");
+  }
+}
+"#;
+    let warnings = warnings(code);
+    let unsat: Vec<_> = warnings
+        .iter()
+        .filter(|w| w.starts_with("warning[unsat]"))
+        .collect();
+    assert_eq!(unsat.len(), 1, "{:?}", warnings);
+    assert!(unsat[0].contains("fn spend"));
+    // Spans of both requirements are reported
+    assert!(unsat[0].contains("require at 6:5"), "{}", unsat[0]);
+    assert!(unsat[0].contains("require at 5:5"), "{}", unsat[0]);
+    assert!(unsat[0].contains("can never be satisfied"));
+}
+
+#[test]
+fn test_implied_requirement_is_redundant() {
+    let code = r#"
+contract Bounds(pubkey owner) {
+  function spend(signature ownerSig, int amount) {
+    require(amount >= 1000, "too small");
+    require(amount > 10, "dust");
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let warnings = warnings(code);
+    assert!(
+        warnings
+            .iter()
+            .any(|w| w
+                == "warning[redundant]: fn spend: require at 5:5 is implied by require at 4:5"),
+        "{:?}",
+        warnings
+    );
+    assert!(!warnings.iter().any(|w| w.starts_with("warning[unsat]")));
+}
+
+#[test]
+fn test_repeated_signature_check_is_redundant() {
+    let code = r#"
+contract Twice(pubkey owner) {
+  function spend(signature ownerSig) {
+    require(checkSig(ownerSig, owner));
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let warnings = warnings(code);
+    assert!(
+        warnings.iter().any(|w| w.starts_with("warning[redundant]")),
+        "{:?}",
+        warnings
+    );
+}
+
+#[test]
+fn test_equal_and_not_equal_are_unsat() {
+    let code = r#"
+contract Equal(pubkey owner) {
+  function spend(signature ownerSig, int mode) {
+    require(mode == 1, "mode");
+    require(mode != 1, "not mode");
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let warnings = warnings(code);
+    assert!(
+        warnings.iter().any(|w| w.starts_with("warning[unsat]")),
+        "{:?}",
+        warnings
+    );
+}
+
+#[test]
+fn test_dead_branch_is_reported() {
+    let code = r#"
+contract Branches(pubkey owner) {
+  function spend(signature ownerSig, int amount) {
+    require(amount >= 500, "too small");
+    if (amount < 100) {
+      require(checkSig(ownerSig, owner));
+    }
+  }
+}
+"#;
+    let warnings = warnings(code);
+    assert!(
+        warnings.iter().any(
+            |w| w.contains("condition at 5:9 contradicts require at 4:5")
+                && w.contains("never taken")
+        ),
+        "{:?}",
+        warnings
+    );
+}
+
+#[test]
+fn test_exclusive_branches_are_not_reported() {
+    let code = r#"
+contract Branches(pubkey owner, int limit) {
+  function spend(signature ownerSig, int amount) {
+    require(checkSig(ownerSig, owner));
+    if (amount < limit) {
+      require(amount >= 10, "dust");
+    }
+    if (amount >= limit) {
+      require(amount <= 100000, "too large");
+    }
+  }
+}
+"#;
+    let warnings = warnings(code);
+    assert!(
+        !warnings
+            .iter()
+            .any(|w| w.starts_with("warning[unsat]") || w.starts_with("warning[redundant]")),
+        "{:?}",
+        warnings
+    );
+}
+
+#[test]
+fn test_rebinding_forgets_constraints() {
+    let code = r#"
+contract Rebind(pubkey owner) {
+  function spend(signature ownerSig) {
+    int x = tx.outputs[0].value;
+    require(x > 100, "low");
+    x = tx.outputs[1].value;
+    require(x < 50, "high");
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let warnings = warnings(code);
+    assert!(
+        !warnings.iter().any(|w| w.starts_with("warning[unsat]")),
+        "{:?}",
+        warnings
+    );
+}
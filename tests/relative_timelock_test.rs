@@ -0,0 +1,109 @@
+use arkade_compiler::compile;
+use arkade_compiler::diagnostics::classify;
+use arkade_compiler::models::{Duration, Requirement, Statement};
+use arkade_compiler::parser::parse;
+use arkade_compiler::printer::print;
+
+const CODE: &str = r#"
+contract Vault(pubkey owner, pubkey backup, int delay) {
+  function unlock(signature ownerSig) {
+    require(tx.age >= 144);
+    require(tx.age >= 1008 blocks);
+    require(tx.age >= delay);
+    require(tx.age >= BLOCKS_PER_DAY);
+    require(checkSig(ownerSig, owner));
+  }
+
+  function recover(signature backupSig) {
+    require(tx.age >= 2 days);
+    require(tx.age >= 1h);
+    require(checkSig(backupSig, backup));
+  }
+}
+"#;
+
+fn asm(name: &str) -> String {
+    let artifact = compile(CODE).unwrap();
+    let function = artifact
+        .functions
+        .iter()
+        .find(|f| f.name == name && f.server_variant)
+        .unwrap();
+    function.asm.join(" ")
+}
+
+#[test]
+fn test_tx_age_parses_durations() {
+    let contract = parse(CODE).unwrap();
+    let ages: Vec<_> = contract
+        .functions
+        .iter()
+        .flat_map(|f| &f.statements)
+        .filter_map(|s| match s {
+            Statement::Require(Requirement::Older { age, timelock_var }, _) => {
+                Some((*age, timelock_var.clone()))
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        ages,
+        [
+            (Duration::Blocks(144), None),
+            (Duration::Blocks(1008), None),
+            (Duration::Blocks(0), Some("delay".to_string())),
+            (Duration::Blocks(0), Some("BLOCKS_PER_DAY".to_string())),
+            (Duration::Seconds(172800), None),
+            (Duration::Seconds(3600), None),
+        ]
+    );
+
+    let printed = print(&contract);
+    assert!(
+        printed.contains("require(tx.age >= 2 days);"),
+        "{}",
+        printed
+    );
+    assert!(
+        printed.contains("require(tx.age >= 1 hour);"),
+        "{}",
+        printed
+    );
+    assert_eq!(print(&parse(&printed).unwrap()), printed);
+}
+
+#[test]
+fn test_block_ages_compile_to_checksequenceverify() {
+    assert!(asm("unlock").starts_with(
+        "144 OP_CHECKSEQUENCEVERIFY OP_DROP 1008 OP_CHECKSEQUENCEVERIFY OP_DROP \
+         <delay> OP_CHECKSEQUENCEVERIFY OP_DROP 144 OP_CHECKSEQUENCEVERIFY OP_DROP"
+    ));
+}
+
+#[test]
+fn test_time_ages_set_the_type_flag() {
+    // 172800 / 512 rounds up to 338 units; 3600 / 512 to 8
+    assert!(asm("recover").starts_with(
+        "4194642 OP_CHECKSEQUENCEVERIFY OP_DROP 4194312 OP_CHECKSEQUENCEVERIFY OP_DROP"
+    ));
+    assert_eq!(Duration::Seconds(172800).sequence(), Ok((1 << 22) | 338));
+}
+
+#[test]
+fn test_ages_beyond_sixteen_bits_are_errors() {
+    for (age, message) in [
+        (
+            "65536",
+            "Relative timelock of 65536 blocks exceeds the maximum of 65535",
+        ),
+        (
+            "400 days",
+            "Relative timelock of 34560000 seconds exceeds the maximum of 33553920",
+        ),
+    ] {
+        let code = CODE.replace("tx.age >= 2 days", &format!("tx.age >= {}", age));
+        let err = compile(&code).unwrap_err().to_string();
+        assert!(err.contains(message), "{}", err);
+        assert_eq!(classify("parse", &err), "E0105");
+    }
+}
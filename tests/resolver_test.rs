@@ -0,0 +1,80 @@
+use arkade_compiler::compiler::compile;
+use arkade_compiler::diagnostics::classify;
+use arkade_compiler::{parser, resolver};
+
+fn contract(body: &str) -> String {
+    format!(
+        r#"
+options {{
+  server = server;
+  exit = 144;
+}}
+
+contract Vault(pubkey owner, pubkey[2] guardians, int unlockTime) {{
+  function spend(signature ownerSig, bytes32 preimage) {{
+{}
+  }}
+}}
+"#,
+        body
+    )
+}
+
+#[test]
+fn test_undeclared_names_in_requirements_are_errors() {
+    for (body, message) in [
+        (
+            "    require(checkSig(ownerSig, ghost));",
+            "fn spend: undeclared variable 'ghost' at 9:5",
+        ),
+        (
+            "    require(tx.outputs[0].value >= minimum);",
+            "fn spend: undeclared variable 'minimum' at 9:5",
+        ),
+        (
+            "    require(checkSig(ownerSig, ownr));",
+            "fn spend: undeclared variable 'ownr' at 9:5; did you mean 'owner'?",
+        ),
+        (
+            "    if (tx.time >= unlockTme) {\n      require(checkSig(ownerSig, owner));\n    }",
+            "fn spend: undeclared variable 'unlockTme' at 9:9; did you mean 'unlockTime'?",
+        ),
+    ] {
+        let err = compile(&contract(body)).unwrap_err().to_string();
+        assert_eq!(err, message);
+        assert_eq!(classify("compile", &err), "E0001");
+    }
+}
+
+#[test]
+fn test_declared_names_resolve() {
+    let body = "    int delay = unlockTime + 10;
+    require(tx.time >= delay);
+    require(checkSig(ownerSig, owner));
+    require(checkSig(ownerSig, guardians[1]));
+    require(sha256(preimage) == preimage);";
+    let source = contract(body);
+    resolver::check(&parser::parse(&source).unwrap()).unwrap();
+    let artifact = compile(&source).unwrap();
+    for function in &artifact.functions {
+        for token in &function.asm {
+            if let Some(name) = token.strip_prefix('<').and_then(|t| t.strip_suffix('>')) {
+                assert!(
+                    [
+                        "delay",
+                        "owner",
+                        "guardians_1",
+                        "unlockTime",
+                        "ownerSig",
+                        "preimage",
+                        "SERVER_KEY",
+                        "serverSig"
+                    ]
+                    .contains(&name),
+                    "{}",
+                    token
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use arkade_compiler::{compile, compile_with_options, CompileOptions, ResourceBudget};
+
+const SWAP_CODE: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Swap(pubkey maker, pubkey taker, bytes32 assetId, int price) {
+  function swap(signature takerSig) {
+    require(checkSig(takerSig, taker));
+    require(tx.outputs[0].value >= price, "underpaid");
+    require(tx.outputs[1].assets.lookup(assetId) >= 1, "no asset");
+  }
+
+  function choose(signature makerSig, signature takerSig, int mode) {
+    if (mode == 0) {
+      require(checkSig(makerSig, maker));
+    } else {
+      require(checkSig(makerSig, maker));
+      require(checkSig(takerSig, taker));
+    }
+  }
+}
+"#;
+
+fn metrics_of(
+    output: &arkade_compiler::ContractJson,
+    name: &str,
+    server_variant: bool,
+) -> arkade_compiler::ResourceCounts {
+    output
+        .metrics
+        .iter()
+        .find(|m| m.function == name && m.server_variant == server_variant)
+        .unwrap()
+        .resources
+}
+
+#[test]
+fn test_metrics_are_reported_per_leaf() {
+    let output = compile(SWAP_CODE).unwrap();
+    assert_eq!(output.metrics.len(), output.functions.len());
+
+    // Taker signature + server signature
+    let swap = metrics_of(&output, "swap", true);
+    assert_eq!(swap.sigops, 2);
+    assert!(swap.introspection_ops >= 2, "{:?}", swap);
+    assert!(swap.arithmetic64_ops >= 1, "{:?}", swap);
+
+    let json = serde_json::to_string(&output).unwrap();
+    assert!(json.contains("\"introspectionOps\""));
+    assert!(json.contains("\"arithmetic64Ops\""));
+}
+
+#[test]
+fn test_branches_count_the_most_expensive_path() {
+    let output = compile(SWAP_CODE).unwrap();
+    // The else branch checks two signatures, plus the server signature
+    assert_eq!(metrics_of(&output, "choose", true).sigops, 3);
+    assert_eq!(metrics_of(&output, "choose", false).sigops, 2);
+}
+
+#[test]
+fn test_budget_exceeded_fails_compilation() {
+    let options = CompileOptions {
+        budget: ResourceBudget {
+            max_sigops: Some(2),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let err = compile_with_options(SWAP_CODE, &options)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("fn choose (server variant)"), "{}", err);
+    assert!(
+        err.contains("3 signature operations exceed the budget of 2"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_budget_within_limits_compiles() {
+    let options = CompileOptions {
+        budget: ResourceBudget {
+            max_sigops: Some(3),
+            max_introspection_ops: Some(16),
+            max_arithmetic64_ops: Some(16),
+        },
+        ..Default::default()
+    };
+    assert!(compile_with_options(SWAP_CODE, &options).is_ok());
+}
+
+#[test]
+fn test_budget_cli_flags() {
+    use std::fs;
+    use tempfile::tempdir;
+
+    let temp_dir = tempdir().unwrap();
+    let input_path = temp_dir.path().join("swap.ark");
+    let output_path = temp_dir.path().join("swap.json");
+    fs::write(&input_path, SWAP_CODE).unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_arkadec"))
+        .arg(input_path.to_str().unwrap())
+        .arg("-o")
+        .arg(output_path.to_str().unwrap())
+        .arg("--max-introspection")
+        .arg("1")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("introspection operations exceed"),
+        "{}",
+        stderr
+    );
+    assert!(!output_path.exists());
+}
@@ -0,0 +1,94 @@
+#![cfg(feature = "std")]
+
+use arkade_compiler::bindings::rust;
+use arkade_compiler::compile;
+
+// The generated bindings are checked in so that this test crate compiles
+// them; the first two tests fail if they drift from the generator.
+#[allow(dead_code)]
+mod htlc {
+    include!("bindings/htlc.rs");
+}
+
+#[allow(dead_code)]
+mod threshold_oracle {
+    include!("bindings/threshold_oracle.rs");
+}
+
+use htlc::HTLC;
+use threshold_oracle::ThresholdOracle;
+
+#[test]
+fn test_htlc_bindings_are_up_to_date() {
+    let artifact = compile(include_str!("../examples/htlc.ark")).unwrap();
+    assert_eq!(rust::generate(&artifact), include_str!("bindings/htlc.rs"));
+}
+
+#[test]
+fn test_threshold_oracle_bindings_are_up_to_date() {
+    let artifact = compile(include_str!("../examples/threshold_oracle.ark")).unwrap();
+    assert_eq!(
+        rust::generate(&artifact),
+        include_str!("bindings/threshold_oracle.rs")
+    );
+}
+
+fn htlc() -> HTLC {
+    HTLC {
+        sender: [2; 33],
+        receiver: [3; 33],
+        hash: vec![0xab; 32],
+        refund_time: 800_000,
+        server_key: [4; 33],
+    }
+}
+
+#[test]
+fn test_constructor_arguments_are_linked() {
+    let spend = htlc().refund([1; 64], [5; 64]);
+
+    assert_eq!(spend.script[1], "02".repeat(33));
+    assert_eq!(spend.script[3], "800000");
+    assert_eq!(spend.script[7], "04".repeat(33));
+    // Witness placeholders stay in the script
+    assert_eq!(spend.script[0], "<senderSig>");
+    assert_eq!(spend.witness, vec![vec![1; 64], vec![5; 64]]);
+}
+
+#[test]
+fn test_exit_variant_takes_no_server_signature() {
+    let spend = htlc().claim_exit([1; 64], b"secret");
+
+    assert_eq!(spend.witness, vec![vec![1; 64], b"secret".to_vec()]);
+    assert!(spend.script.ends_with(&[
+        "144".to_string(),
+        "OP_CHECKSEQUENCEVERIFY".to_string(),
+        "OP_DROP".to_string()
+    ]));
+}
+
+#[test]
+fn test_int_witness_elements_are_script_numbers() {
+    let oracle = ThresholdOracle {
+        token_asset_id_txid: [1; 32],
+        token_asset_id_gidx: 0,
+        ctrl_asset_id_txid: [2; 32],
+        ctrl_asset_id_gidx: 1,
+        oracles_0: [2; 33],
+        oracles_1: [3; 33],
+        oracles_2: [4; 33],
+        threshold: 2,
+        server_key: [5; 33],
+    };
+    let attest = |amount| {
+        oracle
+            .attest(amount, [0; 32], [0; 64], [0; 64], [0; 64], [0; 64], [2; 33])
+            .witness[0]
+            .clone()
+    };
+
+    assert_eq!(attest(0), Vec::<u8>::new());
+    assert_eq!(attest(128), vec![0x80, 0x00]);
+    assert_eq!(attest(-1), vec![0x81]);
+    assert_eq!(attest(1000), vec![0xe8, 0x03]);
+}
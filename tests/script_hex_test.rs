@@ -0,0 +1,111 @@
+#![cfg(feature = "std")]
+
+use std::collections::HashMap;
+
+use arkade_compiler::compile;
+use arkade_compiler::compiler::assemble;
+use arkade_compiler::linker::{leaf_scripts, link, SERVER_KEY};
+
+const HTLC: &str = include_str!("../examples/htlc.ark");
+const SINGLE_SIG: &str = include_str!("../examples/single_sig.ark");
+
+fn htlc_args() -> HashMap<String, String> {
+    [
+        ("sender", format!("02{}", "11".repeat(32))),
+        ("receiver", format!("03{}", "22".repeat(32))),
+        ("hash", "ab".repeat(32)),
+        ("refundTime", "850000".to_string()),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value))
+    .collect()
+}
+
+fn tokens(asm: &[&str]) -> Vec<String> {
+    asm.iter().map(|token| token.to_string()).collect()
+}
+
+#[test]
+fn test_assemble_uses_minimal_pushes() {
+    let asm = tokens(&[
+        "OP_DUP",
+        "0",
+        "5",
+        "-1",
+        "1000",
+        "0xabcd",
+        "<x>",
+        "<w>",
+        "OP_CHECKSIG",
+    ]);
+    let script = assemble(&asm, |name, script| {
+        if name == "x" {
+            script.extend([0x01, 0x07]);
+        }
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(hex::encode(script), "7600554f02e80302abcd0107ac");
+
+    let err = assemble(&tokens(&["<x>"]), |name, _| Err(format!("no {}", name)));
+    assert_eq!(err.unwrap_err(), "no x");
+    let err = assemble(&tokens(&["OP_NOT_AN_OPCODE"]), |_, _| Ok(()));
+    assert_eq!(err.unwrap_err(), "OP_NOT_AN_OPCODE has no known encoding");
+    let err = assemble(&tokens(&["zz"]), |_, _| Ok(()));
+    assert_eq!(err.unwrap_err(), "cannot assemble 'zz'");
+}
+
+#[test]
+fn test_concrete_leaves_have_script_hex_when_compiled() {
+    let source = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Open() {
+  function spend(signature sig, pubkey key) {
+    require(checkSig(sig, key));
+  }
+}
+"#;
+    let artifact = compile(source).unwrap();
+    for function in &artifact.functions {
+        // The server key is only known when linking
+        assert_eq!(
+            function.script_hex.is_some(),
+            !function.server_variant,
+            "{:?}",
+            function.asm
+        );
+    }
+
+    let artifact = compile(HTLC).unwrap();
+    assert!(artifact.functions.iter().all(|f| f.script_hex.is_none()));
+    let json = serde_json::to_value(&artifact).unwrap();
+    assert!(json["functions"][0].get("scriptHex").is_none());
+}
+
+#[test]
+fn test_linking_sets_script_hex() {
+    let artifact = compile(HTLC).unwrap();
+    let linked = link(&artifact, &htlc_args()).unwrap();
+    for function in linked.functions.iter().filter(|f| f.name != "together") {
+        // Concrete once linked, but for the server key
+        assert_eq!(function.script_hex.is_some(), !function.server_variant);
+    }
+
+    let artifact = compile(SINGLE_SIG).unwrap();
+    let args: HashMap<String, String> = [
+        ("user".to_string(), format!("02{}", "11".repeat(32))),
+        (SERVER_KEY.to_string(), format!("03{}", "33".repeat(32))),
+    ]
+    .into();
+    let linked = link(&artifact, &args).unwrap();
+    let scripts = leaf_scripts(&artifact, &args).unwrap();
+    for (function, script) in linked.functions.iter().zip(&scripts) {
+        assert_eq!(function.script_hex, Some(hex::encode(script)));
+    }
+    let json = serde_json::to_value(&linked).unwrap();
+    assert_eq!(json["functions"][0]["scriptHex"], hex::encode(&scripts[0]));
+}
@@ -0,0 +1,95 @@
+use arkade_compiler::compiler::{compile, compile_with_options, CompileOptions};
+use arkade_compiler::diagnostics::classify;
+use arkade_compiler::ir::{self, limits::ScriptLimits};
+
+fn board(members: usize) -> String {
+    format!(
+        r#"
+contract Board(pubkey[{}] members) {{
+  function spend(signature sig) {{
+    for (i, member) in members {{
+      require(checkSig(sig, member));
+    }}
+  }}
+}}
+"#,
+        members
+    )
+}
+
+#[test]
+fn test_unrolled_loop_over_the_opcode_limit_fails() {
+    // A signature check per member, and for all but the last a copy of
+    // the signature
+    compile(&board(100)).unwrap();
+
+    let err = compile(&board(125)).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "fn spend (server variant): has 249 opcodes, more than the standard limit of 201"
+    );
+    assert_eq!(err.code(), "E0303");
+    assert_eq!(classify("compile", &err.to_string()), "E0303");
+}
+
+#[test]
+fn test_script_size_limit() {
+    let options = CompileOptions {
+        script_limits: ScriptLimits {
+            max_opcodes: usize::MAX,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    // A 33-byte key push and an OP_CHECKSIG per member, and a copy of the
+    // signature, a witness element, for all but the last
+    let err = compile_with_options(&board(300), &options)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.ends_with("script is 10499 bytes, more than the standard limit of 10000"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_limits_are_configurable() {
+    let strict = CompileOptions {
+        script_limits: ScriptLimits {
+            max_opcodes: 10,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let err = compile_with_options(&board(20), &strict)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("more than the standard limit of 10"),
+        "{}",
+        err
+    );
+
+    let lifted = CompileOptions {
+        script_limits: ScriptLimits::NONE,
+        ..Default::default()
+    };
+    compile_with_options(&board(400), &lifted).unwrap();
+}
+
+#[test]
+fn test_element_size_limit() {
+    let limits = ScriptLimits::default();
+    let push = |len: usize| ir::lift(&[format!("0x{}", "ab".repeat(len)), "OP_DROP".into()]);
+    limits.check(&push(520), 600).unwrap();
+    assert_eq!(
+        limits.check(&push(521), 600).unwrap_err(),
+        "pushes a 521-byte element, more than the standard limit of 520"
+    );
+    // Pushed data counts towards the script size whole
+    assert_eq!(
+        ir::script_size(&push(521), &Default::default()),
+        3 + 521 + 1
+    );
+}
@@ -0,0 +1,111 @@
+use arkade_compiler::compiler::{compile_with_options, CompileOptions};
+use arkade_compiler::parser::{parse, parse_for_target, ScriptTarget};
+
+const TIMELOCKED_MULTISIG: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Timelocked(pubkey[3] owners, pubkey recovery, int unlockTime) {
+  function spend(signature[3] sigs) {
+    int valid = 0;
+    for (i, sig) in sigs {
+      if (checkSig(sig, owners[i])) {
+        valid = valid + 1;
+      }
+    }
+    require(valid >= 2);
+  }
+
+  function recover(signature sig) {
+    require(tx.time >= unlockTime);
+    require(checkSig(sig, recovery));
+  }
+}
+"#;
+
+const COVENANT: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Covenant(pubkey owner, bytes32 destination) {
+  function send(signature sig) {
+    require(checkSig(sig, owner));
+    require(tx.outputs[0].scriptPubKey == destination);
+  }
+}
+"#;
+
+fn tapscript() -> CompileOptions {
+    CompileOptions {
+        script_target: ScriptTarget::Tapscript,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_core_syntax_compiles_for_tapscript() {
+    compile_with_options(TIMELOCKED_MULTISIG, &tapscript()).unwrap();
+}
+
+#[test]
+fn test_introspection_is_rejected_on_tapscript() {
+    let err = parse_for_target(COVENANT, ScriptTarget::Tapscript)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("--> 10:13"), "{}", err);
+    assert!(
+        err.contains(
+            "`tx.outputs[0].scriptPubKey` uses transaction introspection, \
+             which is not available on this target (tapscript)"
+        ),
+        "{}",
+        err
+    );
+
+    let err = compile_with_options(COVENANT, &tapscript())
+        .unwrap_err()
+        .to_string();
+    assert!(err.starts_with("Parse error at 10:13:"), "{}", err);
+}
+
+#[test]
+fn test_arkade_opcodes_are_rejected_inside_loops() {
+    let source = r#"
+contract Oracles(pubkey[3] oracles, bytes32 message) {
+  function attest(signature[3] sigs) {
+    for (i, sig) in sigs {
+      require(checkSigFromStack(sig, oracles[i], message));
+    }
+  }
+}
+"#;
+    let err = parse_for_target(source, ScriptTarget::Tapscript)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("uses an Arkade opcode, which is not available on this target (tapscript)"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_arkade_is_the_default_target() {
+    assert_eq!(ScriptTarget::default(), ScriptTarget::Arkade);
+    parse(COVENANT).unwrap();
+    parse_for_target(COVENANT, ScriptTarget::Arkade).unwrap();
+}
+
+#[test]
+fn test_script_target_from_str() {
+    assert_eq!("tapscript".parse(), Ok(ScriptTarget::Tapscript));
+    assert_eq!("arkade".parse(), Ok(ScriptTarget::Arkade));
+    assert_eq!(
+        "bitcoin".parse::<ScriptTarget>(),
+        Err("unknown script target 'bitcoin' (expected arkade or tapscript)".to_string())
+    );
+}
@@ -0,0 +1,106 @@
+use arkade_compiler::compiler::compile;
+use arkade_compiler::models::selector;
+use arkade_compiler::ContractJson;
+use std::collections::HashSet;
+
+const HTLC: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract HTLC(pubkey sender, pubkey receiver, bytes32 hash, int refundTime) {
+  function claim(signature receiverSig, bytes32 preimage) {
+    require(sha256(preimage) == hash);
+    require(checkSig(receiverSig, receiver));
+  }
+
+  function refund(signature senderSig) {
+    require(tx.time >= refundTime);
+    require(checkSig(senderSig, sender));
+  }
+}
+"#;
+
+#[test]
+fn test_every_variant_has_a_selector_and_leaf_index() {
+    let artifact = compile(HTLC).unwrap();
+    let selectors: HashSet<&str> = artifact
+        .functions
+        .iter()
+        .map(|f| f.selector.as_str())
+        .collect();
+    assert_eq!(selectors.len(), artifact.functions.len());
+    for (i, function) in artifact.functions.iter().enumerate() {
+        assert_eq!(function.leaf_index, i);
+        assert_eq!(function.selector.len(), 8);
+    }
+
+    let claim = &artifact.functions[0];
+    assert_eq!(
+        claim.selector,
+        selector("claim", &["signature", "bytes32"], true)
+    );
+    let json = serde_json::to_value(claim).unwrap();
+    assert_eq!(json["selector"], claim.selector.as_str());
+    assert_eq!(json["leafIndex"], 0);
+}
+
+#[test]
+fn test_selectors_depend_only_on_the_signature() {
+    let artifact = compile(HTLC).unwrap();
+    let refund = artifact
+        .functions
+        .iter()
+        .find(|f| f.name == "refund" && !f.server_variant)
+        .unwrap();
+
+    // Reordering the functions moves the leaves but keeps the selectors
+    let claim_start = HTLC.find("  function claim").unwrap();
+    let refund_start = HTLC.find("  function refund").unwrap();
+    let refund_end = HTLC.rfind('}').unwrap();
+    let reordered = format!(
+        "{}{}\n{}}}\n",
+        &HTLC[..claim_start],
+        &HTLC[refund_start..refund_end],
+        &HTLC[claim_start..refund_start].trim_end()
+    );
+    let reordered = compile(&reordered).unwrap();
+    assert_eq!(reordered.functions[0].name, "refund");
+    let moved = reordered.function_by_selector(&refund.selector).unwrap();
+    assert_eq!(
+        (moved.name.as_str(), moved.server_variant),
+        ("refund", false)
+    );
+    assert_ne!(moved.leaf_index, refund.leaf_index);
+
+    // So does changing the body
+    let changed = compile(&HTLC.replace(
+        "checkSig(senderSig, sender)",
+        "checkSig(senderSig, receiver)",
+    ))
+    .unwrap();
+    assert_eq!(
+        changed.function_by_selector(&refund.selector).unwrap().name,
+        "refund"
+    );
+
+    // Changing an input type changes it
+    let retyped = compile(&HTLC.replace("bytes32 preimage)", "bytes preimage)")).unwrap();
+    assert_ne!(
+        retyped.functions[0].selector,
+        artifact.functions[0].selector
+    );
+}
+
+#[test]
+fn test_selectors_survive_reserialization() {
+    let artifact = compile(HTLC).unwrap();
+    let json = serde_json::to_string_pretty(&artifact).unwrap();
+    let reread: ContractJson = serde_json::from_str(&json).unwrap();
+    for (a, b) in artifact.functions.iter().zip(&reread.functions) {
+        assert_eq!(a.selector, b.selector);
+        assert_eq!(a.leaf_index, b.leaf_index);
+    }
+    assert!(reread.function_by_selector("00000000").is_none());
+}
@@ -0,0 +1,108 @@
+#![cfg(feature = "std")]
+
+use arkade_compiler::semantic::{encode, semantic_tokens, SemanticKind, TOKEN_TYPES};
+
+const SOURCE: &str = r#"contract Vault(pubkey owner, int delay) {
+  macro ownerSigned(sig) { require(checkSig(sig, owner)); }
+
+  function spend(signature ownerSig, int amount) {
+    let fee = 100;
+    require(tx.outputs[0].value >= amount);
+    require(fee < amount);
+    ownerSigned(ownerSig);
+  }
+
+  function recover(signature fee) {
+    require(tx.time >= delay);
+    require(checkSig(fee, owner));
+  }
+}
+"#;
+
+fn classified(source: &str) -> Vec<(SemanticKind, &str)> {
+    semantic_tokens(source)
+        .into_iter()
+        .map(|token| (token.kind, &source[token.span.start..token.span.end]))
+        .collect()
+}
+
+#[test]
+fn test_identifiers_resolve_in_their_scope() {
+    use SemanticKind::*;
+    assert_eq!(
+        classified(SOURCE),
+        vec![
+            (ContractName, "Vault"),
+            (Parameter, "owner"),
+            (Parameter, "delay"),
+            (Parameter, "sig"),
+            (Builtin, "checkSig"),
+            (Parameter, "sig"),
+            (Parameter, "owner"),
+            (Parameter, "ownerSig"),
+            (Parameter, "amount"),
+            (Local, "fee"),
+            (Builtin, "tx"),
+            (TxProperty, "outputs"),
+            (TxProperty, "value"),
+            (Parameter, "amount"),
+            (Local, "fee"),
+            (Parameter, "amount"),
+            (Parameter, "ownerSig"),
+            // Not a local here: `fee` is this function's input
+            (Parameter, "fee"),
+            (Builtin, "tx"),
+            (TxProperty, "time"),
+            (Parameter, "delay"),
+            (Builtin, "checkSig"),
+            (Parameter, "fee"),
+            (Parameter, "owner"),
+        ]
+    );
+}
+
+#[test]
+fn test_unparsed_source_still_has_builtins() {
+    use SemanticKind::*;
+    assert_eq!(
+        classified("contract Broken(pubkey owner) { function f() { require(tx.time >= owner"),
+        vec![
+            (ContractName, "Broken"),
+            (Builtin, "tx"),
+            (TxProperty, "time"),
+        ]
+    );
+}
+
+#[test]
+fn test_lsp_encoding() {
+    let source =
+        "contract A(pubkey k) {\n  function f(signature s) { require(checkSig(s, k)); }\n}";
+    let data = encode(source, &semantic_tokens(source));
+    let parameter = TOKEN_TYPES.iter().position(|t| *t == "parameter").unwrap() as u32;
+    let builtin = TOKEN_TYPES.iter().position(|t| *t == "builtin").unwrap() as u32;
+    let contract = TOKEN_TYPES
+        .iter()
+        .position(|t| *t == "contractName")
+        .unwrap() as u32;
+    assert_eq!(
+        data,
+        vec![
+            0, 9, 1, contract, 0, // A
+            0, 9, 1, parameter, 0, // k
+            1, 23, 1, parameter, 0, // s
+            0, 13, 8, builtin, 0, // checkSig
+            0, 9, 1, parameter, 0, // s
+            0, 3, 1, parameter, 0, // k
+        ]
+    );
+}
+
+#[test]
+fn test_lsp_positions_count_utf16_units() {
+    // The emoji is two UTF-16 units, four bytes and one character
+    let source = "contract A(pubkey k) {\n  function f(signature s) { require(checkSig(s, k), \"😀\"); require(checkSig(s, k)); }\n}";
+    let data = encode(source, &semantic_tokens(source));
+    // The second `checkSig` starts 19 units after `k`, not 18 or 21
+    assert_eq!(&data[30..33], &[0, 19, 8]);
+}
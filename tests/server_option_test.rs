@@ -0,0 +1,53 @@
+use arkade_compiler::compile;
+
+fn source(server: &str, params: &str) -> String {
+    format!(
+        r#"
+options {{
+  server = {};
+  exit = 144;
+}}
+
+contract Vault({}) {{
+  function spend(signature ownerSig) {{
+    require(checkSig(ownerSig, owner));
+  }}
+}}
+"#,
+        server, params
+    )
+}
+
+#[test]
+fn test_server_option_naming_external_key() {
+    let output = compile(&source("operator", "pubkey owner")).unwrap();
+    let spend = output
+        .functions
+        .iter()
+        .find(|f| f.name == "spend" && f.server_variant)
+        .unwrap();
+    assert!(spend.asm.contains(&"<SERVER_KEY>".to_string()));
+}
+
+#[test]
+fn test_server_option_naming_pubkey_parameter() {
+    assert!(compile(&source("operator", "pubkey owner, pubkey operator")).is_ok());
+}
+
+#[test]
+fn test_server_option_naming_non_pubkey_parameter() {
+    let err = compile(&source("timeout", "pubkey owner, int timeout")).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Option 'server' names parameter 'timeout' of type int, but the server key must be a pubkey"
+    );
+}
+
+#[test]
+fn test_server_option_must_be_a_name() {
+    let err = compile(&source("42", "pubkey owner")).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Option 'server' must name the operator key, got 42"
+    );
+}
@@ -0,0 +1,174 @@
+#![cfg(feature = "std")]
+
+use arkade_compiler::compile;
+use arkade_compiler::diagnostics::classify;
+use arkade_compiler::encoding::{sighash_flag, sighash_names};
+use arkade_compiler::interpreter::{LeafEvaluator, TxContext, TxInput};
+use arkade_compiler::linker::SERVER_KEY;
+use arkade_compiler::models::{Requirement, Statement};
+use arkade_compiler::opcodes::{OP_CAT, OP_CHECKSIGVERIFY, OP_EQUALVERIFY, OP_SIZE};
+use arkade_compiler::parser::parse;
+use arkade_compiler::printer::print;
+use arkade_compiler::testing::{self, TestSuite};
+use arkade_compiler::vectors;
+
+fn offer(flags: &str) -> String {
+    format!(
+        r#"
+options {{
+  server = server;
+  exit = 144;
+}}
+
+contract Offer(pubkey seller) {{
+  function sell(signature sellerSig) {{
+    require(checkSig(sellerSig, seller{}));
+  }}
+}}
+"#,
+        flags
+    )
+}
+
+fn sell_abi(code: &str) -> arkade_compiler::models::AbiFunction {
+    compile(code)
+        .unwrap()
+        .functions
+        .into_iter()
+        .find(|f| f.name == "sell" && f.server_variant)
+        .unwrap()
+}
+
+#[test]
+fn test_sighash_flags_parse_and_print() {
+    let code = offer(", SIGHASH_SINGLE | ANYONECANPAY");
+    let contract = parse(&code).unwrap();
+    let Statement::Require(Requirement::CheckSig { sighash, .. }, _) =
+        &contract.functions[0].statements[0]
+    else {
+        panic!("expected a signature check");
+    };
+    assert_eq!(*sighash, Some(0x83));
+
+    let printed = print(&contract);
+    assert!(printed.contains("checkSig(sellerSig, seller, SIGHASH_SINGLE | ANYONECANPAY)"));
+    assert_eq!(print(&parse(&printed).unwrap()), printed);
+}
+
+#[test]
+fn test_pinned_flags_are_appended_by_the_script() {
+    let abi = sell_abi(&offer(", SIGHASH_SINGLE | ANYONECANPAY"));
+    assert_eq!(
+        abi.asm[..5],
+        ["<sellerSig>", "0x83", OP_CAT, "<seller>", OP_CHECKSIGVERIFY]
+    );
+    let element = &abi.witness_schema[0];
+    assert_eq!(element.sighash, Some(0x83));
+    assert_eq!(element.encoding, "schnorr-64");
+    assert_eq!(
+        abi.require[0].message.as_deref(),
+        Some("Signed with SIGHASH_SINGLE | ANYONECANPAY")
+    );
+
+    let abi = sell_abi(&offer(", SIGHASH_DEFAULT"));
+    assert_eq!(
+        abi.asm[..6],
+        [
+            "<sellerSig>",
+            OP_SIZE,
+            "64",
+            OP_EQUALVERIFY,
+            "<seller>",
+            OP_CHECKSIGVERIFY
+        ]
+    );
+
+    let abi = sell_abi(&offer(""));
+    assert_eq!(abi.asm[..3], ["<sellerSig>", "<seller>", OP_CHECKSIGVERIFY]);
+    assert_eq!(abi.witness_schema[0].sighash, None);
+}
+
+#[test]
+fn test_disallowed_combinations_are_rejected() {
+    for (flags, reason) in [
+        (
+            "ANYONECANPAY",
+            "ANYONECANPAY needs SIGHASH_ALL, SIGHASH_NONE or SIGHASH_SINGLE",
+        ),
+        (
+            "SIGHASH_ALL | SIGHASH_SINGLE",
+            "combine at most one of SIGHASH_ALL, SIGHASH_NONE and SIGHASH_SINGLE",
+        ),
+        (
+            "SIGHASH_DEFAULT | ANYONECANPAY",
+            "SIGHASH_DEFAULT cannot be combined with other flags",
+        ),
+        (
+            "SIGHASH_NONE | ANYONECANPAY | ANYONECANPAY",
+            "ANYONECANPAY is repeated",
+        ),
+    ] {
+        let err = compile(&offer(&format!(", {}", flags)))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains(&format!("Sighash flags '{}': {}", flags, reason)),
+            "{}",
+            err
+        );
+        assert_eq!(classify("parse", &err), "E0106");
+    }
+}
+
+#[test]
+fn test_sighash_names_round_trip() {
+    for flag in [0x00, 0x01, 0x02, 0x03, 0x81, 0x82, 0x83] {
+        let names = sighash_names(flag);
+        let names: Vec<&str> = names.split(" | ").collect();
+        assert_eq!(sighash_flag(&names), Ok(flag));
+    }
+    assert_eq!(
+        sighash_flag(&["SIGHASH_ANYONECANPAY", "SIGHASH_ALL"]),
+        Ok(0x81)
+    );
+}
+
+#[test]
+fn test_pinned_signatures_are_bare_in_contract_tests() {
+    let code = offer(", SIGHASH_ALL");
+    let suite: TestSuite = serde_json::from_str(
+        r#"{
+  "cases": [
+    { "name": "bare signature", "function": "sell", "expect": "pass" }
+  ]
+}"#,
+    )
+    .unwrap();
+    let report = testing::run(&code, &suite).unwrap();
+    assert_eq!(report.failures(), 0, "{:?}", report.results);
+}
+
+#[test]
+fn test_pinned_leaves_run() {
+    for flags in [", SIGHASH_ALL", ", SIGHASH_DEFAULT"] {
+        let code = offer(flags);
+        let artifact = compile(&code).unwrap();
+        let vectors = vectors::generate(&code).unwrap();
+        let valid = vectors
+            .vectors
+            .iter()
+            .find(|v| v.server_variant && v.valid)
+            .unwrap();
+        let tx = TxContext::builder()
+            .sighash(hex::decode(&vectors.sighash).unwrap().try_into().unwrap())
+            .input(TxInput::new(10_000, vec![0x51, 0x20]))
+            .build()
+            .unwrap();
+        let leaf = LeafEvaluator::for_function(&artifact, "sell", true)
+            .unwrap()
+            .params(vectors.constructor_inputs.iter().map(|v| (&v.name, &v.hex)))
+            .param(SERVER_KEY, &vectors.server_key)
+            .args(valid.witness.iter().map(|v| (&v.name, &v.hex)));
+        assert_eq!(leaf.run(&tx), Ok(()), "{}", flags);
+    }
+}
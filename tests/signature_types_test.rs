@@ -0,0 +1,136 @@
+#![cfg(feature = "std")]
+
+use arkade_compiler::compile;
+use arkade_compiler::encoding::{split_sighash, Value};
+use arkade_compiler::testing::{self, TestSuite};
+use arkade_compiler::vectors;
+
+const ORACLE: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Oracle(pubkey owner, pubkey oracle) {
+  function settle(schnorrsig ownerSig, datasig oracleSig, bytes32 price) {
+    require(checkSig(ownerSig, owner));
+    require(checkSigFromStack(oracleSig, oracle, price));
+  }
+}
+"#;
+
+fn compile_error(code: &str) -> String {
+    compile(code).unwrap_err().to_string()
+}
+
+#[test]
+fn test_signature_kinds_reach_the_witness_schema() {
+    let output = compile(ORACLE).unwrap();
+    let settle = output
+        .functions
+        .iter()
+        .find(|f| f.name == "settle" && f.server_variant)
+        .unwrap();
+    let schema: Vec<_> = settle
+        .witness_schema
+        .iter()
+        .map(|e| (e.name.as_str(), e.elem_type.as_str(), e.encoding.as_str()))
+        .collect();
+    // The signed price goes above the oracle signature, as
+    // OP_CHECKSIGFROMSTACK takes them
+    assert_eq!(
+        schema[..3],
+        [
+            ("ownerSig", "schnorrsig", "schnorr-sighash"),
+            ("price", "bytes32", "raw-32"),
+            ("oracleSig", "datasig", "schnorr-64"),
+        ]
+    );
+}
+
+#[test]
+fn test_signature_checks_reject_the_other_kind() {
+    let swapped = ORACLE.replace(
+        "schnorrsig ownerSig, datasig oracleSig",
+        "datasig ownerSig, schnorrsig oracleSig",
+    );
+    let err = compile_error(&swapped);
+    assert!(
+        err.contains("checkSig() arg 1 'ownerSig' has type 'datasig', expected 'schnorrsig'"),
+        "{}",
+        err
+    );
+
+    let data_only = ORACLE.replace("datasig oracleSig", "schnorrsig oracleSig");
+    let err = compile_error(&data_only);
+    assert!(
+        err.contains(
+            "checkSigFromStack() arg 1 'oracleSig' has type 'schnorrsig', expected 'datasig'"
+        ),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_plain_signatures_fit_every_check() {
+    let plain = ORACLE.replace(
+        "schnorrsig ownerSig, datasig oracleSig",
+        "signature ownerSig, signature oracleSig",
+    );
+    assert!(compile(&plain).is_ok());
+
+    let bytes = ORACLE.replace("schnorrsig ownerSig", "bytes32 ownerSig");
+    let err = compile_error(&bytes);
+    assert!(
+        err.contains("checkSig() arg 1 'ownerSig' has type 'bytes32', expected 'signature'"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_schnorrsig_values_carry_an_optional_sighash_flag() {
+    let signature = "ab".repeat(64);
+    assert!(Value::parse(&signature, "schnorrsig").is_ok());
+    assert!(Value::parse(&format!("{}83", signature), "schnorrsig").is_ok());
+    assert_eq!(
+        Value::parse(&format!("{}00", signature), "schnorrsig").unwrap_err(),
+        "invalid sighash flag 0x00"
+    );
+    assert_eq!(
+        Value::parse(&format!("{}83", signature), "datasig").unwrap_err(),
+        "expected 64 bytes for datasig, got 65"
+    );
+
+    let flagged = hex::decode(format!("{}81", signature)).unwrap();
+    let (sig, flag) = split_sighash(&flagged).unwrap();
+    assert_eq!((sig.len(), flag), (64, Some(0x81)));
+}
+
+#[test]
+fn test_contract_tests_accept_a_sighash_flag() {
+    let vectors = vectors::generate(ORACLE).unwrap();
+    let witness = &vectors
+        .vectors
+        .iter()
+        .find(|v| v.function == "settle" && v.server_variant && v.valid)
+        .unwrap()
+        .witness;
+    let owner_sig = &witness.iter().find(|v| v.name == "ownerSig").unwrap().hex;
+    let oracle_sig = &witness.iter().find(|v| v.name == "oracleSig").unwrap().hex;
+
+    let suite: TestSuite = serde_json::from_str(&format!(
+        r#"{{
+  "cases": [
+    {{ "name": "default", "function": "settle", "expect": "pass" }},
+    {{ "name": "flagged", "function": "settle", "witness": {{ "ownerSig": "{owner_sig}01" }}, "expect": "pass" }},
+    {{ "name": "bad flag", "function": "settle", "witness": {{ "ownerSig": "{owner_sig}04" }}, "expect": "fail" }},
+    {{ "name": "flagged data", "function": "settle", "witness": {{ "oracleSig": "{oracle_sig}01" }}, "expect": "fail" }}
+  ]
+}}"#
+    ))
+    .unwrap();
+    let report = testing::run(ORACLE, &suite).unwrap();
+    assert_eq!(report.failures(), 0, "{:?}", report.results);
+}
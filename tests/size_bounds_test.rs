@@ -0,0 +1,86 @@
+#![cfg(feature = "std")]
+
+use arkade_compiler::compile;
+use arkade_compiler::opcodes::{OP_NIP, OP_SIZE, OP_WITHIN};
+use arkade_compiler::testing::{self, TestSuite};
+
+const MEMO: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Memo(pubkey owner) {
+  function spend(signature ownerSig, bytes memo) {
+    require(size(memo) between 1 and 80);
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+
+#[test]
+fn test_size_bounds_compile_to_op_within() {
+    let output = compile(MEMO).unwrap();
+    let spend = output
+        .functions
+        .iter()
+        .find(|f| f.name == "spend" && f.server_variant)
+        .unwrap();
+
+    assert_eq!(
+        spend.asm[..6],
+        ["<memo>", OP_SIZE, "1", "81", OP_WITHIN, OP_NIP]
+    );
+    assert_eq!(spend.require[0].req_type, "size");
+    assert_eq!(
+        spend.require[0].message.as_deref(),
+        Some("Size of memo between 1 and 80 bytes")
+    );
+}
+
+#[test]
+fn test_size_bounds_are_evaluated_by_contract_tests() {
+    let suite: TestSuite = serde_json::from_str(
+        r#"{
+  "cases": [
+    { "name": "dummy memo", "function": "spend", "expect": "pass" },
+    { "name": "empty memo", "function": "spend", "witness": { "memo": "" }, "expect": "fail" },
+    { "name": "longest memo", "function": "spend", "witness": { "memo": "PLACEHOLDER80" }, "expect": "pass" }
+  ]
+}"#
+        .replace("PLACEHOLDER80", &"ab".repeat(80))
+        .as_str(),
+    )
+    .unwrap();
+    let report = testing::run(MEMO, &suite).unwrap();
+    assert_eq!(report.failures(), 0, "{:?}", report.results);
+}
+
+#[test]
+fn test_empty_size_bounds_are_rejected() {
+    let code = MEMO.replace("between 1 and 80", "between 80 and 1");
+    let err = compile(&code).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Size bounds of 'memo' are empty: 80 is greater than 1"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_size_bounds_on_fixed_width_values_are_reported() {
+    let code = MEMO.replace(
+        "size(memo) between 1 and 80",
+        "size(owner) between 1 and 32",
+    );
+    let output = compile(&code).unwrap();
+    assert!(
+        output.warnings.contains(
+            &"warning[type]: fn spend: size(): 'owner' is always 33 bytes, not between 1 and 32"
+                .to_string()
+        ),
+        "{:?}",
+        output.warnings
+    );
+}
@@ -0,0 +1,81 @@
+use arkade_compiler::compiler::{compile_with_options, CompileOptions};
+use arkade_compiler::Clock;
+use sha2::{Digest, Sha256};
+
+const SINGLE_SIG: &str = r#"
+// Contract configuration options
+options {
+  server = server;
+  exit = 144;
+}
+
+contract SingleSig(pubkey owner) {
+  // Cooperative or unilateral spend by the owner
+  function spend(signature ownerSig) {
+    require(checkSig(ownerSig, owner), "bad signature");
+  }
+}
+"#;
+
+const SINGLE_SIG_REWRITTEN: &str = "options { server = server; exit = 144; }
+contract SingleSig(pubkey owner) {
+    macro ownerSigned(sig) { require(checkSig(sig, owner)); }
+    function spend(signature ownerSig) { ownerSigned(ownerSig); }
+}";
+
+fn normalized() -> CompileOptions {
+    CompileOptions {
+        normalize_source: true,
+        clock: Clock::None,
+        ..Default::default()
+    }
+}
+
+fn to_json(source: &str, options: &CompileOptions) -> String {
+    serde_json::to_string(&compile_with_options(source, options).unwrap()).unwrap()
+}
+
+#[test]
+fn test_equivalent_sources_give_identical_artifacts() {
+    assert_eq!(
+        to_json(SINGLE_SIG, &normalized()),
+        to_json(SINGLE_SIG_REWRITTEN, &normalized())
+    );
+
+    let options = CompileOptions {
+        clock: Clock::None,
+        ..Default::default()
+    };
+    assert_ne!(
+        to_json(SINGLE_SIG, &options),
+        to_json(SINGLE_SIG_REWRITTEN, &options)
+    );
+}
+
+#[test]
+fn test_normalized_source_and_hash() {
+    let artifact = compile_with_options(SINGLE_SIG, &normalized()).unwrap();
+    let source = artifact.source.unwrap();
+    assert_eq!(
+        source,
+        "options {\n  server = server;\n  exit = 144;\n}\n\ncontract SingleSig(pubkey owner) {\n  function spend(signature ownerSig) {\n    require(checkSig(ownerSig, owner));\n  }\n}\n"
+    );
+    assert_eq!(
+        artifact.source_hash.unwrap(),
+        hex::encode(Sha256::digest(source.as_bytes()))
+    );
+
+    // Normalizing the normalized source changes nothing
+    assert_eq!(
+        to_json(&source, &normalized()),
+        to_json(SINGLE_SIG, &normalized())
+    );
+}
+
+#[test]
+fn test_no_hash_without_normalization() {
+    let artifact = arkade_compiler::compile(SINGLE_SIG).unwrap();
+    assert!(artifact.source_hash.is_none());
+    let json = serde_json::to_value(&artifact).unwrap();
+    assert!(json.get("sourceHash").is_none());
+}
@@ -0,0 +1,104 @@
+#![cfg(feature = "std")]
+
+use std::collections::HashMap;
+
+use arkade_compiler::compile;
+use arkade_compiler::linker::{link, link_with_address, SERVER_KEY};
+use arkade_compiler::models::{PathLeaf, SpendGraph};
+use arkade_compiler::taproot::{Network, NUMS_INTERNAL_KEY};
+
+fn vault(options: &str) -> String {
+    format!(
+        r#"
+options {{
+  server = server;
+  exit = 144;{}
+}}
+
+contract Vault(pubkey owner, pubkey guardian) {{
+  function withdraw(signature ownerSig) {{
+    require(checkSig(ownerSig, owner));
+  }}
+
+  function recover(signature guardianSig) {{
+    require(checkSig(guardianSig, guardian));
+  }}
+}}
+"#,
+        options
+    )
+}
+
+fn args() -> HashMap<String, String> {
+    [
+        ("owner", format!("02{}", "11".repeat(32))),
+        ("guardian", format!("03{}", "22".repeat(32))),
+        (SERVER_KEY, format!("03{}", "33".repeat(32))),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value))
+    .collect()
+}
+
+#[test]
+fn test_spend_graph_pairs_each_functions_leaves() {
+    let artifact = compile(&vault("")).unwrap();
+    let graph = artifact.spend_graph.as_ref().unwrap();
+    let functions: Vec<&str> = graph.paths.iter().map(|p| p.function.as_str()).collect();
+    assert_eq!(functions, ["withdraw", "recover"]);
+    for path in &graph.paths {
+        for (leaf, server_variant) in [(&path.server, true), (&path.exit, false)] {
+            let function = &artifact.functions[leaf.leaf_index];
+            assert_eq!(function.name, path.function);
+            assert_eq!(function.server_variant, server_variant);
+            assert_eq!(function.selector, leaf.selector);
+        }
+    }
+    assert_eq!(graph.key_path.internal_key, NUMS_INTERNAL_KEY);
+    assert!(!graph.key_path.spendable);
+    assert_eq!(graph.renew, None);
+    assert_eq!(graph.descriptor, None);
+
+    let json = serde_json::to_value(&artifact).unwrap();
+    assert_eq!(
+        json["spendGraph"]["paths"][1]["exit"]["leafIndex"],
+        serde_json::json!(3)
+    );
+}
+
+#[test]
+fn test_key_path_and_renewal() {
+    let artifact = compile(&vault("\n  renew = 1008;\n  internalKey = owner;")).unwrap();
+    let graph = artifact.spend_graph.as_ref().unwrap();
+    assert_eq!(graph.renew, Some(1008));
+    assert_eq!(graph.key_path.internal_key, "<owner>");
+    assert!(graph.key_path.spendable);
+
+    let linked = link(&artifact, &args()).unwrap();
+    let graph = linked.spend_graph.unwrap();
+    assert_eq!(graph.key_path.internal_key, "11".repeat(32));
+
+    let artifact = compile(&vault("\n  internalKey = unspendable;")).unwrap();
+    let graph = artifact.spend_graph.unwrap();
+    assert_eq!(Some(graph.key_path.internal_key), artifact.internal_key);
+    assert!(!graph.key_path.spendable);
+}
+
+#[test]
+fn test_linked_graph_has_the_combined_descriptor() {
+    let artifact = compile(&vault("")).unwrap();
+    let linked = link_with_address(&artifact, &args(), Network::Regtest).unwrap();
+    let graph = linked.spend_graph.as_ref().unwrap();
+    assert!(linked.descriptor.as_deref().unwrap().starts_with("tr("));
+    assert_eq!(graph.descriptor, linked.descriptor);
+
+    // The same graph as rebuilding it from the linked artifact
+    assert_eq!(&SpendGraph::new(&linked, false, None), graph);
+    assert_eq!(
+        graph.paths[0].server,
+        PathLeaf {
+            leaf_index: 0,
+            selector: linked.functions[0].selector.clone(),
+        }
+    );
+}
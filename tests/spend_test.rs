@@ -0,0 +1,273 @@
+#![cfg(feature = "bitcoin")]
+
+use std::str::FromStr;
+
+use arkade_compiler::compile;
+use arkade_compiler::interpreter::{LeafEvaluator, TxContext, TxInput};
+use arkade_compiler::linker::{link_with_address, SERVER_KEY};
+use arkade_compiler::spend::{SpendBuilder, PSBT_PREFIX, PSBT_WITNESS_ELEMENT};
+use arkade_compiler::taproot::Network;
+use arkade_compiler::witness::WitnessBuilder;
+use bitcoin::key::Secp256k1;
+use bitcoin::psbt::Psbt;
+use bitcoin::taproot::LeafVersion;
+use bitcoin::{Address, Amount, OutPoint, Sequence, XOnlyPublicKey};
+use k256::schnorr::SigningKey;
+
+const SINGLE_SIG: &str = include_str!("../examples/single_sig.ark");
+
+/// x-only key of the generator point
+const G: &str = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+
+fn params() -> Vec<(&'static str, String)> {
+    vec![
+        ("user", format!("02{}", "11".repeat(32))),
+        (SERVER_KEY, format!("03{}", "33".repeat(32))),
+    ]
+}
+
+fn output_key(spend: &arkade_compiler::spend::Spend) -> XOnlyPublicKey {
+    XOnlyPublicKey::from_slice(&spend.script_pubkey.as_bytes()[2..]).unwrap()
+}
+
+#[test]
+fn test_witness_ends_with_leaf_script_and_control_block() {
+    let artifact = compile(SINGLE_SIG).unwrap();
+    let spend = SpendBuilder::new(&artifact)
+        .params(params())
+        .function("spend", false)
+        .arg("userSig", "aa".repeat(64))
+        .build()
+        .unwrap();
+
+    let items: Vec<&[u8]> = spend.witness.iter().collect();
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[0], [0xaa; 64]);
+    assert_eq!(items[1], spend.leaf_script.as_bytes());
+    assert_eq!(items[2], spend.control_block.serialize());
+    let leaf = spend.witness.taproot_leaf_script().unwrap();
+    assert_eq!(leaf.script.as_bytes(), spend.leaf_script.as_bytes());
+
+    let secp = Secp256k1::verification_only();
+    assert!(spend.control_block.verify_taproot_commitment(
+        &secp,
+        output_key(&spend),
+        &spend.leaf_script
+    ));
+}
+
+#[test]
+fn test_output_matches_the_linked_address() {
+    let artifact = compile(SINGLE_SIG).unwrap();
+    let spend = SpendBuilder::new(&artifact)
+        .params(params())
+        .function("spend", true)
+        .args([("userSig", "aa".repeat(64)), ("serverSig", "bb".repeat(64))])
+        .build()
+        .unwrap();
+
+    let args = params()
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+    let linked = link_with_address(&artifact, &args, Network::Regtest).unwrap();
+    let address = Address::from_script(&spend.script_pubkey, bitcoin::Network::Regtest).unwrap();
+    assert_eq!(Some(address.to_string()), linked.address);
+}
+
+#[test]
+fn test_internal_key_can_be_given() {
+    let artifact = compile(SINGLE_SIG).unwrap();
+    let key = XOnlyPublicKey::from_str(G).unwrap();
+    let spend = SpendBuilder::new(&artifact)
+        .params(params())
+        .function("spend", false)
+        .arg("userSig", "aa".repeat(64))
+        .internal_key(key)
+        .build()
+        .unwrap();
+    assert_eq!(spend.control_block.internal_key, key);
+
+    let secp = Secp256k1::verification_only();
+    assert!(spend.control_block.verify_taproot_commitment(
+        &secp,
+        output_key(&spend),
+        &spend.leaf_script
+    ));
+}
+
+#[test]
+fn test_errors_name_what_is_missing() {
+    let artifact = compile(SINGLE_SIG).unwrap();
+    let builder = SpendBuilder::new(&artifact).params(params());
+    assert_eq!(builder.build().unwrap_err(), "No function to spend through");
+    assert_eq!(
+        builder.function("spend", false).build().unwrap_err(),
+        "fn spend (exit variant): missing witness element 'userSig'"
+    );
+}
+
+/// The witness element placeholders of the PSBT's input, bottom first.
+fn placeholders(psbt: &Psbt) -> Vec<(u8, String, Vec<u8>)> {
+    psbt.inputs[0]
+        .proprietary
+        .iter()
+        .map(|(key, value)| {
+            assert_eq!(key.prefix, PSBT_PREFIX);
+            assert_eq!(key.subtype, PSBT_WITNESS_ELEMENT);
+            let name = String::from_utf8(key.key[1..].to_vec()).unwrap();
+            (key.key[0], name, value.clone())
+        })
+        .collect()
+}
+
+#[test]
+fn test_psbt_carries_the_leaf_and_placeholders() {
+    let artifact = compile(SINGLE_SIG).unwrap();
+    let builder = SpendBuilder::new(&artifact)
+        .params(params())
+        .function("spend", true)
+        .arg("userSig", "aa".repeat(64));
+    let outpoint = OutPoint::from_str(&format!("{}:1", "cd".repeat(32))).unwrap();
+    let psbt = builder.psbt(outpoint, Amount::from_sat(5000)).unwrap();
+
+    let spend = builder
+        .clone()
+        .arg("serverSig", "bb".repeat(64))
+        .build()
+        .unwrap();
+    let input = &psbt.inputs[0];
+    let utxo = input.witness_utxo.as_ref().unwrap();
+    assert_eq!(utxo.script_pubkey, spend.script_pubkey);
+    assert_eq!(utxo.value, Amount::from_sat(5000));
+    assert_eq!(
+        input.tap_scripts.get(&spend.control_block),
+        Some(&(spend.leaf_script.clone(), LeafVersion::TapScript))
+    );
+    assert_eq!(
+        input.tap_internal_key,
+        Some(spend.control_block.internal_key)
+    );
+    assert_eq!(psbt.unsigned_tx.input[0].previous_output, outpoint);
+
+    let mut user_sig = vec![0x01, 0x40];
+    user_sig.extend([0xaa; 64]);
+    assert_eq!(
+        placeholders(&psbt),
+        [
+            (0, "serverSig".to_string(), vec![]),
+            (1, "userSig".to_string(), user_sig),
+        ]
+    );
+
+    // The PSBT survives its base64 encoding
+    let decoded = Psbt::from_str(&psbt.to_string()).unwrap();
+    assert_eq!(decoded, psbt);
+}
+
+#[test]
+fn test_exit_psbt_waits_for_the_exit_delay() {
+    let artifact = compile(SINGLE_SIG).unwrap();
+    let psbt = SpendBuilder::new(&artifact)
+        .params(params())
+        .function("spend", false)
+        .psbt(OutPoint::null(), Amount::ZERO)
+        .unwrap();
+    let delay = u16::try_from(artifact.exit_delay).unwrap();
+    assert_eq!(
+        psbt.unsigned_tx.input[0].sequence,
+        Sequence::from_height(delay)
+    );
+    assert_eq!(placeholders(&psbt), [(0, "userSig".to_string(), vec![])]);
+}
+
+#[test]
+fn test_psbt_rejects_unknown_witness_elements() {
+    let artifact = compile(SINGLE_SIG).unwrap();
+    let err = SpendBuilder::new(&artifact)
+        .params(params())
+        .function("spend", false)
+        .arg("preimage", "00")
+        .psbt(OutPoint::null(), Amount::ZERO)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        "fn spend (exit variant): no witness element 'preimage'"
+    );
+}
+
+const BOARD: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Board(pubkey[3] members) {
+  function approve(signature[3] sigs) {
+    require(checkMultisig(members, 2));
+  }
+}
+"#;
+
+#[test]
+fn test_a_threshold_spend_leaves_the_missing_signature_empty() {
+    const SIGHASH: [u8; 32] = [0x07; 32];
+    let signer = |seed: u8| SigningKey::from_bytes(&[seed; 32]).unwrap();
+    let pubkey = |seed: u8| format!("02{}", hex::encode(signer(seed).verifying_key().to_bytes()));
+    let sign = |seed: u8| {
+        let signature = signer(seed).sign_raw(&SIGHASH, &[0u8; 32]).unwrap();
+        hex::encode(signature.to_bytes())
+    };
+    let params = [
+        ("members_0", pubkey(1)),
+        ("members_1", pubkey(2)),
+        ("members_2", pubkey(3)),
+        (SERVER_KEY, pubkey(4)),
+    ];
+
+    // The first and last members sign, the second does not
+    let artifact = compile(BOARD).unwrap();
+    let args = [
+        ("sigs_0", sign(1)),
+        ("sigs_1", String::new()),
+        ("sigs_2", sign(3)),
+        ("serverSig", sign(4)),
+    ];
+    let stack = WitnessBuilder::for_function(&artifact, "approve", true)
+        .unwrap()
+        .args(args.clone())
+        .build()
+        .unwrap();
+    assert_eq!(stack.len(), 4);
+    assert!(stack.contains(&Vec::new()));
+    let spend = SpendBuilder::new(&artifact)
+        .params(params.clone())
+        .function("approve", true)
+        .args(args)
+        .build()
+        .unwrap();
+    let items: Vec<Vec<u8>> = spend.witness.iter().map(<[u8]>::to_vec).collect();
+    assert_eq!(items[..items.len() - 2], stack);
+
+    let tx = TxContext::builder()
+        .sighash(SIGHASH)
+        .input(TxInput::new(10_000, spend.script_pubkey.to_bytes()))
+        .build()
+        .unwrap();
+    let leaf = LeafEvaluator::for_function(&artifact, "approve", true)
+        .unwrap()
+        .params(params.clone())
+        .witness_stack(stack.clone());
+    assert_eq!(leaf.run(&tx), Ok(()));
+
+    // An empty signature does not count, but one that does not verify
+    // fails the leaf
+    let position = stack.iter().position(Vec::is_empty).unwrap();
+    let mut forged = stack;
+    forged[position] = vec![0xaa; 64];
+    let leaf = LeafEvaluator::for_function(&artifact, "approve", true)
+        .unwrap()
+        .params(params)
+        .witness_stack(forged);
+    assert!(leaf.run(&tx).is_err());
+}
@@ -0,0 +1,86 @@
+use arkade_compiler::compile;
+use arkade_compiler::opcodes::{OP_EQUALVERIFY, OP_SHA256, OP_SIZE};
+
+fn htlc(options: &str) -> String {
+    format!(
+        r#"
+options {{
+  server = server;
+  exit = 144;{}
+}}
+
+contract HTLC(pubkey receiver, bytes32 hash) {{
+  function claim(signature receiverSig, bytes32 preimage) {{
+    require(sha256(preimage) == hash);
+    require(checkSig(receiverSig, receiver));
+  }}
+}}
+"#,
+        options
+    )
+}
+
+fn claim_asm(code: &str) -> Vec<String> {
+    let output = compile(code).unwrap();
+    output
+        .functions
+        .into_iter()
+        .find(|f| f.name == "claim" && f.server_variant)
+        .unwrap()
+        .asm
+}
+
+#[test]
+fn test_hashlocks_are_not_size_checked_by_default() {
+    let asm = claim_asm(&htlc(""));
+    assert_eq!(
+        asm[..4],
+        ["<preimage>", OP_SHA256, "<hash>", OP_EQUALVERIFY]
+    );
+}
+
+#[test]
+fn test_strict_hashlocks_check_preimage_size() {
+    let asm = claim_asm(&htlc("\n  strictHashlocks = true;"));
+    assert_eq!(
+        asm[..7],
+        [
+            "<preimage>",
+            OP_SIZE,
+            "32",
+            OP_EQUALVERIFY,
+            OP_SHA256,
+            "<hash>",
+            OP_EQUALVERIFY
+        ]
+    );
+}
+
+#[test]
+fn test_unsized_hashlock_warning() {
+    let hashlock_warnings = |code: &str| -> Vec<String> {
+        compile(code)
+            .unwrap()
+            .warnings
+            .into_iter()
+            .filter(|w| w.contains("hash lock"))
+            .collect()
+    };
+
+    assert_eq!(
+        hashlock_warnings(&htlc("")),
+        vec!["warning[malleable]: fn claim: hash lock on `preimage` at 9:5 accepts a preimage of any size; set `strictHashlocks = true` to require 32 bytes"]
+    );
+    assert!(hashlock_warnings(&htlc("\n  strictHashlocks = true;")).is_empty());
+}
+
+#[test]
+fn test_strict_hashlocks_must_be_a_boolean() {
+    let err = compile(&htlc("\n  strictHashlocks = yes;")).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Option 'strictHashlocks' must be true or false, got 'yes'"),
+        "{}",
+        err
+    );
+}
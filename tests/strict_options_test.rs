@@ -0,0 +1,105 @@
+use arkade_compiler::compiler::{compile, compile_with_options, CompileOptions};
+use arkade_compiler::diagnostics::classify;
+
+fn with_options(options: &str) -> String {
+    format!(
+        r#"
+options {{ {} }}
+contract SingleSig(pubkey owner) {{
+  function spend(signature ownerSig) {{
+    require(checkSig(ownerSig, owner));
+  }}
+}}
+"#,
+        options
+    )
+}
+
+fn lenient() -> CompileOptions {
+    CompileOptions {
+        lenient_options: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_misspelt_option_is_rejected_with_a_suggestion() {
+    let err = compile(&with_options("server = server; exitt = 144;")).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Unknown option 'exitt': did you mean 'exit'?"),
+        "{}",
+        err
+    );
+    assert_eq!(
+        err.span().map(|span| span.line),
+        Some(2),
+        "should point at the setting: {}",
+        err
+    );
+    assert_eq!(err.code(), "E0205");
+}
+
+#[test]
+fn test_unrelated_option_lists_the_known_ones() {
+    let err = compile(&with_options("server = server; fee = 10;"))
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains(
+            "Unknown option 'fee': expected server, exit, renew, internalKey or strictHashlocks"
+        ),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_option_values_are_checked_by_kind() {
+    let cases = [
+        (
+            "server = 144;",
+            "Option 'server' must name the operator key, got 144",
+        ),
+        (
+            "server = server; exit = \"144\";",
+            "Option 'exit' must be a number, a duration or a built-in constant, got \"144\"",
+        ),
+        (
+            "server = server; renew = \"1 day\";",
+            "Option 'renew' must be a number, a duration or a built-in constant, got \"1 day\"",
+        ),
+        (
+            "server = server; internalKey = 0;",
+            "Option 'internalKey' must be unspendable, nums or a pubkey parameter",
+        ),
+    ];
+    for (options, expected) in cases {
+        let err = compile(&with_options(options)).unwrap_err().to_string();
+        assert!(err.contains(expected), "{}: {}", options, err);
+    }
+}
+
+#[test]
+fn test_valid_options_compile() {
+    let source = with_options(
+        "server = server; exit = BLOCKS_PER_DAY; renew = 1008; \
+         internalKey = owner; strictHashlocks = true;",
+    );
+    let artifact = compile(&source).unwrap();
+    assert_eq!(artifact.exit_delay, 144);
+}
+
+#[test]
+fn test_lenient_options_ignore_unknown_names() {
+    let source = with_options("server = server; exitt = 144;");
+    let artifact = compile_with_options(&source, &lenient()).unwrap();
+    assert_eq!(artifact.exit_delay, 288);
+
+    // Values are still checked when compiling
+    let source = with_options("server = server; exit = ONE_DAY;");
+    let err = compile_with_options(&source, &lenient())
+        .unwrap_err()
+        .to_string();
+    assert_eq!(classify("compile", &err), "E0204");
+}
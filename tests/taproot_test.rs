@@ -0,0 +1,106 @@
+use arkade_compiler::models::{TapNode, TaprootTree};
+use arkade_compiler::taproot::{
+    leaf_hash, unspendable_key, Network, TaprootOutput, NUMS_INTERNAL_KEY,
+};
+
+fn key(hex_key: &str) -> [u8; 32] {
+    hex::decode(hex_key).unwrap().try_into().unwrap()
+}
+
+// BIP 341 wallet test vector: a single-leaf script tree
+#[test]
+fn test_single_leaf_output_matches_bip341() {
+    let script =
+        hex::decode("20d85a959b0290bf19bb89ed43c916be835475d013da4b362117393e25a48229b8ac")
+            .unwrap();
+    assert_eq!(
+        hex::encode(leaf_hash(&script)),
+        "5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21"
+    );
+
+    let internal = key("187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27");
+    let output = TaprootOutput::with_internal_key(internal, vec![script]).unwrap();
+    assert_eq!(
+        hex::encode(output.output_key),
+        "147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3"
+    );
+    assert_eq!(
+        output.address(Network::Bitcoin),
+        "bc1pz37fc4cn9ah8anwm4xqqhvxygjf9rjf2resrw8h8w4tmvcs0863sa2e586"
+    );
+}
+
+#[test]
+fn test_descriptor_pairs_leaves_in_order() {
+    let output = TaprootOutput::new(vec![vec![0x51], vec![0x52]]).unwrap();
+    assert_eq!(
+        output.descriptor(),
+        format!("tr({},{{raw(51),raw(52)}})#5j7wyf2u", NUMS_INTERNAL_KEY)
+    );
+
+    let three = TaprootOutput::new(vec![vec![0x51], vec![0x52], vec![0x53]]).unwrap();
+    assert!(three.descriptor().contains("{{raw(51),raw(52)},raw(53)}"));
+    assert!(three.address(Network::Regtest).starts_with("bcrt1p"));
+}
+
+#[test]
+fn test_unspendable_key() {
+    assert_eq!(unspendable_key(None).unwrap(), key(NUMS_INTERNAL_KEY));
+
+    // H + 1·G
+    let mut one = [0u8; 32];
+    one[31] = 1;
+    let tweaked = unspendable_key(Some(&one)).unwrap();
+    assert_ne!(tweaked, key(NUMS_INTERNAL_KEY));
+    assert_eq!(tweaked, unspendable_key(Some(&one)).unwrap());
+}
+
+#[test]
+fn test_control_blocks() {
+    let leaves = vec![vec![0x51], vec![0x52], vec![0x53]];
+    let output = TaprootOutput::new(leaves.clone()).unwrap();
+
+    // The third leaf sits one level up, next to the branch of the other two
+    let block = output.control_block(2).unwrap();
+    assert_eq!(block.len(), 33 + 32);
+    assert_eq!(block[0], 0xc0 | u8::from(output.output_key_parity));
+    assert_eq!(&block[1..33], &key(NUMS_INTERNAL_KEY));
+
+    let block = output.control_block(0).unwrap();
+    assert_eq!(block.len(), 33 + 2 * 32);
+    assert_eq!(&block[33..65], &leaf_hash(&leaves[1]));
+
+    assert!(output.control_block(3).is_err());
+}
+
+#[test]
+fn test_tree_lists_every_node_hash() {
+    let leaves = vec![vec![0x51], vec![0x52], vec![0x53]];
+    let output = TaprootOutput::new(leaves.clone()).unwrap();
+    let tree = output.tree();
+    assert_eq!(tree.internal_key, NUMS_INTERNAL_KEY);
+    assert_eq!(tree.output_key, hex::encode(output.output_key));
+    assert_eq!(tree.parity, output.output_key_parity);
+    assert_eq!(tree.merkle_root, hex::encode(output.merkle_root));
+    assert_eq!(tree.root.hash(), tree.merkle_root);
+
+    let TapNode::Branch { left, right, .. } = &tree.root else {
+        panic!("{:?}", tree.root);
+    };
+    assert_eq!(
+        **right,
+        TapNode::Leaf {
+            leaf_index: 2,
+            hash: hex::encode(leaf_hash(&leaves[2])),
+        }
+    );
+    let TapNode::Branch { left: first, .. } = left.as_ref() else {
+        panic!("{:?}", left);
+    };
+    assert_eq!(first.hash(), hex::encode(leaf_hash(&leaves[0])));
+
+    // Leaves and branches read back from JSON
+    let json = serde_json::to_value(&tree).unwrap();
+    assert_eq!(json["root"]["right"]["leafIndex"], 2);
+    assert_eq!(serde_json::from_value::<TaprootTree>(json).unwrap(), tree);
+}
@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+
+use arkade_compiler::compile;
+use arkade_compiler::templates::{self, TEMPLATES};
+
+fn values(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_templates_compile_without_warnings() {
+    for template in TEMPLATES {
+        let source = template.render(&BTreeMap::new()).unwrap();
+        assert!(!source.contains("{{"), "{}: {}", template.name, source);
+        let output = compile(&source).unwrap();
+        assert!(
+            output.warnings.is_empty(),
+            "{}: {:?}",
+            template.name,
+            output.warnings
+        );
+    }
+}
+
+#[test]
+fn test_htlc_template_is_populated_from_options() {
+    let htlc = templates::find("htlc").unwrap();
+    let source = htlc
+        .render(&values(&[("hash", "sha256"), ("timeout", "1008")]))
+        .unwrap();
+    let output = compile(&source).unwrap();
+
+    assert_eq!(output.name, "HTLC");
+    assert_eq!(output.exit_delay, 1008);
+    let claim = output
+        .functions
+        .iter()
+        .find(|f| f.name == "claim" && f.server_variant)
+        .unwrap();
+    assert_eq!(
+        claim.asm[..5],
+        ["<preimage>", "OP_SIZE", "32", "OP_EQUALVERIFY", "OP_SHA256"]
+    );
+}
+
+#[test]
+fn test_htlc_template_timeout_accepts_constants() {
+    let htlc = templates::find("htlc").unwrap();
+    let source = htlc
+        .render(&values(&[("timeout", "BLOCKS_PER_DAY")]))
+        .unwrap();
+    assert_eq!(compile(&source).unwrap().exit_delay, 144);
+}
+
+#[test]
+fn test_template_option_errors() {
+    let htlc = templates::find("htlc").unwrap();
+    assert_eq!(
+        htlc.render(&values(&[("hash", "ripemd160")])).unwrap_err(),
+        "Option 'hash' must be one of sha256, got 'ripemd160'"
+    );
+    assert_eq!(
+        htlc.render(&values(&[("timeout", "soon")])).unwrap_err(),
+        "Option 'timeout' must be a number of blocks or a built-in constant, got 'soon'"
+    );
+    assert_eq!(
+        htlc.render(&values(&[("owner", "alice")])).unwrap_err(),
+        "Template 'htlc' has no option 'owner' (options: hash, timeout)"
+    );
+    assert!(templates::find("escrow").is_none());
+}
+
+#[test]
+fn test_vault_template() {
+    let vault = templates::find("vault").unwrap();
+    let source = vault
+        .render(&values(&[("timeout", "432"), ("renew", "2016")]))
+        .unwrap();
+    let output = compile(&source).unwrap();
+    assert_eq!(output.exit_delay, 432);
+
+    let asm = |name: &str| {
+        output
+            .functions
+            .iter()
+            .find(|f| f.name == name && f.server_variant)
+            .unwrap()
+            .asm
+            .join(" ")
+    };
+    assert!(asm("withdraw").starts_with("<unlockTime> OP_CHECKLOCKTIMEVERIFY OP_DROP"));
+    assert!(asm("clawback")
+        .starts_with("0 OP_INSPECTOUTPUTSCRIPTPUBKEY <VTXO:SingleSig(<recovery>)> OP_EQUAL"));
+}
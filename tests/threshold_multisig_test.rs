@@ -1,6 +1,7 @@
 use arkade_compiler::compile;
 use arkade_compiler::opcodes::{
     OP_2, OP_3, OP_5, OP_CHECKSEQUENCEVERIFY, OP_CHECKSIG, OP_CHECKSIGADD, OP_DROP, OP_NUMEQUAL,
+    OP_NUMEQUALVERIFY,
 };
 use serde_json::Value;
 use std::fs;
@@ -83,16 +84,18 @@ fn test_threshold_multisig() {
     assert_eq!(two_of_two_function.require[0].req_type, "multisig");
 
     // Check assembly instructions
-    assert_eq!(two_of_two_function.asm.len(), 9);
-    assert_eq!(two_of_two_function.asm[0], "<signer>");
-    assert_eq!(two_of_two_function.asm[1], OP_CHECKSIG);
-    assert_eq!(two_of_two_function.asm[2], "<signer1>");
-    assert_eq!(two_of_two_function.asm[3], OP_CHECKSIGADD);
-    assert_eq!(two_of_two_function.asm[4], OP_2);
-    assert_eq!(two_of_two_function.asm[5], OP_NUMEQUAL);
-    assert_eq!(two_of_two_function.asm[6], "<SERVER_KEY>");
-    assert_eq!(two_of_two_function.asm[7], "<serverSig>");
-    assert_eq!(two_of_two_function.asm[8], OP_CHECKSIG);
+    assert_eq!(two_of_two_function.asm.len(), 11);
+    assert_eq!(two_of_two_function.asm[0], "<signer1Sig>");
+    assert_eq!(two_of_two_function.asm[1], "<signerSig>");
+    assert_eq!(two_of_two_function.asm[2], "<signer>");
+    assert_eq!(two_of_two_function.asm[3], OP_CHECKSIG);
+    assert_eq!(two_of_two_function.asm[4], "<signer1>");
+    assert_eq!(two_of_two_function.asm[5], OP_CHECKSIGADD);
+    assert_eq!(two_of_two_function.asm[6], OP_2);
+    assert_eq!(two_of_two_function.asm[7], OP_NUMEQUALVERIFY);
+    assert_eq!(two_of_two_function.asm[8], "<serverSig>");
+    assert_eq!(two_of_two_function.asm[9], "<SERVER_KEY>");
+    assert_eq!(two_of_two_function.asm[10], OP_CHECKSIG);
 
     // Verify fiveOfFive function with server variant
     let five_of_five_function = output
@@ -108,22 +111,27 @@ fn test_threshold_multisig() {
     assert_eq!(five_of_five_function.require[0].req_type, "multisig");
 
     // Check assembly instructions
-    assert_eq!(five_of_five_function.asm.len(), 15);
-    assert_eq!(five_of_five_function.asm[0], "<signer>");
-    assert_eq!(five_of_five_function.asm[1], OP_CHECKSIG);
-    assert_eq!(five_of_five_function.asm[2], "<signer1>");
-    assert_eq!(five_of_five_function.asm[3], OP_CHECKSIGADD);
-    assert_eq!(five_of_five_function.asm[4], "<signer2>");
-    assert_eq!(five_of_five_function.asm[5], OP_CHECKSIGADD);
-    assert_eq!(five_of_five_function.asm[6], "<signer3>");
-    assert_eq!(five_of_five_function.asm[7], OP_CHECKSIGADD);
-    assert_eq!(five_of_five_function.asm[8], "<signer4>");
-    assert_eq!(five_of_five_function.asm[9], OP_CHECKSIGADD);
-    assert_eq!(five_of_five_function.asm[10], OP_5);
-    assert_eq!(five_of_five_function.asm[11], OP_NUMEQUAL);
-    assert_eq!(five_of_five_function.asm[12], "<SERVER_KEY>");
-    assert_eq!(five_of_five_function.asm[13], "<serverSig>");
-    assert_eq!(five_of_five_function.asm[14], OP_CHECKSIG);
+    assert_eq!(five_of_five_function.asm.len(), 20);
+    assert_eq!(five_of_five_function.asm[0], "<signer4Sig>");
+    assert_eq!(five_of_five_function.asm[1], "<signer3Sig>");
+    assert_eq!(five_of_five_function.asm[2], "<signer2Sig>");
+    assert_eq!(five_of_five_function.asm[3], "<signer1Sig>");
+    assert_eq!(five_of_five_function.asm[4], "<signerSig>");
+    assert_eq!(five_of_five_function.asm[5], "<signer>");
+    assert_eq!(five_of_five_function.asm[6], OP_CHECKSIG);
+    assert_eq!(five_of_five_function.asm[7], "<signer1>");
+    assert_eq!(five_of_five_function.asm[8], OP_CHECKSIGADD);
+    assert_eq!(five_of_five_function.asm[9], "<signer2>");
+    assert_eq!(five_of_five_function.asm[10], OP_CHECKSIGADD);
+    assert_eq!(five_of_five_function.asm[11], "<signer3>");
+    assert_eq!(five_of_five_function.asm[12], OP_CHECKSIGADD);
+    assert_eq!(five_of_five_function.asm[13], "<signer4>");
+    assert_eq!(five_of_five_function.asm[14], OP_CHECKSIGADD);
+    assert_eq!(five_of_five_function.asm[15], OP_5);
+    assert_eq!(five_of_five_function.asm[16], OP_NUMEQUALVERIFY);
+    assert_eq!(five_of_five_function.asm[17], "<serverSig>");
+    assert_eq!(five_of_five_function.asm[18], "<SERVER_KEY>");
+    assert_eq!(five_of_five_function.asm[19], OP_CHECKSIG);
 
     // Verify threeOfFive function with server variant
     let three_of_five_function = output
@@ -139,22 +147,27 @@ fn test_threshold_multisig() {
     assert_eq!(three_of_five_function.require[0].req_type, "multisig");
 
     // Check assembly instructions
-    assert_eq!(three_of_five_function.asm.len(), 15);
-    assert_eq!(three_of_five_function.asm[0], "<signer>");
-    assert_eq!(three_of_five_function.asm[1], OP_CHECKSIG);
-    assert_eq!(three_of_five_function.asm[2], "<signer1>");
-    assert_eq!(three_of_five_function.asm[3], OP_CHECKSIGADD);
-    assert_eq!(three_of_five_function.asm[4], "<signer2>");
-    assert_eq!(three_of_five_function.asm[5], OP_CHECKSIGADD);
-    assert_eq!(three_of_five_function.asm[6], "<signer3>");
-    assert_eq!(three_of_five_function.asm[7], OP_CHECKSIGADD);
-    assert_eq!(three_of_five_function.asm[8], "<signer4>");
-    assert_eq!(three_of_five_function.asm[9], OP_CHECKSIGADD);
-    assert_eq!(three_of_five_function.asm[10], OP_3);
-    assert_eq!(three_of_five_function.asm[11], OP_NUMEQUAL);
-    assert_eq!(three_of_five_function.asm[12], "<SERVER_KEY>");
-    assert_eq!(three_of_five_function.asm[13], "<serverSig>");
-    assert_eq!(three_of_five_function.asm[14], OP_CHECKSIG);
+    assert_eq!(three_of_five_function.asm.len(), 20);
+    assert_eq!(three_of_five_function.asm[0], "<signer4Sig>");
+    assert_eq!(three_of_five_function.asm[1], "<signer3Sig>");
+    assert_eq!(three_of_five_function.asm[2], "<signer2Sig>");
+    assert_eq!(three_of_five_function.asm[3], "<signer1Sig>");
+    assert_eq!(three_of_five_function.asm[4], "<signerSig>");
+    assert_eq!(three_of_five_function.asm[5], "<signer>");
+    assert_eq!(three_of_five_function.asm[6], OP_CHECKSIG);
+    assert_eq!(three_of_five_function.asm[7], "<signer1>");
+    assert_eq!(three_of_five_function.asm[8], OP_CHECKSIGADD);
+    assert_eq!(three_of_five_function.asm[9], "<signer2>");
+    assert_eq!(three_of_five_function.asm[10], OP_CHECKSIGADD);
+    assert_eq!(three_of_five_function.asm[11], "<signer3>");
+    assert_eq!(three_of_five_function.asm[12], OP_CHECKSIGADD);
+    assert_eq!(three_of_five_function.asm[13], "<signer4>");
+    assert_eq!(three_of_five_function.asm[14], OP_CHECKSIGADD);
+    assert_eq!(three_of_five_function.asm[15], OP_3);
+    assert_eq!(three_of_five_function.asm[16], OP_NUMEQUALVERIFY);
+    assert_eq!(three_of_five_function.asm[17], "<serverSig>");
+    assert_eq!(three_of_five_function.asm[18], "<SERVER_KEY>");
+    assert_eq!(three_of_five_function.asm[19], OP_CHECKSIG);
 
     // Verify twoOfTwo function with exit path
     let two_of_two_function = output
@@ -170,16 +183,18 @@ fn test_threshold_multisig() {
     assert_eq!(two_of_two_function.require[0].req_type, "multisig");
 
     // Check assembly instructions
-    assert_eq!(two_of_two_function.asm.len(), 9);
-    assert_eq!(two_of_two_function.asm[0], "<signer>");
-    assert_eq!(two_of_two_function.asm[1], OP_CHECKSIG);
-    assert_eq!(two_of_two_function.asm[2], "<signer1>");
-    assert_eq!(two_of_two_function.asm[3], OP_CHECKSIGADD);
-    assert_eq!(two_of_two_function.asm[4], OP_2);
-    assert_eq!(two_of_two_function.asm[5], OP_NUMEQUAL);
-    assert_eq!(two_of_two_function.asm[6], "144");
-    assert_eq!(two_of_two_function.asm[7], OP_CHECKSEQUENCEVERIFY);
-    assert_eq!(two_of_two_function.asm[8], OP_DROP);
+    assert_eq!(two_of_two_function.asm.len(), 11);
+    assert_eq!(two_of_two_function.asm[0], "<signer1Sig>");
+    assert_eq!(two_of_two_function.asm[1], "<signerSig>");
+    assert_eq!(two_of_two_function.asm[2], "<signer>");
+    assert_eq!(two_of_two_function.asm[3], OP_CHECKSIG);
+    assert_eq!(two_of_two_function.asm[4], "<signer1>");
+    assert_eq!(two_of_two_function.asm[5], OP_CHECKSIGADD);
+    assert_eq!(two_of_two_function.asm[6], OP_2);
+    assert_eq!(two_of_two_function.asm[7], OP_NUMEQUAL);
+    assert_eq!(two_of_two_function.asm[8], "144");
+    assert_eq!(two_of_two_function.asm[9], OP_CHECKSEQUENCEVERIFY);
+    assert_eq!(two_of_two_function.asm[10], OP_DROP);
 
     // Verify fiveOfFive function with exit path
     let five_of_five_function = output
@@ -195,22 +210,27 @@ fn test_threshold_multisig() {
     assert_eq!(five_of_five_function.require[0].req_type, "multisig");
 
     // Check assembly instructions
-    assert_eq!(five_of_five_function.asm.len(), 15);
-    assert_eq!(five_of_five_function.asm[0], "<signer>");
-    assert_eq!(five_of_five_function.asm[1], OP_CHECKSIG);
-    assert_eq!(five_of_five_function.asm[2], "<signer1>");
-    assert_eq!(five_of_five_function.asm[3], OP_CHECKSIGADD);
-    assert_eq!(five_of_five_function.asm[4], "<signer2>");
-    assert_eq!(five_of_five_function.asm[5], OP_CHECKSIGADD);
-    assert_eq!(five_of_five_function.asm[6], "<signer3>");
-    assert_eq!(five_of_five_function.asm[7], OP_CHECKSIGADD);
-    assert_eq!(five_of_five_function.asm[8], "<signer4>");
-    assert_eq!(five_of_five_function.asm[9], OP_CHECKSIGADD);
-    assert_eq!(five_of_five_function.asm[10], OP_5);
-    assert_eq!(five_of_five_function.asm[11], OP_NUMEQUAL);
-    assert_eq!(five_of_five_function.asm[12], "144");
-    assert_eq!(five_of_five_function.asm[13], OP_CHECKSEQUENCEVERIFY);
-    assert_eq!(five_of_five_function.asm[14], OP_DROP);
+    assert_eq!(five_of_five_function.asm.len(), 20);
+    assert_eq!(five_of_five_function.asm[0], "<signer4Sig>");
+    assert_eq!(five_of_five_function.asm[1], "<signer3Sig>");
+    assert_eq!(five_of_five_function.asm[2], "<signer2Sig>");
+    assert_eq!(five_of_five_function.asm[3], "<signer1Sig>");
+    assert_eq!(five_of_five_function.asm[4], "<signerSig>");
+    assert_eq!(five_of_five_function.asm[5], "<signer>");
+    assert_eq!(five_of_five_function.asm[6], OP_CHECKSIG);
+    assert_eq!(five_of_five_function.asm[7], "<signer1>");
+    assert_eq!(five_of_five_function.asm[8], OP_CHECKSIGADD);
+    assert_eq!(five_of_five_function.asm[9], "<signer2>");
+    assert_eq!(five_of_five_function.asm[10], OP_CHECKSIGADD);
+    assert_eq!(five_of_five_function.asm[11], "<signer3>");
+    assert_eq!(five_of_five_function.asm[12], OP_CHECKSIGADD);
+    assert_eq!(five_of_five_function.asm[13], "<signer4>");
+    assert_eq!(five_of_five_function.asm[14], OP_CHECKSIGADD);
+    assert_eq!(five_of_five_function.asm[15], OP_5);
+    assert_eq!(five_of_five_function.asm[16], OP_NUMEQUAL);
+    assert_eq!(five_of_five_function.asm[17], "144");
+    assert_eq!(five_of_five_function.asm[18], OP_CHECKSEQUENCEVERIFY);
+    assert_eq!(five_of_five_function.asm[19], OP_DROP);
 
     // Verify threeOfFive function with exit path
     let three_of_five_function = output
@@ -226,22 +246,27 @@ fn test_threshold_multisig() {
     assert_eq!(three_of_five_function.require[0].req_type, "multisig");
 
     // Check assembly instructions
-    assert_eq!(three_of_five_function.asm.len(), 15);
-    assert_eq!(three_of_five_function.asm[0], "<signer>");
-    assert_eq!(three_of_five_function.asm[1], OP_CHECKSIG);
-    assert_eq!(three_of_five_function.asm[2], "<signer1>");
-    assert_eq!(three_of_five_function.asm[3], OP_CHECKSIGADD);
-    assert_eq!(three_of_five_function.asm[4], "<signer2>");
-    assert_eq!(three_of_five_function.asm[5], OP_CHECKSIGADD);
-    assert_eq!(three_of_five_function.asm[6], "<signer3>");
-    assert_eq!(three_of_five_function.asm[7], OP_CHECKSIGADD);
-    assert_eq!(three_of_five_function.asm[8], "<signer4>");
-    assert_eq!(three_of_five_function.asm[9], OP_CHECKSIGADD);
-    assert_eq!(three_of_five_function.asm[10], OP_3);
-    assert_eq!(three_of_five_function.asm[11], OP_NUMEQUAL);
-    assert_eq!(three_of_five_function.asm[12], "144");
-    assert_eq!(three_of_five_function.asm[13], OP_CHECKSEQUENCEVERIFY);
-    assert_eq!(three_of_five_function.asm[14], OP_DROP);
+    assert_eq!(three_of_five_function.asm.len(), 20);
+    assert_eq!(three_of_five_function.asm[0], "<signer4Sig>");
+    assert_eq!(three_of_five_function.asm[1], "<signer3Sig>");
+    assert_eq!(three_of_five_function.asm[2], "<signer2Sig>");
+    assert_eq!(three_of_five_function.asm[3], "<signer1Sig>");
+    assert_eq!(three_of_five_function.asm[4], "<signerSig>");
+    assert_eq!(three_of_five_function.asm[5], "<signer>");
+    assert_eq!(three_of_five_function.asm[6], OP_CHECKSIG);
+    assert_eq!(three_of_five_function.asm[7], "<signer1>");
+    assert_eq!(three_of_five_function.asm[8], OP_CHECKSIGADD);
+    assert_eq!(three_of_five_function.asm[9], "<signer2>");
+    assert_eq!(three_of_five_function.asm[10], OP_CHECKSIGADD);
+    assert_eq!(three_of_five_function.asm[11], "<signer3>");
+    assert_eq!(three_of_five_function.asm[12], OP_CHECKSIGADD);
+    assert_eq!(three_of_five_function.asm[13], "<signer4>");
+    assert_eq!(three_of_five_function.asm[14], OP_CHECKSIGADD);
+    assert_eq!(three_of_five_function.asm[15], OP_3);
+    assert_eq!(three_of_five_function.asm[16], OP_NUMEQUAL);
+    assert_eq!(three_of_five_function.asm[17], "144");
+    assert_eq!(three_of_five_function.asm[18], OP_CHECKSEQUENCEVERIFY);
+    assert_eq!(three_of_five_function.asm[19], OP_DROP);
 }
 
 #[test]
@@ -441,6 +466,66 @@ contract ThresholdMultisig(
     );
 }
 
+#[test]
+fn test_threshold_multisig_over_array_parameter() {
+    let code = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Board(pubkey[3] members) {
+  function twoOfThree(signature[3] sigs) {
+    require(checkMultisig(members, 2));
+  }
+
+  function all(signature[3] sigs) {
+    require(checkMultisig(members));
+  }
+}"#;
+    let output = compile(code).unwrap();
+    // The signatures are read up front, then checked key by key
+    let asm = |name: &str| {
+        let asm = &output
+            .functions
+            .iter()
+            .find(|f| f.name == name && f.server_variant)
+            .unwrap()
+            .asm;
+        let start = asm.iter().position(|t| t == "<members_0>").unwrap();
+        asm[start..].to_vec()
+    };
+
+    let chain = |threshold: &'static str| {
+        vec![
+            "<members_0>",
+            OP_CHECKSIG,
+            "<members_1>",
+            OP_CHECKSIGADD,
+            "<members_2>",
+            OP_CHECKSIGADD,
+            threshold,
+            OP_NUMEQUALVERIFY,
+        ]
+    };
+    assert_eq!(asm("twoOfThree")[..8], chain(OP_2)[..]);
+    assert_eq!(asm("all")[..8], chain(OP_3)[..]);
+
+    let err = compile(&code.replace("checkMultisig(members, 2)", "checkMultisig(members, 4)"))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "m-of-n multisig threshold(m) exceeds acceptable number of signers(n)"
+    );
+
+    let err = compile(&code.replace("checkMultisig(members, 2)", "checkMultisig(sigs_0, 2)"))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "checkMultisig: 'sigs_0' is not an array parameter"
+    );
+}
+
 #[test]
 fn test_threshold_multisig_cli() {
     // Create a temporary directory for our test files
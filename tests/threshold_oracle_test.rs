@@ -105,7 +105,7 @@ fn test_threshold_oracle_has_control_flow() {
     // Should have if/else for counting valid signatures
     // (or at least some form of control flow from the for loop)
     // For now, just verify the function compiles and has the basic structure
-    assert!(server.asm.len() > 0, "Assembly should not be empty");
+    assert!(!server.asm.is_empty(), "Assembly should not be empty");
 }
 
 // ─── Commit 6: Array ABI Flattening Tests ──────────────────────────────────────
@@ -239,21 +239,22 @@ fn test_threshold_oracle_array_indexing_in_loop() {
         server.asm
     );
 
-    // Similarly, sig (the value var) should become oracleSigs_0, etc.
-    assert!(
-        server.asm.iter().any(|s| s == "<oracleSigs_0>"),
-        "Missing <oracleSigs_0> in assembly. ASM: {:?}",
-        server.asm
-    );
-    assert!(
-        server.asm.iter().any(|s| s == "<oracleSigs_1>"),
-        "Missing <oracleSigs_1> in assembly. ASM: {:?}",
-        server.asm
-    );
-    assert!(
-        server.asm.iter().any(|s| s == "<oracleSigs_2>"),
-        "Missing <oracleSigs_2> in assembly. ASM: {:?}",
-        server.asm
+    // Similarly, sig (the value var) should become oracleSigs_0, etc.,
+    // witness elements read from the stack in turn
+    let witness: Vec<&str> = server
+        .witness_schema
+        .iter()
+        .map(|e| e.name.as_str())
+        .collect();
+    assert_eq!(
+        witness[..5],
+        [
+            "amount",
+            "messageHash",
+            "oracleSigs_0",
+            "oracleSigs_1",
+            "oracleSigs_2"
+        ]
     );
 }
 
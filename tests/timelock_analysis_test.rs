@@ -0,0 +1,223 @@
+use arkade_compiler::compile;
+
+fn timelock_warnings(code: &str) -> Vec<String> {
+    compile(code)
+        .unwrap()
+        .warnings
+        .into_iter()
+        .filter(|w| w.starts_with("warning[timelock]"))
+        .collect()
+}
+
+#[test]
+fn test_mixed_height_and_timestamp_locks() {
+    let code = r#"
+contract Mixed(pubkey owner) {
+  function spend(signature ownerSig) {
+    require(tx.time >= 800000);
+    require(tx.time >= 1700000000);
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let warnings = timelock_warnings(code);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(warnings[0].contains("fn spend"));
+    assert!(
+        warnings[0].contains("at 4:5 uses block height 800000"),
+        "{}",
+        warnings[0]
+    );
+    assert!(
+        warnings[0].contains("at 5:5 uses timestamp 1700000000"),
+        "{}",
+        warnings[0]
+    );
+}
+
+#[test]
+fn test_mixed_locks_in_exclusive_branches_are_not_reported() {
+    let code = r#"
+contract Either(pubkey owner) {
+  function spend(signature ownerSig, int mode) {
+    if (mode == 0) {
+      require(tx.time >= 800000);
+    } else {
+      require(tx.time >= 1700000000);
+    }
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    assert!(timelock_warnings(code).is_empty());
+}
+
+#[test]
+fn test_locktime_cap_below_absolute_lock() {
+    let code = r#"
+contract Capped(pubkey owner) {
+  function spend(signature ownerSig) {
+    require(tx.time >= 800000);
+    require(tx.locktime < 700000, "locktime too high");
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let warnings = timelock_warnings(code);
+    // Reading tx.locktime is introspection, so the exit leaf drops the lock
+    assert_eq!(warnings.len(), 2, "{:?}", warnings);
+    assert!(
+        warnings[0].contains("caps tx.locktime at 699999"),
+        "{}",
+        warnings[0]
+    );
+    assert!(
+        warnings[1].contains("the exit path falls back to N-of-N signatures"),
+        "{}",
+        warnings[1]
+    );
+}
+
+#[test]
+fn test_exit_delay_beyond_relative_lock_range() {
+    let code = r#"
+options {
+  server = server;
+  exit = 70000;
+}
+
+contract Long(pubkey owner) {
+  function spend(signature ownerSig) {
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let warnings = timelock_warnings(code);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(warnings[0].contains("exit delay of 70000 blocks exceeds"));
+}
+
+#[test]
+fn test_exit_delay_not_shorter_than_renew() {
+    let code = r#"
+options {
+  server = server;
+  renew = 144;
+  exit = 288;
+}
+
+contract Late(pubkey owner) {
+  function spend(signature ownerSig) {
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let warnings = timelock_warnings(code);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(warnings[0].contains("renew delay of 144 blocks"));
+}
+
+#[test]
+fn test_introspection_exit_drops_absolute_lock() {
+    let code = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Vesting(pubkey owner, int unlockHeight) {
+  function claim(signature ownerSig) {
+    require(tx.time >= unlockHeight);
+    require(tx.outputs[0].scriptPubKey == tx.input.current.scriptPubKey, "wrong output");
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let warnings = timelock_warnings(code);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(
+        warnings[0].contains("fn claim: the exit path falls back to N-of-N signatures"),
+        "{}",
+        warnings[0]
+    );
+    assert!(warnings[0].contains("at 9:5"), "{}", warnings[0]);
+}
+
+#[test]
+fn test_introspection_exit_drops_absolute_lock_at_the_default_delay() {
+    let code = r#"
+options {
+  server = server;
+}
+
+contract Vesting(pubkey owner, int unlockHeight) {
+  function claim(signature ownerSig) {
+    require(tx.time >= unlockHeight);
+    require(tx.outputs[0].scriptPubKey == tx.input.current.scriptPubKey, "wrong output");
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let warnings = timelock_warnings(code);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(
+        warnings[0].contains("fn claim: the exit path falls back to N-of-N signatures"),
+        "{}",
+        warnings[0]
+    );
+}
+
+#[test]
+fn test_consistent_timelocks_are_not_reported() {
+    let code = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract HTLC(pubkey sender, pubkey receiver, bytes32 hash, int refundTime) {
+  function claim(signature receiverSig, bytes32 preimage) {
+    require(sha256(preimage) == hash);
+    require(checkSig(receiverSig, receiver));
+  }
+
+  function refund(signature senderSig) {
+    require(tx.time >= refundTime);
+    require(checkSig(senderSig, sender));
+  }
+}
+"#;
+    assert!(timelock_warnings(code).is_empty());
+}
+
+#[test]
+fn test_relative_timelocks_in_time_conflict_with_blocks() {
+    let code = r#"
+contract Aged(pubkey owner) {
+  function spend(signature ownerSig) {
+    require(tx.age >= 144);
+    require(tx.age >= 2 days);
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let warnings = timelock_warnings(code);
+    assert_eq!(warnings.len(), 2, "{:?}", warnings);
+    assert!(
+        warnings[0].contains(
+            "relative timelock of 144 blocks at 4:5 and relative timelock of 2 days at 5:5 use different units"
+        ),
+        "{}",
+        warnings[0]
+    );
+    assert!(
+        warnings[1].contains(
+            "relative timelock of 2 days at 5:5 counts time but the exit delay counts blocks"
+        ),
+        "{}",
+        warnings[1]
+    );
+
+    let blocks_only = code.replace("2 days", "288 blocks");
+    assert!(timelock_warnings(&blocks_only).is_empty());
+}
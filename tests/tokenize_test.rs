@@ -0,0 +1,116 @@
+#![cfg(feature = "std")]
+
+use arkade_compiler::examples::EXAMPLES;
+use arkade_compiler::parser::{tokenize, TokenKind, BUILTINS, KEYWORDS};
+
+fn kinds(source: &str) -> Vec<(TokenKind, &str)> {
+    tokenize(source)
+        .into_iter()
+        .map(|token| (token.kind, &source[token.span.start..token.span.end]))
+        .collect()
+}
+
+#[test]
+fn test_tokenize_classifies_tokens() {
+    let source = "contract Lock(pubkey owner, bytes32 hash) { // spend paths\n  require(sha256(preimage) == hash, \"a // b\");\n}";
+    assert_eq!(
+        kinds(source),
+        vec![
+            (TokenKind::Keyword, "contract"),
+            (TokenKind::Identifier, "Lock"),
+            (TokenKind::Punctuation, "("),
+            (TokenKind::Type, "pubkey"),
+            (TokenKind::Identifier, "owner"),
+            (TokenKind::Punctuation, ","),
+            (TokenKind::Type, "bytes32"),
+            (TokenKind::Identifier, "hash"),
+            (TokenKind::Punctuation, ")"),
+            (TokenKind::Punctuation, "{"),
+            (TokenKind::Comment, "// spend paths"),
+            (TokenKind::Keyword, "require"),
+            (TokenKind::Punctuation, "("),
+            (TokenKind::Builtin, "sha256"),
+            (TokenKind::Punctuation, "("),
+            (TokenKind::Identifier, "preimage"),
+            (TokenKind::Punctuation, ")"),
+            (TokenKind::Operator, "=="),
+            (TokenKind::Identifier, "hash"),
+            (TokenKind::Punctuation, ","),
+            (TokenKind::String, "\"a // b\""),
+            (TokenKind::Punctuation, ")"),
+            (TokenKind::Punctuation, ";"),
+            (TokenKind::Punctuation, "}"),
+        ]
+    );
+}
+
+#[test]
+fn test_tokenize_follows_the_grammar() {
+    // Words that start with a keyword or type are identifiers
+    assert_eq!(
+        kinds("letter bytes32x int[] tx.time >= 144"),
+        vec![
+            (TokenKind::Identifier, "letter"),
+            (TokenKind::Identifier, "bytes32x"),
+            (TokenKind::Type, "int"),
+            (TokenKind::Punctuation, "["),
+            (TokenKind::Punctuation, "]"),
+            (TokenKind::Builtin, "tx"),
+            (TokenKind::Punctuation, "."),
+            (TokenKind::Identifier, "time"),
+            (TokenKind::Operator, ">="),
+            (TokenKind::Number, "144"),
+        ]
+    );
+}
+
+#[test]
+fn test_tokenize_never_fails() {
+    assert_eq!(
+        kinds("require(\"open @"),
+        vec![
+            (TokenKind::Keyword, "require"),
+            (TokenKind::Punctuation, "("),
+            (TokenKind::Unknown, "\""),
+            (TokenKind::Identifier, "open"),
+            (TokenKind::Unknown, "@"),
+        ]
+    );
+}
+
+#[test]
+fn test_token_positions() {
+    let tokens = tokenize("options {\n  exit = 144;\n}");
+    let exit = tokens[2];
+    assert_eq!((exit.span.line, exit.span.column), (2, 3));
+    assert_eq!((exit.span.start, exit.span.end), (12, 16));
+}
+
+#[test]
+fn test_examples_have_no_unknown_tokens() {
+    for example in EXAMPLES {
+        let unknown = tokenize(example.source)
+            .into_iter()
+            .find(|token| token.kind == TokenKind::Unknown);
+        assert!(unknown.is_none(), "{}: {:?}", example.name, unknown);
+    }
+}
+
+#[test]
+fn test_reserved_words_appear_in_the_grammar() {
+    let grammar = [
+        include_str!("../src/parser/grammar.pest"),
+        include_str!("../src/parser/assets.pest"),
+        include_str!("../src/parser/introspection.pest"),
+        include_str!("../src/parser/loops.pest"),
+        include_str!("../src/parser/opcodes.pest"),
+    ]
+    .concat();
+    for word in KEYWORDS.iter().chain(BUILTINS) {
+        assert!(
+            grammar.contains(&format!("\"{}", word)),
+            "{} is not in the grammar",
+            word
+        );
+    }
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "std")]
+
+use arkade_compiler::examples;
+use arkade_compiler::models::Trivia;
+use arkade_compiler::parser::parse;
+
+#[test]
+fn test_htlc_comments_attach_to_their_nodes() {
+    let contract = parse(examples::find("htlc").unwrap().source).unwrap();
+    assert_eq!(
+        contract.trivia.leading,
+        vec!["Contract configuration options".to_string()]
+    );
+
+    let together = &contract.functions[0];
+    assert_eq!(together.name, "together");
+    assert_eq!(
+        together.trivia.leading,
+        vec!["Cooperative close path".to_string()]
+    );
+
+    let refund = &contract.functions[1];
+    assert_eq!(refund.trivia.leading[0], "Refund path");
+    assert!(refund.trivia.leading.len() > 2);
+}
+
+#[test]
+fn test_trailing_comments_and_blank_lines() {
+    let source = r#"
+contract Vault(pubkey owner) { // Owner-controlled vault
+  // Detached note
+
+  function spend(signature ownerSig) { // Immediate spend
+    require(checkSig(ownerSig, owner), "see // docs");
+  }
+}
+"#;
+    let contract = parse(source).unwrap();
+    assert_eq!(
+        contract.trivia,
+        Trivia {
+            leading: vec![],
+            trailing: Some("Owner-controlled vault".to_string()),
+        }
+    );
+    assert_eq!(
+        contract.functions[0].trivia,
+        Trivia {
+            leading: vec![],
+            trailing: Some("Immediate spend".to_string()),
+        }
+    );
+}
+
+#[test]
+fn test_comments_survive_printing() {
+    let source = examples::find("htlc").unwrap().source;
+    let contract = parse(source).unwrap();
+    let printed = contract.to_string();
+    assert!(printed.contains("// Cooperative close path\n  function together("));
+    assert_eq!(
+        parse(&printed).unwrap().functions[1].trivia,
+        contract.functions[1].trivia
+    );
+    assert!(!contract.without_trivia().to_string().contains("//"));
+}
@@ -0,0 +1,235 @@
+#![cfg(feature = "std")]
+
+use std::collections::HashMap;
+
+use arkade_compiler::compile;
+use arkade_compiler::interpreter::{
+    self, encode_num, AssetGroup, AssetId, TxContext, TxInput, TxOutput,
+};
+use arkade_compiler::ir::{lift, Instr};
+
+fn asm(tokens: &str) -> Vec<Instr> {
+    tokens.split_whitespace().map(Instr::parse).collect()
+}
+
+fn bindings(pairs: &[(&str, Vec<u8>)]) -> HashMap<String, Vec<u8>> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect()
+}
+
+fn le64(n: u64) -> Vec<u8> {
+    n.to_le_bytes().to_vec()
+}
+
+fn token() -> AssetId {
+    AssetId::new([0xaa; 32], 0)
+}
+
+fn tx_paying(value: u64) -> TxContext {
+    TxContext::builder()
+        .input(TxInput::new(10_000, vec![0x51, 0x20]))
+        .output(TxOutput::new(value, vec![0x51, 0x20, 0x01]))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_builder_rejects_dangling_references() {
+    let err = TxContext::builder()
+        .input(TxInput::new(1_000, vec![]))
+        .asset_group(AssetGroup::new(token()).output(1, 5))
+        .build()
+        .unwrap_err();
+    assert_eq!(err, "asset group 0 assigns output 1 out of range");
+
+    let err = TxContext::builder()
+        .input(TxInput::new(1_000, vec![]))
+        .current_input(1)
+        .build()
+        .unwrap_err();
+    assert_eq!(err, "current input 1 out of range (1 inputs)");
+}
+
+#[test]
+fn test_compiled_output_value_check() {
+    let code = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Pay(pubkey owner, int amount) {
+  function pay(signature ownerSig) {
+    require(tx.outputs[0].value >= amount);
+    require(checkSig(ownerSig, owner));
+  }
+}
+"#;
+    let output = compile(code).unwrap();
+    let leaf = output
+        .functions
+        .iter()
+        .find(|f| f.name == "pay" && f.server_variant)
+        .unwrap();
+    // The value check runs up to and including the first OP_VERIFY
+    let end = leaf.asm.iter().position(|t| t == "OP_VERIFY").unwrap();
+    let check = lift(&leaf.asm[..=end]);
+    // An int argument is pushed as a script number and converted to u64le
+    let values = bindings(&[("amount", encode_num(5_000))]);
+
+    assert!(interpreter::execute(&check, &values, &tx_paying(6_000)).is_ok());
+    let err = interpreter::execute(&check, &values, &tx_paying(4_000)).unwrap_err();
+    assert!(err.contains("OP_VERIFY): verification failed"), "{}", err);
+}
+
+#[test]
+fn test_asset_group_sums() {
+    let tx = TxContext::builder()
+        .input(TxInput::new(1_000, vec![]))
+        .input(TxInput::new(1_000, vec![]))
+        .output(TxOutput::new(1_000, vec![]))
+        .output(TxOutput::new(1_000, vec![]))
+        .asset_group(
+            AssetGroup::new(token())
+                .input(0, 70)
+                .input(1, 30)
+                .output(0, 60)
+                .output(1, 25),
+        )
+        .build()
+        .unwrap();
+    let none = HashMap::new();
+
+    let stack = interpreter::execute(&asm("0 0 OP_INSPECTASSETGROUPSUM"), &none, &tx).unwrap();
+    assert_eq!(stack, vec![le64(100)]);
+
+    let stack = interpreter::execute(&asm("0 2 OP_INSPECTASSETGROUPSUM"), &none, &tx).unwrap();
+    assert_eq!(stack, vec![le64(100), le64(85)]);
+
+    // delta = sumOutputs - sumInputs, as the compiler lowers it
+    let delta = asm("0 1 OP_INSPECTASSETGROUPSUM 0 0 OP_INSPECTASSETGROUPSUM OP_SUB64 OP_VERIFY");
+    let stack = interpreter::execute(&delta, &none, &tx).unwrap();
+    assert_eq!(stack, vec![(-15i64).to_le_bytes().to_vec()]);
+}
+
+#[test]
+fn test_asset_lookups() {
+    let other = AssetId::new([0xbb; 32], 3);
+    let tx = TxContext::builder()
+        .input(TxInput::new(1_000, vec![]))
+        .output(TxOutput::new(1_000, vec![]))
+        .asset_group(AssetGroup::new(token()).input(0, 50).output(0, 50))
+        .asset_group(AssetGroup::new(other).control(token()))
+        .build()
+        .unwrap();
+    let values = bindings(&[
+        ("tokenTxid", token().txid.to_vec()),
+        ("otherTxid", other.txid.to_vec()),
+    ]);
+
+    let lookup = asm("0 <tokenTxid> 0 OP_INSPECTOUTASSETLOOKUP");
+    assert_eq!(
+        interpreter::execute(&lookup, &values, &tx).unwrap(),
+        vec![le64(50)]
+    );
+
+    let missing = asm("0 <otherTxid> 3 OP_INSPECTOUTASSETLOOKUP");
+    assert_eq!(
+        interpreter::execute(&missing, &values, &tx).unwrap(),
+        vec![encode_num(-1)]
+    );
+
+    let find = asm("<otherTxid> 3 OP_FINDASSETGROUPBYASSETID");
+    assert_eq!(
+        interpreter::execute(&find, &values, &tx).unwrap(),
+        vec![encode_num(1)]
+    );
+
+    let ctrl = asm("1 OP_INSPECTASSETGROUPCTRL 0 OP_INSPECTASSETGROUPCTRL");
+    assert_eq!(
+        interpreter::execute(&ctrl, &values, &tx).unwrap(),
+        vec![token().txid.to_vec(), encode_num(0), encode_num(-1)]
+    );
+}
+
+#[test]
+fn test_locktime_checks() {
+    let tx = |locktime: u32| {
+        TxContext::builder()
+            .locktime(locktime)
+            .input(TxInput::new(1_000, vec![]).sequence(0xffff_fffe))
+            .build()
+            .unwrap()
+    };
+    let script = asm("800000 OP_CHECKLOCKTIMEVERIFY OP_DROP OP_INSPECTLOCKTIME");
+    let none = HashMap::new();
+
+    let stack = interpreter::execute(&script, &none, &tx(800_001)).unwrap();
+    assert_eq!(stack, vec![800_001u32.to_le_bytes().to_vec()]);
+
+    let err = interpreter::execute(&script, &none, &tx(799_999)).unwrap_err();
+    assert!(err.contains("locktime 800000 not reached"), "{}", err);
+}
+
+#[test]
+fn test_arithmetic64_overflow_leaves_operands() {
+    let values = bindings(&[("max", le64(i64::MAX as u64)), ("one", le64(1))]);
+    let none = TxContext::builder()
+        .input(TxInput::new(0, vec![]))
+        .build()
+        .unwrap();
+
+    let stack = interpreter::execute(&asm("<max> <one> OP_ADD64"), &values, &none).unwrap();
+    assert_eq!(stack, vec![le64(i64::MAX as u64), le64(1), encode_num(0)]);
+
+    let script = asm("<max> <one> OP_ADD64 OP_VERIFY");
+    assert!(!interpreter::succeeds(&script, &values, &none));
+}
+
+#[test]
+fn test_signature_opcodes_check_the_sighash() {
+    use k256::schnorr::SigningKey;
+
+    let key = SigningKey::from_bytes(&[1; 32]).unwrap();
+    let sighash = [0x07; 32];
+    let tx = TxContext::builder()
+        .sighash(sighash)
+        .input(TxInput::new(10_000, vec![0x51, 0x20]))
+        .build()
+        .unwrap();
+    let pubkey = key.verifying_key().to_bytes().to_vec();
+    let signed = |message: &[u8]| key.sign_raw(message, &[0; 32]).unwrap().to_bytes().to_vec();
+
+    let check = asm("<sig> <key> OP_CHECKSIG");
+    let values = bindings(&[("key", pubkey.clone()), ("sig", signed(&sighash))]);
+    assert_eq!(
+        interpreter::execute(&check, &values, &tx).unwrap(),
+        [vec![1]]
+    );
+    // An empty signature fails the check, a wrong one the script
+    let values = bindings(&[("key", pubkey.clone()), ("sig", vec![])]);
+    assert_eq!(
+        interpreter::execute(&check, &values, &tx).unwrap(),
+        [Vec::<u8>::new()]
+    );
+    let values = bindings(&[("key", pubkey.clone()), ("sig", signed(&[0; 32]))]);
+    let err = interpreter::execute(&check, &values, &tx).unwrap_err();
+    assert_eq!(err, "instruction 2 (OP_CHECKSIG): invalid signature");
+
+    let message = [0x55; 32];
+    let check = asm("<sig> <msg> <key> OP_CHECKSIGFROMSTACKVERIFY 1");
+    let values = bindings(&[
+        ("msg", message.to_vec()),
+        ("sig", signed(&message)),
+        ("key", pubkey.clone()),
+    ]);
+    assert!(interpreter::succeeds(&check, &values, &tx));
+
+    // Each valid signature adds one to the count
+    let count = asm("<sig> 0 <key> OP_CHECKSIGADD <none> OP_SWAP <key> OP_CHECKSIGADD");
+    let values = bindings(&[("sig", signed(&sighash)), ("none", vec![]), ("key", pubkey)]);
+    let stack = interpreter::execute(&count, &values, &tx).unwrap();
+    assert_eq!(stack, [encode_num(1)]);
+}
@@ -0,0 +1,80 @@
+use arkade_compiler::compiler::compile;
+use arkade_compiler::diagnostics::classify;
+
+fn contract(body: &str) -> String {
+    format!(
+        r#"
+contract Vault(pubkey ownerPk, bytes32 hash, int unlockTime) {{
+  function spend(signature ownerSig, bytes preimage, int amount) {{
+{}
+    require(checkSig(ownerSig, ownerPk));
+  }}
+}}
+"#,
+        body
+    )
+}
+
+#[test]
+fn test_operands_of_the_wrong_type_are_errors() {
+    for (body, message) in [
+        (
+            "    require(ownerPk >= 5);",
+            "fn spend: '>=' needs numbers, but its left operand 'ownerPk' has type 'pubkey'",
+        ),
+        (
+            "    require(amount < hash);",
+            "fn spend: '<' needs numbers, but its right operand 'hash' has type 'bytes32'",
+        ),
+        (
+            "    int total = amount + ownerSig;",
+            "fn spend: '+' needs numbers, but its right operand 'ownerSig' has type 'signature'",
+        ),
+        (
+            "    int scaled = amount * preimage;",
+            "fn spend: '*' needs numbers, but its right operand 'preimage' has type 'bytes'",
+        ),
+        (
+            "    require(ownerPk == unlockTime);",
+            "fn spend: '==' compares type 'pubkey' with type 'int'",
+        ),
+        (
+            "    require(checkSig(ownerPk, ownerPk));",
+            "fn spend: checkSig() arg 1 'ownerPk' has type 'pubkey', expected 'signature'",
+        ),
+        (
+            "    require(checkMultisig([ownerPk, hash], 1));",
+            "fn spend: checkMultisig() pubkey 'hash' has type 'bytes32', expected 'pubkey'",
+        ),
+    ] {
+        let err = compile(&contract(body)).unwrap_err().to_string();
+        assert_eq!(err, message);
+        assert_eq!(classify("compile", &err), "E0005");
+    }
+}
+
+#[test]
+fn test_swapped_signature_arguments_are_errors() {
+    let err = compile(&contract("    require(checkSig(ownerPk, ownerSig));"))
+        .unwrap_err()
+        .to_string();
+    assert_eq!(classify("compile", &err), "E0006");
+}
+
+#[test]
+fn test_operands_of_matching_types_compile() {
+    let body = "    int total = amount + unlockTime * 2;
+    require(tx.time >= unlockTime);
+    require(tx.input.current.sequence != total);
+    require(sha256(preimage) == hash);
+    require(tx.outputs[0].scriptPubKey == preimage);";
+    let artifact = compile(&contract(body)).unwrap();
+    assert!(
+        !artifact
+            .warnings
+            .iter()
+            .any(|w| w.starts_with("warning[type]")),
+        "{:?}",
+        artifact.warnings
+    );
+}
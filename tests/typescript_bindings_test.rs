@@ -0,0 +1,71 @@
+#![cfg(feature = "std")]
+
+use arkade_compiler::bindings::typescript;
+use arkade_compiler::compile;
+
+const HTLC: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract HTLC(pubkey sender, pubkey receiver, bytes hash, int refundTime) {
+  function refund(signature senderSig) {
+    require(checkSig(senderSig, sender));
+    require(tx.time >= refundTime);
+  }
+
+  function claim(signature receiverSig, bytes preimage) {
+    require(checkSig(receiverSig, receiver));
+    require(sha256(preimage) == hash);
+  }
+}
+"#;
+
+#[test]
+fn test_constructor_inputs() {
+    let dts = typescript::generate(&compile(HTLC).unwrap());
+
+    assert!(dts.contains(
+        "export interface HTLCConstructorInputs {\n  sender: PubKey;\n  receiver: PubKey;\n  hash: Bytes;\n  refundTime: Int;\n}"
+    ));
+    assert!(dts.contains(
+        "export type HTLCConstructorArgs = [sender: PubKey, receiver: PubKey, hash: Bytes, refundTime: Int];"
+    ));
+    assert!(dts.contains("export type Int = bigint | number;"));
+}
+
+#[test]
+fn test_function_input_tuples() {
+    let dts = typescript::generate(&compile(HTLC).unwrap());
+
+    assert!(dts.contains(
+        "export interface HTLCFunctionInputs {\n  refund: [senderSig: Signature];\n  claim: [receiverSig: Signature, preimage: Bytes];\n}"
+    ));
+    assert!(dts
+        .contains("export interface HTLCExitFunctionInputs {\n  refund: [senderSig: Signature];"));
+    assert!(dts.contains(
+        "export type HTLCFunctionName = keyof HTLCFunctionInputs | keyof HTLCExitFunctionInputs;"
+    ));
+}
+
+#[test]
+fn test_exit_variants_take_server_replacement_signatures() {
+    let code = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Forward(pubkey owner, bytes recipientScript) {
+  function forward(signature ownerSig, pubkey next) {
+    require(checkSig(ownerSig, owner));
+    require(tx.outputs[0].scriptPubKey == new Forward(next, recipientScript));
+  }
+}
+"#;
+    let dts = typescript::generate(&compile(code).unwrap());
+
+    assert!(dts.contains("  forward: [ownerSig: Signature, next: PubKey];"));
+    assert!(dts.contains("  forward: [ownerSig: Signature, next: PubKey, nextSig: Signature];"));
+}
@@ -0,0 +1,84 @@
+use arkade_compiler::catalog::{self, EntryKind};
+use arkade_compiler::compiler::compile;
+use arkade_compiler::diagnostics::classify;
+
+fn requiring(condition: &str) -> String {
+    format!(
+        r#"
+contract Payout(pubkey owner, int amount) {{
+  function spend(signature ownerSig) {{
+    require({});
+    require(checkSig(ownerSig, owner));
+  }}
+}}
+"#,
+        condition
+    )
+}
+
+#[test]
+fn test_misspelt_property_suggests_the_known_one() {
+    let cases = [
+        ("tx.tiem >= amount", "tx.tiem", "tx.time"),
+        (
+            "tx.outputs[0].valeu >= amount",
+            "tx.outputs[0].valeu",
+            "tx.outputs[o].value",
+        ),
+        (
+            "tx.input.current.valu == amount",
+            "tx.input.current.valu",
+            "tx.input.current.value",
+        ),
+        (
+            "tx.inputs[1].assets.lenght == 1",
+            "tx.inputs[1].assets.lenght",
+            "tx.inputs[i].assets.length",
+        ),
+    ];
+    for (condition, path, suggestion) in cases {
+        let err = compile(&requiring(condition)).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            format!(
+                "fn spend: unknown property '{}'; did you mean '{}'?",
+                path, suggestion
+            )
+        );
+        assert_eq!(classify("compile", &err), "E0008");
+    }
+}
+
+#[test]
+fn test_unrelated_property_has_no_suggestion() {
+    let err = compile(&requiring("tx.fee.rate == amount"))
+        .unwrap_err()
+        .to_string();
+    assert_eq!(err, "fn spend: unknown property 'tx.fee.rate'");
+}
+
+#[test]
+fn test_known_properties_compile() {
+    for condition in [
+        "tx.time >= amount",
+        "tx.outputs[0].value >= amount",
+        "tx.input.current.value == amount",
+        "this.activeInputIndex == 0",
+    ] {
+        compile(&requiring(condition)).unwrap_or_else(|e| panic!("{}: {}", condition, e));
+    }
+}
+
+#[test]
+fn test_catalog_resolves_concrete_paths() {
+    let entry = catalog::property("tx.inputs[idx].value").unwrap();
+    assert_eq!(entry.name, "tx.inputs[i].value");
+    assert!(catalog::property("tx.inputs[0]").is_none());
+    assert!(catalog::is_property_path("tx.inputs[0]"));
+    assert!(catalog::is_property_path("tx.assetGroups[k].delta"));
+    assert!(!catalog::is_property_path("tx.inpts"));
+    assert_eq!(
+        catalog::suggest(EntryKind::Option, "exitt").map(|entry| entry.name),
+        Some("exit")
+    );
+}
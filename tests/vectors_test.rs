@@ -0,0 +1,296 @@
+#![cfg(feature = "std")]
+
+use arkade_compiler::compile;
+use arkade_compiler::interpreter::{decode_num, LeafEvaluator, TxContext, TxInput, TxOutput};
+use arkade_compiler::linker::SERVER_KEY;
+use arkade_compiler::vectors::{self, TestVectors, VectorValue};
+use k256::schnorr::{Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+
+const HTLC_CODE: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract HTLC(pubkey sender, pubkey receiver, bytes32 hash, int refundTime) {
+  function claim(signature receiverSig, bytes32 preimage) {
+    require(checkSig(receiverSig, receiver));
+    require(sha256(preimage) == hash);
+  }
+
+  function refund(signature senderSig) {
+    require(checkSig(senderSig, sender));
+    require(tx.time >= refundTime);
+  }
+}
+"#;
+
+fn value(values: &[VectorValue], name: &str) -> Vec<u8> {
+    let v = values.iter().find(|v| v.name == name).unwrap();
+    hex::decode(&v.hex).unwrap()
+}
+
+fn verifies(pubkey: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    // BIP-340 keys are x-only: drop the parity byte
+    let key = VerifyingKey::from_bytes(&pubkey[1..]).unwrap();
+    let Ok(signature) = Signature::try_from(signature) else {
+        return false;
+    };
+    key.verify_raw(message, &signature).is_ok()
+}
+
+fn valid_vector<'a>(
+    vectors: &'a TestVectors,
+    function: &str,
+    server_variant: bool,
+) -> &'a [VectorValue] {
+    &vectors
+        .vectors
+        .iter()
+        .find(|v| v.function == function && v.server_variant == server_variant && v.valid)
+        .unwrap()
+        .witness
+}
+
+#[test]
+fn test_valid_vectors_satisfy_signatures_and_hashlocks() {
+    let vectors = vectors::generate(HTLC_CODE).unwrap();
+    let sighash = hex::decode(&vectors.sighash).unwrap();
+    let receiver = value(&vectors.constructor_inputs, "receiver");
+    let hash = value(&vectors.constructor_inputs, "hash");
+    let server_key = hex::decode(&vectors.server_key).unwrap();
+
+    let claim = valid_vector(&vectors, "claim", true);
+    let names: Vec<_> = claim.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(names, ["receiverSig", "preimage", "serverSig"]);
+
+    assert!(verifies(&receiver, &sighash, &value(claim, "receiverSig")));
+    assert!(verifies(&server_key, &sighash, &value(claim, "serverSig")));
+    assert_eq!(Sha256::digest(value(claim, "preimage")).to_vec(), hash);
+}
+
+#[test]
+fn test_invalid_vectors_break_one_check_each() {
+    let vectors = vectors::generate(HTLC_CODE).unwrap();
+    let claim_exit: Vec<_> = vectors
+        .vectors
+        .iter()
+        .filter(|v| v.function == "claim" && !v.server_variant)
+        .collect();
+
+    let descriptions: Vec<_> = claim_exit.iter().map(|v| v.description.as_str()).collect();
+    assert_eq!(
+        descriptions,
+        [
+            "all signature and hash-lock checks satisfied",
+            "empty signature for `receiverSig`",
+            "wrong preimage for `preimage`",
+            "missing witness element `preimage`",
+        ]
+    );
+    assert!(claim_exit[1..].iter().all(|v| !v.valid));
+
+    let hash = value(&vectors.constructor_inputs, "hash");
+    assert_ne!(
+        Sha256::digest(value(&claim_exit[2].witness, "preimage")).to_vec(),
+        hash
+    );
+    assert!(value(&claim_exit[1].witness, "receiverSig").is_empty());
+    assert_eq!(claim_exit[3].witness.len(), 1);
+}
+
+#[test]
+fn test_vectors_are_deterministic() {
+    let a = serde_json::to_string(&vectors::generate(HTLC_CODE).unwrap()).unwrap();
+    let b = serde_json::to_string(&vectors::generate(HTLC_CODE).unwrap()).unwrap();
+    assert_eq!(a, b);
+}
+
+#[test]
+fn test_introspection_exit_path_signs_with_every_key() {
+    let code = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Covenant(pubkey owner, pubkey buyer, int price) {
+  function buy(signature buyerSig) {
+    require(checkSig(buyerSig, buyer));
+    require(tx.outputs[0].value >= price, "underpaid");
+  }
+}
+"#;
+    let vectors = vectors::generate(code).unwrap();
+    let sighash = hex::decode(&vectors.sighash).unwrap();
+    let exit = valid_vector(&vectors, "buy", false);
+    for key in ["owner", "buyer"] {
+        let pubkey = value(&vectors.constructor_inputs, key);
+        let signature = value(exit, &format!("{}Sig", key));
+        assert!(verifies(&pubkey, &sighash, &signature), "{}", key);
+    }
+}
+
+#[test]
+fn test_vectors_cli_command() {
+    use std::fs;
+    use tempfile::tempdir;
+
+    let temp_dir = tempdir().unwrap();
+    let input_path = temp_dir.path().join("htlc.ark");
+    let output_path = temp_dir.path().join("vectors.json");
+    fs::write(&input_path, HTLC_CODE).unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_arkadec"))
+        .arg("vectors")
+        .arg(input_path.to_str().unwrap())
+        .arg("-o")
+        .arg(output_path.to_str().unwrap())
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "CLI failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let written: TestVectors =
+        serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+    assert_eq!(written.contract_name, "HTLC");
+    assert!(written.vectors.iter().any(|v| v.valid));
+    assert!(written.vectors.iter().any(|v| !v.valid));
+}
+
+const COUNCIL: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Council(pubkey alice, pubkey bob, pubkey carol, pubkey[3] members) {
+  function quorum(signature aliceSig, signature bobSig, signature carolSig) {
+    require(checkMultisig([alice, bob, carol], 2));
+  }
+
+  function board(signature[3] sigs) {
+    require(checkMultisig(members, 2));
+  }
+
+  function unanimous(signature[3] sigs) {
+    require(checkMultisig(members, 3));
+  }
+
+  function count(signature aliceSig, signature bobSig, signature carolSig) {
+    let n = checkSigCount([alice, bob, carol]);
+    require(n >= 2);
+  }
+}
+"#;
+
+const COVENANT: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Covenant(pubkey owner, pubkey buyer, int price) {
+  function buy(signature buyerSig) {
+    require(checkSig(buyerSig, buyer));
+    require(tx.outputs[0].value >= price, "underpaid");
+  }
+}
+"#;
+
+/// Run every vector of `source` in its compiled leaf, on a transaction
+/// meeting the timelocks and introspection the vectors do not model.
+fn run_vectors(source: &str) {
+    let artifact = compile(source).unwrap();
+    let vectors = vectors::generate(source).unwrap();
+    let mut sighash = [0u8; 32];
+    sighash.copy_from_slice(&hex::decode(&vectors.sighash).unwrap());
+    let tx = TxContext::builder()
+        .sighash(sighash)
+        .locktime(1)
+        .input(TxInput::new(10_000, vec![0x51, 0x20]).sequence(144))
+        .output(TxOutput::new(10_000, vec![0x51]))
+        .build()
+        .unwrap();
+
+    for vector in &vectors.vectors {
+        let mut leaf =
+            LeafEvaluator::for_function(&artifact, &vector.function, vector.server_variant)
+                .unwrap()
+                .param(SERVER_KEY, vectors.server_key.clone());
+        for input in &vectors.constructor_inputs {
+            let param = artifact
+                .parameters
+                .iter()
+                .find(|p| p.name == input.name)
+                .unwrap();
+            let value = match param.param_type.as_str() {
+                "int" | "bool" => decode_num(&hex::decode(&input.hex).unwrap())
+                    .unwrap()
+                    .to_string(),
+                _ => input.hex.clone(),
+            };
+            leaf = leaf.param(&input.name, value);
+        }
+        // The witness lists the element consumed first first, on top
+        let stack = vector
+            .witness
+            .iter()
+            .rev()
+            .map(|v| hex::decode(&v.hex).unwrap())
+            .collect();
+        let result = leaf.witness_stack(stack).run(&tx);
+        assert_eq!(
+            result.is_ok(),
+            vector.valid,
+            "fn {} ({}): {}: {:?}",
+            vector.function,
+            vector.server_variant,
+            vector.description,
+            result
+        );
+    }
+}
+
+#[test]
+fn test_vectors_run_as_described_in_the_leaf() {
+    run_vectors(HTLC_CODE);
+    run_vectors(COVENANT);
+    run_vectors(COUNCIL);
+}
+
+#[test]
+fn test_threshold_vectors_leave_the_other_signatures_empty() {
+    let vectors = vectors::generate(COUNCIL).unwrap();
+    let sighash = hex::decode(&vectors.sighash).unwrap();
+    let board = valid_vector(&vectors, "board", true);
+    let members: Vec<Vec<u8>> = (0..3)
+        .map(|i| value(&vectors.constructor_inputs, &format!("members_{}", i)))
+        .collect();
+    assert!(verifies(&members[0], &sighash, &value(board, "sigs_0")));
+    assert!(verifies(&members[1], &sighash, &value(board, "sigs_1")));
+    assert!(value(board, "sigs_2").is_empty());
+
+    let unanimous = valid_vector(&vectors, "unanimous", true);
+    assert!(verifies(&members[2], &sighash, &value(unanimous, "sigs_2")));
+
+    // Only the signing keys' signatures are broken
+    let descriptions: Vec<_> = vectors
+        .vectors
+        .iter()
+        .filter(|v| v.function == "quorum" && !v.server_variant && !v.valid)
+        .map(|v| v.description.as_str())
+        .collect();
+    assert_eq!(
+        descriptions,
+        [
+            "empty signature for `aliceSig`",
+            "empty signature for `bobSig`",
+            "missing witness element `carolSig`",
+        ]
+    );
+}
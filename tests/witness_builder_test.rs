@@ -0,0 +1,81 @@
+#![cfg(feature = "std")]
+
+use arkade_compiler::compile;
+use arkade_compiler::witness::WitnessBuilder;
+
+const HTLC: &str = include_str!("../examples/htlc.ark");
+
+fn sig(byte: &str) -> String {
+    byte.repeat(64)
+}
+
+#[test]
+fn test_items_are_in_stack_order() {
+    let artifact = compile(HTLC).unwrap();
+    let witness = WitnessBuilder::for_function(&artifact, "claim", true)
+        .unwrap()
+        .arg("preimage", "0xc0ffee")
+        .arg("receiverSig", sig("aa"))
+        .arg("serverSig", sig("bb"))
+        .build()
+        .unwrap();
+    // The schema is receiverSig, preimage, serverSig; receiverSig is
+    // consumed first, so it is on top
+    assert_eq!(
+        witness,
+        vec![vec![0xbb; 64], vec![0xc0, 0xff, 0xee], vec![0xaa; 64]]
+    );
+}
+
+#[test]
+fn test_exit_variant_has_no_server_signature() {
+    let artifact = compile(HTLC).unwrap();
+    let witness = WitnessBuilder::for_function(&artifact, "claim", false)
+        .unwrap()
+        .args([("receiverSig", sig("aa")), ("preimage", "00".to_string())])
+        .build()
+        .unwrap();
+    assert_eq!(witness.len(), 2);
+}
+
+#[test]
+fn test_values_are_checked() {
+    let artifact = compile(HTLC).unwrap();
+    let builder = WitnessBuilder::for_function(&artifact, "refund", false).unwrap();
+
+    let err = builder.clone().build().unwrap_err();
+    assert_eq!(
+        err,
+        "fn refund (exit variant): missing witness element 'senderSig'"
+    );
+
+    let err = builder
+        .clone()
+        .arg("senderSig", "abcd")
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        "fn refund (exit variant): witness element 'senderSig': expected 64 bytes for signature, got 2"
+    );
+
+    let err = builder
+        .arg("senderSig", sig("aa"))
+        .arg("serverSig", sig("bb"))
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        "fn refund (exit variant): no witness element 'serverSig'"
+    );
+}
+
+#[test]
+fn test_unknown_function_is_rejected() {
+    let artifact = compile(HTLC).unwrap();
+    let err = WitnessBuilder::for_function(&artifact, "steal", true).unwrap_err();
+    assert_eq!(
+        err,
+        "Contract HTLC has no server variant of function 'steal'"
+    );
+}
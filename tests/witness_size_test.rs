@@ -0,0 +1,122 @@
+use arkade_compiler::compile;
+
+const HTLC_CODE: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract HTLC(pubkey sender, pubkey receiver, bytes32 hash, int refundTime) {
+  function claim(signature receiverSig, bytes32 preimage) {
+    require(checkSig(receiverSig, receiver));
+    require(sha256(preimage) == hash);
+  }
+
+  function refund(signature senderSig) {
+    require(checkSig(senderSig, sender));
+    require(tx.time >= refundTime);
+  }
+}
+"#;
+
+fn size_of(name: &str, server_variant: bool) -> arkade_compiler::models::WitnessSize {
+    compile(HTLC_CODE)
+        .unwrap()
+        .functions
+        .into_iter()
+        .find(|f| f.name == name && f.server_variant == server_variant)
+        .unwrap()
+        .witness_size
+}
+
+#[test]
+fn test_stack_size_follows_witness_schema() {
+    // receiverSig (1 + 64) + preimage (1 + 32) + serverSig (1 + 64)
+    assert_eq!(size_of("claim", true).stack, 163);
+    // receiverSig + preimage
+    assert_eq!(size_of("claim", false).stack, 98);
+}
+
+#[test]
+fn test_control_block_reflects_tree_depth() {
+    // 4 leaves -> 2 merkle hashes: 1 + 33 + 2 * 32
+    assert_eq!(size_of("claim", true).control_block, 98);
+    assert_eq!(size_of("refund", false).control_block, 98);
+}
+
+#[test]
+fn test_total_adds_up() {
+    let size = size_of("refund", true);
+    // One byte for the item count: 2 stack elements + script + control block
+    assert_eq!(
+        size.total,
+        1 + size.stack + size.script + size.control_block
+    );
+    assert!(size.script > 0);
+    assert_eq!(size.vbytes(), size.total.div_ceil(4));
+}
+
+#[test]
+fn test_witness_size_in_artifact() {
+    let output = compile(HTLC_CODE).unwrap();
+    let json = serde_json::to_string(&output).unwrap();
+    assert!(json.contains("\"witnessSize\""));
+    assert!(json.contains("\"controlBlock\""));
+}
+
+#[test]
+fn test_size_estimate_summarizes_the_spend() {
+    let output = compile(HTLC_CODE).unwrap();
+    for function in &output.functions {
+        let estimate = function.size_estimate;
+        let witness = function.witness_size;
+        // The script without its length prefix
+        assert_eq!(estimate.script + 1, witness.script);
+        assert_eq!(estimate.witness, witness.total);
+        // 41 non-witness bytes of the input at 4 weight units each
+        assert_eq!(estimate.weight, 164 + witness.total);
+    }
+
+    let json = serde_json::to_value(&output).unwrap();
+    let estimate = &json["functions"][0]["sizeEstimate"];
+    for field in ["script", "witness", "weight"] {
+        assert!(estimate[field].as_u64().unwrap() > 0, "{}", field);
+    }
+}
+
+#[test]
+fn test_size_cli_command() {
+    use std::fs;
+    use tempfile::tempdir;
+
+    let temp_dir = tempdir().unwrap();
+    let input_path = temp_dir.path().join("htlc.ark");
+    fs::write(&input_path, HTLC_CODE).unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_arkadec"))
+        .arg("size")
+        .arg(input_path.to_str().unwrap())
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "CLI failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let claim = size_of("claim", true);
+    assert!(stdout.starts_with("HTLC\n"), "{}", stdout);
+    assert!(
+        stdout.contains(&format!(
+            "claim (server): {} bytes, {} vB",
+            claim.total,
+            claim.vbytes()
+        )),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("refund (exit)"));
+    // No artifact is written
+    assert!(!temp_dir.path().join("htlc.json").exists());
+}
@@ -0,0 +1,118 @@
+#![cfg(feature = "std")]
+
+use arkade_compiler::compile;
+use arkade_compiler::models::{AbiFunction, WitnessRole};
+use arkade_compiler::witness::WitnessBuilder;
+
+const PAYOUT: &str = r#"
+options {
+  server = server;
+  exit = 144;
+}
+
+contract Payout(pubkey owner, bytes32 hash, bytes32 script) {
+  function pay(signature ownerSig, bytes32 preimage, int output, int amount) {
+    require(checkSig(ownerSig, owner, SIGHASH_ALL));
+    require(sha256(preimage) == hash);
+    require(tx.outputs[output].scriptPubKey == script);
+    require(tx.outputs[output].value >= amount);
+  }
+}
+"#;
+
+fn variant(server_variant: bool) -> AbiFunction {
+    compile(PAYOUT)
+        .unwrap()
+        .functions
+        .into_iter()
+        .find(|f| f.name == "pay" && f.server_variant == server_variant)
+        .unwrap()
+}
+
+#[test]
+fn test_template_lists_the_stack_bottom_first() {
+    let pay = variant(true);
+    let template: Vec<_> = pay
+        .witness_template
+        .iter()
+        .map(|item| (item.index, item.name.as_str(), item.role))
+        .collect();
+    assert_eq!(
+        template,
+        [
+            (0, "serverSig", WitnessRole::ServerSignature),
+            (1, "amount", WitnessRole::Value),
+            (2, "output", WitnessRole::Index),
+            (3, "preimage", WitnessRole::Preimage),
+            (4, "ownerSig", WitnessRole::Signature),
+        ]
+    );
+    let owner_sig = &pay.witness_template[4];
+    assert_eq!(
+        (owner_sig.encoding.as_str(), owner_sig.sighash),
+        ("schnorr-64", Some(0x01))
+    );
+}
+
+#[test]
+fn test_template_matches_the_built_witness() {
+    let artifact = compile(PAYOUT).unwrap();
+    let witness = WitnessBuilder::for_function(&artifact, "pay", true)
+        .unwrap()
+        .arg("ownerSig", "11".repeat(64))
+        .arg("preimage", "22".repeat(32))
+        .arg("output", "3")
+        .arg("amount", "1000")
+        .arg("serverSig", "33".repeat(64))
+        .build()
+        .unwrap();
+    let template = &variant(true).witness_template;
+    assert_eq!(witness.len(), template.len());
+    assert_eq!(witness[0], vec![0x33; 64]);
+    assert_eq!(witness[template[2].index], vec![3]);
+    assert_eq!(witness[4], vec![0x11; 64]);
+}
+
+#[test]
+fn test_exit_template_asks_every_key_to_sign() {
+    let template = variant(false).witness_template;
+    assert!(!template.is_empty());
+    assert!(template
+        .iter()
+        .all(|item| item.role == WitnessRole::Signature));
+}
+
+#[test]
+fn test_server_signature_is_always_a_signature() {
+    for entry in std::fs::read_dir("examples").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|e| e.to_str()) != Some("ark") {
+            continue;
+        }
+        let source = std::fs::read_to_string(&path).unwrap();
+        for function in compile(&source).unwrap().functions {
+            let servers: Vec<_> = function
+                .witness_template
+                .iter()
+                .filter(|item| item.role == WitnessRole::ServerSignature)
+                .collect();
+            if function.server_variant {
+                assert_eq!(servers.len(), 1, "{:?} {}", path, function.name);
+            } else {
+                assert!(servers.is_empty(), "{:?} {}", path, function.name);
+            }
+            for item in servers {
+                assert_eq!(item.name, "serverSig");
+                assert_eq!(item.item_type, "signature", "{:?} {}", path, function.name);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_template_serializes_camel_case() {
+    let json = serde_json::to_value(variant(true)).unwrap();
+    assert_eq!(json["witnessTemplate"][0]["role"], "serverSignature");
+    assert_eq!(json["witnessTemplate"][2]["type"], "int");
+    assert!(json["witnessTemplate"][1].get("sighash").is_none());
+}